@@ -0,0 +1,3 @@
+fn main() {
+    std::process::exit(gencode_core::headless::run_cli(std::env::args().skip(1).collect()));
+}
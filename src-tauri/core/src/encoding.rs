@@ -0,0 +1,60 @@
+use chardetng::EncodingDetector;
+use encoding_rs::{Encoding, UTF_8};
+use std::fs as std_fs;
+use std::path::Path;
+
+// ============================================================================
+// ENCODING
+// ============================================================================
+
+#[derive(Debug, Clone, Copy)]
+pub struct FileEncoding {
+    pub encoding: &'static Encoding,
+    pub bom: bool,
+}
+
+impl Default for FileEncoding {
+    fn default() -> Self {
+        FileEncoding {
+            encoding: UTF_8,
+            bom: false,
+        }
+    }
+}
+
+/// BOM first, then UTF-8 validity, then a statistical guess for legacy
+/// encodings like Latin-1 or Shift-JIS.
+pub fn detect_encoding(bytes: &[u8]) -> FileEncoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return FileEncoding { encoding, bom: true };
+    }
+    match std::str::from_utf8(bytes) {
+        // A character cut off at the end of a partial read is still UTF-8
+        Ok(_) => return FileEncoding::default(),
+        Err(e) if e.error_len().is_none() => return FileEncoding::default(),
+        Err(_) => {}
+    }
+    let mut detector = EncodingDetector::new();
+    detector.feed(bytes, true);
+    FileEncoding {
+        encoding: detector.guess(None, true),
+        bom: false,
+    }
+}
+
+pub fn decode_bytes(bytes: &[u8], file_encoding: FileEncoding) -> String {
+    let bom_len = if file_encoding.bom {
+        Encoding::for_bom(bytes).map(|(_, len)| len).unwrap_or(0)
+    } else {
+        0
+    };
+    let (text, _) = file_encoding.encoding.decode_without_bom_handling(&bytes[bom_len..]);
+    text.into_owned()
+}
+
+/// Reads `path` as text in whatever encoding it's in.
+pub fn read_text(path: &Path) -> Result<(String, FileEncoding), String> {
+    let bytes = std_fs::read(path).map_err(|e| e.to_string())?;
+    let encoding = detect_encoding(&bytes);
+    Ok((decode_bytes(&bytes, encoding), encoding))
+}
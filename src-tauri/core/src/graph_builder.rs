@@ -0,0 +1,515 @@
+use serde_json::json;
+use std::collections::HashMap;
+use std::fs as std_fs;
+use std::path::{Component, Path, PathBuf};
+use tree_sitter::Node;
+
+use crate::encoding::read_text;
+use crate::syntax::{node_text, string_value};
+use crate::walk::{walk_entries, DirectoryOptions};
+use crate::{CodeGraph, CodeGraphEdge, CodeGraphFile, CodeGraphNode, ParserState};
+
+const FUNCTION_KINDS: &[&str] = &[
+    "function_declaration",
+    "generator_function_declaration",
+    "function_definition",
+    "function_item",
+    "method_definition",
+    "method_declaration",
+];
+
+const CLASS_KINDS: &[&str] = &[
+    "class_declaration",
+    "class_definition",
+    "interface_declaration",
+    "struct_item",
+    "enum_item",
+    "trait_item",
+    "class_specifier",
+    "struct_specifier",
+];
+
+const IMPORT_KINDS: &[&str] = &[
+    "import_statement",
+    "import_from_statement",
+    "import_declaration",
+    "use_declaration",
+    "preproc_include",
+];
+
+const VARIABLE_KINDS: &[&str] = &["variable_declaration", "lexical_declaration"];
+const CALL_KINDS: &[&str] = &["call_expression", "call", "method_invocation"];
+/// Tried in order for relative imports written without an extension.
+const IMPORT_EXTENSIONS: &[&str] = &["ts", "tsx", "js", "jsx", "mjs", "cjs", "py", "h", "hpp"];
+const MAX_CALLEE_CHARS: usize = 100;
+
+// ============================================================================
+// GRAPH BUILDER
+// ============================================================================
+
+/// Where a definition sits while its children are visited.
+#[derive(Clone, Default)]
+struct Scope {
+    /// Innermost function or class, which DEFINES its variables.
+    container: Option<String>,
+    /// Innermost function, which CALLS what its body calls.
+    function: Option<String>,
+}
+
+/// Links that can only be resolved once every file has been read.
+enum Pending {
+    Import { file: String, path: PathBuf, source: String },
+    Call { from: String, path: String, callee: String, line: usize },
+    Extends { from: String, path: String, parent: String },
+}
+
+/// Builds the same graph as the frontend's `buildCodeGraphFromFiles`
+/// (FILE, FUNCTION, CLASS, VARIABLE and IMPORT nodes with CONTAINS,
+/// IMPORTS_FROM, CALLS, DEFINES and EXTENDS edges) for every supported
+/// language, resolving calls and base classes across files.
+struct GraphBuilder<'a> {
+    parser: &'a ParserState,
+    root: PathBuf,
+    graph: CodeGraph,
+    next_id: usize,
+    /// File path -> FILE node id.
+    files: HashMap<PathBuf, String>,
+    /// "path:name" -> definition node id, as in the frontend builder.
+    by_file: HashMap<String, String>,
+    /// Name -> definition node ids in any file.
+    by_name: HashMap<String, Vec<String>>,
+    pending: Vec<Pending>,
+}
+
+fn graph_node(id: &str, node_type: &str) -> CodeGraphNode {
+    CodeGraphNode {
+        id: id.to_string(),
+        node_type: node_type.to_string(),
+        name: None,
+        path: None,
+        language: None,
+        lines: None,
+        start_line: None,
+        end_line: None,
+        line: None,
+        source: None,
+        extra: HashMap::new(),
+    }
+}
+
+fn graph_edge(from: &str, to: &str, edge_type: &str, secondary: &str) -> CodeGraphEdge {
+    CodeGraphEdge {
+        from: from.to_string(),
+        to: to.to_string(),
+        edge_type: edge_type.to_string(),
+        unresolved: None,
+        edge_type_secondary: Some(secondary.to_string()),
+        extra: HashMap::new(),
+    }
+}
+
+/// A definition's name, from its `name` field or, in C and C++, the
+/// innermost declarator.
+fn definition_name(node: Node, source: &[u8]) -> Option<String> {
+    if let Some(name) = node.child_by_field_name("name") {
+        return Some(node_text(name, source).to_string());
+    }
+    let mut declarator = node.child_by_field_name("declarator");
+    while let Some(current) = declarator {
+        if matches!(
+            current.kind(),
+            "identifier" | "field_identifier" | "qualified_identifier" | "destructor_name" | "operator_name"
+        ) {
+            return Some(node_text(current, source).to_string());
+        }
+        declarator = current.child_by_field_name("declarator");
+    }
+    None
+}
+
+fn first_identifier(node: Node, source: &[u8]) -> Option<String> {
+    if matches!(node.kind(), "identifier" | "type_identifier") {
+        return Some(node_text(node, source).to_string());
+    }
+    let mut cursor = node.walk();
+    let children: Vec<Node> = node.named_children(&mut cursor).collect();
+    children.into_iter().find_map(|child| first_identifier(child, source))
+}
+
+fn parameter_names(node: Node, source: &[u8]) -> Vec<String> {
+    let Some(parameters) = node.child_by_field_name("parameters") else {
+        return Vec::new();
+    };
+    let mut cursor = parameters.walk();
+    parameters
+        .named_children(&mut cursor)
+        .filter_map(|parameter| {
+            let name = if parameter.kind() == "identifier" {
+                parameter
+            } else {
+                parameter
+                    .child_by_field_name("pattern")
+                    .or_else(|| parameter.child_by_field_name("name"))
+                    .filter(|n| n.kind() == "identifier")?
+            };
+            Some(node_text(name, source).to_string())
+        })
+        .collect()
+}
+
+/// Modules an import statement names, as written.
+fn import_sources(node: Node, source: &[u8]) -> Vec<String> {
+    let field = |name: &str| node.child_by_field_name(name);
+    let sources = match node.kind() {
+        // JavaScript `import x from "y"` and Python `import a.b`
+        "import_statement" => match field("source") {
+            Some(module) => vec![string_value(module, source)],
+            None => field("name").map(|n| node_text(n, source).to_string()).into_iter().collect(),
+        },
+        "import_from_statement" => field("module_name").map(|n| node_text(n, source).to_string()).into_iter().collect(),
+        "use_declaration" => field("argument").map(|n| node_text(n, source).to_string()).into_iter().collect(),
+        "preproc_include" => field("path").map(|n| string_value(n, source)).into_iter().collect(),
+        // Go import blocks hold several specs; Java imports one name
+        _ => {
+            let mut found = Vec::new();
+            let mut stack = vec![node];
+            while let Some(current) = stack.pop() {
+                match current.kind() {
+                    "import_spec" => found.extend(current.child_by_field_name("path").map(|p| string_value(p, source))),
+                    "scoped_identifier" | "identifier" if current.parent() == Some(node) => {
+                        found.push(node_text(current, source).to_string())
+                    }
+                    _ => {
+                        let mut cursor = current.walk();
+                        stack.extend(current.named_children(&mut cursor));
+                    }
+                }
+            }
+            found
+        }
+    };
+    sources.into_iter().filter(|s| !s.is_empty()).collect()
+}
+
+/// What a call calls: an identifier, or a member access kept whole like
+/// the frontend builder does.
+fn callee_name(call: Node, source: &[u8]) -> Option<String> {
+    let text = match call.kind() {
+        "method_invocation" => {
+            let name = node_text(call.child_by_field_name("name")?, source);
+            match call.child_by_field_name("object") {
+                Some(object) => format!("{}.{}", node_text(object, source), name),
+                None => name.to_string(),
+            }
+        }
+        _ => {
+            let function = call.child_by_field_name("function")?;
+            if !matches!(
+                function.kind(),
+                "identifier"
+                    | "member_expression"
+                    | "attribute"
+                    | "field_expression"
+                    | "selector_expression"
+                    | "scoped_identifier"
+            ) {
+                return None;
+            }
+            node_text(function, source).to_string()
+        }
+    };
+    (!text.contains('\n') && text.chars().count() <= MAX_CALLEE_CHARS).then_some(text)
+}
+
+/// The last segment of `a.b`, `a::b` or `a->b`.
+fn short_name(name: &str) -> &str {
+    name.rsplit(['.', ':', '>']).next().unwrap_or(name)
+}
+
+/// Lexically resolves `.` and `..`, which imports are full of.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+impl<'a> GraphBuilder<'a> {
+    fn new(parser: &'a ParserState, root: &Path) -> Self {
+        GraphBuilder {
+            parser,
+            root: root.to_path_buf(),
+            graph: CodeGraph {
+                nodes: Vec::new(),
+                edges: Vec::new(),
+                files: Some(Vec::new()),
+            },
+            next_id: 0,
+            files: HashMap::new(),
+            by_file: HashMap::new(),
+            by_name: HashMap::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    fn id(&mut self) -> String {
+        let id = self.next_id;
+        self.next_id += 1;
+        id.to_string()
+    }
+
+    fn define(&mut self, path: &str, name: &str, id: &str) {
+        self.by_file.insert(format!("{}:{}", path, name), id.to_string());
+        self.by_name.entry(name.to_string()).or_default().push(id.to_string());
+    }
+
+    fn add_file(&mut self, path: &Path) {
+        let path_str = path.to_string_lossy().to_string();
+        let Ok((content, _)) = read_text(path) else {
+            return;
+        };
+        let Some((language, tree)) = self.parser.parse_tree(&path_str, &content) else {
+            return;
+        };
+        let file_number = self.next_id;
+        let file_id = self.id();
+        let lines = content.lines().count();
+        let mut file_node = graph_node(&file_id, "file");
+        file_node.path = Some(path_str.clone());
+        file_node.language = Some(language.clone());
+        file_node.lines = Some(lines);
+        file_node.extra.insert("bytes".to_string(), json!(content.len()));
+        self.graph.nodes.push(file_node);
+        if let Some(files) = &mut self.graph.files {
+            files.push(CodeGraphFile {
+                id: file_number,
+                file_type: "file".to_string(),
+                path: path_str.clone(),
+                language,
+                lines,
+            });
+        }
+        self.files.insert(path.to_path_buf(), file_id.clone());
+
+        let source = content.as_bytes();
+        let mut stack = vec![(tree.root_node(), Scope::default())];
+        while let Some((current, scope)) = stack.pop() {
+            let inner = self.visit(current, source, path, &file_id, scope);
+            let mut cursor = current.walk();
+            let children: Vec<Node> = current.named_children(&mut cursor).collect();
+            // Reversed so definitions are numbered in source order
+            for child in children.into_iter().rev() {
+                stack.push((child, inner.clone()));
+            }
+        }
+    }
+
+    /// Adds what `current` defines and returns the scope of its children.
+    fn visit(&mut self, current: Node, source: &[u8], path: &Path, file_id: &str, scope: Scope) -> Scope {
+        let kind = current.kind();
+        let path_str = path.to_string_lossy().to_string();
+        let line = current.start_position().row;
+
+        if IMPORT_KINDS.contains(&kind) {
+            for module in import_sources(current, source) {
+                let id = self.id();
+                let mut import = graph_node(&id, "import");
+                import.source = Some(module.clone());
+                import.line = Some(line);
+                import.extra.insert("file".to_string(), json!(path_str));
+                self.graph.nodes.push(import);
+                self.graph.edges.push(graph_edge(file_id, &id, "CONTAINS", "structural"));
+                self.pending.push(Pending::Import {
+                    file: file_id.to_string(),
+                    path: path.to_path_buf(),
+                    source: module,
+                });
+            }
+        } else if FUNCTION_KINDS.contains(&kind) || CLASS_KINDS.contains(&kind) {
+            let Some(name) = definition_name(current, source) else {
+                return scope;
+            };
+            let is_function = FUNCTION_KINDS.contains(&kind);
+            let id = self.id();
+            let mut definition = graph_node(&id, if is_function { "function" } else { "class" });
+            definition.name = Some(name.clone());
+            definition.path = Some(path_str.clone());
+            definition.start_line = Some(line);
+            definition.end_line = Some(current.end_position().row);
+            if is_function {
+                definition.extra.insert("params".to_string(), json!(parameter_names(current, source)));
+            }
+            self.graph.nodes.push(definition);
+            self.graph.edges.push(graph_edge(file_id, &id, "CONTAINS", "structural"));
+            self.define(&path_str, &name, &id);
+
+            // Python `superclasses`, Java `superclass`, JavaScript `class_heritage`
+            let heritage = current
+                .child_by_field_name("superclasses")
+                .or_else(|| current.child_by_field_name("superclass"))
+                .or_else(|| {
+                    let mut cursor = current.walk();
+                    let heritage = current.named_children(&mut cursor).find(|c| c.kind() == "class_heritage");
+                    heritage
+                });
+            if let Some(parent) = heritage.filter(|_| !is_function).and_then(|h| first_identifier(h, source)) {
+                self.pending.push(Pending::Extends {
+                    from: id.clone(),
+                    path: path_str,
+                    parent,
+                });
+            }
+            return Scope {
+                function: if is_function { Some(id.clone()) } else { scope.function },
+                container: Some(id),
+            };
+        } else if VARIABLE_KINDS.contains(&kind) {
+            let mut cursor = current.walk();
+            let declarators: Vec<Node> = current
+                .named_children(&mut cursor)
+                .filter(|c| c.kind() == "variable_declarator")
+                .collect();
+            for declarator in declarators {
+                let Some(name) = declarator.child_by_field_name("name").filter(|n| n.kind() == "identifier") else {
+                    continue;
+                };
+                let id = self.id();
+                let mut variable = graph_node(&id, "variable");
+                variable.name = Some(node_text(name, source).to_string());
+                variable.line = Some(line);
+                variable.extra.insert("file".to_string(), json!(path_str));
+                self.graph.nodes.push(variable);
+                if let Some(container) = &scope.container {
+                    self.graph.edges.push(graph_edge(container, &id, "DEFINES", "dataflow"));
+                }
+            }
+        } else if CALL_KINDS.contains(&kind) {
+            if let (Some(function), Some(callee)) = (&scope.function, callee_name(current, source)) {
+                self.pending.push(Pending::Call {
+                    from: function.clone(),
+                    path: path_str,
+                    callee,
+                    line,
+                });
+            }
+        }
+        scope
+    }
+
+    /// A definition named `name`: in `path` first, else the only one with
+    /// that name anywhere.
+    fn resolve(&self, path: &str, name: &str) -> Option<String> {
+        let short = short_name(name);
+        if let Some(id) = self.by_file.get(&format!("{}:{}", path, name)).or_else(|| {
+            self.by_file.get(&format!("{}:{}", path, short))
+        }) {
+            return Some(id.clone());
+        }
+        match self.by_name.get(short).map(Vec::as_slice) {
+            Some([only]) => Some(only.clone()),
+            _ => None,
+        }
+    }
+
+    /// The FILE an import refers to, for relative JavaScript and C imports
+    /// and Python modules.
+    fn resolve_import(&self, path: &Path, module: &str) -> Option<String> {
+        let dir = path.parent()?;
+        let mut bases = Vec::new();
+        if module.starts_with("./") || module.starts_with("../") || module.ends_with(".h") || module.ends_with(".hpp") {
+            bases.push(dir.join(module));
+        } else if path.extension().is_some_and(|e| e == "py" || e == "pyw") {
+            let dots = module.chars().take_while(|c| *c == '.').count();
+            let relative = module[dots..].replace('.', "/");
+            let base = if dots == 0 {
+                self.root.clone()
+            } else {
+                (1..dots).try_fold(dir.to_path_buf(), |d, _| d.parent().map(Path::to_path_buf))?
+            };
+            bases.push(base.join(relative));
+        }
+
+        for base in bases {
+            let base = normalize(&base);
+            let mut candidates = vec![base.clone()];
+            for extension in IMPORT_EXTENSIONS {
+                let mut with_extension = base.clone().into_os_string();
+                with_extension.push(format!(".{}", extension));
+                candidates.push(PathBuf::from(with_extension));
+                candidates.push(base.join(format!("index.{}", extension)));
+            }
+            candidates.push(base.join("__init__.py"));
+            if let Some(id) = candidates.iter().find_map(|c| self.files.get(c)) {
+                return Some(id.clone());
+            }
+        }
+        None
+    }
+
+    fn resolve_pending(&mut self) {
+        for pending in std::mem::take(&mut self.pending) {
+            match pending {
+                Pending::Import { file, path, source } => {
+                    if let Some(target) = self.resolve_import(&path, &source) {
+                        let mut import = graph_edge(&file, &target, "IMPORTS_FROM", "dependency");
+                        import.extra.insert("module".to_string(), json!(source));
+                        self.graph.edges.push(import);
+                    }
+                }
+                Pending::Call { from, path, callee, line } => {
+                    let target = self.resolve(&path, &callee);
+                    let mut call = graph_edge(&from, target.as_deref().unwrap_or(&callee), "CALLS", "control_flow");
+                    call.unresolved = Some(target.is_none());
+                    call.extra.insert("line".to_string(), json!(line));
+                    self.graph.edges.push(call);
+                }
+                Pending::Extends { from, path, parent } => {
+                    let target = self.resolve(&path, &parent);
+                    let mut extends = graph_edge(&from, target.as_deref().unwrap_or(&parent), "EXTENDS", "inheritance");
+                    extends.unresolved = Some(target.is_none());
+                    self.graph.edges.push(extends);
+                }
+            }
+        }
+    }
+}
+
+/// Parses every supported file under `root` into a code graph.
+pub fn build_graph(parser: &ParserState, root: &Path) -> Result<CodeGraph, String> {
+    build_graph_with_progress(parser, root, |_, _| Ok(()))
+}
+
+/// `build_graph`, calling `on_file(done, total)` after each file; an error
+/// from it stops the build.
+pub fn build_graph_with_progress(
+    parser: &ParserState,
+    root: &Path,
+    mut on_file: impl FnMut(usize, usize) -> Result<(), String>,
+) -> Result<CodeGraph, String> {
+    let mut files: Vec<PathBuf> = walk_entries(root, &DirectoryOptions::default(), None)?
+        .into_iter()
+        .filter(|(path, is_dir)| !is_dir && parser.detect_language(&path.to_string_lossy()).is_some())
+        .map(|(path, _)| path)
+        .collect();
+    files.sort();
+
+    let mut builder = GraphBuilder::new(parser, root);
+    for (i, path) in files.iter().enumerate() {
+        builder.add_file(path);
+        on_file(i + 1, files.len())?;
+    }
+    builder.resolve_pending();
+    Ok(builder.graph)
+}
+
+pub fn read_graph_file(path: &Path) -> Result<CodeGraph, String> {
+    let json = std_fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    serde_json::from_str(&json).map_err(|e| format!("Invalid graph file {}: {}", path.display(), e))
+}
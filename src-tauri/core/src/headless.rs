@@ -0,0 +1,213 @@
+use std::collections::HashMap;
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use crate::graph_builder::{build_graph, read_graph_file};
+use crate::{run_cypher, CodeGraph, Neo4jState, ParserState};
+
+const USAGE: &str = "Usage: gencode <command> [options]
+
+Commands:
+  index     Parse a project into a code graph
+              --root <dir>        Project to index (default: .)
+              --output <file>     Write the graph as JSON (printed to stdout without --output/--neo4j)
+              --neo4j <uri>       Store the graph in Neo4j, replacing its contents
+  store     Store a graph written by `index --output` in Neo4j
+              --graph <file> --neo4j <uri>
+  analyze   Print node, edge and structure statistics of a graph
+              --graph <file> | --root <dir>
+  query     Run a Cypher query and print the rows as JSON
+              --neo4j <uri> --cypher <query>
+
+Neo4j options:
+  --user <name>          Default: $NEO4J_USER or neo4j
+  --password <password>  Default: $NEO4J_PASSWORD";
+
+// ============================================================================
+// HEADLESS CLI
+// ============================================================================
+
+/// Entry point of the `gencode` binary; returns the process exit code.
+pub fn run_cli(args: Vec<String>) -> i32 {
+    let Some((command, rest)) = args.split_first() else {
+        eprintln!("{}", USAGE);
+        return 2;
+    };
+    if matches!(command.as_str(), "help" | "-h" | "--help") {
+        println!("{}", USAGE);
+        return 0;
+    }
+
+    let runtime = match tokio::runtime::Runtime::new() {
+        Ok(runtime) => runtime,
+        Err(e) => {
+            eprintln!("gencode: Failed to start runtime: {}", e);
+            return 1;
+        }
+    };
+    let result = parse_options(rest).and_then(|options| runtime.block_on(run_command(command, &options)));
+    match result {
+        Ok(()) => 0,
+        Err(e) => {
+            eprintln!("gencode: {}", e);
+            1
+        }
+    }
+}
+
+async fn run_command(command: &str, options: &HashMap<String, String>) -> Result<(), String> {
+    match command {
+        "index" => {
+            let graph = index_root(options.get("root").map(String::as_str).unwrap_or(".")).await?;
+            if let Some(output) = options.get("output") {
+                write_graph(&graph, Path::new(output))?;
+                eprintln!("Wrote {}", output);
+            }
+            if options.contains_key("neo4j") {
+                store_graph(&graph, options).await?;
+            }
+            if !options.contains_key("output") && !options.contains_key("neo4j") {
+                println!("{}", to_json(&graph)?);
+            }
+            Ok(())
+        }
+        "store" => {
+            let graph = read_graph_file(Path::new(required(options, "graph")?))?;
+            store_graph(&graph, options).await
+        }
+        "analyze" => {
+            let graph = match (options.get("graph"), options.get("root")) {
+                (Some(path), _) => read_graph_file(Path::new(path))?,
+                (None, Some(root)) => index_root(root).await?,
+                (None, None) => return Err("analyze needs --graph <file> or --root <dir>".to_string()),
+            };
+            let context = graph.generate_context();
+            let report = serde_json::json!({
+                "summary": context.summary,
+                "nodesByType": context.nodes_by_type,
+                "edgesByType": context.edges_by_type,
+                "statistics": context.graph_statistics,
+            });
+            println!("{}", to_json(&report)?);
+            Ok(())
+        }
+        "query" => {
+            let cypher = required(options, "cypher")?;
+            let graph = connect(options).await?.get_graph().await?;
+            let result = run_cypher(&graph, cypher).await?;
+            if let Some(error) = result.error {
+                return Err(error);
+            }
+            println!("{}", to_json(&result.data)?);
+            eprintln!("{}", result.summary);
+            Ok(())
+        }
+        _ => Err(format!("Unknown command: {}\n\n{}", command, USAGE)),
+    }
+}
+
+/// Accepts `--key value` and `--key=value`.
+fn parse_options(args: &[String]) -> Result<HashMap<String, String>, String> {
+    let mut options = HashMap::new();
+    let mut iter = args.iter();
+    while let Some(arg) = iter.next() {
+        let Some(key) = arg.strip_prefix("--") else {
+            return Err(format!("Unexpected argument: {}", arg));
+        };
+        let (key, value) = match key.split_once('=') {
+            Some((key, value)) => (key.to_string(), value.to_string()),
+            None => {
+                let value = iter.next().ok_or_else(|| format!("Missing value for --{}", key))?;
+                (key.to_string(), value.clone())
+            }
+        };
+        options.insert(key, value);
+    }
+    Ok(options)
+}
+
+fn required<'a>(options: &'a HashMap<String, String>, key: &str) -> Result<&'a str, String> {
+    options.get(key).map(String::as_str).ok_or_else(|| format!("--{} is required", key))
+}
+
+async fn index_root(root: &str) -> Result<CodeGraph, String> {
+    let root_path: PathBuf = Path::new(root)
+        .canonicalize()
+        .map_err(|e| format!("Failed to resolve {}: {}", root, e))?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+
+    let started = Instant::now();
+    let graph = tokio::task::spawn_blocking(move || build_graph(&ParserState::new(), &root_path))
+        .await
+        .map_err(|e| format!("Graph build failed: {}", e))??;
+    eprintln!(
+        "Indexed {} files: {} nodes, {} edges in {:.1}s",
+        graph.files.as_ref().map_or(0, Vec::len),
+        graph.nodes.len(),
+        graph.edges.len(),
+        started.elapsed().as_secs_f64()
+    );
+    Ok(graph)
+}
+
+async fn connect(options: &HashMap<String, String>) -> Result<Neo4jState, String> {
+    let uri = required(options, "neo4j")?;
+    let user = options
+        .get("user")
+        .cloned()
+        .or_else(|| std::env::var("NEO4J_USER").ok())
+        .unwrap_or_else(|| "neo4j".to_string());
+    let password = options
+        .get("password")
+        .cloned()
+        .or_else(|| std::env::var("NEO4J_PASSWORD").ok())
+        .ok_or("--password or NEO4J_PASSWORD is required")?;
+
+    let neo4j = Neo4jState::new();
+    neo4j.connect(uri, &user, &password).await?;
+    Ok(neo4j)
+}
+
+async fn store_graph(graph: &CodeGraph, options: &HashMap<String, String>) -> Result<(), String> {
+    let neo4j = connect(options).await?.get_graph().await?;
+    let message = graph.store_in_neo4j(&neo4j).await?;
+    eprintln!("{}", message);
+    Ok(())
+}
+
+fn write_graph(graph: &CodeGraph, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string(graph).map_err(|e| format!("Failed to serialize graph: {}", e))?;
+    std_fs::write(path, json).map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, String> {
+    serde_json::to_string_pretty(value).map_err(|e| format!("Failed to serialize output: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(list: &[&str]) -> Vec<String> {
+        list.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    #[test]
+    fn options_accept_both_value_forms() {
+        let options = parse_options(&args(&["--root", "src", "--output=graph.json"])).unwrap();
+        assert_eq!(options.get("root").map(String::as_str), Some("src"));
+        assert_eq!(options.get("output").map(String::as_str), Some("graph.json"));
+        // Only the first `=` separates the key
+        let options = parse_options(&args(&["--cypher=MATCH (n {a: '='}) RETURN n"])).unwrap();
+        assert_eq!(options["cypher"], "MATCH (n {a: '='}) RETURN n");
+    }
+
+    #[test]
+    fn options_reject_stray_and_incomplete_arguments() {
+        assert_eq!(parse_options(&args(&["index"])).unwrap_err(), "Unexpected argument: index");
+        assert_eq!(parse_options(&args(&["--root"])).unwrap_err(), "Missing value for --root");
+    }
+}
@@ -0,0 +1,842 @@
+use neo4rs::{Graph, Row, Txn, query};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs as std_fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use tokio::sync::RwLock;
+use tree_sitter::{Language, Node, Parser};
+
+pub mod encoding;
+pub mod graph_builder;
+pub mod headless;
+pub mod locks;
+pub mod syntax;
+pub mod walk;
+use locks::*;
+
+const MAX_QUERY_ROWS: usize = 100;
+
+// ============================================================================
+// NEO4J STATE
+// ============================================================================
+
+/// The connection is shared by every graph command. Readers only clone the
+/// handle out of the lock, so queries never hold it while they run and a
+/// reconnect doesn't wait for them.
+#[derive(Default)]
+pub struct Neo4jState {
+    graph: RwLock<Option<Arc<Graph>>>,
+}
+
+impl Neo4jState {
+    pub fn new() -> Self {
+        Neo4jState {
+            graph: RwLock::new(None),
+        }
+    }
+
+    pub async fn connect(&self, uri: &str, user: &str, password: &str) -> Result<(), String> {
+        let graph = Graph::new(uri, user, password)
+            .await
+            .map_err(|e| format!("Failed to connect to Neo4j: {}", e))?;
+
+        *self.graph.write().await = Some(Arc::new(graph));
+        Ok(())
+    }
+
+    pub async fn disconnect(&self) {
+        self.graph.write().await.take();
+    }
+
+    pub async fn get_graph(&self) -> Result<Arc<Graph>, String> {
+        self.graph
+            .read()
+            .await
+            .clone()
+            .ok_or_else(|| "Not connected to Neo4j".to_string())
+    }
+
+    pub async fn is_connected(&self) -> bool {
+        self.graph.read().await.is_some()
+    }
+}
+
+// ============================================================================
+// CODE GRAPH STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodeGraphNode {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub node_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub lines: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end_line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodeGraphEdge {
+    pub from: String,
+    pub to: String,
+    #[serde(rename = "type")]
+    pub edge_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub unresolved: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub edge_type_secondary: Option<String>,
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodeGraphFile {
+    pub id: usize,
+    #[serde(rename = "type")]
+    pub file_type: String,
+    pub path: String,
+    pub language: String,
+    pub lines: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CodeGraph {
+    pub nodes: Vec<CodeGraphNode>,
+    pub edges: Vec<CodeGraphEdge>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub files: Option<Vec<CodeGraphFile>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphContext {
+    pub summary: String,
+    pub structure_overview: String,
+    pub cypher_schema: String,
+    pub sample_queries: Vec<String>,
+    pub nodes_by_type: HashMap<String, usize>,
+    pub edges_by_type: HashMap<String, usize>,
+    pub graph_statistics: GraphStatistics,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GraphStatistics {
+    pub total_nodes: usize,
+    pub total_edges: usize,
+    pub max_depth: usize,
+    pub connected_components: usize,
+    pub avg_connections_per_node: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CypherQueryResult {
+    pub success: bool,
+    pub data: Vec<serde_json::Value>,
+    pub error: Option<String>,
+    pub summary: String,
+}
+
+// ============================================================================
+// NEO4J OPERATIONS - FIXED
+// ============================================================================
+
+impl CodeGraph {
+    pub async fn store_in_neo4j(&self, graph: &Graph) -> Result<String, String> {
+        self.store_in_neo4j_with_progress(graph, |_, _| Ok(())).await
+    }
+
+    /// `store_in_neo4j`, calling `on_item(done, total)` after each node and
+    /// edge; an error from it stops the import. The import runs in one
+    /// transaction, so a failed or stopped import leaves the previous graph.
+    pub async fn store_in_neo4j_with_progress(
+        &self,
+        graph: &Graph,
+        on_item: impl FnMut(usize, usize) -> Result<(), String>,
+    ) -> Result<String, String> {
+        let mut txn = graph
+            .start_txn()
+            .await
+            .map_err(|e| format!("Failed to start transaction: {}", e))?;
+        if let Err(e) = self.write_graph(&mut txn, on_item).await {
+            let _ = txn.rollback().await;
+            return Err(e);
+        }
+        txn.commit()
+            .await
+            .map_err(|e| format!("Failed to commit graph: {}", e))?;
+
+        Ok(format!(
+            "Successfully stored {} nodes and {} edges in Neo4j",
+            self.nodes.len(),
+            self.edges.len()
+        ))
+    }
+
+    async fn write_graph(
+        &self,
+        txn: &mut Txn,
+        mut on_item: impl FnMut(usize, usize) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let total = self.nodes.len() + self.edges.len();
+        let mut done = 0;
+
+        // Clear existing data
+        txn.run(query("MATCH (n) DETACH DELETE n"))
+            .await
+            .map_err(|e| format!("Failed to clear database: {}", e))?;
+
+        // Create nodes
+        for node in &self.nodes {
+            let node_type = node.node_type.to_uppercase();
+            let id = node.id.clone();
+            let name = node.name.clone().unwrap_or_else(|| "unknown".to_string());
+            let path = node.path.clone().unwrap_or_default();
+
+            // Build dynamic query based on available fields
+            let mut properties = vec!["id: $id", "name: $name", "path: $path"];
+            
+            if node.language.is_some() {
+                properties.push("language: $language");
+            }
+            if node.lines.is_some() {
+                properties.push("lines: $lines");
+            }
+            if node.start_line.is_some() {
+                properties.push("startLine: $startLine");
+            }
+            if node.end_line.is_some() {
+                properties.push("endLine: $endLine");
+            }
+            if node.line.is_some() {
+                properties.push("line: $line");
+            }
+            if node.source.is_some() {
+                properties.push("source: $source");
+            }
+
+            let query_str = format!(
+                "CREATE (n:{} {{{}}})",
+                node_type,
+                properties.join(", ")
+            );
+
+            let mut cypher = query(&query_str)
+                .param("id", id)
+                .param("name", name)
+                .param("path", path);
+
+            if let Some(lang) = &node.language {
+                cypher = cypher.param("language", lang.clone());
+            }
+            if let Some(lines) = node.lines {
+                cypher = cypher.param("lines", lines as i64);
+            }
+            if let Some(start_line) = node.start_line {
+                cypher = cypher.param("startLine", start_line as i64);
+            }
+            if let Some(end_line) = node.end_line {
+                cypher = cypher.param("endLine", end_line as i64);
+            }
+            if let Some(line) = node.line {
+                cypher = cypher.param("line", line as i64);
+            }
+            if let Some(source) = &node.source {
+                cypher = cypher.param("source", source.clone());
+            }
+
+            txn.run(cypher)
+                .await
+                .map_err(|e| format!("Failed to create node {}: {}", node.id, e))?;
+            done += 1;
+            on_item(done, total)?;
+        }
+
+        // Create relationships
+        for edge in &self.edges {
+            let cypher_query = format!(
+                "MATCH (a {{id: $from}}), (b {{id: $to}}) CREATE (a)-[:{}]->(b)",
+                edge.edge_type
+            );
+            
+            let cypher = query(&cypher_query)
+                .param("from", edge.from.clone())
+                .param("to", edge.to.clone());
+
+            txn.run(cypher)
+                .await
+                .map_err(|e| format!("Failed to create relationship {} -> {}: {}", edge.from, edge.to, e))?;
+            done += 1;
+            on_item(done, total)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn generate_context(&self) -> GraphContext {
+        let mut nodes_by_type: HashMap<String, usize> = HashMap::new();
+        let mut edges_by_type: HashMap<String, usize> = HashMap::new();
+
+        for node in &self.nodes {
+            *nodes_by_type.entry(node.node_type.clone()).or_insert(0) += 1;
+        }
+
+        for edge in &self.edges {
+            *edges_by_type.entry(edge.edge_type.clone()).or_insert(0) += 1;
+        }
+
+        let total_nodes = self.nodes.len();
+        let total_edges = self.edges.len();
+        let avg_connections = if total_nodes > 0 {
+            (total_edges as f64 * 2.0) / total_nodes as f64
+        } else {
+            0.0
+        };
+
+        let summary = self.generate_summary(&nodes_by_type, &edges_by_type);
+        let cypher_schema = self.generate_cypher_schema(&nodes_by_type, &edges_by_type);
+        let sample_queries = self.generate_sample_queries();
+        
+        // Generate a structural overview (list of files and key components)
+        let structure_overview = self.generate_structure_overview();
+
+        GraphContext {
+            summary,
+            structure_overview, // Added field
+            cypher_schema,
+            sample_queries,
+            nodes_by_type,
+            edges_by_type,
+            graph_statistics: GraphStatistics {
+                total_nodes,
+                total_edges,
+                max_depth: 10,
+                connected_components: 1,
+                avg_connections_per_node: avg_connections,
+            },
+        }
+    }
+
+    fn generate_structure_overview(&self) -> String {
+        let mut overview = String::new();
+        
+        // Collect files
+        let mut file_paths: Vec<&String> = self.nodes.iter()
+            .filter(|n| n.node_type == "FILE" || n.node_type == "file")
+            .filter_map(|n| n.path.as_ref())
+            .collect();
+        file_paths.sort();
+
+        overview.push_str("### File Structure\n");
+        if file_paths.is_empty() {
+             overview.push_str("- No files detected in graph.\n");
+        } else {
+            for path in file_paths.iter().take(50) { // Limit to 50 files to avoid context overflow
+                overview.push_str(&format!("- {}\n", path));
+            }
+            if file_paths.len() > 50 {
+                overview.push_str(&format!("... and {} more files.\n", file_paths.len() - 50));
+            }
+        }
+
+        overview
+    }
+
+    fn generate_summary(&self, nodes_by_type: &HashMap<String, usize>, edges_by_type: &HashMap<String, usize>) -> String {
+        let mut summary = String::from("# Code Graph Summary\n\n");
+        
+        summary.push_str("## Nodes\n");
+        for (node_type, count) in nodes_by_type {
+            summary.push_str(&format!("- {}: {} nodes\n", node_type, count));
+        }
+        
+        summary.push_str("\n## Relationships\n");
+        for (edge_type, count) in edges_by_type {
+            summary.push_str(&format!("- {}: {} edges\n", edge_type, count));
+        }
+        
+        summary.push_str("\n## Total Statistics\n");
+        summary.push_str(&format!("- Total Nodes: {}\n", self.nodes.len()));
+        summary.push_str(&format!("- Total Edges: {}\n", self.edges.len()));
+        
+        summary
+    }
+
+    fn generate_cypher_schema(&self, nodes_by_type: &HashMap<String, usize>, edges_by_type: &HashMap<String, usize>) -> String {
+        let mut schema = String::from("# Neo4j Graph Schema\n\n");
+        
+        schema.push_str("## Node Labels\n");
+        for node_type in nodes_by_type.keys() {
+            schema.push_str(&format!("- :{} (id: String, name: String, path: String, language: String, lines: Integer)\n", node_type.to_uppercase()));
+        }
+        
+        schema.push_str("\n## Relationship Types\n");
+        for edge_type in edges_by_type.keys() {
+            schema.push_str(&format!("- :{}\n", edge_type));
+        }
+        
+        schema
+    }
+
+    fn generate_sample_queries(&self) -> Vec<String> {
+        vec![
+            "// WHOLE CODEBASE ANALYSIS (Architecture & Dependencies)\nMATCH (n) OPTIONAL MATCH (n)-[r]->(m) RETURN n, r, m LIMIT 1000".to_string(),
+            "// Find all files\nMATCH (f:FILE) RETURN f.path, f.language LIMIT 50".to_string(),
+            "// Find all functions in a specific file\nMATCH (file:FILE)-[:CONTAINS]->(func:FUNCTION)\nWHERE file.path CONTAINS 'example'\nRETURN func.name, func.lines".to_string(),
+            "// Find function call chains\nMATCH path = (f1:FUNCTION)-[:CALLS*1..3]->(f2:FUNCTION)\nRETURN path LIMIT 10".to_string(),
+            "// Find all imports for a file\nMATCH (file:FILE)-[:IMPORTS_FROM]->(imported:FILE)\nRETURN file.path, imported.path LIMIT 20".to_string(),
+            "// Find classes that extend other classes\nMATCH (child:CLASS)-[:EXTENDS]->(parent:CLASS)\nRETURN child.name, parent.name".to_string(),
+            "// Find most connected nodes (Hubs)\nMATCH (n)-[r]-()\nRETURN n.name, n.id, labels(n)[0] as label, count(r) AS connections\nORDER BY connections DESC\nLIMIT 10".to_string(),
+            "// Find circular dependencies\nMATCH path = (a:FILE)-[:IMPORTS_FROM*2..5]->(a)\nRETURN path LIMIT 5".to_string(),
+            "// Find API operations without a handler in code\nMATCH (op:API_OPERATION)\nWHERE NOT (op)-[:HANDLED_BY]->()\nRETURN op.name, op.path".to_string(),
+        ]
+    }
+
+    pub fn to_graph_query_context(&self) -> String {
+        let context = self.generate_context();
+        
+        format!(
+            r#"# Codebase Analysis Report
+
+## Graph Overview
+{}
+
+## Structural Overview
+{}
+
+## Available Node Types
+{}
+
+## Available Relationship Types
+{}
+
+## Analysis Patterns
+You can use these internal patterns to query the codebase knowledge:
+
+{}
+
+## Statistics
+- Total Nodes: {}
+- Total Edges: {}
+- Average Connections per Node: {:.2}
+
+## Query Guidelines
+1. Use MATCH clauses to find patterns
+2. Use WHERE to filter results
+3. Use RETURN to specify what to return
+4. Use LIMIT to control result count
+5. Available node types: {}
+6. Available relationship types: {}
+
+## Important Notes
+- This knowledge graph allows for real-time codebase analysis
+- Use this data to answer user questions with authority
+- Do NOT expose raw query details to the user
+- Use CONTAINS for partial string matching in paths
+"#,
+            context.summary,
+            context.structure_overview, // Inject Structural Overview
+            context.nodes_by_type
+                .iter()
+                .map(|(k, v)| format!("- :{} ({} nodes)", k.to_uppercase(), v))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            context.edges_by_type
+                .iter()
+                .map(|(k, v)| format!("- :{} ({} relationships)", k, v))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            context.sample_queries
+                .iter()
+                .map(|q| format!("```cypher\n{}\n```", q))
+                .collect::<Vec<_>>()
+                .join("\n\n"),
+            context.graph_statistics.total_nodes,
+            context.graph_statistics.total_edges,
+            context.graph_statistics.avg_connections_per_node,
+            context.nodes_by_type.keys().map(|k| k.to_uppercase()).collect::<Vec<_>>().join(", "),
+            context.edges_by_type.keys().map(|k| k.as_str()).collect::<Vec<_>>().join(", ")
+        )
+    }
+}
+
+// ============================================================================
+// PARSER STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParsedFile {
+    pub path: String,
+    pub language: String,
+    pub success: bool,
+    pub error: Option<String>,
+    pub ast: Option<ASTNode>,
+    pub metadata: ParseMetadata,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ASTNode {
+    pub node_type: String,
+    pub text: Option<String>,
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub start_byte: usize,
+    pub end_byte: usize,
+    pub is_named: bool,
+    pub children: Vec<ASTNode>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParseMetadata {
+    pub lines: usize,
+    pub bytes: usize,
+    pub node_count: usize,
+    pub tree_depth: usize,
+    pub has_syntax_errors: bool,
+}
+
+impl ParseMetadata {
+    fn empty() -> Self {
+        ParseMetadata {
+            lines: 0,
+            bytes: 0,
+            node_count: 0,
+            tree_depth: 0,
+            has_syntax_errors: false,
+        }
+    }
+}
+
+// ============================================================================
+// PARSER STATE
+// ============================================================================
+
+pub struct ParserState {
+    parsers: Mutex<HashMap<String, Parser>>,
+    extension_map: HashMap<String, String>,
+}
+
+impl Default for ParserState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ParserState {
+    pub fn new() -> Self {
+        
+        let mut state = ParserState {
+            parsers: Mutex::new(HashMap::new()),
+            extension_map: HashMap::new(),
+        };
+        
+        state.setup_extensions();
+        state.initialize_parsers();
+        
+        state
+    }
+
+    fn setup_extensions(&mut self) {
+        let mappings = vec![
+            ("js", "javascript"),
+            ("jsx", "javascript"),
+            ("mjs", "javascript"),
+            ("cjs", "javascript"),
+            ("ts", "typescript"),
+            ("tsx", "tsx"),
+            ("py", "python"),
+            ("pyw", "python"),
+            ("rs", "rust"),
+            ("java", "java"),
+            ("go", "go"),
+            ("c", "c"),
+            ("h", "c"),
+            ("cpp", "cpp"),
+            ("cc", "cpp"),
+            ("cxx", "cpp"),
+            ("hpp", "cpp"),
+            ("hxx", "cpp"),
+        ];
+
+        for (ext, lang) in mappings {
+            self.extension_map.insert(ext.to_string(), lang.to_string());
+        }
+    }
+
+    fn initialize_parsers(&mut self) {
+        let mut parsers = self.parsers.lock_or_recover();
+        
+        Self::add_parser(&mut parsers, "javascript", tree_sitter_javascript::language());
+        Self::add_parser(&mut parsers, "typescript", tree_sitter_typescript::language_typescript());
+        Self::add_parser(&mut parsers, "tsx", tree_sitter_typescript::language_tsx());
+        Self::add_parser(&mut parsers, "python", tree_sitter_python::language());
+        Self::add_parser(&mut parsers, "rust", tree_sitter_rust::language());
+        Self::add_parser(&mut parsers, "java", tree_sitter_java::language());
+        Self::add_parser(&mut parsers, "go", tree_sitter_go::language());
+        Self::add_parser(&mut parsers, "c", tree_sitter_c::language());
+        Self::add_parser(&mut parsers, "cpp", tree_sitter_cpp::language());
+        
+        eprintln!("  Loaded {} parsers", parsers.len());
+    }
+
+    fn add_parser(parsers: &mut HashMap<String, Parser>, name: &str, language: Language) {
+        let mut parser = Parser::new();
+        
+        match parser.set_language(language) {
+            Ok(_) => {
+                parsers.insert(name.to_string(), parser);
+                eprintln!("  ✓ {}", name);
+            }
+            Err(e) => {
+                eprintln!("  ✗ {} - Failed to set language: {:?}", name, e);
+            }
+        }
+    }
+
+    /// Supported languages with the file extensions mapped to each.
+    pub fn supported_languages(&self) -> HashMap<String, Vec<String>> {
+        let mut result: HashMap<String, Vec<String>> = HashMap::new();
+        for (ext, lang) in &self.extension_map {
+            result.entry(lang.clone()).or_default().push(ext.clone());
+        }
+        result
+    }
+
+    pub fn detect_language(&self, path: &str) -> Option<String> {
+        Path::new(path)
+            .extension()
+            .and_then(|e| e.to_str())
+            .and_then(|ext| self.extension_map.get(ext))
+            .cloned()
+    }
+
+    /// Parses `content` into a syntax tree, with the language detected
+    /// from `path`.
+    pub fn parse_tree(&self, path: &str, content: &str) -> Option<(String, tree_sitter::Tree)> {
+        let language = self.detect_language(path)?;
+        let tree = self.parse_as(&language, content)?;
+        Some((language, tree))
+    }
+
+    /// Parses `content` with the parser for `language`.
+    pub fn parse_as(&self, language: &str, content: &str) -> Option<tree_sitter::Tree> {
+        let mut parsers = self.parsers.lock_or_recover();
+        parsers.get_mut(language)?.parse(content, None)
+    }
+
+    /// Parses the file at `path`, reporting a read failure as a failed parse.
+    pub fn read_and_parse(&self, path: &str) -> ParsedFile {
+        match std_fs::read_to_string(path) {
+            Ok(content) => self.parse_file(path, &content),
+            Err(e) => ParsedFile {
+                path: path.to_string(),
+                language: "unknown".to_string(),
+                success: false,
+                error: Some(format!("Failed to read file: {}", e)),
+                ast: None,
+                metadata: ParseMetadata::empty(),
+            },
+        }
+    }
+
+    pub fn parse_file(&self, path: &str, content: &str) -> ParsedFile {
+        let language = match self.detect_language(path) {
+            Some(lang) => lang,
+            None => {
+                return ParsedFile {
+                    path: path.to_string(),
+                    language: "unknown".to_string(),
+                    success: false,
+                    error: Some("Unsupported file extension".to_string()),
+                    ast: None,
+                    metadata: ParseMetadata::empty(),
+                };
+            }
+        };
+
+        let mut parsers = self.parsers.lock_or_recover();
+        let parser = match parsers.get_mut(&language) {
+            Some(p) => p,
+            None => {
+                return ParsedFile {
+                    path: path.to_string(),
+                    language: language.clone(),
+                    success: false,
+                    error: Some(format!("Parser not available for {}", language)),
+                    ast: None,
+                    metadata: ParseMetadata::empty(),
+                };
+            }
+        };
+
+        match parser.parse(content, None) {
+            Some(tree) => {
+                let root = tree.root_node();
+                let ast = Self::node_to_ast(&root, content, 0, 10);
+                
+                ParsedFile {
+                    path: path.to_string(),
+                    language,
+                    success: true,
+                    error: None,
+                    ast: Some(ast),
+                    metadata: ParseMetadata {
+                        lines: content.lines().count(),
+                        bytes: content.len(),
+                        node_count: Self::count_nodes(&root),
+                        tree_depth: Self::calculate_depth(&root, 0),
+                        has_syntax_errors: root.has_error(),
+                    },
+                }
+            }
+            None => {
+                ParsedFile {
+                    path: path.to_string(),
+                    language,
+                    success: false,
+                    error: Some("Parse failed".to_string()),
+                    ast: None,
+                    metadata: ParseMetadata::empty(),
+                }
+            }
+        }
+    }
+
+    fn node_to_ast(node: &Node, source: &str, depth: usize, max_depth: usize) -> ASTNode {
+        let start = node.start_position();
+        let end = node.end_position();
+        
+        let text = if node.child_count() == 0 && (node.end_byte() - node.start_byte()) < 100 {
+            node.utf8_text(source.as_bytes()).ok().map(|s| s.to_string())
+        } else {
+            None
+        };
+
+        let mut children = Vec::new();
+        if depth < max_depth {
+            for i in 0..node.child_count() {
+                if let Some(child) = node.child(i) {
+                    children.push(Self::node_to_ast(&child, source, depth + 1, max_depth));
+                }
+            }
+        }
+
+        ASTNode {
+            node_type: node.kind().to_string(),
+            text,
+            start_line: start.row,
+            start_col: start.column,
+            end_line: end.row,
+            end_col: end.column,
+            start_byte: node.start_byte(),
+            end_byte: node.end_byte(),
+            is_named: node.is_named(),
+            children,
+        }
+    }
+
+    fn count_nodes(node: &Node) -> usize {
+        let mut count = 1;
+        for i in 0..node.child_count() {
+            if let Some(child) = node.child(i) {
+                count += Self::count_nodes(&child);
+            }
+        }
+        count
+    }
+
+    fn calculate_depth(node: &Node, current: usize) -> usize {
+        let mut max = current;
+        for i in 0..node.child_count() {
+            if let Some(child) = node.child(i) {
+                max = max.max(Self::calculate_depth(&child, current + 1));
+            }
+        }
+        max
+    }
+}
+
+// ============================================================================
+// CYPHER QUERIES
+// ============================================================================
+
+pub async fn run_cypher(graph: &Graph, cypher: &str) -> Result<CypherQueryResult, String> {
+    let mut result = graph
+        .execute(query(cypher))
+        .await
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+
+    let mut data: Vec<serde_json::Value> = Vec::new();
+    while let Ok(Some(row)) = result.next().await {
+        data.push(row_data(&row));
+        if data.len() >= MAX_QUERY_ROWS {
+            break;
+        }
+    }
+    Ok(query_result(data))
+}
+
+/// `run_cypher` inside a transaction that is always rolled back, for queries
+/// from the agent and other outside callers. Whatever a query that got past
+/// the read-only check writes is discarded.
+pub async fn run_read_only_cypher(graph: &Graph, cypher: &str) -> Result<CypherQueryResult, String> {
+    let mut txn = graph
+        .start_txn()
+        .await
+        .map_err(|e| format!("Failed to start transaction: {}", e))?;
+    let mut result = match txn.execute(query(cypher)).await {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = txn.rollback().await;
+            return Err(format!("Query execution failed: {}", e));
+        }
+    };
+
+    let mut data: Vec<serde_json::Value> = Vec::new();
+    while let Ok(Some(row)) = result.next(txn.handle()).await {
+        data.push(row_data(&row));
+        if data.len() >= MAX_QUERY_ROWS {
+            break;
+        }
+    }
+    txn.rollback()
+        .await
+        .map_err(|e| format!("Failed to roll back transaction: {}", e))?;
+    Ok(query_result(data))
+}
+
+fn row_data(row: &Row) -> serde_json::Value {
+    let mut row_data = serde_json::Map::new();
+    if let Ok(row_map) = row.to::<HashMap<String, serde_json::Value>>() {
+        for (key, value) in row_map {
+            row_data.insert(key, value);
+        }
+    }
+    serde_json::Value::Object(row_data)
+}
+
+fn query_result(data: Vec<serde_json::Value>) -> CypherQueryResult {
+    let summary = format!("Query returned {} rows", data.len());
+    CypherQueryResult {
+        success: true,
+        data,
+        error: None,
+        summary,
+    }
+}
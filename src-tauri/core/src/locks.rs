@@ -0,0 +1,15 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// Locking that survives a poisoned mutex. State behind these locks is only
+/// ever replaced wholesale or edited step by step, so what a panicking
+/// holder left behind is still usable, and one failed command must not make
+/// every later command that touches the same state panic too.
+pub trait LockExt<T> {
+    fn lock_or_recover(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> LockExt<T> for Mutex<T> {
+    fn lock_or_recover(&self) -> MutexGuard<'_, T> {
+        self.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
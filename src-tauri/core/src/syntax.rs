@@ -0,0 +1,27 @@
+use tree_sitter::Node;
+
+/// Interpolated expressions inside template strings and f-strings.
+const SUBSTITUTION_NODES: &[&str] = &["template_substitution", "interpolation"];
+
+pub fn node_text<'a>(node: Node, source: &'a [u8]) -> &'a str {
+    node.utf8_text(source).unwrap_or_default()
+}
+
+/// The text of a string literal without quotes and prefixes. Template
+/// and f-string substitutions become `${}` so they normalize like route
+/// parameters.
+pub fn string_value(node: Node, source: &[u8]) -> String {
+    let mut text = String::new();
+    let mut last = node.start_byte();
+    let mut cursor = node.walk();
+    for part in node.named_children(&mut cursor) {
+        if SUBSTITUTION_NODES.contains(&part.kind()) {
+            text.push_str(&String::from_utf8_lossy(&source[last..part.start_byte()]));
+            text.push_str("${}");
+            last = part.end_byte();
+        }
+    }
+    text.push_str(&String::from_utf8_lossy(&source[last..node.end_byte()]));
+    let text = text.trim_start_matches(['r', 'b', 'u', 'f', 'R', 'B', 'U', 'F']);
+    text.trim_matches(['"', '\'', '`', '#']).to_string()
+}
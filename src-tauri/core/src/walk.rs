@@ -0,0 +1,107 @@
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::WalkBuilder;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::locks::LockExt;
+
+pub const IGNORED_DIRS: &[&str] = &["node_modules", "target", ".git", "dist", "build", ".idea", ".vscode", "out"];
+
+// ============================================================================
+// DIRECTORY WALKING
+// ============================================================================
+
+/// Filters for directory reads. Without `exclude`, the usual build and
+/// dependency directories are left out.
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct DirectoryOptions {
+    pub show_hidden: bool,
+    /// List files matched by .gitignore/.git/info/exclude, flagged `ignored`.
+    pub show_ignored: bool,
+    /// Globs a file must match to be listed; directories are always listed.
+    pub include: Vec<String>,
+    pub exclude: Option<Vec<String>>,
+    /// Descend into symlinked directories. Each real directory is still
+    /// visited once, so link cycles can't loop.
+    pub follow_links: bool,
+    /// With `follow_links`, also follow links that leave the walked root.
+    pub follow_external_links: bool,
+}
+
+fn build_override(root: &Path, globs: &[String], negate: bool) -> Result<Override, String> {
+    let mut builder = OverrideBuilder::new(root);
+    for glob in globs {
+        let glob = if negate { format!("!{}", glob) } else { glob.clone() };
+        builder.add(&glob).map_err(|e| format!("Invalid glob '{}': {}", glob, e))?;
+    }
+    builder.build().map_err(|e| format!("Invalid glob: {}", e))
+}
+
+/// Entries below `dir` (not `dir` itself) as (path, is_dir), honoring ignore
+/// files in `dir` and its parents.
+pub fn walk_entries(
+    dir: &Path,
+    options: &DirectoryOptions,
+    max_depth: Option<usize>,
+) -> Result<Vec<(PathBuf, bool)>, String> {
+    let mut excludes: Vec<String> = match &options.exclude {
+        Some(globs) => globs.clone(),
+        None => IGNORED_DIRS.iter().map(|d| d.to_string()).collect(),
+    };
+    excludes.push(".git".to_string());
+
+    let respect_ignore = !options.show_ignored;
+    let mut builder = WalkBuilder::new(dir);
+    builder
+        .hidden(!options.show_hidden)
+        .git_ignore(respect_ignore)
+        .git_exclude(respect_ignore)
+        .git_global(respect_ignore)
+        .ignore(respect_ignore)
+        .parents(true)
+        .require_git(false)
+        .follow_links(options.follow_links)
+        .max_depth(max_depth)
+        .overrides(build_override(dir, &excludes, true)?);
+
+    if options.follow_links {
+        let root = dir.canonicalize().map_err(|e| format!("Failed to resolve {}: {}", dir.display(), e))?;
+        let visited = Arc::new(Mutex::new(HashSet::from([root.clone()])));
+        let follow_external = options.follow_external_links;
+        builder.filter_entry(move |entry| {
+            if !entry.file_type().is_some_and(|t| t.is_dir()) {
+                return true;
+            }
+            let Ok(real) = entry.path().canonicalize() else {
+                return false;
+            };
+            if entry.path_is_symlink() && !follow_external && !real.starts_with(&root) {
+                return false;
+            }
+            visited.lock_or_recover().insert(real)
+        });
+    }
+
+    let include = build_override(dir, &options.include, false)?;
+
+    let mut entries = Vec::new();
+    for result in builder.build() {
+        let Ok(entry) = result else {
+            continue; // Unreadable entries are skipped like before
+        };
+        if entry.depth() == 0 {
+            continue;
+        }
+        // Unfollowed links to directories are still listed as directories
+        let is_dir = entry.file_type().is_some_and(|t| t.is_dir())
+            || (entry.path_is_symlink() && entry.path().is_dir());
+        if !is_dir && !options.include.is_empty() && !include.matched(entry.path(), false).is_whitelist() {
+            continue;
+        }
+        entries.push((entry.into_path(), is_dir));
+    }
+    Ok(entries)
+}
@@ -0,0 +1,367 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+
+use crate::conversations::ConversationState;
+use crate::git::get_git_status;
+use crate::llm::{LlmRequestRecord, LlmState, LoggedPrompt};
+use crate::mcp::{call_client_tool, client_tool_definitions, is_read_only, McpState};
+use crate::plugins::{call_plugin_tool, plugin_tool_definitions, PluginState};
+use crate::sandbox::WorkspaceState;
+use crate::{
+    read_dir_recursive, run_read_only_cypher, search_file_list, ChatMessage, DirEntryInfo, Neo4jState,
+};
+
+const MAX_TOOL_OUTPUT: usize = 8000;
+const MAX_SEARCH_RESULTS: usize = 50;
+
+// ============================================================================
+// AGENT STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolCall {
+    pub function: ToolCallFunction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ToolCallFunction {
+    pub name: String,
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct AgentMessage {
+    role: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    tool_calls: Option<Vec<ToolCall>>,
+}
+
+#[derive(Debug, Serialize)]
+struct AgentChatRequest<'a> {
+    model: &'a str,
+    messages: &'a [AgentMessage],
+    tools: &'a serde_json::Value,
+    stream: bool,
+}
+
+#[derive(Debug, Deserialize)]
+struct AgentChatResponse {
+    message: AgentMessage,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct AgentStepEvent {
+    pub step: usize,
+    pub kind: String, // "tool_call", "tool_result", "final"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arguments: Option<serde_json::Value>,
+    pub content: String,
+}
+
+// ============================================================================
+// TOOLS
+// ============================================================================
+
+pub(crate) fn tool_definitions() -> serde_json::Value {
+    serde_json::json!([
+        {
+            "type": "function",
+            "function": {
+                "name": "execute_cypher",
+                "description": "Run a read-only Cypher query against the code graph stored in Neo4j and return the rows.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "query": { "type": "string", "description": "The Cypher query to execute" }
+                    },
+                    "required": ["query"]
+                }
+            }
+        },
+        {
+            "type": "function",
+            "function": {
+                "name": "read_file",
+                "description": "Read the contents of a file in the project.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "path": { "type": "string", "description": "File path, absolute or relative to the project root" }
+                    },
+                    "required": ["path"]
+                }
+            }
+        },
+        {
+            "type": "function",
+            "function": {
+                "name": "search_code",
+                "description": "Case-insensitive text search across all project files. Returns matching lines with context.",
+                "parameters": {
+                    "type": "object",
+                    "properties": {
+                        "pattern": { "type": "string", "description": "Text to search for" }
+                    },
+                    "required": ["pattern"]
+                }
+            }
+        },
+        {
+            "type": "function",
+            "function": {
+                "name": "git_status",
+                "description": "Get the current branch plus staged and unstaged changes of the project repository.",
+                "parameters": { "type": "object", "properties": {} }
+            }
+        }
+    ])
+}
+
+fn arg_str<'a>(args: &'a serde_json::Value, key: &str) -> Result<&'a str, String> {
+    args.get(key)
+        .and_then(|v| v.as_str())
+        .ok_or_else(|| format!("Missing string argument: {}", key))
+}
+
+fn resolve_path(root: Option<&str>, path: &str) -> String {
+    match root {
+        Some(root) if Path::new(path).is_relative() => Path::new(root).join(path).to_string_lossy().to_string(),
+        _ => path.to_string(),
+    }
+}
+
+fn collect_file_paths(entries: &[DirEntryInfo], out: &mut Vec<String>) {
+    for entry in entries {
+        if entry.is_dir {
+            if let Some(children) = &entry.children {
+                collect_file_paths(children, out);
+            }
+        } else {
+            out.push(entry.path.clone());
+        }
+    }
+}
+
+pub(crate) fn truncate_output(mut output: String, max: usize) -> String {
+    if output.len() > max {
+        let mut end = max;
+        while !output.is_char_boundary(end) {
+            end -= 1;
+        }
+        output.truncate(end);
+        output.push_str("\n... (output truncated)");
+    }
+    output
+}
+
+/// Runs one tool call. File access is confined to the trusted workspaces;
+/// tools of connected MCP servers and enabled plugins are forwarded to them.
+pub(crate) async fn execute_tool(call: &ToolCall, root: Option<&str>, app: &AppHandle) -> Result<String, String> {
+    // Some models send arguments as a JSON-encoded string instead of an object
+    let args = match &call.function.arguments {
+        serde_json::Value::String(raw) => serde_json::from_str(raw).unwrap_or(serde_json::Value::Null),
+        other => other.clone(),
+    };
+
+    let workspace = app.state::<WorkspaceState>();
+    match call.function.name.as_str() {
+        "execute_cypher" => {
+            // The query may come from instructions injected through file contents
+            let query = arg_str(&args, "query")?;
+            if !is_read_only(query) {
+                return Err("execute_cypher only runs read-only Cypher".to_string());
+            }
+            let graph = app.state::<Neo4jState>().get_graph().await?;
+            let result = run_read_only_cypher(&graph, query).await?;
+            serde_json::to_string(&result.data).map_err(|e| e.to_string())
+        }
+        "read_file" => {
+            let path = resolve_path(root, arg_str(&args, "path")?);
+            let checked = workspace.check(&path)?;
+            tokio::fs::read_to_string(&checked)
+                .await
+                .map_err(|e| format!("Failed to read {}: {}", path, e))
+        }
+        "search_code" => {
+            let root = root.ok_or("search_code requires a project root")?;
+            let pattern = arg_str(&args, "pattern")?;
+            workspace.check(root)?;
+
+            let (root, pattern) = (root.to_string(), pattern.to_string());
+            let matches = tokio::task::spawn_blocking(move || {
+                let entries = read_dir_recursive(Path::new(&root))?;
+                let mut files = Vec::new();
+                collect_file_paths(&entries, &mut files);
+                let mut matches = search_file_list(&pattern, &files, false, false);
+                matches.truncate(MAX_SEARCH_RESULTS);
+                Ok::<_, String>(matches)
+            })
+            .await
+            .map_err(|e| format!("Search task failed: {}", e))??;
+            serde_json::to_string(&matches).map_err(|e| e.to_string())
+        }
+        "git_status" => {
+            let root = root.ok_or("git_status requires a project root")?.to_string();
+            let status_app = app.clone();
+            let status = tokio::task::spawn_blocking(move || {
+                get_git_status(root, status_app.state::<WorkspaceState>())
+            })
+            .await
+            .map_err(|e| format!("Git status task failed: {}", e))??;
+            serde_json::to_string(&status).map_err(|e| e.to_string())
+        }
+        other => {
+            if let Some(result) = call_client_tool(&app.state::<McpState>(), other, args.clone()).await {
+                return result;
+            }
+            match call_plugin_tool(app, other, args).await {
+                Some(result) => result,
+                None => Err(format!("Unknown tool: {}", other)),
+            }
+        }
+    }
+}
+
+// ============================================================================
+// AGENT TAURI COMMANDS
+// ============================================================================
+
+/// Runs the model in a loop, executing any tool calls it makes and feeding the
+/// results back until it answers without calling a tool. Every step is emitted
+/// as an `agent-step` event; the final answer is also returned. With a
+/// `conversation_id`, `messages` is only the new turn and the stored history
+/// is sent before it; the turn and the final answer are saved.
+#[tauri::command]
+pub async fn run_agent(
+    window: Window,
+    model: String,
+    messages: Vec<ChatMessage>,
+    conversation_id: Option<i64>,
+    root: Option<String>,
+    max_steps: Option<usize>,
+    llm: State<'_, LlmState>,
+) -> Result<String, String> {
+    let app = window.app_handle().clone();
+    let conversations = app.state::<ConversationState>();
+    let mut tools = tool_definitions();
+    if let Some(list) = tools.as_array_mut() {
+        list.extend(client_tool_definitions(&app.state::<McpState>()));
+        list.extend(plugin_tool_definitions(&app.state::<PluginState>()));
+    }
+    let max_steps = max_steps.unwrap_or(8);
+
+    let mut conversation: Vec<AgentMessage> = conversations
+        .continue_with(conversation_id, messages)?
+        .into_iter()
+        .map(|m| AgentMessage {
+            role: m.role,
+            content: m.content,
+            tool_calls: None,
+        })
+        .collect();
+
+    for step in 0..max_steps {
+        let request = AgentChatRequest {
+            model: &model,
+            messages: &conversation,
+            tools: &tools,
+            stream: false,
+        };
+
+        let started = Instant::now();
+        let result: Result<AgentChatResponse, String> = async {
+            llm.post("/api/chat", &request)
+                .await?
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))
+        }
+        .await;
+
+        llm.log_request(LlmRequestRecord {
+            provider: "ollama",
+            model: &model,
+            endpoint: "/api/chat",
+            prompt: LoggedPrompt::Messages(
+                conversation.iter().map(|m| (m.role.clone(), m.content.clone())).collect(),
+            ),
+            prompt_tokens: result.as_ref().ok().and_then(|r| r.prompt_eval_count),
+            completion_tokens: result.as_ref().ok().and_then(|r| r.eval_count),
+            duration_ms: started.elapsed().as_millis() as u64,
+            error: result.as_ref().err().cloned(),
+        });
+
+        let chat_response = result?;
+
+        let message = chat_response.message;
+        let calls = message.tool_calls.clone().unwrap_or_default();
+
+        if calls.is_empty() {
+            let _ = window.emit(
+                "agent-step",
+                AgentStepEvent {
+                    step,
+                    kind: "final".to_string(),
+                    tool: None,
+                    arguments: None,
+                    content: message.content.clone(),
+                },
+            );
+            conversations.record_reply(conversation_id, &message.content)?;
+            return Ok(message.content);
+        }
+
+        conversation.push(message);
+
+        for call in calls {
+            let _ = window.emit(
+                "agent-step",
+                AgentStepEvent {
+                    step,
+                    kind: "tool_call".to_string(),
+                    tool: Some(call.function.name.clone()),
+                    arguments: Some(call.function.arguments.clone()),
+                    content: String::new(),
+                },
+            );
+
+            let output = match execute_tool(&call, root.as_deref(), &app).await {
+                Ok(output) => truncate_output(output, MAX_TOOL_OUTPUT),
+                Err(e) => format!("Error: {}", e),
+            };
+
+            let _ = window.emit(
+                "agent-step",
+                AgentStepEvent {
+                    step,
+                    kind: "tool_result".to_string(),
+                    tool: Some(call.function.name.clone()),
+                    arguments: None,
+                    content: output.clone(),
+                },
+            );
+
+            conversation.push(AgentMessage {
+                role: "tool".to_string(),
+                content: output,
+                tool_calls: None,
+            });
+        }
+    }
+
+    // Answer the stored turn so the next one doesn't replay it
+    let notice = format!("Agent stopped after {} steps without a final answer", max_steps);
+    conversations.record_reply(conversation_id, &notice)?;
+    Err(notice)
+}
@@ -0,0 +1,256 @@
+use git2::Repository;
+use neo4rs::query;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+use tree_sitter::Node;
+
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::files::read_text;
+use crate::finder::relative_path;
+use crate::git::{blame_lines, BlameLine};
+use crate::sandbox::WorkspaceState;
+use crate::symbols::{node_text, LineIndex, SourcePosition};
+use crate::{Neo4jState, ParserState};
+
+const DEFAULT_TAGS: &[&str] = &["TODO", "FIXME", "HACK", "NOTE"];
+const MAX_TEXT_CHARS: usize = 500;
+
+// ============================================================================
+// ANNOTATION STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct AnnotationOptions {
+    /// Tags to look for, case-sensitive; defaults to TODO, FIXME, HACK and
+    /// NOTE.
+    pub tags: Option<Vec<String>>,
+    /// Look up who last changed each annotated line with git blame.
+    pub include_blame: bool,
+    /// Store the annotations as ANNOTATION nodes in the Neo4j code graph.
+    pub store_in_graph: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CodeAnnotation {
+    pub tag: String,
+    pub text: String,
+    /// Name in parentheses after the tag, as in `TODO(alice): ...`.
+    pub assignee: Option<String>,
+    pub path: String,
+    pub relative_path: String,
+    pub language: String,
+    pub position: SourcePosition,
+    pub blame: Option<BlameLine>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AnnotationScanResult {
+    pub annotations: Vec<CodeAnnotation>,
+    pub counts: BTreeMap<String, usize>,
+    pub files_scanned: usize,
+    /// ANNOTATION nodes written to the graph.
+    pub stored: usize,
+}
+
+// ============================================================================
+// ANNOTATION SCANNING
+// ============================================================================
+
+fn tag_pattern(tags: &[String]) -> Result<Regex, String> {
+    let alternatives: Vec<String> = tags.iter().map(|t| regex::escape(t)).collect();
+    Regex::new(&format!(r"\b({})\b(?:\(([^)]*)\))?:?\s*(.*)", alternatives.join("|")))
+        .map_err(|e| format!("Invalid annotation tags: {}", e))
+}
+
+/// A comment line without its comment markers.
+fn comment_body(line: &str) -> &str {
+    let line = line.trim();
+    let line = line.strip_suffix("*/").unwrap_or(line).trim_end();
+    ["///", "//!", "//", "/**", "/*", "#", "--", "*"]
+        .iter()
+        .find_map(|marker| line.strip_prefix(marker))
+        .unwrap_or(line)
+        .trim()
+}
+
+fn collect_comments<'t>(node: Node<'t>, out: &mut Vec<Node<'t>>) {
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        if node.kind().contains("comment") {
+            out.push(node);
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+}
+
+/// Annotations in the comments of one file. Only comment nodes are read,
+/// so tags inside strings or identifiers don't count.
+fn scan_file(parser: &ParserState, root: &Path, path: &Path, pattern: &Regex) -> Option<Vec<CodeAnnotation>> {
+    let path_str = path.to_string_lossy().to_string();
+    let (content, _) = read_text(path).ok()?;
+    let (language, tree) = parser.parse_tree(&path_str, &content)?;
+    let lines = LineIndex::new(&content);
+    let relative = relative_path(root, path).unwrap_or_default();
+
+    let mut comments = Vec::new();
+    collect_comments(tree.root_node(), &mut comments);
+    comments.sort_by_key(|c| c.start_byte());
+
+    let mut annotations = Vec::new();
+    for comment in comments {
+        let start = lines.position(comment.start_position());
+        for (offset, line) in node_text(comment, content.as_bytes()).lines().enumerate() {
+            let body = comment_body(line);
+            let Some(caps) = pattern.captures(body) else {
+                continue;
+            };
+            // Tags must open the comment text, not appear in prose
+            let tag = caps.get(1).map(|m| m.start()).unwrap_or_default();
+            if !body[..tag].trim().is_empty() {
+                continue;
+            }
+            let column = line.find(&caps[1]).map(|i| line[..i].chars().count()).unwrap_or(0);
+            let column = if offset == 0 { start.column + column } else { column };
+            annotations.push(CodeAnnotation {
+                tag: caps[1].to_string(),
+                text: caps[3].trim().chars().take(MAX_TEXT_CHARS).collect(),
+                assignee: caps.get(2).map(|m| m.as_str().trim().to_string()).filter(|a| !a.is_empty()),
+                path: path_str.clone(),
+                relative_path: relative.clone(),
+                language: language.clone(),
+                position: SourcePosition {
+                    line: start.line + offset,
+                    column,
+                },
+                blame: None,
+            });
+        }
+    }
+    Some(annotations)
+}
+
+/// Fills in blame for the annotated lines, one blame per file. Files
+/// outside a repository or not committed yet are left without.
+fn attach_blame(root: &Path, annotations: &mut [CodeAnnotation]) {
+    let Ok(repo) = Repository::discover(root) else {
+        return;
+    };
+    let mut blames: HashMap<String, Option<Vec<BlameLine>>> = HashMap::new();
+    for annotation in annotations {
+        let blame = blames
+            .entry(annotation.path.clone())
+            .or_insert_with(|| blame_lines(&repo, &annotation.path).ok());
+        annotation.blame = blame
+            .as_ref()
+            .and_then(|lines| lines.iter().find(|l| l.line == annotation.position.line + 1))
+            .cloned();
+    }
+}
+
+fn scan_root(parser: &ParserState, root: &Path, options: &AnnotationOptions) -> Result<AnnotationScanResult, String> {
+    let tags: Vec<String> = match &options.tags {
+        Some(tags) if !tags.is_empty() => tags.clone(),
+        _ => DEFAULT_TAGS.iter().map(|t| t.to_string()).collect(),
+    };
+    let pattern = tag_pattern(&tags)?;
+
+    let mut files: Vec<PathBuf> = walk_entries(root, &DirectoryOptions::default(), None)?
+        .into_iter()
+        .filter(|(path, is_dir)| !is_dir && parser.detect_language(&path.to_string_lossy()).is_some())
+        .map(|(path, _)| path)
+        .collect();
+    files.sort();
+
+    let mut result = AnnotationScanResult {
+        annotations: Vec::new(),
+        counts: BTreeMap::new(),
+        files_scanned: 0,
+        stored: 0,
+    };
+    for path in files {
+        if let Some(annotations) = scan_file(parser, root, &path, &pattern) {
+            result.files_scanned += 1;
+            result.annotations.extend(annotations);
+        }
+    }
+    if options.include_blame {
+        attach_blame(root, &mut result.annotations);
+    }
+    for annotation in &result.annotations {
+        *result.counts.entry(annotation.tag.clone()).or_insert(0) += 1;
+    }
+    Ok(result)
+}
+
+/// Replaces the ANNOTATION nodes of `root` in the graph, each linked from
+/// its FILE node with CONTAINS.
+async fn store_annotations(neo4j: &Neo4jState, root: &str, annotations: &[CodeAnnotation]) -> Result<usize, String> {
+    let graph = neo4j.get_graph().await?;
+    graph
+        .run(query("MATCH (a:ANNOTATION {root: $root}) DETACH DELETE a").param("root", root.to_string()))
+        .await
+        .map_err(|e| format!("Failed to clear annotations: {}", e))?;
+
+    for annotation in annotations {
+        let id = format!("annotation:{}:{}", annotation.relative_path, annotation.position.line + 1);
+        let author = annotation.blame.as_ref().map(|b| b.author.clone()).unwrap_or_default();
+        graph
+            .run(
+                query(
+                    "CREATE (a:ANNOTATION {id: $id, name: $tag, text: $text, path: $path, line: $line, root: $root, author: $author, assignee: $assignee}) \
+                     WITH a MATCH (f:FILE) WHERE f.path IN [$path, $relative] CREATE (f)-[:CONTAINS]->(a)",
+                )
+                .param("id", id)
+                .param("tag", annotation.tag.clone())
+                .param("text", annotation.text.clone())
+                .param("path", annotation.path.clone())
+                .param("relative", annotation.relative_path.clone())
+                .param("line", (annotation.position.line + 1) as i64)
+                .param("root", root.to_string())
+                .param("author", author)
+                .param("assignee", annotation.assignee.clone().unwrap_or_default()),
+            )
+            .await
+            .map_err(|e| format!("Failed to store annotation {}: {}", annotation.relative_path, e))?;
+    }
+    Ok(annotations.len())
+}
+
+// ============================================================================
+// ANNOTATION TAURI COMMANDS
+// ============================================================================
+
+/// TODO/FIXME/HACK/NOTE comments under `root`, found in the parse trees so
+/// strings and code that merely contain a tag aren't reported.
+#[tauri::command]
+pub async fn scan_annotations(
+    app: AppHandle,
+    root: String,
+    options: Option<AnnotationOptions>,
+    workspace: State<'_, WorkspaceState>,
+    neo4j: State<'_, Neo4jState>,
+) -> Result<AnnotationScanResult, String> {
+    let root_path = workspace.check(&root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+    let options = options.unwrap_or_default();
+    let store_in_graph = options.store_in_graph;
+    let mut result = tokio::task::spawn_blocking(move || {
+        let parser = app.state::<ParserState>();
+        scan_root(&parser, &root_path, &options)
+    })
+    .await
+    .map_err(|e| format!("Annotation scan failed: {}", e))??;
+
+    if store_in_graph {
+        result.stored = store_annotations(&neo4j, &root, &result.annotations).await?;
+    }
+    Ok(result)
+}
@@ -0,0 +1,1014 @@
+use neo4rs::query;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+use tree_sitter::Node;
+
+use crate::event_bus::emit_graph_updated;
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::files::read_text;
+use crate::finder::relative_path;
+use crate::literals::{string_value, ROUTE_PARAMETER, STRING_NODES};
+use crate::sandbox::WorkspaceState;
+use crate::symbols::{node_text, FUNCTION_NODES};
+use crate::{Neo4jState, ParserState};
+
+const HTTP_METHODS: &[&str] = &["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+/// Method names that, next to a route literal, say which method it serves.
+const ROUTE_METHOD_HINTS: &[&str] = &["get", "put", "post", "delete", "patch"];
+const GRAPHQL_EXTENSIONS: &[&str] = &["graphql", "graphqls", "gql"];
+const GRAPHQL_BUILTIN_TYPES: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+const MAX_SCHEMA_BYTES: u64 = 5 * 1024 * 1024;
+/// Resolvers and operation ids matching more functions than this are too
+/// ambiguous to link.
+const MAX_NAME_MATCHES: usize = 3;
+/// How many levels above a route literal its registering call or
+/// decorator can be.
+const MAX_ROUTE_DEPTH: usize = 6;
+
+const CALL_NODES: &[&str] = &["call_expression", "call", "method_invocation"];
+const DECORATOR_NODES: &[&str] = &["decorator", "attribute_item", "annotation", "marker_annotation"];
+
+// ============================================================================
+// API SCHEMA STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ApiHandler {
+    pub path: String,
+    pub relative_path: String,
+    /// 0-based line of the handler definition, or of the route registration
+    /// for inline handlers.
+    pub line: usize,
+    /// `None` for inline (anonymous) handlers.
+    pub name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiOperation {
+    pub id: String,
+    /// "openapi" or "graphql".
+    pub kind: String,
+    /// Lowercase HTTP method, or "query", "mutation" or "subscription".
+    pub method: String,
+    /// The path template, or the GraphQL root field.
+    pub route: String,
+    pub operation_id: Option<String>,
+    pub summary: Option<String>,
+    pub schema_file: String,
+    /// API types its parameters, bodies, responses or arguments use.
+    pub types: Vec<String>,
+    pub handlers: Vec<ApiHandler>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiType {
+    pub id: String,
+    pub name: String,
+    /// "schema" for OpenAPI; "type", "interface", "input", "enum", "union"
+    /// or "scalar" for GraphQL.
+    pub kind: String,
+    pub schema_file: String,
+    pub fields: Vec<String>,
+    /// Other API types it refers to.
+    pub references: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApiSchemaReport {
+    pub schema_files: Vec<String>,
+    pub operations: Vec<ApiOperation>,
+    pub types: Vec<ApiType>,
+    /// Operations no handler was found for.
+    pub unhandled: usize,
+    pub nodes_stored: usize,
+    pub warnings: Vec<String>,
+}
+
+/// A route literal in code and the handler registered for it.
+struct RouteSite {
+    route: String,
+    /// Methods named next to the literal; empty when it doesn't say.
+    methods: BTreeSet<String>,
+    handler: ApiHandler,
+}
+
+/// What the code scan found: route registrations and every named function.
+#[derive(Default)]
+struct CodeIndex {
+    routes: Vec<RouteSite>,
+    functions: BTreeMap<String, Vec<ApiHandler>>,
+}
+
+// ============================================================================
+// OPENAPI PARSING
+// ============================================================================
+
+fn is_openapi(spec: &Value) -> bool {
+    (spec.get("openapi").is_some() || spec.get("swagger").is_some()) && spec.get("paths").is_some_and(Value::is_object)
+}
+
+fn normalize_route(parameter: &Regex, route: &str) -> String {
+    let route = route.split(['?', '#']).next().unwrap_or(route);
+    let route = parameter.replace_all(route, "*");
+    let route = route.trim_end_matches('/');
+    if route.is_empty() {
+        "/".to_string()
+    } else {
+        route.to_string()
+    }
+}
+
+/// Path prefixes the spec's operations are served under: Swagger's
+/// `basePath` and the path part of OpenAPI `servers` URLs.
+fn base_paths(spec: &Value) -> Vec<String> {
+    let mut bases: Vec<String> = spec.get("basePath").and_then(Value::as_str).map(str::to_string).into_iter().collect();
+    for server in spec.get("servers").and_then(Value::as_array).into_iter().flatten() {
+        let Some(url) = server.get("url").and_then(Value::as_str) else {
+            continue;
+        };
+        let path = match url.split_once("://") {
+            Some((_, rest)) => rest.find('/').map(|i| &rest[i..]).unwrap_or(""),
+            None => url,
+        };
+        bases.push(path.to_string());
+    }
+    bases.retain(|base| base.starts_with('/') && base.trim_end_matches('/') != "");
+    bases
+}
+
+/// Schema names `value` refers to, following `$ref`s into shared
+/// parameters, request bodies and responses but not into other schemas.
+fn schema_refs(spec: &Value, value: &Value, names: &mut BTreeSet<String>, visited: &mut BTreeSet<String>) {
+    match value {
+        Value::Object(map) => {
+            if let Some(reference) = map.get("$ref").and_then(Value::as_str) {
+                if let Some(name) = reference
+                    .strip_prefix("#/components/schemas/")
+                    .or_else(|| reference.strip_prefix("#/definitions/"))
+                {
+                    names.insert(name.to_string());
+                } else if let Some(pointer) = reference.strip_prefix('#') {
+                    if visited.insert(reference.to_string()) {
+                        if let Some(target) = spec.pointer(pointer) {
+                            schema_refs(spec, target, names, visited);
+                        }
+                    }
+                }
+            }
+            for child in map.values() {
+                schema_refs(spec, child, names, visited);
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                schema_refs(spec, item, names, visited);
+            }
+        }
+        _ => {}
+    }
+}
+
+fn schema_fields(schema: &Value) -> Vec<String> {
+    let mut fields: BTreeSet<String> = BTreeSet::new();
+    let parts = ["allOf", "oneOf", "anyOf"]
+        .iter()
+        .filter_map(|key| schema.get(key).and_then(Value::as_array))
+        .flatten();
+    for part in std::iter::once(schema).chain(parts) {
+        if let Some(properties) = part.get("properties").and_then(Value::as_object) {
+            fields.extend(properties.keys().cloned());
+        }
+    }
+    fields.into_iter().collect()
+}
+
+fn parse_openapi(spec: &Value, schema_file: &str) -> (Vec<ApiOperation>, Vec<ApiType>) {
+    let mut operations = Vec::new();
+    for (route, item) in spec.get("paths").and_then(Value::as_object).into_iter().flatten() {
+        for method in HTTP_METHODS {
+            let Some(operation) = item.get(*method) else {
+                continue;
+            };
+            let mut types = BTreeSet::new();
+            let mut visited = BTreeSet::new();
+            schema_refs(spec, operation, &mut types, &mut visited);
+            if let Some(parameters) = item.get("parameters") {
+                schema_refs(spec, parameters, &mut types, &mut visited);
+            }
+            let text = |key: &str| operation.get(key).and_then(Value::as_str).map(str::to_string);
+            let description = text("description").and_then(|d| d.lines().next().map(str::to_string));
+            operations.push(ApiOperation {
+                id: format!("api_operation:{}:{} {}", schema_file, method.to_uppercase(), route),
+                kind: "openapi".to_string(),
+                method: method.to_string(),
+                route: route.clone(),
+                operation_id: text("operationId"),
+                summary: text("summary").or(description),
+                schema_file: schema_file.to_string(),
+                types: types.into_iter().collect(),
+                handlers: Vec::new(),
+            });
+        }
+    }
+
+    let schemas = spec.pointer("/components/schemas").or_else(|| spec.get("definitions"));
+    let mut types = Vec::new();
+    for (name, schema) in schemas.and_then(Value::as_object).into_iter().flatten() {
+        let mut references = BTreeSet::new();
+        schema_refs(spec, schema, &mut references, &mut BTreeSet::new());
+        references.remove(name);
+        types.push(ApiType {
+            id: format!("api_type:{}:{}", schema_file, name),
+            name: name.clone(),
+            kind: "schema".to_string(),
+            schema_file: schema_file.to_string(),
+            fields: schema_fields(schema),
+            references: references.into_iter().collect(),
+        });
+    }
+    (operations, types)
+}
+
+// ============================================================================
+// GRAPHQL PARSING
+// ============================================================================
+
+#[derive(Debug, PartialEq)]
+enum GraphqlToken {
+    Name(String),
+    Punct(char),
+}
+
+/// Names and punctuation of an SDL document; comments, descriptions,
+/// strings, numbers and commas carry nothing the graph needs.
+fn graphql_tokens(text: &str) -> Vec<GraphqlToken> {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '#' {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '"' {
+            let block = chars[i..].starts_with(&['"', '"', '"']);
+            i += if block { 3 } else { 1 };
+            while i < chars.len() {
+                if chars[i] == '\\' {
+                    i += 2;
+                } else if block && chars[i..].starts_with(&['"', '"', '"']) {
+                    i += 3;
+                    break;
+                } else if !block && (chars[i] == '"' || chars[i] == '\n') {
+                    i += 1;
+                    break;
+                } else {
+                    i += 1;
+                }
+            }
+        } else if c == '_' || c.is_ascii_alphabetic() {
+            let start = i;
+            while i < chars.len() && (chars[i] == '_' || chars[i].is_ascii_alphanumeric()) {
+                i += 1;
+            }
+            tokens.push(GraphqlToken::Name(chars[start..i].iter().collect()));
+        } else if c == '-' || c.is_ascii_digit() {
+            i += 1;
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.' || chars[i] == '+') {
+                i += 1;
+            }
+        } else {
+            if !c.is_whitespace() && c != ',' {
+                tokens.push(GraphqlToken::Punct(c));
+            }
+            i += 1;
+        }
+    }
+    tokens
+}
+
+#[derive(Default)]
+struct GraphqlDefinition {
+    kind: String,
+    /// Field (or enum value) names and the named types each one uses.
+    fields: Vec<(String, BTreeSet<String>)>,
+    /// Implemented interfaces and union members.
+    references: BTreeSet<String>,
+}
+
+struct GraphqlParser {
+    tokens: Vec<GraphqlToken>,
+    i: usize,
+}
+
+impl GraphqlParser {
+    fn at(&self, c: char) -> bool {
+        self.tokens.get(self.i) == Some(&GraphqlToken::Punct(c))
+    }
+
+    fn name(&mut self) -> Option<String> {
+        match self.tokens.get(self.i) {
+            Some(GraphqlToken::Name(name)) => {
+                self.i += 1;
+                Some(name.clone())
+            }
+            _ => None,
+        }
+    }
+
+    fn skip_balanced(&mut self, open: char, close: char) {
+        let mut depth = 0;
+        while let Some(token) = self.tokens.get(self.i) {
+            self.i += 1;
+            if *token == GraphqlToken::Punct(open) {
+                depth += 1;
+            } else if *token == GraphqlToken::Punct(close) {
+                depth -= 1;
+                if depth == 0 {
+                    break;
+                }
+            }
+        }
+    }
+
+    fn skip_directives(&mut self) {
+        while self.at('@') {
+            self.i += 1;
+            self.name();
+            if self.at('(') {
+                self.skip_balanced('(', ')');
+            }
+        }
+    }
+
+    /// The named type of `[Type!]!`-style references.
+    fn type_ref(&mut self) -> Option<String> {
+        while self.at('[') {
+            self.i += 1;
+        }
+        let name = self.name();
+        while self.at('!') || self.at(']') {
+            self.i += 1;
+        }
+        name
+    }
+
+    /// Argument types of a field, from its opening parenthesis.
+    fn arguments(&mut self, types: &mut BTreeSet<String>) {
+        self.i += 1;
+        let mut depth = 1;
+        while depth > 0 && self.i < self.tokens.len() {
+            if self.at(':') && depth == 1 {
+                self.i += 1;
+                types.extend(self.type_ref());
+                continue;
+            }
+            if self.at('(') || self.at('[') || self.at('{') {
+                depth += 1;
+            } else if self.at(')') || self.at(']') || self.at('}') {
+                depth -= 1;
+            }
+            self.i += 1;
+        }
+    }
+
+    /// Fields of a `{ ... }` body at the cursor.
+    fn fields(&mut self) -> Vec<(String, BTreeSet<String>)> {
+        let mut fields = Vec::new();
+        self.i += 1;
+        while self.i < self.tokens.len() {
+            if self.at('}') {
+                self.i += 1;
+                break;
+            }
+            let Some(field) = self.name() else {
+                self.i += 1;
+                continue;
+            };
+            let mut types = BTreeSet::new();
+            if self.at('(') {
+                self.arguments(&mut types);
+            }
+            if self.at(':') {
+                self.i += 1;
+                types.extend(self.type_ref());
+            }
+            self.skip_directives();
+            fields.push((field, types));
+        }
+        fields
+    }
+
+    /// Everything between a definition's name and its body: implemented
+    /// interfaces, union members and directives.
+    fn header(&mut self, references: &mut BTreeSet<String>) {
+        while self.i < self.tokens.len() && !self.at('{') {
+            if self.at('@') {
+                self.skip_directives();
+                continue;
+            }
+            match &self.tokens[self.i] {
+                GraphqlToken::Name(name) if is_graphql_keyword(name) => return,
+                GraphqlToken::Name(name) if name != "implements" => {
+                    references.insert(name.clone());
+                }
+                _ => {}
+            }
+            self.i += 1;
+        }
+    }
+
+    /// Named definitions (with `extend`s merged in) and the root operation
+    /// type names from a `schema` block.
+    fn parse(mut self) -> (BTreeMap<String, GraphqlDefinition>, BTreeMap<String, String>) {
+        let mut definitions: BTreeMap<String, GraphqlDefinition> = BTreeMap::new();
+        let mut roots: BTreeMap<String, String> = BTreeMap::new();
+        for (operation, name) in [("query", "Query"), ("mutation", "Mutation"), ("subscription", "Subscription")] {
+            roots.insert(operation.to_string(), name.to_string());
+        }
+        while self.i < self.tokens.len() {
+            if self.at('{') {
+                // Executable operations and fragments aren't part of the schema
+                self.skip_balanced('{', '}');
+                continue;
+            }
+            let Some(keyword) = self.name() else {
+                self.i += 1;
+                continue;
+            };
+            match keyword.as_str() {
+                "schema" => {
+                    self.skip_directives();
+                    if self.at('{') {
+                        for (operation, types) in self.fields() {
+                            if let Some(name) = types.into_iter().next() {
+                                roots.insert(operation, name);
+                            }
+                        }
+                    }
+                }
+                "type" | "interface" | "input" | "enum" | "union" | "scalar" => {
+                    let Some(name) = self.name() else {
+                        continue;
+                    };
+                    let definition = definitions.entry(name).or_default();
+                    if definition.kind.is_empty() {
+                        definition.kind = keyword.clone();
+                    }
+                    self.header(&mut definition.references);
+                    if self.at('{') {
+                        definition.fields.extend(self.fields());
+                    }
+                }
+                "directive" => {
+                    self.skip_directives();
+                    if self.at('(') {
+                        self.skip_balanced('(', ')');
+                    }
+                    // `repeatable on FIELD | OBJECT` locations are names too
+                    while let Some(GraphqlToken::Name(name)) = self.tokens.get(self.i) {
+                        if is_graphql_keyword(name) && name != "repeatable" {
+                            break;
+                        }
+                        self.i += 1;
+                        while self.at('|') {
+                            self.i += 1;
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+        (definitions, roots)
+    }
+}
+
+fn is_graphql_keyword(name: &str) -> bool {
+    matches!(
+        name,
+        "schema" | "type" | "interface" | "input" | "enum" | "union" | "scalar" | "directive" | "extend" | "repeatable"
+    )
+}
+
+fn parse_graphql(text: &str, schema_file: &str) -> (Vec<ApiOperation>, Vec<ApiType>) {
+    let parser = GraphqlParser {
+        tokens: graphql_tokens(text),
+        i: 0,
+    };
+    let (definitions, roots) = parser.parse();
+    let custom = |types: &BTreeSet<String>| -> Vec<String> {
+        types.iter().filter(|t| !GRAPHQL_BUILTIN_TYPES.contains(&t.as_str())).cloned().collect()
+    };
+
+    let mut operations = Vec::new();
+    for (operation, root) in &roots {
+        let Some(definition) = definitions.get(root) else {
+            continue;
+        };
+        for (field, types) in &definition.fields {
+            operations.push(ApiOperation {
+                id: format!("api_operation:{}:{} {}", schema_file, operation, field),
+                kind: "graphql".to_string(),
+                method: operation.clone(),
+                route: field.clone(),
+                operation_id: None,
+                summary: None,
+                schema_file: schema_file.to_string(),
+                types: custom(types),
+                handlers: Vec::new(),
+            });
+        }
+    }
+
+    let root_names: BTreeSet<&String> = roots.values().collect();
+    let mut types = Vec::new();
+    for (name, definition) in &definitions {
+        if root_names.contains(name) {
+            continue;
+        }
+        let mut references: BTreeSet<String> = definition.references.clone();
+        for (_, field_types) in &definition.fields {
+            references.extend(field_types.iter().cloned());
+        }
+        references.remove(name);
+        types.push(ApiType {
+            id: format!("api_type:{}:{}", schema_file, name),
+            name: name.clone(),
+            kind: definition.kind.clone(),
+            schema_file: schema_file.to_string(),
+            fields: definition.fields.iter().map(|(field, _)| field.clone()).collect(),
+            references: custom(&references),
+        });
+    }
+    (operations, types)
+}
+
+// ============================================================================
+// HANDLER DISCOVERY
+// ============================================================================
+
+/// The name a function is known by: its own, or the variable, key or
+/// property it is assigned to.
+fn function_name(node: Node, source: &[u8]) -> Option<String> {
+    if let Some(name) = node.child_by_field_name("name") {
+        return Some(node_text(name, source).to_string());
+    }
+    let parent = node.parent()?;
+    let target = match parent.kind() {
+        "variable_declarator" => parent.child_by_field_name("name")?,
+        "pair" => parent.child_by_field_name("key")?,
+        "assignment_expression" | "assignment" => parent.child_by_field_name("left")?,
+        _ => return None,
+    };
+    let text = node_text(target, source).trim_matches(['"', '\'']);
+    let name = text.rsplit(['.', ':']).next().unwrap_or(text);
+    (!name.is_empty() && name.chars().all(|c| c == '_' || c.is_alphanumeric())).then(|| name.to_string())
+}
+
+/// The definition a decorator, attribute or annotation applies to.
+fn decorated(decorator: Node, source: &[u8]) -> Option<(Option<String>, usize)> {
+    let parent = decorator.parent()?;
+    let target = match parent.kind() {
+        "decorated_definition" => parent.child_by_field_name("definition")?,
+        // Java annotations sit in the method's modifiers
+        "modifiers" => parent.parent()?,
+        kind if FUNCTION_NODES.contains(&kind) => parent,
+        _ => {
+            let mut next = decorator.next_named_sibling();
+            while let Some(sibling) = next {
+                if !DECORATOR_NODES.contains(&sibling.kind()) && !sibling.kind().contains("comment") {
+                    break;
+                }
+                next = sibling.next_named_sibling();
+            }
+            next?
+        }
+    };
+    Some((function_name(target, source), target.start_position().row))
+}
+
+/// The handler a route literal registers: the function passed after it
+/// (`app.get("/users", listUsers)`, `http.HandleFunc("/users", func...)`)
+/// or the definition it decorates (`@app.get("/users")`,
+/// `#[get("/users")]`, `@GetMapping("/users")`). Literals that register
+/// nothing, such as `fetch("/users")`, have none.
+fn route_handler(literal: Node, source: &[u8]) -> Option<(Option<String>, usize)> {
+    let mut node = literal;
+    for _ in 0..MAX_ROUTE_DEPTH {
+        let parent = node.parent()?;
+        if DECORATOR_NODES.contains(&parent.kind()) {
+            return decorated(parent, source);
+        }
+        if FUNCTION_NODES.contains(&parent.kind()) {
+            return None;
+        }
+        let arguments = parent.child_by_field_name("arguments");
+        if CALL_NODES.contains(&parent.kind()) && arguments.is_some_and(|a| a.id() == node.id()) {
+            let mut cursor = node.walk();
+            let later: Vec<Node> = node
+                .named_children(&mut cursor)
+                .filter(|arg| arg.start_byte() >= literal.end_byte())
+                .collect();
+            if let Some(&handler) = later.last() {
+                if FUNCTION_NODES.contains(&handler.kind()) {
+                    return Some((function_name(handler, source), handler.start_position().row));
+                }
+                let text = node_text(handler, source);
+                let name = text.rsplit(['.', ':']).next().unwrap_or(text);
+                if !name.is_empty() && name.chars().all(|c| c == '_' || c.is_alphanumeric()) {
+                    return Some((Some(name.to_string()), handler.start_position().row));
+                }
+            }
+        }
+        node = parent;
+    }
+    None
+}
+
+/// Methods a route registration line names (`router.post`, `@GetMapping`,
+/// `methods=["PUT"]`).
+fn route_methods(line: &str) -> BTreeSet<String> {
+    line.to_lowercase()
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .map(|word| word.strip_suffix("mapping").unwrap_or(word))
+        .filter(|word| ROUTE_METHOD_HINTS.contains(word))
+        .map(str::to_string)
+        .collect()
+}
+
+fn scan_code(parser: &ParserState, parameter: &Regex, root: &Path, path: &Path, index: &mut CodeIndex) {
+    let path_str = path.to_string_lossy().to_string();
+    let Ok((content, _)) = read_text(path) else {
+        return;
+    };
+    let Some((_, tree)) = parser.parse_tree(&path_str, &content) else {
+        return;
+    };
+    let source = content.as_bytes();
+    let lines: Vec<&str> = content.lines().collect();
+    let relative = relative_path(root, path).unwrap_or_default();
+    let handler = |name: Option<String>, line: usize| ApiHandler {
+        path: path_str.clone(),
+        relative_path: relative.clone(),
+        line,
+        name,
+    };
+
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if STRING_NODES.contains(&node.kind()) {
+            let text = string_value(node, source);
+            if text.starts_with('/') && !text.starts_with("//") && !text.contains(char::is_whitespace) {
+                if let Some((name, line)) = route_handler(node, source) {
+                    let row = node.start_position().row;
+                    index.routes.push(RouteSite {
+                        route: normalize_route(parameter, &text),
+                        methods: route_methods(lines.get(row).copied().unwrap_or_default()),
+                        handler: handler(name, line),
+                    });
+                }
+            }
+            continue;
+        }
+        if FUNCTION_NODES.contains(&node.kind()) {
+            if let Some(name) = function_name(node, source) {
+                let found = handler(Some(name.clone()), node.start_position().row);
+                index.functions.entry(name).or_default().push(found);
+            }
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+}
+
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            snake.push('_');
+        }
+        snake.extend(c.to_lowercase());
+    }
+    snake
+}
+
+/// Functions defined under any of `names`, trying each group in turn and
+/// giving up on groups that match too many.
+fn functions_named(index: &CodeIndex, groups: &[Vec<String>]) -> Vec<ApiHandler> {
+    for names in groups {
+        let found: Vec<ApiHandler> = names
+            .iter()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .flat_map(|name| index.functions.get(name).into_iter().flatten().cloned())
+            .collect();
+        if !found.is_empty() && found.len() <= MAX_NAME_MATCHES {
+            return found;
+        }
+    }
+    Vec::new()
+}
+
+/// Route registrations for `operation`, matching its path with and without
+/// the spec's base paths, or as mounted under a router prefix.
+fn route_handlers(index: &CodeIndex, parameter: &Regex, operation: &ApiOperation, bases: &[String]) -> Vec<ApiHandler> {
+    let mut routes: BTreeSet<String> = [normalize_route(parameter, &operation.route)].into_iter().collect();
+    for base in bases {
+        routes.insert(normalize_route(parameter, &format!("{}{}", base.trim_end_matches('/'), operation.route)));
+    }
+    let serves = |site: &RouteSite| site.methods.is_empty() || site.methods.contains(&operation.method);
+
+    let mut sites: Vec<&RouteSite> = index.routes.iter().filter(|s| serves(s) && routes.contains(&s.route)).collect();
+    if sites.is_empty() {
+        sites = index
+            .routes
+            .iter()
+            .filter(|s| serves(s) && s.route.matches('/').count() >= 2)
+            .filter(|s| routes.iter().any(|route| route.ends_with(&s.route)))
+            .collect();
+    }
+    sites.into_iter().map(|site| resolve_handler(index, &site.handler)).collect()
+}
+
+/// A handler passed by name is defined elsewhere; prefer a definition in
+/// the same file.
+fn resolve_handler(index: &CodeIndex, handler: &ApiHandler) -> ApiHandler {
+    let Some(name) = &handler.name else {
+        return handler.clone();
+    };
+    let definitions = index.functions.get(name).map(Vec::as_slice).unwrap_or_default();
+    let same_file = definitions.iter().find(|d| d.path == handler.path);
+    let unique = definitions.first().filter(|_| definitions.len() == 1);
+    same_file.or(unique).unwrap_or(handler).clone()
+}
+
+fn link_handlers(index: &CodeIndex, parameter: &Regex, operation: &mut ApiOperation, bases: &[String]) {
+    let mut handlers = match operation.kind.as_str() {
+        "openapi" => route_handlers(index, parameter, operation, bases),
+        _ => Vec::new(),
+    };
+    if handlers.is_empty() {
+        let name = operation.operation_id.clone().unwrap_or_else(|| operation.route.clone());
+        let snake = snake_case(&name);
+        let mut capitalized = name.clone();
+        if let Some(first) = capitalized.get_mut(..1) {
+            first.make_ascii_uppercase();
+        }
+        let groups = match operation.kind.as_str() {
+            "openapi" if operation.operation_id.is_some() => vec![vec![name, snake]],
+            "graphql" => vec![
+                vec![
+                    format!("resolve_{}", snake),
+                    format!("resolve{}", capitalized),
+                    format!("Resolve{}", capitalized),
+                ],
+                vec![name, snake],
+            ],
+            _ => Vec::new(),
+        };
+        handlers = functions_named(index, &groups);
+    }
+    handlers.sort_by(|a, b| (&a.path, a.line).cmp(&(&b.path, b.line)));
+    handlers.dedup_by(|a, b| a.path == b.path && a.line == b.line);
+    operation.handlers = handlers;
+}
+
+// ============================================================================
+// API SCHEMA INGESTION
+// ============================================================================
+
+/// "graphql", "json" or "yaml" for files that may hold an API schema.
+fn schema_format(path: &Path) -> Option<&'static str> {
+    let name = path.file_name()?.to_string_lossy().to_lowercase();
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    if GRAPHQL_EXTENSIONS.contains(&extension.as_str()) {
+        return Some("graphql");
+    }
+    let named = name.contains("openapi") || name.contains("swagger") || name.starts_with("api.");
+    match extension.as_str() {
+        "json" if named => Some("json"),
+        "yaml" | "yml" if named => Some("yaml"),
+        _ => None,
+    }
+}
+
+fn collect(parser: &ParserState, root: &Path) -> Result<ApiSchemaReport, String> {
+    let parameter = Regex::new(ROUTE_PARAMETER).unwrap();
+    let mut report = ApiSchemaReport {
+        schema_files: Vec::new(),
+        operations: Vec::new(),
+        types: Vec::new(),
+        unhandled: 0,
+        nodes_stored: 0,
+        warnings: Vec::new(),
+    };
+    let mut code_files: Vec<PathBuf> = Vec::new();
+    let mut bases: BTreeMap<String, Vec<String>> = BTreeMap::new();
+
+    let mut entries = walk_entries(root, &DirectoryOptions::default(), None)?;
+    entries.sort();
+    for (path, is_dir) in entries {
+        if is_dir {
+            continue;
+        }
+        let Some(format) = schema_format(&path) else {
+            if parser.detect_language(&path.to_string_lossy()).is_some() {
+                code_files.push(path);
+            }
+            continue;
+        };
+        if path.metadata().map(|m| m.len()).unwrap_or(0) > MAX_SCHEMA_BYTES {
+            report.warnings.push(format!("Skipped {}: too large", path.display()));
+            continue;
+        }
+        let Ok((content, _)) = read_text(&path) else {
+            continue;
+        };
+        let schema_file = relative_path(root, &path).unwrap_or_else(|| path.to_string_lossy().to_string());
+        let (operations, types) = if format == "graphql" {
+            parse_graphql(&content, &schema_file)
+        } else {
+            let spec: Result<Value, String> = if format == "json" {
+                serde_json::from_str(&content).map_err(|e| e.to_string())
+            } else {
+                serde_yaml::from_str(&content).map_err(|e| e.to_string())
+            };
+            match spec {
+                Ok(spec) if is_openapi(&spec) => {
+                    bases.insert(schema_file.clone(), base_paths(&spec));
+                    parse_openapi(&spec, &schema_file)
+                }
+                Ok(_) => continue,
+                Err(e) => {
+                    report.warnings.push(format!("Failed to parse {}: {}", schema_file, e));
+                    continue;
+                }
+            }
+        };
+        report.schema_files.push(schema_file);
+        report.operations.extend(operations);
+        report.types.extend(types);
+    }
+    if report.operations.is_empty() {
+        return Ok(report);
+    }
+
+    let mut index = CodeIndex::default();
+    for path in &code_files {
+        scan_code(parser, &parameter, root, path, &mut index);
+    }
+    for operation in &mut report.operations {
+        let spec_bases = bases.get(&operation.schema_file).map(Vec::as_slice).unwrap_or_default();
+        link_handlers(&index, &parameter, operation, spec_bases);
+    }
+    report.unhandled = report.operations.iter().filter(|o| o.handlers.is_empty()).count();
+    Ok(report)
+}
+
+/// Replaces the API_OPERATION and API_TYPE nodes of `root` with the
+/// report's, contained by their schema FILE nodes. Operations get
+/// USES_TYPE edges to their types and HANDLED_BY edges to handler FUNCTION
+/// nodes, or to the handler's FILE for inline handlers.
+async fn store_in_graph(neo4j: &Neo4jState, root: &Path, report: &ApiSchemaReport) -> Result<usize, String> {
+    let graph = neo4j.get_graph().await?;
+    let root_str = root.to_string_lossy().to_string();
+    let absolute = |file: &str| root.join(file).to_string_lossy().to_string();
+    graph
+        .run(
+            query("MATCH (n) WHERE (n:API_OPERATION OR n:API_TYPE) AND n.root = $root DETACH DELETE n")
+                .param("root", root_str.clone()),
+        )
+        .await
+        .map_err(|e| format!("Failed to clear API nodes: {}", e))?;
+
+    for api_type in &report.types {
+        graph
+            .run(
+                query(
+                    "CREATE (t:API_TYPE {id: $id, name: $name, kind: $kind, fields: $fields, \
+                         path: $path, root: $root}) \
+                     WITH t MATCH (f:FILE) WHERE f.path IN [$path, $absolute] CREATE (f)-[:CONTAINS]->(t)",
+                )
+                .param("id", api_type.id.clone())
+                .param("name", api_type.name.clone())
+                .param("kind", api_type.kind.clone())
+                .param("fields", api_type.fields.clone())
+                .param("path", api_type.schema_file.clone())
+                .param("absolute", absolute(&api_type.schema_file))
+                .param("root", root_str.clone()),
+            )
+            .await
+            .map_err(|e| format!("Failed to store API type {}: {}", api_type.name, e))?;
+    }
+    for api_type in &report.types {
+        for reference in &api_type.references {
+            graph
+                .run(
+                    query("MATCH (a:API_TYPE {id: $from}), (b:API_TYPE {id: $to}) CREATE (a)-[:REFERENCES]->(b)")
+                        .param("from", api_type.id.clone())
+                        .param("to", format!("api_type:{}:{}", api_type.schema_file, reference)),
+                )
+                .await
+                .map_err(|e| format!("Failed to link API type {}: {}", api_type.name, e))?;
+        }
+    }
+
+    for operation in &report.operations {
+        let name = match operation.kind.as_str() {
+            "openapi" => format!("{} {}", operation.method.to_uppercase(), operation.route),
+            _ => format!("{} {}", operation.method, operation.route),
+        };
+        graph
+            .run(
+                query(
+                    "CREATE (o:API_OPERATION {id: $id, name: $name, kind: $kind, method: $method, route: $route, \
+                         operationId: $operation_id, summary: $summary, path: $path, root: $root, \
+                         handled: $handled}) \
+                     WITH o MATCH (f:FILE) WHERE f.path IN [$path, $absolute] CREATE (f)-[:CONTAINS]->(o)",
+                )
+                .param("id", operation.id.clone())
+                .param("name", name)
+                .param("kind", operation.kind.clone())
+                .param("method", operation.method.clone())
+                .param("route", operation.route.clone())
+                .param("operation_id", operation.operation_id.clone().unwrap_or_default())
+                .param("summary", operation.summary.clone().unwrap_or_default())
+                .param("path", operation.schema_file.clone())
+                .param("absolute", absolute(&operation.schema_file))
+                .param("root", root_str.clone())
+                .param("handled", !operation.handlers.is_empty()),
+            )
+            .await
+            .map_err(|e| format!("Failed to store API operation {}: {}", operation.id, e))?;
+
+        for type_name in &operation.types {
+            graph
+                .run(
+                    query("MATCH (o:API_OPERATION {id: $from}), (t:API_TYPE {id: $to}) CREATE (o)-[:USES_TYPE]->(t)")
+                        .param("from", operation.id.clone())
+                        .param("to", format!("api_type:{}:{}", operation.schema_file, type_name)),
+                )
+                .await
+                .map_err(|e| format!("Failed to link API operation {}: {}", operation.id, e))?;
+        }
+        for handler in &operation.handlers {
+            graph
+                .run(
+                    query(
+                        "MATCH (o:API_OPERATION {id: $id}) \
+                         MATCH (file:FILE) WHERE file.path IN [$path, $relative] \
+                         OPTIONAL MATCH (file)-[:CONTAINS]->(f:FUNCTION {name: $name}) \
+                         WITH o, coalesce(f, file) AS target LIMIT 1 \
+                         CREATE (o)-[:HANDLED_BY {line: $line}]->(target)",
+                    )
+                    .param("id", operation.id.clone())
+                    .param("path", handler.path.clone())
+                    .param("relative", handler.relative_path.clone())
+                    .param("name", handler.name.clone().unwrap_or_default())
+                    .param("line", handler.line as i64),
+                )
+                .await
+                .map_err(|e| format!("Failed to link handler for {}: {}", operation.id, e))?;
+        }
+    }
+    Ok(report.types.len() + report.operations.len())
+}
+
+// ============================================================================
+// API SCHEMA TAURI COMMANDS
+// ============================================================================
+
+/// Parses the OpenAPI/Swagger specs (`openapi.yaml`, `swagger.json`, ...)
+/// and GraphQL schemas under `root` into API_OPERATION and API_TYPE graph
+/// nodes, linking each operation to the code that handles it: route
+/// registrations for HTTP operations, resolvers by name for GraphQL
+/// fields.
+#[tauri::command]
+pub async fn ingest_api_schemas(
+    app: AppHandle,
+    root: String,
+    workspace: State<'_, WorkspaceState>,
+    neo4j: State<'_, Neo4jState>,
+) -> Result<ApiSchemaReport, String> {
+    let root_path = workspace.check(&root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+    let scan_root = root_path.clone();
+    let scan_app = app.clone();
+    let mut report = tokio::task::spawn_blocking(move || {
+        let parser = scan_app.state::<ParserState>();
+        collect(&parser, &scan_root)
+    })
+    .await
+    .map_err(|e| format!("API schema scan failed: {}", e))??;
+
+    report.nodes_stored = store_in_graph(&neo4j, &root_path, &report).await?;
+    emit_graph_updated(&app, "api_schema", Some(&root_path.to_string_lossy()), report.nodes_stored);
+    Ok(report)
+}
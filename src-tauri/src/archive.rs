@@ -0,0 +1,412 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use std::fs::{self as std_fs, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::sandbox::WorkspaceState;
+
+// ============================================================================
+// ARCHIVE STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+struct ArchiveProgress {
+    archive: String,
+    current: usize,
+    total: usize,
+    path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveResult {
+    pub archive: String,
+    pub files: usize,
+    /// Entries with unsafe paths (absolute, escaping with `..`, or running
+    /// through a symlink) that were not extracted.
+    pub skipped: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ArchiveFormat {
+    Zip,
+    TarGz,
+}
+
+fn parse_format(format: &str) -> Result<ArchiveFormat, String> {
+    match format.to_lowercase().as_str() {
+        "zip" => Ok(ArchiveFormat::Zip),
+        "tar.gz" | "tgz" => Ok(ArchiveFormat::TarGz),
+        other => Err(format!("Unsupported archive format: {}", other)),
+    }
+}
+
+/// Format from the file name, falling back to the magic number.
+fn detect_format(path: &Path) -> Result<ArchiveFormat, String> {
+    let name = path.to_string_lossy().to_lowercase();
+    if name.ends_with(".zip") {
+        return Ok(ArchiveFormat::Zip);
+    }
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        return Ok(ArchiveFormat::TarGz);
+    }
+
+    let mut magic = [0u8; 4];
+    File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+    if magic.starts_with(b"PK\x03\x04") {
+        Ok(ArchiveFormat::Zip)
+    } else if magic.starts_with(b"\x1f\x8b") {
+        Ok(ArchiveFormat::TarGz)
+    } else {
+        Err("Unrecognized archive format; expected .zip or .tar.gz".to_string())
+    }
+}
+
+struct Progress<'a> {
+    app: &'a AppHandle,
+    archive: String,
+    total: usize,
+}
+
+impl Progress<'_> {
+    fn emit(&self, current: usize, path: &str) {
+        let _ = self.app.emit(
+            "archive-progress",
+            ArchiveProgress {
+                archive: self.archive.clone(),
+                current,
+                total: self.total,
+                path: path.to_string(),
+            },
+        );
+    }
+}
+
+// ============================================================================
+// EXTRACTION
+// ============================================================================
+
+/// Extraction stops with an error past these, so a zip bomb can't fill the disk
+const MAX_ENTRIES: usize = 100_000;
+const MAX_EXTRACTED_BYTES: u64 = 4 * 1024 * 1024 * 1024;
+
+fn too_large() -> String {
+    format!("Archive expands past the {} GiB limit", MAX_EXTRACTED_BYTES >> 30)
+}
+
+/// Whether any existing component of `relative` under `dest` is a symlink.
+/// Writing through one would land wherever the link points.
+fn through_symlink(dest: &Path, relative: &Path) -> bool {
+    let mut current = dest.to_path_buf();
+    relative.components().any(|component| {
+        current.push(component);
+        std_fs::symlink_metadata(&current).is_ok_and(|meta| meta.file_type().is_symlink())
+    })
+}
+
+fn extract_zip(archive_path: &Path, dest: &Path, app: &AppHandle) -> Result<ArchiveResult, String> {
+    let file = File::open(archive_path).map_err(|e| format!("Failed to open archive: {}", e))?;
+    let mut archive = zip::ZipArchive::new(file).map_err(|e| format!("Failed to read archive: {}", e))?;
+    if archive.len() > MAX_ENTRIES {
+        return Err(format!("Archive has more than {} entries", MAX_ENTRIES));
+    }
+    let progress = Progress {
+        app,
+        archive: archive_path.to_string_lossy().to_string(),
+        total: archive.len(),
+    };
+    let mut result = ArchiveResult {
+        archive: progress.archive.clone(),
+        files: 0,
+        skipped: Vec::new(),
+    };
+
+    let mut extracted: u64 = 0;
+    for i in 0..archive.len() {
+        let mut entry = archive
+            .by_index(i)
+            .map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        // enclosed_name rejects absolute paths and `..` (zip slip)
+        let Some(relative) = entry.enclosed_name().filter(|r| !through_symlink(dest, r)) else {
+            result.skipped.push(entry.name().to_string());
+            continue;
+        };
+        let target = dest.join(relative);
+        progress.emit(i + 1, entry.name());
+
+        if entry.is_dir() {
+            std_fs::create_dir_all(&target).map_err(|e| format!("Failed to create directory: {}", e))?;
+            continue;
+        }
+        if let Some(parent) = target.parent() {
+            std_fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        let mut out = File::create(&target).map_err(|e| format!("Failed to create {}: {}", target.display(), e))?;
+        // Count what actually decompresses; the sizes in the header can lie
+        let remaining = MAX_EXTRACTED_BYTES - extracted;
+        let written = io::copy(&mut (&mut entry).take(remaining + 1), &mut out)
+            .map_err(|e| format!("Failed to extract {}: {}", entry.name(), e))?;
+        if written > remaining {
+            drop(out);
+            let _ = std_fs::remove_file(&target);
+            return Err(too_large());
+        }
+        extracted += written;
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Some(mode) = entry.unix_mode() {
+                let _ = std_fs::set_permissions(&target, std_fs::Permissions::from_mode(mode));
+            }
+        }
+        result.files += 1;
+    }
+    Ok(result)
+}
+
+fn extract_tar_gz(archive_path: &Path, dest: &Path, app: &AppHandle) -> Result<ArchiveResult, String> {
+    let open = || -> Result<tar::Archive<GzDecoder<File>>, String> {
+        let file = File::open(archive_path).map_err(|e| format!("Failed to open archive: {}", e))?;
+        Ok(tar::Archive::new(GzDecoder::new(file)))
+    };
+
+    // Tar has no index, so count entries in a first pass for progress
+    let total = open()?
+        .entries()
+        .map_err(|e| format!("Failed to read archive: {}", e))?
+        .count();
+    if total > MAX_ENTRIES {
+        return Err(format!("Archive has more than {} entries", MAX_ENTRIES));
+    }
+    let progress = Progress {
+        app,
+        archive: archive_path.to_string_lossy().to_string(),
+        total,
+    };
+    let mut result = ArchiveResult {
+        archive: progress.archive.clone(),
+        files: 0,
+        skipped: Vec::new(),
+    };
+
+    let mut archive = open()?;
+    let mut extracted: u64 = 0;
+    let entries = archive
+        .entries()
+        .map_err(|e| format!("Failed to read archive: {}", e))?;
+    for (i, entry) in entries.enumerate() {
+        let mut entry = entry.map_err(|e| format!("Failed to read archive entry: {}", e))?;
+        let name = entry
+            .path()
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default();
+        progress.emit(i + 1, &name);
+
+        let is_file = entry.header().entry_type().is_file();
+        if is_file {
+            // Tar stores each file's data uncompressed, so its size is exact
+            extracted = extracted.saturating_add(entry.size());
+            if extracted > MAX_EXTRACTED_BYTES {
+                return Err(too_large());
+            }
+        }
+        // unpack_in refuses paths that would land outside `dest`
+        let unpacked = entry
+            .unpack_in(dest)
+            .map_err(|e| format!("Failed to extract {}: {}", name, e))?;
+        if !unpacked {
+            result.skipped.push(name);
+        } else if is_file {
+            result.files += 1;
+        }
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// CREATION
+// ============================================================================
+
+/// Files and directories to archive as (path on disk, name in archive,
+/// is_dir). Directories are walked like the explorer does, so ignored
+/// files and build output stay out of the archive.
+fn archive_entries(paths: &[String]) -> Result<Vec<(PathBuf, String, bool)>, String> {
+    let mut entries = Vec::new();
+    for path in paths {
+        let path = Path::new(path);
+        let base = path.parent().unwrap_or(Path::new(""));
+        let name_of = |p: &Path| {
+            p.strip_prefix(base)
+                .unwrap_or(p)
+                .to_string_lossy()
+                .replace('\\', "/")
+        };
+
+        if path.is_dir() {
+            entries.push((path.to_path_buf(), name_of(path), true));
+            for (child, is_dir) in walk_entries(path, &DirectoryOptions::default(), None)? {
+                let name = name_of(&child);
+                entries.push((child, name, is_dir));
+            }
+        } else if path.is_file() {
+            entries.push((path.to_path_buf(), name_of(path), false));
+        } else {
+            return Err(format!("Path does not exist: {}", path.display()));
+        }
+    }
+    Ok(entries)
+}
+
+fn create_zip(entries: &[(PathBuf, String, bool)], dest: &Path, progress: &Progress) -> Result<usize, String> {
+    let file = File::create(dest).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut writer = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+
+    let mut files = 0;
+    for (i, (path, name, is_dir)) in entries.iter().enumerate() {
+        progress.emit(i + 1, name);
+        if *is_dir {
+            writer
+                .add_directory(name.as_str(), options)
+                .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+            continue;
+        }
+        writer
+            .start_file(name.as_str(), options)
+            .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        let mut input = File::open(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        io::copy(&mut input, &mut writer).map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        files += 1;
+    }
+    writer.finish().map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(files)
+}
+
+fn create_tar_gz(entries: &[(PathBuf, String, bool)], dest: &Path, progress: &Progress) -> Result<usize, String> {
+    let file = File::create(dest).map_err(|e| format!("Failed to create archive: {}", e))?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::default()));
+
+    let mut files = 0;
+    for (i, (path, name, is_dir)) in entries.iter().enumerate() {
+        progress.emit(i + 1, name);
+        if *is_dir {
+            builder
+                .append_dir(name, path)
+                .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+        } else {
+            builder
+                .append_path_with_name(path, name)
+                .map_err(|e| format!("Failed to add {}: {}", name, e))?;
+            files += 1;
+        }
+    }
+    builder
+        .into_inner()
+        .and_then(|encoder| encoder.finish())
+        .map_err(|e| format!("Failed to write archive: {}", e))?;
+    Ok(files)
+}
+
+// ============================================================================
+// ARCHIVE TAURI COMMANDS
+// ============================================================================
+
+/// Extracts a .zip or .tar.gz into `dest`, emitting `archive-progress` per
+/// entry. Entries that would escape `dest` are skipped and reported; archives
+/// past MAX_ENTRIES or MAX_EXTRACTED_BYTES fail.
+#[tauri::command]
+pub async fn extract_archive(
+    app: AppHandle,
+    archive_path: String,
+    dest: String,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<ArchiveResult, String> {
+    workspace.check(&archive_path)?;
+    workspace.check(&dest)?;
+    tokio::task::spawn_blocking(move || {
+        let archive_path = Path::new(&archive_path);
+        let dest = Path::new(&dest);
+        std_fs::create_dir_all(dest).map_err(|e| format!("Failed to create directory: {}", e))?;
+
+        match detect_format(archive_path)? {
+            ArchiveFormat::Zip => extract_zip(archive_path, dest, &app),
+            ArchiveFormat::TarGz => extract_tar_gz(archive_path, dest, &app),
+        }
+    })
+    .await
+    .map_err(|e| format!("Archive task failed: {}", e))?
+}
+
+/// Packs `paths` into a new archive at `dest` ("zip" or "tar.gz"). Each path
+/// becomes a top-level entry named after it.
+#[tauri::command]
+pub async fn create_archive(
+    app: AppHandle,
+    paths: Vec<String>,
+    dest: String,
+    format: String,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<ArchiveResult, String> {
+    workspace.check_all(&paths)?;
+    workspace.check(&dest)?;
+    let format = parse_format(&format)?;
+    tokio::task::spawn_blocking(move || {
+        let entries = archive_entries(&paths)?;
+        let dest_path = Path::new(&dest);
+        if let Some(parent) = dest_path.parent() {
+            std_fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+        }
+        let progress = Progress {
+            app: &app,
+            archive: dest.clone(),
+            total: entries.len(),
+        };
+
+        let written = match format {
+            ArchiveFormat::Zip => create_zip(&entries, dest_path, &progress),
+            ArchiveFormat::TarGz => create_tar_gz(&entries, dest_path, &progress),
+        };
+        match written {
+            Ok(files) => Ok(ArchiveResult {
+                archive: dest.clone(),
+                files,
+                skipped: Vec::new(),
+            }),
+            Err(e) => {
+                let _ = std_fs::remove_file(dest_path); // Don't leave a partial archive
+                Err(e)
+            }
+        }
+    })
+    .await
+    .map_err(|e| format!("Archive task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn entries_through_existing_symlinks_are_unsafe() {
+        let root = std::env::temp_dir().join(format!("gencode-archive-{}", std::process::id()));
+        let dest = root.join("dest");
+        let outside = root.join("outside");
+        std_fs::create_dir_all(dest.join("real")).unwrap();
+        std_fs::create_dir_all(&outside).unwrap();
+        std::os::unix::fs::symlink(&outside, dest.join("link")).unwrap();
+
+        let unsafe_entry = through_symlink(&dest, Path::new("link/payload.txt"));
+        let safe_entry = through_symlink(&dest, Path::new("real/new/file.txt"));
+        std_fs::remove_dir_all(&root).unwrap();
+        assert!(unsafe_entry);
+        assert!(!safe_entry);
+    }
+}
@@ -0,0 +1,323 @@
+use regex::Regex;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+use tree_sitter::{Node, Tree};
+
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::files::read_text;
+use crate::finder::relative_path;
+use crate::sandbox::WorkspaceState;
+use crate::symbols::{node_text, LineIndex, SourceRange};
+use crate::ParserState;
+
+const SINGLE_PREFIX: &str = "__mv_";
+const MULTI_PREFIX: &str = "__mvs_";
+const DEFAULT_LIMIT: usize = 500;
+const MAX_MATCH_TEXT: usize = 2000;
+
+// ============================================================================
+// STRUCTURAL SEARCH STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct StructuralMatch {
+    pub path: String,
+    pub relative_path: String,
+    pub range: SourceRange,
+    pub text: String,
+    /// Metavariable name (without `$`) to the source it matched.
+    pub captures: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StructuralSearchResult {
+    pub matches: Vec<StructuralMatch>,
+    pub files_searched: usize,
+    pub truncated: bool,
+}
+
+// ============================================================================
+// PATTERNS
+// ============================================================================
+
+/// A pattern parsed for one language. Metavariables are rewritten to
+/// placeholder identifiers so the pattern parses as ordinary code:
+/// `$A` matches one node, `$$$A` any run of sibling nodes and `$_`/`$$$`
+/// match without capturing.
+struct Pattern {
+    source: String,
+    tree: Tree,
+    start: usize,
+    end: usize,
+}
+
+fn placeholder_source(pattern: &str) -> String {
+    let metavariable = Regex::new(r"\$\$\$([A-Z0-9_]*)|\$([A-Z_][A-Z0-9_]*)").unwrap();
+    metavariable
+        .replace_all(pattern, |caps: &regex::Captures| match (caps.get(1), caps.get(2)) {
+            (Some(name), _) => format!("{}{}", MULTI_PREFIX, name.as_str()),
+            (_, Some(name)) => format!("{}{}", SINGLE_PREFIX, name.as_str()),
+            _ => String::new(),
+        })
+        .to_string()
+}
+
+/// (before, after) wrappers that make a fragment valid where the language
+/// doesn't allow it at the top level, tried in order.
+fn pattern_contexts(language: &str) -> &'static [(&'static str, &'static str)] {
+    match language {
+        "rust" => &[("", ""), ("fn __pattern() { ", " }"), ("fn __pattern() { ", "; }")],
+        "go" => &[("package p\n", ""), ("package p\nfunc _() {\n", "\n}")],
+        "java" => &[("", ""), ("class _P {\n", "\n}"), ("class _P { void _p() {\n", ";\n} }")],
+        "c" | "cpp" => &[("", ""), ("void __pattern() {\n", ";\n}")],
+        _ => &[("", "")],
+    }
+}
+
+impl Pattern {
+    fn parse(parser: &ParserState, language: &str, pattern: &str) -> Result<Self, String> {
+        let code = placeholder_source(pattern.trim());
+        for (before, after) in pattern_contexts(language) {
+            let source = format!("{}{}{}", before, code, after);
+            let start = before.len();
+            let end = start + code.len();
+            let Some(tree) = parser.parse_as(language, &source) else {
+                return Err(format!("Unsupported language: {}", language));
+            };
+            let parsed = Pattern {
+                source,
+                tree,
+                start,
+                end,
+            };
+            if parsed.root().is_some_and(|root| !root.has_error()) {
+                return Ok(parsed);
+            }
+        }
+        Err(format!("Pattern is not valid {} code", language))
+    }
+
+    /// The smallest node spanning exactly the pattern.
+    fn root(&self) -> Option<Node> {
+        let node = self.tree.root_node().named_descendant_for_byte_range(self.start, self.end)?;
+        (node.start_byte() == self.start && node.end_byte() == self.end).then_some(node)
+    }
+
+    /// (is multi, name) if `node` is a metavariable placeholder.
+    fn metavariable(&self, node: Node) -> Option<(bool, &str)> {
+        let text = node_text(node, self.source.as_bytes());
+        let (multi, name) = match text.strip_prefix(MULTI_PREFIX) {
+            Some(name) => (true, name),
+            None => (false, text.strip_prefix(SINGLE_PREFIX)?),
+        };
+        name.chars()
+            .all(|c| c.is_ascii_uppercase() || c.is_ascii_digit() || c == '_')
+            .then_some((multi, name))
+    }
+}
+
+fn bind(captures: &mut BTreeMap<String, String>, name: &str, text: &str) -> bool {
+    if name.is_empty() || name == "_" {
+        return true;
+    }
+    match captures.get(name) {
+        Some(existing) => existing == text,
+        None => {
+            captures.insert(name.to_string(), text.to_string());
+            true
+        }
+    }
+}
+
+fn significant_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.children(&mut cursor).filter(|c| !c.kind().contains("comment")).collect()
+}
+
+struct Matcher<'a> {
+    pattern: &'a Pattern,
+    source: &'a [u8],
+}
+
+impl Matcher<'_> {
+    fn match_node(&self, p: Node, t: Node, captures: &mut BTreeMap<String, String>) -> bool {
+        if let Some((false, name)) = self.pattern.metavariable(p) {
+            return t.is_named() && bind(captures, name, node_text(t, self.source));
+        }
+        if p.kind() != t.kind() {
+            return false;
+        }
+        if p.child_count() == 0 || t.child_count() == 0 {
+            return p.child_count() == t.child_count()
+                && node_text(p, self.pattern.source.as_bytes()) == node_text(t, self.source);
+        }
+        self.match_sequence(&significant_children(p), &significant_children(t), captures)
+    }
+
+    fn match_sequence(&self, ps: &[Node], ts: &[Node], captures: &mut BTreeMap<String, String>) -> bool {
+        let Some((&first, rest)) = ps.split_first() else {
+            return ts.is_empty();
+        };
+
+        if let Some((true, name)) = self.pattern.metavariable(first) {
+            // Shortest run first, backtracking on failure
+            for k in 0..=ts.len() {
+                let text = match (ts.first(), k) {
+                    (Some(head), k) if k > 0 => {
+                        let bytes = &self.source[head.start_byte()..ts[k - 1].end_byte()];
+                        String::from_utf8_lossy(bytes).to_string()
+                    }
+                    _ => String::new(),
+                };
+                let mut trial = captures.clone();
+                if bind(&mut trial, name, &text) && self.match_sequence(rest, &ts[k..], &mut trial) {
+                    *captures = trial;
+                    return true;
+                }
+            }
+            return false;
+        }
+
+        match ts.split_first() {
+            Some((&head, tail)) => self.match_node(first, head, captures) && self.match_sequence(rest, tail, captures),
+            None => false,
+        }
+    }
+
+    /// Matches in the tree below `node`, outermost first; a match's own
+    /// descendants aren't searched again.
+    fn find_all<'t>(&self, node: Node<'t>, root: Node, out: &mut Vec<(Node<'t>, BTreeMap<String, String>)>, limit: usize) {
+        let mut stack = vec![node];
+        while let Some(node) = stack.pop() {
+            if out.len() >= limit {
+                return;
+            }
+            let candidate = self.pattern.metavariable(root).is_some() || node.kind() == root.kind();
+            let mut captures = BTreeMap::new();
+            if candidate && self.match_node(root, node, &mut captures) {
+                out.push((node, captures));
+                continue;
+            }
+            let mut cursor = node.walk();
+            let children: Vec<Node> = node.named_children(&mut cursor).collect();
+            stack.extend(children.into_iter().rev());
+        }
+    }
+}
+
+/// Parser languages searched for a pattern language; TypeScript patterns
+/// also cover .tsx files.
+fn target_languages(language: &str) -> Vec<&str> {
+    match language {
+        "typescript" => vec!["typescript", "tsx"],
+        other => vec![other],
+    }
+}
+
+fn search_files(
+    parser: &ParserState,
+    root: &Path,
+    pattern: &str,
+    language: &str,
+    limit: usize,
+) -> Result<StructuralSearchResult, String> {
+    let languages = target_languages(language);
+    let mut patterns: HashMap<String, Pattern> = HashMap::new();
+    for target in &languages {
+        patterns.insert(target.to_string(), Pattern::parse(parser, target, pattern)?);
+    }
+
+    let mut files: Vec<PathBuf> = walk_entries(root, &DirectoryOptions::default(), None)?
+        .into_iter()
+        .filter(|(_, is_dir)| !is_dir)
+        .map(|(path, _)| path)
+        .collect();
+    files.sort();
+
+    let mut result = StructuralSearchResult {
+        matches: Vec::new(),
+        files_searched: 0,
+        truncated: false,
+    };
+    for path in files {
+        let path_str = path.to_string_lossy().to_string();
+        let Some(pattern) = parser.detect_language(&path_str).and_then(|l| patterns.get(&l)) else {
+            continue;
+        };
+        let Ok((content, _)) = read_text(&path) else {
+            continue;
+        };
+        let Some((_, tree)) = parser.parse_tree(&path_str, &content) else {
+            continue;
+        };
+        let Some(pattern_root) = pattern.root() else {
+            continue;
+        };
+        result.files_searched += 1;
+
+        let matcher = Matcher {
+            pattern,
+            source: content.as_bytes(),
+        };
+        let remaining = limit - result.matches.len();
+        let mut found = Vec::new();
+        matcher.find_all(tree.root_node(), pattern_root, &mut found, remaining + 1);
+        if found.len() > remaining {
+            found.truncate(remaining);
+            result.truncated = true;
+        }
+
+        let lines = LineIndex::new(&content);
+        let relative = relative_path(root, &path).unwrap_or_default();
+        for (node, captures) in found {
+            result.matches.push(StructuralMatch {
+                path: path_str.clone(),
+                relative_path: relative.clone(),
+                range: lines.range(node),
+                text: node_text(node, content.as_bytes()).chars().take(MAX_MATCH_TEXT).collect(),
+                captures,
+            });
+        }
+        if result.truncated {
+            break;
+        }
+    }
+    Ok(result)
+}
+
+// ============================================================================
+// STRUCTURAL SEARCH TAURI COMMANDS
+// ============================================================================
+
+/// Finds code under `root` shaped like `pattern`, e.g. `console.log($A)`,
+/// by comparing syntax trees rather than text: whitespace, comments and
+/// formatting don't matter, and `$NAME` metavariables capture what they
+/// match. Repeating a metavariable requires the same source each time.
+#[tauri::command]
+pub async fn structural_search(
+    app: AppHandle,
+    root: String,
+    pattern: String,
+    language: String,
+    limit: Option<usize>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<StructuralSearchResult, String> {
+    let root_path = workspace.check(&root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+    tokio::task::spawn_blocking(move || {
+        let parser = app.state::<ParserState>();
+        search_files(
+            &parser,
+            &root_path,
+            &pattern,
+            &language.to_lowercase(),
+            limit.unwrap_or(DEFAULT_LIMIT).max(1),
+        )
+    })
+    .await
+    .map_err(|e| format!("Structural search failed: {}", e))?
+}
@@ -0,0 +1,759 @@
+use neo4rs::query;
+use regex::Regex;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+use crate::Neo4jState;
+
+const OSV_API: &str = "https://api.osv.dev/v1";
+/// The most queries OSV accepts in one batch.
+const OSV_BATCH_SIZE: usize = 1000;
+const OSV_TIMEOUT_SECS: u64 = 30;
+/// Cached results younger than this are used without asking OSV again.
+const CACHE_TTL_SECS: i64 = 24 * 60 * 60;
+/// Vulnerability records fetched at once.
+const VULN_FETCH_CONCURRENCY: usize = 16;
+const LICENSE_FILES: &[&str] = &["LICENSE", "LICENSE.md", "LICENSE.txt", "LICENCE", "COPYING"];
+/// (SPDX identifier, phrases that identify the license text), most specific
+/// first.
+const LICENSE_TEXTS: &[(&str, &[&str])] = &[
+    ("Apache-2.0", &["Apache License", "Version 2.0"]),
+    ("MPL-2.0", &["Mozilla Public License", "2.0"]),
+    ("LGPL-3.0", &["GNU LESSER GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("LGPL-2.1", &["GNU LESSER GENERAL PUBLIC LICENSE"]),
+    ("AGPL-3.0", &["GNU AFFERO GENERAL PUBLIC LICENSE"]),
+    ("GPL-3.0", &["GNU GENERAL PUBLIC LICENSE", "Version 3"]),
+    ("GPL-2.0", &["GNU GENERAL PUBLIC LICENSE", "Version 2"]),
+    ("MIT", &["Permission is hereby granted, free of charge"]),
+    ("ISC", &["Permission to use, copy, modify, and/or distribute this software for any purpose"]),
+    ("BSD-3-Clause", &["Redistribution and use in source and binary forms", "Neither the name"]),
+    ("BSD-2-Clause", &["Redistribution and use in source and binary forms"]),
+    ("Unlicense", &["This is free and unencumbered software released into the public domain"]),
+];
+
+// ============================================================================
+// AUDIT STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct AuditOptions {
+    /// Use only the cache, never the OSV API.
+    pub offline: bool,
+    /// Store results on EXTERNAL_PACKAGE nodes of the code graph.
+    pub annotate_graph: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Vulnerability {
+    /// The OSV id, e.g. "GHSA-..." or "RUSTSEC-...".
+    pub id: String,
+    pub aliases: Vec<String>,
+    pub summary: Option<String>,
+    /// The advisory's rating ("CRITICAL", "HIGH", ...) or else its CVSS
+    /// vector.
+    pub severity: Option<String>,
+    /// Versions of this package that fix it.
+    pub fixed_in: Vec<String>,
+    pub url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DependencyAudit {
+    /// OSV ecosystem: "npm", "crates.io" or "Go".
+    pub ecosystem: String,
+    pub name: String,
+    pub version: String,
+    pub lockfile: String,
+    /// A development-only dependency, where the lockfile says so.
+    pub dev: bool,
+    /// SPDX expression from the lockfile or the locally installed package.
+    pub license: Option<String>,
+    /// False when OSV couldn't be reached and nothing was cached.
+    pub checked: bool,
+    pub vulnerabilities: Vec<Vulnerability>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AuditReport {
+    pub lockfiles: Vec<String>,
+    pub packages: Vec<DependencyAudit>,
+    pub vulnerable_packages: usize,
+    pub vulnerabilities: usize,
+    /// SPDX expression -> number of packages under it.
+    pub licenses: BTreeMap<String, usize>,
+    /// Whether some results are older cached ones because OSV was skipped
+    /// or unreachable.
+    pub stale: bool,
+    /// EXTERNAL_PACKAGE nodes written, with `annotate_graph`.
+    pub annotated_nodes: Option<usize>,
+    pub warnings: Vec<String>,
+}
+
+struct LockedPackage {
+    ecosystem: &'static str,
+    name: String,
+    version: String,
+    lockfile: PathBuf,
+    dev: bool,
+    license: Option<String>,
+}
+
+/// OSV results cached by package version and vulnerability id, so audits
+/// work offline and only changed advisories are downloaded again.
+pub struct AuditState {
+    conn: Mutex<Connection>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+impl AuditState {
+    pub fn open(db_path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(db_path).map_err(|e| format!("Failed to open audit cache: {}", e))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS package_vulns (
+                 ecosystem TEXT NOT NULL,
+                 name TEXT NOT NULL,
+                 version TEXT NOT NULL,
+                 vulns TEXT NOT NULL,
+                 fetched_at INTEGER NOT NULL,
+                 PRIMARY KEY (ecosystem, name, version)
+             );
+             CREATE TABLE IF NOT EXISTS vulns (
+                 id TEXT PRIMARY KEY,
+                 modified TEXT NOT NULL,
+                 record TEXT NOT NULL
+             );",
+        )
+        .map_err(|e| format!("Failed to initialize audit cache: {}", e))?;
+
+        Ok(AuditState {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Cached (id, modified) pairs of a package version and when they were
+    /// fetched.
+    fn cached_package(&self, key: &PackageKey) -> Option<(Vec<(String, String)>, i64)> {
+        let conn = self.conn.lock_or_recover();
+        conn.query_row(
+            "SELECT vulns, fetched_at FROM package_vulns WHERE ecosystem = ?1 AND name = ?2 AND version = ?3",
+            params![key.0, key.1, key.2],
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?)),
+        )
+        .optional()
+        .ok()
+        .flatten()
+        .and_then(|(vulns, fetched_at)| Some((serde_json::from_str(&vulns).ok()?, fetched_at)))
+    }
+
+    fn store_package(&self, key: &PackageKey, vulns: &[(String, String)]) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        conn.execute(
+            "INSERT OR REPLACE INTO package_vulns (ecosystem, name, version, vulns, fetched_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![key.0, key.1, key.2, json!(vulns).to_string(), now_secs()],
+        )
+        .map_err(|e| format!("Failed to cache audit result: {}", e))?;
+        Ok(())
+    }
+
+    fn cached_vuln(&self, id: &str) -> Option<(String, Value)> {
+        let conn = self.conn.lock_or_recover();
+        conn.query_row("SELECT modified, record FROM vulns WHERE id = ?1", params![id], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })
+        .optional()
+        .ok()
+        .flatten()
+        .and_then(|(modified, record)| Some((modified, serde_json::from_str(&record).ok()?)))
+    }
+
+    fn store_vuln(&self, id: &str, modified: &str, record: &Value) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        conn.execute(
+            "INSERT OR REPLACE INTO vulns (id, modified, record) VALUES (?1, ?2, ?3)",
+            params![id, modified, record.to_string()],
+        )
+        .map_err(|e| format!("Failed to cache vulnerability {}: {}", id, e))?;
+        Ok(())
+    }
+}
+
+/// (ecosystem, name, version) as OSV is queried.
+type PackageKey = (String, String, String);
+
+// ============================================================================
+// LOCKFILES
+// ============================================================================
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME")
+        .or_else(|| std::env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+/// The `license` of a package.json; old packages use a `licenses` array.
+fn manifest_license(manifest: &Value) -> Option<String> {
+    match manifest.get("license") {
+        Some(Value::String(license)) => Some(license.clone()),
+        Some(license) => license.get("type").and_then(|t| t.as_str()).map(|t| t.to_string()),
+        None => {
+            let types: Vec<&str> = manifest
+                .get("licenses")?
+                .as_array()?
+                .iter()
+                .filter_map(|l| l.get("type").and_then(|t| t.as_str()))
+                .collect();
+            (!types.is_empty()).then(|| types.join(" OR "))
+        }
+    }
+}
+
+fn installed_npm_license(package_dir: &Path) -> Option<String> {
+    let content = std_fs::read_to_string(package_dir.join("package.json")).ok()?;
+    manifest_license(&serde_json::from_str(&content).ok()?)
+}
+
+/// Git, file and link dependencies have no registry version to look up.
+fn is_registry_version(version: &str) -> bool {
+    !version.is_empty() && !version.contains(':') && !version.contains('/')
+}
+
+fn parse_package_lock(path: &Path, content: &str) -> Result<Vec<LockedPackage>, String> {
+    let lock: Value = serde_json::from_str(content).map_err(|e| format!("Failed to parse {}: {}", path.display(), e))?;
+    let dir = path.parent().unwrap_or(path);
+    let mut packages = Vec::new();
+
+    // lockfileVersion 2 and 3: flat "node_modules/<name>" keys
+    if let Some(entries) = lock.get("packages").and_then(|p| p.as_object()) {
+        for (key, entry) in entries {
+            let Some((_, name)) = key.rsplit_once("node_modules/") else {
+                continue; // The root project and workspace members
+            };
+            let version = entry.get("version").and_then(|v| v.as_str()).unwrap_or_default();
+            if entry.get("link").and_then(|l| l.as_bool()) == Some(true) || !is_registry_version(version) {
+                continue;
+            }
+            let name = entry.get("name").and_then(|n| n.as_str()).unwrap_or(name);
+            packages.push(LockedPackage {
+                ecosystem: "npm",
+                name: name.to_string(),
+                version: version.to_string(),
+                lockfile: path.to_path_buf(),
+                dev: entry.get("dev").and_then(|d| d.as_bool()).unwrap_or(false),
+                license: manifest_license(entry).or_else(|| installed_npm_license(&dir.join(key))),
+            });
+        }
+        return Ok(packages);
+    }
+
+    // lockfileVersion 1: nested "dependencies"
+    let mut stack: Vec<&Value> = lock.get("dependencies").into_iter().collect();
+    while let Some(dependencies) = stack.pop() {
+        let Some(dependencies) = dependencies.as_object() else {
+            continue;
+        };
+        for (name, entry) in dependencies {
+            let version = entry.get("version").and_then(|v| v.as_str()).unwrap_or_default();
+            if is_registry_version(version) {
+                packages.push(LockedPackage {
+                    ecosystem: "npm",
+                    name: name.clone(),
+                    version: version.to_string(),
+                    lockfile: path.to_path_buf(),
+                    dev: entry.get("dev").and_then(|d| d.as_bool()).unwrap_or(false),
+                    license: installed_npm_license(&dir.join("node_modules").join(name)),
+                });
+            }
+            stack.extend(entry.get("dependencies"));
+        }
+    }
+    Ok(packages)
+}
+
+/// Cargo.lock records no licenses; the registry checkout under
+/// `$CARGO_HOME/registry/src` has the manifests.
+fn cargo_license(registries: &[PathBuf], name: &str, version: &str) -> Option<String> {
+    let license = Regex::new(r#"(?m)^\s*license\s*=\s*"([^"]+)""#).unwrap();
+    registries.iter().find_map(|registry| {
+        let manifest = std_fs::read_to_string(registry.join(format!("{}-{}", name, version)).join("Cargo.toml")).ok()?;
+        license.captures(&manifest).map(|c| c[1].to_string())
+    })
+}
+
+fn cargo_registries() -> Vec<PathBuf> {
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home_dir().map(|home| home.join(".cargo")));
+    cargo_home
+        .and_then(|home| std_fs::read_dir(home.join("registry").join("src")).ok())
+        .map(|entries| entries.flatten().map(|e| e.path()).collect())
+        .unwrap_or_default()
+}
+
+fn parse_cargo_lock(path: &Path, content: &str) -> Vec<LockedPackage> {
+    let field = Regex::new(r#"^(\w+)\s*=\s*"([^"]*)""#).unwrap();
+    let registries = cargo_registries();
+    let mut packages = Vec::new();
+    let mut current: HashMap<String, String> = HashMap::new();
+
+    let mut flush = |current: &mut HashMap<String, String>| {
+        // Workspace members and path dependencies have no source
+        let from_registry = current.get("source").is_some_and(|s| s.starts_with("registry+"));
+        if let (true, Some(name), Some(version)) = (from_registry, current.get("name"), current.get("version")) {
+            packages.push(LockedPackage {
+                ecosystem: "crates.io",
+                name: name.clone(),
+                version: version.clone(),
+                lockfile: path.to_path_buf(),
+                dev: false,
+                license: cargo_license(&registries, name, version),
+            });
+        }
+        current.clear();
+    };
+    for line in content.lines() {
+        let line = line.trim();
+        if line.starts_with('[') {
+            flush(&mut current);
+        } else if let Some(c) = field.captures(line) {
+            current.insert(c[1].to_string(), c[2].to_string());
+        }
+    }
+    flush(&mut current);
+    packages
+}
+
+/// The module cache spells capitals as "!" plus the lowercase letter.
+fn escape_module_path(module: &str) -> String {
+    module
+        .chars()
+        .map(|c| {
+            if c.is_ascii_uppercase() {
+                format!("!{}", c.to_ascii_lowercase())
+            } else {
+                c.to_string()
+            }
+        })
+        .collect()
+}
+
+/// Identifies the license text of a module in the Go module cache.
+fn go_license(module_cache: Option<&Path>, module: &str, version: &str) -> Option<String> {
+    let dir = module_cache?.join(format!("{}@{}", escape_module_path(module), version));
+    let text = LICENSE_FILES.iter().find_map(|name| std_fs::read_to_string(dir.join(name)).ok())?;
+    LICENSE_TEXTS
+        .iter()
+        .find(|(_, phrases)| phrases.iter().all(|phrase| text.contains(phrase)))
+        .map(|(id, _)| id.to_string())
+}
+
+fn go_module_cache() -> Option<PathBuf> {
+    if let Some(cache) = std::env::var_os("GOMODCACHE") {
+        return Some(PathBuf::from(cache));
+    }
+    let gopath = std::env::var_os("GOPATH")
+        .map(|p| std::env::split_paths(&p).next().unwrap_or_default())
+        .or_else(|| home_dir().map(|home| home.join("go")))?;
+    Some(gopath.join("pkg").join("mod"))
+}
+
+/// Modules with a full content hash in go.sum; lines with only a
+/// `/go.mod` hash are used for version selection and never built.
+fn parse_go_sum(path: &Path, content: &str) -> Vec<LockedPackage> {
+    let module_cache = go_module_cache();
+    let mut seen = BTreeSet::new();
+    let mut packages = Vec::new();
+    for line in content.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some(module), Some(version)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        if version.ends_with("/go.mod") || !seen.insert((module, version)) {
+            continue;
+        }
+        packages.push(LockedPackage {
+            ecosystem: "Go",
+            name: module.to_string(),
+            // OSV lists Go versions without the "v"
+            version: version.trim_start_matches('v').to_string(),
+            lockfile: path.to_path_buf(),
+            dev: false,
+            license: go_license(module_cache.as_deref(), module, version),
+        });
+    }
+    packages
+}
+
+fn collect_packages(root: &Path, warnings: &mut Vec<String>) -> Result<(Vec<PathBuf>, Vec<LockedPackage>), String> {
+    let mut lockfiles = Vec::new();
+    let mut packages = Vec::new();
+    for (path, is_dir) in walk_entries(root, &DirectoryOptions::default(), None)? {
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if is_dir || !matches!(name.as_str(), "package-lock.json" | "Cargo.lock" | "go.sum") {
+            continue;
+        }
+        let content = match std_fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) => {
+                warnings.push(format!("Failed to read {}: {}", path.display(), e));
+                continue;
+            }
+        };
+        let parsed = match name.as_str() {
+            "package-lock.json" => parse_package_lock(&path, &content),
+            "Cargo.lock" => Ok(parse_cargo_lock(&path, &content)),
+            _ => Ok(parse_go_sum(&path, &content)),
+        };
+        match parsed {
+            Ok(parsed) => packages.extend(parsed),
+            Err(e) => warnings.push(e),
+        }
+        lockfiles.push(path);
+    }
+    Ok((lockfiles, packages))
+}
+
+// ============================================================================
+// OSV
+// ============================================================================
+
+/// Asks OSV which vulnerabilities affect each package version; (id,
+/// modified) pairs in query order.
+async fn query_osv(client: &reqwest::Client, keys: &[PackageKey]) -> Result<Vec<Vec<(String, String)>>, String> {
+    let mut results = Vec::with_capacity(keys.len());
+    for chunk in keys.chunks(OSV_BATCH_SIZE) {
+        let queries: Vec<Value> = chunk
+            .iter()
+            .map(|(ecosystem, name, version)| {
+                json!({ "package": { "ecosystem": ecosystem, "name": name }, "version": version })
+            })
+            .collect();
+        let response: Value = client
+            .post(format!("{}/querybatch", OSV_API))
+            .json(&json!({ "queries": queries }))
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| format!("Failed to query OSV: {}", e))?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse OSV response: {}", e))?;
+
+        let batch = response.get("results").and_then(|r| r.as_array()).cloned().unwrap_or_default();
+        if batch.len() != chunk.len() {
+            return Err(format!("OSV answered {} of {} queries", batch.len(), chunk.len()));
+        }
+        for result in batch {
+            let vulns = result
+                .get("vulns")
+                .and_then(|v| v.as_array())
+                .map(|vulns| {
+                    vulns
+                        .iter()
+                        .filter_map(|v| {
+                            let id = v.get("id")?.as_str()?.to_string();
+                            let modified = v.get("modified").and_then(|m| m.as_str()).unwrap_or_default();
+                            Some((id, modified.to_string()))
+                        })
+                        .collect()
+                })
+                .unwrap_or_default();
+            results.push(vulns);
+        }
+    }
+    Ok(results)
+}
+
+async fn fetch_vuln(client: &reqwest::Client, id: &str) -> Result<Value, String> {
+    client
+        .get(format!("{}/vulns/{}", OSV_API, id))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| format!("Failed to fetch {}: {}", id, e))?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse {}: {}", id, e))
+}
+
+fn vulnerability(record: &Value, ecosystem: &str, name: &str) -> Vulnerability {
+    let id = record.get("id").and_then(|i| i.as_str()).unwrap_or_default().to_string();
+    let strings = |value: Option<&Value>| -> Vec<String> {
+        value
+            .and_then(|v| v.as_array())
+            .map(|items| items.iter().filter_map(|i| i.as_str().map(|s| s.to_string())).collect())
+            .unwrap_or_default()
+    };
+    let severity = record
+        .pointer("/database_specific/severity")
+        .and_then(|s| s.as_str())
+        .or_else(|| record.pointer("/severity/0/score").and_then(|s| s.as_str()))
+        .map(|s| s.to_string());
+
+    let mut fixed_in = Vec::new();
+    for affected in record.get("affected").and_then(|a| a.as_array()).into_iter().flatten() {
+        let package = affected.get("package");
+        let matches = package.and_then(|p| p.get("name")).and_then(|n| n.as_str()) == Some(name)
+            && package.and_then(|p| p.get("ecosystem")).and_then(|e| e.as_str()) == Some(ecosystem);
+        if !matches {
+            continue;
+        }
+        for range in affected.get("ranges").and_then(|r| r.as_array()).into_iter().flatten() {
+            for event in range.get("events").and_then(|e| e.as_array()).into_iter().flatten() {
+                if let Some(fixed) = event.get("fixed").and_then(|f| f.as_str()) {
+                    if !fixed_in.iter().any(|f| f == fixed) {
+                        fixed_in.push(fixed.to_string());
+                    }
+                }
+            }
+        }
+    }
+
+    Vulnerability {
+        url: format!("https://osv.dev/vulnerability/{}", id),
+        id,
+        aliases: strings(record.get("aliases")),
+        summary: record.get("summary").and_then(|s| s.as_str()).map(|s| s.to_string()),
+        severity,
+        fixed_in,
+    }
+}
+
+/// Vulnerability ids per package version: fresh cache entries as they are,
+/// the rest from OSV, falling back to stale cache entries when offline.
+async fn package_vulns(
+    state: &AuditState,
+    client: &reqwest::Client,
+    keys: &[PackageKey],
+    offline: bool,
+    stale: &mut bool,
+    warnings: &mut Vec<String>,
+) -> HashMap<PackageKey, Vec<(String, String)>> {
+    let now = now_secs();
+    let mut found = HashMap::new();
+    let mut cached_stale = HashMap::new();
+    let mut missing = Vec::new();
+    for key in keys {
+        match state.cached_package(key) {
+            Some((vulns, fetched_at)) if now - fetched_at < CACHE_TTL_SECS => {
+                found.insert(key.clone(), vulns);
+            }
+            Some((vulns, _)) => {
+                cached_stale.insert(key.clone(), vulns);
+                missing.push(key.clone());
+            }
+            None => missing.push(key.clone()),
+        }
+    }
+    if missing.is_empty() {
+        return found;
+    }
+
+    let queried = if offline {
+        Err("offline".to_string())
+    } else {
+        query_osv(client, &missing).await
+    };
+    match queried {
+        Ok(results) => {
+            for (key, vulns) in missing.into_iter().zip(results) {
+                if let Err(e) = state.store_package(&key, &vulns) {
+                    warnings.push(e);
+                }
+                found.insert(key, vulns);
+            }
+        }
+        Err(e) => {
+            if !offline {
+                warnings.push(format!("{}; using cached results", e));
+            }
+            *stale = true;
+            found.extend(cached_stale);
+        }
+    }
+    found
+}
+
+/// Full records of the given vulnerabilities, downloading those that are
+/// missing from the cache or changed since.
+async fn vuln_records(
+    state: &AuditState,
+    client: &reqwest::Client,
+    ids: &BTreeMap<String, String>,
+    offline: bool,
+    warnings: &mut Vec<String>,
+) -> HashMap<String, Value> {
+    let mut records = HashMap::new();
+    let mut outdated = Vec::new();
+    for (id, modified) in ids {
+        match state.cached_vuln(id) {
+            Some((cached_modified, record)) if cached_modified == *modified || offline => {
+                records.insert(id.clone(), record);
+            }
+            cached => {
+                if let Some((_, record)) = cached {
+                    records.insert(id.clone(), record);
+                }
+                outdated.push((id.clone(), modified.clone()));
+            }
+        }
+    }
+
+    for chunk in outdated.chunks(VULN_FETCH_CONCURRENCY) {
+        let fetched = futures::future::join_all(chunk.iter().map(|(id, _)| fetch_vuln(client, id))).await;
+        for ((id, modified), result) in chunk.iter().zip(fetched) {
+            match result {
+                Ok(record) => {
+                    if let Err(e) = state.store_vuln(id, modified, &record) {
+                        warnings.push(e);
+                    }
+                    records.insert(id.clone(), record);
+                }
+                Err(e) => warnings.push(e),
+            }
+        }
+    }
+    records
+}
+
+// ============================================================================
+// GRAPH ANNOTATION
+// ============================================================================
+
+/// One EXTERNAL_PACKAGE node per ecosystem and name, with every locked
+/// version and the vulnerabilities affecting any of them.
+async fn annotate_graph(neo4j: &Neo4jState, packages: &[DependencyAudit]) -> Result<usize, String> {
+    let graph = neo4j.get_graph().await?;
+    let mut nodes: BTreeMap<(&str, &str), (BTreeSet<&str>, BTreeSet<&str>, BTreeSet<&str>)> = BTreeMap::new();
+    for package in packages {
+        let (versions, licenses, vulns) = nodes.entry((package.ecosystem.as_str(), package.name.as_str())).or_default();
+        versions.insert(&package.version);
+        licenses.extend(package.license.as_deref());
+        vulns.extend(package.vulnerabilities.iter().map(|v| v.id.as_str()));
+    }
+
+    let list = |items: &BTreeSet<&str>| -> Vec<String> { items.iter().map(|i| i.to_string()).collect() };
+    for ((ecosystem, name), (versions, licenses, vulns)) in &nodes {
+        graph
+            .run(
+                query(
+                    "MERGE (p:EXTERNAL_PACKAGE {id: $id}) \
+                     SET p.name = $name, p.ecosystem = $ecosystem, p.versions = $versions, \
+                         p.licenses = $licenses, p.vulnerabilities = $vulns, p.vulnerable = $vulnerable",
+                )
+                .param("id", format!("package:{}:{}", ecosystem, name))
+                .param("name", name.to_string())
+                .param("ecosystem", ecosystem.to_string())
+                .param("versions", list(versions))
+                .param("licenses", list(licenses))
+                .param("vulns", list(vulns))
+                .param("vulnerable", !vulns.is_empty()),
+            )
+            .await
+            .map_err(|e| format!("Failed to store package {}: {}", name, e))?;
+    }
+    Ok(nodes.len())
+}
+
+// ============================================================================
+// AUDIT TAURI COMMANDS
+// ============================================================================
+
+/// Audits the packages pinned by package-lock.json, Cargo.lock and go.sum
+/// files under `root` against the OSV database and reports their licenses.
+/// OSV results are cached for a day and used offline.
+#[tauri::command]
+pub async fn audit_dependencies(
+    root: String,
+    options: Option<AuditOptions>,
+    state: State<'_, AuditState>,
+    neo4j: State<'_, Neo4jState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<AuditReport, String> {
+    let options = options.unwrap_or_default();
+    let root_path = workspace.check(&root)?;
+    let (lockfiles, locked, mut warnings) = tokio::task::spawn_blocking(move || {
+        let mut warnings = Vec::new();
+        collect_packages(&root_path, &mut warnings).map(|(lockfiles, packages)| (lockfiles, packages, warnings))
+    })
+    .await
+    .map_err(|e| format!("Failed to read lockfiles: {}", e))??;
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(OSV_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+    let keys: Vec<PackageKey> = locked
+        .iter()
+        .map(|p| (p.ecosystem.to_string(), p.name.clone(), p.version.clone()))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let mut stale = false;
+    let vulns_by_package = package_vulns(&state, &client, &keys, options.offline, &mut stale, &mut warnings).await;
+    let ids: BTreeMap<String, String> = vulns_by_package.values().flatten().cloned().collect();
+    let records = vuln_records(&state, &client, &ids, options.offline, &mut warnings).await;
+
+    let mut licenses: BTreeMap<String, usize> = BTreeMap::new();
+    let packages: Vec<DependencyAudit> = locked
+        .into_iter()
+        .map(|p| {
+            let key = (p.ecosystem.to_string(), p.name.clone(), p.version.clone());
+            let ids = vulns_by_package.get(&key);
+            let vulnerabilities = ids
+                .into_iter()
+                .flatten()
+                .map(|(id, _)| match records.get(id) {
+                    Some(record) => vulnerability(record, p.ecosystem, &p.name),
+                    None => vulnerability(&json!({ "id": id }), p.ecosystem, &p.name),
+                })
+                .collect();
+            *licenses.entry(p.license.clone().unwrap_or_else(|| "unknown".to_string())).or_default() += 1;
+            DependencyAudit {
+                ecosystem: p.ecosystem.to_string(),
+                name: p.name,
+                version: p.version,
+                lockfile: p.lockfile.to_string_lossy().to_string(),
+                dev: p.dev,
+                license: p.license,
+                checked: ids.is_some(),
+                vulnerabilities,
+            }
+        })
+        .collect();
+
+    let annotated_nodes = if options.annotate_graph {
+        Some(annotate_graph(&neo4j, &packages).await?)
+    } else {
+        None
+    };
+    let vulnerable: Vec<&DependencyAudit> = packages.iter().filter(|p| !p.vulnerabilities.is_empty()).collect();
+    Ok(AuditReport {
+        lockfiles: lockfiles.iter().map(|l| l.to_string_lossy().to_string()).collect(),
+        vulnerable_packages: vulnerable.len(),
+        vulnerabilities: vulnerable.iter().map(|p| p.vulnerabilities.len()).sum(),
+        packages,
+        licenses,
+        stale,
+        annotated_nodes,
+        warnings,
+    })
+}
@@ -0,0 +1,323 @@
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use tauri::{AppHandle, Manager, State};
+use tree_sitter::Node;
+
+use crate::files::{hash_bytes, read_text};
+use crate::sandbox::WorkspaceState;
+use crate::symbols::{defined_symbol, node_text};
+use crate::ParserState;
+
+const DEFAULT_MAX_TOKENS: usize = 400;
+
+/// Wrappers whose inner declaration decides what a top-level unit is.
+const WRAPPER_NODES: &[&str] = &["export_statement", "decorated_definition", "template_declaration"];
+
+// ============================================================================
+// CHUNK STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(default)]
+pub struct ChunkOptions {
+    /// Size cap per chunk, estimated at four characters per token.
+    pub max_tokens: usize,
+    /// Lines of the preceding code repeated at the start of each chunk.
+    pub overlap_lines: usize,
+    /// Prefix chunks cut out of a class or function with its signature.
+    pub include_headers: bool,
+}
+
+impl Default for ChunkOptions {
+    fn default() -> Self {
+        ChunkOptions {
+            max_tokens: DEFAULT_MAX_TOKENS,
+            overlap_lines: 0,
+            include_headers: true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CodeChunk {
+    pub index: usize,
+    /// 0-based, inclusive; overlap lines included.
+    pub start_line: usize,
+    pub end_line: usize,
+    /// Kind of the definition the chunk holds, "mixed" for several
+    /// top-level items and "lines" for plain line windows.
+    pub kind: String,
+    pub symbols: Vec<String>,
+    /// Signatures of the enclosing class/function, outermost first.
+    pub header: Option<String>,
+    pub text: String,
+    pub tokens: usize,
+    pub content_hash: String,
+}
+
+pub(crate) fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(4)
+}
+
+// ============================================================================
+// CHUNKING
+// ============================================================================
+
+/// Consecutive units waiting to be emitted as one chunk.
+struct Group {
+    start: usize,
+    end: usize,
+    kinds: Vec<String>,
+    symbols: Vec<String>,
+}
+
+struct Chunker<'a> {
+    lines: Vec<&'a str>,
+    source: &'a [u8],
+    language: &'a str,
+    options: &'a ChunkOptions,
+    chunks: Vec<CodeChunk>,
+}
+
+/// The node holding a definition's members or statements.
+fn body_of(node: Node) -> Option<Node> {
+    if let Some(body) = node.child_by_field_name("body") {
+        return Some(body);
+    }
+    ["declaration", "definition"]
+        .iter()
+        .find_map(|field| node.child_by_field_name(field))
+        .and_then(|inner| inner.child_by_field_name("body"))
+}
+
+impl Chunker<'_> {
+    fn line_text(&self, start: usize, end: usize) -> String {
+        self.lines[start..=end.min(self.lines.len() - 1)].join("\n")
+    }
+
+    fn tokens(&self, start: usize, end: usize) -> usize {
+        estimate_tokens(&self.line_text(start, end))
+    }
+
+    /// Kind and name of the definition `node` (or the declaration it
+    /// wraps) introduces.
+    fn describe(&self, node: Node) -> (String, Option<String>) {
+        let mut target = node;
+        if WRAPPER_NODES.contains(&node.kind()) {
+            let mut cursor = node.walk();
+            let inner = node.named_children(&mut cursor).find(|c| !c.kind().contains("comment") && c.kind() != "decorator");
+            target = inner.unwrap_or(node);
+        }
+        match defined_symbol(self.language, target, self.source) {
+            Some((kind, name)) => (kind.to_string(), Some(name.to_string())),
+            None => (target.kind().to_string(), None),
+        }
+    }
+
+    fn emit(&mut self, start: usize, end: usize, kind: String, symbols: Vec<String>, header: Option<&str>) {
+        let start = if self.chunks.is_empty() {
+            start
+        } else {
+            start.saturating_sub(self.options.overlap_lines)
+        };
+        let code = self.line_text(start, end);
+        if code.trim().is_empty() {
+            return;
+        }
+        let text = match header {
+            Some(header) if self.options.include_headers => format!("{}\n{}", header, code),
+            _ => code,
+        };
+        self.chunks.push(CodeChunk {
+            index: self.chunks.len(),
+            start_line: start,
+            end_line: end,
+            kind,
+            symbols,
+            header: header.map(|h| h.to_string()),
+            tokens: estimate_tokens(&text),
+            content_hash: hash_bytes(text.as_bytes()),
+            text,
+        });
+    }
+
+    fn flush(&mut self, group: &mut Option<Group>, header: Option<&str>) {
+        let Some(group) = group.take() else {
+            return;
+        };
+        let kind = match group.kinds.as_slice() {
+            [kind] => kind.clone(),
+            _ => "mixed".to_string(),
+        };
+        self.emit(group.start, group.end, kind, group.symbols, header);
+    }
+
+    /// Line windows for code too big to split by syntax.
+    fn split_lines(&mut self, start: usize, end: usize, header: Option<&str>) {
+        let mut window_start = start;
+        let mut tokens = 0;
+        for line in start..=end {
+            let line_tokens = estimate_tokens(self.lines.get(line).copied().unwrap_or_default()) + 1;
+            if tokens + line_tokens > self.options.max_tokens && line > window_start {
+                self.emit(window_start, line - 1, "lines".to_string(), Vec::new(), header);
+                window_start = line;
+                tokens = 0;
+            }
+            tokens += line_tokens;
+        }
+        self.emit(window_start, end, "lines".to_string(), Vec::new(), header);
+    }
+
+    /// Splits an oversized definition along its members or statements,
+    /// with its signature added to the header.
+    fn split_node(&mut self, node: Node, header: Option<&str>) {
+        let start = node.start_position().row;
+        let end = node.end_position().row;
+        let body = body_of(node).filter(|b| b.named_child_count() > 0);
+        let Some(body) = body else {
+            if node.named_child_count() > 1 {
+                let mut cursor = node.walk();
+                let children: Vec<Node> = node.named_children(&mut cursor).collect();
+                self.chunk_nodes(&children, header);
+            } else {
+                self.split_lines(start, end, header);
+            }
+            return;
+        };
+
+        let signature = node_text(node, self.source)
+            .get(..body.start_byte() - node.start_byte())
+            .unwrap_or_default()
+            .trim_end()
+            .to_string();
+        let nested = match header {
+            Some(header) => format!("{}\n{}", header, signature),
+            None => signature,
+        };
+        let mut cursor = body.walk();
+        let members: Vec<Node> = body.named_children(&mut cursor).collect();
+        self.chunk_nodes(&members, Some(&nested));
+    }
+
+    /// Packs sibling nodes into chunks under the size cap. Comments stay
+    /// with the code after them.
+    fn chunk_nodes(&mut self, nodes: &[Node], header: Option<&str>) {
+        let max = self.options.max_tokens;
+        let mut group: Option<Group> = None;
+        let mut comment_start: Option<usize> = None;
+
+        for node in nodes {
+            let start = node.start_position().row;
+            let end = node.end_position().row;
+            if node.kind().contains("comment") {
+                comment_start.get_or_insert(start);
+                continue;
+            }
+            let start = comment_start.take().unwrap_or(start);
+            let (kind, symbol) = self.describe(*node);
+
+            if self.tokens(start, end) > max {
+                self.flush(&mut group, header);
+                if start < node.start_position().row {
+                    // Leading comments of a split definition get their own chunk
+                    self.emit(start, node.start_position().row - 1, "comment".to_string(), Vec::new(), header);
+                }
+                self.split_node(*node, header);
+                continue;
+            }
+
+            if let Some(current) = &group {
+                if self.tokens(current.start, end) > max {
+                    self.flush(&mut group, header);
+                }
+            }
+            let current = group.get_or_insert_with(|| Group {
+                start,
+                end,
+                kinds: Vec::new(),
+                symbols: Vec::new(),
+            });
+            current.end = end;
+            if !current.kinds.contains(&kind) {
+                current.kinds.push(kind);
+            }
+            current.symbols.extend(symbol);
+        }
+
+        if let Some(start) = comment_start {
+            let end = nodes.last().map(|n| n.end_position().row).unwrap_or(start);
+            let current = group.get_or_insert_with(|| Group {
+                start,
+                end,
+                kinds: vec!["comment".to_string()],
+                symbols: Vec::new(),
+            });
+            current.end = end;
+        }
+        self.flush(&mut group, header);
+    }
+}
+
+/// Splits source into chunks along its syntax tree: whole functions and
+/// classes where they fit, their members or statements where they don't,
+/// and small neighbouring items packed together. Files without a parser
+/// are cut into line windows.
+pub(crate) fn chunk_source(parser: &ParserState, path: &str, content: &str, options: &ChunkOptions) -> Vec<CodeChunk> {
+    let options = ChunkOptions {
+        max_tokens: options.max_tokens.max(16),
+        ..options.clone()
+    };
+    let tree = parser.parse_tree(path, content);
+    let mut chunker = Chunker {
+        lines: content.split('\n').map(|l| l.strip_suffix('\r').unwrap_or(l)).collect(),
+        source: content.as_bytes(),
+        language: tree.as_ref().map(|(language, _)| language.as_str()).unwrap_or_default(),
+        options: &options,
+        chunks: Vec::new(),
+    };
+
+    match &tree {
+        Some((_, tree)) => {
+            let root = tree.root_node();
+            let mut cursor = root.walk();
+            let nodes: Vec<Node> = root.named_children(&mut cursor).collect();
+            chunker.chunk_nodes(&nodes, None);
+        }
+        None => {
+            let last = chunker.lines.len() - 1;
+            chunker.split_lines(0, last, None);
+        }
+    }
+    chunker.chunks
+}
+
+// ============================================================================
+// CHUNK TAURI COMMANDS
+// ============================================================================
+
+/// Semantic chunks of a source file for embedding and retrieval. Without
+/// `content`, the file is read from disk.
+#[tauri::command]
+pub async fn chunk_file(
+    app: AppHandle,
+    path: String,
+    content: Option<String>,
+    options: Option<ChunkOptions>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<CodeChunk>, String> {
+    if content.is_none() {
+        workspace.check(&path)?;
+    }
+    tokio::task::spawn_blocking(move || {
+        let content = match content {
+            Some(content) => content,
+            None => read_text(Path::new(&path))
+                .map(|(content, _)| content)
+                .map_err(|e| format!("Failed to read file: {}", e))?,
+        };
+        let parser = app.state::<ParserState>();
+        Ok::<_, String>(chunk_source(&parser, &path, &content, &options.unwrap_or_default()))
+    })
+    .await
+    .map_err(|e| format!("Chunking task failed: {}", e))?
+}
@@ -0,0 +1,628 @@
+use git2::Repository;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, UNIX_EPOCH};
+use tantivy::collector::{DocSetCollector, TopDocs};
+use tantivy::directory::MmapDirectory;
+use tantivy::query::{AllQuery, BooleanQuery, BoostQuery, Occur, Query, TermQuery};
+use tantivy::schema::{Field, IndexRecordOption, Schema, TextFieldIndexing, TextOptions, Value, STORED, STRING};
+use tantivy::tokenizer::{Token, TokenStream, Tokenizer};
+use tantivy::{Index, IndexReader, IndexWriter, ReloadPolicy, TantivyDocument, Term};
+use tauri::{AppHandle, State};
+
+use crate::event_bus::emit_indexing_finished;
+use crate::explorer::{language_for, walk_entries, DirectoryOptions};
+use crate::files::{hash_bytes, read_text};
+use crate::finder::{is_excluded, relative_path};
+use crate::git::path_ignored;
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+
+const CODE_TOKENIZER: &str = "code";
+const MAX_TOKEN_BYTES: usize = 64;
+const MAX_INDEXED_BYTES: u64 = 1024 * 1024;
+const WRITER_MEMORY_BYTES: usize = 50_000_000;
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(300);
+const DEFAULT_LIMIT: usize = 20;
+const MAX_SNIPPETS: usize = 3;
+const MAX_SNIPPET_CHARS: usize = 200;
+
+// ============================================================================
+// CODE TOKENIZER
+// ============================================================================
+
+/// Splits source into identifiers and indexes each one whole plus its
+/// camelCase/snake_case parts, so `getUserName` is found by `getusername`,
+/// `user` or `name`. Everything is lowercased.
+#[derive(Clone)]
+struct CodeTokenizer;
+
+struct CodeTokenStream {
+    tokens: Vec<Token>,
+    index: usize,
+}
+
+impl Tokenizer for CodeTokenizer {
+    type TokenStream<'a> = CodeTokenStream;
+
+    fn token_stream<'a>(&'a mut self, text: &'a str) -> CodeTokenStream {
+        CodeTokenStream {
+            tokens: code_tokens(text),
+            index: 0,
+        }
+    }
+}
+
+impl TokenStream for CodeTokenStream {
+    fn advance(&mut self) -> bool {
+        self.index += 1;
+        self.index <= self.tokens.len()
+    }
+
+    fn token(&self) -> &Token {
+        &self.tokens[self.index - 1]
+    }
+
+    fn token_mut(&mut self) -> &mut Token {
+        &mut self.tokens[self.index - 1]
+    }
+}
+
+/// Byte ranges of the words in an identifier: `_` separates words, and so
+/// do lower-to-upper humps, letter/digit switches and the end of an
+/// acronym (`HTTPServer` is `HTTP` + `Server`).
+fn split_identifier(word: &str) -> Vec<(usize, usize)> {
+    let chars: Vec<(usize, char)> = word.char_indices().collect();
+    let mut parts = Vec::new();
+    let mut start: Option<usize> = None;
+
+    for (i, &(offset, c)) in chars.iter().enumerate() {
+        if c == '_' {
+            if let Some(s) = start.take() {
+                parts.push((s, offset));
+            }
+            continue;
+        }
+        let prev = i.checked_sub(1).map(|p| chars[p].1).filter(|p| *p != '_');
+        let next = chars.get(i + 1).map(|(_, n)| *n);
+        let boundary = prev.is_some_and(|prev| {
+            (prev.is_lowercase() && c.is_uppercase())
+                || prev.is_alphabetic() != c.is_alphabetic()
+                || (prev.is_uppercase() && c.is_uppercase() && next.is_some_and(|n| n.is_lowercase()))
+        });
+        if boundary {
+            if let Some(s) = start.take() {
+                parts.push((s, offset));
+            }
+        }
+        start.get_or_insert(offset);
+    }
+    if let Some(s) = start {
+        parts.push((s, word.len()));
+    }
+    parts
+}
+
+fn code_tokens(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut push = |from: usize, to: usize| {
+        let word = &text[from..to];
+        if word.len() > MAX_TOKEN_BYTES || word.chars().all(|c| c == '_') {
+            return;
+        }
+        tokens.push(Token {
+            offset_from: from,
+            offset_to: to,
+            position: tokens.len(),
+            text: word.to_lowercase(),
+            position_length: 1,
+        });
+    };
+
+    let mut start = None;
+    for (offset, c) in text.char_indices().chain(std::iter::once((text.len(), ' '))) {
+        if c.is_alphanumeric() || c == '_' {
+            start.get_or_insert(offset);
+            continue;
+        }
+        let Some(from) = start.take() else {
+            continue;
+        };
+        push(from, offset);
+        let parts = split_identifier(&text[from..offset]);
+        if parts.len() > 1 {
+            for (part_from, part_to) in parts {
+                push(from + part_from, from + part_to);
+            }
+        }
+    }
+    tokens
+}
+
+/// Distinct lowercase search terms of `text`, split like indexed code.
+pub(crate) fn code_terms(text: &str) -> Vec<String> {
+    let mut seen = HashSet::new();
+    code_tokens(text)
+        .into_iter()
+        .map(|token| token.text)
+        .filter(|term| seen.insert(term.clone()))
+        .collect()
+}
+
+// ============================================================================
+// CODE INDEX
+// ============================================================================
+
+#[derive(Clone, Copy)]
+struct Fields {
+    path: Field,
+    name: Field,
+    dir: Field,
+    language: Field,
+    content: Field,
+    modified: Field,
+}
+
+fn build_schema() -> (Schema, Fields) {
+    let code = |stored: bool| {
+        let indexing = TextFieldIndexing::default()
+            .set_tokenizer(CODE_TOKENIZER)
+            .set_index_option(IndexRecordOption::WithFreqsAndPositions);
+        let options = TextOptions::default().set_indexing_options(indexing);
+        if stored {
+            options.set_stored()
+        } else {
+            options
+        }
+    };
+
+    let mut builder = Schema::builder();
+    let fields = Fields {
+        path: builder.add_text_field("path", STRING | STORED),
+        name: builder.add_text_field("name", code(false)),
+        // Every ancestor directory, so a subtree can be filtered or dropped
+        dir: builder.add_text_field("dir", STRING),
+        language: builder.add_text_field("language", STRING | STORED),
+        content: builder.add_text_field("content", code(true)),
+        modified: builder.add_u64_field("modified", STORED),
+    };
+    (builder.build(), fields)
+}
+
+/// Full-text index of one project's source files, stored on disk so it
+/// survives restarts and only changed files are reindexed.
+pub(crate) struct CodeIndex {
+    root: PathBuf,
+    fields: Fields,
+    reader: IndexReader,
+    writer: Mutex<IndexWriter>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexSummary {
+    pub root: String,
+    pub files: usize,
+    pub updated: usize,
+    pub removed: usize,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct IndexQueryFilters {
+    /// Lowercase parser language names, e.g. "rust" or "typescript".
+    pub languages: Vec<String>,
+    /// Directory relative to the project root to search within.
+    pub path: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexSnippet {
+    pub line: usize, // 1-based
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct IndexHit {
+    pub path: String,
+    pub relative_path: String,
+    pub language: String,
+    pub score: f32,
+    pub snippets: Vec<IndexSnippet>,
+}
+
+fn modified_nanos(metadata: &std_fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+/// Lowercase language name for indexing, or None for non-source files.
+fn index_language(path: &Path) -> Option<String> {
+    language_for(path).map(|(name, ..)| name.to_lowercase())
+}
+
+impl CodeIndex {
+    fn open(root: &Path, index_dir: &Path) -> Result<Self, String> {
+        std_fs::create_dir_all(index_dir).map_err(|e| format!("Failed to create index directory: {}", e))?;
+        let (schema, fields) = build_schema();
+        let open = || -> tantivy::Result<Index> { Index::open_or_create(MmapDirectory::open(index_dir)?, schema.clone()) };
+        let index = match open() {
+            Ok(index) => index,
+            Err(_) => {
+                // Written by an older schema: start over
+                std_fs::remove_dir_all(index_dir).map_err(|e| format!("Failed to reset index: {}", e))?;
+                std_fs::create_dir_all(index_dir).map_err(|e| format!("Failed to create index directory: {}", e))?;
+                open().map_err(|e| format!("Failed to open code index: {}", e))?
+            }
+        };
+        index.tokenizers().register(CODE_TOKENIZER, CodeTokenizer);
+
+        let writer = index
+            .writer(WRITER_MEMORY_BYTES)
+            .map_err(|e| format!("Failed to open code index for writing: {}", e))?;
+        let reader = index
+            .reader_builder()
+            .reload_policy(ReloadPolicy::Manual)
+            .try_into()
+            .map_err(|e| format!("Failed to open code index: {}", e))?;
+
+        Ok(CodeIndex {
+            root: root.to_path_buf(),
+            fields,
+            reader,
+            writer: Mutex::new(writer),
+        })
+    }
+
+    /// Modification time of every indexed file, by relative path.
+    fn indexed_files(&self) -> Result<HashMap<String, u64>, String> {
+        let searcher = self.reader.searcher();
+        let addresses = searcher.search(&AllQuery, &DocSetCollector).map_err(|e| e.to_string())?;
+        let mut files = HashMap::new();
+        for address in addresses {
+            let doc: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+            let path = doc.get_first(self.fields.path).and_then(|v| v.as_str());
+            let modified = doc.get_first(self.fields.modified).and_then(|v| v.as_u64());
+            if let (Some(path), Some(modified)) = (path, modified) {
+                files.insert(path.to_string(), modified);
+            }
+        }
+        Ok(files)
+    }
+
+    /// Replaces the document of one file. Returns false if the file isn't
+    /// indexable source, after removing any stale document.
+    fn index_file(&self, writer: &IndexWriter, relative: &str) -> Result<bool, String> {
+        writer.delete_term(Term::from_field_text(self.fields.path, relative));
+
+        let path = self.root.join(relative);
+        let Some(language) = index_language(&path) else {
+            return Ok(false);
+        };
+        let Ok(metadata) = std_fs::metadata(&path) else {
+            return Ok(false);
+        };
+        if !metadata.is_file() || metadata.len() > MAX_INDEXED_BYTES {
+            return Ok(false);
+        }
+        let Ok((content, _)) = read_text(&path) else {
+            return Ok(false);
+        };
+
+        let mut doc = TantivyDocument::default();
+        doc.add_text(self.fields.path, relative);
+        doc.add_text(self.fields.name, relative.rsplit('/').next().unwrap_or(relative));
+        let mut dir = relative;
+        while let Some((parent, _)) = dir.rsplit_once('/') {
+            doc.add_text(self.fields.dir, parent);
+            dir = parent;
+        }
+        doc.add_text(self.fields.language, &language);
+        doc.add_text(self.fields.content, &content);
+        doc.add_u64(self.fields.modified, modified_nanos(&metadata));
+        writer
+            .add_document(doc)
+            .map_err(|e| format!("Failed to index {}: {}", relative, e))?;
+        Ok(true)
+    }
+
+    fn commit(&self, writer: &mut IndexWriter) -> Result<(), String> {
+        writer.commit().map_err(|e| format!("Failed to commit code index: {}", e))?;
+        self.reader.reload().map_err(|e| format!("Failed to reload code index: {}", e))
+    }
+
+    /// Brings the index in line with the files on disk, calling
+    /// `on_file(done, total)` after each file; an error from it discards the
+    /// uncommitted changes.
+    fn sync(&self, mut on_file: impl FnMut(usize, usize) -> Result<(), String>) -> Result<IndexSummary, String> {
+        let mut on_disk = BTreeSet::new();
+        for (path, is_dir) in walk_entries(&self.root, &DirectoryOptions::default(), None)? {
+            if !is_dir && index_language(&path).is_some() {
+                if let Some(relative) = relative_path(&self.root, &path) {
+                    on_disk.insert(relative);
+                }
+            }
+        }
+
+        let mut indexed = self.indexed_files()?;
+        let mut writer = self.writer.lock_or_recover();
+        let mut summary = IndexSummary {
+            root: self.root.to_string_lossy().to_string(),
+            files: 0,
+            updated: 0,
+            removed: 0,
+        };
+
+        for (i, relative) in on_disk.iter().enumerate() {
+            let modified = std_fs::metadata(self.root.join(relative)).map(|m| modified_nanos(&m)).ok();
+            let stale = indexed.remove(relative) != modified;
+            if !stale {
+                summary.files += 1;
+            } else if self.index_file(&writer, relative)? {
+                summary.files += 1;
+                summary.updated += 1;
+            }
+            if let Err(e) = on_file(i + 1, on_disk.len()) {
+                let _ = writer.rollback();
+                return Err(e);
+            }
+        }
+        for relative in indexed.keys() {
+            writer.delete_term(Term::from_field_text(self.fields.path, relative));
+            summary.removed += 1;
+        }
+
+        self.commit(&mut writer)?;
+        Ok(summary)
+    }
+
+    /// Reindexes paths reported by the file watcher. Removed directories
+    /// take everything below them along.
+    fn apply_changes(&self, paths: &BTreeSet<PathBuf>, repo: Option<&Repository>) -> Result<(), String> {
+        let mut writer = self.writer.lock_or_recover();
+        for path in paths {
+            let Some(relative) = relative_path(&self.root, path) else {
+                continue;
+            };
+            if relative.is_empty() || is_excluded(&relative) || repo.is_some_and(|repo| path_ignored(repo, path)) {
+                continue;
+            }
+
+            if path.is_dir() {
+                for (child, is_dir) in walk_entries(path, &DirectoryOptions::default(), None)? {
+                    if let Some(child) = relative_path(&self.root, &child).filter(|_| !is_dir) {
+                        self.index_file(&writer, &child)?;
+                    }
+                }
+            } else if path.is_file() {
+                self.index_file(&writer, &relative)?;
+            } else {
+                writer.delete_term(Term::from_field_text(self.fields.path, &relative));
+                writer.delete_term(Term::from_field_text(self.fields.dir, &relative));
+            }
+        }
+        self.commit(&mut writer)
+    }
+
+    pub(crate) fn search(&self, text: &str, filters: &IndexQueryFilters) -> Result<Vec<IndexHit>, String> {
+        let terms = code_terms(text);
+        if terms.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut matches: Vec<(Occur, Box<dyn Query>)> = Vec::new();
+        for term in &terms {
+            for (field, boost) in [(self.fields.content, 1.0), (self.fields.name, 3.0)] {
+                let query = TermQuery::new(Term::from_field_text(field, term), IndexRecordOption::WithFreqs);
+                matches.push((Occur::Should, Box::new(BoostQuery::new(Box::new(query), boost))));
+            }
+        }
+        let mut clauses: Vec<(Occur, Box<dyn Query>)> = vec![(Occur::Must, Box::new(BooleanQuery::new(matches)))];
+
+        if !filters.languages.is_empty() {
+            let languages = filters
+                .languages
+                .iter()
+                .map(|language| {
+                    let term = Term::from_field_text(self.fields.language, &language.to_lowercase());
+                    let query: Box<dyn Query> = Box::new(TermQuery::new(term, IndexRecordOption::Basic));
+                    (Occur::Should, query)
+                })
+                .collect();
+            clauses.push((Occur::Must, Box::new(BooleanQuery::new(languages))));
+        }
+        if let Some(dir) = &filters.path {
+            let dir = dir.replace('\\', "/").trim_matches('/').to_string();
+            if !dir.is_empty() {
+                let term = Term::from_field_text(self.fields.dir, &dir);
+                clauses.push((Occur::Must, Box::new(TermQuery::new(term, IndexRecordOption::Basic))));
+            }
+        }
+
+        let searcher = self.reader.searcher();
+        let limit = filters.limit.unwrap_or(DEFAULT_LIMIT).max(1);
+        let top = searcher
+            .search(&BooleanQuery::new(clauses), &TopDocs::with_limit(limit))
+            .map_err(|e| format!("Index search failed: {}", e))?;
+
+        let mut hits = Vec::with_capacity(top.len());
+        for (score, address) in top {
+            let doc: TantivyDocument = searcher.doc(address).map_err(|e| e.to_string())?;
+            let field_text = |field| doc.get_first(field).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+            let relative = field_text(self.fields.path);
+            hits.push(IndexHit {
+                path: self.root.join(&relative).to_string_lossy().to_string(),
+                snippets: snippets(&field_text(self.fields.content), &terms),
+                relative_path: relative,
+                language: field_text(self.fields.language),
+                score,
+            });
+        }
+        Ok(hits)
+    }
+}
+
+/// The lines matching the most distinct query terms, in file order.
+fn snippets(content: &str, terms: &[String]) -> Vec<IndexSnippet> {
+    let mut scored: Vec<(usize, usize, &str)> = content
+        .lines()
+        .enumerate()
+        .filter_map(|(i, line)| {
+            let line_terms = code_terms(line);
+            let hits = terms.iter().filter(|t| line_terms.contains(t)).count();
+            (hits > 0).then_some((hits, i, line))
+        })
+        .collect();
+    scored.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+    scored.truncate(MAX_SNIPPETS);
+    scored.sort_by_key(|(_, i, _)| *i);
+
+    scored
+        .into_iter()
+        .map(|(_, i, line)| IndexSnippet {
+            line: i + 1,
+            text: line.trim().chars().take(MAX_SNIPPET_CHARS).collect(),
+        })
+        .collect()
+}
+
+// ============================================================================
+// CODE INDEX STATE
+// ============================================================================
+
+/// The open project's code index and the watcher keeping it current.
+pub struct CodeIndexState {
+    data_dir: PathBuf,
+    current: Mutex<Option<Arc<CodeIndex>>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+impl CodeIndexState {
+    pub fn new(data_dir: PathBuf) -> Self {
+        CodeIndexState {
+            data_dir,
+            current: Mutex::new(None),
+            watcher: Mutex::new(None),
+        }
+    }
+
+    pub(crate) fn current(&self) -> Result<Arc<CodeIndex>, String> {
+        self.current
+            .lock_or_recover()
+            .clone()
+            .ok_or_else(|| "No project indexed; call index_project first".to_string())
+    }
+}
+
+/// Feeds watcher events to a background thread that batches them for
+/// `WATCH_DEBOUNCE`, so a burst of saves costs one commit.
+fn watch_index(index: Arc<CodeIndex>) -> Result<RecommendedWatcher, String> {
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    let root = index.root.clone();
+    std::thread::spawn(move || {
+        let repo = Repository::discover(&index.root).ok();
+        while let Ok(first) = rx.recv() {
+            let mut changed = BTreeSet::from([first]);
+            while let Ok(path) = rx.recv_timeout(WATCH_DEBOUNCE) {
+                changed.insert(path);
+            }
+            if let Err(e) = index.apply_changes(&changed, repo.as_ref()) {
+                eprintln!("Code index update failed: {}", e);
+            }
+        }
+    });
+
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        if matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+            for path in event.paths {
+                let _ = tx.send(path);
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+    Ok(watcher)
+}
+
+// ============================================================================
+// CODE INDEX TAURI COMMANDS
+// ============================================================================
+
+/// Opens (or builds) the persistent code index for `root`, reindexes files
+/// changed since the last run and keeps it current with a file watcher.
+#[tauri::command]
+pub async fn index_project(
+    app: AppHandle,
+    root: String,
+    state: State<'_, CodeIndexState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<IndexSummary, String> {
+    index_project_with_progress(&app, &root, &state, &workspace, |_, _| Ok(())).await
+}
+
+/// `index_project`, calling `on_file(done, total)` after each file; an error
+/// from it stops indexing and leaves the index as it was.
+pub(crate) async fn index_project_with_progress(
+    app: &AppHandle,
+    root: &str,
+    state: &CodeIndexState,
+    workspace: &WorkspaceState,
+    on_file: impl FnMut(usize, usize) -> Result<(), String> + Send + 'static,
+) -> Result<IndexSummary, String> {
+    let root_path = workspace.check(root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+
+    // Stop watching the old project; reuse the index if it's the same one,
+    // since only one writer may hold an index directory
+    state.watcher.lock_or_recover().take();
+    let existing = state.current.lock_or_recover().clone().filter(|index| index.root == root_path);
+    let index_dir = state
+        .data_dir
+        .join(&hash_bytes(root_path.to_string_lossy().as_bytes())[..16]);
+
+    let (index, summary) = tokio::task::spawn_blocking(move || {
+        let index = match existing {
+            Some(index) => index,
+            None => Arc::new(CodeIndex::open(&root_path, &index_dir)?),
+        };
+        let summary = index.sync(on_file)?;
+        Ok::<_, String>((index, summary))
+    })
+    .await
+    .map_err(|e| format!("Indexing task failed: {}", e))??;
+
+    *state.current.lock_or_recover() = Some(index.clone());
+    *state.watcher.lock_or_recover() = Some(watch_index(index)?);
+    emit_indexing_finished(app, "code", &summary);
+    Ok(summary)
+}
+
+/// Ranked files matching `text`, with the most relevant lines as snippets.
+/// Terms are split like code, so `userName` also matches `user_name`.
+#[tauri::command]
+pub async fn query_index(
+    text: String,
+    filters: Option<IndexQueryFilters>,
+    state: State<'_, CodeIndexState>,
+) -> Result<Vec<IndexHit>, String> {
+    let index = state.current()?;
+    tokio::task::spawn_blocking(move || index.search(&text, &filters.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Search task failed: {}", e))?
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, State};
+use tokio::task::{self, AbortHandle};
+
+use crate::llm::{post_with_retry, LlmRequestRecord, LlmState, LoggedPrompt, RetryPolicy};
+use crate::locks::LockExt;
+
+// ============================================================================
+// COMPLETION STATE
+// ============================================================================
+
+/// Tracks the in-flight completion so a newer keystroke (or an explicit
+/// cancel) aborts the previous request instead of letting it finish.
+#[derive(Default)]
+pub struct CompletionState {
+    current: Mutex<Option<AbortHandle>>,
+}
+
+impl CompletionState {
+    fn replace(&self, handle: AbortHandle) {
+        let mut current = self.current.lock_or_recover();
+        if let Some(previous) = current.replace(handle) {
+            previous.abort();
+        }
+    }
+
+    fn cancel(&self) {
+        if let Some(handle) = self.current.lock_or_recover().take() {
+            handle.abort();
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+pub struct CodeCompletion {
+    pub text: String,
+    pub model: String,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaGenerateResponse {
+    #[serde(default)]
+    response: String,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+}
+
+// ============================================================================
+// FIM PROMPTS
+// ============================================================================
+
+/// Builds a raw fill-in-the-middle prompt for the model family, or `None` when
+/// the family is unknown and Ollama's own `suffix` handling should be used.
+fn fim_prompt(model: &str, prefix: &str, suffix: &str) -> Option<(String, Vec<&'static str>)> {
+    let model = model.to_lowercase();
+
+    if model.contains("codellama") {
+        Some((format!("<PRE> {} <SUF>{} <MID>", prefix, suffix), vec!["<EOT>"]))
+    } else if model.contains("deepseek-coder") {
+        Some((
+            format!("<｜fim▁begin｜>{}<｜fim▁hole｜>{}<｜fim▁end｜>", prefix, suffix),
+            vec!["<｜end▁of▁sentence｜>", "<|EOT|>"],
+        ))
+    } else if model.contains("qwen") || model.contains("codegemma") {
+        Some((
+            format!("<|fim_prefix|>{}<|fim_suffix|>{}<|fim_middle|>", prefix, suffix),
+            vec!["<|endoftext|>", "<|file_separator|>", "<|fim_pad|>"],
+        ))
+    } else if model.contains("starcoder") || model.contains("stable-code") {
+        Some((
+            format!("<fim_prefix>{}<fim_suffix>{}<fim_middle>", prefix, suffix),
+            vec!["<|endoftext|>", "<file_sep>"],
+        ))
+    } else {
+        None
+    }
+}
+
+fn comment_prefix(language: &str) -> &'static str {
+    match language {
+        "python" | "ruby" | "shell" | "bash" | "yaml" | "toml" => "#",
+        "sql" | "lua" | "haskell" => "--",
+        "html" | "xml" | "markdown" => "",
+        _ => "//",
+    }
+}
+
+async fn request_completion(
+    policy: RetryPolicy,
+    app: AppHandle,
+    model: String,
+    prefix: String,
+    suffix: String,
+    language: Option<String>,
+    max_tokens: u32,
+) -> Result<OllamaGenerateResponse, String> {
+    // A leading file-type hint helps small models pick the right syntax
+    let prefix = match language.as_deref().map(|l| (l, comment_prefix(l))) {
+        Some((lang, comment)) if !comment.is_empty() => format!("{} language: {}\n{}", comment, lang, prefix),
+        _ => prefix,
+    };
+
+    let body = match fim_prompt(&model, &prefix, &suffix) {
+        Some((prompt, stop)) => serde_json::json!({
+            "model": model,
+            "prompt": prompt,
+            "raw": true,
+            "stream": false,
+            "options": { "num_predict": max_tokens, "temperature": 0.2, "stop": stop },
+        }),
+        None => serde_json::json!({
+            "model": model,
+            "prompt": prefix,
+            "suffix": suffix,
+            "stream": false,
+            "options": { "num_predict": max_tokens, "temperature": 0.2 },
+        }),
+    };
+
+    post_with_retry(&policy, Some(&app), "/api/generate", &body)
+        .await?
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse response: {}", e))
+}
+
+// ============================================================================
+// COMPLETION TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub async fn complete_code(
+    model: String,
+    prefix: String,
+    suffix: String,
+    language: Option<String>,
+    max_tokens: Option<u32>,
+    timeout_ms: Option<u64>,
+    state: State<'_, CompletionState>,
+    llm: State<'_, LlmState>,
+) -> Result<CodeCompletion, String> {
+    let started = Instant::now();
+    let timeout = Duration::from_millis(timeout_ms.unwrap_or(3000));
+    let prompt = LoggedPrompt::Raw(format!("{}<cursor>{}", prefix, suffix));
+
+    let handle = task::spawn(request_completion(
+        llm.retry_policy(),
+        llm.app_handle(),
+        model.clone(),
+        prefix,
+        suffix,
+        language,
+        max_tokens.unwrap_or(64),
+    ));
+    let abort = handle.abort_handle();
+    state.replace(handle.abort_handle());
+
+    let result = match tokio::time::timeout(timeout, handle).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(e)) if e.is_cancelled() => Err("Completion cancelled".to_string()),
+        Ok(Err(e)) => Err(format!("Completion task failed: {}", e)),
+        Err(_) => {
+            abort.abort();
+            Err(format!("Completion timed out after {}ms", timeout.as_millis()))
+        }
+    };
+
+    llm.log_request(LlmRequestRecord {
+        provider: "ollama",
+        model: &model,
+        endpoint: "/api/generate",
+        prompt,
+        prompt_tokens: result.as_ref().ok().and_then(|r| r.prompt_eval_count),
+        completion_tokens: result.as_ref().ok().and_then(|r| r.eval_count),
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: result.as_ref().err().cloned(),
+    });
+
+    let text = result?.response;
+    Ok(CodeCompletion {
+        text,
+        model,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+#[tauri::command]
+pub fn cancel_completion(state: State<'_, CompletionState>) -> Result<(), String> {
+    state.cancel();
+    Ok(())
+}
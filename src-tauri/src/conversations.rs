@@ -0,0 +1,293 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::locks::LockExt;
+use crate::ChatMessage;
+
+// ============================================================================
+// CONVERSATION STATE
+// ============================================================================
+
+pub struct ConversationState {
+    conn: Mutex<Connection>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Conversation {
+    pub id: i64,
+    pub title: String,
+    pub created_at: i64,
+    pub updated_at: i64,
+    pub message_count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StoredMessage {
+    pub id: i64,
+    pub conversation_id: i64,
+    pub role: String,
+    pub content: String,
+    pub created_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+impl ConversationState {
+    pub fn open(db_path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(db_path)
+            .map_err(|e| format!("Failed to open conversation database: {}", e))?;
+
+        conn.execute_batch(
+            "PRAGMA foreign_keys = ON;
+             CREATE TABLE IF NOT EXISTS conversations (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 title TEXT NOT NULL,
+                 created_at INTEGER NOT NULL,
+                 updated_at INTEGER NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS messages (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 conversation_id INTEGER NOT NULL REFERENCES conversations(id) ON DELETE CASCADE,
+                 role TEXT NOT NULL,
+                 content TEXT NOT NULL,
+                 created_at INTEGER NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_messages_conversation ON messages(conversation_id, id);",
+        )
+        .map_err(|e| format!("Failed to initialize conversation database: {}", e))?;
+
+        Ok(ConversationState {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn get_conversation(conn: &Connection, id: i64) -> Result<Conversation, String> {
+        conn.query_row(
+            "SELECT c.id, c.title, c.created_at, c.updated_at,
+                    (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id)
+             FROM conversations c WHERE c.id = ?1",
+            params![id],
+            |row| {
+                Ok(Conversation {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    created_at: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    message_count: row.get(4)?,
+                })
+            },
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Conversation not found: {}", id))
+    }
+
+    /// Returns the messages of a conversation in chronological order, ready to
+    /// be sent to the model.
+    pub fn load_messages(&self, conversation_id: i64) -> Result<Vec<ChatMessage>, String> {
+        let conn = self.conn.lock_or_recover();
+        let mut stmt = conn
+            .prepare("SELECT role, content FROM messages WHERE conversation_id = ?1 ORDER BY id ASC")
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map(params![conversation_id], |row| {
+                Ok(ChatMessage {
+                    role: row.get(0)?,
+                    content: row.get(1)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    }
+
+    pub fn append(&self, conversation_id: i64, message: &ChatMessage) -> Result<StoredMessage, String> {
+        let conn = self.conn.lock_or_recover();
+        let created_at = now_secs();
+
+        conn.execute(
+            "INSERT INTO messages (conversation_id, role, content, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![conversation_id, message.role, message.content, created_at],
+        )
+        .map_err(|e| format!("Failed to append message: {}", e))?;
+        let id = conn.last_insert_rowid();
+
+        conn.execute(
+            "UPDATE conversations SET updated_at = ?1 WHERE id = ?2",
+            params![created_at, conversation_id],
+        )
+        .map_err(|e| e.to_string())?;
+
+        Ok(StoredMessage {
+            id,
+            conversation_id,
+            role: message.role.clone(),
+            content: message.content.clone(),
+            created_at,
+        })
+    }
+
+    /// Stores `messages` as the newest turn of a conversation and returns its
+    /// whole history for the model. Without a conversation the messages are
+    /// the whole history and nothing is stored.
+    pub fn continue_with(
+        &self,
+        conversation_id: Option<i64>,
+        messages: Vec<ChatMessage>,
+    ) -> Result<Vec<ChatMessage>, String> {
+        let Some(conversation_id) = conversation_id else {
+            return Ok(messages);
+        };
+        Self::get_conversation(&self.conn.lock_or_recover(), conversation_id)?;
+        for message in &messages {
+            self.append(conversation_id, message)?;
+        }
+        self.load_messages(conversation_id)
+    }
+
+    /// Stores the model's reply to a turn started with `continue_with`.
+    pub fn record_reply(&self, conversation_id: Option<i64>, content: &str) -> Result<(), String> {
+        let Some(conversation_id) = conversation_id else {
+            return Ok(());
+        };
+        let reply = ChatMessage {
+            role: "assistant".to_string(),
+            content: content.to_string(),
+        };
+        self.append(conversation_id, &reply).map(|_| ())
+    }
+}
+
+// ============================================================================
+// CONVERSATION TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn create_conversation(
+    title: Option<String>,
+    state: State<'_, ConversationState>,
+) -> Result<Conversation, String> {
+    let conn = state.conn.lock_or_recover();
+    let now = now_secs();
+    let title = title.unwrap_or_else(|| "New conversation".to_string());
+
+    conn.execute(
+        "INSERT INTO conversations (title, created_at, updated_at) VALUES (?1, ?2, ?2)",
+        params![title, now],
+    )
+    .map_err(|e| format!("Failed to create conversation: {}", e))?;
+
+    ConversationState::get_conversation(&conn, conn.last_insert_rowid())
+}
+
+#[tauri::command]
+pub fn list_conversations(state: State<'_, ConversationState>) -> Result<Vec<Conversation>, String> {
+    let conn = state.conn.lock_or_recover();
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.id, c.title, c.created_at, c.updated_at,
+                    (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id)
+             FROM conversations c ORDER BY c.updated_at DESC, c.id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map([], |row| {
+            Ok(Conversation {
+                id: row.get(0)?,
+                title: row.get(1)?,
+                created_at: row.get(2)?,
+                updated_at: row.get(3)?,
+                message_count: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn rename_conversation(
+    id: i64,
+    title: String,
+    state: State<'_, ConversationState>,
+) -> Result<Conversation, String> {
+    let conn = state.conn.lock_or_recover();
+    let updated = conn
+        .execute(
+            "UPDATE conversations SET title = ?1, updated_at = ?2 WHERE id = ?3",
+            params![title, now_secs(), id],
+        )
+        .map_err(|e| format!("Failed to rename conversation: {}", e))?;
+
+    if updated == 0 {
+        return Err(format!("Conversation not found: {}", id));
+    }
+
+    ConversationState::get_conversation(&conn, id)
+}
+
+#[tauri::command]
+pub fn delete_conversation(id: i64, state: State<'_, ConversationState>) -> Result<(), String> {
+    let conn = state.conn.lock_or_recover();
+    conn.execute("DELETE FROM conversations WHERE id = ?1", params![id])
+        .map_err(|e| format!("Failed to delete conversation: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn append_message(
+    conversation_id: i64,
+    message: ChatMessage,
+    state: State<'_, ConversationState>,
+) -> Result<StoredMessage, String> {
+    state.append(conversation_id, &message)
+}
+
+/// Pages backwards through a conversation: pass the smallest id already
+/// loaded as `before_id` to fetch the previous `limit` messages.
+#[tauri::command]
+pub fn get_conversation_messages(
+    conversation_id: i64,
+    limit: Option<usize>,
+    before_id: Option<i64>,
+    state: State<'_, ConversationState>,
+) -> Result<Vec<StoredMessage>, String> {
+    let conn = state.conn.lock_or_recover();
+    let limit = limit.unwrap_or(100) as i64;
+    let before_id = before_id.unwrap_or(i64::MAX);
+
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, conversation_id, role, content, created_at FROM messages
+             WHERE conversation_id = ?1 AND id < ?2
+             ORDER BY id DESC LIMIT ?3",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![conversation_id, before_id, limit], |row| {
+            Ok(StoredMessage {
+                id: row.get(0)?,
+                conversation_id: row.get(1)?,
+                role: row.get(2)?,
+                content: row.get(3)?,
+                created_at: row.get(4)?,
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut messages = rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())?;
+    messages.reverse();
+    Ok(messages)
+}
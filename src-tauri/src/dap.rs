@@ -0,0 +1,774 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::{AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::TcpStream;
+use tokio::process::{Child, Command};
+use tokio::sync::oneshot;
+
+use crate::locks::LockExt;
+use crate::lsp::read_message;
+use crate::sandbox::WorkspaceState;
+
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+/// How long an adapter gets to send `initialized` or accept a connection.
+const STARTUP_TIMEOUT_SECS: u64 = 10;
+const DISCONNECT_TIMEOUT_SECS: u64 = 3;
+/// Replaced by a free local port in the arguments of TCP adapters.
+const PORT_PLACEHOLDER: &str = "{port}";
+
+// ============================================================================
+// DAP STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DapAdapterConfig {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// "stdio", or "tcp" for adapters that listen on `{port}`.
+    #[serde(default = "default_transport")]
+    pub transport: String,
+}
+
+fn default_transport() -> String {
+    "stdio".to_string()
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct BreakpointSpec {
+    /// 0-based, like every line and column of the bridge.
+    pub line: u32,
+    pub column: Option<u32>,
+    pub condition: Option<String>,
+    pub hit_condition: Option<String>,
+    /// Logs instead of stopping (a logpoint).
+    pub log_message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DapBreakpoint {
+    #[serde(default)]
+    pub id: Option<i64>,
+    #[serde(default)]
+    pub verified: bool,
+    #[serde(default)]
+    pub line: Option<u32>,
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DapThread {
+    pub id: i64,
+    pub name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DapSource {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DapStackFrame {
+    pub id: i64,
+    pub name: String,
+    #[serde(default)]
+    pub source: Option<DapSource>,
+    pub line: u32,
+    pub column: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DapScope {
+    pub name: String,
+    #[serde(rename(deserialize = "variablesReference"))]
+    pub variables_reference: i64,
+    #[serde(default)]
+    pub expensive: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DapVariable {
+    pub name: String,
+    pub value: String,
+    #[serde(default, rename(deserialize = "type"))]
+    pub type_name: Option<String>,
+    /// Non-zero when the value has children to fetch with `dap_variables`.
+    #[serde(default, rename(deserialize = "variablesReference"))]
+    pub variables_reference: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DapEvaluation {
+    pub result: String,
+    #[serde(default, rename(deserialize = "type"))]
+    pub type_name: Option<String>,
+    #[serde(default, rename(deserialize = "variablesReference"))]
+    pub variables_reference: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DebugSessionStatus {
+    pub session_id: String,
+    pub adapter_id: String,
+    pub state: String, // "starting", "running", "stopped", "terminated"
+    /// Why execution stopped: "breakpoint", "step", "exception", ...
+    pub stop_reason: Option<String>,
+    pub stopped_thread: Option<i64>,
+    pub exit_code: Option<i64>,
+    pub capabilities: Value,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct DapEventPayload {
+    session_id: String,
+    event: String,
+    body: Value,
+}
+
+type PendingRequests = HashMap<i64, oneshot::Sender<Result<Value, String>>>;
+type DapWriter = Box<dyn AsyncWrite + Send + Unpin>;
+
+struct DapSession {
+    session_id: String,
+    writer: tokio::sync::Mutex<DapWriter>,
+    child: Mutex<Child>,
+    seq: AtomicI64,
+    pending: Mutex<PendingRequests>,
+    /// Fired by the adapter's `initialized` event, when it accepts
+    /// breakpoints.
+    initialized: Mutex<Option<oneshot::Sender<()>>>,
+    status: Mutex<DebugSessionStatus>,
+}
+
+/// Debug sessions by id and the adapter configurations by id.
+pub struct DapState {
+    sessions: Mutex<HashMap<String, Arc<DapSession>>>,
+    configs: Mutex<HashMap<String, DapAdapterConfig>>,
+}
+
+impl Default for DapState {
+    fn default() -> Self {
+        let config = |program: &str, args: &[&str], transport: &str| DapAdapterConfig {
+            program: program.to_string(),
+            args: args.iter().map(|a| a.to_string()).collect(),
+            transport: transport.to_string(),
+        };
+        let python = if cfg!(target_os = "windows") { "python" } else { "python3" };
+        DapState {
+            sessions: Mutex::new(HashMap::new()),
+            configs: Mutex::new(HashMap::from([
+                ("debugpy".to_string(), config(python, &["-m", "debugpy.adapter"], "stdio")),
+                ("codelldb".to_string(), config("codelldb", &["--port", PORT_PLACEHOLDER], "tcp")),
+                ("node".to_string(), config("js-debug-adapter", &[PORT_PLACEHOLDER], "tcp")),
+            ])),
+        }
+    }
+}
+
+// ============================================================================
+// DAP TRANSPORT
+// ============================================================================
+
+impl DapSession {
+    async fn send(&self, message: Value) -> Result<(), String> {
+        let body = message.to_string();
+        let mut writer = self.writer.lock().await;
+        writer
+            .write_all(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to debug adapter: {}", e))?;
+        writer
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to write to debug adapter: {}", e))
+    }
+
+    async fn request_with_timeout(&self, command: &str, arguments: Value, timeout: Duration) -> Result<Value, String> {
+        let seq = self.seq.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock_or_recover().insert(seq, sender);
+
+        let message = json!({ "seq": seq, "type": "request", "command": command, "arguments": arguments });
+        if let Err(e) = self.send(message).await {
+            self.pending.lock_or_recover().remove(&seq);
+            return Err(e);
+        }
+        match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err("Debug adapter exited".to_string()),
+            Err(_) => {
+                self.pending.lock_or_recover().remove(&seq);
+                Err(format!("{} timed out after {}s", command, timeout.as_secs()))
+            }
+        }
+    }
+
+    /// Sends a request and returns the response body.
+    async fn request(&self, command: &str, arguments: Value) -> Result<Value, String> {
+        self.request_with_timeout(command, arguments, Duration::from_secs(REQUEST_TIMEOUT_SECS)).await
+    }
+
+    /// Reverse requests such as `runInTerminal` aren't supported; the
+    /// adapter falls back to its own console.
+    async fn refuse(&self, request: &Value) {
+        let seq = self.seq.fetch_add(1, Ordering::SeqCst);
+        let command = request.get("command").and_then(|c| c.as_str()).unwrap_or_default();
+        let response = json!({
+            "seq": seq,
+            "type": "response",
+            "request_seq": request.get("seq").cloned().unwrap_or(Value::Null),
+            "success": false,
+            "command": command,
+            "message": format!("Unsupported request: {}", command),
+        });
+        let _ = self.send(response).await;
+    }
+
+    fn snapshot(&self) -> DebugSessionStatus {
+        self.status.lock_or_recover().clone()
+    }
+
+    fn set_state(&self, app: &AppHandle, update: impl FnOnce(&mut DebugSessionStatus)) {
+        update(&mut self.status.lock_or_recover());
+        let _ = app.emit("dap-session-status", self.snapshot());
+    }
+}
+
+/// Tracks execution state from adapter events and forwards every event as
+/// `dap-event`.
+fn handle_event(app: &AppHandle, session: &DapSession, event: &str, body: Value) {
+    match event {
+        "initialized" => {
+            if let Some(sender) = session.initialized.lock_or_recover().take() {
+                let _ = sender.send(());
+            }
+        }
+        "stopped" => session.set_state(app, |s| {
+            s.state = "stopped".to_string();
+            s.stop_reason = body.get("reason").and_then(|r| r.as_str()).map(|r| r.to_string());
+            s.stopped_thread = body.get("threadId").and_then(|t| t.as_i64());
+        }),
+        "continued" => session.set_state(app, |s| {
+            s.state = "running".to_string();
+            s.stop_reason = None;
+            s.stopped_thread = None;
+        }),
+        "exited" => session.set_state(app, |s| s.exit_code = body.get("exitCode").and_then(|c| c.as_i64())),
+        "terminated" => session.set_state(app, |s| s.state = "terminated".to_string()),
+        _ => {}
+    }
+    let _ = app.emit(
+        "dap-event",
+        DapEventPayload {
+            session_id: session.session_id.clone(),
+            event: event.to_string(),
+            body,
+        },
+    );
+}
+
+async fn read_loop<R: AsyncRead + Unpin>(app: AppHandle, session: Arc<DapSession>, reader: R) {
+    let mut reader = BufReader::new(reader);
+    loop {
+        let message = match read_message(&mut reader).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("Debug adapter {}: {}", session.session_id, e);
+                break;
+            }
+        };
+        match message.get("type").and_then(|t| t.as_str()) {
+            Some("response") => {
+                let Some(sender) = message
+                    .get("request_seq")
+                    .and_then(|s| s.as_i64())
+                    .and_then(|seq| session.pending.lock_or_recover().remove(&seq))
+                else {
+                    continue;
+                };
+                let result = if message.get("success").and_then(|s| s.as_bool()) == Some(true) {
+                    Ok(message.get("body").cloned().unwrap_or(Value::Null))
+                } else {
+                    // Adapters put user-facing text in body.error.format
+                    let detail = message
+                        .pointer("/body/error/format")
+                        .or_else(|| message.get("message"))
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("Request failed");
+                    Err(detail.to_string())
+                };
+                let _ = sender.send(result);
+            }
+            Some("event") => {
+                let event = message.get("event").and_then(|e| e.as_str()).unwrap_or_default();
+                let body = message.get("body").cloned().unwrap_or(Value::Null);
+                handle_event(&app, &session, event, body);
+            }
+            Some("request") => session.refuse(&message).await,
+            _ => {}
+        }
+    }
+
+    session.pending.lock_or_recover().clear();
+    session.set_state(&app, |s| s.state = "terminated".to_string());
+}
+
+async fn forward_stderr(app: AppHandle, session_id: String, stderr: tokio::process::ChildStderr) {
+    let mut lines = tokio::io::AsyncBufReadExt::lines(BufReader::new(stderr));
+    while let Ok(Some(line)) = lines.next_line().await {
+        let _ = app.emit(
+            "dap-event",
+            DapEventPayload {
+                session_id: session_id.clone(),
+                event: "output".to_string(),
+                body: json!({ "category": "stderr", "output": format!("{}\n", line) }),
+            },
+        );
+    }
+}
+
+/// A port that was free a moment ago, for TCP adapters to listen on.
+fn free_port() -> Result<u16, String> {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .and_then(|listener| listener.local_addr())
+        .map(|addr| addr.port())
+        .map_err(|e| format!("Failed to find a free port: {}", e))
+}
+
+/// The adapter listens once it has started; retry until it accepts.
+async fn connect(port: u16) -> Result<TcpStream, String> {
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(STARTUP_TIMEOUT_SECS);
+    loop {
+        match TcpStream::connect(("127.0.0.1", port)).await {
+            Ok(stream) => return Ok(stream),
+            Err(e) if tokio::time::Instant::now() >= deadline => {
+                return Err(format!("Failed to connect to debug adapter on port {}: {}", port, e));
+            }
+            Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+        }
+    }
+}
+
+async fn spawn_session(
+    app: &AppHandle,
+    session_id: &str,
+    adapter_id: &str,
+    config: &DapAdapterConfig,
+    cwd: &std::path::Path,
+) -> Result<(Arc<DapSession>, oneshot::Receiver<()>), String> {
+    let tcp = config.transport == "tcp";
+    let port = if tcp { Some(free_port()?) } else { None };
+    let args: Vec<String> = config
+        .args
+        .iter()
+        .map(|a| match port {
+            Some(port) => a.replace(PORT_PLACEHOLDER, &port.to_string()),
+            None => a.clone(),
+        })
+        .collect();
+
+    let mut child = Command::new(&config.program)
+        .args(&args)
+        .current_dir(cwd)
+        .stdin(if tcp { Stdio::null() } else { Stdio::piped() })
+        .stdout(if tcp { Stdio::null() } else { Stdio::piped() })
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {}", config.program, e))?;
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+
+    let (reader, writer): (Box<dyn AsyncRead + Send + Unpin>, DapWriter) = match port {
+        Some(port) => {
+            let (read, write) = connect(port).await?.into_split();
+            (Box::new(read), Box::new(write))
+        }
+        None => {
+            let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+            let stdin = child.stdin.take().ok_or("Failed to capture stdin")?;
+            (Box::new(stdout), Box::new(stdin))
+        }
+    };
+
+    let (initialized, initialized_receiver) = oneshot::channel();
+    let session = Arc::new(DapSession {
+        session_id: session_id.to_string(),
+        writer: tokio::sync::Mutex::new(writer),
+        child: Mutex::new(child),
+        seq: AtomicI64::new(1),
+        pending: Mutex::new(HashMap::new()),
+        initialized: Mutex::new(Some(initialized)),
+        status: Mutex::new(DebugSessionStatus {
+            session_id: session_id.to_string(),
+            adapter_id: adapter_id.to_string(),
+            state: "starting".to_string(),
+            stop_reason: None,
+            stopped_thread: None,
+            exit_code: None,
+            capabilities: Value::Null,
+        }),
+    });
+    tokio::spawn(read_loop(app.clone(), session.clone(), reader));
+    tokio::spawn(forward_stderr(app.clone(), session_id.to_string(), stderr));
+    let _ = app.emit("dap-session-status", session.snapshot());
+    Ok((session, initialized_receiver))
+}
+
+fn source_breakpoints(path: &str, breakpoints: &[BreakpointSpec]) -> Value {
+    let breakpoints: Vec<Value> = breakpoints
+        .iter()
+        .map(|b| {
+            let mut breakpoint = json!({ "line": b.line });
+            let fields = [
+                ("column", b.column.map(Value::from)),
+                ("condition", b.condition.clone().map(Value::from)),
+                ("hitCondition", b.hit_condition.clone().map(Value::from)),
+                ("logMessage", b.log_message.clone().map(Value::from)),
+            ];
+            for (key, value) in fields {
+                if let Some(value) = value {
+                    breakpoint[key] = value;
+                }
+            }
+            breakpoint
+        })
+        .collect();
+    json!({ "source": { "path": path }, "breakpoints": breakpoints })
+}
+
+async fn set_breakpoints(
+    session: &DapSession,
+    path: &str,
+    breakpoints: &[BreakpointSpec],
+) -> Result<Vec<DapBreakpoint>, String> {
+    let body = session.request("setBreakpoints", source_breakpoints(path, breakpoints)).await?;
+    body.get("breakpoints")
+        .cloned()
+        .map(serde_json::from_value)
+        .transpose()
+        .map(|b| b.unwrap_or_default())
+        .map_err(|e| format!("Invalid setBreakpoints response: {}", e))
+}
+
+fn ready_or_err<E>(ready: Result<Result<(), oneshot::error::RecvError>, E>) -> Result<(), String> {
+    match ready {
+        Ok(Ok(())) => Ok(()),
+        _ => Err("Debug adapter never sent initialized".to_string()),
+    }
+}
+
+fn launch_result(request: &str, result: Result<Result<Value, String>, tokio::task::JoinError>) -> Result<(), String> {
+    result
+        .map_err(|e| format!("{} failed: {}", request, e))?
+        .map(|_| ())
+        .map_err(|e| format!("{} failed: {}", request, e))
+}
+
+/// Initialize, launch or attach, then the configuration phase: adapters
+/// take breakpoints after `initialized` and only answer the launch request
+/// once `configurationDone` is sent.
+async fn configure(
+    app: &AppHandle,
+    session: &Arc<DapSession>,
+    adapter_id: &str,
+    request: &str,
+    configuration: Value,
+    breakpoints: &HashMap<String, Vec<BreakpointSpec>>,
+    initialized: oneshot::Receiver<()>,
+) -> Result<(), String> {
+    let capabilities = session
+        .request(
+            "initialize",
+            json!({
+                "clientID": "gencode",
+                "clientName": "GenCode",
+                "adapterID": adapter_id,
+                "linesStartAt1": false,
+                "columnsStartAt1": false,
+                "pathFormat": "path",
+                "supportsVariableType": true,
+                "supportsRunInTerminalRequest": false,
+                "locale": "en",
+            }),
+        )
+        .await?;
+    session.set_state(app, |s| s.capabilities = capabilities.clone());
+
+    let mut launch = {
+        let (session, request) = (session.clone(), request.to_string());
+        tokio::spawn(async move { session.request(&request, configuration).await })
+    };
+    let ready = tokio::time::timeout(Duration::from_secs(STARTUP_TIMEOUT_SECS), initialized);
+    tokio::pin!(ready);
+    // A failing launch is answered before `initialized` ever arrives
+    let early_launch = tokio::select! {
+        ready = &mut ready => {
+            ready_or_err(ready)?;
+            None
+        }
+        result = &mut launch => Some(result),
+    };
+    let launched = early_launch.is_some();
+    if let Some(result) = early_launch {
+        launch_result(request, result)?;
+        ready_or_err((&mut ready).await)?;
+    }
+
+    for (path, specs) in breakpoints {
+        set_breakpoints(session, path, specs).await?;
+    }
+    if capabilities.get("exceptionBreakpointFilters").is_some() {
+        session.request("setExceptionBreakpoints", json!({ "filters": [] })).await?;
+    }
+    if capabilities.get("supportsConfigurationDoneRequest").and_then(|s| s.as_bool()) == Some(true) {
+        session.request("configurationDone", json!({})).await?;
+    }
+    if !launched {
+        launch_result(request, launch.await)?;
+    }
+
+    session.set_state(app, |s| {
+        if s.state == "starting" {
+            s.state = "running".to_string();
+        }
+    });
+    Ok(())
+}
+
+async fn disconnect(app: &AppHandle, session: &DapSession, terminate: bool) {
+    let timeout = Duration::from_secs(DISCONNECT_TIMEOUT_SECS);
+    let _ = session
+        .request_with_timeout("disconnect", json!({ "terminateDebuggee": terminate }), timeout)
+        .await;
+    let _ = session.child.lock_or_recover().start_kill();
+    session.set_state(app, |s| s.state = "terminated".to_string());
+}
+
+impl DapState {
+    /// Kills every adapter; used on app exit, when there's no time to
+    /// disconnect politely.
+    pub fn shutdown(&self) {
+        for (_, session) in self.sessions.lock_or_recover().drain() {
+            let _ = session.child.lock_or_recover().start_kill();
+        }
+    }
+
+    fn session(&self, session_id: &str) -> Result<Arc<DapSession>, String> {
+        self.sessions
+            .lock_or_recover()
+            .get(session_id)
+            .cloned()
+            .ok_or_else(|| format!("Debug session not found: {}", session_id))
+    }
+}
+
+// ============================================================================
+// DAP TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn dap_get_adapter_configs(state: State<'_, DapState>) -> HashMap<String, DapAdapterConfig> {
+    state.configs.lock_or_recover().clone()
+}
+
+#[tauri::command]
+pub fn dap_configure_adapter(adapter_id: String, config: DapAdapterConfig, state: State<'_, DapState>) {
+    state.configs.lock_or_recover().insert(adapter_id, config);
+}
+
+/// Starts a debug session: spawns the adapter, sends `launch` or `attach`
+/// (`request`) with the adapter-specific `configuration`, and sets the
+/// initial breakpoints by file path. Adapter events arrive as `dap-event`
+/// and state changes as `dap-session-status`.
+#[tauri::command]
+pub async fn dap_start_session(
+    app: AppHandle,
+    session_id: String,
+    adapter_id: String,
+    root: String,
+    request: String,
+    configuration: Value,
+    breakpoints: Option<HashMap<String, Vec<BreakpointSpec>>>,
+) -> Result<DebugSessionStatus, String> {
+    use tauri::Manager;
+    let workspace = app.state::<WorkspaceState>();
+    let state = app.state::<DapState>();
+
+    let root = workspace.check(&root)?;
+    let breakpoints = breakpoints.unwrap_or_default();
+    for path in breakpoints.keys() {
+        workspace.check(path)?;
+    }
+    if request != "launch" && request != "attach" {
+        return Err(format!("Unknown debug request: {}", request));
+    }
+    if state.sessions.lock_or_recover().contains_key(&session_id) {
+        return Err(format!("Debug session already exists: {}", session_id));
+    }
+    let config = state
+        .configs
+        .lock_or_recover()
+        .get(&adapter_id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown debug adapter: {}", adapter_id))?;
+
+    let mut configuration = configuration;
+    if configuration.is_object() && configuration.get("cwd").is_none() {
+        configuration["cwd"] = Value::from(root.to_string_lossy().to_string());
+    }
+
+    let (session, initialized) = spawn_session(&app, &session_id, &adapter_id, &config, &root).await?;
+    state.sessions.lock_or_recover().insert(session_id.clone(), session.clone());
+    if let Err(e) = configure(&app, &session, &adapter_id, &request, configuration, &breakpoints, initialized).await {
+        state.sessions.lock_or_recover().remove(&session_id);
+        disconnect(&app, &session, true).await;
+        return Err(e);
+    }
+    Ok(session.snapshot())
+}
+
+/// Ends the session, terminating the debuggee unless `terminate_debuggee`
+/// is false (useful after attaching).
+#[tauri::command]
+pub async fn dap_stop_session(
+    app: AppHandle,
+    session_id: String,
+    terminate_debuggee: Option<bool>,
+    state: State<'_, DapState>,
+) -> Result<(), String> {
+    let session = state
+        .sessions
+        .lock_or_recover()
+        .remove(&session_id)
+        .ok_or_else(|| format!("Debug session not found: {}", session_id))?;
+    disconnect(&app, &session, terminate_debuggee.unwrap_or(true)).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn dap_list_sessions(state: State<'_, DapState>) -> Vec<DebugSessionStatus> {
+    let sessions = state.sessions.lock_or_recover();
+    let mut statuses: Vec<DebugSessionStatus> = sessions.values().map(|s| s.snapshot()).collect();
+    statuses.sort_by(|a, b| a.session_id.cmp(&b.session_id));
+    statuses
+}
+
+/// Replaces the breakpoints of `path`; an empty list clears them.
+#[tauri::command]
+pub async fn dap_set_breakpoints(
+    session_id: String,
+    path: String,
+    breakpoints: Vec<BreakpointSpec>,
+    state: State<'_, DapState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<DapBreakpoint>, String> {
+    let resolved = workspace.check(&path)?;
+    let session = state.session(&session_id)?;
+    set_breakpoints(&session, &resolved.to_string_lossy(), &breakpoints).await
+}
+
+/// Resumes or steps `thread_id`: `action` is "continue", "next",
+/// "step_in", "step_out" or "pause".
+#[tauri::command]
+pub async fn dap_step(
+    session_id: String,
+    thread_id: i64,
+    action: String,
+    state: State<'_, DapState>,
+) -> Result<(), String> {
+    let command = match action.as_str() {
+        "continue" => "continue",
+        "next" => "next",
+        "step_in" => "stepIn",
+        "step_out" => "stepOut",
+        "pause" => "pause",
+        other => return Err(format!("Unknown step action: {}", other)),
+    };
+    let session = state.session(&session_id)?;
+    session.request(command, json!({ "threadId": thread_id })).await?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn dap_threads(session_id: String, state: State<'_, DapState>) -> Result<Vec<DapThread>, String> {
+    let session = state.session(&session_id)?;
+    let body = session.request("threads", Value::Null).await?;
+    serde_json::from_value(body.get("threads").cloned().unwrap_or_else(|| json!([])))
+        .map_err(|e| format!("Invalid threads response: {}", e))
+}
+
+#[tauri::command]
+pub async fn dap_stack_trace(
+    session_id: String,
+    thread_id: i64,
+    levels: Option<u32>,
+    state: State<'_, DapState>,
+) -> Result<Vec<DapStackFrame>, String> {
+    let session = state.session(&session_id)?;
+    let body = session
+        .request(
+            "stackTrace",
+            json!({ "threadId": thread_id, "startFrame": 0, "levels": levels.unwrap_or(0) }),
+        )
+        .await?;
+    serde_json::from_value(body.get("stackFrames").cloned().unwrap_or_else(|| json!([])))
+        .map_err(|e| format!("Invalid stackTrace response: {}", e))
+}
+
+#[tauri::command]
+pub async fn dap_scopes(
+    session_id: String,
+    frame_id: i64,
+    state: State<'_, DapState>,
+) -> Result<Vec<DapScope>, String> {
+    let session = state.session(&session_id)?;
+    let body = session.request("scopes", json!({ "frameId": frame_id })).await?;
+    serde_json::from_value(body.get("scopes").cloned().unwrap_or_else(|| json!([])))
+        .map_err(|e| format!("Invalid scopes response: {}", e))
+}
+
+/// Children of a scope or structured variable.
+#[tauri::command]
+pub async fn dap_variables(
+    session_id: String,
+    variables_reference: i64,
+    state: State<'_, DapState>,
+) -> Result<Vec<DapVariable>, String> {
+    let session = state.session(&session_id)?;
+    let body = session
+        .request("variables", json!({ "variablesReference": variables_reference }))
+        .await?;
+    serde_json::from_value(body.get("variables").cloned().unwrap_or_else(|| json!([])))
+        .map_err(|e| format!("Invalid variables response: {}", e))
+}
+
+/// Evaluates `expression` in a stack frame; `context` is "watch",
+/// "repl" or "hover" (default "repl").
+#[tauri::command]
+pub async fn dap_evaluate(
+    session_id: String,
+    expression: String,
+    frame_id: Option<i64>,
+    context: Option<String>,
+    state: State<'_, DapState>,
+) -> Result<DapEvaluation, String> {
+    let session = state.session(&session_id)?;
+    let context = context.unwrap_or_else(|| "repl".to_string());
+    let mut arguments = json!({ "expression": expression, "context": context });
+    if let Some(frame_id) = frame_id {
+        arguments["frameId"] = Value::from(frame_id);
+    }
+    let body = session.request("evaluate", arguments).await?;
+    serde_json::from_value(body).map_err(|e| format!("Invalid evaluate response: {}", e))
+}
@@ -0,0 +1,340 @@
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs as std_fs;
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tree_sitter::Node;
+
+use crate::locks::LockExt;
+use crate::lsp::{LspDiagnostic, LspPosition, LspRange};
+use crate::sandbox::WorkspaceState;
+use crate::ParserState;
+
+const SEVERITIES: &[&str] = &["error", "warning", "information", "hint"];
+/// Syntax errors reported per file; one bad edit can cascade into many.
+const MAX_SYNTAX_DIAGNOSTICS: usize = 100;
+const SYNTAX_PROVIDER: &str = "syntax";
+
+// ============================================================================
+// DIAGNOSTICS STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Diagnostic {
+    /// 0-based, columns in UTF-16 code units like LSP ranges.
+    pub range: LspRange,
+    /// "error", "warning", "information" or "hint".
+    pub severity: String,
+    pub message: String,
+    pub code: Option<String>,
+    /// What published it: "lsp:<server>", "syntax" or "linter:<tool>".
+    pub provider: String,
+    /// The tool named by the diagnostic itself, e.g. "rustc" or "eslint".
+    pub source: String,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct DiagnosticCounts {
+    pub errors: usize,
+    pub warnings: usize,
+    pub information: usize,
+    pub hints: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FileDiagnostics {
+    pub path: String,
+    pub diagnostics: Vec<Diagnostic>,
+    pub counts: DiagnosticCounts,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DiagnosticsReport {
+    pub files: Vec<FileDiagnostics>,
+    pub counts: DiagnosticCounts,
+}
+
+/// Diagnostics of every file by provider. Each provider replaces its own
+/// set for a file, so LSP servers, syntax checks and linters don't clobber
+/// each other.
+#[derive(Default)]
+pub struct DiagnosticsState {
+    files: Mutex<HashMap<String, BTreeMap<String, Vec<Diagnostic>>>>,
+}
+
+fn severity_rank(severity: &str) -> usize {
+    SEVERITIES.iter().position(|s| *s == severity).unwrap_or(0)
+}
+
+pub(crate) fn count(diagnostics: &[Diagnostic]) -> DiagnosticCounts {
+    let mut counts = DiagnosticCounts::default();
+    for diagnostic in diagnostics {
+        match diagnostic.severity.as_str() {
+            "warning" => counts.warnings += 1,
+            "information" => counts.information += 1,
+            "hint" => counts.hints += 1,
+            _ => counts.errors += 1,
+        }
+    }
+    counts
+}
+
+/// One file's diagnostics from all providers, in document order, with
+/// duplicates (same start, severity and message) reported once.
+fn merge(providers: &BTreeMap<String, Vec<Diagnostic>>) -> Vec<Diagnostic> {
+    let mut merged: Vec<Diagnostic> = Vec::new();
+    for diagnostic in providers.values().flatten() {
+        let duplicate = merged.iter().any(|m| {
+            m.message == diagnostic.message
+                && m.severity == diagnostic.severity
+                && m.range.start.line == diagnostic.range.start.line
+                && m.range.start.character == diagnostic.range.start.character
+        });
+        if !duplicate {
+            merged.push(diagnostic.clone());
+        }
+    }
+    merged.sort_by_key(|d| (d.range.start.line, d.range.start.character, severity_rank(&d.severity)));
+    merged
+}
+
+impl DiagnosticsState {
+    /// Replaces what `provider` reported for `path` and emits the merged
+    /// result as `diagnostics-updated`.
+    pub(crate) fn publish(&self, app: &AppHandle, path: &str, provider: &str, diagnostics: Vec<Diagnostic>) {
+        let merged = {
+            let mut files = self.files.lock_or_recover();
+            if diagnostics.is_empty() && !files.get(path).is_some_and(|p| p.contains_key(provider)) {
+                return;
+            }
+            let providers = files.entry(path.to_string()).or_default();
+            if diagnostics.is_empty() {
+                providers.remove(provider);
+            } else {
+                providers.insert(provider.to_string(), diagnostics);
+            }
+            let merged = merge(providers);
+            if providers.is_empty() {
+                files.remove(path);
+            }
+            merged
+        };
+        let _ = app.emit(
+            "diagnostics-updated",
+            FileDiagnostics {
+                path: path.to_string(),
+                counts: count(&merged),
+                diagnostics: merged,
+            },
+        );
+    }
+
+    /// Files `provider` currently reports diagnostics for.
+    pub(crate) fn paths_with(&self, provider: &str) -> Vec<String> {
+        let files = self.files.lock_or_recover();
+        files
+            .iter()
+            .filter(|(_, providers)| providers.contains_key(provider))
+            .map(|(path, _)| path.clone())
+            .collect()
+    }
+
+    /// Drops everything `provider` reported, e.g. when its server exits.
+    pub(crate) fn clear_provider(&self, app: &AppHandle, provider: &str) {
+        for path in self.paths_with(provider) {
+            self.publish(app, &path, provider, Vec::new());
+        }
+    }
+}
+
+pub(crate) fn from_lsp(diagnostic: LspDiagnostic, server_id: &str) -> Diagnostic {
+    Diagnostic {
+        range: diagnostic.range,
+        severity: diagnostic.severity,
+        message: diagnostic.message,
+        code: diagnostic.code,
+        provider: format!("lsp:{}", server_id),
+        source: diagnostic.source.unwrap_or_else(|| server_id.to_string()),
+    }
+}
+
+// ============================================================================
+// SYNTAX DIAGNOSTICS
+// ============================================================================
+
+/// Byte columns from tree-sitter become UTF-16 columns.
+fn position(lines: &[&str], point: tree_sitter::Point) -> LspPosition {
+    let text = lines.get(point.row).copied().unwrap_or_default();
+    let prefix = text.get(..point.column).unwrap_or(text);
+    LspPosition {
+        line: point.row as u32,
+        character: prefix.encode_utf16().count() as u32,
+    }
+}
+
+fn collect_syntax_errors(node: Node, source: &str, lines: &[&str], out: &mut Vec<Diagnostic>) {
+    if out.len() >= MAX_SYNTAX_DIAGNOSTICS {
+        return;
+    }
+    let message = if node.is_missing() {
+        Some(format!("Missing {}", node.kind()))
+    } else if node.is_error() {
+        let text = node.utf8_text(source.as_bytes()).unwrap_or_default().trim();
+        Some(if !text.is_empty() && text.len() <= 30 && !text.contains('\n') {
+            format!("Unexpected `{}`", text)
+        } else {
+            "Syntax error".to_string()
+        })
+    } else {
+        None
+    };
+    if let Some(message) = message {
+        out.push(Diagnostic {
+            range: LspRange {
+                start: position(lines, node.start_position()),
+                end: position(lines, node.end_position()),
+            },
+            severity: "error".to_string(),
+            message,
+            code: None,
+            provider: SYNTAX_PROVIDER.to_string(),
+            source: "tree-sitter".to_string(),
+        });
+        // Errors nested in an error node are noise
+        if node.is_error() {
+            return;
+        }
+    }
+
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if child.has_error() || child.is_missing() {
+            collect_syntax_errors(child, source, lines, out);
+        }
+    }
+}
+
+/// ERROR and MISSING nodes of the parse tree; `None` if the language has
+/// no parser.
+fn syntax_diagnostics(parser: &ParserState, path: &str, content: &str) -> Option<Vec<Diagnostic>> {
+    let (_, tree) = parser.parse_tree(path, content)?;
+    let lines: Vec<&str> = content.split('\n').collect();
+    let mut diagnostics = Vec::new();
+    let root = tree.root_node();
+    if root.has_error() {
+        collect_syntax_errors(root, content, &lines, &mut diagnostics);
+    }
+    Some(diagnostics)
+}
+
+// ============================================================================
+// DIAGNOSTICS TAURI COMMANDS
+// ============================================================================
+
+/// Diagnostics of `path`, or of every file when omitted, at least as
+/// severe as `min_severity` ("error", "warning", "information", "hint").
+#[tauri::command]
+pub fn get_diagnostics(
+    path: Option<String>,
+    min_severity: Option<String>,
+    state: State<'_, DiagnosticsState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<DiagnosticsReport, String> {
+    let path = match path {
+        Some(path) => Some(workspace.check(&path)?.to_string_lossy().to_string()),
+        None => None,
+    };
+    let max_rank = match min_severity.as_deref() {
+        Some(severity) if !SEVERITIES.contains(&severity) => {
+            return Err(format!("Unknown severity: {}", severity));
+        }
+        Some(severity) => severity_rank(severity),
+        None => SEVERITIES.len(),
+    };
+
+    let files = state.files.lock_or_recover();
+    let mut report: Vec<FileDiagnostics> = files
+        .iter()
+        .filter(|(file, _)| path.as_ref().is_none_or(|p| p == *file))
+        .filter_map(|(file, providers)| {
+            let diagnostics: Vec<Diagnostic> = merge(providers)
+                .into_iter()
+                .filter(|d| severity_rank(&d.severity) <= max_rank)
+                .collect();
+            (!diagnostics.is_empty()).then(|| FileDiagnostics {
+                path: file.clone(),
+                counts: count(&diagnostics),
+                diagnostics,
+            })
+        })
+        .collect();
+    report.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let all: Vec<Diagnostic> = report.iter().flat_map(|f| f.diagnostics.iter().cloned()).collect();
+    Ok(DiagnosticsReport {
+        counts: count(&all),
+        files: report,
+    })
+}
+
+/// Reparses `path` (or the unsaved `content`) and publishes its syntax
+/// errors. Returns them, empty when the language has no parser.
+#[tauri::command]
+pub async fn check_syntax(
+    app: AppHandle,
+    path: String,
+    content: Option<String>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<Diagnostic>, String> {
+    let resolved = workspace.check(&path)?;
+    tokio::task::spawn_blocking(move || {
+        let content = match content {
+            Some(content) => content,
+            None => std_fs::read_to_string(&resolved).map_err(|e| format!("Failed to read file: {}", e))?,
+        };
+        let key = resolved.to_string_lossy().to_string();
+        let diagnostics = syntax_diagnostics(&app.state::<ParserState>(), &key, &content).unwrap_or_default();
+        app.state::<DiagnosticsState>()
+            .publish(&app, &key, SYNTAX_PROVIDER, diagnostics.clone());
+        Ok(diagnostics)
+    })
+    .await
+    .map_err(|e| format!("Syntax check failed: {}", e))?
+}
+
+/// Forgets diagnostics of `path` (all files when omitted), optionally only
+/// those of one provider.
+#[tauri::command]
+pub fn clear_diagnostics(
+    app: AppHandle,
+    path: Option<String>,
+    provider: Option<String>,
+    state: State<'_, DiagnosticsState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<(), String> {
+    let path = match path {
+        Some(path) => Some(workspace.check(&path)?.to_string_lossy().to_string()),
+        None => None,
+    };
+    let targets: Vec<(String, Vec<String>)> = {
+        let files = state.files.lock_or_recover();
+        files
+            .iter()
+            .filter(|(file, _)| path.as_ref().is_none_or(|p| p == *file))
+            .map(|(file, providers)| {
+                let providers = providers
+                    .keys()
+                    .filter(|p| provider.as_ref().is_none_or(|wanted| wanted == *p))
+                    .cloned()
+                    .collect();
+                (file.clone(), providers)
+            })
+            .collect()
+    };
+    for (file, providers) in targets {
+        for provider in providers {
+            state.publish(&app, &file, &provider, Vec::new());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,383 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{State, Window};
+use tokio::task::AbortHandle;
+
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::formatter::on_path;
+use crate::locks::LockExt;
+use crate::process::{run_process, CommandResult};
+use crate::sandbox::WorkspaceState;
+use crate::terminal::{spawn_terminal, TerminalOptions, TerminalState};
+
+const QUERY_TIMEOUT_MS: u64 = 15_000;
+const COMPOSE_FILES: &[&str] = &["compose.yaml", "compose.yml", "docker-compose.yaml", "docker-compose.yml"];
+const DEFAULT_LOG_TAIL: usize = 200;
+/// Prefers bash but falls back to sh, which every image has.
+const CONTAINER_SHELL: &str = "if command -v bash >/dev/null 2>&1; then exec bash; else exec sh; fi";
+
+// ============================================================================
+// DOCKER STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct DockerProject {
+    pub dockerfiles: Vec<String>,
+    /// Compose files, including `docker-compose.override.yml` style ones.
+    pub compose_files: Vec<String>,
+    pub docker_available: bool,
+    /// "docker compose" or the standalone "docker-compose".
+    pub compose_command: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DockerContainer {
+    pub id: String,
+    pub name: String,
+    pub image: String,
+    /// "running", "exited", "paused", ...
+    pub state: String,
+    pub status: String,
+    pub ports: String,
+    pub compose_project: Option<String>,
+    pub compose_service: Option<String>,
+}
+
+/// Streaming `docker compose logs` followers, so they can be stopped.
+#[derive(Default)]
+pub struct DockerState {
+    logs: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl DockerState {
+    /// Aborts every log follower, whose process dies as the task is dropped.
+    pub fn shutdown(&self) {
+        for (_, follower) in self.logs.lock_or_recover().drain() {
+            follower.abort();
+        }
+    }
+}
+
+// ============================================================================
+// DOCKER CLI
+// ============================================================================
+
+fn is_dockerfile(name: &str) -> bool {
+    let name = name.to_lowercase();
+    name == "dockerfile"
+        || name == "containerfile"
+        || name.starts_with("dockerfile.")
+        || name.ends_with(".dockerfile")
+}
+
+fn is_compose_file(name: &str) -> bool {
+    COMPOSE_FILES.contains(&name)
+        || (name.starts_with("docker-compose.") || name.starts_with("compose."))
+            && (name.ends_with(".yml") || name.ends_with(".yaml"))
+}
+
+/// The compose CLI: the `docker compose` plugin, else `docker-compose`.
+async fn compose_command() -> Result<Vec<String>, String> {
+    if on_path("docker").is_some() {
+        let version = run_process(
+            None,
+            "docker-compose-version",
+            None,
+            "docker",
+            &["compose".to_string(), "version".to_string()],
+            &HashMap::new(),
+            Some(QUERY_TIMEOUT_MS),
+        )
+        .await;
+        if version.is_ok_and(|v| v.exit_code == Some(0)) {
+            return Ok(vec!["docker".to_string(), "compose".to_string()]);
+        }
+    }
+    if on_path("docker-compose").is_some() {
+        return Ok(vec!["docker-compose".to_string()]);
+    }
+    Err("Docker Compose not found; install Docker Desktop or the compose plugin".to_string())
+}
+
+/// Runs `<compose> -f <file> <args>` from the compose file's directory,
+/// streaming output as `command-output` events when a window is given.
+async fn run_compose(
+    window: Option<Window>,
+    command_id: &str,
+    compose_file: &Path,
+    args: Vec<String>,
+    timeout_ms: Option<u64>,
+) -> Result<CommandResult, String> {
+    let compose = compose_command().await?;
+    let mut full_args: Vec<String> = compose[1..].to_vec();
+    full_args.push("-f".to_string());
+    full_args.push(compose_file.to_string_lossy().to_string());
+    full_args.extend(args);
+    let cwd = compose_file.parent().map(|p| p.to_string_lossy().to_string());
+    run_process(window, command_id, cwd.as_deref(), &compose[0], &full_args, &HashMap::new(), timeout_ms).await
+}
+
+fn require_success(result: CommandResult, action: &str) -> Result<CommandResult, String> {
+    if result.exit_code == Some(0) {
+        Ok(result)
+    } else if result.timed_out {
+        Err(format!("{} timed out", action))
+    } else {
+        Err(format!("{} failed: {}", action, result.stderr.trim()))
+    }
+}
+
+/// `--format json` prints one object per line on current releases and a
+/// single array on some older compose versions.
+fn json_records(output: &str) -> Vec<Value> {
+    let trimmed = output.trim();
+    if trimmed.starts_with('[') {
+        return serde_json::from_str::<Vec<Value>>(trimmed).unwrap_or_default();
+    }
+    trimmed
+        .lines()
+        .filter_map(|line| serde_json::from_str::<Value>(line).ok())
+        .collect()
+}
+
+fn field(record: &Value, name: &str) -> String {
+    match record.get(name) {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Null) | None => String::new(),
+        Some(other) => other.to_string(),
+    }
+}
+
+/// `docker ps` gives labels as one "key=value,key=value" string.
+fn label(labels: &str, key: &str) -> Option<String> {
+    labels
+        .split(',')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| *k == key)
+        .map(|(_, v)| v.to_string())
+}
+
+// ============================================================================
+// DOCKER TAURI COMMANDS
+// ============================================================================
+
+/// Dockerfiles and compose files under `root`, and which CLIs are
+/// installed.
+#[tauri::command]
+pub async fn docker_detect(root: String, workspace: State<'_, WorkspaceState>) -> Result<DockerProject, String> {
+    let root = workspace.check(&root)?;
+    let entries = tokio::task::spawn_blocking(move || walk_entries(&root, &DirectoryOptions::default(), None))
+        .await
+        .map_err(|e| format!("Failed to scan for Docker files: {}", e))??;
+
+    let mut project = DockerProject {
+        dockerfiles: Vec::new(),
+        compose_files: Vec::new(),
+        docker_available: on_path("docker").is_some(),
+        compose_command: compose_command().await.ok().map(|c| c.join(" ")),
+    };
+    for (path, is_dir) in entries {
+        if is_dir {
+            continue;
+        }
+        let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        if is_dockerfile(&name) {
+            project.dockerfiles.push(path.to_string_lossy().to_string());
+        } else if is_compose_file(&name) {
+            project.compose_files.push(path.to_string_lossy().to_string());
+        }
+    }
+    project.dockerfiles.sort();
+    project.compose_files.sort();
+    Ok(project)
+}
+
+/// Builds an image from `dockerfile` with `context` (default: its directory)
+/// as build context. Output streams as `command-output` events tagged with
+/// `build_id`.
+#[tauri::command]
+pub async fn docker_build_image(
+    window: Window,
+    build_id: String,
+    dockerfile: String,
+    context: Option<String>,
+    tag: Option<String>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<CommandResult, String> {
+    let dockerfile = workspace.check(&dockerfile)?;
+    let context = match context {
+        Some(context) => workspace.check(&context)?,
+        None => dockerfile.parent().unwrap_or(&dockerfile).to_path_buf(),
+    };
+    let mut args = vec![
+        "build".to_string(),
+        "-f".to_string(),
+        dockerfile.to_string_lossy().to_string(),
+    ];
+    if let Some(tag) = tag {
+        args.push("-t".to_string());
+        args.push(tag);
+    }
+    args.push(context.to_string_lossy().to_string());
+    let cwd = context.to_string_lossy().to_string();
+    run_process(Some(window), &build_id, Some(&cwd), "docker", &args, &HashMap::new(), None).await
+}
+
+/// The services a compose file defines.
+#[tauri::command]
+pub async fn docker_compose_services(
+    compose_file: String,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<String>, String> {
+    let compose_file = workspace.check(&compose_file)?;
+    let args = vec!["config".to_string(), "--services".to_string()];
+    let result = run_compose(None, "docker-compose-services", &compose_file, args, Some(QUERY_TIMEOUT_MS)).await?;
+    let result = require_success(result, "docker compose config")?;
+    Ok(result.stdout.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect())
+}
+
+/// Starts `services` (all when omitted) in the background, building images
+/// as needed. Progress streams as `command-output` events tagged with
+/// `command_id`.
+#[tauri::command]
+pub async fn docker_compose_up(
+    window: Window,
+    command_id: String,
+    compose_file: String,
+    services: Option<Vec<String>>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<CommandResult, String> {
+    let compose_file = workspace.check(&compose_file)?;
+    let mut args = vec!["up".to_string(), "-d".to_string()];
+    args.extend(services.unwrap_or_default());
+    run_compose(Some(window), &command_id, &compose_file, args, None).await
+}
+
+/// Stops `services` (all when omitted). With `remove`, their containers
+/// are removed too (`down` for the whole project).
+#[tauri::command]
+pub async fn docker_compose_stop(
+    window: Window,
+    command_id: String,
+    compose_file: String,
+    services: Option<Vec<String>>,
+    remove: Option<bool>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<CommandResult, String> {
+    let compose_file = workspace.check(&compose_file)?;
+    let services = services.unwrap_or_default();
+    let mut args = match (remove.unwrap_or(false), services.is_empty()) {
+        (true, true) => vec!["down".to_string()],
+        (true, false) => vec!["rm".to_string(), "--stop".to_string(), "--force".to_string()],
+        (false, _) => vec!["stop".to_string()],
+    };
+    args.extend(services);
+    run_compose(Some(window), &command_id, &compose_file, args, None).await
+}
+
+/// Follows the logs of `services` (all when omitted) as `command-output`
+/// events tagged with `log_id` until `docker_stop_logs` is called or the
+/// services stop.
+#[tauri::command]
+pub async fn docker_compose_logs(
+    window: Window,
+    log_id: String,
+    compose_file: String,
+    services: Option<Vec<String>>,
+    tail: Option<usize>,
+    state: State<'_, DockerState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<(), String> {
+    let compose_file = workspace.check(&compose_file)?;
+    let mut args = vec![
+        "logs".to_string(),
+        "--follow".to_string(),
+        "--tail".to_string(),
+        tail.unwrap_or(DEFAULT_LOG_TAIL).to_string(),
+    ];
+    args.extend(services.unwrap_or_default());
+
+    if state.logs.lock_or_recover().contains_key(&log_id) {
+        return Err(format!("Log stream already running: {}", log_id));
+    }
+    let id = log_id.clone();
+    let handle = tokio::spawn(async move { run_compose(Some(window), &id, &compose_file, args, None).await });
+    state.logs.lock_or_recover().insert(log_id.clone(), handle.abort_handle());
+    let outcome = handle.await;
+    state.logs.lock_or_recover().remove(&log_id);
+    match outcome {
+        Ok(result) => result.map(|_| ()),
+        Err(e) if e.is_cancelled() => Ok(()),
+        Err(e) => Err(format!("Log stream failed: {}", e)),
+    }
+}
+
+/// Aborting the follower drops its process, which `kill_on_drop` kills.
+#[tauri::command]
+pub fn docker_stop_logs(log_id: String, state: State<'_, DockerState>) -> Result<(), String> {
+    let handle = state
+        .logs
+        .lock_or_recover()
+        .remove(&log_id)
+        .ok_or_else(|| format!("Log stream not found: {}", log_id))?;
+    handle.abort();
+    Ok(())
+}
+
+/// Containers known to Docker, running or not (`all`), with the compose
+/// project and service they belong to.
+#[tauri::command]
+pub async fn docker_list_containers(all: Option<bool>) -> Result<Vec<DockerContainer>, String> {
+    let mut args = vec!["ps".to_string(), "--format".to_string(), "{{json .}}".to_string()];
+    if all.unwrap_or(false) {
+        args.push("--all".to_string());
+    }
+    let result = run_process(None, "docker-ps", None, "docker", &args, &HashMap::new(), Some(QUERY_TIMEOUT_MS)).await?;
+    let result = require_success(result, "docker ps")?;
+    Ok(json_records(&result.stdout)
+        .iter()
+        .map(|record| {
+            let labels = field(record, "Labels");
+            DockerContainer {
+                id: field(record, "ID"),
+                name: field(record, "Names"),
+                image: field(record, "Image"),
+                state: field(record, "State"),
+                status: field(record, "Status"),
+                ports: field(record, "Ports"),
+                compose_project: label(&labels, "com.docker.compose.project"),
+                compose_service: label(&labels, "com.docker.compose.service"),
+            }
+        })
+        .collect())
+}
+
+/// Opens an interactive shell inside a running container in a new terminal
+/// (`docker exec -it`); `shell` defaults to bash, falling back to sh.
+#[tauri::command]
+pub async fn docker_open_terminal(
+    window: Window,
+    terminal_id: String,
+    container: String,
+    shell: Option<String>,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    if on_path("docker").is_none() {
+        return Err("docker not found on PATH".to_string());
+    }
+    let mut args = vec!["exec".to_string(), "-it".to_string(), container.clone()];
+    match shell {
+        Some(shell) => args.push(shell),
+        None => args.extend(["sh".to_string(), "-c".to_string(), CONTAINER_SHELL.to_string()]),
+    }
+    let options = TerminalOptions {
+        title: Some(container),
+        shell: Some("docker".to_string()),
+        args,
+        ..Default::default()
+    };
+    spawn_terminal(&window, &state, terminal_id, None, None, options, None)
+}
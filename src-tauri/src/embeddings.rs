@@ -0,0 +1,667 @@
+use git2::Repository;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Manager, State};
+
+use crate::chunker::{chunk_source, estimate_tokens, ChunkOptions, CodeChunk};
+use crate::event_bus::emit_indexing_finished;
+use crate::explorer::{language_for, walk_entries, DirectoryOptions};
+use crate::files::read_text;
+use crate::finder::{is_excluded, relative_path};
+use crate::git::path_ignored;
+use crate::llm::{LlmRequestRecord, LlmState, LoggedPrompt};
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+use crate::ParserState;
+
+const EMBED_BATCH: usize = 32;
+const MAX_EMBEDDED_BYTES: u64 = 1024 * 1024;
+const WATCH_DEBOUNCE: Duration = Duration::from_millis(500);
+
+// ============================================================================
+// EMBEDDING INDEX STATE
+// ============================================================================
+
+/// Chunk embeddings of the open project. Vectors are stored once per
+/// (model, chunk content hash), so unchanged chunks are never embedded
+/// twice, even after a file moves.
+pub struct EmbeddingIndexState {
+    conn: Mutex<Connection>,
+    project: Mutex<Option<EmbeddingProject>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+    activity: Mutex<Activity>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct EmbeddingProject {
+    pub(crate) root: PathBuf,
+    pub(crate) model: String,
+}
+
+#[derive(Default)]
+struct Activity {
+    /// Files changed on disk that the watcher hasn't re-embedded yet.
+    pending: BTreeSet<String>,
+    updating: bool,
+    last_error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EmbeddingIndexStatus {
+    pub root: Option<String>,
+    pub model: Option<String>,
+    pub files: usize,
+    pub chunks: usize,
+    pub embedded: usize,
+    /// Chunks without a vector plus files changed since they were embedded.
+    pub stale: usize,
+    pub updating: bool,
+    pub last_update: Option<i64>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct OllamaEmbedRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Deserialize)]
+struct OllamaEmbedResponse {
+    embeddings: Vec<Vec<f32>>,
+    prompt_eval_count: Option<u64>,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn modified_nanos(path: &Path) -> i64 {
+    std_fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as i64)
+        .unwrap_or(0)
+}
+
+fn encode_vector(vector: &[f32]) -> Vec<u8> {
+    vector.iter().flat_map(|v| v.to_le_bytes()).collect()
+}
+
+fn decode_vector(bytes: &[u8]) -> Vec<f32> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+        .collect()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm = |v: &[f32]| v.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let denominator = norm(a) * norm(b);
+    if denominator == 0.0 {
+        0.0
+    } else {
+        dot / denominator
+    }
+}
+
+/// Source files worth embedding.
+fn is_embeddable(path: &Path) -> bool {
+    language_for(path).is_some() && std_fs::metadata(path).is_ok_and(|m| m.is_file() && m.len() <= MAX_EMBEDDED_BYTES)
+}
+
+impl EmbeddingIndexState {
+    pub fn open(db_path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(db_path)
+            .map_err(|e| format!("Failed to open embedding database: {}", e))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS projects (
+                 root TEXT PRIMARY KEY,
+                 model TEXT NOT NULL,
+                 last_update INTEGER
+             );
+             CREATE TABLE IF NOT EXISTS files (
+                 root TEXT NOT NULL,
+                 relative_path TEXT NOT NULL,
+                 modified INTEGER NOT NULL,
+                 PRIMARY KEY (root, relative_path)
+             );
+             CREATE TABLE IF NOT EXISTS chunks (
+                 root TEXT NOT NULL,
+                 relative_path TEXT NOT NULL,
+                 chunk_index INTEGER NOT NULL,
+                 start_line INTEGER NOT NULL,
+                 end_line INTEGER NOT NULL,
+                 kind TEXT NOT NULL,
+                 symbols TEXT NOT NULL,
+                 content_hash TEXT NOT NULL,
+                 text TEXT NOT NULL,
+                 PRIMARY KEY (root, relative_path, chunk_index)
+             );
+             CREATE INDEX IF NOT EXISTS idx_chunks_hash ON chunks(content_hash);
+             CREATE TABLE IF NOT EXISTS vectors (
+                 model TEXT NOT NULL,
+                 content_hash TEXT NOT NULL,
+                 vector BLOB NOT NULL,
+                 PRIMARY KEY (model, content_hash)
+             );",
+        )
+        .map_err(|e| format!("Failed to initialize embedding database: {}", e))?;
+
+        Ok(EmbeddingIndexState {
+            conn: Mutex::new(conn),
+            project: Mutex::new(None),
+            watcher: Mutex::new(None),
+            activity: Mutex::new(Activity::default()),
+        })
+    }
+
+    pub(crate) fn project(&self) -> Result<EmbeddingProject, String> {
+        self.project
+            .lock_or_recover()
+            .clone()
+            .ok_or_else(|| "No embedding index; call index_embeddings first".to_string())
+    }
+
+    fn indexed_files(&self, root: &str) -> Result<HashMap<String, i64>, String> {
+        let conn = self.conn.lock_or_recover();
+        let mut stmt = conn
+            .prepare("SELECT relative_path, modified FROM files WHERE root = ?1")
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![root], |row| Ok((row.get(0)?, row.get(1)?)))
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<HashMap<_, _>, _>>().map_err(|e| e.to_string())
+    }
+
+    fn remove_file(&self, root: &str, relative: &str) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        conn.execute("DELETE FROM chunks WHERE root = ?1 AND relative_path = ?2", params![root, relative])
+            .and_then(|_| conn.execute("DELETE FROM files WHERE root = ?1 AND relative_path = ?2", params![root, relative]))
+            .map(|_| ())
+            .map_err(|e| format!("Failed to remove {} from the embedding index: {}", relative, e))
+    }
+
+    fn store_chunks(&self, root: &str, relative: &str, modified: i64, chunks: &[CodeChunk]) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        tx.execute("DELETE FROM chunks WHERE root = ?1 AND relative_path = ?2", params![root, relative])
+            .map_err(|e| e.to_string())?;
+        for chunk in chunks {
+            let symbols = serde_json::to_string(&chunk.symbols).map_err(|e| e.to_string())?;
+            tx.execute(
+                "INSERT INTO chunks (root, relative_path, chunk_index, start_line, end_line, kind, symbols, content_hash, text)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    root,
+                    relative,
+                    chunk.index as i64,
+                    chunk.start_line as i64,
+                    chunk.end_line as i64,
+                    chunk.kind,
+                    symbols,
+                    chunk.content_hash,
+                    chunk.text
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.execute(
+            "INSERT INTO files (root, relative_path, modified) VALUES (?1, ?2, ?3)
+             ON CONFLICT(root, relative_path) DO UPDATE SET modified = ?3",
+            params![root, relative, modified],
+        )
+        .map_err(|e| e.to_string())?;
+        tx.commit()
+            .map_err(|e| format!("Failed to store chunks of {}: {}", relative, e))
+    }
+
+    /// Re-chunks one file, or drops it from the index if it's gone or no
+    /// longer source.
+    fn refresh_file(&self, parser: &ParserState, root: &Path, relative: &str) -> Result<(), String> {
+        let root_key = root.to_string_lossy();
+        let path = root.join(relative);
+        if !is_embeddable(&path) {
+            return self.remove_file(&root_key, relative);
+        }
+        let Ok((content, _)) = read_text(&path) else {
+            return self.remove_file(&root_key, relative);
+        };
+        let chunks = chunk_source(parser, &path.to_string_lossy(), &content, &ChunkOptions::default());
+        self.store_chunks(&root_key, relative, modified_nanos(&path), &chunks)
+    }
+
+    /// Re-chunks every file changed on disk since it was indexed and drops
+    /// deleted ones.
+    fn sync_chunks(
+        &self,
+        parser: &ParserState,
+        root: &Path,
+        mut on_file: impl FnMut(usize, usize) -> Result<(), String>,
+    ) -> Result<(), String> {
+        let root_key = root.to_string_lossy().to_string();
+        let mut indexed = self.indexed_files(&root_key)?;
+        let files: Vec<PathBuf> = walk_entries(root, &DirectoryOptions::default(), None)?
+            .into_iter()
+            .filter(|(path, is_dir)| !is_dir && is_embeddable(path))
+            .map(|(path, _)| path)
+            .collect();
+        for (i, path) in files.iter().enumerate() {
+            if let Some(relative) = relative_path(root, path) {
+                if indexed.remove(&relative) != Some(modified_nanos(path)) {
+                    self.refresh_file(parser, root, &relative)?;
+                }
+            }
+            on_file(i + 1, files.len())?;
+        }
+        for relative in indexed.keys() {
+            self.remove_file(&root_key, relative)?;
+        }
+        Ok(())
+    }
+
+    fn count_missing(&self, project: &EmbeddingProject) -> Result<usize, String> {
+        let conn = self.conn.lock_or_recover();
+        let count: i64 = conn
+            .query_row(
+                "SELECT COUNT(DISTINCT c.content_hash) FROM chunks c
+                 LEFT JOIN vectors v ON v.model = ?2 AND v.content_hash = c.content_hash
+                 WHERE c.root = ?1 AND v.content_hash IS NULL",
+                params![project.root.to_string_lossy(), project.model],
+                |row| row.get(0),
+            )
+            .map_err(|e| e.to_string())?;
+        Ok(count as usize)
+    }
+
+    /// (content hash, text) of chunks that have no vector for `model` yet.
+    fn missing_vectors(&self, project: &EmbeddingProject, limit: usize) -> Result<Vec<(String, String)>, String> {
+        let conn = self.conn.lock_or_recover();
+        let mut stmt = conn
+            .prepare(
+                "SELECT c.content_hash, MIN(c.text) FROM chunks c
+                 LEFT JOIN vectors v ON v.model = ?2 AND v.content_hash = c.content_hash
+                 WHERE c.root = ?1 AND v.content_hash IS NULL
+                 GROUP BY c.content_hash LIMIT ?3",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(
+                params![project.root.to_string_lossy(), project.model, limit as i64],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    }
+
+    fn store_vectors(&self, model: &str, vectors: &[(String, Vec<f32>)]) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        let tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+        for (hash, vector) in vectors {
+            tx.execute(
+                "INSERT OR REPLACE INTO vectors (model, content_hash, vector) VALUES (?1, ?2, ?3)",
+                params![model, hash, encode_vector(vector)],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+        tx.commit().map_err(|e| format!("Failed to store embeddings: {}", e))
+    }
+
+    /// Drops vectors no chunk points to any more, and those of models no
+    /// project uses.
+    fn collect_garbage(&self) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        conn.execute_batch(
+            "DELETE FROM vectors WHERE content_hash NOT IN (SELECT content_hash FROM chunks);
+             DELETE FROM vectors WHERE model NOT IN (SELECT model FROM projects);",
+        )
+        .map_err(|e| format!("Failed to clean up embeddings: {}", e))
+    }
+
+    fn set_project(&self, project: &EmbeddingProject) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        conn.execute(
+            "INSERT INTO projects (root, model) VALUES (?1, ?2)
+             ON CONFLICT(root) DO UPDATE SET model = ?2",
+            params![project.root.to_string_lossy(), project.model],
+        )
+        .map_err(|e| e.to_string())?;
+        *self.project.lock_or_recover() = Some(project.clone());
+        Ok(())
+    }
+
+    fn mark_updated(&self, root: &Path) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        conn.execute(
+            "UPDATE projects SET last_update = ?2 WHERE root = ?1",
+            params![root.to_string_lossy(), now_secs()],
+        )
+        .map(|_| ())
+        .map_err(|e| e.to_string())
+    }
+
+    /// The `limit` chunks most similar to `vector`, best first, as
+    /// (relative path, chunk, cosine similarity).
+    pub(crate) fn nearest(
+        &self,
+        project: &EmbeddingProject,
+        vector: &[f32],
+        limit: usize,
+    ) -> Result<Vec<(String, CodeChunk, f32)>, String> {
+        let conn = self.conn.lock_or_recover();
+        let mut stmt = conn
+            .prepare(
+                "SELECT c.relative_path, c.chunk_index, c.start_line, c.end_line, c.kind, c.symbols, c.content_hash, c.text, v.vector
+                 FROM chunks c JOIN vectors v ON v.model = ?2 AND v.content_hash = c.content_hash
+                 WHERE c.root = ?1",
+            )
+            .map_err(|e| e.to_string())?;
+        let rows = stmt
+            .query_map(params![project.root.to_string_lossy(), project.model], |row| {
+                let symbols: String = row.get(5)?;
+                let text: String = row.get(7)?;
+                let stored: Vec<u8> = row.get(8)?;
+                let chunk = CodeChunk {
+                    index: row.get::<_, i64>(1)? as usize,
+                    start_line: row.get::<_, i64>(2)? as usize,
+                    end_line: row.get::<_, i64>(3)? as usize,
+                    kind: row.get(4)?,
+                    symbols: serde_json::from_str(&symbols).unwrap_or_default(),
+                    header: None,
+                    tokens: estimate_tokens(&text),
+                    content_hash: row.get(6)?,
+                    text,
+                };
+                let similarity = cosine_similarity(vector, &decode_vector(&stored));
+                Ok((row.get(0)?, chunk, similarity))
+            })
+            .map_err(|e| e.to_string())?;
+
+        let mut scored = rows.collect::<Result<Vec<(String, CodeChunk, f32)>, _>>().map_err(|e| e.to_string())?;
+        scored.sort_by(|a, b| b.2.total_cmp(&a.2));
+        scored.truncate(limit);
+        Ok(scored)
+    }
+
+    fn status(&self) -> Result<EmbeddingIndexStatus, String> {
+        let project = self.project.lock_or_recover().clone();
+        let activity = self.activity.lock_or_recover();
+        let mut status = EmbeddingIndexStatus {
+            root: None,
+            model: None,
+            files: 0,
+            chunks: 0,
+            embedded: 0,
+            stale: activity.pending.len(),
+            updating: activity.updating,
+            last_update: None,
+            last_error: activity.last_error.clone(),
+        };
+        drop(activity);
+        let Some(project) = project else {
+            return Ok(status);
+        };
+
+        let root = project.root.to_string_lossy().to_string();
+        let conn = self.conn.lock_or_recover();
+        let (files, chunks, embedded): (i64, i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM files WHERE root = ?1),
+                        (SELECT COUNT(*) FROM chunks WHERE root = ?1),
+                        (SELECT COUNT(*) FROM chunks c JOIN vectors v ON v.model = ?2 AND v.content_hash = c.content_hash
+                         WHERE c.root = ?1)",
+                params![root, project.model],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .map_err(|e| e.to_string())?;
+        status.files = files as usize;
+        status.chunks = chunks as usize;
+        status.embedded = embedded as usize;
+        status.stale += status.chunks - status.embedded;
+        status.last_update = conn
+            .query_row("SELECT last_update FROM projects WHERE root = ?1", params![root], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())?
+            .flatten();
+        status.root = Some(root);
+        status.model = Some(project.model);
+        Ok(status)
+    }
+}
+
+// ============================================================================
+// EMBEDDING
+// ============================================================================
+
+/// Embeds `texts` with an Ollama embedding model, one vector per text.
+pub(crate) async fn embed_texts(llm: &LlmState, model: &str, texts: &[String]) -> Result<Vec<Vec<f32>>, String> {
+    let started = Instant::now();
+    let request = OllamaEmbedRequest { model, input: texts };
+    let result: Result<OllamaEmbedResponse, String> = async {
+        llm.post("/api/embed", &request)
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse embeddings: {}", e))
+    }
+    .await;
+
+    llm.log_request(LlmRequestRecord {
+        provider: "ollama",
+        model,
+        endpoint: "/api/embed",
+        prompt: LoggedPrompt::Summary(format!("[{} chunks]", texts.len())),
+        prompt_tokens: result.as_ref().ok().and_then(|r| r.prompt_eval_count),
+        completion_tokens: None,
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: result.as_ref().err().cloned(),
+    });
+
+    let embeddings = result?.embeddings;
+    if embeddings.len() != texts.len() {
+        return Err(format!("Expected {} embeddings, got {}", texts.len(), embeddings.len()));
+    }
+    Ok(embeddings)
+}
+
+/// Embeds every chunk of the project that has no vector yet, in batches,
+/// then drops vectors nothing refers to. `on_batch(done, total)` is called
+/// after each stored batch; an error from it stops before the next one.
+async fn embed_missing(
+    state: &EmbeddingIndexState,
+    llm: &LlmState,
+    project: &EmbeddingProject,
+    mut on_batch: impl FnMut(usize, usize) -> Result<(), String>,
+) -> Result<(), String> {
+    let total = state.count_missing(project)?;
+    let mut done = 0;
+    loop {
+        let missing = state.missing_vectors(project, EMBED_BATCH)?;
+        if missing.is_empty() {
+            break;
+        }
+        let (hashes, texts): (Vec<String>, Vec<String>) = missing.into_iter().unzip();
+        let vectors = embed_texts(llm, &project.model, &texts).await?;
+        state.store_vectors(&project.model, &hashes.into_iter().zip(vectors).collect::<Vec<_>>())?;
+        done += texts.len();
+        on_batch(done, total.max(done))?;
+    }
+    state.collect_garbage()?;
+    state.mark_updated(&project.root)
+}
+
+/// Re-chunks the watcher's changed paths and embeds what changed. Removed
+/// directories take everything below them along.
+fn apply_changes(app: &AppHandle, project: &EmbeddingProject, paths: &BTreeSet<PathBuf>) -> Result<(), String> {
+    let state = app.state::<EmbeddingIndexState>();
+    let parser = app.state::<ParserState>();
+    let root_key = project.root.to_string_lossy().to_string();
+    for path in paths {
+        let Some(relative) = relative_path(&project.root, path) else {
+            continue;
+        };
+        if path.is_dir() {
+            for (child, is_dir) in walk_entries(path, &DirectoryOptions::default(), None)? {
+                if let Some(child) = relative_path(&project.root, &child).filter(|_| !is_dir) {
+                    state.refresh_file(&parser, &project.root, &child)?;
+                }
+            }
+        } else if path.exists() {
+            state.refresh_file(&parser, &project.root, &relative)?;
+        } else {
+            let prefix = format!("{}/", relative);
+            for indexed in state.indexed_files(&root_key)?.into_keys() {
+                if indexed == relative || indexed.starts_with(&prefix) {
+                    state.remove_file(&root_key, &indexed)?;
+                }
+            }
+        }
+    }
+    let llm = app.state::<LlmState>();
+    tauri::async_runtime::block_on(embed_missing(&state, &llm, project, |_, _| Ok(())))
+}
+
+/// Feeds watcher events to a background thread that batches them for
+/// `WATCH_DEBOUNCE` before re-embedding.
+fn watch_embeddings(app: AppHandle, project: EmbeddingProject) -> Result<RecommendedWatcher, String> {
+    let (tx, rx) = mpsc::channel::<PathBuf>();
+    let root = project.root.clone();
+    let repo = Repository::discover(&root).ok();
+
+    let watcher_app = app.clone();
+    let watcher_root = root.clone();
+    std::thread::spawn(move || {
+        while let Ok(first) = rx.recv() {
+            let mut changed = BTreeSet::from([first]);
+            while let Ok(path) = rx.recv_timeout(WATCH_DEBOUNCE) {
+                changed.insert(path);
+            }
+            let state = app.state::<EmbeddingIndexState>();
+            state.activity.lock_or_recover().updating = true;
+            let result = apply_changes(&app, &project, &changed);
+            let mut activity = state.activity.lock_or_recover();
+            for path in &changed {
+                if let Some(relative) = relative_path(&project.root, path) {
+                    activity.pending.remove(&relative);
+                }
+            }
+            activity.updating = false;
+            activity.last_error = result.err();
+        }
+    });
+
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        let Ok(event) = result else {
+            return;
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+            return;
+        }
+        let state = watcher_app.state::<EmbeddingIndexState>();
+        for path in event.paths {
+            let Some(relative) = relative_path(&watcher_root, &path) else {
+                continue;
+            };
+            if relative.is_empty() || is_excluded(&relative) || repo.as_ref().is_some_and(|repo| path_ignored(repo, &path)) {
+                continue;
+            }
+            state.activity.lock_or_recover().pending.insert(relative);
+            let _ = tx.send(path);
+        }
+    })
+    .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+    watcher
+        .watch(&root, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", root.display(), e))?;
+    Ok(watcher)
+}
+
+// ============================================================================
+// EMBEDDING TAURI COMMANDS
+// ============================================================================
+
+/// Chunks and embeds the source files under `root` and keeps the vectors
+/// current as files change. Only chunks whose content changed are sent to
+/// the model. Without `model`, the `embedding` model route decides.
+#[tauri::command]
+pub async fn index_embeddings(
+    app: AppHandle,
+    root: String,
+    model: Option<String>,
+    state: State<'_, EmbeddingIndexState>,
+    llm: State<'_, LlmState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<EmbeddingIndexStatus, String> {
+    index_embeddings_with_progress(&app, &root, model, &state, &llm, &workspace, |_, _, _| Ok(())).await
+}
+
+/// `index_embeddings`, calling `on_progress(phase, done, total)` after each
+/// chunked file and each embedded batch; an error from it stops indexing.
+/// Whatever was stored by then is kept and picked up by the next run.
+pub(crate) async fn index_embeddings_with_progress(
+    app: &AppHandle,
+    root: &str,
+    model: Option<String>,
+    state: &EmbeddingIndexState,
+    llm: &LlmState,
+    workspace: &WorkspaceState,
+    mut on_progress: impl FnMut(&str, usize, usize) -> Result<(), String> + Send + 'static,
+) -> Result<EmbeddingIndexStatus, String> {
+    let root_path = workspace.check(root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+    let model = match model {
+        Some(model) => model,
+        None => llm.resolve_model("embedding").await?,
+    };
+    let project = EmbeddingProject { root: root_path, model };
+
+    state.watcher.lock_or_recover().take();
+    state.activity.lock_or_recover().pending.clear();
+    state.set_project(&project)?;
+
+    let chunk_app = app.clone();
+    let chunk_root = project.root.clone();
+    let mut on_progress = tokio::task::spawn_blocking(move || {
+        let state = chunk_app.state::<EmbeddingIndexState>();
+        let parser = chunk_app.state::<ParserState>();
+        state.sync_chunks(&parser, &chunk_root, |done, total| on_progress("Chunking files", done, total))?;
+        Ok::<_, String>(on_progress)
+    })
+    .await
+    .map_err(|e| format!("Chunking task failed: {}", e))??;
+
+    state.activity.lock_or_recover().updating = true;
+    let result = embed_missing(state, llm, &project, |done, total| on_progress("Embedding chunks", done, total)).await;
+    {
+        let mut activity = state.activity.lock_or_recover();
+        activity.updating = false;
+        activity.last_error = result.as_ref().err().cloned();
+    }
+    result?;
+
+    *state.watcher.lock_or_recover() = Some(watch_embeddings(app.clone(), project)?);
+    let status = state.status()?;
+    emit_indexing_finished(app, "embeddings", &status);
+    Ok(status)
+}
+
+/// Size and freshness of the embedding index.
+#[tauri::command]
+pub fn get_embedding_index_status(state: State<'_, EmbeddingIndexState>) -> Result<EmbeddingIndexStatus, String> {
+    state.status()
+}
@@ -0,0 +1,325 @@
+use futures::{SinkExt, StreamExt};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::sync::Mutex;
+use tauri::{AppHandle, Emitter, EventId, Listener, Manager, State};
+use tokio::io::AsyncWriteExt;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::task::AbortHandle;
+use tokio_tungstenite::tungstenite::handshake::derive_accept_key;
+use tokio_tungstenite::tungstenite::protocol::Role;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::code_index::{index_project, CodeIndexState};
+use crate::locks::LockExt;
+use crate::mcp::{allowed_origin, is_read_only, random_token, read_request, respond, HttpRequest};
+use crate::sandbox::WorkspaceState;
+use crate::{run_read_only_cypher, Neo4jState};
+
+/// App events forwarded to bus clients.
+const BROADCAST_EVENTS: &[&str] = &[
+    "graph-updated",
+    "indexing-finished",
+    "job-progress",
+    "diagnostics-updated",
+    "task-status",
+    "test-run-event",
+    "dev-process-status",
+    "linter-status",
+    "plugin-started",
+    "plugin-exited",
+    "external-event",
+];
+/// Events a slow client can fall behind before it starts missing some.
+const EVENT_BUFFER: usize = 256;
+
+// ============================================================================
+// EVENT BUS STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct EventBusInfo {
+    pub port: u16,
+    /// WebSocket endpoint that pushes events and accepts commands.
+    pub websocket_url: String,
+    /// `GET /events` streams events as SSE, `POST /command` runs one command.
+    pub http_url: String,
+    /// Sent as `Authorization: Bearer <token>` or a `token` query parameter.
+    pub token: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct BusEvent {
+    event: String,
+    payload: Value,
+}
+
+struct RunningBus {
+    info: EventBusInfo,
+    abort: AbortHandle,
+    listeners: Vec<EventId>,
+}
+
+/// The local event bus server, when running.
+#[derive(Default)]
+pub struct EventBusState {
+    server: Mutex<Option<RunningBus>>,
+}
+
+impl EventBusState {
+    fn stop(&self, app: &AppHandle) {
+        if let Some(server) = self.server.lock_or_recover().take() {
+            server.abort.abort();
+            // Dropping the listeners drops the senders, which ends every open stream
+            for id in server.listeners {
+                app.unlisten(id);
+            }
+        }
+    }
+}
+
+/// Emitted whenever the Neo4j graph changes; `root` is absent when the whole
+/// graph was replaced.
+pub(crate) fn emit_graph_updated(app: &AppHandle, source: &str, root: Option<&str>, nodes: usize) {
+    let _ = app.emit("graph-updated", json!({ "source": source, "root": root, "nodes": nodes }));
+}
+
+/// Emitted when one of the project indexes has been (re)built.
+pub(crate) fn emit_indexing_finished<T: Serialize>(app: &AppHandle, kind: &str, summary: &T) {
+    let _ = app.emit("indexing-finished", json!({ "kind": kind, "summary": summary }));
+}
+
+// ============================================================================
+// EVENT BUS SERVER
+// ============================================================================
+
+async fn run_command(app: &AppHandle, command: &str, args: &Value) -> Result<Value, String> {
+    let arg = |name: &str| {
+        args.get(name)
+            .and_then(Value::as_str)
+            .ok_or_else(|| format!("{} is required", name))
+    };
+    match command {
+        "ping" => Ok(json!("pong")),
+        "list_events" => Ok(json!(BROADCAST_EVENTS)),
+        "query_graph" => {
+            let cypher = arg("cypher")?;
+            if !is_read_only(cypher) {
+                return Err("Only read-only queries are allowed".to_string());
+            }
+            let graph = app.state::<Neo4jState>().get_graph().await?;
+            let result = run_read_only_cypher(&graph, cypher).await?;
+            serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+        }
+        "index_project" => {
+            let root = arg("root")?.to_string();
+            let summary = index_project(
+                app.clone(),
+                root,
+                app.state::<CodeIndexState>(),
+                app.state::<WorkspaceState>(),
+            )
+            .await?;
+            serde_json::to_value(summary).map_err(|e| format!("Failed to serialize result: {}", e))
+        }
+        "open_file" => {
+            let path = app.state::<WorkspaceState>().check(arg("path")?)?;
+            let line = args.get("line").and_then(Value::as_u64);
+            let _ = app.emit("open-file-requested", json!({ "path": path.to_string_lossy(), "line": line }));
+            Ok(Value::Null)
+        }
+        // Relayed to the app and every bus client
+        "publish" => {
+            let event = arg("event")?;
+            let payload = args.get("payload").cloned().unwrap_or(Value::Null);
+            let _ = app.emit("external-event", json!({ "event": event, "payload": payload }));
+            Ok(Value::Null)
+        }
+        _ => Err(format!("Unknown command: {}", command)),
+    }
+}
+
+/// Commands are `{"id", "command", "args"}`; the reply echoes `id` with
+/// either `result` or `error`.
+async fn handle_command(app: &AppHandle, message: &[u8]) -> Value {
+    let Ok(message) = serde_json::from_slice::<Value>(message) else {
+        return json!({ "id": null, "error": "Invalid JSON" });
+    };
+    let id = message.get("id").cloned().unwrap_or(Value::Null);
+    let command = message.get("command").and_then(Value::as_str).unwrap_or_default();
+    let args = message.get("args").cloned().unwrap_or_else(|| json!({}));
+    match run_command(app, command, &args).await {
+        Ok(result) => json!({ "id": id, "result": result }),
+        Err(error) => json!({ "id": id, "error": error }),
+    }
+}
+
+fn authorized(request: &HttpRequest, token: &str) -> bool {
+    let query = request.target.split_once('?').map(|(_, query)| query).unwrap_or_default();
+    let query_token = query.split('&').find_map(|pair| pair.strip_prefix("token="));
+    let header_token = request
+        .headers
+        .get("authorization")
+        .and_then(|value| value.strip_prefix("Bearer "));
+    header_token.or(query_token) == Some(token)
+}
+
+async fn stream_websocket(app: AppHandle, stream: TcpStream, mut events: broadcast::Receiver<BusEvent>) {
+    let (mut sink, mut source) = WebSocketStream::from_raw_socket(stream, Role::Server, None).await.split();
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let message = match event {
+                    Ok(event) => json!(event),
+                    Err(RecvError::Lagged(skipped)) => json!({ "event": "events-dropped", "payload": skipped }),
+                    Err(RecvError::Closed) => break,
+                };
+                if sink.send(Message::Text(message.to_string())).await.is_err() {
+                    break;
+                }
+            }
+            message = source.next() => match message {
+                Some(Ok(Message::Text(text))) => {
+                    let reply = handle_command(&app, text.as_bytes()).await;
+                    if sink.send(Message::Text(reply.to_string())).await.is_err() {
+                        break;
+                    }
+                }
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+    let _ = sink.close().await;
+}
+
+async fn stream_sse(mut stream: TcpStream, mut events: broadcast::Receiver<BusEvent>) {
+    let headers = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\n\r\n";
+    if stream.write_all(headers.as_bytes()).await.is_err() {
+        return;
+    }
+    loop {
+        let (name, payload) = match events.recv().await {
+            Ok(event) => (event.event, event.payload),
+            Err(RecvError::Lagged(skipped)) => ("events-dropped".to_string(), json!(skipped)),
+            Err(RecvError::Closed) => break,
+        };
+        let chunk = format!("event: {}\ndata: {}\n\n", name, payload);
+        if stream.write_all(chunk.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+    let _ = stream.shutdown().await;
+}
+
+async fn handle_connection(
+    app: AppHandle,
+    token: String,
+    mut stream: TcpStream,
+    events: broadcast::Receiver<BusEvent>,
+) {
+    let Some(request) = read_request(&mut stream).await else {
+        return;
+    };
+    if request.headers.get("origin").is_some_and(|origin| !allowed_origin(origin)) {
+        return respond(&mut stream, "403 Forbidden", None).await;
+    }
+    if !authorized(&request, &token) {
+        return respond(&mut stream, "401 Unauthorized", None).await;
+    }
+
+    let path = request.target.split('?').next().unwrap_or_default();
+    match (request.method.as_str(), path) {
+        ("GET", "/ws") => {
+            let Some(key) = request.headers.get("sec-websocket-key") else {
+                return respond(&mut stream, "400 Bad Request", None).await;
+            };
+            let handshake = format!(
+                "HTTP/1.1 101 Switching Protocols\r\nUpgrade: websocket\r\nConnection: Upgrade\r\n\
+                 Sec-WebSocket-Accept: {}\r\n\r\n",
+                derive_accept_key(key.as_bytes())
+            );
+            if stream.write_all(handshake.as_bytes()).await.is_ok() {
+                stream_websocket(app, stream, events).await;
+            }
+        }
+        ("GET", "/events") => stream_sse(stream, events).await,
+        ("POST", "/command") => {
+            let reply = handle_command(&app, &request.body).await;
+            let status = if reply.get("error").is_some() { "400 Bad Request" } else { "200 OK" };
+            respond(&mut stream, status, Some(&reply.to_string())).await;
+        }
+        _ => respond(&mut stream, "404 Not Found", None).await,
+    }
+}
+
+// ============================================================================
+// EVENT BUS TAURI COMMANDS
+// ============================================================================
+
+/// Starts a local server on 127.0.0.1 (an ephemeral port unless `port` is
+/// given) that broadcasts app events and accepts commands from external
+/// scripts and editor plugins. Restarting replaces the previous server and
+/// token and disconnects its clients.
+#[tauri::command]
+pub async fn event_bus_start(
+    app: AppHandle,
+    port: Option<u16>,
+    state: State<'_, EventBusState>,
+) -> Result<EventBusInfo, String> {
+    state.stop(&app);
+    let listener = TcpListener::bind(("127.0.0.1", port.unwrap_or(0)))
+        .await
+        .map_err(|e| format!("Failed to start event bus: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start event bus: {}", e))?
+        .port();
+    let info = EventBusInfo {
+        websocket_url: format!("ws://127.0.0.1:{}/ws", port),
+        http_url: format!("http://127.0.0.1:{}", port),
+        token: random_token(),
+        port,
+    };
+
+    let (sender, _) = broadcast::channel::<BusEvent>(EVENT_BUFFER);
+    let listeners = BROADCAST_EVENTS
+        .iter()
+        .map(|name| {
+            let (name, sender) = (name.to_string(), sender.clone());
+            app.listen_any(name.clone(), move |event| {
+                let payload = serde_json::from_str(event.payload()).unwrap_or(Value::Null);
+                let _ = sender.send(BusEvent {
+                    event: name.clone(),
+                    payload,
+                });
+            })
+        })
+        .collect();
+
+    let token = info.token.clone();
+    let accept_app = app.clone();
+    let task = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle_connection(accept_app.clone(), token.clone(), stream, sender.subscribe()));
+        }
+    });
+    *state.server.lock_or_recover() = Some(RunningBus {
+        info: info.clone(),
+        abort: task.abort_handle(),
+        listeners,
+    });
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn event_bus_stop(app: AppHandle, state: State<'_, EventBusState>) {
+    state.stop(&app);
+}
+
+#[tauri::command]
+pub fn event_bus_info(state: State<'_, EventBusState>) -> Option<EventBusInfo> {
+    state.server.lock_or_recover().as_ref().map(|s| s.info.clone())
+}
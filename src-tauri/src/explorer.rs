@@ -0,0 +1,349 @@
+use serde::Serialize;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+pub use gencode_core::walk::DirectoryOptions;
+pub(crate) use gencode_core::walk::{walk_entries, IGNORED_DIRS};
+
+use crate::git::path_ignored;
+use crate::sandbox::WorkspaceState;
+use crate::DirEntryInfo;
+
+const DEFAULT_PAGE_SIZE: usize = 500;
+const DEFAULT_LARGEST_FILES: usize = 10;
+const MAX_COUNTED_BYTES: u64 = 4 * 1024 * 1024;
+
+/// (language, extensions, line comment markers, block comment delimiters)
+pub(crate) type LanguageSpec = (&'static str, &'static [&'static str], &'static [&'static str], Option<(&'static str, &'static str)>);
+
+const LANGUAGES: &[LanguageSpec] = &[
+    ("Rust", &["rs"], &["//"], Some(("/*", "*/"))),
+    ("TypeScript", &["ts", "tsx", "mts", "cts"], &["//"], Some(("/*", "*/"))),
+    ("JavaScript", &["js", "jsx", "mjs", "cjs"], &["//"], Some(("/*", "*/"))),
+    ("Python", &["py", "pyw"], &["#"], None),
+    ("Java", &["java"], &["//"], Some(("/*", "*/"))),
+    ("Go", &["go"], &["//"], Some(("/*", "*/"))),
+    ("C", &["c", "h"], &["//"], Some(("/*", "*/"))),
+    ("C++", &["cpp", "cc", "cxx", "hpp", "hxx"], &["//"], Some(("/*", "*/"))),
+    ("C#", &["cs"], &["//"], Some(("/*", "*/"))),
+    ("Kotlin", &["kt", "kts"], &["//"], Some(("/*", "*/"))),
+    ("Swift", &["swift"], &["//"], Some(("/*", "*/"))),
+    ("Ruby", &["rb"], &["#"], None),
+    ("PHP", &["php"], &["//", "#"], Some(("/*", "*/"))),
+    ("Shell", &["sh", "bash", "zsh"], &["#"], None),
+    ("HTML", &["html", "htm"], &[], Some(("<!--", "-->"))),
+    ("CSS", &["css", "scss", "less"], &[], Some(("/*", "*/"))),
+    ("SQL", &["sql"], &["--"], Some(("/*", "*/"))),
+    ("JSON", &["json"], &[], None),
+    ("YAML", &["yaml", "yml"], &["#"], None),
+    ("TOML", &["toml"], &["#"], None),
+    ("Markdown", &["md", "markdown"], &[], None),
+];
+
+// ============================================================================
+// DIRECTORY WALKING
+// ============================================================================
+
+fn entry_info(path: &Path, is_dir: bool, repo: Option<&git2::Repository>) -> DirEntryInfo {
+    DirEntryInfo {
+        name: file_name(path),
+        path: path.to_string_lossy().to_string(),
+        is_dir,
+        is_symlink: path.symlink_metadata().is_ok_and(|m| m.file_type().is_symlink()),
+        ignored: repo.is_some_and(|repo| path_ignored(repo, path)),
+        children: None,
+    }
+}
+
+fn assemble_tree(
+    dir: &Path,
+    groups: &mut HashMap<PathBuf, Vec<(PathBuf, bool)>>,
+    repo: Option<&git2::Repository>,
+    follow_links: bool,
+) -> Vec<DirEntryInfo> {
+    let mut children = groups.remove(dir).unwrap_or_default();
+    children.sort_by(|a, b| compare_entries(a.1, &file_name(&a.0), b.1, &file_name(&b.0)));
+
+    children
+        .into_iter()
+        .map(|(path, is_dir)| {
+            let mut info = entry_info(&path, is_dir, repo);
+            // Unfollowed links keep `children: None`; they weren't read
+            if is_dir && (follow_links || !info.is_symlink) {
+                info.children = Some(assemble_tree(&path, groups, repo, follow_links));
+            }
+            info
+        })
+        .collect()
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// The full tree below `dir`, sorted like the explorer shows it.
+pub(crate) fn read_tree(dir: &Path, options: &DirectoryOptions) -> Result<Vec<DirEntryInfo>, String> {
+    let mut groups: HashMap<PathBuf, Vec<(PathBuf, bool)>> = HashMap::new();
+    for (path, is_dir) in walk_entries(dir, options, None)? {
+        if let Some(parent) = path.parent() {
+            groups.entry(parent.to_path_buf()).or_default().push((path, is_dir));
+        }
+    }
+
+    // Ignored entries are only listed (and worth flagging) with show_ignored
+    let repo = if options.show_ignored {
+        git2::Repository::discover(dir).ok()
+    } else {
+        None
+    };
+    Ok(assemble_tree(dir, &mut groups, repo.as_ref(), options.follow_links))
+}
+
+// ============================================================================
+// LAZY DIRECTORY LISTING
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct DirectoryPage {
+    pub entries: Vec<DirEntryInfo>,
+    /// Pass back as `continuation` to read the next page; None on the last one.
+    pub next_token: Option<String>,
+    pub total: usize,
+}
+
+/// Explorer order: directories first, then names case-insensitively.
+fn compare_entries(a_dir: bool, a_name: &str, b_dir: bool, b_name: &str) -> Ordering {
+    b_dir
+        .cmp(&a_dir)
+        .then_with(|| a_name.to_lowercase().cmp(&b_name.to_lowercase()))
+        .then_with(|| a_name.cmp(b_name))
+}
+
+/// Tokens name the last returned entry rather than an offset, so pages stay
+/// consistent when files are created or deleted between requests.
+fn encode_token(is_dir: bool, name: &str) -> String {
+    format!("{}:{}", if is_dir { "d" } else { "f" }, name)
+}
+
+fn decode_token(token: &str) -> Result<(bool, &str), String> {
+    match token.split_once(':') {
+        Some(("d", name)) => Ok((true, name)),
+        Some(("f", name)) => Ok((false, name)),
+        _ => Err("Invalid continuation token".to_string()),
+    }
+}
+
+/// One level of `path`, sorted for the explorer and capped at `limit`
+/// entries. Directories come back with `children: None`; the frontend
+/// expands them with another call.
+#[tauri::command]
+pub fn read_directory_children(
+    path: String,
+    limit: Option<usize>,
+    continuation: Option<String>,
+    options: Option<DirectoryOptions>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<DirectoryPage, String> {
+    workspace.check(&path)?;
+    let dir = Path::new(&path);
+    if !dir.is_dir() {
+        return Err(format!("Path is not a directory: {}", path));
+    }
+
+    let options = options.unwrap_or_default();
+    let mut listed: Vec<(String, bool, PathBuf)> = walk_entries(dir, &options, Some(1))?
+        .into_iter()
+        .map(|(entry_path, is_dir)| (file_name(&entry_path), is_dir, entry_path))
+        .collect();
+    listed.sort_by(|a, b| compare_entries(a.1, &a.0, b.1, &b.0));
+    let total = listed.len();
+
+    let start = match &continuation {
+        Some(token) => {
+            let (token_dir, token_name) = decode_token(token)?;
+            listed
+                .iter()
+                .position(|(name, is_dir, _)| compare_entries(*is_dir, name, token_dir, token_name) == Ordering::Greater)
+                .unwrap_or(total)
+        }
+        None => 0,
+    };
+
+    let limit = limit.unwrap_or(DEFAULT_PAGE_SIZE).max(1);
+    let end = (start + limit).min(total);
+    let repo = if options.show_ignored {
+        git2::Repository::discover(dir).ok()
+    } else {
+        None
+    };
+
+    let entries: Vec<DirEntryInfo> = listed[start..end]
+        .iter()
+        .map(|(_, is_dir, entry_path)| entry_info(entry_path, *is_dir, repo.as_ref()))
+        .collect();
+
+    let next_token = if end < total {
+        entries.last().map(|last| encode_token(last.is_dir, &last.name))
+    } else {
+        None
+    };
+
+    Ok(DirectoryPage {
+        entries,
+        next_token,
+        total,
+    })
+}
+
+// ============================================================================
+// PATH STATISTICS
+// ============================================================================
+
+#[derive(Debug, Serialize, Default, Clone)]
+pub struct LanguageStats {
+    pub language: String,
+    pub files: usize,
+    pub code: usize,
+    pub comments: usize,
+    pub blanks: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LargeFile {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PathStats {
+    pub path: String,
+    pub total_size: u64,
+    pub file_count: usize,
+    pub dir_count: usize,
+    /// Sorted by lines of code, largest first.
+    pub languages: Vec<LanguageStats>,
+    pub largest_files: Vec<LargeFile>,
+}
+
+pub(crate) fn language_for(path: &Path) -> Option<&'static LanguageSpec> {
+    let extension = path.extension()?.to_string_lossy().to_lowercase();
+    LANGUAGES.iter().find(|(_, extensions, _, _)| extensions.contains(&extension.as_str()))
+}
+
+/// Adds the code, comment and blank lines of `content` to `stats`. A line
+/// counts as a comment only if nothing but comment is on it.
+fn count_lines(content: &str, spec: &LanguageSpec, stats: &mut LanguageStats) {
+    let (_, _, line_comments, block) = spec;
+    let mut in_block = false;
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if in_block {
+            stats.comments += 1;
+            if let Some((_, close)) = block {
+                if let Some(end) = trimmed.find(close) {
+                    in_block = false;
+                    // Code after the block closes makes it a code line
+                    if !trimmed[end + close.len()..].trim().is_empty() {
+                        stats.comments -= 1;
+                        stats.code += 1;
+                    }
+                }
+            }
+            continue;
+        }
+        if trimmed.is_empty() {
+            stats.blanks += 1;
+        } else if line_comments.iter().any(|marker| trimmed.starts_with(marker)) {
+            stats.comments += 1;
+        } else if let Some((open, close)) = block.filter(|(open, _)| trimmed.starts_with(open)) {
+            stats.comments += 1;
+            in_block = !trimmed[open.len()..].contains(close);
+        } else {
+            stats.code += 1;
+            if let Some((open, close)) = block {
+                if let Some(start) = trimmed.find(open) {
+                    in_block = !trimmed[start + open.len()..].contains(close);
+                }
+            }
+        }
+    }
+}
+
+fn collect_stats(root: &Path, options: &DirectoryOptions, largest: usize) -> Result<PathStats, String> {
+    let mut stats = PathStats {
+        path: root.to_string_lossy().to_string(),
+        total_size: 0,
+        file_count: 0,
+        dir_count: 0,
+        languages: Vec::new(),
+        largest_files: Vec::new(),
+    };
+    let mut languages: HashMap<&str, LanguageStats> = HashMap::new();
+    let mut sizes: Vec<(u64, PathBuf)> = Vec::new();
+
+    for (path, is_dir) in walk_entries(root, options, None)? {
+        if is_dir {
+            stats.dir_count += 1;
+            continue;
+        }
+        let Ok(metadata) = std::fs::metadata(&path) else {
+            continue;
+        };
+        stats.file_count += 1;
+        stats.total_size += metadata.len();
+
+        if let Some(spec) = language_for(&path) {
+            let entry = languages.entry(spec.0).or_insert_with(|| LanguageStats {
+                language: spec.0.to_string(),
+                ..Default::default()
+            });
+            entry.files += 1;
+            if metadata.len() <= MAX_COUNTED_BYTES {
+                if let Ok(content) = std::fs::read_to_string(&path) {
+                    count_lines(&content, spec, entry);
+                }
+            }
+        }
+        sizes.push((metadata.len(), path));
+    }
+
+    stats.languages = languages.into_values().collect();
+    stats.languages.sort_by(|a, b| b.code.cmp(&a.code).then_with(|| a.language.cmp(&b.language)));
+
+    sizes.sort_by(|a, b| b.0.cmp(&a.0));
+    stats.largest_files = sizes
+        .into_iter()
+        .take(largest)
+        .map(|(size, path)| LargeFile {
+            path: path.to_string_lossy().to_string(),
+            size,
+        })
+        .collect();
+    Ok(stats)
+}
+
+/// Size, file counts, per-language line counts and the largest files below
+/// `path`, skipping what a directory read would skip.
+#[tauri::command]
+pub async fn get_path_stats(
+    path: String,
+    options: Option<DirectoryOptions>,
+    largest: Option<usize>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<PathStats, String> {
+    workspace.check(&path)?;
+    let root = PathBuf::from(&path);
+    if !root.is_dir() {
+        return Err(format!("Path is not a directory: {}", path));
+    }
+    tokio::task::spawn_blocking(move || {
+        collect_stats(&root, &options.unwrap_or_default(), largest.unwrap_or(DEFAULT_LARGEST_FILES))
+    })
+    .await
+    .map_err(|e| format!("Statistics task failed: {}", e))?
+}
@@ -0,0 +1,980 @@
+use encoding_rs::{Encoding, UTF_16BE, UTF_16LE, UTF_8};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::{self as std_fs, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, Manager, State};
+
+pub(crate) use gencode_core::encoding::{decode_bytes, detect_encoding, read_text, FileEncoding};
+
+use crate::git::{patch_hunks, DiffHunk, DiffLine, WordSegment};
+use crate::history::HistoryState;
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+
+const DEFAULT_MAX_TEXT_BYTES: u64 = 2 * 1024 * 1024;
+const PREVIEW_BYTES: usize = 64 * 1024;
+const SNIFF_BYTES: usize = 8192;
+const DEFAULT_RANGE_BYTES: usize = 64 * 1024;
+const MAX_RANGE_BYTES: usize = 1024 * 1024;
+const MAX_WORD_DIFF_CELLS: usize = 250_000;
+
+// ============================================================================
+// CONTENT DETECTION
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct SmartFileContent {
+    pub path: String,
+    pub size: u64,
+    pub mime: String,
+    pub binary: bool,
+    /// Detected encoding name such as "UTF-8" or "Shift_JIS"; None for
+    /// binary files.
+    pub encoding: Option<String>,
+    pub bom: bool,
+    /// Decoded text: the whole file, or its first bytes when `truncated`.
+    pub content: Option<String>,
+    pub truncated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileRange {
+    pub offset: u64,
+    pub bytes: Vec<u8>,
+    pub size: u64,
+    pub eof: bool,
+}
+
+/// Mime type from well-known magic numbers, for content that may be misnamed.
+fn sniff_mime(head: &[u8]) -> Option<&'static str> {
+    const SIGNATURES: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xff\xd8\xff", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"\x1f\x8b", "application/gzip"),
+        (b"\0asm", "application/wasm"),
+        (b"\x7fELF", "application/x-executable"),
+        (b"SQLite format 3\0", "application/vnd.sqlite3"),
+    ];
+    if head.len() >= 12 && &head[..4] == b"RIFF" && &head[8..12] == b"WEBP" {
+        return Some("image/webp");
+    }
+    SIGNATURES
+        .iter()
+        .find(|(magic, _)| head.starts_with(magic))
+        .map(|(_, mime)| *mime)
+}
+
+fn mime_from_extension(path: &Path) -> &'static str {
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "html" | "htm" => "text/html",
+        "css" => "text/css",
+        "js" | "mjs" | "cjs" | "jsx" => "text/javascript",
+        "ts" | "tsx" => "text/typescript",
+        "json" => "application/json",
+        "md" | "markdown" => "text/markdown",
+        "xml" => "application/xml",
+        "svg" => "image/svg+xml",
+        "yaml" | "yml" => "application/yaml",
+        "toml" => "application/toml",
+        "csv" => "text/csv",
+        "ico" => "image/x-icon",
+        "mp3" => "audio/mpeg",
+        "mp4" => "video/mp4",
+        "woff" => "font/woff",
+        "woff2" => "font/woff2",
+        "ttf" => "font/ttf",
+        _ => "text/plain",
+    }
+}
+
+pub(crate) fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn looks_binary(head: &[u8]) -> bool {
+    if Encoding::for_bom(head).is_some() {
+        return false; // UTF-16 text is full of NUL bytes
+    }
+    if sniff_mime(head).is_some() {
+        return true;
+    }
+    head.iter().take(SNIFF_BYTES).any(|b| *b == 0)
+}
+
+// ============================================================================
+// ENCODING
+// ============================================================================
+
+/// Encodings files were read in, by path, so saving writes them back the
+/// same way.
+#[derive(Default)]
+pub struct EncodingState {
+    files: Mutex<HashMap<String, FileEncoding>>,
+}
+
+impl EncodingState {
+    pub(crate) fn remember(&self, path: &str, encoding: FileEncoding) {
+        self.files.lock_or_recover().insert(path.to_string(), encoding);
+    }
+
+    pub(crate) fn get(&self, path: &str) -> Option<FileEncoding> {
+        self.files.lock_or_recover().get(path).copied()
+    }
+}
+
+/// Encodes `text` for writing. Fails instead of silently replacing
+/// characters the target encoding can't represent.
+pub(crate) fn encode_text(text: &str, file_encoding: FileEncoding) -> Result<Vec<u8>, String> {
+    let encoding = file_encoding.encoding;
+    let mut bytes = Vec::with_capacity(text.len());
+
+    // encoding_rs only encodes to UTF-8 for UTF-16 targets
+    if encoding == UTF_16LE || encoding == UTF_16BE {
+        let little_endian = encoding == UTF_16LE;
+        if file_encoding.bom {
+            bytes.extend_from_slice(if little_endian { b"\xff\xfe" } else { b"\xfe\xff" });
+        }
+        for unit in text.encode_utf16() {
+            let pair = if little_endian { unit.to_le_bytes() } else { unit.to_be_bytes() };
+            bytes.extend_from_slice(&pair);
+        }
+        return Ok(bytes);
+    }
+
+    if file_encoding.bom && encoding == UTF_8 {
+        bytes.extend_from_slice(b"\xef\xbb\xbf");
+    }
+    let (encoded, _, unmappable) = encoding.encode(text);
+    if unmappable {
+        return Err(format!(
+            "Content contains characters that can't be saved as {}",
+            encoding.name()
+        ));
+    }
+    bytes.extend_from_slice(&encoded);
+    Ok(bytes)
+}
+
+/// Picks the encoding to save `path` in: `label` if given, otherwise the one
+/// it was read in, otherwise UTF-8.
+pub(crate) fn resolve_write_encoding(
+    path: &str,
+    label: Option<&str>,
+    state: &EncodingState,
+) -> Result<FileEncoding, String> {
+    let remembered = state.get(path);
+    let Some(label) = label else {
+        return Ok(remembered.unwrap_or_default());
+    };
+    let encoding = Encoding::for_label(label.trim().as_bytes()).ok_or_else(|| format!("Unknown encoding: {}", label))?;
+    Ok(FileEncoding {
+        encoding,
+        bom: remembered.is_some_and(|r| r.encoding == encoding && r.bom),
+    })
+}
+
+// ============================================================================
+// FILE TAURI COMMANDS
+// ============================================================================
+
+/// Opens a file for the editor without choking on binary or huge files.
+/// Text up to `max_bytes` (2MB by default) is returned whole; larger files
+/// only get a preview of their first 64KB. Binary files come back without
+/// content; use `read_file_range` for a hex view.
+#[tauri::command]
+pub fn open_file_smart(
+    path: String,
+    max_bytes: Option<u64>,
+    state: State<'_, EncodingState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<SmartFileContent, String> {
+    workspace.check(&path)?;
+    let file_path = Path::new(&path);
+    let size = std_fs::metadata(file_path)
+        .map_err(|e| format!("Failed to get metadata: {}", e))?
+        .len();
+    let max_bytes = max_bytes.unwrap_or(DEFAULT_MAX_TEXT_BYTES);
+    let truncated = size > max_bytes;
+
+    let mut file = File::open(file_path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut bytes = Vec::new();
+    let read_limit = if truncated { PREVIEW_BYTES as u64 } else { size };
+    file.by_ref()
+        .take(read_limit)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let binary = looks_binary(&bytes);
+    let mime = sniff_mime(&bytes).unwrap_or_else(|| mime_from_extension(file_path));
+    if binary {
+        let mime = if mime.starts_with("text/") {
+            "application/octet-stream"
+        } else {
+            mime
+        };
+        return Ok(SmartFileContent {
+            path,
+            size,
+            mime: mime.to_string(),
+            binary,
+            encoding: None,
+            bom: false,
+            content: None,
+            truncated: false,
+        });
+    }
+
+    let encoding = detect_encoding(&bytes);
+    let mut content = decode_bytes(&bytes, encoding);
+    if truncated && content.ends_with('\u{FFFD}') {
+        content.pop(); // Character cut off by the preview limit
+    }
+    state.remember(&path, encoding);
+
+    Ok(SmartFileContent {
+        path,
+        size,
+        mime: mime.to_string(),
+        binary,
+        encoding: Some(encoding.encoding.name().to_string()),
+        bom: encoding.bom,
+        content: Some(content),
+        truncated,
+    })
+}
+
+/// Raw bytes of `path` from `offset`, at most 1MB per call, for hex and
+/// large-file views.
+#[tauri::command]
+pub fn read_file_range(
+    path: String,
+    offset: u64,
+    length: Option<usize>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<FileRange, String> {
+    workspace.check(&path)?;
+    let mut file = File::open(&path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let size = file
+        .metadata()
+        .map_err(|e| format!("Failed to get metadata: {}", e))?
+        .len();
+    let length = length.unwrap_or(DEFAULT_RANGE_BYTES).min(MAX_RANGE_BYTES);
+
+    file.seek(SeekFrom::Start(offset.min(size)))
+        .map_err(|e| format!("Failed to seek file: {}", e))?;
+    let mut bytes = Vec::with_capacity(length);
+    file.take(length as u64)
+        .read_to_end(&mut bytes)
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+
+    Ok(FileRange {
+        offset: offset.min(size),
+        eof: offset.min(size) + bytes.len() as u64 >= size,
+        bytes,
+        size,
+    })
+}
+
+// ============================================================================
+// COPY AND MOVE
+// ============================================================================
+
+#[derive(Debug, Serialize, Default)]
+pub struct TransferResult {
+    pub files: usize,
+    /// Destinations that already existed and were left untouched.
+    pub conflicts: Vec<String>,
+}
+
+/// `path` with symlinks, `.` and `..` resolved, for paths that may not exist
+/// yet. The longest existing prefix is canonicalized and the rest applied
+/// lexically; none of the rest exists, so none of it can be a symlink.
+pub(crate) fn resolve_path(path: &Path) -> PathBuf {
+    let components: Vec<Component> = path.components().collect();
+    let mut existing = components.len();
+    let mut resolved = loop {
+        if existing == 0 {
+            break if path.is_absolute() {
+                PathBuf::new()
+            } else {
+                std::env::current_dir().unwrap_or_default()
+            };
+        }
+        let prefix: PathBuf = components[..existing].iter().collect();
+        if let Ok(resolved) = prefix.canonicalize() {
+            break resolved;
+        }
+        existing -= 1;
+    };
+    for component in &components[existing..] {
+        match component {
+            Component::ParentDir => {
+                resolved.pop();
+            }
+            Component::CurDir => {}
+            other => resolved.push(other),
+        }
+    }
+    resolved
+}
+
+fn check_transfer(src: &Path, dst: &Path) -> Result<(), String> {
+    if !src.exists() {
+        return Err(format!("Path does not exist: {}", src.display()));
+    }
+    let (src, dst) = (resolve_path(src), resolve_path(dst));
+    if src == dst {
+        return Err("Source and destination are the same".to_string());
+    }
+    if src.is_dir() && dst.starts_with(&src) {
+        return Err("Cannot copy or move a directory into itself".to_string());
+    }
+    Ok(())
+}
+
+/// Copies `src` to `dst`, merging into existing directories. Existing files
+/// are only replaced with `overwrite`; otherwise they're reported. Symlinks
+/// are copied as links and never descended into or written through, so a
+/// link to an ancestor can't recurse and a link leaving the workspace can't
+/// pull its target's content in or write out to it.
+fn copy_recursive(src: &Path, dst: &Path, overwrite: bool, result: &mut TransferResult) -> Result<(), String> {
+    let file_type = std_fs::symlink_metadata(src)
+        .map_err(|e| format!("Failed to read {}: {}", src.display(), e))?
+        .file_type();
+    let existing = std_fs::symlink_metadata(dst).ok().map(|m| m.file_type());
+
+    if file_type.is_dir() {
+        if existing.is_some_and(|t| !t.is_dir()) {
+            result.conflicts.push(dst.to_string_lossy().to_string());
+            return Ok(());
+        }
+        std_fs::create_dir_all(dst).map_err(|e| format!("Failed to create directory: {}", e))?;
+        for entry in std_fs::read_dir(src).map_err(|e| format!("Failed to read directory: {}", e))? {
+            let entry = entry.map_err(|e| format!("Failed to read directory: {}", e))?;
+            copy_recursive(&entry.path(), &dst.join(entry.file_name()), overwrite, result)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(existing) = existing {
+        if !overwrite || existing.is_dir() {
+            result.conflicts.push(dst.to_string_lossy().to_string());
+            return Ok(());
+        }
+        if existing.is_symlink() {
+            std_fs::remove_file(dst).map_err(|e| format!("Failed to replace {}: {}", dst.display(), e))?;
+        }
+    }
+    if file_type.is_symlink() {
+        copy_symlink(src, dst)?;
+    } else {
+        std_fs::copy(src, dst).map_err(|e| format!("Failed to copy {}: {}", src.display(), e))?;
+    }
+    result.files += 1;
+    Ok(())
+}
+
+fn copy_symlink(src: &Path, dst: &Path) -> Result<(), String> {
+    let target = std_fs::read_link(src).map_err(|e| format!("Failed to read link {}: {}", src.display(), e))?;
+    #[cfg(unix)]
+    let created = std::os::unix::fs::symlink(&target, dst);
+    #[cfg(windows)]
+    let created = if src.is_dir() {
+        std::os::windows::fs::symlink_dir(&target, dst)
+    } else {
+        std::os::windows::fs::symlink_file(&target, dst)
+    };
+    created.map_err(|e| format!("Failed to copy link {}: {}", src.display(), e))
+}
+
+/// Files in the tree at `path`; links count as one file and aren't followed.
+fn count_files(path: &Path) -> usize {
+    if !std_fs::symlink_metadata(path).is_ok_and(|m| m.is_dir()) {
+        return 1;
+    }
+    std_fs::read_dir(path)
+        .map(|entries| entries.flatten().map(|e| count_files(&e.path())).sum())
+        .unwrap_or(0)
+}
+
+pub(crate) fn remove_path(path: &Path) -> Result<(), String> {
+    if path.is_dir() {
+        std_fs::remove_dir_all(path).map_err(|e| format!("Failed to delete directory: {}", e))
+    } else {
+        std_fs::remove_file(path).map_err(|e| format!("Failed to delete file: {}", e))
+    }
+}
+
+/// Copies a file or directory tree. Without `overwrite`, files that already
+/// exist at the destination are skipped and returned as conflicts.
+#[tauri::command]
+pub async fn copy_path(
+    src: String,
+    dst: String,
+    overwrite: Option<bool>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<TransferResult, String> {
+    workspace.check(&src)?;
+    workspace.check(&dst)?;
+    tokio::task::spawn_blocking(move || {
+        let (src, dst) = (Path::new(&src), Path::new(&dst));
+        check_transfer(src, dst)?;
+        if let Some(parent) = dst.parent() {
+            std_fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
+        }
+
+        let mut result = TransferResult::default();
+        copy_recursive(src, dst, overwrite.unwrap_or(false), &mut result)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Copy task failed: {}", e))?
+}
+
+/// Moves a file or directory, across directories and drives. Nothing moves
+/// if the destination exists; it comes back as the conflict instead.
+#[tauri::command]
+pub async fn move_path(
+    src: String,
+    dst: String,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<TransferResult, String> {
+    workspace.check(&src)?;
+    workspace.check(&dst)?;
+    tokio::task::spawn_blocking(move || {
+        let (src, dst) = (Path::new(&src), Path::new(&dst));
+        check_transfer(src, dst)?;
+        if dst.exists() {
+            return Ok(TransferResult {
+                files: 0,
+                conflicts: vec![dst.to_string_lossy().to_string()],
+            });
+        }
+        if let Some(parent) = dst.parent() {
+            std_fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
+        }
+
+        let files = count_files(src);
+        if std_fs::rename(src, dst).is_ok() {
+            return Ok(TransferResult {
+                files,
+                conflicts: Vec::new(),
+            });
+        }
+
+        // Rename fails across filesystems; fall back to copy and delete
+        let mut result = TransferResult::default();
+        if let Err(e) = copy_recursive(src, dst, false, &mut result) {
+            let _ = remove_path(dst);
+            return Err(e);
+        }
+        remove_path(src)?;
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Move task failed: {}", e))?
+}
+
+/// Copies `path` next to itself as "name copy.ext", "name copy 2.ext", ...
+/// and returns the new path.
+#[tauri::command]
+pub async fn duplicate_path(path: String, workspace: State<'_, WorkspaceState>) -> Result<String, String> {
+    workspace.check(&path)?;
+    tokio::task::spawn_blocking(move || {
+        let src = Path::new(&path);
+        if !src.exists() {
+            return Err(format!("Path does not exist: {}", path));
+        }
+        let parent = src.parent().ok_or("Cannot duplicate a root directory")?;
+        let (stem, extension) = if src.is_dir() {
+            (src.file_name(), None)
+        } else {
+            (src.file_stem(), src.extension())
+        };
+        let stem = stem.map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+        let extension = extension.map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+
+        let dst = (1..)
+            .map(|n| {
+                let suffix = if n == 1 { " copy".to_string() } else { format!(" copy {}", n) };
+                parent.join(format!("{}{}{}", stem, suffix, extension))
+            })
+            .find(|candidate| !candidate.exists())
+            .ok_or("No free name for the duplicate")?;
+
+        let mut result = TransferResult::default();
+        copy_recursive(src, &dst, false, &mut result)?;
+        Ok(dst.to_string_lossy().to_string())
+    })
+    .await
+    .map_err(|e| format!("Duplicate task failed: {}", e))?
+}
+
+// ============================================================================
+// DELETION
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct DeleteFailure {
+    pub path: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteResult {
+    pub deleted: Vec<String>,
+    pub failed: Vec<DeleteFailure>,
+}
+
+/// Sends `path` to the OS trash, or removes it for good with `permanent`.
+pub(crate) fn delete_path(path: &Path, permanent: bool) -> Result<(), String> {
+    if !path.exists() {
+        return Err(format!("Path does not exist: {}", path.display()));
+    }
+    if permanent {
+        remove_path(path)
+    } else {
+        trash::delete(path).map_err(|e| format!("Failed to move to trash: {}", e))
+    }
+}
+
+/// Deletes several paths, continuing past failures so one locked file
+/// doesn't keep the rest of a selection around.
+#[tauri::command]
+pub async fn delete_paths(app: AppHandle, paths: Vec<String>, permanent: Option<bool>) -> Result<DeleteResult, String> {
+    let permanent = permanent.unwrap_or(false);
+    tokio::task::spawn_blocking(move || {
+        let (history, workspace) = (app.state::<HistoryState>(), app.state::<WorkspaceState>());
+        let mut result = DeleteResult {
+            deleted: Vec::new(),
+            failed: Vec::new(),
+        };
+        for path in paths {
+            if let Err(error) = workspace.check(&path) {
+                result.failed.push(DeleteFailure { path, error });
+                continue;
+            }
+            let _ = history.snapshot(Path::new(&path), "delete");
+            match delete_path(Path::new(&path), permanent) {
+                Ok(()) => result.deleted.push(path),
+                Err(error) => result.failed.push(DeleteFailure { path, error }),
+            }
+        }
+        result
+    })
+    .await
+    .map_err(|e| format!("Delete task failed: {}", e))
+}
+
+// ============================================================================
+// BATCH READS
+// ============================================================================
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct BatchReadOptions {
+    /// Files larger than this are reported with `skipped` and no content.
+    pub max_bytes: Option<u64>,
+    /// Only stat and hash the files.
+    pub metadata_only: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BatchFileResult {
+    pub path: String,
+    pub content: Option<String>,
+    pub size: Option<u64>,
+    pub modified: Option<u64>,
+    pub hash: Option<String>, // SHA-256 of the raw bytes
+    pub encoding: Option<String>,
+    pub binary: bool,
+    pub skipped: bool,
+    pub error: Option<String>,
+}
+
+fn read_one(path: String, options: &BatchReadOptions) -> BatchFileResult {
+    let mut result = BatchFileResult {
+        path,
+        content: None,
+        size: None,
+        modified: None,
+        hash: None,
+        encoding: None,
+        binary: false,
+        skipped: false,
+        error: None,
+    };
+
+    let metadata = match std_fs::metadata(&result.path) {
+        Ok(metadata) if metadata.is_file() => metadata,
+        Ok(_) => {
+            result.error = Some("Not a file".to_string());
+            return result;
+        }
+        Err(e) => {
+            result.error = Some(format!("Failed to get metadata: {}", e));
+            return result;
+        }
+    };
+    result.size = Some(metadata.len());
+    result.modified = metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_secs());
+
+    if options.max_bytes.is_some_and(|max| metadata.len() > max) {
+        result.skipped = true;
+        return result;
+    }
+
+    let bytes = match std_fs::read(&result.path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            result.error = Some(format!("Failed to read file: {}", e));
+            return result;
+        }
+    };
+    result.hash = Some(hash_bytes(&bytes));
+    result.binary = looks_binary(&bytes);
+    if result.binary || options.metadata_only {
+        return result;
+    }
+
+    let encoding = detect_encoding(&bytes);
+    result.encoding = Some(encoding.encoding.name().to_string());
+    result.content = Some(decode_bytes(&bytes, encoding));
+    result
+}
+
+/// Reads many files in parallel. Every requested path gets a result, in
+/// request order, with the reason when it couldn't be read.
+#[tauri::command]
+pub async fn read_files_batch(
+    paths: Vec<String>,
+    options: Option<BatchReadOptions>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<BatchFileResult>, String> {
+    workspace.check_all(&paths)?;
+    let options = options.unwrap_or_default();
+    let handles: Vec<_> = paths
+        .into_iter()
+        .map(|path| {
+            let options = options.clone();
+            tokio::task::spawn_blocking(move || read_one(path, &options))
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        results.push(handle.await.map_err(|e| format!("Read task failed: {}", e))?);
+    }
+    Ok(results)
+}
+
+// ============================================================================
+// FILE COMPARE
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct FileComparison {
+    pub path_a: String,
+    pub path_b: String,
+    pub identical: bool,
+    pub binary: bool,
+    pub additions: usize,
+    pub deletions: usize,
+    pub hunks: Vec<DiffHunk>,
+}
+
+/// Words, whitespace runs and single punctuation characters.
+fn word_tokens(line: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        let word = c.is_alphanumeric() || c == '_';
+        let space = c.is_whitespace();
+        if word || space {
+            while let Some(&(_, next)) = chars.peek() {
+                let same = if word { next.is_alphanumeric() || next == '_' } else { next.is_whitespace() };
+                if !same {
+                    break;
+                }
+                chars.next();
+            }
+        }
+        let end = chars.peek().map(|(j, _)| *j).unwrap_or(line.len());
+        tokens.push(&line[i..end]);
+    }
+    tokens
+}
+
+fn push_segment(segments: &mut Vec<WordSegment>, kind: &str, text: &str) {
+    match segments.last_mut() {
+        Some(last) if last.kind == kind => last.text.push_str(text),
+        _ => segments.push(WordSegment {
+            kind: kind.to_string(),
+            text: text.to_string(),
+        }),
+    }
+}
+
+/// Word segments of a changed line pair via LCS over tokens, or None when
+/// the lines are too long to compare cheaply.
+fn word_diff(old: &str, new: &str) -> Option<(Vec<WordSegment>, Vec<WordSegment>)> {
+    let (a, b) = (word_tokens(old), word_tokens(new));
+    if (a.len() + 1) * (b.len() + 1) > MAX_WORD_DIFF_CELLS {
+        return None;
+    }
+
+    // lcs[i][j]: common tokens of a[i..] and b[j..]
+    let width = b.len() + 1;
+    let mut lcs = vec![0u32; (a.len() + 1) * width];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i * width + j] = if a[i] == b[j] {
+                lcs[(i + 1) * width + j + 1] + 1
+            } else {
+                lcs[(i + 1) * width + j].max(lcs[i * width + j + 1])
+            };
+        }
+    }
+
+    let (mut old_segments, mut new_segments) = (Vec::new(), Vec::new());
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() || j < b.len() {
+        if i < a.len() && j < b.len() && a[i] == b[j] {
+            push_segment(&mut old_segments, "equal", a[i]);
+            push_segment(&mut new_segments, "equal", b[j]);
+            i += 1;
+            j += 1;
+        } else if j == b.len() || (i < a.len() && lcs[(i + 1) * width + j] >= lcs[i * width + j + 1]) {
+            push_segment(&mut old_segments, "delete", a[i]);
+            i += 1;
+        } else {
+            push_segment(&mut new_segments, "add", b[j]);
+            j += 1;
+        }
+    }
+    Some((old_segments, new_segments))
+}
+
+/// Pairs each run of deleted lines with the added lines right after it and
+/// annotates the pairs with word-level changes.
+fn add_word_diffs(lines: &mut [DiffLine]) {
+    let mut i = 0;
+    while i < lines.len() {
+        if lines[i].kind != "delete" {
+            i += 1;
+            continue;
+        }
+        let deleted_start = i;
+        while i < lines.len() && lines[i].kind == "delete" {
+            i += 1;
+        }
+        let added_start = i;
+        while i < lines.len() && lines[i].kind == "add" {
+            i += 1;
+        }
+
+        let pairs = (added_start - deleted_start).min(i - added_start);
+        for k in 0..pairs {
+            let (old, new) = (deleted_start + k, added_start + k);
+            if let Some((old_words, new_words)) = word_diff(&lines[old].content, &lines[new].content) {
+                lines[old].words = Some(old_words);
+                lines[new].words = Some(new_words);
+            }
+        }
+    }
+}
+
+/// Diffs two arbitrary files on disk into git-style hunks, decoding each in
+/// its own encoding. With `word_level`, changed line pairs also carry the
+/// changed words.
+#[tauri::command]
+pub fn diff_files(
+    path_a: String,
+    path_b: String,
+    word_level: Option<bool>,
+    context_lines: Option<u32>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<FileComparison, String> {
+    workspace.check(&path_a)?;
+    workspace.check(&path_b)?;
+    let bytes_a = std_fs::read(&path_a).map_err(|e| format!("Failed to read {}: {}", path_a, e))?;
+    let bytes_b = std_fs::read(&path_b).map_err(|e| format!("Failed to read {}: {}", path_b, e))?;
+    let mut comparison = FileComparison {
+        identical: bytes_a == bytes_b,
+        binary: looks_binary(&bytes_a) || looks_binary(&bytes_b),
+        path_a,
+        path_b,
+        additions: 0,
+        deletions: 0,
+        hunks: Vec::new(),
+    };
+    if comparison.identical || comparison.binary {
+        return Ok(comparison);
+    }
+
+    let text_a = decode_bytes(&bytes_a, detect_encoding(&bytes_a));
+    let text_b = decode_bytes(&bytes_b, detect_encoding(&bytes_b));
+    let mut opts = git2::DiffOptions::new();
+    opts.context_lines(context_lines.unwrap_or(3));
+    let patch = git2::Patch::from_buffers(
+        text_a.as_bytes(),
+        Some(Path::new(&comparison.path_a)),
+        text_b.as_bytes(),
+        Some(Path::new(&comparison.path_b)),
+        Some(&mut opts),
+    )
+    .map_err(|e| e.message().to_string())?;
+
+    let (_, additions, deletions) = patch.line_stats().map_err(|e| e.message().to_string())?;
+    comparison.additions = additions;
+    comparison.deletions = deletions;
+    comparison.hunks = patch_hunks(&patch)?;
+    if word_level.unwrap_or(false) {
+        for hunk in &mut comparison.hunks {
+            add_word_diffs(&mut hunk.lines);
+        }
+    }
+    Ok(comparison)
+}
+
+// ============================================================================
+// PERMISSIONS
+// ============================================================================
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct UnixMetadata {
+    pub mode: u32, // Permission bits only
+    pub uid: u32,
+    pub gid: u32,
+}
+
+#[cfg(unix)]
+pub(crate) fn unix_metadata(metadata: &std_fs::Metadata) -> Option<UnixMetadata> {
+    use std::os::unix::fs::MetadataExt;
+    Some(UnixMetadata {
+        mode: metadata.mode() & 0o7777,
+        uid: metadata.uid(),
+        gid: metadata.gid(),
+    })
+}
+
+#[cfg(not(unix))]
+pub(crate) fn unix_metadata(_metadata: &std_fs::Metadata) -> Option<UnixMetadata> {
+    None
+}
+
+/// `ls -l` style permission string for the low nine mode bits.
+pub(crate) fn permission_string(mode: u32) -> String {
+    (0..9)
+        .map(|i| {
+            if mode & (0o400 >> i) == 0 {
+                '-'
+            } else {
+                ['r', 'w', 'x'][i % 3]
+            }
+        })
+        .collect()
+}
+
+fn apply_mode(path: &str, mode: u32) -> Result<(), String> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std_fs::set_permissions(&path, std_fs::Permissions::from_mode(mode & 0o7777))
+            .map_err(|e| format!("Failed to set permissions: {}", e))
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = (path, mode);
+        Err("File permissions are not supported on this platform".to_string())
+    }
+}
+
+/// Sets Unix permission bits, e.g. 0o755. Not available on Windows.
+#[tauri::command]
+pub fn set_permissions(path: String, mode: u32, workspace: State<'_, WorkspaceState>) -> Result<(), String> {
+    workspace.check(&path)?;
+    apply_mode(&path, mode)
+}
+
+/// Adds or removes execute permission like `chmod +x` / `chmod -x`:
+/// whoever may read the file gets to execute it. Returns the new mode.
+#[tauri::command]
+pub fn set_executable(path: String, executable: bool, workspace: State<'_, WorkspaceState>) -> Result<u32, String> {
+    workspace.check(&path)?;
+    let metadata = std_fs::metadata(&path).map_err(|e| format!("Failed to get metadata: {}", e))?;
+    if !metadata.is_file() {
+        return Err(format!("Not a file: {}", path));
+    }
+    let unix = unix_metadata(&metadata).ok_or("File permissions are not supported on this platform")?;
+
+    let mode = if executable {
+        unix.mode | ((unix.mode & 0o444) >> 2)
+    } else {
+        unix.mode & !0o111
+    };
+    apply_mode(&path, mode)?;
+    Ok(mode)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resolve_path_applies_dots_below_the_existing_prefix() {
+        let dir = resolve_path(&std::env::temp_dir());
+        assert_eq!(resolve_path(&dir.join("missing/./a/../b")), dir.join("missing/b"));
+        assert_eq!(resolve_path(&dir.join("missing/..")), dir);
+        assert_eq!(resolve_path(&dir.join("missing/../..")), dir.parent().unwrap());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn copies_symlinks_as_links_without_following_them() {
+        let dir = std::env::temp_dir().join(format!("gencode-files-links-{}", std::process::id()));
+        let _ = std_fs::remove_dir_all(&dir);
+        std_fs::create_dir_all(dir.join("src/nested")).unwrap();
+        std_fs::create_dir_all(dir.join("outside")).unwrap();
+        std_fs::write(dir.join("src/nested/a.txt"), "a").unwrap();
+        std_fs::write(dir.join("outside/secret.txt"), "secret").unwrap();
+        // A cycle back to the copied directory and a link leaving it
+        std::os::unix::fs::symlink("..", dir.join("src/nested/up")).unwrap();
+        std::os::unix::fs::symlink(dir.join("outside"), dir.join("src/out")).unwrap();
+
+        assert_eq!(count_files(&dir.join("src")), 3);
+        let mut result = TransferResult::default();
+        copy_recursive(&dir.join("src"), &dir.join("copy"), false, &mut result).unwrap();
+        assert_eq!(result.files, 3);
+        assert_eq!(std_fs::read_to_string(dir.join("copy/nested/a.txt")).unwrap(), "a");
+        assert_eq!(std_fs::read_link(dir.join("copy/nested/up")).unwrap(), Path::new(".."));
+        assert!(std_fs::symlink_metadata(dir.join("copy/out")).unwrap().file_type().is_symlink());
+
+        // Merging into a copy whose link leaves it doesn't write through the link
+        std_fs::create_dir_all(dir.join("more/out")).unwrap();
+        std_fs::write(dir.join("more/out/secret.txt"), "replaced").unwrap();
+        let mut result = TransferResult::default();
+        copy_recursive(&dir.join("more"), &dir.join("copy"), true, &mut result).unwrap();
+        assert_eq!(result.conflicts, vec![dir.join("copy/out").to_string_lossy().to_string()]);
+        assert_eq!(std_fs::read_to_string(dir.join("outside/secret.txt")).unwrap(), "secret");
+        let _ = std_fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,320 @@
+use git2::Repository;
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::Serialize;
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use tauri::State;
+
+use crate::explorer::{walk_entries, DirectoryOptions, IGNORED_DIRS};
+use crate::git::path_ignored;
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+
+const DEFAULT_LIMIT: usize = 50;
+
+const SCORE_MATCH: i64 = 16;
+const BONUS_BOUNDARY: i64 = 8;
+const BONUS_CAMEL: i64 = 7;
+const BONUS_CONSECUTIVE: i64 = 4;
+const BONUS_FILE_NAME: i64 = 12;
+const PENALTY_GAP_START: i64 = 3;
+const PENALTY_GAP_EXTENSION: i64 = 1;
+
+// ============================================================================
+// FILE INDEX
+// ============================================================================
+
+#[derive(Default)]
+struct FileIndex {
+    root: PathBuf,
+    paths: BTreeSet<String>, // Relative to root, '/'-separated
+}
+
+/// Project file list for quick-open, kept current by a file watcher.
+#[derive(Default)]
+pub struct FinderState {
+    index: Arc<Mutex<FileIndex>>,
+    watcher: Mutex<Option<RecommendedWatcher>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FuzzyMatch {
+    pub path: String,
+    pub relative_path: String,
+    pub score: i64,
+    /// Matched character indices in `relative_path`, for highlighting.
+    pub positions: Vec<usize>,
+}
+
+pub(crate) fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    Some(relative.to_string_lossy().replace('\\', "/"))
+}
+
+/// Mirrors the default walk: hidden entries and build/dependency dirs are out.
+pub(crate) fn is_excluded(relative: &str) -> bool {
+    relative
+        .split('/')
+        .any(|part| part.starts_with('.') || IGNORED_DIRS.contains(&part))
+}
+
+fn index_files(root: &Path, dir: &Path, paths: &mut BTreeSet<String>) -> Result<(), String> {
+    for (path, is_dir) in walk_entries(dir, &DirectoryOptions::default(), None)? {
+        if !is_dir {
+            if let Some(relative) = relative_path(root, &path) {
+                paths.insert(relative);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn apply_event(index: &Mutex<FileIndex>, repo: Option<&Repository>, event: Event) {
+    if !matches!(
+        event.kind,
+        EventKind::Create(_) | EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))
+    ) {
+        return;
+    }
+
+    let mut index = index.lock_or_recover();
+    let root = index.root.clone();
+    for path in event.paths {
+        let Some(relative) = relative_path(&root, &path) else {
+            continue;
+        };
+        if relative.is_empty() || is_excluded(&relative) || repo.is_some_and(|repo| path_ignored(repo, &path)) {
+            continue;
+        }
+
+        if path.is_dir() {
+            let _ = index_files(&root, &path, &mut index.paths);
+        } else if path.is_file() {
+            index.paths.insert(relative);
+        } else {
+            // Gone: drop the file, or everything below a removed directory
+            let prefix = format!("{}/", relative);
+            let removed: Vec<String> = index
+                .paths
+                .range(prefix.clone()..)
+                .take_while(|p| p.starts_with(&prefix))
+                .cloned()
+                .collect();
+            for p in removed {
+                index.paths.remove(&p);
+            }
+            index.paths.remove(&relative);
+        }
+    }
+}
+
+// ============================================================================
+// FUZZY SCORING
+// ============================================================================
+
+fn char_bonus(prev: Option<char>, current: char) -> i64 {
+    match prev {
+        None => BONUS_BOUNDARY,
+        Some('/' | '\\' | '_' | '-' | '.' | ' ') => BONUS_BOUNDARY,
+        Some(p) if p.is_lowercase() && current.is_uppercase() => BONUS_CAMEL,
+        Some(p) if !p.is_ascii_digit() && current.is_ascii_digit() => BONUS_CAMEL,
+        _ => 0,
+    }
+}
+
+/// fzf-style score of `query` against `candidate`: the shortest window
+/// containing the query in order is found with a forward and a backward
+/// pass, then matches on word boundaries, camelCase humps and consecutive
+/// runs are rewarded and gaps penalized.
+pub(crate) fn fuzzy_score(query: &[char], candidate: &str, case_sensitive: bool) -> Option<(i64, Vec<usize>)> {
+    if query.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let chars: Vec<char> = candidate.chars().collect();
+    let eq = |a: char, b: char| {
+        if case_sensitive {
+            a == b
+        } else {
+            a.to_lowercase().eq(b.to_lowercase())
+        }
+    };
+
+    let mut qi = 0;
+    let mut end = 0;
+    for (i, c) in chars.iter().enumerate() {
+        if eq(*c, query[qi]) {
+            qi += 1;
+            if qi == query.len() {
+                end = i;
+                break;
+            }
+        }
+    }
+    if qi < query.len() {
+        return None;
+    }
+
+    let mut qi = query.len();
+    let mut start = end;
+    for i in (0..=end).rev() {
+        if eq(chars[i], query[qi - 1]) {
+            qi -= 1;
+            if qi == 0 {
+                start = i;
+                break;
+            }
+        }
+    }
+
+    let mut score = 0;
+    let mut positions = Vec::with_capacity(query.len());
+    let mut chunk_bonus = 0;
+    for (i, c) in chars.iter().enumerate().take(end + 1).skip(start) {
+        if positions.len() == query.len() {
+            break;
+        }
+        if !eq(*c, query[positions.len()]) {
+            continue;
+        }
+        let bonus = char_bonus(if i == 0 { None } else { Some(chars[i - 1]) }, *c);
+        match positions.last() {
+            Some(&last) if last + 1 == i => {
+                chunk_bonus = chunk_bonus.max(bonus).max(BONUS_CONSECUTIVE);
+                score += SCORE_MATCH + chunk_bonus;
+            }
+            Some(&last) => {
+                let gap = (i - last - 1) as i64;
+                score -= PENALTY_GAP_START + PENALTY_GAP_EXTENSION * (gap - 1);
+                chunk_bonus = bonus;
+                score += SCORE_MATCH + bonus;
+            }
+            None => {
+                chunk_bonus = bonus;
+                score += SCORE_MATCH + bonus * 2; // The first character counts double
+            }
+        }
+        positions.push(i);
+    }
+
+    let file_name_start = chars.iter().rposition(|c| *c == '/').map(|i| i + 1).unwrap_or(0);
+    if start >= file_name_start {
+        score += BONUS_FILE_NAME;
+    }
+    Some((score, positions))
+}
+
+/// Scores every space-separated term of `query`; all must match.
+fn score_terms(terms: &[Vec<char>], candidate: &str, case_sensitive: bool) -> Option<(i64, Vec<usize>)> {
+    let mut total = 0;
+    let mut positions = Vec::new();
+    for term in terms {
+        let (score, term_positions) = fuzzy_score(term, candidate, case_sensitive)?;
+        total += score;
+        positions.extend(term_positions);
+    }
+    positions.sort_unstable();
+    positions.dedup();
+    Some((total, positions))
+}
+
+// ============================================================================
+// FINDER TAURI COMMANDS
+// ============================================================================
+
+/// Builds the quick-open index for `root` and watches it for changes,
+/// replacing any previously indexed project. Returns the file count.
+#[tauri::command]
+pub async fn index_project_files(
+    root: String,
+    state: State<'_, FinderState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<usize, String> {
+    workspace.check(&root)?;
+    let root_path = PathBuf::from(&root);
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+
+    // Stop watching the old project before swapping the index
+    state.watcher.lock_or_recover().take();
+
+    let walk_root = root_path.clone();
+    let paths = tokio::task::spawn_blocking(move || {
+        let mut paths = BTreeSet::new();
+        index_files(&walk_root, &walk_root, &mut paths).map(|_| paths)
+    })
+    .await
+    .map_err(|e| format!("Indexing task failed: {}", e))??;
+
+    let count = paths.len();
+    *state.index.lock_or_recover() = FileIndex {
+        root: root_path.clone(),
+        paths,
+    };
+
+    let index = state.index.clone();
+    let repo = Repository::discover(&root_path).ok();
+    let mut watcher = notify::recommended_watcher(move |result: notify::Result<Event>| {
+        if let Ok(event) = result {
+            apply_event(&index, repo.as_ref(), event);
+        }
+    })
+    .map_err(|e| format!("Failed to start file watcher: {}", e))?;
+    watcher
+        .watch(&root_path, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to watch {}: {}", root, e))?;
+    *state.watcher.lock_or_recover() = Some(watcher);
+
+    Ok(count)
+}
+
+/// Ranked quick-open matches from the index. Lowercase queries match
+/// case-insensitively; any uppercase character makes the query exact.
+#[tauri::command]
+pub async fn fuzzy_find_files(
+    query: String,
+    limit: Option<usize>,
+    state: State<'_, FinderState>,
+) -> Result<Vec<FuzzyMatch>, String> {
+    let index = state.index.clone();
+    tokio::task::spawn_blocking(move || {
+        let index = index.lock_or_recover();
+        if index.root.as_os_str().is_empty() {
+            return Err("No project indexed; call index_project_files first".to_string());
+        }
+
+        let terms: Vec<Vec<char>> = query.split_whitespace().map(|t| t.chars().collect()).collect();
+        let case_sensitive = query.chars().any(|c| c.is_uppercase());
+
+        let mut scored: Vec<(i64, Vec<usize>, &String)> = index
+            .paths
+            .iter()
+            .filter_map(|relative| {
+                let (score, positions) = score_terms(&terms, relative, case_sensitive)?;
+                Some((score, positions, relative))
+            })
+            .collect();
+
+        scored.sort_by(|a, b| {
+            b.0.cmp(&a.0)
+                .then_with(|| a.2.len().cmp(&b.2.len()))
+                .then_with(|| a.2.cmp(b.2))
+        });
+        scored.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+
+        let matches = scored
+            .into_iter()
+            .map(|(score, positions, relative)| FuzzyMatch {
+                path: index.root.join(relative).to_string_lossy().to_string(),
+                relative_path: relative.clone(),
+                score,
+                positions,
+            })
+            .collect();
+        Ok(matches)
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))?
+}
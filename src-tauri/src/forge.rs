@@ -0,0 +1,638 @@
+use git2::{BranchType, Repository};
+use reqwest::header::{ACCEPT, USER_AGENT};
+use reqwest::Method;
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::time::Duration;
+use tauri::{AppHandle, State};
+
+use crate::git::{remote_host, stored_token};
+use crate::sandbox::WorkspaceState;
+
+const FORGE_TIMEOUT_SECS: u64 = 30;
+const PAGE_SIZE: usize = 100;
+const DEFAULT_REMOTE: &str = "origin";
+
+// ============================================================================
+// FORGE STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ForgeKind {
+    GitHub,
+    GitLab,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForgeRepo {
+    pub kind: ForgeKind,
+    pub host: String,
+    /// "owner/repo"; GitLab projects can sit in nested groups.
+    pub project: String,
+    pub remote: String,
+    pub web_url: String,
+    /// The checked-out branch and the remote branch it tracks.
+    pub branch: Option<String>,
+    pub upstream: Option<String>,
+    pub authenticated: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForgePullRequest {
+    pub number: u64,
+    pub title: String,
+    /// "open", "closed" or "merged".
+    pub state: String,
+    pub draft: bool,
+    pub author: Option<String>,
+    pub source_branch: String,
+    pub target_branch: String,
+    pub url: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// Only filled in by `forge_get_pull_request`.
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForgeIssue {
+    pub number: u64,
+    pub title: String,
+    /// "open" or "closed".
+    pub state: String,
+    pub author: Option<String>,
+    pub labels: Vec<String>,
+    pub comments: u64,
+    pub url: String,
+    pub created_at: Option<String>,
+    pub updated_at: Option<String>,
+    /// Only filled in by `forge_get_issue`.
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ForgeReviewComment {
+    pub id: String,
+    pub author: Option<String>,
+    pub body: String,
+    /// The commented file and 0-based line in the new version, for inline
+    /// comments.
+    pub path: Option<String>,
+    pub line: Option<u64>,
+    /// The comment this one answers, for threaded replies.
+    pub in_reply_to: Option<String>,
+    pub created_at: Option<String>,
+    pub url: Option<String>,
+}
+
+struct Forge {
+    kind: ForgeKind,
+    host: String,
+    project: String,
+    api: String,
+    token: Option<String>,
+    client: reqwest::Client,
+}
+
+// ============================================================================
+// FORGE DETECTION
+// ============================================================================
+
+/// `owner/repo` from `https://host/owner/repo.git` or
+/// `git@host:owner/repo.git`.
+fn remote_project(url: &str) -> Option<String> {
+    let rest = url.split_once("://").map(|(_, rest)| rest).unwrap_or(url);
+    let rest = rest.rsplit_once('@').map(|(_, host)| host).unwrap_or(rest);
+    let (_, path) = rest.split_once(['/', ':'])?;
+    // An ssh:// URL may carry a port before the path
+    let path = match path.split_once('/') {
+        Some((port, path)) if url.starts_with("ssh://") && port.chars().all(|c| c.is_ascii_digit()) => path,
+        _ => path,
+    };
+    let project = path.trim_matches('/').trim_end_matches(".git");
+    project.contains('/').then(|| project.to_string())
+}
+
+fn forge_kind(host: &str) -> Option<ForgeKind> {
+    if host.contains("github") {
+        Some(ForgeKind::GitHub)
+    } else if host.contains("gitlab") {
+        Some(ForgeKind::GitLab)
+    } else {
+        None
+    }
+}
+
+/// The stored HTTPS token of the host (see `git_set_credentials`), else the
+/// usual environment variables.
+fn forge_token(app: &AppHandle, kind: ForgeKind, host: &str) -> Option<String> {
+    let variables: &[&str] = match kind {
+        ForgeKind::GitHub => &["GITHUB_TOKEN", "GH_TOKEN"],
+        ForgeKind::GitLab => &["GITLAB_TOKEN"],
+    };
+    stored_token(app, host)
+        .map(|(_, token)| token)
+        .or_else(|| variables.iter().find_map(|v| std::env::var(v).ok().filter(|t| !t.is_empty())))
+}
+
+/// The checked-out branch and the name of its upstream on the remote.
+fn current_branch(repo: &Repository) -> (Option<String>, Option<(String, String)>) {
+    let Ok(head) = repo.head() else {
+        return (None, None);
+    };
+    let Some(branch) = head.shorthand().filter(|_| head.is_branch()).map(|s| s.to_string()) else {
+        return (None, None);
+    };
+    let upstream = repo
+        .find_branch(&branch, BranchType::Local)
+        .ok()
+        .and_then(|b| b.upstream().ok())
+        .and_then(|u| u.name().ok().flatten().map(|n| n.to_string()))
+        .and_then(|name| {
+            let remote = repo.branch_remote_name(&format!("refs/remotes/{}", name)).ok()?;
+            let remote = remote.as_str()?.to_string();
+            let remote_branch = name.strip_prefix(&format!("{}/", remote))?.to_string();
+            Some((remote, remote_branch))
+        });
+    (Some(branch), upstream)
+}
+
+struct RepoContext {
+    forge: Forge,
+    remote: String,
+    branch: Option<String>,
+    upstream: Option<String>,
+}
+
+/// The forge of `remote`, defaulting to the current branch's upstream
+/// remote and then `origin`.
+fn open_forge(app: &AppHandle, repo_path: &str, remote: Option<&str>) -> Result<RepoContext, String> {
+    let repo = Repository::open(repo_path).map_err(|e| e.message().to_string())?;
+    let (branch, upstream) = current_branch(&repo);
+    let remote_name = remote
+        .map(|r| r.to_string())
+        .or_else(|| upstream.as_ref().map(|(remote, _)| remote.clone()))
+        .unwrap_or_else(|| DEFAULT_REMOTE.to_string());
+    let remote = repo
+        .find_remote(&remote_name)
+        .map_err(|e| format!("Failed to find remote {}: {}", remote_name, e.message()))?;
+    let url = remote.url().ok_or_else(|| format!("Remote {} has no URL", remote_name))?;
+
+    let host = remote_host(url);
+    let kind = forge_kind(&host).ok_or_else(|| format!("{} is not a GitHub or GitLab host", host))?;
+    let project = remote_project(url).ok_or_else(|| format!("Failed to parse repository from {}", url))?;
+    let api = match kind {
+        ForgeKind::GitHub if host == "github.com" => "https://api.github.com".to_string(),
+        ForgeKind::GitHub => format!("https://{}/api/v3", host), // GitHub Enterprise
+        ForgeKind::GitLab => format!("https://{}/api/v4", host),
+    };
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(FORGE_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    Ok(RepoContext {
+        forge: Forge {
+            token: forge_token(app, kind, &host),
+            kind,
+            host,
+            project,
+            api,
+            client,
+        },
+        remote: remote_name,
+        branch,
+        upstream: upstream.map(|(_, branch)| branch),
+    })
+}
+
+// ============================================================================
+// FORGE API
+// ============================================================================
+
+impl Forge {
+    /// `/repos/owner/repo` or `/projects/group%2Frepo`.
+    fn repo_endpoint(&self) -> String {
+        match self.kind {
+            ForgeKind::GitHub => format!("/repos/{}", self.project),
+            ForgeKind::GitLab => format!("/projects/{}", self.project.replace('/', "%2F")),
+        }
+    }
+
+    fn web_url(&self) -> String {
+        format!("https://{}/{}", self.host, self.project)
+    }
+
+    fn request(&self, method: Method, endpoint: &str) -> reqwest::RequestBuilder {
+        let url = format!("{}{}{}", self.api, self.repo_endpoint(), endpoint);
+        let mut builder = self.client.request(method, url).header(USER_AGENT, "GenCode");
+        builder = match self.kind {
+            ForgeKind::GitHub => builder.header(ACCEPT, "application/vnd.github+json"),
+            ForgeKind::GitLab => builder,
+        };
+        match (&self.token, self.kind) {
+            (Some(token), ForgeKind::GitHub) => builder.bearer_auth(token),
+            (Some(token), ForgeKind::GitLab) => builder.header("PRIVATE-TOKEN", token),
+            (None, _) => builder,
+        }
+    }
+
+    async fn send(&self, builder: reqwest::RequestBuilder) -> Result<reqwest::Response, String> {
+        let response = builder
+            .send()
+            .await
+            .map_err(|e| format!("Failed to reach {}: {}", self.host, e))?;
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        if status == reqwest::StatusCode::UNAUTHORIZED || (status.as_u16() == 404 && self.token.is_none()) {
+            return Err(format!(
+                "Authentication required for {}; save a token with git_set_credentials",
+                self.host
+            ));
+        }
+        let body: Value = response.json().await.unwrap_or(Value::Null);
+        let message = body
+            .get("message")
+            .or_else(|| body.get("error"))
+            .map(|m| m.as_str().map(|s| s.to_string()).unwrap_or_else(|| m.to_string()))
+            .unwrap_or_else(|| status.to_string());
+        Err(format!("{} API error ({}): {}", self.host, status.as_u16(), message))
+    }
+
+    async fn json(&self, builder: reqwest::RequestBuilder) -> Result<Value, String> {
+        self.send(builder)
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response from {}: {}", self.host, e))
+    }
+
+    async fn get(&self, endpoint: &str) -> Result<Value, String> {
+        self.json(self.request(Method::GET, endpoint)).await
+    }
+
+    async fn get_list(&self, endpoint: &str, query: &[(&str, String)]) -> Result<Vec<Value>, String> {
+        let mut query = query.to_vec();
+        query.push(("per_page", PAGE_SIZE.to_string()));
+        let builder = self.request(Method::GET, endpoint).query(&query);
+        Ok(self.json(builder).await?.as_array().cloned().unwrap_or_default())
+    }
+
+    async fn default_branch(&self) -> Result<String, String> {
+        let repo = self.get("").await?;
+        repo.get("default_branch")
+            .and_then(|b| b.as_str())
+            .map(|b| b.to_string())
+            .ok_or_else(|| format!("{} has no default branch", self.project))
+    }
+}
+
+fn text(value: &Value, pointer: &str) -> Option<String> {
+    value.pointer(pointer).and_then(|v| v.as_str()).map(|s| s.to_string())
+}
+
+fn integer(value: &Value, pointer: &str) -> u64 {
+    value.pointer(pointer).and_then(|v| v.as_u64()).unwrap_or(0)
+}
+
+fn pull_request(kind: ForgeKind, value: &Value, with_body: bool) -> ForgePullRequest {
+    match kind {
+        ForgeKind::GitHub => ForgePullRequest {
+            number: integer(value, "/number"),
+            title: text(value, "/title").unwrap_or_default(),
+            state: if value.get("merged_at").is_some_and(|m| !m.is_null()) {
+                "merged".to_string()
+            } else {
+                text(value, "/state").unwrap_or_default()
+            },
+            draft: value.get("draft").and_then(|d| d.as_bool()).unwrap_or(false),
+            author: text(value, "/user/login"),
+            source_branch: text(value, "/head/ref").unwrap_or_default(),
+            target_branch: text(value, "/base/ref").unwrap_or_default(),
+            url: text(value, "/html_url").unwrap_or_default(),
+            created_at: text(value, "/created_at"),
+            updated_at: text(value, "/updated_at"),
+            body: if with_body { text(value, "/body") } else { None },
+        },
+        ForgeKind::GitLab => ForgePullRequest {
+            number: integer(value, "/iid"),
+            title: text(value, "/title").unwrap_or_default(),
+            state: match text(value, "/state").as_deref() {
+                Some("opened") => "open".to_string(),
+                Some("merged") => "merged".to_string(),
+                _ => "closed".to_string(),
+            },
+            draft: value.get("draft").and_then(|d| d.as_bool()).unwrap_or(false),
+            author: text(value, "/author/username"),
+            source_branch: text(value, "/source_branch").unwrap_or_default(),
+            target_branch: text(value, "/target_branch").unwrap_or_default(),
+            url: text(value, "/web_url").unwrap_or_default(),
+            created_at: text(value, "/created_at"),
+            updated_at: text(value, "/updated_at"),
+            body: if with_body { text(value, "/description") } else { None },
+        },
+    }
+}
+
+fn issue(kind: ForgeKind, value: &Value, with_body: bool) -> ForgeIssue {
+    let labels = value
+        .get("labels")
+        .and_then(|l| l.as_array())
+        .map(|labels| {
+            labels
+                .iter()
+                .filter_map(|l| l.as_str().or_else(|| l.get("name").and_then(|n| n.as_str())))
+                .map(|l| l.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    let (number_field, author, comments, url, body) = match kind {
+        ForgeKind::GitHub => ("/number", "/user/login", "/comments", "/html_url", "/body"),
+        ForgeKind::GitLab => ("/iid", "/author/username", "/user_notes_count", "/web_url", "/description"),
+    };
+    ForgeIssue {
+        number: integer(value, number_field),
+        title: text(value, "/title").unwrap_or_default(),
+        state: if text(value, "/state").as_deref() == Some("closed") { "closed" } else { "open" }.to_string(),
+        author: text(value, author),
+        labels,
+        comments: integer(value, comments),
+        url: text(value, url).unwrap_or_default(),
+        created_at: text(value, "/created_at"),
+        updated_at: text(value, "/updated_at"),
+        body: if with_body { text(value, body) } else { None },
+    }
+}
+
+/// Maps "open", "closed", "merged" or "all" to the forge's filter.
+fn state_filter(kind: ForgeKind, state: &str, pull_requests: bool) -> Result<&'static str, String> {
+    Ok(match (kind, state) {
+        (_, "all") => "all",
+        (ForgeKind::GitHub, "open") => "open",
+        (ForgeKind::GitHub, "closed") | (ForgeKind::GitHub, "merged") => "closed",
+        (ForgeKind::GitLab, "open") => "opened",
+        (ForgeKind::GitLab, "closed") => "closed",
+        (ForgeKind::GitLab, "merged") if pull_requests => "merged",
+        (_, other) => return Err(format!("Unknown state filter: {}", other)),
+    })
+}
+
+/// GitLab returns each file's hunks separately; this rebuilds one unified
+/// diff like GitHub's.
+fn gitlab_diff(files: &[Value]) -> String {
+    let mut diff = String::new();
+    for file in files {
+        let old_path = text(file, "/old_path").unwrap_or_default();
+        let new_path = text(file, "/new_path").unwrap_or_default();
+        let flag = |field: &str| file.get(field).and_then(|f| f.as_bool()).unwrap_or(false);
+        diff.push_str(&format!("diff --git a/{} b/{}\n", old_path, new_path));
+        let old_name = if flag("new_file") { "/dev/null".to_string() } else { format!("a/{}", old_path) };
+        let new_name = if flag("deleted_file") { "/dev/null".to_string() } else { format!("b/{}", new_path) };
+        diff.push_str(&format!("--- {}\n+++ {}\n", old_name, new_name));
+        let hunks = text(file, "/diff").unwrap_or_default();
+        diff.push_str(&hunks);
+        if !hunks.is_empty() && !hunks.ends_with('\n') {
+            diff.push('\n');
+        }
+    }
+    diff
+}
+
+// ============================================================================
+// FORGE TAURI COMMANDS
+// ============================================================================
+
+/// The GitHub or GitLab repository behind `remote` (the upstream remote of
+/// the current branch, else origin).
+#[tauri::command]
+pub fn forge_repo_info(
+    app: AppHandle,
+    repo_path: String,
+    remote: Option<String>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<ForgeRepo, String> {
+    workspace.check(&repo_path)?;
+    let context = open_forge(&app, &repo_path, remote.as_deref())?;
+    Ok(ForgeRepo {
+        kind: context.forge.kind,
+        web_url: context.forge.web_url(),
+        authenticated: context.forge.token.is_some(),
+        host: context.forge.host,
+        project: context.forge.project,
+        remote: context.remote,
+        branch: context.branch,
+        upstream: context.upstream,
+    })
+}
+
+/// Pull (merge) requests in `state`: "open" (default), "closed", "merged"
+/// or "all". The most recently updated 100 are returned.
+#[tauri::command]
+pub async fn forge_list_pull_requests(
+    app: AppHandle,
+    repo_path: String,
+    state: Option<String>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<ForgePullRequest>, String> {
+    workspace.check(&repo_path)?;
+    let forge = open_forge(&app, &repo_path, None)?.forge;
+    let state = state.unwrap_or_else(|| "open".to_string());
+    let filter = state_filter(forge.kind, &state, true)?;
+    let (endpoint, sort) = match forge.kind {
+        ForgeKind::GitHub => ("/pulls", ("sort", "updated".to_string())),
+        ForgeKind::GitLab => ("/merge_requests", ("order_by", "updated_at".to_string())),
+    };
+    let items = forge.get_list(endpoint, &[("state", filter.to_string()), sort]).await?;
+    Ok(items
+        .iter()
+        .map(|item| pull_request(forge.kind, item, false))
+        // GitHub lists merged and closed ones together
+        .filter(|pr| state == "all" || pr.state == state)
+        .collect())
+}
+
+#[tauri::command]
+pub async fn forge_get_pull_request(
+    app: AppHandle,
+    repo_path: String,
+    number: u64,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<ForgePullRequest, String> {
+    workspace.check(&repo_path)?;
+    let forge = open_forge(&app, &repo_path, None)?.forge;
+    let endpoint = match forge.kind {
+        ForgeKind::GitHub => format!("/pulls/{}", number),
+        ForgeKind::GitLab => format!("/merge_requests/{}", number),
+    };
+    Ok(pull_request(forge.kind, &forge.get(&endpoint).await?, true))
+}
+
+/// The pull request's changes as a unified diff.
+#[tauri::command]
+pub async fn forge_pull_request_diff(
+    app: AppHandle,
+    repo_path: String,
+    number: u64,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<String, String> {
+    workspace.check(&repo_path)?;
+    let forge = open_forge(&app, &repo_path, None)?.forge;
+    match forge.kind {
+        ForgeKind::GitHub => {
+            let builder = forge
+                .request(Method::GET, &format!("/pulls/{}", number))
+                .header(ACCEPT, "application/vnd.github.diff");
+            forge
+                .send(builder)
+                .await?
+                .text()
+                .await
+                .map_err(|e| format!("Failed to read diff: {}", e))
+        }
+        ForgeKind::GitLab => {
+            let files = forge.get_list(&format!("/merge_requests/{}/diffs", number), &[]).await?;
+            Ok(gitlab_diff(&files))
+        }
+    }
+}
+
+/// Inline review comments of a pull request, oldest first.
+#[tauri::command]
+pub async fn forge_review_comments(
+    app: AppHandle,
+    repo_path: String,
+    number: u64,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<ForgeReviewComment>, String> {
+    workspace.check(&repo_path)?;
+    let forge = open_forge(&app, &repo_path, None)?.forge;
+    match forge.kind {
+        ForgeKind::GitHub => {
+            let comments = forge.get_list(&format!("/pulls/{}/comments", number), &[]).await?;
+            Ok(comments
+                .iter()
+                .map(|c| ForgeReviewComment {
+                    id: integer(c, "/id").to_string(),
+                    author: text(c, "/user/login"),
+                    body: text(c, "/body").unwrap_or_default(),
+                    path: text(c, "/path"),
+                    line: c.get("line").and_then(|l| l.as_u64()).map(|l| l.saturating_sub(1)),
+                    in_reply_to: c.get("in_reply_to_id").and_then(|r| r.as_u64()).map(|r| r.to_string()),
+                    created_at: text(c, "/created_at"),
+                    url: text(c, "/html_url"),
+                })
+                .collect())
+        }
+        ForgeKind::GitLab => {
+            let discussions = forge
+                .get_list(&format!("/merge_requests/{}/discussions", number), &[])
+                .await?;
+            let mut comments = Vec::new();
+            for discussion in &discussions {
+                let notes = discussion.get("notes").and_then(|n| n.as_array()).cloned().unwrap_or_default();
+                let first = notes.first().map(|n| integer(n, "/id").to_string());
+                // System notes record pushes and label changes
+                for note in notes.iter().filter(|n| n.get("system").and_then(|s| s.as_bool()) != Some(true)) {
+                    let id = integer(note, "/id").to_string();
+                    comments.push(ForgeReviewComment {
+                        in_reply_to: first.clone().filter(|first| *first != id),
+                        id,
+                        author: text(note, "/author/username"),
+                        body: text(note, "/body").unwrap_or_default(),
+                        path: text(note, "/position/new_path"),
+                        line: note.pointer("/position/new_line").and_then(|l| l.as_u64()).map(|l| l.saturating_sub(1)),
+                        created_at: text(note, "/created_at"),
+                        url: None,
+                    });
+                }
+            }
+            Ok(comments)
+        }
+    }
+}
+
+/// Issues in `state`: "open" (default), "closed" or "all". Pull requests,
+/// which GitHub lists as issues too, are left out.
+#[tauri::command]
+pub async fn forge_list_issues(
+    app: AppHandle,
+    repo_path: String,
+    state: Option<String>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<ForgeIssue>, String> {
+    workspace.check(&repo_path)?;
+    let forge = open_forge(&app, &repo_path, None)?.forge;
+    let filter = state_filter(forge.kind, state.as_deref().unwrap_or("open"), false)?;
+    let sort = match forge.kind {
+        ForgeKind::GitHub => ("sort", "updated".to_string()),
+        ForgeKind::GitLab => ("order_by", "updated_at".to_string()),
+    };
+    let items = forge.get_list("/issues", &[("state", filter.to_string()), sort]).await?;
+    Ok(items
+        .iter()
+        .filter(|item| item.get("pull_request").is_none())
+        .map(|item| issue(forge.kind, item, false))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn forge_get_issue(
+    app: AppHandle,
+    repo_path: String,
+    number: u64,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<ForgeIssue, String> {
+    workspace.check(&repo_path)?;
+    let forge = open_forge(&app, &repo_path, None)?.forge;
+    Ok(issue(forge.kind, &forge.get(&format!("/issues/{}", number)).await?, true))
+}
+
+/// Opens a pull request from the current branch, which must already be
+/// pushed, into `base` (default: the repository's default branch).
+#[tauri::command]
+pub async fn forge_create_pull_request(
+    app: AppHandle,
+    repo_path: String,
+    title: String,
+    body: Option<String>,
+    base: Option<String>,
+    draft: Option<bool>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<ForgePullRequest, String> {
+    workspace.check(&repo_path)?;
+    let context = open_forge(&app, &repo_path, None)?;
+    let branch = context.branch.ok_or("HEAD is not on a branch")?;
+    let head = context
+        .upstream
+        .ok_or_else(|| format!("Push {} before opening a pull request", branch))?;
+    let forge = context.forge;
+    let base = match base {
+        Some(base) => base,
+        None => forge.default_branch().await?,
+    };
+    if head == base {
+        return Err(format!("{} is the target branch", head));
+    }
+    let draft = draft.unwrap_or(false);
+
+    let (endpoint, payload) = match forge.kind {
+        ForgeKind::GitHub => (
+            "/pulls",
+            json!({ "title": title, "head": head, "base": base, "body": body.unwrap_or_default(), "draft": draft }),
+        ),
+        ForgeKind::GitLab => (
+            "/merge_requests",
+            json!({
+                "title": if draft { format!("Draft: {}", title) } else { title },
+                "source_branch": head,
+                "target_branch": base,
+                "description": body.unwrap_or_default(),
+            }),
+        ),
+    };
+    let created = forge.json(forge.request(Method::POST, endpoint).json(&payload)).await?;
+    Ok(pull_request(forge.kind, &created, true))
+}
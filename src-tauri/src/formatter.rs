@@ -0,0 +1,254 @@
+use regex::Regex;
+use serde::Serialize;
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+use tauri::State;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::sandbox::WorkspaceState;
+use crate::terminal::find_in_path;
+
+const FORMAT_TIMEOUT_SECS: u64 = 30;
+const DEFAULT_RUST_EDITION: &str = "2021";
+
+const PRETTIER_EXTENSIONS: &[&str] = &[
+    "js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "json", "jsonc", "json5", "css", "scss", "less", "html",
+    "vue", "md", "markdown", "mdx", "yaml", "yml", "graphql", "gql",
+];
+const PRETTIER_CONFIGS: &[&str] = &[
+    ".prettierrc",
+    ".prettierrc.json",
+    ".prettierrc.json5",
+    ".prettierrc.yaml",
+    ".prettierrc.yml",
+    ".prettierrc.toml",
+    ".prettierrc.js",
+    ".prettierrc.cjs",
+    ".prettierrc.mjs",
+    "prettier.config.js",
+    "prettier.config.cjs",
+    "prettier.config.mjs",
+];
+const RUSTFMT_CONFIGS: &[&str] = &["rustfmt.toml", ".rustfmt.toml"];
+
+// ============================================================================
+// FORMATTER STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct FormatError {
+    pub message: String,
+    /// 0-based location of the error, when the formatter reports one.
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FormatResult {
+    /// "prettier", "rustfmt", "black" or "gofmt".
+    pub formatter: String,
+    pub program: String,
+    /// The project configuration the formatter picks up, if any.
+    pub config_file: Option<String>,
+    /// The formatted text; `None` when the formatter rejected the input.
+    pub formatted: Option<String>,
+    pub changed: bool,
+    pub error: Option<FormatError>,
+}
+
+struct Formatter {
+    name: &'static str,
+    program: PathBuf,
+    args: Vec<String>,
+    config_file: Option<PathBuf>,
+    /// Line and column captures in the formatter's error output.
+    error_location: &'static str,
+}
+
+// ============================================================================
+// FORMATTER DETECTION
+// ============================================================================
+
+fn nearest(dir: &Path, names: &[&str]) -> Option<PathBuf> {
+    dir.ancestors()
+        .flat_map(|ancestor| names.iter().map(move |name| ancestor.join(name)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Windows installs formatters as `.exe` or, for npm packages, `.cmd` shims.
+fn program_names(name: &str) -> Vec<String> {
+    if cfg!(target_os = "windows") {
+        vec![format!("{}.exe", name), format!("{}.cmd", name)]
+    } else {
+        vec![name.to_string()]
+    }
+}
+
+pub(crate) fn on_path(name: &str) -> Option<PathBuf> {
+    program_names(name).iter().find_map(|candidate| find_in_path(candidate))
+}
+
+/// A project-local install in `node_modules/.bin`, preferred over a global
+/// one so the project's pinned version formats its code.
+pub(crate) fn node_bin(dir: &Path, name: &str) -> Option<PathBuf> {
+    let names: Vec<String> = program_names(name).into_iter().map(|n| format!("node_modules/.bin/{}", n)).collect();
+    let names: Vec<&str> = names.iter().map(|n| n.as_str()).collect();
+    nearest(dir, &names)
+}
+
+/// The edition from the nearest Cargo.toml; rustfmt assumes 2015 otherwise.
+fn rust_edition(dir: &Path) -> String {
+    let edition = Regex::new(r#"(?m)^\s*edition\s*=\s*"(\d{4})""#).unwrap();
+    nearest(dir, &["Cargo.toml"])
+        .and_then(|manifest| std_fs::read_to_string(manifest).ok())
+        .and_then(|content| edition.captures(&content).map(|c| c[1].to_string()))
+        .unwrap_or_else(|| DEFAULT_RUST_EDITION.to_string())
+}
+
+fn detect_formatter(path: &Path) -> Result<Formatter, String> {
+    let dir = path.parent().unwrap_or(path);
+    let extension = path
+        .extension()
+        .map(|e| e.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    let file_path = path.to_string_lossy().to_string();
+
+    match extension.as_str() {
+        "rs" => {
+            let program = on_path("rustfmt")
+                .ok_or("rustfmt not found on PATH; install it with `rustup component add rustfmt`")?;
+            let config_file = nearest(dir, RUSTFMT_CONFIGS);
+            let mut args = vec!["--edition".to_string(), rust_edition(dir)];
+            if let Some(config) = &config_file {
+                args.push("--config-path".to_string());
+                args.push(config.to_string_lossy().to_string());
+            }
+            Ok(Formatter {
+                name: "rustfmt",
+                program,
+                args,
+                config_file,
+                error_location: r"<stdin>:(\d+):(\d+)",
+            })
+        }
+        "py" | "pyi" => Ok(Formatter {
+            name: "black",
+            program: on_path("black").ok_or("black not found on PATH; install it with `pip install black`")?,
+            args: vec!["--quiet".to_string(), "--stdin-filename".to_string(), file_path, "-".to_string()],
+            config_file: nearest(dir, &["pyproject.toml"]).filter(|config| {
+                std_fs::read_to_string(config).is_ok_and(|content| content.contains("[tool.black]"))
+            }),
+            error_location: r"Cannot parse[^:]*: (\d+):(\d+)",
+        }),
+        "go" => Ok(Formatter {
+            name: "gofmt",
+            program: on_path("gofmt").ok_or("gofmt not found on PATH; it ships with the Go toolchain")?,
+            args: Vec::new(),
+            config_file: None,
+            error_location: r"<standard input>:(\d+):(\d+)",
+        }),
+        ext if PRETTIER_EXTENSIONS.contains(&ext) => Ok(Formatter {
+            name: "prettier",
+            program: node_bin(dir, "prettier")
+                .or_else(|| on_path("prettier"))
+                .ok_or("prettier not found in node_modules or on PATH; install it with `npm install --save-dev prettier`")?,
+            // The file path selects the parser and the config to resolve
+            args: vec!["--stdin-filepath".to_string(), file_path],
+            config_file: nearest(dir, PRETTIER_CONFIGS),
+            error_location: r"\((\d+):(\d+)\)",
+        }),
+        "" => Err(format!("No formatter for {}", path.display())),
+        ext => Err(format!("No formatter for .{} files", ext)),
+    }
+}
+
+// ============================================================================
+// FORMATTING
+// ============================================================================
+
+/// Pipes `content` through the formatter, run from the file's directory so
+/// it finds the project configuration.
+async fn run_formatter(formatter: &Formatter, cwd: &Path, content: &str) -> Result<std::process::Output, String> {
+    let mut child = Command::new(&formatter.program)
+        .args(&formatter.args)
+        .current_dir(cwd)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {}", formatter.name, e))?;
+
+    // Written concurrently so a large file can't fill both pipes
+    let mut stdin = child.stdin.take().ok_or("Failed to capture stdin")?;
+    let input = content.as_bytes().to_vec();
+    let writer = tokio::spawn(async move {
+        let _ = stdin.write_all(&input).await;
+    });
+
+    let output = tokio::time::timeout(Duration::from_secs(FORMAT_TIMEOUT_SECS), child.wait_with_output())
+        .await
+        .map_err(|_| format!("{} timed out after {}s", formatter.name, FORMAT_TIMEOUT_SECS))?
+        .map_err(|e| format!("Failed to run {}: {}", formatter.name, e))?;
+    let _ = writer.await;
+    Ok(output)
+}
+
+fn format_error(formatter: &Formatter, stderr: &str) -> FormatError {
+    let location = Regex::new(formatter.error_location)
+        .ok()
+        .and_then(|pattern| pattern.captures(stderr))
+        .and_then(|c| Some((c[1].parse::<usize>().ok()?, c[2].parse::<usize>().ok()?)));
+    let message = stderr.trim();
+    FormatError {
+        message: if message.is_empty() {
+            format!("{} failed", formatter.name)
+        } else {
+            message.to_string()
+        },
+        line: location.map(|(line, _)| line.saturating_sub(1)),
+        column: location.map(|(_, column)| column.saturating_sub(1)),
+    }
+}
+
+// ============================================================================
+// FORMATTER TAURI COMMANDS
+// ============================================================================
+
+/// Formats `content` (or the file on disk) with the formatter for its
+/// language: prettier, rustfmt, black or gofmt, preferring a project-local
+/// install. Nothing is written; a rejected input is reported in `error`.
+#[tauri::command]
+pub async fn format_file(
+    path: String,
+    content: Option<String>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<FormatResult, String> {
+    let resolved = workspace.check(&path)?;
+    let content = match content {
+        Some(content) => content,
+        None => tokio::fs::read_to_string(&resolved)
+            .await
+            .map_err(|e| format!("Failed to read file: {}", e))?,
+    };
+    let formatter = detect_formatter(&resolved)?;
+    let cwd = resolved.parent().unwrap_or(&resolved);
+    let output = run_formatter(&formatter, cwd, &content).await?;
+
+    let (formatted, error) = if output.status.success() {
+        (Some(String::from_utf8_lossy(&output.stdout).to_string()), None)
+    } else {
+        (None, Some(format_error(&formatter, &String::from_utf8_lossy(&output.stderr))))
+    };
+    Ok(FormatResult {
+        formatter: formatter.name.to_string(),
+        program: formatter.program.to_string_lossy().to_string(),
+        config_file: formatter.config_file.map(|c| c.to_string_lossy().to_string()),
+        changed: formatted.as_ref().is_some_and(|f| *f != content),
+        formatted,
+        error,
+    })
+}
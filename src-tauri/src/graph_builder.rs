@@ -0,0 +1,36 @@
+use tauri::{AppHandle, Manager, State};
+
+pub(crate) use gencode_core::graph_builder::{build_graph, build_graph_with_progress, read_graph_file};
+
+use crate::sandbox::WorkspaceState;
+use crate::{CodeGraph, ParserState};
+
+// ============================================================================
+// GRAPH BUILDER TAURI COMMANDS
+// ============================================================================
+
+/// Builds the code graph of `root` natively, as `gencode index` does.
+#[tauri::command]
+pub async fn build_code_graph(
+    app: AppHandle,
+    root: String,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<CodeGraph, String> {
+    let root_path = workspace.check(&root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+    tokio::task::spawn_blocking(move || build_graph(&app.state::<ParserState>(), &root_path))
+        .await
+        .map_err(|e| format!("Graph build failed: {}", e))?
+}
+
+/// Opens a graph precomputed with `gencode index --output`, such as a CI
+/// artifact, so it can be viewed or stored without re-parsing.
+#[tauri::command]
+pub async fn load_code_graph(path: String, workspace: State<'_, WorkspaceState>) -> Result<CodeGraph, String> {
+    let path = workspace.check(&path)?;
+    tokio::task::spawn_blocking(move || read_graph_file(&path))
+        .await
+        .map_err(|e| format!("Failed to load graph: {}", e))?
+}
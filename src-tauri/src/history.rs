@@ -0,0 +1,227 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::fs as std_fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::files::{decode_bytes, detect_encoding, hash_bytes, resolve_path};
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+
+const MAX_SNAPSHOT_BYTES: u64 = 5 * 1024 * 1024;
+const MAX_VERSIONS_PER_FILE: i64 = 50;
+const MAX_TOTAL_BYTES: i64 = 256 * 1024 * 1024;
+
+// ============================================================================
+// FILE HISTORY STATE
+// ============================================================================
+
+/// Local timeline of file contents, saved before the editor overwrites or
+/// deletes a file. Contents are stored once per hash.
+pub struct HistoryState {
+    conn: Mutex<Connection>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileVersion {
+    pub id: i64,
+    pub path: String,
+    pub hash: String,
+    pub size: i64,
+    pub reason: String, // "write", "create", "delete" or "restore"
+    pub created_at: i64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn history_key(path: &Path) -> String {
+    resolve_path(path).to_string_lossy().to_string()
+}
+
+fn row_to_version(row: &rusqlite::Row) -> rusqlite::Result<FileVersion> {
+    Ok(FileVersion {
+        id: row.get(0)?,
+        path: row.get(1)?,
+        hash: row.get(2)?,
+        size: row.get(3)?,
+        reason: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+impl HistoryState {
+    pub fn open(db_path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(db_path)
+            .map_err(|e| format!("Failed to open history database: {}", e))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS blobs (
+                 hash TEXT PRIMARY KEY,
+                 content BLOB NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS versions (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 path TEXT NOT NULL,
+                 hash TEXT NOT NULL REFERENCES blobs(hash),
+                 size INTEGER NOT NULL,
+                 reason TEXT NOT NULL,
+                 created_at INTEGER NOT NULL
+             );
+             CREATE INDEX IF NOT EXISTS idx_versions_path ON versions(path, id);",
+        )
+        .map_err(|e| format!("Failed to initialize history database: {}", e))?;
+
+        Ok(HistoryState {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    /// Saves the current content of `path` before it changes. Missing,
+    /// oversized and unchanged files are skipped; directories aren't
+    /// snapshotted (deleted ones go to the trash).
+    pub(crate) fn snapshot(&self, path: &Path, reason: &str) -> Result<(), String> {
+        let Ok(metadata) = std_fs::metadata(path) else {
+            return Ok(());
+        };
+        if !metadata.is_file() || metadata.len() > MAX_SNAPSHOT_BYTES {
+            return Ok(());
+        }
+        let content = std_fs::read(path).map_err(|e| format!("Failed to read file: {}", e))?;
+        let hash = hash_bytes(&content);
+
+        let key = history_key(path);
+        let conn = self.conn.lock_or_recover();
+        let latest: Option<String> = conn
+            .query_row(
+                "SELECT hash FROM versions WHERE path = ?1 ORDER BY id DESC LIMIT 1",
+                params![key],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| e.to_string())?;
+        if latest.as_deref() == Some(hash.as_str()) {
+            return Ok(());
+        }
+
+        conn.execute(
+            "INSERT OR IGNORE INTO blobs (hash, content) VALUES (?1, ?2)",
+            params![hash, content],
+        )
+        .map_err(|e| e.to_string())?;
+        conn.execute(
+            "INSERT INTO versions (path, hash, size, reason, created_at) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![key, hash, content.len() as i64, reason, now_secs()],
+        )
+        .map_err(|e| e.to_string())?;
+
+        prune(&conn, &key)
+    }
+}
+
+/// Keeps the newest versions of `path`, then drops the oldest versions
+/// overall until stored content fits the size cap.
+fn prune(conn: &Connection, path: &str) -> Result<(), String> {
+    conn.execute(
+        "DELETE FROM versions WHERE path = ?1 AND id NOT IN (
+             SELECT id FROM versions WHERE path = ?1 ORDER BY id DESC LIMIT ?2
+         )",
+        params![path, MAX_VERSIONS_PER_FILE],
+    )
+    .map_err(|e| e.to_string())?;
+    conn.execute("DELETE FROM blobs WHERE hash NOT IN (SELECT hash FROM versions)", [])
+        .map_err(|e| e.to_string())?;
+
+    loop {
+        let total: i64 = conn
+            .query_row("SELECT COALESCE(SUM(LENGTH(content)), 0) FROM blobs", [], |row| row.get(0))
+            .map_err(|e| e.to_string())?;
+        if total <= MAX_TOTAL_BYTES {
+            return Ok(());
+        }
+        let removed = conn
+            .execute("DELETE FROM versions WHERE id = (SELECT MIN(id) FROM versions)", [])
+            .map_err(|e| e.to_string())?;
+        conn.execute("DELETE FROM blobs WHERE hash NOT IN (SELECT hash FROM versions)", [])
+            .map_err(|e| e.to_string())?;
+        if removed == 0 {
+            return Ok(());
+        }
+    }
+}
+
+// ============================================================================
+// FILE HISTORY TAURI COMMANDS
+// ============================================================================
+
+/// Saved versions of `path`, newest first.
+#[tauri::command]
+pub fn list_file_history(
+    path: String,
+    state: State<'_, HistoryState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<FileVersion>, String> {
+    workspace.check(&path)?;
+    let conn = state.conn.lock_or_recover();
+    let mut stmt = conn
+        .prepare(
+            "SELECT id, path, hash, size, reason, created_at FROM versions
+             WHERE path = ?1 ORDER BY id DESC",
+        )
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt
+        .query_map(params![history_key(Path::new(&path))], row_to_version)
+        .map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+fn version_content(conn: &Connection, id: i64) -> Result<(String, Vec<u8>), String> {
+    conn.query_row(
+        "SELECT v.path, b.content FROM versions v JOIN blobs b ON b.hash = v.hash WHERE v.id = ?1",
+        params![id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )
+    .optional()
+    .map_err(|e| e.to_string())?
+    .ok_or_else(|| format!("File version not found: {}", id))
+}
+
+/// Content of a saved version, decoded as text for the diff view.
+#[tauri::command]
+pub fn get_file_version(
+    id: i64,
+    state: State<'_, HistoryState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<String, String> {
+    let conn = state.conn.lock_or_recover();
+    let (path, content) = version_content(&conn, id)?;
+    workspace.check(&path)?;
+    Ok(decode_bytes(&content, detect_encoding(&content)))
+}
+
+/// Writes a saved version back to its file. The current content is
+/// snapshotted first, so a restore can itself be undone.
+#[tauri::command]
+pub fn restore_file_version(
+    id: i64,
+    state: State<'_, HistoryState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<String, String> {
+    let (path, content) = version_content(&state.conn.lock_or_recover(), id)?;
+    workspace.check(&path)?;
+    let file_path = Path::new(&path);
+
+    state.snapshot(file_path, "restore")?;
+    if let Some(parent) = file_path.parent() {
+        std_fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
+    }
+    std_fs::write(file_path, content).map_err(|e| format!("Failed to restore file: {}", e))?;
+    Ok(path)
+}
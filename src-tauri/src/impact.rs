@@ -0,0 +1,225 @@
+use neo4rs::{query, Graph};
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap, VecDeque};
+use tauri::State;
+
+use crate::locks::LockExt;
+use crate::symbols::SymbolIndexState;
+use crate::Neo4jState;
+
+const DEFAULT_MAX_DEPTH: usize = 10;
+
+/// File-level dependencies in the code graph: imports between files and
+/// calls between functions of different files.
+const DEPENDENCY_QUERY: &str = "MATCH (a:FILE)-[:IMPORTS_FROM]->(b:FILE) RETURN a.path AS from, b.path AS to, 'import' AS via \
+     UNION MATCH (a:FILE)-[:CONTAINS]->()-[:CALLS]->()<-[:CONTAINS]-(b:FILE) WHERE a <> b \
+     RETURN a.path AS from, b.path AS to, 'call' AS via";
+
+// ============================================================================
+// AFFECTED FILES STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct AffectedFile {
+    pub path: String,
+    /// 1 for direct dependents of a changed file, 2 for their dependents,
+    /// and so on.
+    pub depth: usize,
+    /// "import" or "call": how `path` depends on `through`.
+    pub via: String,
+    pub through: String,
+    pub is_test: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AffectedFilesResult {
+    /// Changed paths as they were matched in the dependency graph.
+    pub changed: Vec<String>,
+    /// Changed paths the dependency graph doesn't know.
+    pub unknown: Vec<String>,
+    pub affected: Vec<AffectedFile>,
+    /// Test files among the changed and affected files, to run first.
+    pub tests: Vec<String>,
+    /// "graph" for the Neo4j code graph, "symbols" for the symbol index.
+    pub source: String,
+}
+
+/// Reverse dependencies: file to the files that depend on it, with how.
+type Dependents = HashMap<String, Vec<(String, String)>>;
+
+// ============================================================================
+// DEPENDENCY GRAPH
+// ============================================================================
+
+async fn graph_dependents(graph: &Graph) -> Result<Dependents, String> {
+    let mut result = graph
+        .execute(query(DEPENDENCY_QUERY))
+        .await
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+
+    let mut dependents: Dependents = HashMap::new();
+    while let Ok(Some(row)) = result.next().await {
+        let Ok(row) = row.to::<HashMap<String, serde_json::Value>>() else {
+            continue;
+        };
+        let field = |key: &str| row.get(key).and_then(|v| v.as_str()).unwrap_or_default().to_string();
+        let (from, to) = (field("from"), field("to"));
+        if !from.is_empty() && !to.is_empty() {
+            dependents.entry(to).or_default().push((from, field("via")));
+        }
+    }
+    Ok(dependents)
+}
+
+/// Import dependencies from the symbol index, used when no graph is
+/// connected.
+fn symbol_dependents(state: &SymbolIndexState) -> Result<Dependents, String> {
+    let table = state.table.lock_or_recover();
+    if table.root.as_os_str().is_empty() {
+        return Err("Neither a code graph nor a symbol index is available; store the graph or call index_symbols first".to_string());
+    }
+
+    let mut dependents: Dependents = HashMap::new();
+    let files: BTreeSet<&String> = table.files.keys().collect();
+    for (relative, file) in &table.files {
+        for target in file.imports.iter().filter_map(|i| i.resolved.as_ref()) {
+            // Package imports (Go, Java) resolve to a directory
+            let targets: Vec<&String> = if files.contains(target) {
+                vec![target]
+            } else {
+                let prefix = format!("{}/", target);
+                files
+                    .iter()
+                    .copied()
+                    .filter(|f| f.strip_prefix(&prefix).is_some_and(|rest| !rest.contains('/')))
+                    .collect()
+            };
+            for target in targets {
+                if target != relative {
+                    dependents
+                        .entry(target.clone())
+                        .or_default()
+                        .push((relative.clone(), "import".to_string()));
+                }
+            }
+        }
+    }
+    Ok(dependents)
+}
+
+fn normalize(path: &str) -> String {
+    path.replace('\\', "/").trim_start_matches("./").trim_end_matches('/').to_string()
+}
+
+/// The graph's name for `changed`: graph paths may be absolute or relative
+/// to the project, so either may be a suffix of the other.
+fn match_path<'a>(changed: &str, known: &BTreeSet<&'a str>) -> Option<&'a str> {
+    let changed = normalize(changed);
+    known.iter().copied().find(|known| {
+        *known == changed
+            || known.ends_with(&format!("/{}", changed))
+            || changed.ends_with(&format!("/{}", known))
+    })
+}
+
+fn is_test_path(path: &str) -> bool {
+    let path = normalize(path).to_lowercase();
+    let name = path.rsplit('/').next().unwrap_or(&path);
+    path.split('/').any(|part| matches!(part, "test" | "tests" | "__tests__" | "spec" | "specs"))
+        || name.contains(".test.")
+        || name.contains(".spec.")
+        || name.contains("_test.")
+        || name.starts_with("test_")
+}
+
+/// Breadth-first walk over reverse dependencies, so every file gets the
+/// shortest distance to a change.
+fn walk_dependents(dependents: &Dependents, changed: &[String], max_depth: usize) -> Vec<AffectedFile> {
+    let mut seen: BTreeSet<&str> = changed.iter().map(|c| c.as_str()).collect();
+    let mut queue: VecDeque<(&str, usize)> = changed.iter().map(|c| (c.as_str(), 0)).collect();
+    let mut affected = Vec::new();
+
+    while let Some((path, depth)) = queue.pop_front() {
+        if depth >= max_depth {
+            continue;
+        }
+        let Some(users) = dependents.get(path) else {
+            continue;
+        };
+        for (user, via) in users {
+            if !seen.insert(user.as_str()) {
+                continue;
+            }
+            affected.push(AffectedFile {
+                path: user.clone(),
+                depth: depth + 1,
+                via: via.clone(),
+                through: path.to_string(),
+                is_test: is_test_path(user),
+            });
+            queue.push_back((user.as_str(), depth + 1));
+        }
+    }
+    affected.sort_by(|a, b| a.depth.cmp(&b.depth).then_with(|| a.path.cmp(&b.path)));
+    affected
+}
+
+fn affected_from(
+    dependents: Dependents,
+    changed_paths: &[String],
+    max_depth: Option<usize>,
+    source: &str,
+) -> AffectedFilesResult {
+    let known: BTreeSet<&str> = dependents
+        .iter()
+        .flat_map(|(to, users)| std::iter::once(to.as_str()).chain(users.iter().map(|(from, _)| from.as_str())))
+        .collect();
+
+    let mut changed = Vec::new();
+    let mut unknown = Vec::new();
+    for path in changed_paths {
+        match match_path(path, &known) {
+            Some(found) => changed.push(found.to_string()),
+            None => unknown.push(path.clone()),
+        }
+    }
+    changed.sort();
+    changed.dedup();
+
+    let affected = walk_dependents(&dependents, &changed, max_depth.unwrap_or(DEFAULT_MAX_DEPTH));
+    let tests: BTreeSet<&str> = changed
+        .iter()
+        .map(|c| c.as_str())
+        .chain(affected.iter().map(|a| a.path.as_str()))
+        .filter(|p| is_test_path(p))
+        .collect();
+
+    AffectedFilesResult {
+        tests: tests.into_iter().map(|t| t.to_string()).collect(),
+        changed,
+        unknown,
+        affected,
+        source: source.to_string(),
+    }
+}
+
+// ============================================================================
+// AFFECTED FILES TAURI COMMANDS
+// ============================================================================
+
+/// The blast radius of changing `changed_paths`: every file that imports
+/// or calls into them, directly or transitively, up to `max_depth` hops.
+/// Uses the Neo4j code graph when connected, otherwise the import graph
+/// of the symbol index.
+#[tauri::command]
+pub async fn get_affected_files(
+    changed_paths: Vec<String>,
+    max_depth: Option<usize>,
+    neo4j: State<'_, Neo4jState>,
+    symbols: State<'_, SymbolIndexState>,
+) -> Result<AffectedFilesResult, String> {
+    match neo4j.get_graph().await {
+        Ok(graph) => Ok(affected_from(graph_dependents(&graph).await?, &changed_paths, max_depth, "graph")),
+        Err(_) => Ok(affected_from(symbol_dependents(&symbols)?, &changed_paths, max_depth, "symbols")),
+    }
+}
@@ -0,0 +1,373 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::task::AbortHandle;
+
+use crate::api_schema::ingest_api_schemas;
+use crate::code_index::{index_project_with_progress, CodeIndexState};
+use crate::embeddings::{index_embeddings_with_progress, EmbeddingIndexState};
+use crate::event_bus::emit_graph_updated;
+use crate::graph_builder::build_graph_with_progress;
+use crate::llm::LlmState;
+use crate::locks::LockExt;
+use crate::plugins::plugin_extract_graph;
+use crate::sandbox::WorkspaceState;
+use crate::search::{search_with_progress, SearchOptions};
+use crate::symbols::{index_symbols_with_progress, SymbolIndexState};
+use crate::{CodeGraph, Neo4jState, ParserState};
+
+/// Finished jobs kept for `list_jobs`; older ones are dropped.
+const MAX_FINISHED_JOBS: usize = 50;
+/// Progress updates closer together than this are not emitted.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+// ============================================================================
+// JOB STRUCTURES
+// ============================================================================
+
+/// A long-running operation to run in the background, as
+/// `{"kind": ..., "args": {...}}`.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "kind", content = "args", rename_all = "snake_case")]
+pub enum JobRequest {
+    IndexProject { root: String },
+    IndexSymbols { root: String },
+    IndexEmbeddings { root: String, model: Option<String> },
+    /// Builds the code graph of `root` and replaces the Neo4j graph with it.
+    IndexGraph { root: String },
+    /// Replaces the Neo4j graph with one built elsewhere.
+    StoreGraph { graph: CodeGraph },
+    IngestApiSchemas { root: String },
+    PluginExtractGraph { root: String, plugin_id: Option<String> },
+    /// Searches file contents like `search_in_files`, with the job id as the
+    /// `search_id` of its events.
+    SearchInFiles { root: String, query: String, options: Option<SearchOptions> },
+}
+
+impl JobRequest {
+    /// Whether the runner checks for cancellation between items. Others are
+    /// aborted at their next await instead.
+    fn stops_itself(&self) -> bool {
+        !matches!(self, Self::IngestApiSchemas { .. } | Self::PluginExtractGraph { .. })
+    }
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Also the payload of every `job-progress` event.
+#[derive(Debug, Serialize, Clone)]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: String,
+    pub status: JobStatus,
+    /// Units done out of `total`; `total` is 0 while the job can't tell.
+    pub current: usize,
+    pub total: usize,
+    pub message: Option<String>,
+    /// Milliseconds since the Unix epoch.
+    pub started_at: u64,
+    pub finished_at: Option<u64>,
+    pub result: Option<Value>,
+    pub error: Option<String>,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancelled: Arc<AtomicBool>,
+    abort: Option<AbortHandle>,
+}
+
+#[derive(Default)]
+pub struct JobState {
+    jobs: Mutex<HashMap<String, JobEntry>>,
+    next_id: AtomicU64,
+}
+
+/// What a running job reports progress through.
+#[derive(Clone)]
+pub(crate) struct JobHandle {
+    app: AppHandle,
+    id: String,
+    cancelled: Arc<AtomicBool>,
+    last_emit: Arc<Mutex<Option<Instant>>>,
+}
+
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+fn emit_job(app: &AppHandle, info: &JobInfo) {
+    let _ = app.emit("job-progress", info);
+}
+
+impl JobHandle {
+    /// Records progress and emits it at most every `PROGRESS_INTERVAL`,
+    /// always for the last unit. Fails once the job has been cancelled, so
+    /// loops calling it stop.
+    pub(crate) fn progress(&self, message: &str, current: usize, total: usize) -> Result<(), String> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err("Cancelled".to_string());
+        }
+        let state = self.app.state::<JobState>();
+        let info = {
+            let mut jobs = state.jobs.lock_or_recover();
+            let Some(entry) = jobs.get_mut(&self.id) else {
+                return Ok(());
+            };
+            entry.info.current = current;
+            entry.info.total = total;
+            entry.info.message = Some(message.to_string());
+            entry.info.clone()
+        };
+
+        let mut last_emit = self.last_emit.lock_or_recover();
+        let due = match *last_emit {
+            Some(at) => at.elapsed() >= PROGRESS_INTERVAL,
+            None => true,
+        };
+        if due || current == total {
+            *last_emit = Some(Instant::now());
+            emit_job(&self.app, &info);
+        }
+        Ok(())
+    }
+}
+
+impl JobState {
+    fn finish(&self, app: &AppHandle, id: &str, result: Result<Value, String>) {
+        let info = {
+            let mut jobs = self.jobs.lock_or_recover();
+            let Some(entry) = jobs.get_mut(id) else {
+                return;
+            };
+            // A cancelled job already reported its final state
+            if entry.info.status != JobStatus::Running {
+                return;
+            }
+            entry.abort = None;
+            entry.info.finished_at = Some(now_millis());
+            match result {
+                Ok(value) => {
+                    entry.info.status = JobStatus::Completed;
+                    entry.info.result = Some(value);
+                }
+                Err(error) => {
+                    entry.info.status = JobStatus::Failed;
+                    entry.info.error = Some(error);
+                }
+            }
+            let info = entry.info.clone();
+            prune(&mut jobs);
+            info
+        };
+        emit_job(app, &info);
+    }
+}
+
+fn prune(jobs: &mut HashMap<String, JobEntry>) {
+    let mut finished: Vec<(u64, String)> = jobs
+        .values()
+        .filter(|entry| entry.info.status != JobStatus::Running)
+        .map(|entry| (entry.info.finished_at.unwrap_or(0), entry.info.id.clone()))
+        .collect();
+    if finished.len() <= MAX_FINISHED_JOBS {
+        return;
+    }
+    finished.sort();
+    for (_, id) in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+        jobs.remove(id);
+    }
+}
+
+// ============================================================================
+// JOB RUNNERS
+// ============================================================================
+
+fn to_result<T: Serialize>(value: T) -> Result<Value, String> {
+    serde_json::to_value(value).map_err(|e| format!("Failed to serialize job result: {}", e))
+}
+
+async fn store_graph(app: &AppHandle, job: &JobHandle, graph: &CodeGraph) -> Result<Value, String> {
+    let neo4j = app.state::<Neo4jState>().get_graph().await?;
+    let message = graph
+        .store_in_neo4j_with_progress(&neo4j, |done, total| job.progress("Storing graph", done, total))
+        .await?;
+    emit_graph_updated(app, "store", None, graph.nodes.len());
+    Ok(json!({
+        "message": message,
+        "nodes": graph.nodes.len(),
+        "edges": graph.edges.len(),
+    }))
+}
+
+/// Runs the existing command behind each job kind. All but the API schema
+/// and plugin jobs report per-item progress and stop between items when
+/// cancelled.
+async fn run_job(app: AppHandle, job: JobHandle, request: JobRequest) -> Result<Value, String> {
+    let workspace = app.state::<WorkspaceState>();
+    let progress = |message: &'static str| {
+        let job = job.clone();
+        move |done, total| job.progress(message, done, total)
+    };
+    match request {
+        JobRequest::IndexProject { root } => {
+            let on_file = progress("Indexing files");
+            let state = app.state::<CodeIndexState>();
+            to_result(index_project_with_progress(&app, &root, &state, &workspace, on_file).await?)
+        }
+        JobRequest::IndexSymbols { root } => {
+            let on_file = progress("Indexing symbols");
+            let state = app.state::<SymbolIndexState>();
+            to_result(index_symbols_with_progress(&app, &root, &state, &workspace, on_file).await?)
+        }
+        JobRequest::IndexEmbeddings { root, model } => {
+            let progress_job = job.clone();
+            let on_progress = move |phase: &str, done, total| progress_job.progress(phase, done, total);
+            let (state, llm) = (app.state::<EmbeddingIndexState>(), app.state::<LlmState>());
+            to_result(index_embeddings_with_progress(&app, &root, model, &state, &llm, &workspace, on_progress).await?)
+        }
+        JobRequest::IndexGraph { root } => {
+            let root_path = workspace.check(&root)?;
+            if !root_path.is_dir() {
+                return Err(format!("Not a directory: {}", root));
+            }
+            let (build_app, build_job) = (app.clone(), job.clone());
+            let graph = tokio::task::spawn_blocking(move || {
+                build_graph_with_progress(&build_app.state::<ParserState>(), &root_path, |done, total| {
+                    build_job.progress("Parsing files", done, total)
+                })
+            })
+            .await
+            .map_err(|e| format!("Graph build failed: {}", e))??;
+            store_graph(&app, &job, &graph).await
+        }
+        JobRequest::StoreGraph { graph } => store_graph(&app, &job, &graph).await,
+        JobRequest::IngestApiSchemas { root } => {
+            to_result(ingest_api_schemas(app.clone(), root, app.state(), app.state()).await?)
+        }
+        JobRequest::PluginExtractGraph { root, plugin_id } => to_result(
+            plugin_extract_graph(app.clone(), root, plugin_id, app.state(), app.state(), app.state()).await?,
+        ),
+        JobRequest::SearchInFiles { root, query, options } => {
+            let on_file = progress("Searching files");
+            let summary = search_with_progress(&app, &job.id, &root, query, options, &workspace, on_file).await?;
+            if summary.cancelled {
+                return Err("Cancelled".to_string());
+            }
+            to_result(summary)
+        }
+    }
+}
+
+// ============================================================================
+// JOB TAURI COMMANDS
+// ============================================================================
+
+/// Starts `kind` (see `JobRequest`) in the background and returns at once.
+/// Progress and the final status, result or error arrive as `job-progress`
+/// events carrying the job's `JobInfo`.
+#[tauri::command]
+pub async fn start_job(
+    app: AppHandle,
+    kind: String,
+    args: Option<Value>,
+    state: State<'_, JobState>,
+) -> Result<JobInfo, String> {
+    let args = args.unwrap_or_else(|| json!({}));
+    let request: JobRequest = serde_json::from_value(json!({ "kind": kind, "args": args }))
+        .map_err(|e| format!("Invalid job {}: {}", kind, e))?;
+
+    let stops_itself = request.stops_itself();
+    let id = format!("job-{}", state.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+    let info = JobInfo {
+        id: id.clone(),
+        kind,
+        status: JobStatus::Running,
+        current: 0,
+        total: 0,
+        message: None,
+        started_at: now_millis(),
+        finished_at: None,
+        result: None,
+        error: None,
+    };
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state.jobs.lock_or_recover().insert(
+        id.clone(),
+        JobEntry {
+            info: info.clone(),
+            cancelled: cancelled.clone(),
+            abort: None,
+        },
+    );
+    emit_job(&app, &info);
+
+    let handle = JobHandle {
+        app: app.clone(),
+        id: id.clone(),
+        cancelled,
+        last_emit: Arc::new(Mutex::new(None)),
+    };
+    let task_app = app.clone();
+    let task_id = id.clone();
+    let task = tokio::spawn(async move {
+        let result = run_job(task_app.clone(), handle, request).await;
+        task_app.state::<JobState>().finish(&task_app, &task_id, result);
+    });
+    // Jobs that stop themselves are left to roll back what they were writing.
+    // The job may also be done already; then there is nothing left to abort
+    if let Some(entry) = state.jobs.lock_or_recover().get_mut(&id).filter(|_| !stops_itself) {
+        if entry.info.status == JobStatus::Running {
+            entry.abort = Some(task.abort_handle());
+        }
+    }
+    Ok(info)
+}
+
+/// Stops a running job. Indexing, searching and graph jobs stop after the
+/// current item and roll back an index commit or graph store in progress;
+/// the others stop at their next await. Work already handed to another
+/// process, such as a plugin's extraction, runs to completion.
+#[tauri::command]
+pub fn cancel_job(app: AppHandle, id: String, state: State<'_, JobState>) -> Result<JobInfo, String> {
+    let info = {
+        let mut jobs = state.jobs.lock_or_recover();
+        let entry = jobs.get_mut(&id).ok_or_else(|| format!("No such job: {}", id))?;
+        if entry.info.status != JobStatus::Running {
+            return Err(format!("Job {} is not running", id));
+        }
+        entry.cancelled.store(true, Ordering::Relaxed);
+        if let Some(abort) = entry.abort.take() {
+            abort.abort();
+        }
+        entry.info.status = JobStatus::Cancelled;
+        entry.info.finished_at = Some(now_millis());
+        let info = entry.info.clone();
+        prune(&mut jobs);
+        info
+    };
+    emit_job(&app, &info);
+    Ok(info)
+}
+
+/// Running and recently finished jobs, newest first.
+#[tauri::command]
+pub fn list_jobs(state: State<'_, JobState>) -> Vec<JobInfo> {
+    let mut jobs: Vec<JobInfo> = state.jobs.lock_or_recover().values().map(|entry| entry.info.clone()).collect();
+    jobs.sort_by_key(|job| std::cmp::Reverse(job.started_at));
+    jobs
+}
@@ -1,687 +1,117 @@
 use futures::StreamExt;
-use neo4rs::{Graph, query};
-use portable_pty::{native_pty_system, CommandBuilder, PtySize};
+use neo4rs::query;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs as std_fs;
-use std::io::{Read, Write};
 use std::path::Path;
-use std::sync::{Arc, Mutex};
-use tauri::{Emitter, State, Window};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
 use tokio::fs;
 use tokio::task;
-use tree_sitter::{Language, Node, Parser};
 
+pub use gencode_core::{
+    locks, ASTNode, CodeGraph, CodeGraphEdge, CodeGraphFile, CodeGraphNode, CypherQueryResult, GraphContext,
+    GraphStatistics, Neo4jState, ParseMetadata, ParsedFile, ParserState,
+};
+pub(crate) use gencode_core::{run_cypher, run_read_only_cypher};
+
+pub mod agent;
+pub mod annotations;
+pub mod api_schema;
+pub mod archive;
+pub mod ast_search;
+pub mod audit;
+pub mod chunker;
+pub mod code_index;
+pub mod completion;
+pub mod conversations;
+pub mod dap;
+pub mod diagnostics;
+pub mod docker;
+pub mod embeddings;
+pub mod event_bus;
+pub mod explorer;
+pub mod files;
+pub mod finder;
+pub mod forge;
+pub mod formatter;
 pub mod git;
+pub mod graph_builder;
+pub mod history;
+pub mod impact;
+pub mod jobs;
+pub mod linter;
+pub mod literals;
+pub mod llm;
+pub mod lsp;
+pub mod mcp;
+pub mod owners;
+pub mod plugins;
+pub mod process;
+pub mod prompts;
+pub mod recent;
+pub mod refactor;
+pub mod references;
+pub mod retrieval;
+pub mod review;
+pub mod sandbox;
+pub mod search;
+pub mod snippets;
+pub mod streaming;
+pub mod structured;
+pub mod summarize;
+pub mod symbols;
+pub mod tasks;
+pub mod terminal;
+pub mod test_runner;
+use agent::*;
+use annotations::*;
+use api_schema::*;
+use archive::*;
+use ast_search::*;
+use audit::*;
+use chunker::*;
+use code_index::*;
+use completion::*;
+use conversations::*;
+use dap::*;
+use diagnostics::*;
+use docker::*;
+use embeddings::*;
+use event_bus::*;
+use explorer::*;
+use files::*;
+use finder::*;
+use forge::*;
+use formatter::*;
 use git::*;
-
-// ============================================================================
-// NEO4J STATE
-// ============================================================================
-
-pub struct Neo4jState {
-    graph: Arc<Mutex<Option<Arc<Graph>>>>,
-}
-
-impl Neo4jState {
-    pub fn new() -> Self {
-        Neo4jState {
-            graph: Arc::new(Mutex::new(None)),
-        }
-    }
-
-    pub async fn connect(&self, uri: &str, user: &str, password: &str) -> Result<(), String> {
-        let graph = Graph::new(uri, user, password)
-            .await
-            .map_err(|e| format!("Failed to connect to Neo4j: {}", e))?;
-        
-        let mut g = self.graph.lock().unwrap();
-        *g = Some(Arc::new(graph));
-        Ok(())
-    }
-
-    pub fn get_graph(&self) -> Result<Arc<Graph>, String> {
-        let g = self.graph.lock().unwrap();
-        g.as_ref()
-            .ok_or_else(|| "Not connected to Neo4j".to_string())
-            .cloned()
-    }
-
-    pub fn is_connected(&self) -> bool {
-        let g = self.graph.lock().unwrap();
-        g.is_some()
-    }
-}
-
-// ============================================================================
-// CODE GRAPH STRUCTURES
-// ============================================================================
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct CodeGraphNode {
-    pub id: String,
-    #[serde(rename = "type")]
-    pub node_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub name: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub path: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub language: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub lines: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub start_line: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub end_line: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub line: Option<usize>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub source: Option<String>,
-    #[serde(flatten)]
-    pub extra: HashMap<String, serde_json::Value>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct CodeGraphEdge {
-    pub from: String,
-    pub to: String,
-    #[serde(rename = "type")]
-    pub edge_type: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub unresolved: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub edge_type_secondary: Option<String>,
-    #[serde(flatten)]
-    pub extra: HashMap<String, serde_json::Value>,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct CodeGraphFile {
-    pub id: usize,
-    #[serde(rename = "type")]
-    pub file_type: String,
-    pub path: String,
-    pub language: String,
-    pub lines: usize,
-}
-
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct CodeGraph {
-    pub nodes: Vec<CodeGraphNode>,
-    pub edges: Vec<CodeGraphEdge>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub files: Option<Vec<CodeGraphFile>>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GraphContext {
-    pub summary: String,
-    pub structure_overview: String,
-    pub cypher_schema: String,
-    pub sample_queries: Vec<String>,
-    pub nodes_by_type: HashMap<String, usize>,
-    pub edges_by_type: HashMap<String, usize>,
-    pub graph_statistics: GraphStatistics,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct GraphStatistics {
-    pub total_nodes: usize,
-    pub total_edges: usize,
-    pub max_depth: usize,
-    pub connected_components: usize,
-    pub avg_connections_per_node: f64,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct CypherQueryResult {
-    pub success: bool,
-    pub data: Vec<serde_json::Value>,
-    pub error: Option<String>,
-    pub summary: String,
-}
-
-// ============================================================================
-// NEO4J OPERATIONS - FIXED
-// ============================================================================
-
-impl CodeGraph {
-    pub async fn store_in_neo4j(&self, graph: &Graph) -> Result<String, String> {
-        // Clear existing data
-        graph
-            .run(query("MATCH (n) DETACH DELETE n"))
-            .await
-            .map_err(|e| format!("Failed to clear database: {}", e))?;
-
-        // Create nodes
-        for node in &self.nodes {
-            let node_type = node.node_type.to_uppercase();
-            let id = node.id.clone();
-            let name = node.name.clone().unwrap_or_else(|| "unknown".to_string());
-            let path = node.path.clone().unwrap_or_default();
-
-            // Build dynamic query based on available fields
-            let mut properties = vec!["id: $id", "name: $name", "path: $path"];
-            
-            if node.language.is_some() {
-                properties.push("language: $language");
-            }
-            if node.lines.is_some() {
-                properties.push("lines: $lines");
-            }
-            if node.start_line.is_some() {
-                properties.push("startLine: $startLine");
-            }
-            if node.end_line.is_some() {
-                properties.push("endLine: $endLine");
-            }
-            if node.line.is_some() {
-                properties.push("line: $line");
-            }
-            if node.source.is_some() {
-                properties.push("source: $source");
-            }
-
-            let query_str = format!(
-                "CREATE (n:{} {{{}}})",
-                node_type,
-                properties.join(", ")
-            );
-
-            let mut cypher = query(&query_str)
-                .param("id", id)
-                .param("name", name)
-                .param("path", path);
-
-            if let Some(lang) = &node.language {
-                cypher = cypher.param("language", lang.clone());
-            }
-            if let Some(lines) = node.lines {
-                cypher = cypher.param("lines", lines as i64);
-            }
-            if let Some(start_line) = node.start_line {
-                cypher = cypher.param("startLine", start_line as i64);
-            }
-            if let Some(end_line) = node.end_line {
-                cypher = cypher.param("endLine", end_line as i64);
-            }
-            if let Some(line) = node.line {
-                cypher = cypher.param("line", line as i64);
-            }
-            if let Some(source) = &node.source {
-                cypher = cypher.param("source", source.clone());
-            }
-
-            graph
-                .run(cypher)
-                .await
-                .map_err(|e| format!("Failed to create node {}: {}", node.id, e))?;
-        }
-
-        // Create relationships
-        for edge in &self.edges {
-            let cypher_query = format!(
-                "MATCH (a {{id: $from}}), (b {{id: $to}}) CREATE (a)-[:{}]->(b)",
-                edge.edge_type
-            );
-            
-            let cypher = query(&cypher_query)
-                .param("from", edge.from.clone())
-                .param("to", edge.to.clone());
-
-            graph
-                .run(cypher)
-                .await
-                .map_err(|e| format!("Failed to create relationship {} -> {}: {}", edge.from, edge.to, e))?;
-        }
-
-        Ok(format!(
-            "Successfully stored {} nodes and {} edges in Neo4j",
-            self.nodes.len(),
-            self.edges.len()
-        ))
-    }
-
-    pub fn generate_context(&self) -> GraphContext {
-        let mut nodes_by_type: HashMap<String, usize> = HashMap::new();
-        let mut edges_by_type: HashMap<String, usize> = HashMap::new();
-
-        for node in &self.nodes {
-            *nodes_by_type.entry(node.node_type.clone()).or_insert(0) += 1;
-        }
-
-        for edge in &self.edges {
-            *edges_by_type.entry(edge.edge_type.clone()).or_insert(0) += 1;
-        }
-
-        let total_nodes = self.nodes.len();
-        let total_edges = self.edges.len();
-        let avg_connections = if total_nodes > 0 {
-            (total_edges as f64 * 2.0) / total_nodes as f64
-        } else {
-            0.0
-        };
-
-        let summary = self.generate_summary(&nodes_by_type, &edges_by_type);
-        let cypher_schema = self.generate_cypher_schema(&nodes_by_type, &edges_by_type);
-        let sample_queries = self.generate_sample_queries();
-        
-        // Generate a structural overview (list of files and key components)
-        let structure_overview = self.generate_structure_overview();
-
-        GraphContext {
-            summary,
-            structure_overview, // Added field
-            cypher_schema,
-            sample_queries,
-            nodes_by_type,
-            edges_by_type,
-            graph_statistics: GraphStatistics {
-                total_nodes,
-                total_edges,
-                max_depth: 10,
-                connected_components: 1,
-                avg_connections_per_node: avg_connections,
-            },
-        }
-    }
-
-    fn generate_structure_overview(&self) -> String {
-        let mut overview = String::new();
-        
-        // Collect files
-        let mut file_paths: Vec<&String> = self.nodes.iter()
-            .filter(|n| n.node_type == "FILE" || n.node_type == "file")
-            .filter_map(|n| n.path.as_ref())
-            .collect();
-        file_paths.sort();
-
-        overview.push_str("### File Structure\n");
-        if file_paths.is_empty() {
-             overview.push_str("- No files detected in graph.\n");
-        } else {
-            for path in file_paths.iter().take(50) { // Limit to 50 files to avoid context overflow
-                overview.push_str(&format!("- {}\n", path));
-            }
-            if file_paths.len() > 50 {
-                overview.push_str(&format!("... and {} more files.\n", file_paths.len() - 50));
-            }
-        }
-
-        overview
-    }
-
-    fn generate_summary(&self, nodes_by_type: &HashMap<String, usize>, edges_by_type: &HashMap<String, usize>) -> String {
-        let mut summary = String::from("# Code Graph Summary\n\n");
-        
-        summary.push_str("## Nodes\n");
-        for (node_type, count) in nodes_by_type {
-            summary.push_str(&format!("- {}: {} nodes\n", node_type, count));
-        }
-        
-        summary.push_str("\n## Relationships\n");
-        for (edge_type, count) in edges_by_type {
-            summary.push_str(&format!("- {}: {} edges\n", edge_type, count));
-        }
-        
-        summary.push_str(&format!("\n## Total Statistics\n"));
-        summary.push_str(&format!("- Total Nodes: {}\n", self.nodes.len()));
-        summary.push_str(&format!("- Total Edges: {}\n", self.edges.len()));
-        
-        summary
-    }
-
-    fn generate_cypher_schema(&self, nodes_by_type: &HashMap<String, usize>, edges_by_type: &HashMap<String, usize>) -> String {
-        let mut schema = String::from("# Neo4j Graph Schema\n\n");
-        
-        schema.push_str("## Node Labels\n");
-        for (node_type, _) in nodes_by_type {
-            schema.push_str(&format!("- :{} (id: String, name: String, path: String, language: String, lines: Integer)\n", node_type.to_uppercase()));
-        }
-        
-        schema.push_str("\n## Relationship Types\n");
-        for (edge_type, _) in edges_by_type {
-            schema.push_str(&format!("- :{}\n", edge_type));
-        }
-        
-        schema
-    }
-
-    fn generate_sample_queries(&self) -> Vec<String> {
-        vec![
-            "// WHOLE CODEBASE ANALYSIS (Architecture & Dependencies)\nMATCH (n) OPTIONAL MATCH (n)-[r]->(m) RETURN n, r, m LIMIT 1000".to_string(),
-            "// Find all files\nMATCH (f:FILE) RETURN f.path, f.language LIMIT 50".to_string(),
-            "// Find all functions in a specific file\nMATCH (file:FILE)-[:CONTAINS]->(func:FUNCTION)\nWHERE file.path CONTAINS 'example'\nRETURN func.name, func.lines".to_string(),
-            "// Find function call chains\nMATCH path = (f1:FUNCTION)-[:CALLS*1..3]->(f2:FUNCTION)\nRETURN path LIMIT 10".to_string(),
-            "// Find all imports for a file\nMATCH (file:FILE)-[:IMPORTS_FROM]->(imported:FILE)\nRETURN file.path, imported.path LIMIT 20".to_string(),
-            "// Find classes that extend other classes\nMATCH (child:CLASS)-[:EXTENDS]->(parent:CLASS)\nRETURN child.name, parent.name".to_string(),
-            "// Find most connected nodes (Hubs)\nMATCH (n)-[r]-()\nRETURN n.name, n.id, labels(n)[0] as label, count(r) AS connections\nORDER BY connections DESC\nLIMIT 10".to_string(),
-            "// Find circular dependencies\nMATCH path = (a:FILE)-[:IMPORTS_FROM*2..5]->(a)\nRETURN path LIMIT 5".to_string(),
-        ]
-    }
-
-    pub fn to_graph_query_context(&self) -> String {
-        let context = self.generate_context();
-        
-        format!(
-            r#"# Codebase Analysis Report
-
-## Graph Overview
-{}
-
-## Structural Overview
-{}
-
-## Available Node Types
-{}
-
-## Available Relationship Types
-{}
-
-## Analysis Patterns
-You can use these internal patterns to query the codebase knowledge:
-
-{}
-
-## Statistics
-- Total Nodes: {}
-- Total Edges: {}
-- Average Connections per Node: {:.2}
-
-## Query Guidelines
-1. Use MATCH clauses to find patterns
-2. Use WHERE to filter results
-3. Use RETURN to specify what to return
-4. Use LIMIT to control result count
-5. Available node types: {}
-6. Available relationship types: {}
-
-## Important Notes
-- This knowledge graph allows for real-time codebase analysis
-- Use this data to answer user questions with authority
-- Do NOT expose raw query details to the user
-- Use CONTAINS for partial string matching in paths
-"#,
-            context.summary,
-            context.structure_overview, // Inject Structural Overview
-            context.nodes_by_type
-                .iter()
-                .map(|(k, v)| format!("- :{} ({} nodes)", k.to_uppercase(), v))
-                .collect::<Vec<_>>()
-                .join("\n"),
-            context.edges_by_type
-                .iter()
-                .map(|(k, v)| format!("- :{} ({} relationships)", k, v))
-                .collect::<Vec<_>>()
-                .join("\n"),
-            context.sample_queries
-                .iter()
-                .map(|q| format!("```cypher\n{}\n```", q))
-                .collect::<Vec<_>>()
-                .join("\n\n"),
-            context.graph_statistics.total_nodes,
-            context.graph_statistics.total_edges,
-            context.graph_statistics.avg_connections_per_node,
-            context.nodes_by_type.keys().map(|k| k.to_uppercase()).collect::<Vec<_>>().join(", "),
-            context.edges_by_type.keys().map(|k| k.as_str()).collect::<Vec<_>>().join(", ")
-        )
-    }
-}
-
-// ============================================================================
-// PARSER STRUCTURES
-// ============================================================================
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ParsedFile {
-    pub path: String,
-    pub language: String,
-    pub success: bool,
-    pub error: Option<String>,
-    pub ast: Option<ASTNode>,
-    pub metadata: ParseMetadata,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ASTNode {
-    pub node_type: String,
-    pub text: Option<String>,
-    pub start_line: usize,
-    pub start_col: usize,
-    pub end_line: usize,
-    pub end_col: usize,
-    pub start_byte: usize,
-    pub end_byte: usize,
-    pub is_named: bool,
-    pub children: Vec<ASTNode>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ParseMetadata {
-    pub lines: usize,
-    pub bytes: usize,
-    pub node_count: usize,
-    pub tree_depth: usize,
-    pub has_syntax_errors: bool,
-}
-
-impl ParseMetadata {
-    fn empty() -> Self {
-        ParseMetadata {
-            lines: 0,
-            bytes: 0,
-            node_count: 0,
-            tree_depth: 0,
-            has_syntax_errors: false,
-        }
-    }
-}
-
-// ============================================================================
-// PARSER STATE
-// ============================================================================
-
-pub struct ParserState {
-    parsers: Mutex<HashMap<String, Parser>>,
-    extension_map: HashMap<String, String>,
-}
-
-impl ParserState {
-    pub fn new() -> Self {
-        
-        let mut state = ParserState {
-            parsers: Mutex::new(HashMap::new()),
-            extension_map: HashMap::new(),
-        };
-        
-        state.setup_extensions();
-        state.initialize_parsers();
-        
-        state
-    }
-
-    fn setup_extensions(&mut self) {
-        let mappings = vec![
-            ("js", "javascript"),
-            ("jsx", "javascript"),
-            ("mjs", "javascript"),
-            ("cjs", "javascript"),
-            ("ts", "typescript"),
-            ("tsx", "tsx"),
-            ("py", "python"),
-            ("pyw", "python"),
-            ("rs", "rust"),
-            ("java", "java"),
-            ("go", "go"),
-            ("c", "c"),
-            ("h", "c"),
-            ("cpp", "cpp"),
-            ("cc", "cpp"),
-            ("cxx", "cpp"),
-            ("hpp", "cpp"),
-            ("hxx", "cpp"),
-        ];
-
-        for (ext, lang) in mappings {
-            self.extension_map.insert(ext.to_string(), lang.to_string());
-        }
-    }
-
-    fn initialize_parsers(&mut self) {
-        let mut parsers = self.parsers.lock().unwrap();
-        
-        Self::add_parser(&mut parsers, "javascript", tree_sitter_javascript::language());
-        Self::add_parser(&mut parsers, "typescript", tree_sitter_typescript::language_typescript());
-        Self::add_parser(&mut parsers, "tsx", tree_sitter_typescript::language_tsx());
-        Self::add_parser(&mut parsers, "python", tree_sitter_python::language());
-        Self::add_parser(&mut parsers, "rust", tree_sitter_rust::language());
-        Self::add_parser(&mut parsers, "java", tree_sitter_java::language());
-        Self::add_parser(&mut parsers, "go", tree_sitter_go::language());
-        Self::add_parser(&mut parsers, "c", tree_sitter_c::language());
-        Self::add_parser(&mut parsers, "cpp", tree_sitter_cpp::language());
-        
-        eprintln!("  Loaded {} parsers", parsers.len());
-    }
-
-    fn add_parser(parsers: &mut HashMap<String, Parser>, name: &str, language: Language) {
-        let mut parser = Parser::new();
-        
-        match parser.set_language(language) {
-            Ok(_) => {
-                parsers.insert(name.to_string(), parser);
-                eprintln!("  ✓ {}", name);
-            }
-            Err(e) => {
-                eprintln!("  ✗ {} - Failed to set language: {:?}", name, e);
-            }
-        }
-    }
-
-    fn detect_language(&self, path: &str) -> Option<String> {
-        Path::new(path)
-            .extension()
-            .and_then(|e| e.to_str())
-            .and_then(|ext| self.extension_map.get(ext))
-            .cloned()
-    }
-
-    pub fn parse_file(&self, path: &str, content: &str) -> ParsedFile {
-        let language = match self.detect_language(path) {
-            Some(lang) => lang,
-            None => {
-                return ParsedFile {
-                    path: path.to_string(),
-                    language: "unknown".to_string(),
-                    success: false,
-                    error: Some("Unsupported file extension".to_string()),
-                    ast: None,
-                    metadata: ParseMetadata::empty(),
-                };
-            }
-        };
-
-        let mut parsers = self.parsers.lock().unwrap();
-        let parser = match parsers.get_mut(&language) {
-            Some(p) => p,
-            None => {
-                return ParsedFile {
-                    path: path.to_string(),
-                    language: language.clone(),
-                    success: false,
-                    error: Some(format!("Parser not available for {}", language)),
-                    ast: None,
-                    metadata: ParseMetadata::empty(),
-                };
-            }
-        };
-
-        match parser.parse(content, None) {
-            Some(tree) => {
-                let root = tree.root_node();
-                let ast = Self::node_to_ast(&root, content, 0, 10);
-                
-                ParsedFile {
-                    path: path.to_string(),
-                    language,
-                    success: true,
-                    error: None,
-                    ast: Some(ast),
-                    metadata: ParseMetadata {
-                        lines: content.lines().count(),
-                        bytes: content.len(),
-                        node_count: Self::count_nodes(&root),
-                        tree_depth: Self::calculate_depth(&root, 0),
-                        has_syntax_errors: root.has_error(),
-                    },
-                }
-            }
-            None => {
-                ParsedFile {
-                    path: path.to_string(),
-                    language,
-                    success: false,
-                    error: Some("Parse failed".to_string()),
-                    ast: None,
-                    metadata: ParseMetadata::empty(),
-                }
-            }
-        }
-    }
-
-    fn node_to_ast(node: &Node, source: &str, depth: usize, max_depth: usize) -> ASTNode {
-        let start = node.start_position();
-        let end = node.end_position();
-        
-        let text = if node.child_count() == 0 && (node.end_byte() - node.start_byte()) < 100 {
-            node.utf8_text(source.as_bytes()).ok().map(|s| s.to_string())
-        } else {
-            None
-        };
-
-        let mut children = Vec::new();
-        if depth < max_depth {
-            for i in 0..node.child_count() {
-                if let Some(child) = node.child(i) {
-                    children.push(Self::node_to_ast(&child, source, depth + 1, max_depth));
-                }
-            }
-        }
-
-        ASTNode {
-            node_type: node.kind().to_string(),
-            text,
-            start_line: start.row,
-            start_col: start.column,
-            end_line: end.row,
-            end_col: end.column,
-            start_byte: node.start_byte(),
-            end_byte: node.end_byte(),
-            is_named: node.is_named(),
-            children,
-        }
-    }
-
-    fn count_nodes(node: &Node) -> usize {
-        let mut count = 1;
-        for i in 0..node.child_count() {
-            if let Some(child) = node.child(i) {
-                count += Self::count_nodes(&child);
-            }
-        }
-        count
-    }
-
-    fn calculate_depth(node: &Node, current: usize) -> usize {
-        let mut max = current;
-        for i in 0..node.child_count() {
-            if let Some(child) = node.child(i) {
-                max = max.max(Self::calculate_depth(&child, current + 1));
-            }
-        }
-        max
-    }
-}
+use graph_builder::*;
+use history::*;
+use impact::*;
+use jobs::*;
+use linter::*;
+use literals::*;
+use llm::*;
+use lsp::*;
+use mcp::*;
+use owners::*;
+use plugins::*;
+use process::*;
+use prompts::*;
+use recent::*;
+use refactor::*;
+use references::*;
+use retrieval::*;
+use review::*;
+use sandbox::*;
+use search::*;
+use snippets::*;
+use streaming::*;
+use structured::*;
+use summarize::*;
+use symbols::*;
+use tasks::*;
+use terminal::*;
+use test_runner::*;
 
 // ============================================================================
 // NEO4J TAURI COMMANDS
@@ -700,23 +130,25 @@ async fn connect_neo4j(
 
 #[tauri::command]
 async fn disconnect_neo4j(state: State<'_, Neo4jState>) -> Result<String, String> {
-    let mut g = state.graph.lock().unwrap();
-    *g = None;
+    state.disconnect().await;
     Ok("Disconnected from Neo4j".to_string())
 }
 
 #[tauri::command]
 async fn check_neo4j_connection(state: State<'_, Neo4jState>) -> Result<bool, String> {
-    Ok(state.is_connected())
+    Ok(state.is_connected().await)
 }
 
 #[tauri::command]
 async fn store_graph_in_neo4j(
+    app: AppHandle,
     graph: CodeGraph,
     state: State<'_, Neo4jState>,
 ) -> Result<String, String> {
-    let neo4j = state.get_graph()?;
-    graph.store_in_neo4j(&neo4j).await
+    let neo4j = state.get_graph().await?;
+    let message = graph.store_in_neo4j(&neo4j).await?;
+    emit_graph_updated(&app, "store", None, graph.nodes.len());
+    Ok(message)
 }
 
 #[tauri::command]
@@ -724,46 +156,13 @@ async fn execute_cypher_query(
     cypher: String,
     state: State<'_, Neo4jState>,
 ) -> Result<CypherQueryResult, String> {
-    let graph = state.get_graph()?;
-    
-    let mut result = graph
-        .execute(query(&cypher))
-        .await
-        .map_err(|e| format!("Query execution failed: {}", e))?;
-
-    let mut data: Vec<serde_json::Value> = Vec::new();
-    let mut row_count = 0;
-
-    while let Ok(Some(row)) = result.next().await {
-        row_count += 1;
-        let mut row_data = serde_json::Map::new();
-
-        if let Ok(row_map) = row.to::<HashMap<String, serde_json::Value>>() {
-            for (key, value) in row_map {
-                row_data.insert(key, value);
-            }
-        }
-
-        data.push(serde_json::Value::Object(row_data));
-
-        if row_count >= 100 {
-            break;
-        }
-    }
-
-    let summary = format!("Query returned {} rows", data.len());
-
-    Ok(CypherQueryResult {
-        success: true,
-        data,
-        error: None,
-        summary,
-    })
+    let graph = state.get_graph().await?;
+    run_cypher(&graph, &cypher).await
 }
 
 #[tauri::command]
 async fn get_graph_stats(state: State<'_, Neo4jState>) -> Result<serde_json::Value, String> {
-    let graph = state.get_graph()?;
+    let graph = state.get_graph().await?;
 
     let node_count_query = "MATCH (n) RETURN count(n) as count";
     let mut result = graph
@@ -811,80 +210,63 @@ fn graph_to_query_context(graph: CodeGraph) -> Result<String, String> {
 // ============================================================================
 
 #[tauri::command]
-async fn parse_files(
-    files: Vec<(String, String)>,
-    state: State<'_, ParserState>,
-) -> Result<Vec<ParsedFile>, String> {
-    let results: Vec<ParsedFile> = files
-        .iter()
-        .map(|(path, content)| state.parse_file(path, content))
-        .collect();
-    
-    Ok(results)
+async fn parse_files(app: AppHandle, files: Vec<(String, String)>) -> Result<Vec<ParsedFile>, String> {
+    task::spawn_blocking(move || {
+        let state = app.state::<ParserState>();
+        files
+            .iter()
+            .map(|(path, content)| state.parse_file(path, content))
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Parse task failed: {}", e))
 }
 
 #[tauri::command]
-async fn parse_single_file(
-    path: String,
-    content: String,
-    state: State<'_, ParserState>,
-) -> Result<ParsedFile, String> {
-    Ok(state.parse_file(&path, &content))
+async fn parse_single_file(app: AppHandle, path: String, content: String) -> Result<ParsedFile, String> {
+    task::spawn_blocking(move || app.state::<ParserState>().parse_file(&path, &content))
+        .await
+        .map_err(|e| format!("Parse task failed: {}", e))
 }
 
 #[tauri::command]
 async fn read_and_parse_files(
+    app: AppHandle,
     paths: Vec<String>,
-    state: State<'_, ParserState>,
+    workspace: State<'_, WorkspaceState>,
 ) -> Result<Vec<ParsedFile>, String> {
-    let mut results = Vec::new();
-    
-    for path in paths {
-        match std_fs::read_to_string(&path) {
-            Ok(content) => {
-                let parsed = state.parse_file(&path, &content);
-                results.push(parsed);
-            }
-            Err(e) => {
-                results.push(ParsedFile {
-                    path: path.clone(),
-                    language: "unknown".to_string(),
-                    success: false,
-                    error: Some(format!("Failed to read file: {}", e)),
-                    ast: None,
-                    metadata: ParseMetadata::empty(),
-                });
-            }
-        }
-    }
-    
-    Ok(results)
+    workspace.check_all(&paths)?;
+    task::spawn_blocking(move || {
+        let state = app.state::<ParserState>();
+        paths.iter().map(|path| state.read_and_parse(path)).collect()
+    })
+    .await
+    .map_err(|e| format!("Parse task failed: {}", e))
 }
 
 #[tauri::command]
 fn get_supported_languages(
     state: State<'_, ParserState>
 ) -> Result<HashMap<String, Vec<String>>, String> {
-    let mut result = HashMap::new();
-    
-    for (ext, lang) in &state.extension_map {
-        result.entry(lang.clone())
-            .or_insert_with(Vec::new)
-            .push(ext.clone());
-    }
-    
-    Ok(result)
+    Ok(state.supported_languages())
 }
 
 #[tauri::command]
-async fn read_file_content(paths: Vec<String>) -> Vec<(String, String)> {
+async fn read_file_content(
+    paths: Vec<String>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<(String, String)>, String> {
+    workspace.check_all(&paths)?;
     let mut handles = Vec::new();
 
     for path in paths {
         let path_clone = path.clone();
         handles.push(task::spawn(async move {
-            match fs::read_to_string(&path_clone).await {
-                Ok(content) => Some((path_clone, content)),
+            match fs::read(&path_clone).await {
+                Ok(bytes) => {
+                    let content = decode_bytes(&bytes, detect_encoding(&bytes));
+                    Some((path_clone, content))
+                }
                 Err(_) => None,
             }
         }));
@@ -898,7 +280,7 @@ async fn read_file_content(paths: Vec<String>) -> Vec<(String, String)> {
         }
     }
 
-    results
+    Ok(results)
 }
 
 #[tauri::command]
@@ -911,60 +293,57 @@ pub struct DirEntryInfo {
     pub name: String,
     pub path: String,
     pub is_dir: bool,
+    pub is_symlink: bool,
+    pub ignored: bool,
     pub children: Option<Vec<DirEntryInfo>>,
 }
 
+/// Reads a file in any encoding as UTF-8, remembering the original encoding
+/// for `write_file_content`.
 #[tauri::command]
-fn read_file_while_content(path: &str) -> Result<String, String> {
-    std_fs::read_to_string(path).map_err(|e| e.to_string())
+fn read_file_while_content(
+    path: &str,
+    state: State<'_, EncodingState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<String, String> {
+    workspace.check(path)?;
+    let (content, encoding) = read_text(Path::new(path))?;
+    state.remember(path, encoding);
+    Ok(content)
 }
 
-fn read_dir_recursive(dir: &Path) -> Result<Vec<DirEntryInfo>, String> {
-    let mut entries = Vec::new();
-    let ignored_dirs = ["node_modules", "target", ".git", "dist", "build", ".idea", ".vscode", "out"];
-
-    for entry in std_fs::read_dir(dir)
-        .map_err(|e| format!("Failed to read dir {}: {}", dir.display(), e))?
-    {
-        let entry = entry.map_err(|e| format!("Failed to read entry: {}", e))?;
-        let path = entry.path();
-        let file_name = entry.file_name().to_string_lossy().to_string();
-
-        if ignored_dirs.contains(&file_name.as_str()) || file_name.starts_with('.') {
-            continue;
-        }
-
-        let metadata = entry.metadata().map_err(|e| {
-            format!("Failed to read metadata for {}: {}", path.display(), e)
-        })?;
-
-        let children = if metadata.is_dir() {
-            match read_dir_recursive(&path) {
-                Ok(child_entries) => Some(child_entries),
-                Err(_) => Some(Vec::new()),
-            }
-        } else {
-            None
-        };
-
-        entries.push(DirEntryInfo {
-            name: file_name,
-            path: path.to_string_lossy().to_string(),
-            is_dir: metadata.is_dir(),
-            children,
-        });
-    }
-
-    Ok(entries)
+pub(crate) fn read_dir_recursive(dir: &Path) -> Result<Vec<DirEntryInfo>, String> {
+    read_tree(dir, &DirectoryOptions::default())
 }
 
+/// Writes `content` in `encoding` (a label like "Shift_JIS"), or the encoding
+/// the file was read in.
 #[tauri::command]
-fn write_file_content(path: &str, content: &str) -> Result<(), String> {
-    std_fs::write(path, content).map_err(|e| e.to_string())
+fn write_file_content(
+    path: &str,
+    content: &str,
+    encoding: Option<String>,
+    state: State<'_, EncodingState>,
+    history: State<'_, HistoryState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<(), String> {
+    workspace.check(path)?;
+    let file_encoding = resolve_write_encoding(path, encoding.as_deref(), &state)?;
+    let bytes = encode_text(content, file_encoding)?;
+    // History is a safety net; failing to record it mustn't block the save
+    let _ = history.snapshot(Path::new(path), "write");
+    std_fs::write(path, bytes).map_err(|e| e.to_string())?;
+    state.remember(path, file_encoding);
+    Ok(())
 }
 
 #[tauri::command]
-fn read_directory(path: &str) -> Result<Vec<DirEntryInfo>, String> {
+fn read_directory(
+    path: &str,
+    options: Option<DirectoryOptions>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<DirEntryInfo>, String> {
+    workspace.check(path)?;
     let dir = Path::new(path);
 
     if !dir.exists() {
@@ -975,10 +354,12 @@ fn read_directory(path: &str) -> Result<Vec<DirEntryInfo>, String> {
         return Err(format!("Path is not a directory: {}", path));
     }
 
-    read_dir_recursive(dir)
+    read_tree(dir, &options.unwrap_or_default())
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+pub(crate) const OLLAMA_URL: &str = "http://localhost:11434";
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 pub struct ChatMessage {
     pub role: String,
     pub content: String,
@@ -993,8 +374,57 @@ struct OllamaChatRequest {
 
 #[derive(Debug, Deserialize)]
 struct OllamaChatResponse {
+    #[serde(default)]
     message: ChatMessage,
+    #[serde(default)]
     done: bool,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    total_duration: Option<u64>,
+    #[serde(default)]
+    load_duration: Option<u64>,
+    #[serde(default)]
+    prompt_eval_count: Option<u64>,
+    #[serde(default)]
+    prompt_eval_duration: Option<u64>,
+    #[serde(default)]
+    eval_count: Option<u64>,
+    #[serde(default)]
+    eval_duration: Option<u64>,
+}
+
+/// Generation statistics Ollama reports on the final chunk. Durations are
+/// converted from nanoseconds to milliseconds.
+#[derive(Debug, Serialize, Clone)]
+struct ChatStats {
+    prompt_tokens: Option<u64>,
+    eval_count: Option<u64>,
+    tokens_per_second: Option<f64>,
+    total_duration_ms: Option<f64>,
+    load_duration_ms: Option<f64>,
+    prompt_eval_duration_ms: Option<f64>,
+    eval_duration_ms: Option<f64>,
+}
+
+impl OllamaChatResponse {
+    fn stats(&self) -> ChatStats {
+        let to_ms = |ns: Option<u64>| ns.map(|ns| ns as f64 / 1_000_000.0);
+        let tokens_per_second = match (self.eval_count, self.eval_duration) {
+            (Some(count), Some(duration)) if duration > 0 => Some(count as f64 / (duration as f64 / 1_000_000_000.0)),
+            _ => None,
+        };
+
+        ChatStats {
+            prompt_tokens: self.prompt_eval_count,
+            eval_count: self.eval_count,
+            tokens_per_second,
+            total_duration_ms: to_ms(self.total_duration),
+            load_duration_ms: to_ms(self.load_duration),
+            prompt_eval_duration_ms: to_ms(self.prompt_eval_duration),
+            eval_duration_ms: to_ms(self.eval_duration),
+        }
+    }
 }
 
 #[derive(Debug, Deserialize)]
@@ -1011,14 +441,38 @@ struct OllamaModel {
 
 #[derive(Debug, Serialize, Clone)]
 struct ChatStreamEvent {
+    #[serde(rename = "type")]
+    event_type: String, // "token", "done", "error"
     content: String,
     done: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stats: Option<ChatStats>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl ChatStreamEvent {
+    fn error(message: String) -> Self {
+        ChatStreamEvent {
+            event_type: "error".to_string(),
+            content: String::new(),
+            done: true,
+            stats: None,
+            error: Some(message),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ChatCompletion {
+    content: String,
+    stats: Option<ChatStats>,
 }
 
 #[tauri::command]
 async fn check_ollama_connection() -> Result<bool, String> {
     let client = reqwest::Client::new();
-    match client.get("http://localhost:11434/api/tags").send().await {
+    match client.get(format!("{}/api/tags", OLLAMA_URL)).send().await {
         Ok(response) => Ok(response.status().is_success()),
         Err(_) => Ok(false),
     }
@@ -1028,7 +482,7 @@ async fn check_ollama_connection() -> Result<bool, String> {
 async fn get_ollama_models() -> Result<Vec<String>, String> {
     let client = reqwest::Client::new();
     let response = client
-        .get("http://localhost:11434/api/tags")
+        .get(format!("{}/api/tags", OLLAMA_URL))
         .send()
         .await
         .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
@@ -1041,23 +495,184 @@ async fn get_ollama_models() -> Result<Vec<String>, String> {
     Ok(models.models.into_iter().map(|m| m.name).collect())
 }
 
+/// With a `conversation_id`, `messages` is only the new turn: the stored
+/// history is sent before it and the turn and reply are saved.
 #[tauri::command]
 async fn chat_with_ollama(
     window: Window,
     model: String,
     messages: Vec<ChatMessage>,
-) -> Result<String, String> {
-    let client = reqwest::Client::new();
+    conversation_id: Option<i64>,
+    llm: State<'_, LlmState>,
+    conversations: State<'_, ConversationState>,
+) -> Result<ChatCompletion, String> {
+    let messages = conversations.continue_with(conversation_id, messages)?;
+    let prompt = LoggedPrompt::from_messages(&messages);
+    let started = std::time::Instant::now();
+
+    let result = stream_ollama_chat(&window, &llm, model.clone(), messages).await;
+
+    let stats = result.as_ref().ok().and_then(|c| c.stats.as_ref());
+    llm.log_request(LlmRequestRecord {
+        provider: "ollama",
+        model: &model,
+        endpoint: "/api/chat",
+        prompt,
+        prompt_tokens: stats.and_then(|s| s.prompt_tokens),
+        completion_tokens: stats.and_then(|s| s.eval_count),
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: result.as_ref().err().cloned(),
+    });
 
+    let completion = result?;
+    conversations.record_reply(conversation_id, &completion.content)?;
+    Ok(completion)
+}
+
+/// Splits the complete NDJSON lines off the front of `pending`. Lines are
+/// only decoded once whole, so characters split across chunks survive.
+fn take_ndjson_lines(pending: &mut Vec<u8>) -> Vec<String> {
+    let Some(end) = pending.iter().rposition(|b| *b == b'\n') else {
+        return Vec::new();
+    };
+    let complete: Vec<u8> = pending.drain(..=end).collect();
+    String::from_utf8_lossy(&complete)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+async fn stream_ollama_chat(
+    window: &Window,
+    llm: &LlmState,
+    model: String,
+    messages: Vec<ChatMessage>,
+) -> Result<ChatCompletion, String> {
     let request = OllamaChatRequest {
         model,
         messages,
         stream: true,
     };
 
+    // Only establishing the stream is retried; a stream that fails midway
+    // has already delivered partial output to the UI
+    let response = match llm.post("/api/chat", &request).await {
+        Ok(response) => response,
+        Err(message) => {
+            let _ = window.emit("chat-stream", ChatStreamEvent::error(message.clone()));
+            return Err(message);
+        }
+    };
+
+    let mut stream = response.bytes_stream();
+    let mut full_response = String::new();
+    let mut stats = None;
+    // Chunks don't align with NDJSON lines, so only parse complete lines
+    let mut pending = Vec::new();
+
+    while let Some(chunk) = stream.next().await {
+        let bytes = match chunk {
+            Ok(bytes) => bytes,
+            Err(e) => {
+                let message = format!("Stream error: {}", e);
+                let _ = window.emit("chat-stream", ChatStreamEvent::error(message.clone()));
+                return Err(message);
+            }
+        };
+        pending.extend_from_slice(&bytes);
+
+        for line in take_ndjson_lines(&mut pending) {
+            match serde_json::from_str::<OllamaChatResponse>(&line) {
+                Ok(response) => {
+                    if let Some(error) = &response.error {
+                        let message = format!("Ollama error: {}", error);
+                        let _ = window.emit("chat-stream", ChatStreamEvent::error(message.clone()));
+                        return Err(message);
+                    }
+
+                    full_response.push_str(&response.message.content);
+                    let event_stats = if response.done { Some(response.stats()) } else { None };
+                    if event_stats.is_some() {
+                        stats = event_stats.clone();
+                    }
+
+                    let event = ChatStreamEvent {
+                        event_type: if response.done { "done" } else { "token" }.to_string(),
+                        content: response.message.content,
+                        done: response.done,
+                        stats: event_stats,
+                        error: None,
+                    };
+                    let _ = window.emit("chat-stream", event);
+                }
+                Err(e) => {
+                    eprintln!("Failed to parse Ollama response: {} - Line: {}", e, line);
+                }
+            }
+        }
+    }
+
+    Ok(ChatCompletion {
+        content: full_response,
+        stats,
+    })
+}
+
+#[tauri::command]
+async fn chat_with_ollama_sync(
+    model: String,
+    messages: Vec<ChatMessage>,
+    llm: State<'_, LlmState>,
+) -> Result<String, String> {
+    complete_chat(&llm, &model, messages).await
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaPullStatus {
+    status: String,
+    #[serde(default)]
+    digest: Option<String>,
+    #[serde(default)]
+    total: Option<u64>,
+    #[serde(default)]
+    completed: Option<u64>,
+    #[serde(default)]
+    error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct ModelPullProgress {
+    name: String,
+    status: String,
+    digest: Option<String>,
+    total: Option<u64>,
+    completed: Option<u64>,
+    percent: Option<f64>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct OllamaModelInfo {
+    #[serde(default)]
+    modelfile: String,
+    #[serde(default)]
+    parameters: String,
+    #[serde(default)]
+    template: String,
+    #[serde(default)]
+    details: serde_json::Value,
+    #[serde(default)]
+    model_info: serde_json::Value,
+}
+
+#[tauri::command]
+async fn pull_ollama_model(window: Window, name: String) -> Result<(), String> {
+    let client = reqwest::Client::new();
+
     let response = client
-        .post("http://localhost:11434/api/chat")
-        .json(&request)
+        .post(format!("{}/api/pull", OLLAMA_URL))
+        .json(&serde_json::json!({ "name": name, "stream": true }))
         .send()
         .await
         .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
@@ -1067,56 +682,71 @@ async fn chat_with_ollama(
     }
 
     let mut stream = response.bytes_stream();
-    let mut full_response = String::new();
+    // Progress lines can be split across chunks, so only parse complete lines
+    let mut pending = Vec::new();
 
     while let Some(chunk) = stream.next().await {
-        match chunk {
-            Ok(bytes) => {
-                let text = String::from_utf8_lossy(&bytes);
-                for line in text.lines() {
-                    if line.trim().is_empty() {
-                        continue;
-                    }
-                    match serde_json::from_str::<OllamaChatResponse>(line) {
-                        Ok(response) => {
-                            full_response.push_str(&response.message.content);
-                            let event = ChatStreamEvent {
-                                content: response.message.content,
-                                done: response.done,
-                            };
-                            let _ = window.emit("chat-stream", event);
-                        }
-                        Err(e) => {
-                            eprintln!("Failed to parse Ollama response: {} - Line: {}", e, line);
-                        }
-                    }
+        let bytes = chunk.map_err(|e| format!("Stream error: {}", e))?;
+        pending.extend_from_slice(&bytes);
+
+        for line in take_ndjson_lines(&mut pending) {
+            let status: OllamaPullStatus = match serde_json::from_str(&line) {
+                Ok(status) => status,
+                Err(e) => {
+                    eprintln!("Failed to parse pull progress: {} - Line: {}", e, line);
+                    continue;
                 }
+            };
+
+            if let Some(error) = status.error {
+                return Err(format!("Failed to pull {}: {}", name, error));
             }
-            Err(e) => {
-                return Err(format!("Stream error: {}", e));
-            }
+
+            let percent = match (status.completed, status.total) {
+                (Some(completed), Some(total)) if total > 0 => Some(completed as f64 / total as f64 * 100.0),
+                _ => None,
+            };
+
+            let _ = window.emit(
+                "model-pull-progress",
+                ModelPullProgress {
+                    name: name.clone(),
+                    status: status.status,
+                    digest: status.digest,
+                    total: status.total,
+                    completed: status.completed,
+                    percent,
+                },
+            );
         }
     }
 
-    Ok(full_response)
+    Ok(())
 }
 
 #[tauri::command]
-async fn chat_with_ollama_sync(
-    model: String,
-    messages: Vec<ChatMessage>,
-) -> Result<String, String> {
+async fn delete_ollama_model(name: String) -> Result<(), String> {
     let client = reqwest::Client::new();
+    let response = client
+        .delete(format!("{}/api/delete", OLLAMA_URL))
+        .json(&serde_json::json!({ "name": name }))
+        .send()
+        .await
+        .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
 
-    let request = OllamaChatRequest {
-        model,
-        messages,
-        stream: false,
-    };
+    if !response.status().is_success() {
+        return Err(format!("Failed to delete {}: {}", name, response.status()));
+    }
+
+    Ok(())
+}
 
+#[tauri::command]
+async fn show_model_info(name: String) -> Result<OllamaModelInfo, String> {
+    let client = reqwest::Client::new();
     let response = client
-        .post("http://localhost:11434/api/chat")
-        .json(&request)
+        .post(format!("{}/api/show", OLLAMA_URL))
+        .json(&serde_json::json!({ "name": name }))
         .send()
         .await
         .map_err(|e| format!("Failed to connect to Ollama: {}", e))?;
@@ -1125,34 +755,46 @@ async fn chat_with_ollama_sync(
         return Err(format!("Ollama error: {}", response.status()));
     }
 
-    let chat_response: OllamaChatResponse = response
+    response
         .json()
         .await
-        .map_err(|e| format!("Failed to parse response: {}", e))?;
-
-    Ok(chat_response.message.content)
+        .map_err(|e| format!("Failed to parse model info: {}", e))
 }
 
 #[tauri::command]
-fn create_file(path: &str, content: &str) -> Result<(), String> {
-    if let Some(parent) = Path::new(path).parent() {
+fn create_file(
+    path: &str,
+    content: &str,
+    history: State<'_, HistoryState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<(), String> {
+    let path = workspace.check(path)?;
+    let _ = history.snapshot(&path, "create");
+    if let Some(parent) = path.parent() {
         std_fs::create_dir_all(parent).map_err(|e| format!("Failed to create directories: {}", e))?;
     }
-    std_fs::write(path, content).map_err(|e| format!("Failed to create file: {}", e))
+    // Checked again in case the directories now resolve somewhere else
+    let path = workspace.check(&path)?;
+    std_fs::write(&path, content).map_err(|e| format!("Failed to create file: {}", e))
 }
 
+/// Moves a file or directory to the trash; `permanent` deletes it outright.
 #[tauri::command]
-fn delete_file(path: &str) -> Result<(), String> {
-    let path = Path::new(path);
-    if path.is_dir() {
-        std_fs::remove_dir_all(path).map_err(|e| format!("Failed to delete directory: {}", e))
-    } else {
-        std_fs::remove_file(path).map_err(|e| format!("Failed to delete file: {}", e))
-    }
+fn delete_file(
+    path: &str,
+    permanent: Option<bool>,
+    history: State<'_, HistoryState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<(), String> {
+    workspace.check(path)?;
+    let _ = history.snapshot(Path::new(path), "delete");
+    delete_path(Path::new(path), permanent.unwrap_or(false))
 }
 
 #[tauri::command]
-fn rename_file(old_path: &str, new_path: &str) -> Result<(), String> {
+fn rename_file(old_path: &str, new_path: &str, workspace: State<'_, WorkspaceState>) -> Result<(), String> {
+    workspace.check(old_path)?;
+    workspace.check(new_path)?;
     std_fs::rename(old_path, new_path).map_err(|e| format!("Failed to rename file: {}", e))
 }
 
@@ -1161,195 +803,49 @@ struct FileMetadata {
     size: u64,
     is_dir: bool,
     is_file: bool,
+    is_symlink: bool,
     modified: Option<u64>,
+    created: Option<u64>,
+    readonly: bool,
+    // Unix only
+    mode: Option<u32>,
+    permissions: Option<String>, // "rwxr-xr-x"
+    executable: bool,
+    uid: Option<u32>,
+    gid: Option<u32>,
 }
 
 #[tauri::command]
-fn get_file_metadata(path: &str) -> Result<FileMetadata, String> {
+fn get_file_metadata(path: &str, workspace: State<'_, WorkspaceState>) -> Result<FileMetadata, String> {
+    workspace.check(path)?;
     let metadata = std_fs::metadata(path).map_err(|e| format!("Failed to get metadata: {}", e))?;
-
-    let modified = metadata
-        .modified()
-        .ok()
-        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
-        .map(|d| d.as_secs());
+    let to_secs = |time: std::io::Result<std::time::SystemTime>| {
+        time.ok()
+            .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|d| d.as_secs())
+    };
+    let unix = unix_metadata(&metadata);
 
     Ok(FileMetadata {
         size: metadata.len(),
         is_dir: metadata.is_dir(),
         is_file: metadata.is_file(),
-        modified,
+        is_symlink: std_fs::symlink_metadata(path).is_ok_and(|m| m.file_type().is_symlink()),
+        modified: to_secs(metadata.modified()),
+        created: to_secs(metadata.created()),
+        readonly: metadata.permissions().readonly(),
+        mode: unix.map(|u| u.mode),
+        permissions: unix.map(|u| permission_string(u.mode)),
+        executable: unix.is_some_and(|u| metadata.is_file() && u.mode & 0o111 != 0),
+        uid: unix.map(|u| u.uid),
+        gid: unix.map(|u| u.gid),
     })
 }
 
-type PtyWriter = Arc<Mutex<Box<dyn Write + Send>>>;
-type PtyReader = Arc<Mutex<Box<dyn Read + Send>>>;
-
-struct TerminalInstance {
-    writer: PtyWriter,
-    _reader: PtyReader,
-}
-
-struct TerminalState {
-    terminals: Mutex<HashMap<String, TerminalInstance>>,
-}
-
-impl Default for TerminalState {
-    fn default() -> Self {
-        Self {
-            terminals: Mutex::new(HashMap::new()),
-        }
-    }
-}
-
-#[derive(Debug, Serialize, Clone)]
-struct TerminalOutput {
-    terminal_id: String,
-    data: String,
-}
-
-#[tauri::command]
-async fn create_terminal(
-    window: Window,
-    terminal_id: String,
-    cwd: Option<String>,
-    state: State<'_, TerminalState>,
-) -> Result<(), String> {
-    let pty_system = native_pty_system();
-
-    let pair = pty_system
-        .openpty(PtySize {
-            rows: 24,
-            cols: 80,
-            pixel_width: 0,
-            pixel_height: 0,
-        })
-        .map_err(|e| format!("Failed to open PTY: {}", e))?;
-
-    #[cfg(target_os = "windows")]
-    let shell = {
-        let git_bash = Path::new("C:\\Program Files\\Git\\bin\\bash.exe");
-        let wsl = Path::new("C:\\Windows\\System32\\wsl.exe");
-        if git_bash.exists() {
-            git_bash.to_str().unwrap().to_string()
-        } else if wsl.exists() {
-            wsl.to_str().unwrap().to_string()
-        } else {
-            std::env::var("COMSPEC").unwrap_or_else(|_| "cmd.exe".to_string())
-        }
-    };
-    
-    #[cfg(not(target_os = "windows"))]
-    let shell = std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string());
-
-    let mut cmd = CommandBuilder::new(&shell);
-    
-    if let Some(dir) = cwd {
-        if Path::new(&dir).exists() {
-             cmd.cwd(dir);
-        }
-    }
-
-    let _child = pair
-        .slave
-        .spawn_command(cmd)
-        .map_err(|e| format!("Failed to spawn shell: {}", e))?;
-
-    let reader = pair.master.try_clone_reader()
-        .map_err(|e| format!("Failed to clone reader: {}", e))?;
-    let writer = pair.master.take_writer()
-        .map_err(|e| format!("Failed to take writer: {}", e))?;
-
-    let reader = Arc::new(Mutex::new(reader));
-    let writer = Arc::new(Mutex::new(writer));
-
-    {
-        let mut terminals = state.terminals.lock().unwrap();
-        terminals.insert(
-            terminal_id.clone(),
-            TerminalInstance {
-                writer: writer.clone(),
-                _reader: reader.clone(),
-            },
-        );
-    }
-
-    let terminal_id_clone = terminal_id.clone();
-    let window_clone = window.clone();
-    
-    std::thread::spawn(move || {
-        let mut buf = [0u8; 4096];
-        loop {
-            let n = {
-                let mut reader_guard = reader.lock().unwrap();
-                match reader_guard.read(&mut buf) {
-                    Ok(0) => break,
-                    Ok(n) => n,
-                    Err(e) => {
-                        eprintln!("Error reading from PTY: {}", e);
-                        break;
-                    }
-                }
-            };
-
-            let data = String::from_utf8_lossy(&buf[..n]).to_string();
-            let _ = window_clone.emit(
-                "terminal-output",
-                TerminalOutput {
-                    terminal_id: terminal_id_clone.clone(),
-                    data,
-                },
-            );
-        }
-    });
-
-    Ok(())
-}
-
-#[tauri::command]
-fn write_terminal(
-    terminal_id: String,
-    data: String,
-    state: State<'_, TerminalState>,
-) -> Result<(), String> {
-    let terminals = state.terminals.lock().unwrap();
-    
-    if let Some(terminal) = terminals.get(&terminal_id) {
-        let mut writer = terminal.writer.lock().unwrap();
-        writer
-            .write_all(data.as_bytes())
-            .map_err(|e| format!("Failed to write to terminal: {}", e))?;
-        writer.flush().map_err(|e| format!("Failed to flush: {}", e))?;
-        Ok(())
-    } else {
-        Err(format!("Terminal not found: {}", terminal_id))
-    }
-}
-
-#[tauri::command]
-fn resize_terminal(
-    terminal_id: String,
-    rows: u16,
-    cols: u16,
-    _state: State<'_, TerminalState>,
-) -> Result<(), String> {
-    eprintln!("Resize terminal {} to {}x{}", terminal_id, cols, rows);
-    Ok(())
-}
-
-#[tauri::command]
-fn close_terminal(
-    terminal_id: String,
-    state: State<'_, TerminalState>,
-) -> Result<(), String> {
-    let mut terminals = state.terminals.lock().unwrap();
-    terminals.remove(&terminal_id);
-    Ok(())
-}
-
 // Add these command handlers to your existing list:
 #[tauri::command]
-fn get_directory_tree(path: String, depth: u32) -> Result<String, String> {
+fn get_directory_tree(path: String, depth: u32, workspace: State<'_, WorkspaceState>) -> Result<String, String> {
+    workspace.check(&path)?;
     fn build_tree(dir: &Path, current_depth: u32, max_depth: u32, prefix: &str) -> Result<String, String> {
         if current_depth > max_depth {
             return Ok(format!("{}...\n", prefix));
@@ -1367,7 +863,8 @@ fn get_directory_tree(path: String, depth: u32) -> Result<String, String> {
                 continue;
             }
             
-            let is_dir = path.is_dir();
+            // file_type() doesn't follow links, so symlinked dirs can't loop
+            let is_dir = entry.file_type().is_ok_and(|t| t.is_dir());
             let icon = if is_dir { "📁" } else { "📄" };
             result.push_str(&format!("{}{} {}\n", prefix, icon, name));
             
@@ -1388,20 +885,34 @@ async fn search_code(
     pattern: String,
     paths: Vec<String>,
     options: HashMap<String, bool>,
+    workspace: State<'_, WorkspaceState>,
 ) -> Result<Vec<serde_json::Value>, String> {
-    let mut results = Vec::new();
+    workspace.check_all(&paths)?;
     let case_sensitive = options.get("case_sensitive").copied().unwrap_or(false);
     let regex = options.get("regex").copied().unwrap_or(false);
-    
+
+    task::spawn_blocking(move || search_file_list(&pattern, &paths, case_sensitive, regex))
+        .await
+        .map_err(|e| format!("Search task failed: {}", e))
+}
+
+pub(crate) fn search_file_list(
+    pattern: &str,
+    paths: &[String],
+    case_sensitive: bool,
+    regex: bool,
+) -> Vec<serde_json::Value> {
+    let mut results = Vec::new();
+
     for path in paths {
         if let Ok(content) = std_fs::read_to_string(&path) {
             let lines: Vec<&str> = content.lines().collect();
             for (i, line) in lines.iter().enumerate() {
                 let matches = if regex {
                     // Use regex crate in production
-                    line.contains(&pattern)
+                    line.contains(pattern)
                 } else if case_sensitive {
-                    line.contains(&pattern)
+                    line.contains(pattern)
                 } else {
                     line.to_lowercase().contains(&pattern.to_lowercase())
                 };
@@ -1418,7 +929,7 @@ async fn search_code(
         }
     }
     
-    Ok(results)
+    results
 }
 
 // ============================================================================
@@ -1437,12 +948,54 @@ pub fn run() {
         .plugin(tauri_plugin_notification::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .plugin(tauri_plugin_sql::Builder::default().build())
-        .manage(TerminalState::default())
         .manage(ParserState::new())
         .manage(Neo4jState::new())
+        .manage(CompletionState::default())
+        .manage(TaskState::default())
+        .manage(DevProcessState::default())
+        .manage(GitCloneState::default())
+        .manage(CredentialState::default())
+        .manage(SearchState::default())
+        .manage(ReplaceState::default())
+        .manage(FinderState::default())
+        .manage(EncodingState::default())
+        .manage(SymbolIndexState::default())
+        .manage(LspState::default())
+        .manage(DiagnosticsState::default())
+        .manage(TestRunState::default())
+        .manage(DapState::default())
+        .manage(McpState::default())
+        .manage(SnippetState::default())
+        .manage(DockerState::default())
+        .manage(EventBusState::default())
+        .manage(JobState::default())
+        .setup(|app| {
+            let data_dir = app.path().app_data_dir()?;
+            std_fs::create_dir_all(&data_dir)?;
+            let _ = std_fs::remove_file(data_dir.join(LEGACY_CREDENTIAL_STORE));
+            app.manage(ConversationState::open(&data_dir.join("conversations.db"))?);
+            app.manage(PromptState::open(&data_dir.join("prompts.db"))?);
+            app.manage(LlmState::open(&data_dir.join("llm.db"), app.handle().clone())?);
+            app.manage(TerminalState::open(&data_dir.join("terminals.db"))?);
+            app.manage(HistoryState::open(&data_dir.join("history.db"))?);
+            app.manage(RecentFilesState::open(&data_dir.join("recent.db"))?);
+            app.manage(WorkspaceState::open(&data_dir.join("workspaces.json"))?);
+            app.manage(CodeIndexState::new(data_dir.join("code-index")));
+            app.manage(EmbeddingIndexState::open(&data_dir.join("embeddings.db"))?);
+            app.manage(AuditState::open(&data_dir.join("audit.db"))?);
+            app.manage(PluginState::open(&data_dir.join("plugins"), &data_dir.join("plugins.json"))?);
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             greet,
+            pick_workspace_folder,
+            open_workspace,
+            request_path_access,
+            get_workspace_roots,
+            close_workspace,
             read_directory,
+            read_directory_children,
+            get_path_stats,
             read_file_while_content,
             read_file_content,
             write_file_content,
@@ -1450,14 +1003,40 @@ pub fn run() {
             get_ollama_models,
             chat_with_ollama,
             chat_with_ollama_sync,
+            pull_ollama_model,
+            delete_ollama_model,
+            show_model_info,
             create_file,
             delete_file,
             rename_file,
             get_file_metadata,
+            set_permissions,
+            set_executable,
+            record_file_open,
+            get_recent_files,
+            clear_recent_files,
             create_terminal,
             write_terminal,
+            list_available_shells,
             resize_terminal,
+            kill_terminal,
+            get_terminal_buffer,
+            get_terminal_cwd,
+            search_terminal_output,
+            get_command_history,
             close_terminal,
+            list_terminals,
+            restore_terminals,
+            save_terminal_sessions,
+            run_command,
+            start_dev_process,
+            stop_dev_process,
+            restart_dev_process,
+            get_dev_process_logs,
+            get_dev_process_status,
+            list_dev_processes,
+            detect_tasks,
+            run_task,
             parse_files,
             parse_single_file,
             read_and_parse_files,
@@ -1471,16 +1050,263 @@ pub fn run() {
             generate_graph_context,
             graph_to_query_context,
             check_is_git_repo,
+            is_path_ignored,
+            are_paths_ignored,
             get_git_status,
             get_diff_content,
+            git_show_file,
+            git_diff_file,
+            git_diff_summary,
+            git_diff_range,
             get_commit_history,
+            git_file_history,
+            git_blame,
             git_add,
+            git_add_all,
+            git_unstage_all,
+            git_unstage,
+            git_stage_hunk,
+            git_stage_lines,
+            git_discard_changes,
             git_commit,
+            git_commit_amend,
+            git_revert,
+            git_cherry_pick,
+            git_rebase,
+            git_rebase_continue,
+            git_rebase_abort,
             git_push,
             git_pull,
+            git_remotes,
+            git_add_remote,
+            git_remove_remote,
+            git_set_remote_url,
+            git_fetch,
+            git_sync_status,
+            git_conflicts,
+            git_accept_ours,
+            git_accept_theirs,
+            git_mark_resolved,
+            git_set_credentials,
+            git_clear_credentials,
+            git_set_ssh_passphrase,
+            git_clone,
+            git_cancel_clone,
+            git_branches,
+            git_create_branch,
+            git_checkout_branch,
+            git_delete_branch,
+            git_rename_branch,
+            git_tags,
+            git_create_tag,
+            git_delete_tag,
+            git_push_tags,
+            git_worktrees,
+            git_add_worktree,
+            git_remove_worktree,
+            git_submodules,
+            git_submodule_init,
+            git_submodule_update,
+            git_stash_save,
+            git_stash_list,
+            git_stash_apply,
+            git_stash_pop,
+            git_stash_drop,
             get_directory_tree,
-            search_code
+            search_code,
+            search_in_files,
+            cancel_search,
+            replace_in_files,
+            apply_replacements,
+            undo_replacements,
+            index_project_files,
+            fuzzy_find_files,
+            index_project,
+            query_index,
+            index_symbols,
+            find_definition,
+            find_references,
+            structural_search,
+            chunk_file,
+            rename_symbol,
+            scan_annotations,
+            index_embeddings,
+            get_embedding_index_status,
+            hybrid_search,
+            find_shared_literals,
+            get_affected_files,
+            get_owners,
+            attach_owners_to_graph,
+            lsp_get_server_configs,
+            lsp_configure_server,
+            lsp_start_server,
+            lsp_stop_server,
+            lsp_list_servers,
+            lsp_did_open,
+            lsp_did_change,
+            lsp_did_save,
+            lsp_did_close,
+            lsp_hover,
+            lsp_completion,
+            lsp_definition,
+            lsp_diagnostics,
+            get_diagnostics,
+            check_syntax,
+            clear_diagnostics,
+            format_file,
+            run_linter,
+            discover_tests,
+            run_tests,
+            cancel_test_run,
+            dap_get_adapter_configs,
+            dap_configure_adapter,
+            dap_start_session,
+            dap_stop_session,
+            dap_list_sessions,
+            dap_set_breakpoints,
+            dap_step,
+            dap_threads,
+            dap_stack_trace,
+            dap_scopes,
+            dap_variables,
+            dap_evaluate,
+            audit_dependencies,
+            forge_repo_info,
+            forge_list_pull_requests,
+            forge_get_pull_request,
+            forge_pull_request_diff,
+            forge_review_comments,
+            forge_list_issues,
+            forge_get_issue,
+            forge_create_pull_request,
+            mcp_start_server,
+            mcp_stop_server,
+            mcp_server_info,
+            mcp_connect_client,
+            mcp_list_clients,
+            mcp_call_tool,
+            mcp_disconnect_client,
+            execute_snippet,
+            cancel_snippet,
+            docker_detect,
+            docker_build_image,
+            docker_compose_services,
+            docker_compose_up,
+            docker_compose_stop,
+            docker_compose_logs,
+            docker_stop_logs,
+            docker_list_containers,
+            docker_open_terminal,
+            ingest_api_schemas,
+            list_plugins,
+            get_plugins_directory,
+            set_plugin_enabled,
+            plugin_extract_graph,
+            plugin_run_command,
+            plugin_call_tool,
+            build_code_graph,
+            load_code_graph,
+            event_bus_start,
+            event_bus_stop,
+            event_bus_info,
+            parse_files_stream,
+            build_code_graph_stream,
+            load_code_graph_stream,
+            store_graph_binary,
+            start_job,
+            cancel_job,
+            list_jobs,
+            open_file_smart,
+            read_file_range,
+            copy_path,
+            move_path,
+            duplicate_path,
+            delete_paths,
+            list_file_history,
+            get_file_version,
+            restore_file_version,
+            read_files_batch,
+            extract_archive,
+            create_archive,
+            diff_files,
+            create_conversation,
+            list_conversations,
+            rename_conversation,
+            delete_conversation,
+            append_message,
+            get_conversation_messages,
+            run_agent,
+            chat_structured,
+            list_prompt_templates,
+            get_prompt_template,
+            save_prompt_template,
+            delete_prompt_template,
+            render_prompt,
+            complete_code,
+            cancel_completion,
+            generate_commit_message,
+            review_changes,
+            set_llm_cache_enabled,
+            get_llm_cache_stats,
+            clear_llm_cache,
+            get_llm_logging_settings,
+            set_llm_logging_settings,
+            get_llm_request_log,
+            export_llm_request_log,
+            clear_llm_request_log,
+            get_llm_retry_policy,
+            set_llm_retry_policy,
+            get_model_routes,
+            set_model_route,
+            delete_model_route,
+            resolve_model,
+            summarize_repository
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
-}
\ No newline at end of file
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|app, event| {
+            // Tauri exits without dropping managed state, so `kill_on_drop`
+            // never fires; everything that owns a process is stopped here
+            if let tauri::RunEvent::Exit = event {
+                if let Some(terminals) = app.try_state::<TerminalState>() {
+                    terminals.shutdown();
+                }
+                if let Some(plugins) = app.try_state::<PluginState>() {
+                    plugins.shutdown();
+                }
+                app.state::<DevProcessState>().shutdown();
+                app.state::<LspState>().shutdown();
+                app.state::<DapState>().shutdown();
+                app.state::<McpState>().shutdown();
+                app.state::<DockerState>().shutdown();
+                app.state::<TestRunState>().shutdown();
+                app.state::<SnippetState>().shutdown();
+                // Aborted tasks drop, and so kill, their processes on the runtime's threads
+                std::thread::sleep(std::time::Duration::from_millis(200));
+            }
+        });
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ndjson_lines_wait_for_their_newline() {
+        let mut pending = b"{\"a\":1}\n\n{\"b\":".to_vec();
+        assert_eq!(take_ndjson_lines(&mut pending), vec!["{\"a\":1}"]);
+        assert_eq!(pending, b"{\"b\":");
+        pending.extend_from_slice(b"2}\r\n");
+        assert_eq!(take_ndjson_lines(&mut pending), vec!["{\"b\":2}"]);
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn ndjson_characters_split_across_chunks_survive() {
+        let text = "{\"content\":\"é\"}\n".as_bytes();
+        let (first, second) = text.split_at(13);
+        let mut pending = first.to_vec();
+        assert!(take_ndjson_lines(&mut pending).is_empty());
+        pending.extend_from_slice(second);
+        assert_eq!(take_ndjson_lines(&mut pending), vec!["{\"content\":\"é\"}"]);
+    }
+}
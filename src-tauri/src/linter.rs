@@ -0,0 +1,504 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter, Manager, State};
+
+use crate::diagnostics::{count, Diagnostic, DiagnosticsState, FileDiagnostics};
+use crate::formatter::{node_bin, on_path};
+use crate::lsp::{LspPosition, LspRange};
+use crate::process::run_process;
+use crate::sandbox::WorkspaceState;
+
+/// Clippy builds the crate first, which can take a while.
+const LINT_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+const VERSION_TIMEOUT_MS: u64 = 10_000;
+const STDERR_TAIL_LINES: usize = 20;
+
+const ESLINT_CONFIGS: &[&str] = &[
+    "eslint.config.js",
+    "eslint.config.mjs",
+    "eslint.config.cjs",
+    "eslint.config.ts",
+    ".eslintrc",
+    ".eslintrc.js",
+    ".eslintrc.cjs",
+    ".eslintrc.json",
+    ".eslintrc.yaml",
+    ".eslintrc.yml",
+];
+const ESLINT_EXTENSIONS: &[&str] = &["js", "jsx", "mjs", "cjs", "ts", "tsx", "mts", "cts", "vue"];
+
+// ============================================================================
+// LINTER STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LinterRun {
+    /// "eslint", "clippy", "ruff" or "golangci-lint".
+    pub linter: String,
+    pub program: String,
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub duration_ms: u64,
+    pub diagnostics: usize,
+    pub files: usize,
+    /// Why the linter produced no usable output, e.g. a missing program.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LintReport {
+    pub runs: Vec<LinterRun>,
+    pub files: Vec<FileDiagnostics>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct LinterStatusEvent {
+    linter: String,
+    state: &'static str, // "running", "finished", "failed"
+    run: Option<LinterRun>,
+}
+
+struct Linter {
+    name: &'static str,
+    /// `None` if the project is configured for the linter but it isn't
+    /// installed.
+    program: Option<PathBuf>,
+    extensions: &'static [&'static str],
+}
+
+/// Diagnostics by absolute path.
+type LintOutput = BTreeMap<String, Vec<Diagnostic>>;
+
+// ============================================================================
+// LINTER DETECTION
+// ============================================================================
+
+fn file_contains(path: &Path, needle: &str) -> bool {
+    std_fs::read_to_string(path).is_ok_and(|content| content.contains(needle))
+}
+
+/// Linters the project at `root` is set up for.
+fn detect_linters(root: &Path) -> Vec<Linter> {
+    let mut linters = Vec::new();
+    if ESLINT_CONFIGS.iter().any(|c| root.join(c).is_file())
+        || file_contains(&root.join("package.json"), "\"eslintConfig\"")
+    {
+        linters.push(Linter {
+            name: "eslint",
+            program: node_bin(root, "eslint").or_else(|| on_path("eslint")),
+            extensions: ESLINT_EXTENSIONS,
+        });
+    }
+    if root.join("Cargo.toml").is_file() {
+        linters.push(Linter {
+            name: "clippy",
+            program: on_path("cargo"),
+            extensions: &["rs"],
+        });
+    }
+    if root.join("ruff.toml").is_file()
+        || root.join(".ruff.toml").is_file()
+        || file_contains(&root.join("pyproject.toml"), "[tool.ruff")
+    {
+        linters.push(Linter {
+            name: "ruff",
+            program: on_path("ruff"),
+            extensions: &["py", "pyi"],
+        });
+    }
+    if root.join("go.mod").is_file() {
+        linters.push(Linter {
+            name: "golangci-lint",
+            program: on_path("golangci-lint"),
+            extensions: &["go"],
+        });
+    }
+    linters
+}
+
+fn has_extension(path: &Path, extensions: &[&str]) -> bool {
+    path.extension()
+        .is_some_and(|e| extensions.contains(&e.to_string_lossy().to_lowercase().as_str()))
+}
+
+/// golangci-lint v2 replaced `--out-format` with per-format output flags.
+async fn golangci_args(program: &str, root: &str) -> Vec<String> {
+    let args = ["--version".to_string()];
+    let env = HashMap::new();
+    let version = run_process(None, "golangci-lint-version", Some(root), program, &args, &env, Some(VERSION_TIMEOUT_MS))
+        .await
+        .map(|r| r.stdout)
+        .unwrap_or_default();
+    if version.contains("version 2.") || version.contains("version v2.") {
+        vec!["run".to_string(), "--output.json.path=stdout".to_string(), "--show-stats=false".to_string()]
+    } else {
+        vec!["run".to_string(), "--out-format=json".to_string()]
+    }
+}
+
+async fn linter_args(linter: &Linter, program: &str, root: &Path, files: &[PathBuf]) -> Vec<String> {
+    let targets: Vec<String> = files.iter().map(|f| f.to_string_lossy().to_string()).collect();
+    let or_root = |targets: Vec<String>| if targets.is_empty() { vec![".".to_string()] } else { targets };
+    match linter.name {
+        "eslint" => [vec!["--format".to_string(), "json".to_string()], or_root(targets)].concat(),
+        "ruff" => [
+            vec!["check".to_string(), "--output-format".to_string(), "json".to_string(), "--exit-zero".to_string()],
+            or_root(targets),
+        ]
+        .concat(),
+        // Clippy always checks whole crates; results are filtered afterwards
+        "clippy" => ["clippy", "--message-format=json", "--quiet", "--all-targets"]
+            .iter()
+            .map(|a| a.to_string())
+            .collect(),
+        _ => {
+            let mut args = golangci_args(program, &root.to_string_lossy()).await;
+            // It lints packages, so name the files' directories
+            let dirs: BTreeSet<String> = files
+                .iter()
+                .filter_map(|f| f.parent())
+                .map(|d| d.to_string_lossy().to_string())
+                .collect();
+            if dirs.is_empty() {
+                args.push("./...".to_string());
+            } else {
+                args.extend(dirs);
+            }
+            args
+        }
+    }
+}
+
+// ============================================================================
+// OUTPUT PARSING
+// ============================================================================
+
+/// Linters report 1-based lines and columns.
+fn diagnostic(
+    linter: &str,
+    severity: &str,
+    message: String,
+    code: Option<String>,
+    start: (u64, u64),
+    end: Option<(u64, u64)>,
+) -> Diagnostic {
+    let position = |(line, column): (u64, u64)| LspPosition {
+        line: line.saturating_sub(1) as u32,
+        character: column.saturating_sub(1) as u32,
+    };
+    Diagnostic {
+        range: LspRange {
+            start: position(start),
+            end: position(end.unwrap_or(start)),
+        },
+        severity: severity.to_string(),
+        message,
+        code,
+        provider: format!("linter:{}", linter),
+        source: linter.to_string(),
+    }
+}
+
+fn field_u64(value: &Value, key: &str) -> Option<u64> {
+    value.get(key).and_then(|v| v.as_u64())
+}
+
+fn field_str(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(|v| v.as_str()).map(|v| v.to_string())
+}
+
+fn parse_eslint(stdout: &str) -> Result<LintOutput, String> {
+    let results: Vec<Value> = serde_json::from_str(stdout).map_err(|e| format!("Invalid eslint output: {}", e))?;
+    let mut output = LintOutput::new();
+    for result in results {
+        let Some(path) = field_str(&result, "filePath") else {
+            continue;
+        };
+        let messages = result.get("messages").and_then(|m| m.as_array()).cloned().unwrap_or_default();
+        let diagnostics = messages.iter().filter_map(|m| {
+            let start = (field_u64(m, "line")?, field_u64(m, "column").unwrap_or(1));
+            let end = field_u64(m, "endLine").zip(field_u64(m, "endColumn"));
+            let severity = if field_u64(m, "severity") == Some(2) { "error" } else { "warning" };
+            Some(diagnostic("eslint", severity, field_str(m, "message")?, field_str(m, "ruleId"), start, end))
+        });
+        output.entry(path).or_default().extend(diagnostics);
+    }
+    Ok(output)
+}
+
+fn parse_ruff(stdout: &str) -> Result<LintOutput, String> {
+    let results: Vec<Value> = serde_json::from_str(stdout).map_err(|e| format!("Invalid ruff output: {}", e))?;
+    let mut output = LintOutput::new();
+    for result in results {
+        let location = |key: &str| {
+            let location = result.get(key)?;
+            Some((field_u64(location, "row")?, field_u64(location, "column")?))
+        };
+        let (Some(path), Some(message), Some(start)) =
+            (field_str(&result, "filename"), field_str(&result, "message"), location("location"))
+        else {
+            continue;
+        };
+        // Only syntax errors come without a rule code
+        let code = field_str(&result, "code");
+        let severity = if code.is_some() { "warning" } else { "error" };
+        output
+            .entry(path)
+            .or_default()
+            .push(diagnostic("ruff", severity, message, code, start, location("end_location")));
+    }
+    Ok(output)
+}
+
+/// Cargo prints span paths relative to the workspace root, which may be
+/// above `root`.
+fn resolve_span_path(root: &Path, file_name: &str) -> Option<PathBuf> {
+    let path = Path::new(file_name);
+    if path.is_absolute() {
+        return Some(path.to_path_buf());
+    }
+    root.ancestors().map(|dir| dir.join(path)).find(|candidate| candidate.is_file())
+}
+
+fn parse_clippy(stdout: &str, root: &Path) -> LintOutput {
+    let mut output = LintOutput::new();
+    for line in stdout.lines() {
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            continue;
+        };
+        if event.get("reason").and_then(|r| r.as_str()) != Some("compiler-message") {
+            continue;
+        }
+        let Some(message) = event.get("message") else {
+            continue;
+        };
+        let severity = match message.get("level").and_then(|l| l.as_str()) {
+            Some("error") => "error",
+            Some("warning") => "warning",
+            _ => continue,
+        };
+        let spans = message.get("spans").and_then(|s| s.as_array()).cloned().unwrap_or_default();
+        let Some(span) = spans.iter().find(|s| s.get("is_primary").and_then(|p| p.as_bool()) == Some(true)) else {
+            continue;
+        };
+        let Some(path) = field_str(span, "file_name").and_then(|f| resolve_span_path(root, &f)) else {
+            continue;
+        };
+        // Spans into registry crates or the standard library
+        if !path.starts_with(root) {
+            continue;
+        }
+        let (Some(text), Some(line_start), Some(column_start)) =
+            (field_str(message, "message"), field_u64(span, "line_start"), field_u64(span, "column_start"))
+        else {
+            continue;
+        };
+        let end = field_u64(span, "line_end").zip(field_u64(span, "column_end"));
+        let code = message.get("code").and_then(|c| field_str(c, "code"));
+        output
+            .entry(path.to_string_lossy().to_string())
+            .or_default()
+            .push(diagnostic("clippy", severity, text, code, (line_start, column_start), end));
+    }
+    output
+}
+
+fn parse_golangci(stdout: &str, root: &Path) -> Result<LintOutput, String> {
+    // v2 may follow the JSON with a text summary
+    let json = stdout.lines().find(|l| l.trim_start().starts_with('{')).unwrap_or_default();
+    let report: Value = serde_json::from_str(json).map_err(|e| format!("Invalid golangci-lint output: {}", e))?;
+    let mut output = LintOutput::new();
+    for issue in report.get("Issues").and_then(|i| i.as_array()).cloned().unwrap_or_default() {
+        let Some(position) = issue.get("Pos") else {
+            continue;
+        };
+        let (Some(file), Some(text), Some(line)) =
+            (field_str(position, "Filename"), field_str(&issue, "Text"), field_u64(position, "Line"))
+        else {
+            continue;
+        };
+        let severity = match field_str(&issue, "Severity").as_deref() {
+            Some("error") => "error",
+            Some("info") => "information",
+            _ => "warning",
+        };
+        output.entry(root.join(file).to_string_lossy().to_string()).or_default().push(diagnostic(
+            "golangci-lint",
+            severity,
+            text,
+            field_str(&issue, "FromLinter"),
+            (line, field_u64(position, "Column").unwrap_or(1)),
+            None,
+        ));
+    }
+    Ok(output)
+}
+
+fn emit_status(app: &AppHandle, run: &LinterRun, state: &'static str) {
+    let event = LinterStatusEvent {
+        linter: run.linter.clone(),
+        state,
+        run: (state != "running").then(|| run.clone()),
+    };
+    let _ = app.emit("linter-status", event);
+}
+
+fn stderr_tail(stderr: &str) -> String {
+    let lines: Vec<&str> = stderr.trim_end().lines().collect();
+    lines[lines.len().saturating_sub(STDERR_TAIL_LINES)..].join("\n")
+}
+
+// ============================================================================
+// LINTING
+// ============================================================================
+
+/// Runs one linter and publishes its diagnostics, replacing what it
+/// reported before for the linted scope.
+async fn lint(app: &AppHandle, linter: &Linter, root: &Path, files: &[PathBuf]) -> (LinterRun, LintOutput) {
+    let mut run = LinterRun {
+        linter: linter.name.to_string(),
+        program: String::new(),
+        exit_code: None,
+        timed_out: false,
+        duration_ms: 0,
+        diagnostics: 0,
+        files: 0,
+        error: None,
+    };
+    let Some(program) = &linter.program else {
+        run.error = Some(format!("{} is configured for this project but not installed", linter.name));
+        emit_status(app, &run, "failed");
+        return (run, LintOutput::new());
+    };
+    run.program = program.to_string_lossy().to_string();
+    emit_status(app, &run, "running");
+
+    let args = linter_args(linter, &run.program, root, files).await;
+    let cwd = root.to_string_lossy().to_string();
+    let command_id = format!("lint-{}", linter.name);
+    let env = HashMap::new();
+    let result = run_process(None, &command_id, Some(&cwd), &run.program, &args, &env, Some(LINT_TIMEOUT_MS)).await;
+    let result = match result {
+        Ok(result) => result,
+        Err(e) => {
+            run.error = Some(e);
+            emit_status(app, &run, "failed");
+            return (run, LintOutput::new());
+        }
+    };
+    run.exit_code = result.exit_code;
+    run.timed_out = result.timed_out;
+    run.duration_ms = result.duration_ms;
+
+    let parsed = match linter.name {
+        "eslint" => parse_eslint(&result.stdout),
+        "ruff" => parse_ruff(&result.stdout),
+        "clippy" => Ok(parse_clippy(&result.stdout, root)),
+        _ => parse_golangci(&result.stdout, root),
+    };
+    let mut output = match parsed {
+        Ok(output) => output,
+        Err(e) => {
+            let stderr = stderr_tail(&result.stderr);
+            run.error = Some(if stderr.is_empty() { e } else { stderr });
+            LintOutput::new()
+        }
+    };
+    // A build failure that produced no compiler messages
+    let failed_build = linter.name == "clippy" && result.exit_code.is_some_and(|c| c != 0);
+    if run.error.is_none() && output.is_empty() && failed_build {
+        run.error = Some(stderr_tail(&result.stderr));
+    }
+
+    // Crate-wide linters also report files outside the selection
+    if !files.is_empty() {
+        let selected: BTreeSet<String> = files.iter().map(|f| f.to_string_lossy().to_string()).collect();
+        output.retain(|path, _| selected.contains(path));
+    }
+    output.retain(|_, diagnostics| !diagnostics.is_empty());
+
+    let provider = format!("linter:{}", linter.name);
+    let state = app.state::<DiagnosticsState>();
+    if run.error.is_none() {
+        let stale: Vec<String> = state
+            .paths_with(&provider)
+            .into_iter()
+            .filter(|path| !output.contains_key(path))
+            .filter(|path| files.is_empty() || files.iter().any(|f| f.to_string_lossy() == path.as_str()))
+            .collect();
+        for path in stale {
+            state.publish(app, &path, &provider, Vec::new());
+        }
+    }
+    for (path, diagnostics) in &output {
+        state.publish(app, path, &provider, diagnostics.clone());
+    }
+
+    run.files = output.len();
+    run.diagnostics = output.values().map(|d| d.len()).sum();
+    emit_status(app, &run, if run.error.is_some() { "failed" } else { "finished" });
+    (run, output)
+}
+
+// ============================================================================
+// LINTER TAURI COMMANDS
+// ============================================================================
+
+/// Runs the linters the project at `root` is configured for (eslint,
+/// clippy, ruff, golangci-lint) concurrently, optionally only on `files`
+/// or only the named `linters`. Each linter publishes into the diagnostics
+/// store as soon as it finishes, with `linter-status` events along the way.
+#[tauri::command]
+pub async fn run_linter(
+    app: AppHandle,
+    root: String,
+    files: Option<Vec<String>>,
+    linters: Option<Vec<String>>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<LintReport, String> {
+    let root = workspace.check(&root)?;
+    let files: Vec<PathBuf> = files
+        .unwrap_or_default()
+        .iter()
+        .map(|f| workspace.check(root.join(f)))
+        .collect::<Result<_, _>>()?;
+
+    let selected: Vec<(Linter, Vec<PathBuf>)> = detect_linters(&root)
+        .into_iter()
+        .filter(|l| linters.as_ref().is_none_or(|names| names.iter().any(|n| n == l.name)))
+        .filter_map(|linter| {
+            let matching: Vec<PathBuf> =
+                files.iter().filter(|f| has_extension(f, linter.extensions)).cloned().collect();
+            (files.is_empty() || !matching.is_empty()).then_some((linter, matching))
+        })
+        .collect();
+    if selected.is_empty() {
+        return Err(format!("No configured linter for {}", root.display()));
+    }
+
+    let runs = futures::future::join_all(selected.iter().map(|(linter, files)| lint(&app, linter, &root, files)))
+        .await;
+
+    let mut merged = LintOutput::new();
+    let mut report_runs = Vec::new();
+    for (run, output) in runs {
+        report_runs.push(run);
+        for (path, diagnostics) in output {
+            merged.entry(path).or_default().extend(diagnostics);
+        }
+    }
+    Ok(LintReport {
+        runs: report_runs,
+        files: merged
+            .into_iter()
+            .map(|(path, diagnostics)| FileDiagnostics {
+                path,
+                counts: count(&diagnostics),
+                diagnostics,
+            })
+            .collect(),
+    })
+}
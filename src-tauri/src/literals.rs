@@ -0,0 +1,317 @@
+use neo4rs::query;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+
+pub(crate) use gencode_core::syntax::string_value;
+
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::files::read_text;
+use crate::finder::relative_path;
+use crate::sandbox::WorkspaceState;
+use crate::symbols::{node_text, LineIndex, SourcePosition};
+use crate::{Neo4jState, ParserState};
+
+const MIN_LITERAL_CHARS: usize = 3;
+/// Literals in more files than this are too generic to say anything.
+const MAX_FILES_PER_LITERAL: usize = 50;
+const MAX_LINE_CHARS: usize = 200;
+
+pub(crate) const STRING_NODES: &[&str] = &[
+    "string",
+    "string_literal",
+    "template_string",
+    "interpreted_string_literal",
+    "raw_string_literal",
+];
+
+/// Route parameters (`${id}`, `{id}`, `:id`, `<id>`), normalized to `*`.
+pub(crate) const ROUTE_PARAMETER: &str = r"\$\{[^}]*\}|\{[^}/]*\}|<[^>/]*>|:[A-Za-z_][A-Za-z0-9_]*";
+
+// ============================================================================
+// LITERAL STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct LiteralOptions {
+    /// Literal kinds to report: "route", "url", "env", "key" and "table".
+    /// Empty reports all of them.
+    pub kinds: Vec<String>,
+    /// Also report literals shared within a single language.
+    pub include_same_language: bool,
+    /// Replace the SHARES_LITERAL edges between FILE nodes in the graph.
+    pub store_in_graph: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LiteralOccurrence {
+    pub path: String,
+    pub relative_path: String,
+    pub language: String,
+    pub position: SourcePosition,
+    /// The literal as written, before route parameters were normalized.
+    pub raw: String,
+    pub line_text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedLiteral {
+    /// Route parameters (`${id}`, `{id}`, `:id`, `<id>`) read as `*`.
+    pub value: String,
+    pub kind: String,
+    pub languages: Vec<String>,
+    pub occurrences: Vec<LiteralOccurrence>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SharedLiteralsResult {
+    pub literals: Vec<SharedLiteral>,
+    pub files_scanned: usize,
+    /// SHARES_LITERAL edges written to the graph.
+    pub edges_stored: usize,
+}
+
+// ============================================================================
+// LITERAL EXTRACTION
+// ============================================================================
+
+struct Classifier {
+    env: Regex,
+    key: Regex,
+    sql: Regex,
+    table: Regex,
+    parameter: Regex,
+}
+
+impl Classifier {
+    fn new() -> Self {
+        Classifier {
+            env: Regex::new(r"^[A-Z][A-Z0-9]*(_[A-Z0-9]+)+$").unwrap(),
+            key: Regex::new(r"^[a-z][a-zA-Z0-9_-]*(\.[a-zA-Z0-9_-]+)+$").unwrap(),
+            sql: Regex::new(r"(?i)^\s*(select|insert|update|delete|create|alter|drop|with)\s").unwrap(),
+            table: Regex::new(r"(?i)\b(?:from|join|into|update|table)\s+(?:if\s+(?:not\s+)?exists\s+)?[`\x22\[]?([A-Za-z_][A-Za-z0-9_.]*)").unwrap(),
+            parameter: Regex::new(ROUTE_PARAMETER).unwrap(),
+        }
+    }
+
+    /// (kind, normalized value) pairs a literal contributes. SQL strings
+    /// yield one entry per table they mention.
+    fn classify(&self, text: &str) -> Vec<(&'static str, String)> {
+        let text = text.trim();
+        if text.chars().count() < MIN_LITERAL_CHARS || (text.contains('\n') && !self.sql.is_match(text)) {
+            return Vec::new();
+        }
+        if self.sql.is_match(text) {
+            let tables: BTreeSet<String> = self.table.captures_iter(text).map(|c| c[1].to_lowercase()).collect();
+            return tables.into_iter().map(|table| ("table", table)).collect();
+        }
+        if text.starts_with("http://") || text.starts_with("https://") || text.starts_with("ws://") || text.starts_with("wss://") {
+            let url = text.split(['?', '#']).next().unwrap_or(text);
+            return vec![("url", self.parameter.replace_all(url, "*").trim_end_matches('/').to_string())];
+        }
+        if text.starts_with('/') && text.len() > 1 && !text.contains(char::is_whitespace) && !text.starts_with("//") {
+            let route = text.split(['?', '#']).next().unwrap_or(text);
+            return vec![("route", self.parameter.replace_all(route, "*").trim_end_matches('/').to_string())];
+        }
+        if self.env.is_match(text) {
+            return vec![("env", text.to_string())];
+        }
+        if self.key.is_match(text) && !looks_like_file_name(text) {
+            return vec![("key", text.to_string())];
+        }
+        Vec::new()
+    }
+}
+
+/// `config.json`-style names match the translation key pattern too.
+fn looks_like_file_name(text: &str) -> bool {
+    const EXTENSIONS: &[&str] = &[
+        "js", "ts", "tsx", "jsx", "json", "rs", "py", "go", "java", "c", "h", "cpp", "css", "html", "md", "toml", "yaml",
+        "yml", "txt", "png", "svg", "db",
+    ];
+    text.rsplit('.').next().is_some_and(|ext| EXTENSIONS.contains(&ext))
+}
+
+/// Language a literal is attributed to; .ts and .tsx files count as one.
+fn literal_language(language: &str) -> String {
+    match language {
+        "tsx" => "typescript".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn scan_file(
+    parser: &ParserState,
+    classifier: &Classifier,
+    root: &Path,
+    path: &Path,
+    found: &mut BTreeMap<(String, String), Vec<LiteralOccurrence>>,
+) -> bool {
+    let path_str = path.to_string_lossy().to_string();
+    let Ok((content, _)) = read_text(path) else {
+        return false;
+    };
+    let Some((language, tree)) = parser.parse_tree(&path_str, &content) else {
+        return false;
+    };
+    let language = literal_language(&language);
+    let lines = LineIndex::new(&content);
+    let text_lines: Vec<&str> = content.lines().collect();
+    let relative = relative_path(root, path).unwrap_or_default();
+
+    let mut stack = vec![tree.root_node()];
+    while let Some(node) = stack.pop() {
+        if !STRING_NODES.contains(&node.kind()) {
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+            continue;
+        }
+        let raw = string_value(node, content.as_bytes());
+        for (kind, value) in classifier.classify(&raw) {
+            let position = lines.position(node.start_position());
+            let line_text = text_lines.get(position.line).copied().unwrap_or_default();
+            found.entry((kind.to_string(), value)).or_default().push(LiteralOccurrence {
+                path: path_str.clone(),
+                relative_path: relative.clone(),
+                language: language.clone(),
+                position,
+                raw: raw.clone(),
+                line_text: line_text.trim().chars().take(MAX_LINE_CHARS).collect(),
+            });
+        }
+    }
+    true
+}
+
+fn find_shared(parser: &ParserState, root: &Path, options: &LiteralOptions) -> Result<SharedLiteralsResult, String> {
+    let mut files: Vec<PathBuf> = walk_entries(root, &DirectoryOptions::default(), None)?
+        .into_iter()
+        .filter(|(path, is_dir)| !is_dir && parser.detect_language(&path.to_string_lossy()).is_some())
+        .map(|(path, _)| path)
+        .collect();
+    files.sort();
+
+    let classifier = Classifier::new();
+    let mut found = BTreeMap::new();
+    let mut files_scanned = 0;
+    for path in &files {
+        if scan_file(parser, &classifier, root, path, &mut found) {
+            files_scanned += 1;
+        }
+    }
+
+    let mut literals = Vec::new();
+    for ((kind, value), occurrences) in found {
+        if !options.kinds.is_empty() && !options.kinds.contains(&kind) {
+            continue;
+        }
+        let files: BTreeSet<&str> = occurrences.iter().map(|o| o.relative_path.as_str()).collect();
+        let languages: BTreeSet<&str> = occurrences.iter().map(|o| o.language.as_str()).collect();
+        let shared = if options.include_same_language {
+            files.len() > 1
+        } else {
+            languages.len() > 1
+        };
+        if !shared || files.len() > MAX_FILES_PER_LITERAL {
+            continue;
+        }
+        literals.push(SharedLiteral {
+            languages: languages.into_iter().map(|l| l.to_string()).collect(),
+            value,
+            kind,
+            occurrences,
+        });
+    }
+    // Literals spanning the most languages first
+    literals.sort_by(|a, b| b.languages.len().cmp(&a.languages.len()).then_with(|| a.value.cmp(&b.value)));
+
+    Ok(SharedLiteralsResult {
+        literals,
+        files_scanned,
+        edges_stored: 0,
+    })
+}
+
+/// Replaces the graph's SHARES_LITERAL edges with one per pair of files
+/// using a literal, skipping same-language pairs unless they were asked
+/// for.
+async fn store_edges(neo4j: &Neo4jState, literals: &[SharedLiteral], include_same_language: bool) -> Result<usize, String> {
+    let graph = neo4j.get_graph().await?;
+    graph
+        .run(query("MATCH ()-[r:SHARES_LITERAL]->() DELETE r"))
+        .await
+        .map_err(|e| format!("Failed to clear literal edges: {}", e))?;
+
+    let mut stored = 0;
+    for literal in literals {
+        let mut files: Vec<(&str, &str, &str)> = literal
+            .occurrences
+            .iter()
+            .map(|o| (o.path.as_str(), o.relative_path.as_str(), o.language.as_str()))
+            .collect();
+        files.sort();
+        files.dedup();
+        for (i, from) in files.iter().enumerate() {
+            for to in &files[i + 1..] {
+                if from.2 == to.2 && !include_same_language {
+                    continue;
+                }
+                graph
+                    .run(
+                        query(
+                            "MATCH (a:FILE) WHERE a.path IN [$from, $from_relative] \
+                             MATCH (b:FILE) WHERE b.path IN [$to, $to_relative] \
+                             CREATE (a)-[:SHARES_LITERAL {literal: $literal, kind: $kind}]->(b)",
+                        )
+                        .param("from", from.0.to_string())
+                        .param("from_relative", from.1.to_string())
+                        .param("to", to.0.to_string())
+                        .param("to_relative", to.1.to_string())
+                        .param("literal", literal.value.clone())
+                        .param("kind", literal.kind.clone()),
+                    )
+                    .await
+                    .map_err(|e| format!("Failed to store literal edge for {}: {}", literal.value, e))?;
+                stored += 1;
+            }
+        }
+    }
+    Ok(stored)
+}
+
+// ============================================================================
+// LITERAL TAURI COMMANDS
+// ============================================================================
+
+/// String literals (routes, URLs, environment variable names, translation
+/// keys and SQL table names) that appear in files of more than one
+/// language under `root`, such as an API path used by both the frontend
+/// and the backend.
+#[tauri::command]
+pub async fn find_shared_literals(
+    app: AppHandle,
+    root: String,
+    options: Option<LiteralOptions>,
+    workspace: State<'_, WorkspaceState>,
+    neo4j: State<'_, Neo4jState>,
+) -> Result<SharedLiteralsResult, String> {
+    let root_path = workspace.check(&root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+    let options = options.unwrap_or_default();
+    let (mut result, options) = tokio::task::spawn_blocking(move || {
+        let parser = app.state::<ParserState>();
+        find_shared(&parser, &root_path, &options).map(|result| (result, options))
+    })
+    .await
+    .map_err(|e| format!("Literal scan failed: {}", e))??;
+
+    if options.store_in_graph {
+        result.edges_stored = store_edges(&neo4j, &result.literals, options.include_same_language).await?;
+    }
+    Ok(result)
+}
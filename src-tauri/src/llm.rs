@@ -0,0 +1,769 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, State};
+
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+use crate::{ChatMessage, OllamaChatRequest, OllamaChatResponse, OLLAMA_URL};
+
+// ============================================================================
+// LLM STATE
+// ============================================================================
+
+/// Shared plumbing for every non-streaming LLM call made by the backend.
+pub struct LlmState {
+    app: AppHandle,
+    conn: Mutex<Connection>,
+    cache: Mutex<CacheCounters>,
+    logging: Mutex<LogSettings>,
+    retry: Mutex<RetryPolicy>,
+}
+
+/// How transient failures are retried. Each provider in the chain (the local
+/// Ollama first, then `fallback_urls` in order) gets `max_retries` retries
+/// with exponential backoff before the next one is tried.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    /// Base URLs of Ollama-compatible servers, e.g. `http://gpu-box:11434`
+    #[serde(default)]
+    pub fallback_urls: Vec<String>,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: 3,
+            initial_delay_ms: 500,
+            max_delay_ms: 8000,
+            fallback_urls: Vec::new(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct RetryEvent {
+    endpoint: String,
+    provider_url: String,
+    attempt: u32,
+    max_retries: u32,
+    delay_ms: u64,
+    error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct LogSettings {
+    pub enabled: bool,
+    /// Replace file contents in logged prompts with a placeholder: tool and
+    /// system messages, raw completion prompts and fenced code blocks
+    pub redact_content: bool,
+}
+
+#[derive(Default)]
+struct CacheCounters {
+    enabled: bool,
+    hits: u64,
+    misses: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelRoute {
+    pub task: String,
+    /// Candidate models in order of preference
+    pub models: Vec<String>,
+}
+
+/// Small, fast models for latency-sensitive work; larger ones where answer
+/// quality matters more than speed.
+const DEFAULT_ROUTES: &[(&str, &[&str])] = &[
+    ("completion", &["qwen2.5-coder:1.5b", "deepseek-coder:1.3b", "starcoder2:3b"]),
+    ("chat", &["qwen2.5-coder:14b", "llama3.1:8b", "qwen2.5-coder:7b"]),
+    ("review", &["qwen2.5-coder:14b", "llama3.1:8b"]),
+    ("commit_message", &["qwen2.5-coder:7b", "llama3.1:8b"]),
+    ("summarize", &["llama3.1:8b", "qwen2.5-coder:7b"]),
+    ("embedding", &["nomic-embed-text", "mxbai-embed-large", "all-minilm"]),
+];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LlmLogEntry {
+    pub id: i64,
+    pub timestamp: i64,
+    pub provider: String,
+    pub model: String,
+    pub endpoint: String,
+    pub prompt_tokens: Option<i64>,
+    pub completion_tokens: Option<i64>,
+    pub duration_ms: i64,
+    pub prompt_preview: String,
+    pub error: Option<String>,
+}
+
+/// One outgoing request as reported by a call site.
+pub(crate) struct LlmRequestRecord<'a> {
+    pub provider: &'a str,
+    pub model: &'a str,
+    pub endpoint: &'a str,
+    pub prompt: LoggedPrompt,
+    pub prompt_tokens: Option<u64>,
+    pub completion_tokens: Option<u64>,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+const LOG_PREVIEW_CHARS: usize = 500;
+
+#[derive(Debug, Serialize)]
+pub struct LlmCacheStats {
+    pub enabled: bool,
+    pub entries: i64,
+    pub total_bytes: i64,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+impl LlmState {
+    pub fn open(db_path: &Path, app: AppHandle) -> Result<Self, String> {
+        let conn = Connection::open(db_path)
+            .map_err(|e| format!("Failed to open LLM database: {}", e))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS settings (
+                 key TEXT PRIMARY KEY,
+                 value TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS response_cache (
+                 key TEXT PRIMARY KEY,
+                 model TEXT NOT NULL,
+                 response TEXT NOT NULL,
+                 created_at INTEGER NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS model_routes (
+                 task TEXT PRIMARY KEY,
+                 models TEXT NOT NULL
+             );
+             CREATE TABLE IF NOT EXISTS request_log (
+                 id INTEGER PRIMARY KEY AUTOINCREMENT,
+                 timestamp INTEGER NOT NULL,
+                 provider TEXT NOT NULL,
+                 model TEXT NOT NULL,
+                 endpoint TEXT NOT NULL,
+                 prompt_tokens INTEGER,
+                 completion_tokens INTEGER,
+                 duration_ms INTEGER NOT NULL,
+                 prompt_preview TEXT NOT NULL,
+                 error TEXT
+             );",
+        )
+        .map_err(|e| format!("Failed to initialize LLM database: {}", e))?;
+
+        for (task, models) in DEFAULT_ROUTES {
+            let models = serde_json::to_string(models).map_err(|e| e.to_string())?;
+            conn.execute(
+                "INSERT OR IGNORE INTO model_routes (task, models) VALUES (?1, ?2)",
+                params![task, models],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        let enabled = Self::read_setting(&conn, "cache_enabled")?.as_deref() == Some("true");
+        // Logging and redaction are on unless the user turned them off
+        let logging = LogSettings {
+            enabled: Self::read_setting(&conn, "log_enabled")?.as_deref() != Some("false"),
+            redact_content: Self::read_setting(&conn, "log_redact_content")?.as_deref() != Some("false"),
+        };
+
+        let retry = Self::read_setting(&conn, "retry_policy")?
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+
+        Ok(LlmState {
+            app,
+            conn: Mutex::new(conn),
+            cache: Mutex::new(CacheCounters {
+                enabled,
+                ..Default::default()
+            }),
+            logging: Mutex::new(logging),
+            retry: Mutex::new(retry),
+        })
+    }
+
+    fn read_setting(conn: &Connection, key: &str) -> Result<Option<String>, String> {
+        conn.query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+            .map_err(|e| e.to_string())
+    }
+
+    pub(crate) fn set_setting(&self, key: &str, value: &str) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        conn.execute(
+            "INSERT INTO settings (key, value) VALUES (?1, ?2)
+             ON CONFLICT(key) DO UPDATE SET value = ?2",
+            params![key, value],
+        )
+        .map_err(|e| e.to_string())?;
+        Ok(())
+    }
+
+    // ------------------------------------------------------------------------
+    // Response cache
+    // ------------------------------------------------------------------------
+
+    /// Content address of a request: the same provider, model, messages and
+    /// options always hash to the same key.
+    pub(crate) fn cache_key(provider: &str, model: &str, messages: &[ChatMessage], options: &serde_json::Value) -> String {
+        let payload = serde_json::json!({
+            "provider": provider,
+            "model": model,
+            "messages": messages,
+            "options": options,
+        });
+
+        let mut hasher = Sha256::new();
+        hasher.update(payload.to_string().as_bytes());
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    pub(crate) fn cache_get(&self, key: &str) -> Option<String> {
+        let mut counters = self.cache.lock_or_recover();
+        if !counters.enabled {
+            return None;
+        }
+
+        let conn = self.conn.lock_or_recover();
+        let cached: Option<String> = conn
+            .query_row("SELECT response FROM response_cache WHERE key = ?1", params![key], |row| row.get(0))
+            .optional()
+            .unwrap_or(None);
+
+        if cached.is_some() {
+            counters.hits += 1;
+        } else {
+            counters.misses += 1;
+        }
+        cached
+    }
+
+    pub(crate) fn cache_put(&self, key: &str, model: &str, response: &str) {
+        if !self.cache.lock_or_recover().enabled {
+            return;
+        }
+
+        let conn = self.conn.lock_or_recover();
+        if let Err(e) = conn.execute(
+            "INSERT OR REPLACE INTO response_cache (key, model, response, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![key, model, response, now_secs()],
+        ) {
+            eprintln!("Failed to cache LLM response: {}", e);
+        }
+    }
+}
+
+// ============================================================================
+// RETRY AND FALLBACK
+// ============================================================================
+
+fn is_transient_status(status: reqwest::StatusCode) -> bool {
+    status == reqwest::StatusCode::TOO_MANY_REQUESTS
+        || status == reqwest::StatusCode::REQUEST_TIMEOUT
+        || (status.is_server_error() && status != reqwest::StatusCode::NOT_IMPLEMENTED)
+}
+
+/// POSTs `body` to `endpoint` on each provider in turn, retrying connection
+/// errors and transient HTTP statuses with exponential backoff. Every retry is
+/// announced with a `chat-retry` event. Other HTTP errors fail immediately.
+pub(crate) async fn post_with_retry<T: Serialize + ?Sized>(
+    policy: &RetryPolicy,
+    app: Option<&AppHandle>,
+    endpoint: &str,
+    body: &T,
+) -> Result<reqwest::Response, String> {
+    let client = reqwest::Client::new();
+    let mut providers = vec![OLLAMA_URL.to_string()];
+    providers.extend(policy.fallback_urls.iter().map(|u| u.trim_end_matches('/').to_string()));
+
+    let mut last_error = String::new();
+
+    for provider in &providers {
+        let mut delay = policy.initial_delay_ms;
+
+        for attempt in 0..=policy.max_retries {
+            let error = match client.post(format!("{}{}", provider, endpoint)).json(body).send().await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) if is_transient_status(response.status()) => {
+                    format!("Ollama error: {}", response.status())
+                }
+                Ok(response) => return Err(format!("Ollama error: {}", response.status())),
+                Err(e) => format!("Failed to connect to Ollama: {}", e),
+            };
+            last_error = error.clone();
+
+            if attempt == policy.max_retries {
+                break;
+            }
+
+            if let Some(app) = app {
+                let _ = app.emit(
+                    "chat-retry",
+                    RetryEvent {
+                        endpoint: endpoint.to_string(),
+                        provider_url: provider.clone(),
+                        attempt: attempt + 1,
+                        max_retries: policy.max_retries,
+                        delay_ms: delay,
+                        error,
+                    },
+                );
+            }
+
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+            delay = (delay * 2).min(policy.max_delay_ms);
+        }
+    }
+
+    Err(last_error)
+}
+
+impl LlmState {
+    pub(crate) fn retry_policy(&self) -> RetryPolicy {
+        self.retry.lock_or_recover().clone()
+    }
+
+    pub(crate) fn app_handle(&self) -> AppHandle {
+        self.app.clone()
+    }
+
+    /// Sends an LLM request using the configured retry policy.
+    pub(crate) async fn post<T: Serialize + ?Sized>(&self, endpoint: &str, body: &T) -> Result<reqwest::Response, String> {
+        let policy = self.retry_policy();
+        post_with_retry(&policy, Some(&self.app), endpoint, body).await
+    }
+}
+
+// ============================================================================
+// REQUEST LOG
+// ============================================================================
+
+/// What a call site sent, kept in parts so redaction can treat each by its
+/// source.
+pub(crate) enum LoggedPrompt {
+    /// Chat messages as `(role, content)`
+    Messages(Vec<(String, String)>),
+    /// A raw completion prompt, which is mostly file contents
+    Raw(String),
+    /// A description written by the call site that holds no user content
+    Summary(String),
+}
+
+impl LoggedPrompt {
+    pub(crate) fn from_messages(messages: &[ChatMessage]) -> Self {
+        Self::Messages(messages.iter().map(|m| (m.role.clone(), m.content.clone())).collect())
+    }
+
+    /// Flattens the prompt into the text that is logged for a request.
+    fn render(self, redact: bool) -> String {
+        match self {
+            Self::Messages(messages) => messages
+                .into_iter()
+                .map(|(role, content)| {
+                    let content = if redact { redact_message(&role, &content) } else { content };
+                    format!("{}: {}", role, content)
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            Self::Raw(prompt) if redact => redacted_placeholder(prompt.len()),
+            Self::Raw(prompt) | Self::Summary(prompt) => prompt,
+        }
+    }
+}
+
+fn redacted_placeholder(chars: usize) -> String {
+    format!("[{} chars redacted]", chars)
+}
+
+/// Tool results and system prompts carry file contents and project context
+/// without any fencing, so only user and assistant text is kept.
+fn redact_message(role: &str, content: &str) -> String {
+    match role {
+        "user" | "assistant" => redact_code_blocks(content),
+        _ => redacted_placeholder(content.len()),
+    }
+}
+
+/// Replaces the body of every fenced code block, which is where file contents
+/// end up in prompts.
+fn redact_code_blocks(text: &str) -> String {
+    let mut output = Vec::new();
+    let mut in_block = false;
+    let mut redacted = 0;
+
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            if in_block {
+                output.push(redacted_placeholder(redacted));
+                redacted = 0;
+            }
+            in_block = !in_block;
+            output.push(line.to_string());
+        } else if in_block {
+            redacted += line.len() + 1;
+        } else {
+            output.push(line.to_string());
+        }
+    }
+    if in_block {
+        output.push(redacted_placeholder(redacted));
+    }
+
+    output.join("\n")
+}
+
+impl LlmState {
+    pub(crate) fn log_request(&self, record: LlmRequestRecord) {
+        let settings = *self.logging.lock_or_recover();
+        if !settings.enabled {
+            return;
+        }
+
+        let prompt = record.prompt.render(settings.redact_content);
+        let preview = crate::agent::truncate_output(prompt, LOG_PREVIEW_CHARS);
+
+        let conn = self.conn.lock_or_recover();
+        if let Err(e) = conn.execute(
+            "INSERT INTO request_log (timestamp, provider, model, endpoint, prompt_tokens, completion_tokens, duration_ms, prompt_preview, error)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                now_secs(),
+                record.provider,
+                record.model,
+                record.endpoint,
+                record.prompt_tokens.map(|t| t as i64),
+                record.completion_tokens.map(|t| t as i64),
+                record.duration_ms as i64,
+                preview,
+                record.error
+            ],
+        ) {
+            eprintln!("Failed to write LLM request log: {}", e);
+        }
+    }
+
+    fn log_entries(&self, limit: Option<usize>, offset: usize, model: Option<&str>) -> Result<Vec<LlmLogEntry>, String> {
+        let conn = self.conn.lock_or_recover();
+        let mut stmt = conn
+            .prepare(
+                "SELECT id, timestamp, provider, model, endpoint, prompt_tokens, completion_tokens, duration_ms, prompt_preview, error
+                 FROM request_log WHERE (?1 IS NULL OR model = ?1)
+                 ORDER BY id DESC LIMIT ?2 OFFSET ?3",
+            )
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map(params![model, limit.map(|l| l as i64).unwrap_or(-1), offset as i64], |row| {
+                Ok(LlmLogEntry {
+                    id: row.get(0)?,
+                    timestamp: row.get(1)?,
+                    provider: row.get(2)?,
+                    model: row.get(3)?,
+                    endpoint: row.get(4)?,
+                    prompt_tokens: row.get(5)?,
+                    completion_tokens: row.get(6)?,
+                    duration_ms: row.get(7)?,
+                    prompt_preview: row.get(8)?,
+                    error: row.get(9)?,
+                })
+            })
+            .map_err(|e| e.to_string())?;
+
+        rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+    }
+}
+
+// ============================================================================
+// MODEL ROUTING
+// ============================================================================
+
+/// `llama3.1` and `llama3.1:latest` name the same installed model.
+fn model_matches(installed: &str, wanted: &str) -> bool {
+    installed == wanted || installed.strip_suffix(":latest") == Some(wanted) || wanted.strip_suffix(":latest") == Some(installed)
+}
+
+impl LlmState {
+    fn routes(&self) -> Result<Vec<ModelRoute>, String> {
+        let conn = self.conn.lock_or_recover();
+        let mut stmt = conn
+            .prepare("SELECT task, models FROM model_routes ORDER BY task")
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map([], |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)))
+            .map_err(|e| e.to_string())?;
+
+        let mut routes = Vec::new();
+        for row in rows {
+            let (task, models) = row.map_err(|e| e.to_string())?;
+            routes.push(ModelRoute {
+                task,
+                models: serde_json::from_str(&models).unwrap_or_default(),
+            });
+        }
+        Ok(routes)
+    }
+
+    fn route(&self, task: &str) -> Result<Vec<String>, String> {
+        Ok(self
+            .routes()?
+            .into_iter()
+            .find(|r| r.task == task)
+            .map(|r| r.models)
+            .unwrap_or_default())
+    }
+
+    /// Picks the first routed model for `task` that is installed. Falls back to
+    /// the `chat` route and finally to any installed model.
+    pub(crate) async fn resolve_model(&self, task: &str) -> Result<String, String> {
+        let installed = crate::get_ollama_models().await?;
+
+        let mut candidates = self.route(task)?;
+        if task != "chat" {
+            candidates.extend(self.route("chat")?);
+        }
+
+        for wanted in &candidates {
+            if let Some(model) = installed.iter().find(|m| model_matches(m, wanted)) {
+                return Ok(model.clone());
+            }
+        }
+
+        installed
+            .into_iter()
+            .next()
+            .ok_or_else(|| "No Ollama models are installed".to_string())
+    }
+}
+
+// ============================================================================
+// COMPLETIONS
+// ============================================================================
+
+/// Non-streaming chat completion shared by backend features that need a
+/// single answer from the model.
+pub(crate) async fn complete_chat(llm: &LlmState, model: &str, messages: Vec<ChatMessage>) -> Result<String, String> {
+    let key = LlmState::cache_key("ollama", model, &messages, &serde_json::Value::Null);
+    if let Some(cached) = llm.cache_get(&key) {
+        return Ok(cached);
+    }
+
+    let prompt = LoggedPrompt::from_messages(&messages);
+    let started = Instant::now();
+
+    let request = OllamaChatRequest {
+        model: model.to_string(),
+        messages,
+        stream: false,
+    };
+
+    let result: Result<OllamaChatResponse, String> = async {
+        llm.post("/api/chat", &request)
+            .await?
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))
+    }
+    .await;
+
+    llm.log_request(LlmRequestRecord {
+        provider: "ollama",
+        model,
+        endpoint: "/api/chat",
+        prompt,
+        prompt_tokens: result.as_ref().ok().and_then(|r| r.prompt_eval_count),
+        completion_tokens: result.as_ref().ok().and_then(|r| r.eval_count),
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: result.as_ref().err().cloned(),
+    });
+
+    let chat_response = result?;
+    llm.cache_put(&key, model, &chat_response.message.content);
+    Ok(chat_response.message.content)
+}
+
+// ============================================================================
+// LLM TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn set_llm_cache_enabled(enabled: bool, state: State<'_, LlmState>) -> Result<(), String> {
+    state.set_setting("cache_enabled", if enabled { "true" } else { "false" })?;
+    state.cache.lock_or_recover().enabled = enabled;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_llm_cache_stats(state: State<'_, LlmState>) -> Result<LlmCacheStats, String> {
+    let (entries, total_bytes) = {
+        let conn = state.conn.lock_or_recover();
+        conn.query_row(
+            "SELECT COUNT(*), COALESCE(SUM(LENGTH(response)), 0) FROM response_cache",
+            [],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .map_err(|e| e.to_string())?
+    };
+
+    let counters = state.cache.lock_or_recover();
+    Ok(LlmCacheStats {
+        enabled: counters.enabled,
+        entries,
+        total_bytes,
+        hits: counters.hits,
+        misses: counters.misses,
+    })
+}
+
+#[tauri::command]
+pub fn clear_llm_cache(state: State<'_, LlmState>) -> Result<(), String> {
+    {
+        let conn = state.conn.lock_or_recover();
+        conn.execute("DELETE FROM response_cache", [])
+            .map_err(|e| format!("Failed to clear LLM cache: {}", e))?;
+    }
+
+    let mut counters = state.cache.lock_or_recover();
+    counters.hits = 0;
+    counters.misses = 0;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_model_routes(state: State<'_, LlmState>) -> Result<Vec<ModelRoute>, String> {
+    state.routes()
+}
+
+#[tauri::command]
+pub fn set_model_route(route: ModelRoute, state: State<'_, LlmState>) -> Result<(), String> {
+    let models = serde_json::to_string(&route.models).map_err(|e| e.to_string())?;
+    let conn = state.conn.lock_or_recover();
+    conn.execute(
+        "INSERT INTO model_routes (task, models) VALUES (?1, ?2)
+         ON CONFLICT(task) DO UPDATE SET models = ?2",
+        params![route.task, models],
+    )
+    .map_err(|e| format!("Failed to save model route: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn delete_model_route(task: String, state: State<'_, LlmState>) -> Result<(), String> {
+    let conn = state.conn.lock_or_recover();
+    conn.execute("DELETE FROM model_routes WHERE task = ?1", params![task])
+        .map_err(|e| format!("Failed to delete model route: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn resolve_model(task: String, state: State<'_, LlmState>) -> Result<String, String> {
+    state.resolve_model(&task).await
+}
+
+#[tauri::command]
+pub fn get_llm_logging_settings(state: State<'_, LlmState>) -> Result<LogSettings, String> {
+    Ok(*state.logging.lock_or_recover())
+}
+
+#[tauri::command]
+pub fn set_llm_logging_settings(settings: LogSettings, state: State<'_, LlmState>) -> Result<(), String> {
+    state.set_setting("log_enabled", if settings.enabled { "true" } else { "false" })?;
+    state.set_setting("log_redact_content", if settings.redact_content { "true" } else { "false" })?;
+    *state.logging.lock_or_recover() = settings;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_llm_request_log(
+    limit: Option<usize>,
+    offset: Option<usize>,
+    model: Option<String>,
+    state: State<'_, LlmState>,
+) -> Result<Vec<LlmLogEntry>, String> {
+    state.log_entries(Some(limit.unwrap_or(100)), offset.unwrap_or(0), model.as_deref())
+}
+
+/// Writes the full request log to `path`, which must be inside an approved
+/// workspace root, as pretty-printed JSON.
+#[tauri::command]
+pub fn export_llm_request_log(
+    path: String,
+    state: State<'_, LlmState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<usize, String> {
+    let path = workspace.check(&path)?;
+    let entries = state.log_entries(None, 0, None)?;
+    let json = serde_json::to_string_pretty(&entries).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to export log: {}", e))?;
+    Ok(entries.len())
+}
+
+#[tauri::command]
+pub fn clear_llm_request_log(state: State<'_, LlmState>) -> Result<(), String> {
+    let conn = state.conn.lock_or_recover();
+    conn.execute("DELETE FROM request_log", [])
+        .map_err(|e| format!("Failed to clear log: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn get_llm_retry_policy(state: State<'_, LlmState>) -> Result<RetryPolicy, String> {
+    Ok(state.retry_policy())
+}
+
+#[tauri::command]
+pub fn set_llm_retry_policy(policy: RetryPolicy, state: State<'_, LlmState>) -> Result<(), String> {
+    let json = serde_json::to_string(&policy).map_err(|e| e.to_string())?;
+    state.set_setting("retry_policy", &json)?;
+    *state.retry.lock_or_recover() = policy;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_only_the_body_of_code_blocks() {
+        let text = "Fix this:\n```rust\nlet secret = 1;\n```\nThanks";
+        assert_eq!(redact_code_blocks(text), "Fix this:\n```rust\n[16 chars redacted]\n```\nThanks");
+        // An unclosed block is redacted up to the end
+        assert_eq!(redact_code_blocks("```\nabc"), "```\n[4 chars redacted]");
+    }
+
+    #[test]
+    fn redacted_prompts_keep_only_user_and_assistant_text() {
+        let prompt = LoggedPrompt::Messages(vec![
+            ("system".to_string(), "Project context".to_string()),
+            ("user".to_string(), "Why?".to_string()),
+            ("tool".to_string(), "file contents".to_string()),
+        ]);
+        assert_eq!(prompt.render(true), "system: [15 chars redacted]\nuser: Why?\ntool: [13 chars redacted]");
+        assert_eq!(LoggedPrompt::Raw("fn main() {}".to_string()).render(true), "[12 chars redacted]");
+        assert_eq!(LoggedPrompt::Summary("3 chunks".to_string()).render(true), "3 chunks");
+        assert_eq!(LoggedPrompt::Raw("fn main() {}".to_string()).render(false), "fn main() {}");
+    }
+}
@@ -0,0 +1,1017 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, ChildStdout, Command};
+use tokio::sync::oneshot;
+
+use crate::diagnostics::{from_lsp, DiagnosticsState};
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+use crate::ParserState;
+
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+const SHUTDOWN_TIMEOUT_SECS: u64 = 2;
+
+/// LSP CompletionItemKind, 1-based.
+const COMPLETION_KINDS: &[&str] = &[
+    "text", "method", "function", "constructor", "field", "variable", "class", "interface", "module", "property",
+    "unit", "value", "enum", "keyword", "snippet", "color", "file", "reference", "folder", "enum_member",
+    "constant", "struct", "event", "operator", "type_parameter",
+];
+
+// ============================================================================
+// LSP STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LspServerConfig {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Parser languages the server handles, e.g. "rust" or "tsx".
+    pub languages: Vec<String>,
+    /// Files marking a project root; the nearest directory holding one
+    /// becomes the server's root.
+    #[serde(default)]
+    pub root_markers: Vec<String>,
+    #[serde(default)]
+    pub initialization_options: Option<Value>,
+}
+
+/// 0-based line, with `character` in UTF-16 code units as in the editor.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct LspPosition {
+    pub line: u32,
+    pub character: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy)]
+pub struct LspRange {
+    pub start: LspPosition,
+    pub end: LspPosition,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LspDiagnostic {
+    pub range: LspRange,
+    /// "error", "warning", "information" or "hint".
+    pub severity: String,
+    pub code: Option<String>,
+    pub source: Option<String>,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LspHover {
+    /// Markdown.
+    pub contents: String,
+    pub range: Option<LspRange>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LspCompletionItem {
+    pub label: String,
+    pub kind: Option<String>,
+    pub detail: Option<String>,
+    /// Markdown.
+    pub documentation: Option<String>,
+    pub insert_text: String,
+    /// `insert_text` uses snippet syntax ($1, ${2:name}).
+    pub is_snippet: bool,
+    /// Range replaced by `insert_text`, when the server gives one.
+    pub range: Option<LspRange>,
+    pub sort_text: Option<String>,
+    pub filter_text: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LspCompletionList {
+    /// More typing should re-request rather than filter these items.
+    pub is_incomplete: bool,
+    pub items: Vec<LspCompletionItem>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LspLocation {
+    pub path: String,
+    pub range: LspRange,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct LspServerStatus {
+    pub server_id: String,
+    pub root: String,
+    pub state: String, // "starting", "running", "exited", "stopped"
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub open_documents: usize,
+    /// The server's `ServerCapabilities`, as sent on initialize.
+    pub capabilities: Value,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct LspDiagnosticsEvent {
+    server_id: String,
+    path: String,
+    diagnostics: Vec<LspDiagnostic>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct LspLogEvent {
+    server_id: String,
+    level: &'static str, // "error", "warning", "info", "log", "stderr"
+    message: String,
+}
+
+type PendingRequests = HashMap<i64, oneshot::Sender<Result<Value, String>>>;
+
+struct LspServer {
+    server_id: String,
+    root: PathBuf,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    child: Mutex<Child>,
+    next_id: AtomicI64,
+    pending: Mutex<PendingRequests>,
+    /// Open documents by URI, with their version.
+    documents: Mutex<HashMap<String, i32>>,
+    /// Last published diagnostics by path.
+    diagnostics: Mutex<HashMap<String, Vec<LspDiagnostic>>>,
+    status: Mutex<LspServerStatus>,
+}
+
+/// Running language servers, one per server and project root, and the
+/// server configurations by id.
+pub struct LspState {
+    servers: tokio::sync::Mutex<HashMap<String, Arc<LspServer>>>,
+    configs: Mutex<HashMap<String, LspServerConfig>>,
+}
+
+fn default_configs() -> HashMap<String, LspServerConfig> {
+    let config = |program: &str, args: &[&str], languages: &[&str], root_markers: &[&str]| LspServerConfig {
+        program: program.to_string(),
+        args: args.iter().map(|a| a.to_string()).collect(),
+        languages: languages.iter().map(|l| l.to_string()).collect(),
+        root_markers: root_markers.iter().map(|m| m.to_string()).collect(),
+        initialization_options: None,
+    };
+    HashMap::from([
+        ("rust-analyzer".to_string(), config("rust-analyzer", &[], &["rust"], &["Cargo.toml"])),
+        (
+            "typescript".to_string(),
+            config(
+                "typescript-language-server",
+                &["--stdio"],
+                &["typescript", "tsx", "javascript"],
+                &["tsconfig.json", "jsconfig.json", "package.json"],
+            ),
+        ),
+        (
+            "pyright".to_string(),
+            config(
+                "pyright-langserver",
+                &["--stdio"],
+                &["python"],
+                &["pyrightconfig.json", "pyproject.toml", "setup.py", "setup.cfg", "requirements.txt"],
+            ),
+        ),
+    ])
+}
+
+impl Default for LspState {
+    fn default() -> Self {
+        LspState {
+            servers: tokio::sync::Mutex::new(HashMap::new()),
+            configs: Mutex::new(default_configs()),
+        }
+    }
+}
+
+// ============================================================================
+// JSON-RPC TRANSPORT
+// ============================================================================
+
+fn path_to_uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::from("file://");
+    // Windows drive paths become file:///C:/...
+    if !path.starts_with('/') {
+        uri.push('/');
+    }
+    for byte in path.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' | b':' => uri.push(byte as char),
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    uri
+}
+
+fn uri_to_path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?;
+    let bytes = encoded.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = if bytes[i] == b'%' {
+            encoded.get(i + 1..i + 3).and_then(|hex| u8::from_str_radix(hex, 16).ok())
+        } else {
+            None
+        };
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    let path = String::from_utf8(decoded).ok()?;
+    let path = match path.as_bytes() {
+        [b'/', _, b':', ..] => path[1..].to_string(),
+        _ => path,
+    };
+    Some(PathBuf::from(path))
+}
+
+/// Reads one `Content-Length` framed message; `None` at end of stream.
+pub(crate) async fn read_message<R: AsyncBufRead + Unpin>(reader: &mut R) -> Result<Option<Value>, String> {
+    let mut length = None;
+    let mut line = String::new();
+    loop {
+        line.clear();
+        if reader.read_line(&mut line).await.map_err(|e| format!("Failed to read header: {}", e))? == 0 {
+            return Ok(None);
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+            continue;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                length = value.trim().parse::<usize>().ok();
+            }
+        }
+    }
+
+    let mut body = vec![0; length.unwrap_or_default()];
+    reader
+        .read_exact(&mut body)
+        .await
+        .map_err(|e| format!("Failed to read message: {}", e))?;
+    serde_json::from_slice(&body)
+        .map(Some)
+        .map_err(|e| format!("Invalid message: {}", e))
+}
+
+impl LspServer {
+    async fn send(&self, message: Value) -> Result<(), String> {
+        let body = message.to_string();
+        let mut stdin = self.stdin.lock().await;
+        stdin
+            .write_all(format!("Content-Length: {}\r\n\r\n{}", body.len(), body).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to {}: {}", self.server_id, e))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to write to {}: {}", self.server_id, e))
+    }
+
+    async fn notify(&self, method: &str, params: Value) -> Result<(), String> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method, "params": params })).await
+    }
+
+    async fn request_with_timeout(&self, method: &str, params: Value, timeout: Duration) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock_or_recover().insert(id, sender);
+
+        let message = json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params });
+        if let Err(e) = self.send(message).await {
+            self.pending.lock_or_recover().remove(&id);
+            return Err(e);
+        }
+        match tokio::time::timeout(timeout, receiver).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(format!("Language server {} exited", self.server_id)),
+            Err(_) => {
+                self.pending.lock_or_recover().remove(&id);
+                let _ = self.notify("$/cancelRequest", json!({ "id": id })).await;
+                Err(format!("{} timed out after {}s", method, timeout.as_secs()))
+            }
+        }
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        self.request_with_timeout(method, params, Duration::from_secs(REQUEST_TIMEOUT_SECS)).await
+    }
+
+    /// Answers the requests servers send to clients. Configuration is
+    /// left to the server's defaults.
+    async fn answer(&self, id: Value, method: &str, params: Option<&Value>) {
+        let response = match method {
+            "workspace/configuration" => {
+                let items = params.and_then(|p| p.get("items")).and_then(|i| i.as_array()).map_or(0, |i| i.len());
+                json!({ "jsonrpc": "2.0", "id": id, "result": vec![Value::Null; items] })
+            }
+            "workspace/workspaceFolders" => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "result": [{ "uri": path_to_uri(&self.root), "name": self.root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default() }],
+            }),
+            "client/registerCapability"
+            | "client/unregisterCapability"
+            | "window/workDoneProgress/create"
+            | "window/showMessageRequest"
+            | "workspace/diagnostic/refresh"
+            | "workspace/semanticTokens/refresh"
+            | "workspace/inlayHint/refresh" => json!({ "jsonrpc": "2.0", "id": id, "result": null }),
+            _ => json!({
+                "jsonrpc": "2.0",
+                "id": id,
+                "error": { "code": -32601, "message": format!("Unsupported request: {}", method) },
+            }),
+        };
+        let _ = self.send(response).await;
+    }
+
+    fn snapshot(&self) -> LspServerStatus {
+        let mut status = self.status.lock_or_recover().clone();
+        status.open_documents = self.documents.lock_or_recover().len();
+        status
+    }
+
+    fn set_state(&self, app: &AppHandle, update: impl FnOnce(&mut LspServerStatus)) {
+        update(&mut self.status.lock_or_recover());
+        let _ = app.emit("lsp-server-status", self.snapshot());
+    }
+}
+
+fn parse_diagnostic(value: &Value) -> Option<LspDiagnostic> {
+    Some(LspDiagnostic {
+        range: serde_json::from_value(value.get("range")?.clone()).ok()?,
+        severity: match value.get("severity").and_then(|s| s.as_u64()) {
+            Some(2) => "warning",
+            Some(3) => "information",
+            Some(4) => "hint",
+            _ => "error",
+        }
+        .to_string(),
+        code: value.get("code").filter(|c| !c.is_null()).map(|code| match code {
+            Value::String(code) => code.clone(),
+            other => other.to_string(),
+        }),
+        source: value.get("source").and_then(|s| s.as_str()).map(|s| s.to_string()),
+        message: value.get("message")?.as_str()?.to_string(),
+    })
+}
+
+fn publish_diagnostics(app: &AppHandle, server_id: &str, path: &str, diagnostics: &[LspDiagnostic]) {
+    let converted = diagnostics.iter().map(|d| from_lsp(d.clone(), server_id)).collect();
+    app.state::<DiagnosticsState>()
+        .publish(app, path, &format!("lsp:{}", server_id), converted);
+}
+
+fn handle_notification(app: &AppHandle, server: &LspServer, method: &str, params: Option<&Value>) {
+    let Some(params) = params else {
+        return;
+    };
+    match method {
+        "textDocument/publishDiagnostics" => {
+            let Some(path) = params.get("uri").and_then(|u| u.as_str()).and_then(uri_to_path) else {
+                return;
+            };
+            let path = path.to_string_lossy().to_string();
+            let diagnostics: Vec<LspDiagnostic> = params
+                .get("diagnostics")
+                .and_then(|d| d.as_array())
+                .map(|d| d.iter().filter_map(parse_diagnostic).collect())
+                .unwrap_or_default();
+            server.diagnostics.lock_or_recover().insert(path.clone(), diagnostics.clone());
+            publish_diagnostics(app, &server.server_id, &path, &diagnostics);
+            let _ = app.emit(
+                "lsp-diagnostics",
+                LspDiagnosticsEvent {
+                    server_id: server.server_id.clone(),
+                    path,
+                    diagnostics,
+                },
+            );
+        }
+        "window/logMessage" | "window/showMessage" => {
+            let level = match params.get("type").and_then(|t| t.as_u64()) {
+                Some(1) => "error",
+                Some(2) => "warning",
+                Some(3) => "info",
+                _ => "log",
+            };
+            let _ = app.emit(
+                "lsp-log",
+                LspLogEvent {
+                    server_id: server.server_id.clone(),
+                    level,
+                    message: params.get("message").and_then(|m| m.as_str()).unwrap_or_default().to_string(),
+                },
+            );
+        }
+        _ => {}
+    }
+}
+
+/// Dispatches everything the server writes until it exits, then fails
+/// the requests still waiting for an answer.
+async fn read_loop(app: AppHandle, server: Arc<LspServer>, stdout: ChildStdout) {
+    let mut reader = BufReader::new(stdout);
+    loop {
+        let message = match read_message(&mut reader).await {
+            Ok(Some(message)) => message,
+            Ok(None) => break,
+            Err(e) => {
+                eprintln!("Language server {}: {}", server.server_id, e);
+                break;
+            }
+        };
+        let method = message.get("method").and_then(|m| m.as_str());
+        match (method, message.get("id")) {
+            (Some(method), Some(id)) => server.answer(id.clone(), method, message.get("params")).await,
+            (Some(method), None) => handle_notification(&app, &server, method, message.get("params")),
+            (None, Some(id)) => {
+                let Some(sender) = id.as_i64().and_then(|id| server.pending.lock_or_recover().remove(&id)) else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(error
+                        .get("message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("Request failed")
+                        .to_string()),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(result);
+            }
+            _ => {}
+        }
+    }
+
+    server.pending.lock_or_recover().clear();
+    app.state::<DiagnosticsState>()
+        .clear_provider(&app, &format!("lsp:{}", server.server_id));
+    let exit_code = server.child.lock_or_recover().try_wait().ok().flatten().and_then(|s| s.code());
+    server.set_state(&app, |s| {
+        if s.state != "stopped" {
+            s.state = "exited".to_string();
+        }
+        s.pid = None;
+        s.exit_code = exit_code;
+    });
+}
+
+async fn forward_stderr(app: AppHandle, server_id: String, stderr: tokio::process::ChildStderr) {
+    let mut lines = BufReader::new(stderr).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let _ = app.emit(
+            "lsp-log",
+            LspLogEvent {
+                server_id: server_id.clone(),
+                level: "stderr",
+                message: line,
+            },
+        );
+    }
+}
+
+// ============================================================================
+// SERVER LIFECYCLE
+// ============================================================================
+
+fn client_capabilities() -> Value {
+    json!({
+        "general": { "positionEncodings": ["utf-16"] },
+        "textDocument": {
+            "synchronization": { "didSave": true, "dynamicRegistration": false },
+            "hover": { "contentFormat": ["markdown", "plaintext"] },
+            "completion": {
+                "completionItem": {
+                    "snippetSupport": true,
+                    "documentationFormat": ["markdown", "plaintext"],
+                },
+                "contextSupport": true,
+            },
+            "definition": { "linkSupport": true },
+            "publishDiagnostics": { "relatedInformation": false, "versionSupport": true },
+            "diagnostic": { "dynamicRegistration": false },
+        },
+        "workspace": { "configuration": true, "workspaceFolders": true },
+        "window": { "workDoneProgress": false },
+    })
+}
+
+async fn spawn_server(
+    app: &AppHandle,
+    server_id: &str,
+    config: &LspServerConfig,
+    root: &Path,
+) -> Result<Arc<LspServer>, String> {
+    let mut child = Command::new(&config.program)
+        .args(&config.args)
+        .current_dir(root)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {}", config.program, e))?;
+
+    let stdin = child.stdin.take().ok_or("Failed to capture stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    let pid = child.id();
+
+    let server = Arc::new(LspServer {
+        server_id: server_id.to_string(),
+        root: root.to_path_buf(),
+        stdin: tokio::sync::Mutex::new(stdin),
+        child: Mutex::new(child),
+        next_id: AtomicI64::new(1),
+        pending: Mutex::new(HashMap::new()),
+        documents: Mutex::new(HashMap::new()),
+        diagnostics: Mutex::new(HashMap::new()),
+        status: Mutex::new(LspServerStatus {
+            server_id: server_id.to_string(),
+            root: root.to_string_lossy().to_string(),
+            state: "starting".to_string(),
+            pid,
+            exit_code: None,
+            open_documents: 0,
+            capabilities: Value::Null,
+        }),
+    });
+    tokio::spawn(read_loop(app.clone(), server.clone(), stdout));
+    tokio::spawn(forward_stderr(app.clone(), server_id.to_string(), stderr));
+    let _ = app.emit("lsp-server-status", server.snapshot());
+
+    let root_uri = path_to_uri(root);
+    let params = json!({
+        "processId": std::process::id(),
+        "clientInfo": { "name": "GenCode" },
+        "rootUri": root_uri,
+        "rootPath": root.to_string_lossy(),
+        "workspaceFolders": [{ "uri": root_uri, "name": root.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default() }],
+        "capabilities": client_capabilities(),
+        "initializationOptions": config.initialization_options,
+    });
+    let initialized = match server.request("initialize", params).await {
+        Ok(result) => server.notify("initialized", json!({})).await.map(|_| result),
+        Err(e) => Err(e),
+    };
+    let result = match initialized {
+        Ok(result) => result,
+        Err(e) => {
+            let _ = server.child.lock_or_recover().start_kill();
+            return Err(format!("Failed to initialize {}: {}", server_id, e));
+        }
+    };
+
+    server.set_state(app, |s| {
+        s.state = "running".to_string();
+        s.capabilities = result.get("capabilities").cloned().unwrap_or(Value::Null);
+    });
+    Ok(server)
+}
+
+/// Asks the server to shut down, then kills it if it's still running.
+async fn shutdown_server(app: &AppHandle, server: &LspServer) {
+    server.set_state(app, |s| s.state = "stopped".to_string());
+    let timeout = Duration::from_secs(SHUTDOWN_TIMEOUT_SECS);
+    if server.request_with_timeout("shutdown", Value::Null, timeout).await.is_ok() {
+        let _ = server.notify("exit", Value::Null).await;
+    }
+    let _ = server.child.lock_or_recover().start_kill();
+}
+
+fn server_key(server_id: &str, root: &Path) -> String {
+    format!("{}@{}", server_id, root.display())
+}
+
+/// The nearest ancestor of `path` holding one of `markers`, else the
+/// file's directory.
+fn find_root(path: &Path, markers: &[String]) -> PathBuf {
+    let dir = path.parent().unwrap_or(path);
+    dir.ancestors()
+        .find(|ancestor| markers.iter().any(|m| ancestor.join(m).exists()))
+        .unwrap_or(dir)
+        .to_path_buf()
+}
+
+/// LSP language ids differ from parser names for React files.
+fn language_id(language: &str, path: &Path) -> String {
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+    match (language, extension.as_deref()) {
+        ("tsx", _) => "typescriptreact".to_string(),
+        ("javascript", Some("jsx")) => "javascriptreact".to_string(),
+        _ => language.to_string(),
+    }
+}
+
+impl LspState {
+    /// Kills every language server; used on app exit, when there's no time
+    /// for the shutdown handshake.
+    pub fn shutdown(&self) {
+        for (_, server) in self.servers.blocking_lock().drain() {
+            let _ = server.child.lock_or_recover().start_kill();
+        }
+    }
+
+    /// The running server for `(server_id, root)`, starting it if needed.
+    async fn ensure_server(&self, app: &AppHandle, server_id: &str, root: &Path) -> Result<Arc<LspServer>, String> {
+        let config = self
+            .configs
+            .lock_or_recover()
+            .get(server_id)
+            .cloned()
+            .ok_or_else(|| format!("Unknown language server: {}", server_id))?;
+
+        let key = server_key(server_id, root);
+        let mut servers = self.servers.lock().await;
+        if let Some(server) = servers.get(&key) {
+            if server.status.lock_or_recover().state == "running" {
+                return Ok(server.clone());
+            }
+        }
+        let server = spawn_server(app, server_id, &config, root).await?;
+        servers.insert(key, server.clone());
+        Ok(server)
+    }
+
+    /// The server that has `path` open.
+    async fn server_for(&self, path: &Path) -> Result<Arc<LspServer>, String> {
+        let uri = path_to_uri(path);
+        let servers = self.servers.lock().await;
+        servers
+            .values()
+            .find(|s| s.documents.lock_or_recover().contains_key(&uri))
+            .cloned()
+            .ok_or_else(|| format!("No language server has {} open; call lsp_did_open first", path.display()))
+    }
+}
+
+fn position_params(path: &Path, line: u32, col: u32) -> Value {
+    json!({
+        "textDocument": { "uri": path_to_uri(path) },
+        "position": { "line": line, "character": col },
+    })
+}
+
+fn markup_text(value: &Value) -> String {
+    match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .map(markup_text)
+            .filter(|t| !t.is_empty())
+            .collect::<Vec<_>>()
+            .join("\n\n"),
+        Value::Object(object) => match (
+            object.get("language").and_then(|l| l.as_str()),
+            object.get("value").and_then(|v| v.as_str()),
+        ) {
+            (Some(language), Some(code)) => format!("```{}\n{}\n```", language, code),
+            (None, Some(text)) => text.to_string(),
+            _ => String::new(),
+        },
+        _ => String::new(),
+    }
+}
+
+fn parse_completion(item: &Value) -> Option<LspCompletionItem> {
+    let label = item.get("label")?.as_str()?.to_string();
+    let text = |key: &str| item.get(key).and_then(|v| v.as_str()).map(|v| v.to_string());
+    let edit = item.get("textEdit");
+    // InsertReplaceEdit carries `insert` and `replace` instead of `range`
+    let range = edit
+        .and_then(|e| e.get("range").or_else(|| e.get("replace")))
+        .and_then(|r| serde_json::from_value(r.clone()).ok());
+    let insert_text = edit
+        .and_then(|e| e.get("newText"))
+        .and_then(|t| t.as_str())
+        .map(|t| t.to_string())
+        .or_else(|| text("insertText"))
+        .unwrap_or_else(|| label.clone());
+    Some(LspCompletionItem {
+        kind: item
+            .get("kind")
+            .and_then(|k| k.as_u64())
+            .and_then(|k| COMPLETION_KINDS.get((k as usize).wrapping_sub(1)))
+            .map(|k| k.to_string()),
+        detail: text("detail"),
+        documentation: item.get("documentation").map(markup_text).filter(|d| !d.is_empty()),
+        insert_text,
+        is_snippet: item.get("insertTextFormat").and_then(|f| f.as_u64()) == Some(2),
+        range,
+        sort_text: text("sortText"),
+        filter_text: text("filterText"),
+        label,
+    })
+}
+
+/// Location, Location[] and LocationLink[] all become locations.
+fn parse_locations(value: &Value) -> Vec<LspLocation> {
+    let items = match value {
+        Value::Array(items) => items.iter().collect(),
+        Value::Object(_) => vec![value],
+        _ => Vec::new(),
+    };
+    items
+        .into_iter()
+        .filter_map(|item| {
+            let uri = item.get("uri").or_else(|| item.get("targetUri"))?.as_str()?;
+            let range = item
+                .get("range")
+                .or_else(|| item.get("targetSelectionRange"))
+                .or_else(|| item.get("targetRange"))?;
+            Some(LspLocation {
+                path: uri_to_path(uri)?.to_string_lossy().to_string(),
+                range: serde_json::from_value(range.clone()).ok()?,
+            })
+        })
+        .collect()
+}
+
+// ============================================================================
+// LSP TAURI COMMANDS
+// ============================================================================
+
+/// Built-in and user-configured language servers by id.
+#[tauri::command]
+pub fn lsp_get_server_configs(state: State<'_, LspState>) -> HashMap<String, LspServerConfig> {
+    state.configs.lock_or_recover().clone()
+}
+
+/// Adds or replaces a server configuration. Running servers keep their
+/// old configuration until restarted.
+#[tauri::command]
+pub fn lsp_configure_server(server_id: String, config: LspServerConfig, state: State<'_, LspState>) {
+    state.configs.lock_or_recover().insert(server_id, config);
+}
+
+/// Starts `server_id` for the project at `root`, or returns the running
+/// one. State changes arrive as `lsp-server-status` events and server
+/// messages as `lsp-log`.
+#[tauri::command]
+pub async fn lsp_start_server(
+    app: AppHandle,
+    server_id: String,
+    root: String,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<LspServerStatus, String> {
+    let root = workspace.check(&root)?;
+    let server = state.ensure_server(&app, &server_id, &root).await?;
+    Ok(server.snapshot())
+}
+
+#[tauri::command]
+pub async fn lsp_stop_server(
+    app: AppHandle,
+    server_id: String,
+    root: String,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<(), String> {
+    let root = workspace.check(&root)?;
+    let server = state
+        .servers
+        .lock()
+        .await
+        .remove(&server_key(&server_id, &root))
+        .ok_or_else(|| format!("Language server not running: {} for {}", server_id, root.display()))?;
+    shutdown_server(&app, &server).await;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn lsp_list_servers(state: State<'_, LspState>) -> Result<Vec<LspServerStatus>, String> {
+    let servers = state.servers.lock().await;
+    let mut statuses: Vec<LspServerStatus> = servers.values().map(|s| s.snapshot()).collect();
+    statuses.sort_by(|a, b| a.server_id.cmp(&b.server_id).then_with(|| a.root.cmp(&b.root)));
+    Ok(statuses)
+}
+
+/// Opens a document in the language server for its language, starting
+/// the server at the nearest project root if needed. Returns the
+/// server's status, or `None` if no server handles the language.
+#[tauri::command]
+pub async fn lsp_did_open(
+    app: AppHandle,
+    path: String,
+    content: String,
+    language: Option<String>,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Option<LspServerStatus>, String> {
+    let resolved = workspace.check(&path)?;
+    let Some(language) = language.or_else(|| app.state::<ParserState>().detect_language(&path)) else {
+        return Ok(None);
+    };
+    let found = state
+        .configs
+        .lock_or_recover()
+        .iter()
+        .filter(|(_, config)| config.languages.contains(&language))
+        .min_by(|a, b| a.0.cmp(b.0))
+        .map(|(id, config)| (id.clone(), config.root_markers.clone()));
+    let Some((server_id, markers)) = found else {
+        return Ok(None);
+    };
+
+    // Stay inside the sandbox even if a marker sits above it
+    let root = find_root(&resolved, &markers);
+    let root = workspace
+        .check(&root)
+        .unwrap_or_else(|_| resolved.parent().unwrap_or(&resolved).to_path_buf());
+    let server = state.ensure_server(&app, &server_id, &root).await?;
+
+    let uri = path_to_uri(&resolved);
+    server.documents.lock_or_recover().insert(uri.clone(), 1);
+    server
+        .notify(
+            "textDocument/didOpen",
+            json!({
+                "textDocument": {
+                    "uri": uri,
+                    "languageId": language_id(&language, &resolved),
+                    "version": 1,
+                    "text": content,
+                },
+            }),
+        )
+        .await?;
+    Ok(Some(server.snapshot()))
+}
+
+/// Sends the full new content of an open document.
+#[tauri::command]
+pub async fn lsp_did_change(
+    path: String,
+    content: String,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<(), String> {
+    let resolved = workspace.check(&path)?;
+    let server = state.server_for(&resolved).await?;
+    let uri = path_to_uri(&resolved);
+    let version = {
+        let mut documents = server.documents.lock_or_recover();
+        let version = documents.entry(uri.clone()).or_insert(1);
+        *version += 1;
+        *version
+    };
+    server
+        .notify(
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": uri, "version": version },
+                "contentChanges": [{ "text": content }],
+            }),
+        )
+        .await
+}
+
+#[tauri::command]
+pub async fn lsp_did_save(
+    path: String,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<(), String> {
+    let resolved = workspace.check(&path)?;
+    let server = state.server_for(&resolved).await?;
+    server
+        .notify("textDocument/didSave", json!({ "textDocument": { "uri": path_to_uri(&resolved) } }))
+        .await
+}
+
+#[tauri::command]
+pub async fn lsp_did_close(
+    path: String,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<(), String> {
+    let resolved = workspace.check(&path)?;
+    let server = state.server_for(&resolved).await?;
+    let uri = path_to_uri(&resolved);
+    server.documents.lock_or_recover().remove(&uri);
+    server
+        .notify("textDocument/didClose", json!({ "textDocument": { "uri": uri } }))
+        .await
+}
+
+/// Hover information at a 0-based `line`/`col` of an open document.
+#[tauri::command]
+pub async fn lsp_hover(
+    path: String,
+    line: u32,
+    col: u32,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Option<LspHover>, String> {
+    let resolved = workspace.check(&path)?;
+    let server = state.server_for(&resolved).await?;
+    let result = server.request("textDocument/hover", position_params(&resolved, line, col)).await?;
+    let Some(contents) = result.get("contents").map(markup_text).filter(|c| !c.is_empty()) else {
+        return Ok(None);
+    };
+    Ok(Some(LspHover {
+        contents,
+        range: result.get("range").and_then(|r| serde_json::from_value(r.clone()).ok()),
+    }))
+}
+
+#[tauri::command]
+pub async fn lsp_completion(
+    path: String,
+    line: u32,
+    col: u32,
+    trigger_character: Option<String>,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<LspCompletionList, String> {
+    let resolved = workspace.check(&path)?;
+    let server = state.server_for(&resolved).await?;
+    let mut params = position_params(&resolved, line, col);
+    params["context"] = match trigger_character {
+        Some(character) => json!({ "triggerKind": 2, "triggerCharacter": character }),
+        None => json!({ "triggerKind": 1 }),
+    };
+    let result = server.request("textDocument/completion", params).await?;
+
+    let (items, is_incomplete) = match &result {
+        Value::Array(items) => (items.as_slice(), false),
+        Value::Object(list) => (
+            list.get("items").and_then(|i| i.as_array()).map_or(&[][..], |i| i.as_slice()),
+            list.get("isIncomplete").and_then(|i| i.as_bool()).unwrap_or(false),
+        ),
+        _ => (&[][..], false),
+    };
+    Ok(LspCompletionList {
+        is_incomplete,
+        items: items.iter().filter_map(parse_completion).collect(),
+    })
+}
+
+#[tauri::command]
+pub async fn lsp_definition(
+    path: String,
+    line: u32,
+    col: u32,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<LspLocation>, String> {
+    let resolved = workspace.check(&path)?;
+    let server = state.server_for(&resolved).await?;
+    let result = server
+        .request("textDocument/definition", position_params(&resolved, line, col))
+        .await?;
+    Ok(parse_locations(&result))
+}
+
+/// Diagnostics of an open document: pulled from servers that support
+/// `textDocument/diagnostic`, otherwise the last ones the server
+/// published (also sent as `lsp-diagnostics` events).
+#[tauri::command]
+pub async fn lsp_diagnostics(
+    app: AppHandle,
+    path: String,
+    state: State<'_, LspState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<LspDiagnostic>, String> {
+    let resolved = workspace.check(&path)?;
+    let server = state.server_for(&resolved).await?;
+    let key = resolved.to_string_lossy().to_string();
+
+    let pull = server.status.lock_or_recover().capabilities.get("diagnosticProvider").is_some();
+    if pull {
+        let params = json!({ "textDocument": { "uri": path_to_uri(&resolved) } });
+        let report = server.request("textDocument/diagnostic", params).await?;
+        // An "unchanged" report means the published ones still hold
+        if let Some(items) = report.get("items").and_then(|i| i.as_array()) {
+            let diagnostics: Vec<LspDiagnostic> = items.iter().filter_map(parse_diagnostic).collect();
+            server.diagnostics.lock_or_recover().insert(key.clone(), diagnostics.clone());
+            publish_diagnostics(&app, &server.server_id, &key, &diagnostics);
+            return Ok(diagnostics);
+        }
+    }
+    Ok(server.diagnostics.lock_or_recover().get(&key).cloned().unwrap_or_default())
+}
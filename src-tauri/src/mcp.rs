@@ -0,0 +1,721 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::hash_map::RandomState;
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, LazyLock, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+use tokio::task::AbortHandle;
+
+use crate::agent::{execute_tool, tool_definitions, truncate_output, ToolCall, ToolCallFunction};
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+
+/// Protocol revisions this side speaks, newest first.
+const PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+const SERVER_NAME: &str = "gencode";
+/// Procedures that only describe the graph; any other `CALL` is refused.
+const READ_ONLY_PROCEDURES: &[&str] = &[
+    "db.labels",
+    "db.relationshipTypes",
+    "db.propertyKeys",
+    "db.schema.visualization",
+    "db.schema.nodeTypeProperties",
+    "db.schema.relTypeProperties",
+];
+
+static WRITE_CLAUSES: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(CREATE|MERGE|DELETE|DETACH|SET|REMOVE|DROP|FOREACH|LOAD\s+CSV)\b|\bapoc\.").unwrap()
+});
+/// `CALL` as a clause, not a property or label named `call`.
+static CALL_CLAUSE: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)(^|[^\w.:`$])CALL\b").unwrap());
+/// A subquery's `{` or the procedure name right after `CALL`.
+static CALL_TARGET: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"^\s*(\{|[A-Za-z_`][\w.`]*)").unwrap());
+/// MCP tool name -> agent tool it runs.
+const SERVER_TOOLS: &[(&str, &str)] = &[
+    ("query_graph", "execute_cypher"),
+    ("read_file", "read_file"),
+    ("search_code", "search_code"),
+    ("git_status", "git_status"),
+];
+const MAX_SERVER_OUTPUT: usize = 64 * 1024;
+const MAX_REQUEST_BYTES: usize = 4 * 1024 * 1024;
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+/// Prefix of third-party tools offered to the agent: `mcp__<server>__<tool>`.
+const CLIENT_TOOL_PREFIX: &str = "mcp__";
+
+// ============================================================================
+// MCP STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct McpServerInfo {
+    pub root: String,
+    pub port: u16,
+    /// Streamable HTTP endpoint for MCP clients.
+    pub url: String,
+    /// Clients send it as `Authorization: Bearer <token>`.
+    pub token: String,
+}
+
+struct RunningServer {
+    info: McpServerInfo,
+    abort: AbortHandle,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct McpClientConfig {
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub cwd: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct McpTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "empty_schema", rename(deserialize = "inputSchema"))]
+    pub input_schema: Value,
+}
+
+fn empty_schema() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct McpClientInfo {
+    pub server_id: String,
+    /// The name the server reports about itself.
+    pub server_name: Option<String>,
+    pub tools: Vec<McpTool>,
+}
+
+type PendingRequests = HashMap<i64, oneshot::Sender<Result<Value, String>>>;
+
+struct McpClient {
+    server_id: String,
+    server_name: Mutex<Option<String>>,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    child: Mutex<Child>,
+    next_id: AtomicI64,
+    pending: Mutex<PendingRequests>,
+    tools: Mutex<Vec<McpTool>>,
+}
+
+/// The embedded MCP server, when running, and connections to third-party
+/// MCP servers whose tools the agent can use.
+#[derive(Default)]
+pub struct McpState {
+    server: Mutex<Option<RunningServer>>,
+    clients: Mutex<HashMap<String, Arc<McpClient>>>,
+}
+
+impl McpState {
+    /// Kills every connected MCP server process; used on app exit.
+    pub fn shutdown(&self) {
+        for (_, client) in self.clients.lock_or_recover().drain() {
+            let _ = client.child.lock_or_recover().start_kill();
+        }
+    }
+}
+
+// ============================================================================
+// MCP SERVER
+// ============================================================================
+
+pub(crate) fn random_token() -> String {
+    // RandomState is seeded from the OS
+    let hash = || RandomState::new().hash_one(std::time::SystemTime::now());
+    format!("{:016x}{:016x}", hash(), hash())
+}
+
+fn negotiate_version(requested: Option<&str>) -> &'static str {
+    PROTOCOL_VERSIONS
+        .iter()
+        .find(|v| Some(**v) == requested)
+        .copied()
+        .unwrap_or(PROTOCOL_VERSIONS[0])
+}
+
+fn server_tools() -> Vec<Value> {
+    let definitions = tool_definitions();
+    SERVER_TOOLS
+        .iter()
+        .filter_map(|(name, agent_name)| {
+            let function = definitions
+                .as_array()?
+                .iter()
+                .map(|d| &d["function"])
+                .find(|f| f["name"] == *agent_name)?;
+            Some(json!({
+                "name": name,
+                "description": function["description"],
+                "inputSchema": function["parameters"],
+            }))
+        })
+        .collect()
+}
+
+/// Graph queries from outside must not change the graph. Callers also run
+/// them in a transaction that is rolled back; this check is still needed
+/// because procedures such as `dbms.*` act outside any transaction.
+pub(crate) fn is_read_only(cypher: &str) -> bool {
+    if WRITE_CLAUSES.is_match(cypher) {
+        return false;
+    }
+    CALL_CLAUSE.find_iter(cypher).all(|call| {
+        CALL_TARGET.captures(&cypher[call.end()..]).is_some_and(|target| {
+            let target = target[1].replace('`', "");
+            target == "{" || READ_ONLY_PROCEDURES.iter().any(|p| p.eq_ignore_ascii_case(&target))
+        })
+    })
+}
+
+async fn call_server_tool(app: &AppHandle, root: &str, params: &Value) -> Value {
+    let name = params.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+    let arguments = params.get("arguments").cloned().unwrap_or_else(|| json!({}));
+    let result = match SERVER_TOOLS.iter().find(|(tool, _)| *tool == name) {
+        None => Err(format!("Unknown tool: {}", name)),
+        Some(_) if name == "query_graph" && !arguments["query"].as_str().is_some_and(is_read_only) => {
+            Err("query_graph only runs read-only Cypher".to_string())
+        }
+        Some((_, agent_name)) => {
+            let call = ToolCall {
+                function: ToolCallFunction {
+                    name: agent_name.to_string(),
+                    arguments,
+                },
+            };
+            execute_tool(&call, Some(root), app).await
+        }
+    };
+    let (text, is_error) = match result {
+        Ok(output) => (truncate_output(output, MAX_SERVER_OUTPUT), false),
+        Err(e) => (e, true),
+    };
+    json!({ "content": [{ "type": "text", "text": text }], "isError": is_error })
+}
+
+/// Answers one JSON-RPC message; `None` for notifications.
+async fn handle_rpc(app: &AppHandle, root: &str, message: &Value) -> Option<Value> {
+    let id = message.get("id").cloned()?;
+    let method = message.get("method").and_then(|m| m.as_str()).unwrap_or_default();
+    let params = message.get("params").cloned().unwrap_or(Value::Null);
+    let result = match method {
+        "initialize" => Ok(json!({
+            "protocolVersion": negotiate_version(params.get("protocolVersion").and_then(|v| v.as_str())),
+            "capabilities": { "tools": {} },
+            "serverInfo": { "name": SERVER_NAME, "version": env!("CARGO_PKG_VERSION") },
+            "instructions": format!("Code graph, files and git state of the project at {}.", root),
+        })),
+        "ping" => Ok(json!({})),
+        "tools/list" => Ok(json!({ "tools": server_tools() })),
+        "tools/call" => Ok(call_server_tool(app, root, &params).await),
+        other => Err(json!({ "code": -32601, "message": format!("Method not found: {}", other) })),
+    };
+    Some(match result {
+        Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+        Err(error) => json!({ "jsonrpc": "2.0", "id": id, "error": error }),
+    })
+}
+
+pub(crate) async fn respond(stream: &mut TcpStream, status: &str, body: Option<&str>) {
+    let body = body.unwrap_or_default();
+    let content_type = if body.is_empty() { "" } else { "Content-Type: application/json\r\n" };
+    let response = format!(
+        "HTTP/1.1 {}\r\n{}Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Only local pages may call the server from a browser.
+pub(crate) fn allowed_origin(origin: &str) -> bool {
+    let host = origin.split_once("://").map(|(_, rest)| rest).unwrap_or(origin);
+    let host = host.split([':', '/']).next().unwrap_or(host);
+    matches!(host, "localhost" | "127.0.0.1" | "[::1]" | "tauri.localhost")
+}
+
+pub(crate) struct HttpRequest {
+    pub method: String,
+    /// Path and query, e.g. `/events?token=...`.
+    pub target: String,
+    /// Names are lowercased.
+    pub headers: HashMap<String, String>,
+    pub body: Vec<u8>,
+}
+
+/// Reads one request; `None` if the connection failed or the body was too
+/// large, which has already been answered with 413.
+pub(crate) async fn read_request(stream: &mut TcpStream) -> Option<HttpRequest> {
+    let mut reader = BufReader::new(&mut *stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await.ok()?;
+    let mut headers = HashMap::new();
+    loop {
+        let mut line = String::new();
+        match reader.read_line(&mut line).await {
+            Ok(0) | Err(_) => return None,
+            Ok(_) if line.trim().is_empty() => break,
+            Ok(_) => {
+                if let Some((name, value)) = line.split_once(':') {
+                    headers.insert(name.trim().to_lowercase(), value.trim().to_string());
+                }
+            }
+        }
+    }
+    let length = headers.get("content-length").and_then(|l| l.parse::<usize>().ok()).unwrap_or(0);
+    if length > MAX_REQUEST_BYTES {
+        drop(reader);
+        respond(stream, "413 Payload Too Large", None).await;
+        return None;
+    }
+    let mut body = vec![0; length];
+    reader.read_exact(&mut body).await.ok()?;
+    let mut parts = request_line.split_whitespace();
+    Some(HttpRequest {
+        method: parts.next().unwrap_or_default().to_string(),
+        target: parts.next().unwrap_or_default().to_string(),
+        headers,
+        body,
+    })
+}
+
+/// One request per connection over Streamable HTTP: a JSON-RPC message or
+/// batch is POSTed and the responses come back as JSON.
+async fn handle_connection(app: AppHandle, root: String, token: String, mut stream: TcpStream) {
+    let Some(HttpRequest { method, headers, body, .. }) = read_request(&mut stream).await else {
+        return;
+    };
+
+    if headers.get("origin").is_some_and(|origin| !allowed_origin(origin)) {
+        return respond(&mut stream, "403 Forbidden", None).await;
+    }
+    if headers.get("authorization").map(|a| a.as_str()) != Some(format!("Bearer {}", token).as_str()) {
+        return respond(&mut stream, "401 Unauthorized", None).await;
+    }
+    if method != "POST" {
+        // No server-initiated messages, so no SSE stream to open
+        return respond(&mut stream, "405 Method Not Allowed", None).await;
+    }
+
+    let Ok(message) = serde_json::from_slice::<Value>(&body) else {
+        let error = json!({ "jsonrpc": "2.0", "id": null, "error": { "code": -32700, "message": "Parse error" } });
+        return respond(&mut stream, "400 Bad Request", Some(&error.to_string())).await;
+    };
+    let reply = match &message {
+        Value::Array(batch) => {
+            let mut replies = Vec::new();
+            for message in batch {
+                replies.extend(handle_rpc(&app, &root, message).await);
+            }
+            (!replies.is_empty()).then(|| Value::Array(replies))
+        }
+        message => handle_rpc(&app, &root, message).await,
+    };
+    match reply {
+        Some(reply) => respond(&mut stream, "200 OK", Some(&reply.to_string())).await,
+        None => respond(&mut stream, "202 Accepted", None).await,
+    }
+}
+
+// ============================================================================
+// MCP CLIENT
+// ============================================================================
+
+impl McpClient {
+    async fn send(&self, message: Value) -> Result<(), String> {
+        let mut stdin = self.stdin.lock().await;
+        // The stdio transport is newline-delimited JSON
+        stdin
+            .write_all(format!("{}\n", message).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to MCP server {}: {}", self.server_id, e))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to write to MCP server {}: {}", self.server_id, e))
+    }
+
+    async fn request(&self, method: &str, params: Value) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock_or_recover().insert(id, sender);
+        if let Err(e) = self
+            .send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await
+        {
+            self.pending.lock_or_recover().remove(&id);
+            return Err(e);
+        }
+        match tokio::time::timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS), receiver).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(format!("MCP server {} exited", self.server_id)),
+            Err(_) => {
+                self.pending.lock_or_recover().remove(&id);
+                Err(format!("{} timed out after {}s", method, REQUEST_TIMEOUT_SECS))
+            }
+        }
+    }
+
+    async fn notify(&self, method: &str) -> Result<(), String> {
+        self.send(json!({ "jsonrpc": "2.0", "method": method })).await
+    }
+
+    /// Re-reads the tool list, following pagination.
+    async fn refresh_tools(&self) -> Result<Vec<McpTool>, String> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = match &cursor {
+                Some(cursor) => json!({ "cursor": cursor }),
+                None => json!({}),
+            };
+            let page = self.request("tools/list", params).await?;
+            let listed: Vec<McpTool> = serde_json::from_value(page.get("tools").cloned().unwrap_or_else(|| json!([])))
+                .map_err(|e| format!("Invalid tools/list response: {}", e))?;
+            tools.extend(listed);
+            cursor = page.get("nextCursor").and_then(|c| c.as_str()).map(|c| c.to_string());
+            if cursor.is_none() {
+                break;
+            }
+        }
+        *self.tools.lock_or_recover() = tools.clone();
+        Ok(tools)
+    }
+
+    async fn call_tool(&self, name: &str, arguments: Value) -> Result<String, String> {
+        let result = self
+            .request("tools/call", json!({ "name": name, "arguments": arguments }))
+            .await?;
+        let text: Vec<String> = result
+            .get("content")
+            .and_then(|c| c.as_array())
+            .into_iter()
+            .flatten()
+            .map(|item| match item.get("type").and_then(|t| t.as_str()) {
+                Some("text") => item["text"].as_str().unwrap_or_default().to_string(),
+                Some("resource") => item["resource"]["text"]
+                    .as_str()
+                    .map(|t| t.to_string())
+                    .unwrap_or_else(|| format!("[resource {}]", item["resource"]["uri"].as_str().unwrap_or_default())),
+                Some(other) => format!("[{} content]", other),
+                None => String::new(),
+            })
+            .collect();
+        let text = text.join("\n");
+        if result.get("isError").and_then(|e| e.as_bool()) == Some(true) {
+            Err(text)
+        } else {
+            Ok(text)
+        }
+    }
+
+    fn info(&self) -> McpClientInfo {
+        McpClientInfo {
+            server_id: self.server_id.clone(),
+            server_name: self.server_name.lock_or_recover().clone(),
+            tools: self.tools.lock_or_recover().clone(),
+        }
+    }
+}
+
+async fn client_read_loop(app: AppHandle, client: Arc<McpClient>, stdout: tokio::process::ChildStdout) {
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue; // Servers sometimes log to stdout
+        };
+        let id = message.get("id").cloned();
+        match (message.get("method").and_then(|m| m.as_str()), id) {
+            (None, Some(id)) => {
+                let Some(sender) = id.as_i64().and_then(|id| client.pending.lock_or_recover().remove(&id)) else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(error
+                        .get("message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("Request failed")
+                        .to_string()),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(result);
+            }
+            (Some(method), Some(id)) => {
+                // Roots, sampling and elicitation aren't offered in initialize
+                let reply = match method {
+                    "ping" => json!({ "jsonrpc": "2.0", "id": id, "result": {} }),
+                    _ => json!({
+                        "jsonrpc": "2.0",
+                        "id": id,
+                        "error": { "code": -32601, "message": format!("Method not found: {}", method) },
+                    }),
+                };
+                let _ = client.send(reply).await;
+            }
+            (Some("notifications/tools/list_changed"), None) => {
+                let (app, client) = (app.clone(), client.clone());
+                tokio::spawn(async move {
+                    if client.refresh_tools().await.is_ok() {
+                        let _ = app.emit("mcp-client-updated", client.info());
+                    }
+                });
+            }
+            _ => {}
+        }
+    }
+
+    client.pending.lock_or_recover().clear();
+    let state = app.state::<McpState>();
+    let mut clients = state.clients.lock_or_recover();
+    if clients.get(&client.server_id).is_some_and(|c| Arc::ptr_eq(c, &client)) {
+        clients.remove(&client.server_id);
+        let _ = app.emit("mcp-client-exited", client.server_id.clone());
+    }
+}
+
+/// Tools of connected MCP servers in the agent's function format.
+pub(crate) fn client_tool_definitions(state: &McpState) -> Vec<Value> {
+    let clients = state.clients.lock_or_recover();
+    let mut definitions = Vec::new();
+    for client in clients.values() {
+        for tool in client.tools.lock_or_recover().iter() {
+            definitions.push(json!({
+                "type": "function",
+                "function": {
+                    "name": format!("{}{}__{}", CLIENT_TOOL_PREFIX, client.server_id, tool.name),
+                    "description": tool.description.clone().unwrap_or_default(),
+                    "parameters": tool.input_schema,
+                }
+            }));
+        }
+    }
+    definitions
+}
+
+/// Runs an `mcp__<server>__<tool>` call; `None` if `name` isn't one.
+pub(crate) async fn call_client_tool(state: &McpState, name: &str, arguments: Value) -> Option<Result<String, String>> {
+    let (server_id, tool) = name.strip_prefix(CLIENT_TOOL_PREFIX)?.split_once("__")?;
+    let client = state.clients.lock_or_recover().get(server_id).cloned();
+    Some(match client {
+        Some(client) => client.call_tool(tool, arguments).await,
+        None => Err(format!("MCP server not connected: {}", server_id)),
+    })
+}
+
+// ============================================================================
+// MCP TAURI COMMANDS
+// ============================================================================
+
+/// Serves the code graph, files, search and git status of `root` to MCP
+/// clients over Streamable HTTP on 127.0.0.1 (an ephemeral port unless
+/// `port` is given). Restarting replaces the previous server and token.
+#[tauri::command]
+pub async fn mcp_start_server(
+    app: AppHandle,
+    root: String,
+    port: Option<u16>,
+    state: State<'_, McpState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<McpServerInfo, String> {
+    let root = workspace.check(&root)?.to_string_lossy().to_string();
+    if let Some(previous) = state.server.lock_or_recover().take() {
+        previous.abort.abort();
+    }
+    let listener = TcpListener::bind(("127.0.0.1", port.unwrap_or(0)))
+        .await
+        .map_err(|e| format!("Failed to start MCP server: {}", e))?;
+    let port = listener
+        .local_addr()
+        .map_err(|e| format!("Failed to start MCP server: {}", e))?
+        .port();
+    let info = McpServerInfo {
+        url: format!("http://127.0.0.1:{}/mcp", port),
+        token: random_token(),
+        root: root.clone(),
+        port,
+    };
+
+    let token = info.token.clone();
+    let accept_app = app.clone();
+    let task = tokio::spawn(async move {
+        while let Ok((stream, _)) = listener.accept().await {
+            tokio::spawn(handle_connection(accept_app.clone(), root.clone(), token.clone(), stream));
+        }
+    });
+    *state.server.lock_or_recover() = Some(RunningServer {
+        info: info.clone(),
+        abort: task.abort_handle(),
+    });
+    Ok(info)
+}
+
+#[tauri::command]
+pub fn mcp_stop_server(state: State<'_, McpState>) {
+    if let Some(server) = state.server.lock_or_recover().take() {
+        server.abort.abort();
+    }
+}
+
+#[tauri::command]
+pub fn mcp_server_info(state: State<'_, McpState>) -> Option<McpServerInfo> {
+    state.server.lock_or_recover().as_ref().map(|s| s.info.clone())
+}
+
+/// Spawns a third-party MCP server speaking stdio and lists its tools,
+/// which the agent can then call as `mcp__<server_id>__<tool>`.
+#[tauri::command]
+pub async fn mcp_connect_client(
+    app: AppHandle,
+    server_id: String,
+    config: McpClientConfig,
+    state: State<'_, McpState>,
+) -> Result<McpClientInfo, String> {
+    // Tool names must stay valid function names for the model
+    if server_id.is_empty() || !server_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid MCP server id: {}", server_id));
+    }
+    if state.clients.lock_or_recover().contains_key(&server_id) {
+        return Err(format!("MCP server already connected: {}", server_id));
+    }
+
+    let mut command = Command::new(&config.program);
+    command
+        .args(&config.args)
+        .envs(&config.env)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    if let Some(cwd) = &config.cwd {
+        command.current_dir(cwd);
+    }
+    let mut child = command
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {}", config.program, e))?;
+    let stdin = child.stdin.take().ok_or("Failed to capture stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+
+    let client = Arc::new(McpClient {
+        server_id: server_id.clone(),
+        server_name: Mutex::new(None),
+        stdin: tokio::sync::Mutex::new(stdin),
+        child: Mutex::new(child),
+        next_id: AtomicI64::new(1),
+        pending: Mutex::new(HashMap::new()),
+        tools: Mutex::new(Vec::new()),
+    });
+    tokio::spawn(client_read_loop(app.clone(), client.clone(), stdout));
+
+    let handshake = async {
+        let initialized = client
+            .request(
+                "initialize",
+                json!({
+                    "protocolVersion": PROTOCOL_VERSIONS[0],
+                    "capabilities": {},
+                    "clientInfo": { "name": "GenCode", "version": env!("CARGO_PKG_VERSION") },
+                }),
+            )
+            .await?;
+        *client.server_name.lock_or_recover() = initialized
+            .pointer("/serverInfo/name")
+            .and_then(|n| n.as_str())
+            .map(|n| n.to_string());
+        client.notify("notifications/initialized").await?;
+        client.refresh_tools().await
+    };
+    if let Err(e) = handshake.await {
+        let _ = client.child.lock_or_recover().start_kill();
+        return Err(format!("Failed to connect to MCP server {}: {}", server_id, e));
+    }
+    state.clients.lock_or_recover().insert(server_id, client.clone());
+    Ok(client.info())
+}
+
+#[tauri::command]
+pub fn mcp_list_clients(state: State<'_, McpState>) -> Vec<McpClientInfo> {
+    let clients = state.clients.lock_or_recover();
+    let mut infos: Vec<McpClientInfo> = clients.values().map(|c| c.info()).collect();
+    infos.sort_by(|a, b| a.server_id.cmp(&b.server_id));
+    infos
+}
+
+/// Calls a tool of a connected MCP server and returns its text output.
+#[tauri::command]
+pub async fn mcp_call_tool(
+    server_id: String,
+    name: String,
+    arguments: Option<Value>,
+    state: State<'_, McpState>,
+) -> Result<String, String> {
+    let client = state
+        .clients
+        .lock_or_recover()
+        .get(&server_id)
+        .cloned()
+        .ok_or_else(|| format!("MCP server not connected: {}", server_id))?;
+    client.call_tool(&name, arguments.unwrap_or_else(|| json!({}))).await
+}
+
+#[tauri::command]
+pub fn mcp_disconnect_client(server_id: String, state: State<'_, McpState>) -> Result<(), String> {
+    let client = state
+        .clients
+        .lock_or_recover()
+        .remove(&server_id)
+        .ok_or_else(|| format!("MCP server not connected: {}", server_id))?;
+    let _ = client.child.lock_or_recover().start_kill();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn read_only_allows_plain_reads() {
+        assert!(is_read_only("MATCH (n:FUNCTION) RETURN n.name LIMIT 10"));
+        assert!(is_read_only("MATCH (n) WHERE n.name CONTAINS 'settings' RETURN count(n)"));
+        // Keywords only count as whole words
+        assert!(is_read_only("MATCH (n) RETURN n.created_at, n.offset"));
+    }
+
+    #[test]
+    fn read_only_rejects_writes_in_any_case() {
+        assert!(!is_read_only("CREATE (n:FILE {path: 'x'})"));
+        assert!(!is_read_only("match (n) detach delete n"));
+        assert!(!is_read_only("MATCH (n) SET n.name = 'x'"));
+        assert!(!is_read_only("MATCH (n) REMOVE n:FILE"));
+        assert!(!is_read_only("LOAD  CSV FROM 'file:///x.csv' AS row RETURN row"));
+        assert!(!is_read_only("CALL apoc.periodic.iterate('MATCH (n) RETURN n', 'DELETE n', {})"));
+    }
+
+    #[test]
+    fn read_only_only_calls_schema_procedures() {
+        assert!(is_read_only("CALL db.labels() YIELD label RETURN label"));
+        assert!(is_read_only("MATCH (f:FILE) CALL { WITH f MATCH (f)-->(n) RETURN count(n) AS c } RETURN c"));
+        assert!(is_read_only("MATCH (a)-[:CALLS]->(b) RETURN a.call"));
+        assert!(!is_read_only("CALL db.createLabel('X')"));
+        assert!(!is_read_only("CALL dbms.security.createUser('x', 'y')"));
+        assert!(!is_read_only("call `db`.`createProperty`('x')"));
+        assert!(!is_read_only("MATCH (n) CALL/**/db.createLabel('X') RETURN n"));
+    }
+}
@@ -0,0 +1,266 @@
+use git2::Repository;
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use neo4rs::query;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::finder::relative_path;
+use crate::git::blame_lines;
+use crate::sandbox::WorkspaceState;
+use crate::Neo4jState;
+
+/// Where GitHub, GitLab and Bitbucket look for the file, in order.
+const CODEOWNERS_LOCATIONS: &[&str] = &[".github/CODEOWNERS", "CODEOWNERS", "docs/CODEOWNERS", ".gitlab/CODEOWNERS"];
+/// Files blamed at most when aggregating a directory.
+const MAX_BLAMED_FILES: usize = 200;
+
+// ============================================================================
+// OWNERSHIP STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct OwnersOptions {
+    /// Also aggregate git blame into line counts per author.
+    pub include_blame: bool,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct OwnerRule {
+    pub pattern: String,
+    pub owners: Vec<String>,
+    /// 1-based line in the CODEOWNERS file.
+    pub line: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct Contributor {
+    pub name: String,
+    pub email: String,
+    pub lines: usize,
+    /// Fraction of the blamed lines, 0 to 1.
+    pub share: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OwnershipInfo {
+    pub path: String,
+    pub relative_path: String,
+    /// Owners of the last matching CODEOWNERS rule; empty if none matches.
+    pub owners: Vec<String>,
+    pub rule: Option<OwnerRule>,
+    pub codeowners_file: Option<String>,
+    /// Authors of the committed lines, most lines first.
+    pub contributors: Vec<Contributor>,
+}
+
+// ============================================================================
+// CODEOWNERS
+// ============================================================================
+
+/// Parsed CODEOWNERS rules of one repository. Later rules take
+/// precedence, as on GitHub.
+struct CodeOwners {
+    root: PathBuf,
+    file: PathBuf,
+    rules: Vec<(OwnerRule, Gitignore)>,
+}
+
+impl CodeOwners {
+    fn load(root: &Path) -> Option<Self> {
+        let file = CODEOWNERS_LOCATIONS.iter().map(|l| root.join(l)).find(|p| p.is_file())?;
+        let content = std_fs::read_to_string(&file).ok()?;
+
+        let mut rules = Vec::new();
+        for (index, line) in content.lines().enumerate() {
+            let line = line.split(" #").next().unwrap_or(line).trim();
+            // Comments and GitLab section headers
+            if line.is_empty() || line.starts_with('#') || line.starts_with('[') || line.starts_with("^[") {
+                continue;
+            }
+            let mut parts = line.split_whitespace();
+            let Some(pattern) = parts.next() else {
+                continue;
+            };
+            let mut builder = GitignoreBuilder::new(root);
+            if builder.add_line(None, pattern).is_err() {
+                continue;
+            }
+            let Ok(matcher) = builder.build() else {
+                continue;
+            };
+            let rule = OwnerRule {
+                pattern: pattern.to_string(),
+                owners: parts.map(|o| o.to_string()).collect(),
+                line: index + 1,
+            };
+            rules.push((rule, matcher));
+        }
+        Some(CodeOwners {
+            root: root.to_path_buf(),
+            file,
+            rules,
+        })
+    }
+
+    /// The last rule matching `path`, absolute or relative to the root.
+    fn rule_for(&self, path: &Path, is_dir: bool) -> Option<&OwnerRule> {
+        // The matchers panic on paths outside their root
+        let path = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative,
+            Err(_) if path.is_relative() => path,
+            Err(_) => return None,
+        };
+        self.rules
+            .iter()
+            .rev()
+            .find(|(_, matcher)| matcher.matched_path_or_any_parents(path, is_dir).is_ignore())
+            .map(|(rule, _)| rule)
+    }
+}
+
+/// The repository root for `path`, or else the closest ancestor holding a
+/// CODEOWNERS file.
+fn ownership_root(path: &Path) -> Option<PathBuf> {
+    if let Some(workdir) = Repository::discover(path).ok().and_then(|r| r.workdir().map(|w| w.to_path_buf())) {
+        return Some(workdir.canonicalize().unwrap_or(workdir));
+    }
+    path.ancestors()
+        .find(|dir| CODEOWNERS_LOCATIONS.iter().any(|l| dir.join(l).is_file()))
+        .map(|dir| dir.to_path_buf())
+}
+
+/// Committed lines per author of `path`, or of up to `MAX_BLAMED_FILES`
+/// files below it.
+fn contributors(root: &Path, path: &Path) -> Vec<Contributor> {
+    let Ok(repo) = Repository::discover(root) else {
+        return Vec::new();
+    };
+    let files: Vec<PathBuf> = if path.is_dir() {
+        walk_entries(path, &DirectoryOptions::default(), None)
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|(_, is_dir)| !is_dir)
+            .map(|(file, _)| file)
+            .take(MAX_BLAMED_FILES)
+            .collect()
+    } else {
+        vec![path.to_path_buf()]
+    };
+
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+    let mut total = 0;
+    for file in files {
+        let Ok(lines) = blame_lines(&repo, &file.to_string_lossy()) else {
+            continue;
+        };
+        for line in lines.into_iter().filter(|l| l.committed) {
+            *counts.entry((line.author, line.email)).or_insert(0) += 1;
+            total += 1;
+        }
+    }
+
+    let mut contributors: Vec<Contributor> = counts
+        .into_iter()
+        .map(|((name, email), lines)| Contributor {
+            name,
+            email,
+            lines,
+            share: lines as f64 / total.max(1) as f64,
+        })
+        .collect();
+    contributors.sort_by(|a, b| b.lines.cmp(&a.lines).then_with(|| a.name.cmp(&b.name)));
+    contributors
+}
+
+// ============================================================================
+// OWNERSHIP TAURI COMMANDS
+// ============================================================================
+
+/// Who owns `path` according to CODEOWNERS and, optionally, who wrote its
+/// lines according to git blame.
+#[tauri::command]
+pub async fn get_owners(
+    path: String,
+    options: Option<OwnersOptions>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<OwnershipInfo, String> {
+    let resolved = workspace.check(&path)?;
+    if !resolved.exists() {
+        return Err(format!("Path not found: {}", path));
+    }
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        let root = ownership_root(&resolved).unwrap_or_else(|| resolved.clone());
+        let codeowners = CodeOwners::load(&root);
+        let rule = codeowners.as_ref().and_then(|c| c.rule_for(&resolved, resolved.is_dir())).cloned();
+        Ok(OwnershipInfo {
+            relative_path: relative_path(&root, &resolved).unwrap_or_default(),
+            path,
+            owners: rule.as_ref().map(|r| r.owners.clone()).unwrap_or_default(),
+            rule,
+            codeowners_file: codeowners.map(|c| c.file.to_string_lossy().to_string()),
+            contributors: if options.include_blame {
+                contributors(&root, &resolved)
+            } else {
+                Vec::new()
+            },
+        })
+    })
+    .await
+    .map_err(|e| format!("Ownership lookup failed: {}", e))?
+}
+
+/// Sets the `owners` property of every FILE node in the graph from the
+/// CODEOWNERS file of `root`. Returns the number of files with owners.
+#[tauri::command]
+pub async fn attach_owners_to_graph(
+    root: String,
+    neo4j: State<'_, Neo4jState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<usize, String> {
+    let root_path = workspace.check(&root)?;
+    let codeowners = CodeOwners::load(&root_path).ok_or_else(|| format!("No CODEOWNERS file under {}", root))?;
+    let graph = neo4j.get_graph().await?;
+
+    let mut result = graph
+        .execute(query("MATCH (f:FILE) RETURN f.path AS path"))
+        .await
+        .map_err(|e| format!("Query execution failed: {}", e))?;
+    let mut paths = Vec::new();
+    while let Ok(Some(row)) = result.next().await {
+        if let Some(path) = row
+            .to::<HashMap<String, serde_json::Value>>()
+            .ok()
+            .and_then(|row| row.get("path").and_then(|p| p.as_str()).map(|p| p.to_string()))
+        {
+            paths.push(path);
+        }
+    }
+
+    let mut owned = 0;
+    for path in paths {
+        let full_path = if Path::new(&path).is_relative() {
+            root_path.join(&path)
+        } else {
+            PathBuf::from(&path)
+        };
+        let owners = codeowners.rule_for(&full_path, false).map(|r| r.owners.clone()).unwrap_or_default();
+        if !owners.is_empty() {
+            owned += 1;
+        }
+        graph
+            .run(
+                query("MATCH (f:FILE {path: $path}) SET f.owners = $owners")
+                    .param("path", path.clone())
+                    .param("owners", owners),
+            )
+            .await
+            .map_err(|e| format!("Failed to store owners for {}: {}", path, e))?;
+    }
+    Ok(owned)
+}
@@ -0,0 +1,729 @@
+use neo4rs::query;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::atomic::{AtomicI64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::process::{Child, ChildStdin, Command};
+use tokio::sync::oneshot;
+
+use crate::event_bus::emit_graph_updated;
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::finder::relative_path;
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+use crate::{CodeGraphEdge, CodeGraphNode, Neo4jState};
+
+/// Version of the manifest and JSON-RPC interface plugins are written
+/// against. Plugins declaring a newer one are refused.
+const PLUGIN_API_VERSION: u64 = 1;
+const MANIFEST_FILE: &str = "plugin.json";
+const REQUEST_TIMEOUT_SECS: u64 = 60;
+/// Extraction gets longer, since plugins parse a batch of files per call.
+const EXTRACT_TIMEOUT_SECS: u64 = 300;
+const EXTRACT_BATCH_FILES: usize = 200;
+/// Prefix of plugin tools offered to the agent: `plugin__<plugin>__<tool>`.
+const TOOL_PREFIX: &str = "plugin__";
+
+// ============================================================================
+// PLUGIN STRUCTURES
+// ============================================================================
+
+/// What a plugin's extractor wants to see.
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct PluginExtractor {
+    /// File extensions without the dot, such as "py" or "vue".
+    pub extensions: Vec<String>,
+    /// Exact file names, such as "urls.py" or "routes.rb".
+    pub filenames: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PluginTool {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "empty_schema", rename(deserialize = "inputSchema"))]
+    pub input_schema: Value,
+}
+
+fn empty_schema() -> Value {
+    json!({ "type": "object", "properties": {} })
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PluginCommand {
+    pub id: String,
+    pub title: String,
+    #[serde(default)]
+    pub description: Option<String>,
+}
+
+/// `plugin.json` in the plugin's directory. What a plugin contributes is
+/// declared here, so it only has to run once something is used.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all(deserialize = "camelCase"))]
+pub struct PluginManifest {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    #[serde(default)]
+    pub description: Option<String>,
+    #[serde(default = "default_api_version")]
+    pub api_version: u64,
+    /// Run from the plugin directory; relative paths resolve against it.
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub extractor: Option<PluginExtractor>,
+    #[serde(default)]
+    pub tools: Vec<PluginTool>,
+    #[serde(default)]
+    pub commands: Vec<PluginCommand>,
+}
+
+fn default_api_version() -> u64 {
+    PLUGIN_API_VERSION
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginInfo {
+    pub id: String,
+    pub dir: String,
+    /// `None` when the manifest couldn't be read; see `error`.
+    pub manifest: Option<PluginManifest>,
+    pub enabled: bool,
+    pub running: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PluginExtractionReport {
+    pub plugins: Vec<String>,
+    pub files: usize,
+    pub nodes: usize,
+    pub edges: usize,
+    pub warnings: Vec<String>,
+}
+
+/// What an `extract` call returns, in the graph's own node and edge format.
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+struct ExtractedGraph {
+    nodes: Vec<CodeGraphNode>,
+    edges: Vec<CodeGraphEdge>,
+}
+
+struct DiscoveredPlugin {
+    dir: PathBuf,
+    manifest: Result<PluginManifest, String>,
+}
+
+type PendingRequests = HashMap<i64, oneshot::Sender<Result<Value, String>>>;
+
+struct PluginProcess {
+    plugin_id: String,
+    stdin: tokio::sync::Mutex<ChildStdin>,
+    child: Mutex<Child>,
+    next_id: AtomicI64,
+    pending: Mutex<PendingRequests>,
+}
+
+/// Plugins found in the plugins directory, which of them the user enabled
+/// (persisted; plugins start disabled) and the processes of those in use.
+pub struct PluginState {
+    dir: PathBuf,
+    settings_file: PathBuf,
+    enabled: Mutex<BTreeSet<String>>,
+    plugins: Mutex<BTreeMap<String, DiscoveredPlugin>>,
+    running: Mutex<HashMap<String, Arc<PluginProcess>>>,
+}
+
+impl PluginState {
+    pub fn open(dir: &Path, settings_file: &Path) -> Result<Self, String> {
+        std_fs::create_dir_all(dir).map_err(|e| format!("Failed to create plugins directory: {}", e))?;
+        let enabled: BTreeSet<String> = match std_fs::read_to_string(settings_file) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Failed to read plugin settings: {}", e))?,
+            Err(_) => BTreeSet::new(),
+        };
+        let state = PluginState {
+            dir: dir.to_path_buf(),
+            settings_file: settings_file.to_path_buf(),
+            enabled: Mutex::new(enabled),
+            plugins: Mutex::new(BTreeMap::new()),
+            running: Mutex::new(HashMap::new()),
+        };
+        state.discover();
+        Ok(state)
+    }
+
+    /// Rescans the plugins directory: one plugin per subdirectory holding a
+    /// `plugin.json`.
+    fn discover(&self) {
+        let mut plugins = BTreeMap::new();
+        for entry in std_fs::read_dir(&self.dir).into_iter().flatten().flatten() {
+            let dir = entry.path();
+            let manifest_path = dir.join(MANIFEST_FILE);
+            if !manifest_path.is_file() {
+                continue;
+            }
+            let fallback_id = entry.file_name().to_string_lossy().to_string();
+            let manifest = read_manifest(&manifest_path);
+            let id = manifest.as_ref().map(|m| m.id.clone()).unwrap_or(fallback_id);
+            if plugins.contains_key(&id) {
+                continue;
+            }
+            plugins.insert(id, DiscoveredPlugin { dir, manifest });
+        }
+        *self.plugins.lock_or_recover() = plugins;
+    }
+
+    fn infos(&self) -> Vec<PluginInfo> {
+        let enabled = self.enabled.lock_or_recover();
+        let running = self.running.lock_or_recover();
+        self.plugins
+            .lock_or_recover()
+            .iter()
+            .map(|(id, plugin)| PluginInfo {
+                id: id.clone(),
+                dir: plugin.dir.to_string_lossy().to_string(),
+                manifest: plugin.manifest.as_ref().ok().cloned(),
+                enabled: enabled.contains(id),
+                running: running.contains_key(id),
+                error: plugin.manifest.as_ref().err().cloned(),
+            })
+            .collect()
+    }
+
+    fn save_enabled(&self, enabled: &BTreeSet<String>) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(enabled).map_err(|e| e.to_string())?;
+        std_fs::write(&self.settings_file, json).map_err(|e| format!("Failed to save plugin settings: {}", e))
+    }
+
+    /// Manifests of enabled plugins that loaded.
+    fn enabled_manifests(&self) -> Vec<PluginManifest> {
+        let enabled = self.enabled.lock_or_recover();
+        self.plugins
+            .lock_or_recover()
+            .iter()
+            .filter(|(id, _)| enabled.contains(*id))
+            .filter_map(|(_, plugin)| plugin.manifest.as_ref().ok().cloned())
+            .collect()
+    }
+
+    fn stop(&self, plugin_id: &str) {
+        if let Some(process) = self.running.lock_or_recover().remove(plugin_id) {
+            let _ = process.child.lock_or_recover().start_kill();
+        }
+    }
+
+    /// Kills every running plugin; used on app exit.
+    pub fn shutdown(&self) {
+        for (_, process) in self.running.lock_or_recover().drain() {
+            let _ = process.child.lock_or_recover().start_kill();
+        }
+    }
+}
+
+fn read_manifest(path: &Path) -> Result<PluginManifest, String> {
+    let json = std_fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+    let manifest: PluginManifest =
+        serde_json::from_str(&json).map_err(|e| format!("Invalid {}: {}", path.display(), e))?;
+    // Tool names must stay valid function names for the model
+    if manifest.id.is_empty() || !manifest.id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return Err(format!("Invalid plugin id: {}", manifest.id));
+    }
+    if manifest.api_version > PLUGIN_API_VERSION {
+        return Err(format!(
+            "Plugin {} needs plugin API {}; this version of GenCode supports {}",
+            manifest.id, manifest.api_version, PLUGIN_API_VERSION
+        ));
+    }
+    Ok(manifest)
+}
+
+// ============================================================================
+// PLUGIN PROCESSES
+// ============================================================================
+
+impl PluginProcess {
+    async fn send(&self, message: Value) -> Result<(), String> {
+        let mut stdin = self.stdin.lock().await;
+        // Newline-delimited JSON-RPC 2.0, like MCP's stdio transport
+        stdin
+            .write_all(format!("{}\n", message).as_bytes())
+            .await
+            .map_err(|e| format!("Failed to write to plugin {}: {}", self.plugin_id, e))?;
+        stdin
+            .flush()
+            .await
+            .map_err(|e| format!("Failed to write to plugin {}: {}", self.plugin_id, e))
+    }
+
+    async fn request(&self, method: &str, params: Value, timeout_secs: u64) -> Result<Value, String> {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let (sender, receiver) = oneshot::channel();
+        self.pending.lock_or_recover().insert(id, sender);
+        if let Err(e) = self
+            .send(json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params }))
+            .await
+        {
+            self.pending.lock_or_recover().remove(&id);
+            return Err(e);
+        }
+        match tokio::time::timeout(Duration::from_secs(timeout_secs), receiver).await {
+            Ok(Ok(result)) => result,
+            Ok(Err(_)) => Err(format!("Plugin {} exited", self.plugin_id)),
+            Err(_) => {
+                self.pending.lock_or_recover().remove(&id);
+                Err(format!("{} timed out after {}s", method, timeout_secs))
+            }
+        }
+    }
+}
+
+/// Routes responses to their requests and `log` notifications to
+/// `plugin-log` events. Stderr is not read; plugins log through `log`.
+async fn read_loop(app: AppHandle, process: Arc<PluginProcess>, stdout: tokio::process::ChildStdout) {
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let Ok(message) = serde_json::from_str::<Value>(&line) else {
+            continue;
+        };
+        match (message.get("method").and_then(|m| m.as_str()), message.get("id")) {
+            (None, Some(id)) => {
+                let Some(sender) = id.as_i64().and_then(|id| process.pending.lock_or_recover().remove(&id)) else {
+                    continue;
+                };
+                let result = match message.get("error") {
+                    Some(error) => Err(error
+                        .get("message")
+                        .and_then(|m| m.as_str())
+                        .unwrap_or("Request failed")
+                        .to_string()),
+                    None => Ok(message.get("result").cloned().unwrap_or(Value::Null)),
+                };
+                let _ = sender.send(result);
+            }
+            (Some("log"), None) => {
+                let _ = app.emit(
+                    "plugin-log",
+                    json!({
+                        "plugin_id": process.plugin_id,
+                        "level": message.pointer("/params/level").and_then(|l| l.as_str()).unwrap_or("info"),
+                        "message": message.pointer("/params/message").and_then(|m| m.as_str()).unwrap_or_default(),
+                    }),
+                );
+            }
+            (Some(method), Some(id)) => {
+                let reply = json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("Method not found: {}", method) },
+                });
+                let _ = process.send(reply).await;
+            }
+            _ => {}
+        }
+    }
+
+    process.pending.lock_or_recover().clear();
+    let state = app.state::<PluginState>();
+    let mut running = state.running.lock_or_recover();
+    if running.get(&process.plugin_id).is_some_and(|p| Arc::ptr_eq(p, &process)) {
+        running.remove(&process.plugin_id);
+        let _ = app.emit("plugin-exited", process.plugin_id.clone());
+    }
+}
+
+/// The plugin's process, started and initialized on first use.
+async fn plugin_process(app: &AppHandle, plugin_id: &str) -> Result<Arc<PluginProcess>, String> {
+    let state = app.state::<PluginState>();
+    if let Some(process) = state.running.lock_or_recover().get(plugin_id) {
+        return Ok(process.clone());
+    }
+    if !state.enabled.lock_or_recover().contains(plugin_id) {
+        return Err(format!("Plugin not enabled: {}", plugin_id));
+    }
+    let (dir, manifest) = {
+        let plugins = state.plugins.lock_or_recover();
+        let plugin = plugins.get(plugin_id).ok_or_else(|| format!("Plugin not found: {}", plugin_id))?;
+        (plugin.dir.clone(), plugin.manifest.clone()?)
+    };
+
+    let program = if manifest.program.contains(['/', '\\']) {
+        dir.join(&manifest.program).to_string_lossy().to_string()
+    } else {
+        manifest.program.clone()
+    };
+    let mut child = Command::new(&program)
+        .args(&manifest.args)
+        .envs(&manifest.env)
+        .current_dir(&dir)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start plugin {}: {}", plugin_id, e))?;
+    let stdin = child.stdin.take().ok_or("Failed to capture stdin")?;
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+
+    let process = Arc::new(PluginProcess {
+        plugin_id: plugin_id.to_string(),
+        stdin: tokio::sync::Mutex::new(stdin),
+        child: Mutex::new(child),
+        next_id: AtomicI64::new(1),
+        pending: Mutex::new(HashMap::new()),
+    });
+    tokio::spawn(read_loop(app.clone(), process.clone(), stdout));
+
+    let params = json!({
+        "apiVersion": PLUGIN_API_VERSION,
+        "host": { "name": "GenCode", "version": env!("CARGO_PKG_VERSION") },
+    });
+    if let Err(e) = process.request("initialize", params, REQUEST_TIMEOUT_SECS).await {
+        let _ = process.child.lock_or_recover().start_kill();
+        return Err(format!("Failed to initialize plugin {}: {}", plugin_id, e));
+    }
+
+    let mut running = state.running.lock_or_recover();
+    if let Some(existing) = running.get(plugin_id) {
+        // Started concurrently by another caller
+        let _ = process.child.lock_or_recover().start_kill();
+        return Ok(existing.clone());
+    }
+    running.insert(plugin_id.to_string(), process.clone());
+    let _ = app.emit("plugin-started", plugin_id.to_string());
+    Ok(process)
+}
+
+/// Tools of enabled plugins in the agent's function format.
+pub(crate) fn plugin_tool_definitions(state: &PluginState) -> Vec<Value> {
+    let mut definitions = Vec::new();
+    for manifest in state.enabled_manifests() {
+        for tool in &manifest.tools {
+            definitions.push(json!({
+                "type": "function",
+                "function": {
+                    "name": format!("{}{}__{}", TOOL_PREFIX, manifest.id, tool.name),
+                    "description": tool.description.clone().unwrap_or_default(),
+                    "parameters": tool.input_schema,
+                }
+            }));
+        }
+    }
+    definitions
+}
+
+/// Runs a `plugin__<plugin>__<tool>` call; `None` if `name` isn't one.
+pub(crate) async fn call_plugin_tool(app: &AppHandle, name: &str, arguments: Value) -> Option<Result<String, String>> {
+    let (plugin_id, tool) = name.strip_prefix(TOOL_PREFIX)?.split_once("__")?;
+    Some(run_tool(app, plugin_id, tool, arguments).await)
+}
+
+async fn run_tool(app: &AppHandle, plugin_id: &str, tool: &str, arguments: Value) -> Result<String, String> {
+    let process = plugin_process(app, plugin_id).await?;
+    let result = process
+        .request("tools/call", json!({ "name": tool, "arguments": arguments }), REQUEST_TIMEOUT_SECS)
+        .await?;
+    Ok(match result {
+        Value::String(text) => text,
+        Value::Object(map) if map.get("output").is_some_and(Value::is_string) => {
+            map["output"].as_str().unwrap_or_default().to_string()
+        }
+        other => other.to_string(),
+    })
+}
+
+// ============================================================================
+// PLUGIN GRAPH EXTRACTION
+// ============================================================================
+
+fn wants_file(extractor: &PluginExtractor, path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase());
+    extractor.filenames.contains(&name)
+        || extension.is_some_and(|ext| {
+            extractor
+                .extensions
+                .iter()
+                .any(|wanted| wanted.trim_start_matches('.').eq_ignore_ascii_case(&ext))
+        })
+}
+
+/// Node labels and relationship types are spliced into Cypher, so only
+/// identifier characters are allowed.
+fn graph_label(name: &str) -> Option<String> {
+    let label = name.to_uppercase();
+    let valid = label.chars().next().is_some_and(|c| c.is_ascii_alphabetic())
+        && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+    valid.then_some(label)
+}
+
+/// Replaces what `plugin_id` extracted for `root` before. Nodes are merged
+/// by id, so a plugin can attach properties and edges to existing nodes;
+/// only nodes it created are removed on the next run.
+async fn store_extraction(
+    neo4j: &Neo4jState,
+    root: &str,
+    plugin_id: &str,
+    extracted: &ExtractedGraph,
+    warnings: &mut Vec<String>,
+) -> Result<(usize, usize), String> {
+    let graph = neo4j.get_graph().await?;
+    graph
+        .run(
+            query("MATCH ()-[r]->() WHERE r.plugin = $plugin AND r.root = $root DELETE r")
+                .param("plugin", plugin_id.to_string())
+                .param("root", root.to_string()),
+        )
+        .await
+        .map_err(|e| format!("Failed to clear plugin edges: {}", e))?;
+    graph
+        .run(
+            query("MATCH (n) WHERE n.plugin = $plugin AND n.root = $root DETACH DELETE n")
+                .param("plugin", plugin_id.to_string())
+                .param("root", root.to_string()),
+        )
+        .await
+        .map_err(|e| format!("Failed to clear plugin nodes: {}", e))?;
+
+    let mut nodes = 0;
+    for node in &extracted.nodes {
+        let Some(label) = graph_label(&node.node_type) else {
+            warnings.push(format!("{}: invalid node type {}", plugin_id, node.node_type));
+            continue;
+        };
+        let optional = [
+            ("n.name = $name", node.name.is_some()),
+            ("n.path = $path", node.path.is_some()),
+            ("n.language = $language", node.language.is_some()),
+            ("n.startLine = $startLine", node.start_line.is_some()),
+            ("n.endLine = $endLine", node.end_line.is_some()),
+            ("n.line = $line", node.line.is_some()),
+        ];
+        let properties: Vec<&str> = optional.iter().filter(|(_, present)| *present).map(|(set, _)| *set).collect();
+        let mut merge = format!("MERGE (n:{} {{id: $id}}) ON CREATE SET n.plugin = $plugin, n.root = $root", label);
+        if !properties.is_empty() {
+            merge.push_str(&format!(" SET {}", properties.join(", ")));
+        }
+        let mut cypher = query(&merge)
+        .param("id", node.id.clone())
+        .param("plugin", plugin_id.to_string())
+        .param("root", root.to_string());
+        if let Some(name) = &node.name {
+            cypher = cypher.param("name", name.clone());
+        }
+        if let Some(path) = &node.path {
+            cypher = cypher.param("path", path.clone());
+        }
+        if let Some(language) = &node.language {
+            cypher = cypher.param("language", language.clone());
+        }
+        if let Some(start_line) = node.start_line {
+            cypher = cypher.param("startLine", start_line as i64);
+        }
+        if let Some(end_line) = node.end_line {
+            cypher = cypher.param("endLine", end_line as i64);
+        }
+        if let Some(line) = node.line {
+            cypher = cypher.param("line", line as i64);
+        }
+        graph
+            .run(cypher)
+            .await
+            .map_err(|e| format!("Failed to store plugin node {}: {}", node.id, e))?;
+        nodes += 1;
+    }
+
+    let mut edges = 0;
+    for edge in &extracted.edges {
+        let Some(edge_type) = graph_label(&edge.edge_type) else {
+            warnings.push(format!("{}: invalid edge type {}", plugin_id, edge.edge_type));
+            continue;
+        };
+        graph
+            .run(
+                query(&format!(
+                    "MATCH (a {{id: $from}}), (b {{id: $to}}) \
+                     MERGE (a)-[r:{} {{plugin: $plugin}}]->(b) SET r.root = $root",
+                    edge_type
+                ))
+                .param("from", edge.from.clone())
+                .param("to", edge.to.clone())
+                .param("plugin", plugin_id.to_string())
+                .param("root", root.to_string()),
+            )
+            .await
+            .map_err(|e| format!("Failed to store plugin edge {} -> {}: {}", edge.from, edge.to, e))?;
+        edges += 1;
+    }
+    Ok((nodes, edges))
+}
+
+// ============================================================================
+// PLUGIN TAURI COMMANDS
+// ============================================================================
+
+/// Rescans the plugins directory and lists what it holds.
+#[tauri::command]
+pub fn list_plugins(state: State<'_, PluginState>) -> Vec<PluginInfo> {
+    state.discover();
+    state.infos()
+}
+
+#[tauri::command]
+pub fn get_plugins_directory(state: State<'_, PluginState>) -> String {
+    state.dir.to_string_lossy().to_string()
+}
+
+/// Enables or disables a plugin; disabling stops its process.
+#[tauri::command]
+pub fn set_plugin_enabled(plugin_id: String, enabled: bool, state: State<'_, PluginState>) -> Result<(), String> {
+    if !state.plugins.lock_or_recover().contains_key(&plugin_id) {
+        return Err(format!("Plugin not found: {}", plugin_id));
+    }
+    let mut enabled_plugins = state.enabled.lock_or_recover();
+    if enabled {
+        enabled_plugins.insert(plugin_id.clone());
+    } else {
+        enabled_plugins.remove(&plugin_id);
+    }
+    state.save_enabled(&enabled_plugins)?;
+    drop(enabled_plugins);
+    if !enabled {
+        state.stop(&plugin_id);
+    }
+    Ok(())
+}
+
+/// Runs the extractors of enabled plugins (or just `plugin_id`) over the
+/// files under `root` they asked for, and stores the nodes and edges they
+/// return in the graph. Files are sent as paths in batches through
+/// `extract`.
+#[tauri::command]
+pub async fn plugin_extract_graph(
+    app: AppHandle,
+    root: String,
+    plugin_id: Option<String>,
+    state: State<'_, PluginState>,
+    workspace: State<'_, WorkspaceState>,
+    neo4j: State<'_, Neo4jState>,
+) -> Result<PluginExtractionReport, String> {
+    let root_path = workspace.check(&root)?;
+    let manifests: Vec<PluginManifest> = state
+        .enabled_manifests()
+        .into_iter()
+        .filter(|manifest| manifest.extractor.is_some())
+        .filter(|manifest| match &plugin_id {
+            Some(id) => *id == manifest.id,
+            None => true,
+        })
+        .collect();
+    if let Some(id) = &plugin_id {
+        if manifests.is_empty() {
+            return Err(format!("No enabled extractor plugin: {}", id));
+        }
+    }
+
+    let walk_root = root_path.clone();
+    let entries = tokio::task::spawn_blocking(move || walk_entries(&walk_root, &DirectoryOptions::default(), None))
+        .await
+        .map_err(|e| format!("Failed to list files: {}", e))??;
+    let root_str = root_path.to_string_lossy().to_string();
+
+    let mut report = PluginExtractionReport {
+        plugins: Vec::new(),
+        files: 0,
+        nodes: 0,
+        edges: 0,
+        warnings: Vec::new(),
+    };
+    for manifest in manifests {
+        let extractor = manifest.extractor.clone().unwrap_or_default();
+        let files: Vec<Value> = entries
+            .iter()
+            .filter(|(path, is_dir)| !is_dir && wants_file(&extractor, path))
+            .map(|(path, _)| {
+                json!({
+                    "path": path.to_string_lossy(),
+                    "relative_path": relative_path(&root_path, path).unwrap_or_default(),
+                })
+            })
+            .collect();
+        if files.is_empty() {
+            continue;
+        }
+
+        let process = plugin_process(&app, &manifest.id).await?;
+        let mut extracted = ExtractedGraph::default();
+        for batch in files.chunks(EXTRACT_BATCH_FILES) {
+            let result = process
+                .request("extract", json!({ "root": root_str, "files": batch }), EXTRACT_TIMEOUT_SECS)
+                .await
+                .map_err(|e| format!("Plugin {} failed to extract: {}", manifest.id, e))?;
+            let graph: ExtractedGraph = serde_json::from_value(result)
+                .map_err(|e| format!("Plugin {} returned an invalid graph: {}", manifest.id, e))?;
+            extracted.nodes.extend(graph.nodes);
+            extracted.edges.extend(graph.edges);
+        }
+        let (nodes, edges) = store_extraction(&neo4j, &root_str, &manifest.id, &extracted, &mut report.warnings).await?;
+        report.plugins.push(manifest.id.clone());
+        report.files += files.len();
+        report.nodes += nodes;
+        report.edges += edges;
+    }
+    if !report.plugins.is_empty() {
+        emit_graph_updated(&app, "plugins", Some(&root_str), report.nodes);
+    }
+    Ok(report)
+}
+
+/// Runs a command a plugin contributes and returns its JSON result.
+#[tauri::command]
+pub async fn plugin_run_command(
+    app: AppHandle,
+    plugin_id: String,
+    command: String,
+    args: Option<Value>,
+    state: State<'_, PluginState>,
+) -> Result<Value, String> {
+    let declared = state
+        .enabled_manifests()
+        .iter()
+        .any(|manifest| manifest.id == plugin_id && manifest.commands.iter().any(|c| c.id == command));
+    if !declared {
+        return Err(format!("Plugin {} has no command {}", plugin_id, command));
+    }
+    let process = plugin_process(&app, &plugin_id).await?;
+    process
+        .request(
+            "commands/run",
+            json!({ "command": command, "args": args.unwrap_or(Value::Null) }),
+            REQUEST_TIMEOUT_SECS,
+        )
+        .await
+}
+
+/// Calls a tool a plugin contributes, as the agent would.
+#[tauri::command]
+pub async fn plugin_call_tool(
+    app: AppHandle,
+    plugin_id: String,
+    name: String,
+    arguments: Option<Value>,
+) -> Result<String, String> {
+    run_tool(&app, &plugin_id, &name, arguments.unwrap_or_else(|| json!({}))).await
+}
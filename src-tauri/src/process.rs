@@ -0,0 +1,665 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, State, Window};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::task::{AbortHandle, JoinHandle};
+
+use crate::locks::LockExt;
+
+const MAX_DEV_LOG_LINES: usize = 2000;
+const MAX_RESTART_DELAY_SECS: u64 = 30;
+/// How long output is still read once the process tree is gone.
+const OUTPUT_GRACE: Duration = Duration::from_secs(2);
+
+// ============================================================================
+// PROCESS STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+struct CommandOutputEvent {
+    command_id: String,
+    stream: &'static str, // "stdout", "stderr"
+    data: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CommandResult {
+    pub exit_code: Option<i32>,
+    pub timed_out: bool,
+    pub stdout: String,
+    pub stderr: String,
+    pub duration_ms: u64,
+}
+
+// ============================================================================
+// PROCESS EXECUTION
+// ============================================================================
+
+/// Forwards a pipe line by line as `command-output` events and appends
+/// everything that was read to `collected`.
+async fn pump_output<R: AsyncRead + Unpin>(
+    pipe: R,
+    window: Option<Window>,
+    command_id: String,
+    stream: &'static str,
+    collected: Arc<Mutex<String>>,
+) {
+    let mut reader = BufReader::new(pipe);
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let data = String::from_utf8_lossy(&line).to_string();
+                if let Some(window) = &window {
+                    let _ = window.emit(
+                        "command-output",
+                        CommandOutputEvent {
+                            command_id: command_id.clone(),
+                            stream,
+                            data: data.clone(),
+                        },
+                    );
+                }
+                collected.lock_or_recover().push_str(&data);
+            }
+        }
+    }
+}
+
+/// What a pump collected once its pipe closed, or after `OUTPUT_GRACE` if
+/// something that left the process tree still holds the pipe open.
+async fn collect_output(pump: JoinHandle<()>, collected: Arc<Mutex<String>>) -> String {
+    let abort = pump.abort_handle();
+    if tokio::time::timeout(OUTPUT_GRACE, pump).await.is_err() {
+        abort.abort();
+    }
+    std::mem::take(&mut *collected.lock_or_recover())
+}
+
+/// Spawns `program` without a PTY and waits for it, killing it and
+/// everything it started once `timeout_ms` elapses. Output is streamed when
+/// a window is given.
+pub(crate) async fn run_process(
+    window: Option<Window>,
+    command_id: &str,
+    cwd: Option<&str>,
+    program: &str,
+    args: &[String],
+    env: &HashMap<String, String>,
+    timeout_ms: Option<u64>,
+) -> Result<CommandResult, String> {
+    let started = Instant::now();
+
+    let mut cmd = Command::new(program);
+    cmd.args(args)
+        .envs(env)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    if let Some(dir) = cwd {
+        cmd.current_dir(dir);
+    }
+    ProcessTree::isolate(&mut cmd);
+
+    let mut child = cmd
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {}", program, e))?;
+    let mut tree = ProcessTree::attach(&child);
+
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    let (stdout_text, stderr_text) = (Arc::new(Mutex::new(String::new())), Arc::new(Mutex::new(String::new())));
+    let stdout_task =
+        tokio::spawn(pump_output(stdout, window.clone(), command_id.to_string(), "stdout", stdout_text.clone()));
+    let stderr_task = tokio::spawn(pump_output(stderr, window, command_id.to_string(), "stderr", stderr_text.clone()));
+
+    let (status, timed_out) = match timeout_ms {
+        Some(ms) => match tokio::time::timeout(Duration::from_millis(ms), child.wait()).await {
+            Ok(status) => (status, false),
+            Err(_) => {
+                tree.kill();
+                let _ = child.kill().await;
+                (child.wait().await, true)
+            }
+        },
+        None => (child.wait().await, false),
+    };
+    // Anything still running in the tree would keep the pipes open
+    tree.kill();
+    let status = status.map_err(|e| format!("Failed to wait for {}: {}", program, e))?;
+
+    Ok(CommandResult {
+        exit_code: if timed_out { None } else { status.code() },
+        timed_out,
+        stdout: collect_output(stdout_task, stdout_text).await,
+        stderr: collect_output(stderr_task, stderr_text).await,
+        duration_ms: started.elapsed().as_millis() as u64,
+    })
+}
+
+// ============================================================================
+// PROCESS TREES
+// ============================================================================
+
+/// A spawned process and everything it starts: its process group on unix,
+/// a job object on Windows. `kill_on_drop` only reaches the direct child,
+/// while e.g. the server behind `npm run dev` is a grandchild, so the whole
+/// tree is killed when this is dropped.
+pub(crate) struct ProcessTree {
+    #[cfg(unix)]
+    group: Option<i32>,
+    /// The job's HANDLE, kept as an integer so the tree is `Send`.
+    #[cfg(windows)]
+    job: Option<usize>,
+}
+
+#[cfg(unix)]
+impl ProcessTree {
+    /// Must be applied before spawning for `attach` to cover the tree.
+    pub(crate) fn isolate(cmd: &mut Command) {
+        cmd.process_group(0);
+    }
+
+    pub(crate) fn attach(child: &Child) -> Self {
+        ProcessTree {
+            group: child.id().and_then(|pid| i32::try_from(pid).ok()),
+        }
+    }
+
+    pub(crate) fn kill(&mut self) {
+        if let Some(group) = self.group.take() {
+            // SAFETY: kill has no memory effects; a negative pid names the group
+            unsafe {
+                libc::kill(-group, libc::SIGKILL);
+            }
+        }
+    }
+}
+
+#[cfg(windows)]
+impl ProcessTree {
+    pub(crate) fn isolate(_cmd: &mut Command) {}
+
+    /// Processes the child starts before it is assigned to the job, right
+    /// after spawning, are not covered.
+    pub(crate) fn attach(child: &Child) -> Self {
+        use windows_sys::Win32::System::JobObjects::{
+            AssignProcessToJobObject, CreateJobObjectW, JobObjectExtendedLimitInformation, SetInformationJobObject,
+            JOBOBJECT_EXTENDED_LIMIT_INFORMATION, JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE,
+        };
+
+        let Some(process) = child.raw_handle() else {
+            return ProcessTree { job: None };
+        };
+        // SAFETY: plain Win32 calls on a handle we own and one tokio keeps open
+        unsafe {
+            let job = CreateJobObjectW(std::ptr::null(), std::ptr::null());
+            if job.is_null() {
+                return ProcessTree { job: None };
+            }
+            // Also kills the tree if the app dies without dropping this
+            let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = std::mem::zeroed();
+            limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+            SetInformationJobObject(
+                job,
+                JobObjectExtendedLimitInformation,
+                &limits as *const _ as *const std::ffi::c_void,
+                std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+            );
+            AssignProcessToJobObject(job, process as _);
+            ProcessTree { job: Some(job as usize) }
+        }
+    }
+
+    pub(crate) fn kill(&mut self) {
+        use windows_sys::Win32::Foundation::CloseHandle;
+        use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+
+        if let Some(job) = self.job.take() {
+            // SAFETY: `job` is a job handle only this tree owns
+            unsafe {
+                TerminateJobObject(job as _, 1);
+                CloseHandle(job as _);
+            }
+        }
+    }
+}
+
+impl Drop for ProcessTree {
+    fn drop(&mut self) {
+        self.kill();
+    }
+}
+
+// ============================================================================
+// PROCESS TAURI COMMANDS
+// ============================================================================
+
+/// Runs a non-interactive command, emitting its stdout and stderr as
+/// `command-output` events tagged with `command_id`.
+#[tauri::command]
+pub async fn run_command(
+    window: Window,
+    command_id: String,
+    cwd: Option<String>,
+    program: String,
+    args: Option<Vec<String>>,
+    env: Option<HashMap<String, String>>,
+    timeout_ms: Option<u64>,
+) -> Result<CommandResult, String> {
+    run_process(
+        Some(window),
+        &command_id,
+        cwd.as_deref(),
+        &program,
+        &args.unwrap_or_default(),
+        &env.unwrap_or_default(),
+        timeout_ms,
+    )
+    .await
+}
+
+// ============================================================================
+// DEV PROCESS MANAGER
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct DevProcessConfig {
+    pub cwd: Option<String>,
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// "never", "on_failure" or "always"
+    #[serde(default = "default_restart_policy")]
+    pub restart_policy: String,
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+}
+
+fn default_restart_policy() -> String {
+    "on_failure".to_string()
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DevProcessStatus {
+    pub process_id: String,
+    pub state: String, // "starting", "running", "restarting", "exited", "crashed", "stopped"
+    pub pid: Option<u32>,
+    pub exit_code: Option<i32>,
+    pub restarts: u32,
+    pub ports: Vec<u16>,
+    pub started_at: i64,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct DevLogLine {
+    pub stream: &'static str, // "stdout", "stderr"
+    pub line: String,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct DevProcessOutputEvent {
+    process_id: String,
+    stream: &'static str,
+    line: String,
+}
+
+struct DevProcess {
+    config: DevProcessConfig,
+    status: Arc<Mutex<DevProcessStatus>>,
+    logs: Arc<Mutex<VecDeque<DevLogLine>>>,
+    /// The current run's tree, so it can be killed without waiting for the
+    /// aborted supervisor to be dropped.
+    tree: Arc<Mutex<Option<ProcessTree>>>,
+    supervisor: AbortHandle,
+}
+
+/// Long-running processes such as dev servers and watchers, supervised by the
+/// backend independently of any terminal.
+#[derive(Default)]
+pub struct DevProcessState {
+    processes: Mutex<HashMap<String, DevProcess>>,
+}
+
+impl DevProcessState {
+    /// Kills every supervised process; used on app exit.
+    pub fn shutdown(&self) {
+        for (_, process) in self.processes.lock_or_recover().drain() {
+            process.supervisor.abort();
+            process.tree.lock_or_recover().take();
+        }
+    }
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+/// Picks up ports from lines such as `http://localhost:5173/`,
+/// `Listening on 0.0.0.0:8080` or `running on port 3000`.
+fn detect_ports(line: &str) -> Vec<u16> {
+    let lower = line.to_lowercase();
+    let mut ports = Vec::new();
+
+    for marker in ["localhost:", "127.0.0.1:", "0.0.0.0:", "[::]:", "[::1]:", "port "] {
+        let mut rest = lower.as_str();
+        while let Some(pos) = rest.find(marker) {
+            rest = &rest[pos + marker.len()..];
+            let digits: String = rest.chars().take_while(|c| c.is_ascii_digit()).collect();
+            if let Ok(port) = digits.parse::<u16>() {
+                if port > 0 && !ports.contains(&port) {
+                    ports.push(port);
+                }
+            }
+        }
+    }
+    ports
+}
+
+fn set_state(window: &Window, status: &Mutex<DevProcessStatus>, update: impl FnOnce(&mut DevProcessStatus)) {
+    let snapshot = {
+        let mut status = status.lock_or_recover();
+        update(&mut status);
+        status.clone()
+    };
+    let _ = window.emit("dev-process-status", snapshot);
+}
+
+async fn pump_dev_output<R: AsyncRead + Unpin>(
+    pipe: R,
+    window: Window,
+    stream: &'static str,
+    status: Arc<Mutex<DevProcessStatus>>,
+    logs: Arc<Mutex<VecDeque<DevLogLine>>>,
+) {
+    let mut reader = BufReader::new(pipe);
+    let mut line = Vec::new();
+
+    loop {
+        line.clear();
+        match reader.read_until(b'\n', &mut line).await {
+            Ok(0) | Err(_) => break,
+            Ok(_) => {
+                let text = String::from_utf8_lossy(&line).trim_end().to_string();
+
+                let new_ports: Vec<u16> = {
+                    let status = status.lock_or_recover();
+                    detect_ports(&text).into_iter().filter(|p| !status.ports.contains(p)).collect()
+                };
+                if !new_ports.is_empty() {
+                    set_state(&window, &status, |s| s.ports.extend(new_ports));
+                }
+
+                {
+                    let mut logs = logs.lock_or_recover();
+                    if logs.len() >= MAX_DEV_LOG_LINES {
+                        logs.pop_front();
+                    }
+                    logs.push_back(DevLogLine {
+                        stream,
+                        line: text.clone(),
+                        timestamp: now_secs(),
+                    });
+                }
+
+                let process_id = status.lock_or_recover().process_id.clone();
+                let _ = window.emit(
+                    "dev-process-output",
+                    DevProcessOutputEvent {
+                        process_id,
+                        stream,
+                        line: text,
+                    },
+                );
+            }
+        }
+    }
+}
+
+/// Runs the process and restarts it according to its policy, backing off
+/// a little longer after each restart.
+async fn supervise(
+    window: Window,
+    config: DevProcessConfig,
+    status: Arc<Mutex<DevProcessStatus>>,
+    logs: Arc<Mutex<VecDeque<DevLogLine>>>,
+    tree: Arc<Mutex<Option<ProcessTree>>>,
+) {
+    loop {
+        let mut cmd = Command::new(&config.program);
+        cmd.args(&config.args)
+            .envs(&config.env)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true);
+        if let Some(dir) = &config.cwd {
+            cmd.current_dir(dir);
+        }
+        ProcessTree::isolate(&mut cmd);
+
+        let mut child = match cmd.spawn() {
+            Ok(child) => child,
+            Err(e) => {
+                logs.lock_or_recover().push_back(DevLogLine {
+                    stream: "stderr",
+                    line: format!("Failed to spawn {}: {}", config.program, e),
+                    timestamp: now_secs(),
+                });
+                set_state(&window, &status, |s| {
+                    s.state = "crashed".to_string();
+                    s.pid = None;
+                });
+                return;
+            }
+        };
+
+        *tree.lock_or_recover() = Some(ProcessTree::attach(&child));
+
+        set_state(&window, &status, |s| {
+            s.state = "running".to_string();
+            s.pid = child.id();
+            s.exit_code = None;
+            s.ports.clear();
+            s.started_at = now_secs();
+        });
+
+        let mut pumps = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            pumps.push(tokio::spawn(pump_dev_output(stdout, window.clone(), "stdout", status.clone(), logs.clone())));
+        }
+        if let Some(stderr) = child.stderr.take() {
+            pumps.push(tokio::spawn(pump_dev_output(stderr, window.clone(), "stderr", status.clone(), logs.clone())));
+        }
+
+        let exit = child.wait().await;
+        // Servers it started would keep the port and the pipes
+        tree.lock_or_recover().take();
+        for pump in pumps {
+            let _ = pump.await;
+        }
+
+        let exit_code = exit.as_ref().ok().and_then(|s| s.code());
+        let success = exit.map(|s| s.success()).unwrap_or(false);
+        let restarts = status.lock_or_recover().restarts;
+
+        let restart = match config.restart_policy.as_str() {
+            "always" => true,
+            "on_failure" => !success,
+            _ => false,
+        } && restarts < config.max_restarts;
+
+        set_state(&window, &status, |s| {
+            s.state = if restart {
+                "restarting"
+            } else if success {
+                "exited"
+            } else {
+                "crashed"
+            }
+            .to_string();
+            s.pid = None;
+            s.exit_code = exit_code;
+        });
+
+        if !restart {
+            return;
+        }
+
+        status.lock_or_recover().restarts += 1;
+        let delay = (1u64 << restarts.min(5)).min(MAX_RESTART_DELAY_SECS);
+        tokio::time::sleep(Duration::from_secs(delay)).await;
+    }
+}
+
+fn start_supervisor(window: Window, process_id: &str, config: DevProcessConfig, restarts: u32) -> DevProcess {
+    let status = Arc::new(Mutex::new(DevProcessStatus {
+        process_id: process_id.to_string(),
+        state: "starting".to_string(),
+        pid: None,
+        exit_code: None,
+        restarts,
+        ports: Vec::new(),
+        started_at: now_secs(),
+        program: config.program.clone(),
+        args: config.args.clone(),
+    }));
+    let logs = Arc::new(Mutex::new(VecDeque::new()));
+    let tree = Arc::new(Mutex::new(None));
+
+    let handle = tokio::spawn(supervise(window, config.clone(), status.clone(), logs.clone(), tree.clone()));
+
+    DevProcess {
+        config,
+        status,
+        logs,
+        tree,
+        supervisor: handle.abort_handle(),
+    }
+}
+
+/// Dropping the run's `ProcessTree` kills the child and everything it
+/// started.
+fn stop_process(window: &Window, process: &DevProcess) {
+    process.supervisor.abort();
+    process.tree.lock_or_recover().take();
+    set_state(window, &process.status, |s| {
+        s.state = "stopped".to_string();
+        s.pid = None;
+    });
+}
+
+/// Starts a supervised background process. Output lines arrive as
+/// `dev-process-output` events and state changes (including detected ports)
+/// as `dev-process-status`.
+#[tauri::command]
+pub async fn start_dev_process(
+    window: Window,
+    process_id: String,
+    config: DevProcessConfig,
+    state: State<'_, DevProcessState>,
+) -> Result<DevProcessStatus, String> {
+    let mut processes = state.processes.lock_or_recover();
+    if let Some(existing) = processes.get(&process_id) {
+        if !existing.supervisor.is_finished() {
+            return Err(format!("Process already running: {}", process_id));
+        }
+    }
+
+    let process = start_supervisor(window, &process_id, config, 0);
+    let status = process.status.lock_or_recover().clone();
+    processes.insert(process_id, process);
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn stop_dev_process(
+    window: Window,
+    process_id: String,
+    state: State<'_, DevProcessState>,
+) -> Result<(), String> {
+    let processes = state.processes.lock_or_recover();
+    let process = processes
+        .get(&process_id)
+        .ok_or_else(|| format!("Process not found: {}", process_id))?;
+    stop_process(&window, process);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn restart_dev_process(
+    window: Window,
+    process_id: String,
+    state: State<'_, DevProcessState>,
+) -> Result<DevProcessStatus, String> {
+    let mut processes = state.processes.lock_or_recover();
+    let previous = processes
+        .remove(&process_id)
+        .ok_or_else(|| format!("Process not found: {}", process_id))?;
+    stop_process(&window, &previous);
+
+    let restarts = previous.status.lock_or_recover().restarts + 1;
+    let process = start_supervisor(window, &process_id, previous.config, restarts);
+    let status = process.status.lock_or_recover().clone();
+    processes.insert(process_id, process);
+    Ok(status)
+}
+
+/// Returns the last `limit` log lines of a process, oldest first.
+#[tauri::command]
+pub fn get_dev_process_logs(
+    process_id: String,
+    limit: Option<usize>,
+    state: State<'_, DevProcessState>,
+) -> Result<Vec<DevLogLine>, String> {
+    let processes = state.processes.lock_or_recover();
+    let process = processes
+        .get(&process_id)
+        .ok_or_else(|| format!("Process not found: {}", process_id))?;
+
+    let logs = process.logs.lock_or_recover();
+    let limit = limit.unwrap_or(MAX_DEV_LOG_LINES);
+    Ok(logs.iter().skip(logs.len().saturating_sub(limit)).cloned().collect())
+}
+
+#[tauri::command]
+pub fn get_dev_process_status(
+    process_id: String,
+    state: State<'_, DevProcessState>,
+) -> Result<DevProcessStatus, String> {
+    let processes = state.processes.lock_or_recover();
+    let process = processes
+        .get(&process_id)
+        .ok_or_else(|| format!("Process not found: {}", process_id))?;
+    let status = process.status.lock_or_recover().clone();
+    Ok(status)
+}
+
+#[tauri::command]
+pub fn list_dev_processes(state: State<'_, DevProcessState>) -> Vec<DevProcessStatus> {
+    let processes = state.processes.lock_or_recover();
+    let mut statuses: Vec<DevProcessStatus> =
+        processes.values().map(|p| p.status.lock_or_recover().clone()).collect();
+    statuses.sort_by(|a, b| a.process_id.cmp(&b.process_id));
+    statuses
+}
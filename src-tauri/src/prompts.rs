@@ -0,0 +1,252 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::State;
+
+use crate::locks::LockExt;
+
+// ============================================================================
+// PROMPT TEMPLATE STATE
+// ============================================================================
+
+pub struct PromptState {
+    conn: Mutex<Connection>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PromptTemplate {
+    pub id: String,
+    pub name: String,
+    pub description: String,
+    pub template: String,
+    pub variables: Vec<String>,
+    pub builtin: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PromptTemplateInput {
+    pub id: Option<String>,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub template: String,
+}
+
+const BUILTIN_TEMPLATES: &[(&str, &str, &str, &str)] = &[
+    (
+        "explain",
+        "Explain",
+        "Explain what the selected code does",
+        "Explain what the following {{language}} code from `{{file_path}}` does. Describe its purpose, inputs, outputs and any non-obvious behaviour.\n\n```{{language}}\n{{selected_code}}\n```",
+    ),
+    (
+        "write_tests",
+        "Write tests",
+        "Generate unit tests for the selected code",
+        "Write thorough unit tests for the following {{language}} code from `{{file_path}}`. Cover normal cases, edge cases and error handling, using the testing conventions of the project.\n\n```{{language}}\n{{selected_code}}\n```",
+    ),
+    (
+        "find_bugs",
+        "Find bugs",
+        "Look for bugs in the selected code",
+        "Review the following {{language}} code from `{{file_path}}` for bugs, edge cases and incorrect assumptions. For each problem give the line, why it is wrong and a fix.\n\n```{{language}}\n{{selected_code}}\n```\n\nProject context:\n{{graph_context}}",
+    ),
+];
+
+/// Returns the distinct `{{variable}}` names used in a template, in order of
+/// first appearance.
+pub(crate) fn template_variables(template: &str) -> Vec<String> {
+    let mut variables = Vec::new();
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let name = after[..end].trim().to_string();
+                if !name.is_empty() && !variables.contains(&name) {
+                    variables.push(name);
+                }
+                rest = &after[end + 2..];
+            }
+            None => break,
+        }
+    }
+
+    variables
+}
+
+/// Substitutes every `{{variable}}` in `template`. Fails if a variable has no
+/// value so that half-rendered prompts never reach the model.
+pub(crate) fn render_template(template: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let missing: Vec<String> = template_variables(template)
+        .into_iter()
+        .filter(|v| !vars.contains_key(v))
+        .collect();
+    if !missing.is_empty() {
+        return Err(format!("Missing template variables: {}", missing.join(", ")));
+    }
+
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find("{{") {
+        output.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        match after.find("}}") {
+            Some(end) => {
+                let name = after[..end].trim();
+                match vars.get(name) {
+                    Some(value) => output.push_str(value),
+                    None => output.push_str(&rest[start..start + 2 + end + 2]),
+                }
+                rest = &after[end + 2..];
+            }
+            None => {
+                output.push_str(&rest[start..]);
+                rest = "";
+            }
+        }
+    }
+    output.push_str(rest);
+
+    Ok(output)
+}
+
+fn slugify(name: &str) -> String {
+    let slug: String = name
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    slug.trim_matches('_').to_string()
+}
+
+fn row_to_template(row: &rusqlite::Row) -> rusqlite::Result<PromptTemplate> {
+    let template: String = row.get(3)?;
+    Ok(PromptTemplate {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        description: row.get(2)?,
+        variables: template_variables(&template),
+        template,
+        builtin: row.get(4)?,
+    })
+}
+
+impl PromptState {
+    pub fn open(db_path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(db_path)
+            .map_err(|e| format!("Failed to open prompt database: {}", e))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS prompt_templates (
+                 id TEXT PRIMARY KEY,
+                 name TEXT NOT NULL,
+                 description TEXT NOT NULL,
+                 template TEXT NOT NULL,
+                 builtin INTEGER NOT NULL DEFAULT 0
+             );
+             CREATE TABLE IF NOT EXISTS deleted_builtins (
+                 id TEXT PRIMARY KEY
+             );",
+        )
+        .map_err(|e| format!("Failed to initialize prompt database: {}", e))?;
+
+        // Seed built-in actions the user hasn't deleted; edits are kept and
+        // deletions are remembered so they stay deleted
+        for (id, name, description, template) in BUILTIN_TEMPLATES {
+            conn.execute(
+                "INSERT OR IGNORE INTO prompt_templates (id, name, description, template, builtin)
+                 SELECT ?1, ?2, ?3, ?4, 1 WHERE NOT EXISTS (SELECT 1 FROM deleted_builtins WHERE id = ?1)",
+                params![id, name, description, template],
+            )
+            .map_err(|e| e.to_string())?;
+        }
+
+        Ok(PromptState {
+            conn: Mutex::new(conn),
+        })
+    }
+
+    pub fn get(&self, id: &str) -> Result<PromptTemplate, String> {
+        let conn = self.conn.lock_or_recover();
+        conn.query_row(
+            "SELECT id, name, description, template, builtin FROM prompt_templates WHERE id = ?1",
+            params![id],
+            row_to_template,
+        )
+        .optional()
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("Prompt template not found: {}", id))
+    }
+}
+
+// ============================================================================
+// PROMPT TEMPLATE TAURI COMMANDS
+// ============================================================================
+
+#[tauri::command]
+pub fn list_prompt_templates(state: State<'_, PromptState>) -> Result<Vec<PromptTemplate>, String> {
+    let conn = state.conn.lock_or_recover();
+    let mut stmt = conn
+        .prepare("SELECT id, name, description, template, builtin FROM prompt_templates ORDER BY builtin DESC, name ASC")
+        .map_err(|e| e.to_string())?;
+
+    let rows = stmt.query_map([], row_to_template).map_err(|e| e.to_string())?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_prompt_template(id: String, state: State<'_, PromptState>) -> Result<PromptTemplate, String> {
+    state.get(&id)
+}
+
+#[tauri::command]
+pub fn save_prompt_template(
+    input: PromptTemplateInput,
+    state: State<'_, PromptState>,
+) -> Result<PromptTemplate, String> {
+    let id = input.id.unwrap_or_else(|| slugify(&input.name));
+    if id.is_empty() {
+        return Err("Prompt template needs a name".to_string());
+    }
+
+    {
+        let conn = state.conn.lock_or_recover();
+        conn.execute(
+            "INSERT INTO prompt_templates (id, name, description, template, builtin)
+             VALUES (?1, ?2, ?3, ?4, 0)
+             ON CONFLICT(id) DO UPDATE SET name = ?2, description = ?3, template = ?4",
+            params![id, input.name, input.description, input.template],
+        )
+        .map_err(|e| format!("Failed to save prompt template: {}", e))?;
+    }
+
+    state.get(&id)
+}
+
+#[tauri::command]
+pub fn delete_prompt_template(id: String, state: State<'_, PromptState>) -> Result<(), String> {
+    let conn = state.conn.lock_or_recover();
+    conn.execute(
+        "INSERT OR IGNORE INTO deleted_builtins (id) SELECT id FROM prompt_templates WHERE id = ?1 AND builtin = 1",
+        params![id],
+    )
+    .map_err(|e| format!("Failed to delete prompt template: {}", e))?;
+    conn.execute("DELETE FROM prompt_templates WHERE id = ?1", params![id])
+        .map_err(|e| format!("Failed to delete prompt template: {}", e))?;
+    Ok(())
+}
+
+#[tauri::command]
+pub fn render_prompt(
+    template_id: String,
+    vars: HashMap<String, String>,
+    state: State<'_, PromptState>,
+) -> Result<String, String> {
+    let template = state.get(&template_id)?;
+    render_template(&template.template, &vars)
+}
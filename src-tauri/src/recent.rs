@@ -0,0 +1,178 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::State;
+
+use crate::files::resolve_path;
+use crate::locks::LockExt;
+
+const HALF_LIFE_SECS: f64 = 3.0 * 24.0 * 60.0 * 60.0;
+const DEFAULT_LIMIT: usize = 20;
+const MAX_ENTRIES_PER_PROJECT: i64 = 500;
+
+// ============================================================================
+// RECENT FILES STATE
+// ============================================================================
+
+/// Opened files per project, ranked by frecency: every open adds one point
+/// and points halve every three days.
+pub struct RecentFilesState {
+    conn: Mutex<Connection>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RecentFile {
+    pub path: String,
+    pub project: String,
+    pub open_count: i64,
+    pub last_opened: i64,
+    pub score: f64,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+fn decayed(score: f64, since: i64, now: i64) -> f64 {
+    score * 0.5f64.powf((now - since).max(0) as f64 / HALF_LIFE_SECS)
+}
+
+/// The project a file belongs to: the given root, else its repository's
+/// working directory, else its parent directory.
+fn project_for(path: &Path, project: Option<String>) -> String {
+    if let Some(project) = project {
+        return resolve_path(Path::new(&project)).to_string_lossy().to_string();
+    }
+    let root = git2::Repository::discover(path)
+        .ok()
+        .and_then(|repo| repo.workdir().map(|w| w.to_path_buf()))
+        .or_else(|| path.parent().map(|p| p.to_path_buf()))
+        .unwrap_or_default();
+    resolve_path(&root).to_string_lossy().to_string()
+}
+
+impl RecentFilesState {
+    pub fn open(db_path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(db_path)
+            .map_err(|e| format!("Failed to open recent files database: {}", e))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS recent_files (
+                 project TEXT NOT NULL,
+                 path TEXT NOT NULL,
+                 open_count INTEGER NOT NULL,
+                 last_opened INTEGER NOT NULL,
+                 score REAL NOT NULL,
+                 PRIMARY KEY (project, path)
+             );",
+        )
+        .map_err(|e| format!("Failed to initialize recent files database: {}", e))?;
+
+        Ok(RecentFilesState {
+            conn: Mutex::new(conn),
+        })
+    }
+}
+
+// ============================================================================
+// RECENT FILES TAURI COMMANDS
+// ============================================================================
+
+/// Records that `path` was opened. `project` defaults to the file's
+/// repository root.
+#[tauri::command]
+pub fn record_file_open(
+    path: String,
+    project: Option<String>,
+    state: State<'_, RecentFilesState>,
+) -> Result<(), String> {
+    let file_path = resolve_path(Path::new(&path));
+    let project = project_for(&file_path, project);
+    let path = file_path.to_string_lossy().to_string();
+    let now = now_secs();
+
+    let conn = state.conn.lock_or_recover();
+    let existing: Option<(f64, i64)> = conn
+        .query_row(
+            "SELECT score, last_opened FROM recent_files WHERE project = ?1 AND path = ?2",
+            params![project, path],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+        .optional()
+        .map_err(|e| e.to_string())?;
+    let score = existing.map(|(score, since)| decayed(score, since, now)).unwrap_or(0.0) + 1.0;
+
+    conn.execute(
+        "INSERT INTO recent_files (project, path, open_count, last_opened, score)
+         VALUES (?1, ?2, 1, ?3, ?4)
+         ON CONFLICT(project, path) DO UPDATE SET
+             open_count = open_count + 1, last_opened = ?3, score = ?4",
+        params![project, path, now, score],
+    )
+    .map_err(|e| e.to_string())?;
+
+    // Forget the least recently opened files of big projects
+    conn.execute(
+        "DELETE FROM recent_files WHERE project = ?1 AND path NOT IN (
+             SELECT path FROM recent_files WHERE project = ?1 ORDER BY last_opened DESC LIMIT ?2
+         )",
+        params![project, MAX_ENTRIES_PER_PROJECT],
+    )
+    .map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+/// Recently opened files of `project` that still exist, best frecency
+/// first.
+#[tauri::command]
+pub fn get_recent_files(
+    project: String,
+    limit: Option<usize>,
+    state: State<'_, RecentFilesState>,
+) -> Result<Vec<RecentFile>, String> {
+    let project = resolve_path(Path::new(&project)).to_string_lossy().to_string();
+    let now = now_secs();
+
+    let conn = state.conn.lock_or_recover();
+    let mut stmt = conn
+        .prepare("SELECT path, project, open_count, last_opened, score FROM recent_files WHERE project = ?1")
+        .map_err(|e| e.to_string())?;
+    let rows = stmt
+        .query_map(params![project], |row| {
+            let last_opened: i64 = row.get(3)?;
+            let score: f64 = row.get(4)?;
+            Ok(RecentFile {
+                path: row.get(0)?,
+                project: row.get(1)?,
+                open_count: row.get(2)?,
+                last_opened,
+                score: decayed(score, last_opened, now),
+            })
+        })
+        .map_err(|e| e.to_string())?;
+
+    let mut files: Vec<RecentFile> = rows
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|e| e.to_string())?
+        .into_iter()
+        .filter(|file| Path::new(&file.path).exists())
+        .collect();
+    files.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| b.last_opened.cmp(&a.last_opened)));
+    files.truncate(limit.unwrap_or(DEFAULT_LIMIT));
+    Ok(files)
+}
+
+/// Clears the recently opened list of `project`.
+#[tauri::command]
+pub fn clear_recent_files(project: String, state: State<'_, RecentFilesState>) -> Result<(), String> {
+    let project = resolve_path(Path::new(&project)).to_string_lossy().to_string();
+    let conn = state.conn.lock_or_recover();
+    conn.execute("DELETE FROM recent_files WHERE project = ?1", params![project])
+        .map_err(|e| e.to_string())?;
+    Ok(())
+}
@@ -0,0 +1,235 @@
+use serde::{Deserialize, Serialize};
+use std::fs as std_fs;
+use std::path::PathBuf;
+use tauri::{AppHandle, Manager, State};
+
+use crate::locks::LockExt;
+use crate::references::{collect_references, resolve_target, FileReferences};
+use crate::sandbox::WorkspaceState;
+use crate::search::{content_hash, write_all_or_none, ReplaceState};
+use crate::symbols::{LineIndex, SourceRange, SymbolIndexState, SymbolInfo, SymbolTable};
+use crate::ParserState;
+
+const MAX_PREVIEW_CHARS: usize = 300;
+
+// ============================================================================
+// RENAME STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct RenameOptions {
+    /// Write the edits; otherwise only the preview is returned.
+    pub apply: bool,
+    /// Also rename uses that only match by name.
+    pub include_possible: bool,
+    /// Apply even when the new name collides with an existing symbol.
+    pub force: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenameEdit {
+    pub range: SourceRange,
+    /// Reference kind from `find_references`.
+    pub kind: String,
+    pub confidence: String,
+    pub preview_before: String,
+    pub preview_after: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileRenamePreview {
+    pub path: String,
+    pub relative_path: String,
+    pub content_hash: String,
+    pub edits: Vec<RenameEdit>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RenameResult {
+    pub symbol: SymbolInfo,
+    pub new_name: String,
+    pub files: Vec<FileRenamePreview>,
+    pub total_edits: usize,
+    /// Name-only matches left out because `include_possible` was off.
+    pub skipped_possible: usize,
+    /// Existing symbols the new name would clash with or be shadowed by.
+    pub conflicts: Vec<String>,
+    pub applied: bool,
+    /// Pass to `undo_replacements` to revert an applied rename.
+    pub undo_id: Option<String>,
+}
+
+// ============================================================================
+// RENAME PLANNING
+// ============================================================================
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+fn preview(line: &str) -> String {
+    line.trim().chars().take(MAX_PREVIEW_CHARS).collect()
+}
+
+/// Symbols already named `new_name` that the rename would collide with:
+/// a sibling of the target, or a definition a renamed use would resolve
+/// to instead.
+fn rename_conflicts(table: &SymbolTable, target: &SymbolInfo, new_name: &str, files: &[FileReferences]) -> Vec<String> {
+    let mut conflicts = Vec::new();
+    let mut push = |symbol: &SymbolInfo| {
+        let conflict = format!(
+            "{} `{}` already defined at {}:{}",
+            symbol.kind,
+            symbol.name,
+            symbol.relative_path,
+            symbol.name_range.start.line + 1
+        );
+        if !conflicts.contains(&conflict) {
+            conflicts.push(conflict);
+        }
+    };
+
+    if let Some(file) = table.files.get(&target.relative_path) {
+        for symbol in &file.symbols {
+            if symbol.name == new_name && symbol.container == target.container && symbol.scope == target.scope {
+                push(symbol);
+            }
+        }
+    }
+    for file in files {
+        for reference in &file.references {
+            let candidates = table.definitions(&file.relative_path, new_name, None, reference.range.start);
+            if let Some(candidate) = candidates.first().filter(|c| c.reason != "project") {
+                push(&candidate.symbol);
+            }
+        }
+    }
+    conflicts
+}
+
+/// Edits for one file and its renamed content. Each range is checked to
+/// still hold the old name.
+fn plan_file(
+    file: &FileReferences,
+    old_name: &str,
+    new_name: &str,
+    include_possible: bool,
+) -> Result<(FileRenamePreview, String, String), String> {
+    let content = std_fs::read_to_string(&file.path).map_err(|e| format!("Failed to read {}: {}", file.path, e))?;
+    let lines = LineIndex::new(&content);
+
+    let mut edits = Vec::new();
+    let mut updated = String::with_capacity(content.len());
+    let mut last = 0;
+    for reference in &file.references {
+        if reference.confidence != "exact" && !include_possible {
+            continue;
+        }
+        let start = lines.offset(reference.range.start);
+        let end = lines.offset(reference.range.end);
+        if start < last || content.get(start..end) != Some(old_name) {
+            return Err(format!("{} changed since it was indexed; index symbols again", file.path));
+        }
+        updated.push_str(&content[last..start]);
+        updated.push_str(new_name);
+        last = end;
+
+        let line_start = content[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+        let line_end = content[end..].find('\n').map(|i| end + i).unwrap_or(content.len());
+        edits.push(RenameEdit {
+            range: reference.range,
+            kind: reference.kind.clone(),
+            confidence: reference.confidence.clone(),
+            preview_before: preview(&content[line_start..line_end]),
+            preview_after: preview(&format!("{}{}{}", &content[line_start..start], new_name, &content[end..line_end])),
+        });
+    }
+    updated.push_str(&content[last..]);
+
+    let preview = FileRenamePreview {
+        path: file.path.clone(),
+        relative_path: file.relative_path.clone(),
+        content_hash: content_hash(&content),
+        edits,
+    };
+    Ok((preview, content, updated))
+}
+
+// ============================================================================
+// RENAME TAURI COMMANDS
+// ============================================================================
+
+/// Renames a symbol and every use `find_references` resolves to it. By
+/// default only previews the edits, grouped by file; with `apply` the
+/// files are written together, restoring all of them if any write fails.
+/// Needs `index_symbols` first.
+#[tauri::command]
+pub async fn rename_symbol(
+    app: AppHandle,
+    symbol_id: String,
+    new_name: String,
+    options: Option<RenameOptions>,
+    state: State<'_, SymbolIndexState>,
+) -> Result<RenameResult, String> {
+    let new_name = new_name.trim().to_string();
+    if !is_valid_name(&new_name) {
+        return Err(format!("Not a valid identifier: {}", new_name));
+    }
+    let options = options.unwrap_or_default();
+    let table = state.table.clone();
+    tokio::task::spawn_blocking(move || {
+        let parser = app.state::<ParserState>();
+        let mut table = table.lock_or_recover();
+        let symbol = resolve_target(&mut table, &parser, Some(&symbol_id), None, 0, 0)?;
+        if symbol.name == new_name {
+            return Err(format!("Symbol is already named {}", new_name));
+        }
+        let references = collect_references(&mut table, &parser, &symbol);
+        let conflicts = rename_conflicts(&table, &symbol, &new_name, &references);
+        drop(table);
+
+        let mut files = Vec::new();
+        let mut planned: Vec<(PathBuf, String, String)> = Vec::new();
+        let mut skipped_possible = 0;
+        for file in &references {
+            let (preview, original, updated) = plan_file(file, &symbol.name, &new_name, options.include_possible)?;
+            skipped_possible += file.references.len() - preview.edits.len();
+            if !preview.edits.is_empty() {
+                planned.push((PathBuf::from(&file.path), original, updated));
+                files.push(preview);
+            }
+        }
+        let total_edits = files.iter().map(|f| f.edits.len()).sum();
+
+        let mut undo_id = None;
+        if options.apply {
+            if !conflicts.is_empty() && !options.force {
+                return Err(format!("Renaming to {} conflicts: {}", new_name, conflicts.join("; ")));
+            }
+            let workspace = app.state::<WorkspaceState>();
+            for (path, _, _) in &planned {
+                workspace.check(path)?;
+            }
+            write_all_or_none(&planned)?;
+            undo_id = Some(app.state::<ReplaceState>().record("rename", planned));
+        }
+
+        Ok(RenameResult {
+            symbol,
+            new_name,
+            files,
+            total_edits,
+            skipped_possible,
+            conflicts,
+            applied: options.apply,
+            undo_id,
+        })
+    })
+    .await
+    .map_err(|e| format!("Rename failed: {}", e))?
+}
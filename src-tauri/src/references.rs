@@ -0,0 +1,243 @@
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+use tree_sitter::Node;
+
+use crate::files::read_text;
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+use crate::symbols::{
+    definitions_at, is_identifier, node_text, qualifier_of, LineIndex, SourceRange, SymbolIndexState, SymbolInfo,
+    SymbolTable,
+};
+use crate::ParserState;
+
+const MAX_LINE_CHARS: usize = 200;
+
+/// Ancestors that make an identifier part of an import.
+const IMPORT_NODES: &[&str] = &[
+    "import_statement",
+    "import_from_statement",
+    "use_declaration",
+    "import_declaration",
+    "import_spec",
+    "preproc_include",
+];
+
+const CALL_NODES: &[&str] = &[
+    "call_expression",
+    "call",
+    "method_invocation",
+    "macro_invocation",
+    "new_expression",
+    "object_creation_expression",
+];
+
+/// Member accesses whose last child is the accessed name.
+const MEMBER_NODES: &[&str] = &[
+    "member_expression",
+    "field_expression",
+    "attribute",
+    "selector_expression",
+    "scoped_identifier",
+    "qualified_identifier",
+    "field_access",
+];
+
+// ============================================================================
+// REFERENCE STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SymbolReference {
+    pub range: SourceRange,
+    /// "definition", "import", "call" or "reference".
+    pub kind: String,
+    /// "exact" when the use resolves to the symbol, "possible" when it only
+    /// matches by name, like a method called on an object of unknown type.
+    pub confidence: String,
+    pub line_text: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FileReferences {
+    pub path: String,
+    pub relative_path: String,
+    pub references: Vec<SymbolReference>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReferencesResult {
+    pub symbol: SymbolInfo,
+    pub files: Vec<FileReferences>,
+    pub total: usize,
+}
+
+// ============================================================================
+// REFERENCE SEARCH
+// ============================================================================
+
+fn usage_kind(node: Node) -> &'static str {
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        if IMPORT_NODES.contains(&parent.kind()) {
+            return "import";
+        }
+        current = parent;
+    }
+
+    // The callee is the identifier itself or the member access ending in it
+    let callee = match node.parent() {
+        Some(parent) if MEMBER_NODES.contains(&parent.kind()) => parent,
+        _ => node,
+    };
+    let is_call = callee.parent().is_some_and(|call| {
+        CALL_NODES.contains(&call.kind())
+            && (call.named_child(0).is_some_and(|first| first.id() == callee.id())
+                || call.child_by_field_name("name").is_some_and(|name| name.id() == node.id()))
+    });
+    if is_call {
+        "call"
+    } else {
+        "reference"
+    }
+}
+
+/// The symbol named by `symbol_id`, or defined at the identifier under
+/// `path`/`line`/`col`.
+pub(crate) fn resolve_target(
+    table: &mut SymbolTable,
+    parser: &ParserState,
+    symbol_id: Option<&str>,
+    path: Option<&str>,
+    line: usize,
+    col: usize,
+) -> Result<SymbolInfo, String> {
+    if let Some(id) = symbol_id {
+        let relative = id.split('#').next().unwrap_or_default().to_string();
+        table.refresh(parser, &relative);
+        return table.symbol(id).cloned().ok_or_else(|| format!("Symbol not found: {}", id));
+    }
+
+    let path = path.ok_or_else(|| "Either a symbol id or a path and position is required".to_string())?;
+    let candidates = definitions_at(table, parser, path, line, col)?;
+    match candidates.into_iter().next() {
+        Some(candidate) if candidate.symbol.kind != "module" => Ok(candidate.symbol),
+        _ => Err(format!("No symbol definition found at {}:{}:{}", path, line + 1, col + 1)),
+    }
+}
+
+/// Every use of `target`, grouped by file in path order. Each identifier
+/// with the symbol's name is resolved the way go-to-definition would, so
+/// shadowing locals and unrelated same-named symbols are left out.
+pub(crate) fn collect_references(table: &mut SymbolTable, parser: &ParserState, target: &SymbolInfo) -> Vec<FileReferences> {
+    // Locals can't be seen from other files
+    let mut relatives: Vec<String> = if target.local {
+        vec![target.relative_path.clone()]
+    } else {
+        table.files.keys().cloned().collect()
+    };
+    relatives.sort();
+
+    let mut sources = Vec::new();
+    for relative in relatives {
+        if !table.refresh(parser, &relative) {
+            continue;
+        }
+        if let Ok((content, _)) = read_text(&table.root.join(&relative)) {
+            if content.contains(&target.name) {
+                sources.push((relative, content));
+            }
+        }
+    }
+
+    let mut files = Vec::new();
+    for (relative, content) in sources {
+        let path = table.root.join(&relative).to_string_lossy().to_string();
+        let Some((_, tree)) = parser.parse_tree(&path, &content) else {
+            continue;
+        };
+        let lines = LineIndex::new(&content);
+        let source = content.as_bytes();
+        let text_lines: Vec<&str> = content.lines().collect();
+
+        let mut references = Vec::new();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            let mut cursor = node.walk();
+            stack.extend(node.named_children(&mut cursor));
+            if !is_identifier(node.kind()) || node_text(node, source) != target.name {
+                continue;
+            }
+
+            let range = lines.range(node);
+            let (kind, confidence) = if relative == target.relative_path && range == target.name_range {
+                ("definition", "exact")
+            } else {
+                let candidates = table.definitions(&relative, &target.name, qualifier_of(node, source), range.start);
+                let confidence = match candidates.iter().position(|c| c.symbol.id == target.id) {
+                    Some(0) if candidates[0].reason != "project" => "exact",
+                    Some(_) if candidates.iter().all(|c| c.reason == "project") => "possible",
+                    _ => continue,
+                };
+                (usage_kind(node), confidence)
+            };
+
+            let line_text = text_lines.get(range.start.line).copied().unwrap_or_default();
+            references.push(SymbolReference {
+                range,
+                kind: kind.to_string(),
+                confidence: confidence.to_string(),
+                line_text: line_text.trim().chars().take(MAX_LINE_CHARS).collect(),
+            });
+        }
+
+        if !references.is_empty() {
+            references.sort_by_key(|r| r.range.start);
+            files.push(FileReferences {
+                path,
+                relative_path: relative,
+                references,
+            });
+        }
+    }
+    files
+}
+
+// ============================================================================
+// REFERENCE TAURI COMMANDS
+// ============================================================================
+
+/// All usage sites of a symbol, given by id or by a position (0-based line
+/// and character column) on any of its uses. Needs `index_symbols` first.
+#[tauri::command]
+pub async fn find_references(
+    app: AppHandle,
+    symbol_id: Option<String>,
+    path: Option<String>,
+    line: Option<usize>,
+    col: Option<usize>,
+    state: State<'_, SymbolIndexState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<ReferencesResult, String> {
+    if let Some(path) = &path {
+        workspace.check(path)?;
+    }
+    let table = state.table.clone();
+    tokio::task::spawn_blocking(move || {
+        let parser = app.state::<ParserState>();
+        let mut table = table.lock_or_recover();
+        let symbol = resolve_target(
+            &mut table,
+            &parser,
+            symbol_id.as_deref(),
+            path.as_deref(),
+            line.unwrap_or(0),
+            col.unwrap_or(0),
+        )?;
+        let files = collect_references(&mut table, &parser, &symbol);
+        let total = files.iter().map(|f| f.references.len()).sum();
+        Ok(ReferencesResult { symbol, files, total })
+    })
+    .await
+    .map_err(|e| format!("Reference search failed: {}", e))?
+}
@@ -0,0 +1,267 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::{AppHandle, Manager, State};
+
+use crate::code_index::{CodeIndexState, IndexQueryFilters};
+use crate::embeddings::{embed_texts, EmbeddingIndexState};
+use crate::finder::fuzzy_score;
+use crate::llm::LlmState;
+use crate::locks::LockExt;
+use crate::symbols::SymbolIndexState;
+
+const DEFAULT_K: usize = 20;
+/// Candidates taken from each retriever per requested hit.
+const CANDIDATE_FACTOR: usize = 3;
+/// Reciprocal-rank fusion constant; larger values flatten rank differences.
+const RRF_K: f64 = 60.0;
+const SNIPPET_LINES: usize = 3;
+
+// ============================================================================
+// HYBRID SEARCH STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HitSource {
+    /// "keyword", "symbol" or "vector".
+    pub source: String,
+    /// 1-based rank within that retriever.
+    pub rank: usize,
+    /// The retriever's own score: BM25, fuzzy match score or cosine
+    /// similarity.
+    pub score: f64,
+    /// What matched: the symbol name or the chunk's symbols.
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct HybridHit {
+    pub path: String,
+    pub relative_path: String,
+    /// 0-based lines of the best located match (symbol or chunk).
+    pub start_line: Option<usize>,
+    pub end_line: Option<usize>,
+    pub snippet: String,
+    /// Sum of 1 / (60 + rank) over the retrievers that found the file.
+    pub score: f64,
+    pub sources: Vec<HitSource>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HybridSearchResult {
+    pub hits: Vec<HybridHit>,
+    /// Retrievers that were available and searched.
+    pub searched: Vec<String>,
+    /// Why a retriever was skipped, e.g. an index that was never built.
+    pub warnings: Vec<String>,
+}
+
+/// One retriever's result for a file, best first within its list.
+struct Candidate {
+    path: String,
+    relative_path: String,
+    score: f64,
+    detail: Option<String>,
+    lines: Option<(usize, usize)>,
+    snippet: String,
+}
+
+// ============================================================================
+// RETRIEVERS
+// ============================================================================
+
+fn keyword_candidates(app: &AppHandle, query: &str, limit: usize) -> Result<Vec<Candidate>, String> {
+    let index = app.state::<CodeIndexState>().current()?;
+    let filters = IndexQueryFilters {
+        limit: Some(limit),
+        ..Default::default()
+    };
+    let hits = index.search(query, &filters)?;
+    Ok(hits
+        .into_iter()
+        .map(|hit| Candidate {
+            snippet: hit.snippets.iter().map(|s| s.text.as_str()).collect::<Vec<_>>().join("\n"),
+            lines: hit.snippets.first().map(|s| (s.line - 1, s.line - 1)),
+            path: hit.path,
+            relative_path: hit.relative_path,
+            score: hit.score as f64,
+            detail: None,
+        })
+        .collect())
+}
+
+/// Files ranked by their best fuzzy symbol-name match. Every query term
+/// that matches adds to a symbol's score; more matched terms rank first.
+fn symbol_candidates(app: &AppHandle, query: &str, limit: usize) -> Result<Vec<Candidate>, String> {
+    let terms: Vec<Vec<char>> = query
+        .split_whitespace()
+        .filter(|t| t.chars().count() > 1)
+        .map(|t| t.chars().collect())
+        .collect();
+    let state = app.state::<SymbolIndexState>();
+    let table = state.table.lock_or_recover();
+    if table.root.as_os_str().is_empty() {
+        return Err("Symbols not indexed; call index_symbols first".to_string());
+    }
+
+    let mut best: HashMap<&str, ((usize, i64), Candidate)> = HashMap::new();
+    for (relative, file) in &table.files {
+        for symbol in file.symbols.iter().filter(|s| !s.local) {
+            let mut matched = 0;
+            let mut score = 0;
+            for term in &terms {
+                if let Some((term_score, _)) = fuzzy_score(term, &symbol.name, false) {
+                    matched += 1;
+                    score += term_score;
+                }
+            }
+            if matched == 0 || best.get(relative.as_str()).is_some_and(|(key, _)| *key >= (matched, score)) {
+                continue;
+            }
+            let candidate = Candidate {
+                path: symbol.path.clone(),
+                relative_path: relative.clone(),
+                score: score as f64,
+                detail: Some(symbol.name.clone()),
+                lines: Some((symbol.range.start.line, symbol.range.end.line)),
+                snippet: format!("{} {}", symbol.kind, symbol.name),
+            };
+            best.insert(relative.as_str(), ((matched, score), candidate));
+        }
+    }
+
+    let mut ranked: Vec<((usize, i64), Candidate)> = best.into_values().collect();
+    ranked.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.relative_path.cmp(&b.1.relative_path)));
+    Ok(ranked.into_iter().take(limit).map(|(_, candidate)| candidate).collect())
+}
+
+/// Files ranked by their chunk most similar to the query embedding.
+fn vector_candidates(app: &AppHandle, query_vector: &[f32], limit: usize) -> Result<Vec<Candidate>, String> {
+    let state = app.state::<EmbeddingIndexState>();
+    let project = state.project()?;
+    // Several chunks of one file may rank high; over-fetch before grouping
+    let nearest = state.nearest(&project, query_vector, limit * CANDIDATE_FACTOR)?;
+
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for (relative, chunk, similarity) in nearest {
+        if candidates.len() >= limit {
+            break;
+        }
+        if candidates.iter().any(|c| c.relative_path == relative) {
+            continue;
+        }
+        candidates.push(Candidate {
+            path: project.root.join(&relative).to_string_lossy().to_string(),
+            relative_path: relative,
+            score: similarity as f64,
+            detail: (!chunk.symbols.is_empty()).then(|| chunk.symbols.join(", ")),
+            lines: Some((chunk.start_line, chunk.end_line)),
+            snippet: chunk.text.lines().take(SNIPPET_LINES).collect::<Vec<_>>().join("\n"),
+        });
+    }
+    Ok(candidates)
+}
+
+/// Reciprocal-rank fusion of per-retriever rankings. A file's location
+/// and snippet come from the first retriever that located it.
+fn fuse(rankings: Vec<(&str, Vec<Candidate>)>, k: usize) -> Vec<HybridHit> {
+    let mut hits: HashMap<String, HybridHit> = HashMap::new();
+    for (source, candidates) in rankings {
+        for (index, candidate) in candidates.into_iter().enumerate() {
+            let rank = index + 1;
+            let hit = hits.entry(candidate.relative_path.clone()).or_insert_with(|| HybridHit {
+                path: candidate.path.clone(),
+                relative_path: candidate.relative_path.clone(),
+                start_line: None,
+                end_line: None,
+                snippet: String::new(),
+                score: 0.0,
+                sources: Vec::new(),
+            });
+            hit.score += 1.0 / (RRF_K + rank as f64);
+            if hit.start_line.is_none() && source != "keyword" {
+                if let Some((start, end)) = candidate.lines {
+                    hit.start_line = Some(start);
+                    hit.end_line = Some(end);
+                    hit.snippet = candidate.snippet.clone();
+                }
+            }
+            if hit.snippet.is_empty() {
+                hit.snippet = candidate.snippet;
+            }
+            hit.sources.push(HitSource {
+                source: source.to_string(),
+                rank,
+                score: candidate.score,
+                detail: candidate.detail,
+            });
+        }
+    }
+
+    let mut hits: Vec<HybridHit> = hits.into_values().collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.relative_path.cmp(&b.relative_path)));
+    hits.truncate(k);
+    hits
+}
+
+// ============================================================================
+// HYBRID SEARCH TAURI COMMANDS
+// ============================================================================
+
+/// Searches the open project with the keyword index, fuzzy symbol names
+/// and embedding similarity, and merges the three rankings with
+/// reciprocal-rank fusion into one list of files. Retrievers whose index
+/// hasn't been built are skipped with a warning.
+#[tauri::command]
+pub async fn hybrid_search(
+    app: AppHandle,
+    query: String,
+    k: Option<usize>,
+    llm: State<'_, LlmState>,
+) -> Result<HybridSearchResult, String> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    let k = k.unwrap_or(DEFAULT_K).max(1);
+    let limit = k * CANDIDATE_FACTOR;
+
+    let mut warnings = Vec::new();
+    let query_vector = match app.state::<EmbeddingIndexState>().project() {
+        Ok(project) => match embed_texts(&llm, &project.model, std::slice::from_ref(&query)).await {
+            Ok(mut vectors) => vectors.pop(),
+            Err(e) => {
+                warnings.push(format!("vector: {}", e));
+                None
+            }
+        },
+        Err(e) => {
+            warnings.push(format!("vector: {}", e));
+            None
+        }
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let mut rankings = Vec::new();
+        let mut searched = Vec::new();
+        let mut run = |source: &'static str, result: Result<Vec<Candidate>, String>| match result {
+            Ok(candidates) => {
+                searched.push(source.to_string());
+                rankings.push((source, candidates));
+            }
+            Err(e) => warnings.push(format!("{}: {}", source, e)),
+        };
+        run("keyword", keyword_candidates(&app, &query, limit));
+        run("symbol", symbol_candidates(&app, &query, limit));
+        if let Some(vector) = &query_vector {
+            run("vector", vector_candidates(&app, vector, limit));
+        }
+
+        Ok(HybridSearchResult {
+            hits: fuse(rankings, k),
+            searched,
+            warnings,
+        })
+    })
+    .await
+    .map_err(|e| format!("Hybrid search failed: {}", e))?
+}
@@ -0,0 +1,145 @@
+use git2::Repository;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::git::working_tree_file_diffs;
+use crate::llm::LlmState;
+use crate::sandbox::WorkspaceState;
+use crate::structured::structured_chat;
+use crate::ChatMessage;
+
+const MAX_CHUNK_CHARS: usize = 8000;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReviewFinding {
+    pub file: String,
+    pub start_line: usize,
+    pub end_line: usize,
+    pub severity: String, // "info", "warning", "error"
+    pub comment: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewResponse {
+    #[serde(default)]
+    findings: Vec<ReviewFinding>,
+}
+
+/// Packs per-file diffs into prompt-sized chunks. Files larger than a chunk
+/// are split on hunk boundaries so every piece still carries its `@@` header.
+fn chunk_diffs(files: Vec<(String, String)>) -> Vec<String> {
+    let mut chunks = Vec::new();
+    let mut current = String::new();
+
+    let mut push = |piece: String, current: &mut String| {
+        if !current.is_empty() && current.len() + piece.len() > MAX_CHUNK_CHARS {
+            chunks.push(std::mem::take(current));
+        }
+        current.push_str(&piece);
+    };
+
+    for (path, diff) in files {
+        if diff.len() <= MAX_CHUNK_CHARS {
+            push(diff, &mut current);
+            continue;
+        }
+
+        // Keep the file header on every hunk so the model knows the path
+        let header = format!("--- a/{}\n+++ b/{}\n", path, path);
+        let mut hunk = String::new();
+        for line in diff.lines() {
+            if line.starts_with("@@") && !hunk.is_empty() {
+                push(format!("{}{}", header, hunk), &mut current);
+                hunk.clear();
+            }
+            if line.starts_with("@@") || !hunk.is_empty() {
+                hunk.push_str(line);
+                hunk.push('\n');
+            }
+        }
+        if !hunk.is_empty() {
+            push(format!("{}{}", header, hunk), &mut current);
+        }
+    }
+
+    if !current.is_empty() {
+        chunks.push(current);
+    }
+    chunks
+}
+
+fn review_schema() -> serde_json::Value {
+    serde_json::json!({
+        "type": "object",
+        "properties": {
+            "findings": {
+                "type": "array",
+                "items": {
+                    "type": "object",
+                    "properties": {
+                        "file": { "type": "string" },
+                        "start_line": { "type": "integer" },
+                        "end_line": { "type": "integer" },
+                        "severity": { "type": "string", "enum": ["info", "warning", "error"] },
+                        "comment": { "type": "string" }
+                    },
+                    "required": ["file", "start_line", "end_line", "severity", "comment"]
+                }
+            }
+        },
+        "required": ["findings"]
+    })
+}
+
+#[tauri::command]
+pub async fn review_changes(
+    repo_path: String,
+    model: String,
+    llm: State<'_, LlmState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<ReviewFinding>, String> {
+    let repo_path = workspace.check(&repo_path)?;
+    let files = tokio::task::spawn_blocking(move || {
+        let repo = Repository::open(&repo_path).map_err(|e| e.message().to_string())?;
+        working_tree_file_diffs(&repo)
+    })
+    .await
+    .map_err(|e| format!("Diff task failed: {}", e))??;
+
+    if files.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let schema = review_schema();
+    let mut findings = Vec::new();
+
+    for chunk in chunk_diffs(files) {
+        let messages = vec![
+            ChatMessage {
+                role: "system".to_string(),
+                content: "You are a meticulous code reviewer. Report real problems only: bugs, security issues, \
+                          race conditions, missing error handling and clear maintainability issues. \
+                          Line numbers refer to the new file as given by the hunk headers. \
+                          Return an empty findings list when the changes look fine."
+                    .to_string(),
+            },
+            ChatMessage {
+                role: "user".to_string(),
+                content: format!("Review this diff:\n\n```diff\n{}\n```", chunk),
+            },
+        ];
+
+        let value = structured_chat(&llm, &model, messages, &schema, 1).await?;
+        let response: ReviewResponse =
+            serde_json::from_value(value).map_err(|e| format!("Failed to parse review: {}", e))?;
+
+        findings.extend(response.findings.into_iter().map(|mut f| {
+            if f.end_line < f.start_line {
+                f.end_line = f.start_line;
+            }
+            f
+        }));
+    }
+
+    Ok(findings)
+}
@@ -0,0 +1,227 @@
+use serde::Serialize;
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use tauri::{AppHandle, State};
+use tauri_plugin_dialog::{DialogExt, MessageDialogButtons, MessageDialogKind};
+
+use crate::files::resolve_path;
+use crate::locks::LockExt;
+
+// ============================================================================
+// WORKSPACE STATE
+// ============================================================================
+
+/// Directories the filesystem and git commands may touch. Paths only get in
+/// through a native dialog the user answered: picking a folder, or
+/// confirming access to one. Folders confirmed once are trusted from then
+/// on.
+pub struct WorkspaceState {
+    roots: Mutex<Vec<PathBuf>>,
+    trusted: Mutex<Vec<PathBuf>>,
+    trust_file: PathBuf,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkspaceRoots {
+    pub roots: Vec<String>,
+    pub trusted: Vec<String>,
+}
+
+impl WorkspaceState {
+    pub fn open(trust_file: &Path) -> Result<Self, String> {
+        let trusted: Vec<PathBuf> = match std_fs::read_to_string(trust_file) {
+            Ok(json) => serde_json::from_str(&json).map_err(|e| format!("Failed to read trusted workspaces: {}", e))?,
+            Err(_) => Vec::new(),
+        };
+        Ok(WorkspaceState {
+            roots: Mutex::new(Vec::new()),
+            trusted: Mutex::new(trusted),
+            trust_file: trust_file.to_path_buf(),
+        })
+    }
+
+    /// Resolves `path` (symlinks and `..` included) and fails unless it lies
+    /// inside an open workspace root.
+    pub(crate) fn check(&self, path: impl AsRef<Path>) -> Result<PathBuf, String> {
+        let path = path.as_ref();
+        let resolved = resolve_path(path);
+        let roots = self.roots.lock_or_recover();
+        if roots.iter().any(|root| resolved.starts_with(root)) {
+            Ok(resolved)
+        } else {
+            Err(format!("Access denied: {} is outside the open workspace", path.display()))
+        }
+    }
+
+    pub(crate) fn check_all(&self, paths: &[String]) -> Result<(), String> {
+        for path in paths {
+            self.check(path)?;
+        }
+        Ok(())
+    }
+
+    fn add_root(&self, root: PathBuf) {
+        let mut roots = self.roots.lock_or_recover();
+        if !roots.contains(&root) {
+            roots.push(root);
+        }
+    }
+
+    fn trust(&self, root: &Path) -> Result<(), String> {
+        let mut trusted = self.trusted.lock_or_recover();
+        if trusted.iter().any(|t| t == root) {
+            return Ok(());
+        }
+        trusted.push(root.to_path_buf());
+        let json = serde_json::to_string_pretty(&*trusted).map_err(|e| e.to_string())?;
+        std_fs::write(&self.trust_file, json).map_err(|e| format!("Failed to save trusted workspaces: {}", e))
+    }
+}
+
+fn confirm_access(app: &AppHandle, path: &Path) -> bool {
+    app.dialog()
+        .message(format!(
+            "Allow GenCode to read and modify files in:\n\n{}",
+            path.display()
+        ))
+        .title("Allow folder access?")
+        .kind(MessageDialogKind::Warning)
+        .buttons(MessageDialogButtons::OkCancel)
+        .blocking_show()
+}
+
+// ============================================================================
+// WORKSPACE TAURI COMMANDS
+// ============================================================================
+
+/// Shows the native folder picker and opens the chosen folder as a trusted
+/// workspace root. Returns None if the user cancelled.
+#[tauri::command]
+pub async fn pick_workspace_folder(app: AppHandle, state: State<'_, WorkspaceState>) -> Result<Option<String>, String> {
+    let picker = app.clone();
+    let picked = tokio::task::spawn_blocking(move || picker.dialog().file().blocking_pick_folder())
+        .await
+        .map_err(|e| format!("Folder picker failed: {}", e))?;
+    let Some(picked) = picked else {
+        return Ok(None);
+    };
+
+    let root = resolve_path(&picked.into_path().map_err(|e| format!("Invalid folder: {}", e))?);
+    state.trust(&root)?;
+    state.add_root(root.clone());
+    Ok(Some(root.to_string_lossy().to_string()))
+}
+
+/// Opens `path` as a workspace root. Folders that aren't trusted yet need
+/// the user's confirmation in a native dialog. Returns whether access was
+/// granted.
+#[tauri::command]
+pub async fn open_workspace(app: AppHandle, path: String, state: State<'_, WorkspaceState>) -> Result<bool, String> {
+    let root = resolve_path(Path::new(&path));
+    if !root.is_dir() {
+        return Err(format!("Path is not a directory: {}", path));
+    }
+
+    let trusted = state.trusted.lock_or_recover().iter().any(|t| *t == root);
+    if !trusted {
+        let dialog_root = root.clone();
+        let allowed = tokio::task::spawn_blocking(move || confirm_access(&app, &dialog_root))
+            .await
+            .map_err(|e| format!("Consent dialog failed: {}", e))?;
+        if !allowed {
+            return Ok(false);
+        }
+        state.trust(&root)?;
+    }
+    state.add_root(root);
+    Ok(true)
+}
+
+/// Asks the user to allow one path outside the open workspace, such as a
+/// file passed on the command line. Access lasts for this session.
+#[tauri::command]
+pub async fn request_path_access(app: AppHandle, path: String, state: State<'_, WorkspaceState>) -> Result<bool, String> {
+    if state.check(&path).is_ok() {
+        return Ok(true);
+    }
+    let target = resolve_path(Path::new(&path));
+    let dialog_target = target.clone();
+    let allowed = tokio::task::spawn_blocking(move || confirm_access(&app, &dialog_target))
+        .await
+        .map_err(|e| format!("Consent dialog failed: {}", e))?;
+    if allowed {
+        state.add_root(target);
+    }
+    Ok(allowed)
+}
+
+#[tauri::command]
+pub fn get_workspace_roots(state: State<'_, WorkspaceState>) -> WorkspaceRoots {
+    let to_strings = |paths: &[PathBuf]| -> Vec<String> { paths.iter().map(|p| p.to_string_lossy().to_string()).collect() };
+    WorkspaceRoots {
+        roots: to_strings(&state.roots.lock_or_recover()),
+        trusted: to_strings(&state.trusted.lock_or_recover()),
+    }
+}
+
+/// Closes a workspace root for this session; it stays trusted.
+#[tauri::command]
+pub fn close_workspace(path: String, state: State<'_, WorkspaceState>) {
+    let root = resolve_path(Path::new(&path));
+    state.roots.lock_or_recover().retain(|r| *r != root);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A fresh `<tmp>/<name>` holding a `workspace` root that is open, and
+    /// the state guarding it.
+    fn open_workspace_in(name: &str) -> (PathBuf, WorkspaceState) {
+        let dir = std::env::temp_dir().join(format!("gencode-sandbox-{}-{}", name, std::process::id()));
+        let _ = std_fs::remove_dir_all(&dir);
+        std_fs::create_dir_all(dir.join("workspace/src")).unwrap();
+        std_fs::create_dir_all(dir.join("outside")).unwrap();
+        let state = WorkspaceState::open(&dir.join("trusted.json")).unwrap();
+        state.add_root(resolve_path(&dir.join("workspace")));
+        (resolve_path(&dir), state)
+    }
+
+    #[test]
+    fn allows_existing_and_new_paths_inside_the_root() {
+        let (dir, state) = open_workspace_in("inside");
+        let root = dir.join("workspace");
+        assert_eq!(state.check(root.join("src")).unwrap(), root.join("src"));
+        assert_eq!(state.check(root.join("new/dir/file.txt")).unwrap(), root.join("new/dir/file.txt"));
+        assert_eq!(state.check(root.join("new/../src/./lib.rs")).unwrap(), root.join("src/lib.rs"));
+    }
+
+    #[test]
+    fn rejects_parent_dirs_that_leave_the_root() {
+        let (dir, state) = open_workspace_in("parent");
+        let root = dir.join("workspace");
+        assert!(state.check(root.join("../outside/evil.txt")).is_err());
+        assert!(state.check(root.join("src/../../outside")).is_err());
+        // `nope` doesn't exist, so this can't be resolved by canonicalizing
+        assert!(state.check(root.join("nope/../../outside/evil.txt")).is_err());
+        assert!(state.check(root.join("nope/a/../../..")).is_err());
+    }
+
+    #[test]
+    fn rejects_sibling_directories_sharing_the_root_prefix() {
+        let (dir, state) = open_workspace_in("prefix");
+        std_fs::create_dir_all(dir.join("workspace-other")).unwrap();
+        assert!(state.check(dir.join("workspace-other/file.txt")).is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_symlinks_that_leave_the_root() {
+        let (dir, state) = open_workspace_in("symlink");
+        let link = dir.join("workspace/link");
+        std::os::unix::fs::symlink(dir.join("outside"), &link).unwrap();
+        assert!(state.check(&link).is_err());
+        assert!(state.check(link.join("new.txt")).is_err());
+    }
+}
@@ -0,0 +1,644 @@
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, VecDeque};
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::files::{decode_bytes, detect_encoding, encode_text};
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+
+const DEFAULT_MAX_RESULTS: usize = 2000;
+const MAX_FILE_BYTES: u64 = 4 * 1024 * 1024;
+const MAX_PREVIEW_CHARS: usize = 300;
+const MAX_UNDO_ENTRIES: usize = 20;
+
+// ============================================================================
+// SEARCH STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize, Default, Clone)]
+#[serde(default)]
+pub struct SearchOptions {
+    pub regex: bool,
+    pub case_sensitive: bool,
+    pub whole_word: bool,
+    pub include: Vec<String>,
+    pub exclude: Vec<String>,
+    pub include_ignored: bool,
+    pub max_results: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchMatch {
+    pub path: String,
+    pub line: usize,   // 1-based
+    pub column: usize, // 1-based, in characters
+    pub length: usize, // In characters
+    pub preview: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct SearchResultsEvent {
+    search_id: String,
+    path: String,
+    matches: Vec<SearchMatch>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchSummary {
+    pub search_id: String,
+    pub total_matches: usize,
+    pub files_with_matches: usize,
+    pub files_searched: usize,
+    pub truncated: bool,
+    pub cancelled: bool,
+}
+
+/// Cancellation flags of running searches, keyed by search id.
+#[derive(Default)]
+pub struct SearchState {
+    active: Mutex<HashMap<String, Arc<AtomicBool>>>,
+}
+
+// ============================================================================
+// SEARCH ENGINE
+// ============================================================================
+
+pub(crate) fn build_matcher(query: &str, options: &SearchOptions) -> Result<Regex, String> {
+    let mut pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    if options.whole_word {
+        pattern = format!(r"\b(?:{})\b", pattern);
+    }
+    RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid search pattern: {}", e))
+}
+
+/// Text content of a searchable file, decoded from whatever encoding it's
+/// in; binary and oversized files are None. Previews and applied replaces
+/// both read through this, so they always see the same text.
+pub(crate) fn read_text_file(path: &Path) -> Option<String> {
+    let metadata = std_fs::metadata(path).ok()?;
+    if metadata.len() > MAX_FILE_BYTES {
+        return None;
+    }
+    let bytes = std_fs::read(path).ok()?;
+    if bytes.iter().take(8192).any(|b| *b == 0) {
+        return None;
+    }
+    Some(decode_bytes(&bytes, detect_encoding(&bytes)))
+}
+
+fn preview(line: &str) -> String {
+    let trimmed = line.trim_end_matches(['\r', '\n']);
+    if trimmed.chars().count() > MAX_PREVIEW_CHARS {
+        trimmed.chars().take(MAX_PREVIEW_CHARS).collect()
+    } else {
+        trimmed.to_string()
+    }
+}
+
+pub(crate) fn find_matches(path: &str, content: &str, matcher: &Regex, limit: usize) -> Vec<SearchMatch> {
+    let mut matches = Vec::new();
+    for (i, line) in content.lines().enumerate() {
+        for found in matcher.find_iter(line) {
+            if found.start() == found.end() {
+                continue; // Empty regex matches aren't useful results
+            }
+            matches.push(SearchMatch {
+                path: path.to_string(),
+                line: i + 1,
+                column: line[..found.start()].chars().count() + 1,
+                length: found.as_str().chars().count(),
+                preview: preview(line),
+            });
+            if matches.len() >= limit {
+                return matches;
+            }
+        }
+    }
+    matches
+}
+
+/// Files below `root` that a search with `options` looks at.
+pub(crate) fn search_candidates(root: &Path, options: &SearchOptions) -> Result<Vec<std::path::PathBuf>, String> {
+    let walk_options = DirectoryOptions {
+        show_hidden: false,
+        show_ignored: options.include_ignored,
+        include: options.include.clone(),
+        exclude: if options.exclude.is_empty() {
+            None
+        } else {
+            Some(options.exclude.clone())
+        },
+        ..Default::default()
+    };
+    Ok(walk_entries(root, &walk_options, None)?
+        .into_iter()
+        .filter(|(_, is_dir)| !is_dir)
+        .map(|(path, _)| path)
+        .collect())
+}
+
+fn run_search(
+    app: &AppHandle,
+    search_id: &str,
+    root: &Path,
+    query: &str,
+    options: &SearchOptions,
+    mut on_file: impl FnMut(usize, usize) -> Result<(), String>,
+) -> Result<SearchSummary, String> {
+    let matcher = build_matcher(query, options)?;
+    let max_results = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS);
+
+    let mut summary = SearchSummary {
+        search_id: search_id.to_string(),
+        total_matches: 0,
+        files_with_matches: 0,
+        files_searched: 0,
+        truncated: false,
+        cancelled: false,
+    };
+
+    let candidates = search_candidates(root, options)?;
+    for (i, path) in candidates.iter().enumerate() {
+        if on_file(i, candidates.len()).is_err() {
+            summary.cancelled = true;
+            break;
+        }
+        if summary.total_matches >= max_results {
+            summary.truncated = true;
+            break;
+        }
+        let Some(content) = read_text_file(path) else {
+            continue;
+        };
+        summary.files_searched += 1;
+
+        let path_str = path.to_string_lossy().to_string();
+        let matches = find_matches(&path_str, &content, &matcher, max_results - summary.total_matches);
+        if matches.is_empty() {
+            continue;
+        }
+
+        summary.total_matches += matches.len();
+        summary.files_with_matches += 1;
+        let _ = app.emit(
+            "search-results",
+            SearchResultsEvent {
+                search_id: search_id.to_string(),
+                path: path_str,
+                matches,
+            },
+        );
+    }
+
+    Ok(summary)
+}
+
+// ============================================================================
+// SEARCH TAURI COMMANDS
+// ============================================================================
+
+/// Searches file contents below `root`, skipping gitignored, binary and very
+/// large files. Matches stream as `search-results` events per file; the
+/// summary is returned and also emitted as `search-done`.
+#[tauri::command]
+pub async fn search_in_files(
+    window: Window,
+    search_id: String,
+    root: String,
+    query: String,
+    options: Option<SearchOptions>,
+    state: State<'_, SearchState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<SearchSummary, String> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    state.active.lock_or_recover().insert(search_id.clone(), cancelled.clone());
+
+    let on_file = move |_: usize, _: usize| {
+        if cancelled.load(Ordering::Relaxed) {
+            Err("Cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    };
+    let app = window.app_handle();
+    let result = search_with_progress(app, &search_id, &root, query, options, &workspace, on_file).await;
+
+    state.active.lock_or_recover().remove(&search_id);
+    result
+}
+
+/// `search_in_files`, calling `on_file(done, total)` before each candidate
+/// file; an error from it stops the search, which still returns a summary.
+pub(crate) async fn search_with_progress(
+    app: &AppHandle,
+    search_id: &str,
+    root: &str,
+    query: String,
+    options: Option<SearchOptions>,
+    workspace: &WorkspaceState,
+    on_file: impl FnMut(usize, usize) -> Result<(), String> + Send + 'static,
+) -> Result<SearchSummary, String> {
+    workspace.check(root)?;
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    let root_path = std::path::PathBuf::from(root);
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+
+    let task_app = app.clone();
+    let task_id = search_id.to_string();
+    let summary = tokio::task::spawn_blocking(move || {
+        run_search(&task_app, &task_id, &root_path, &query, &options.unwrap_or_default(), on_file)
+    })
+    .await
+    .map_err(|e| format!("Search task failed: {}", e))??;
+
+    let _ = app.emit("search-done", summary.clone());
+    Ok(summary)
+}
+
+#[tauri::command]
+pub fn cancel_search(search_id: String, state: State<'_, SearchState>) -> Result<(), String> {
+    let active = state.active.lock_or_recover();
+    let cancelled = active
+        .get(&search_id)
+        .ok_or_else(|| format!("No search in progress: {}", search_id))?;
+    cancelled.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+// ============================================================================
+// FIND AND REPLACE
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ReplaceEdit {
+    pub id: usize,
+    pub line: usize,
+    pub column: usize,
+    pub original: String,
+    pub replacement: String,
+    pub preview_before: String,
+    pub preview_after: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct FileReplacePreview {
+    pub path: String,
+    /// Must be passed back to `apply_replacements`; files changed since the
+    /// preview are refused.
+    pub content_hash: String,
+    pub edits: Vec<ReplaceEdit>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ReplaceSelection {
+    pub path: String,
+    pub content_hash: String,
+    /// Edit ids from the preview; None applies every edit in the file.
+    pub edit_ids: Option<Vec<usize>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplaceApplyResult {
+    pub undo_id: String,
+    pub files_changed: usize,
+    pub edits_applied: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReplaceUndoResult {
+    pub restored: Vec<String>,
+    pub conflicts: Vec<String>, // Edited again since the replace; left alone
+}
+
+struct UndoFile {
+    path: PathBuf,
+    original: String,
+    applied_hash: String,
+}
+
+/// Original contents of recent replace operations, newest last.
+#[derive(Default)]
+pub struct ReplaceState {
+    undo: Mutex<VecDeque<(String, Vec<UndoFile>)>>,
+    next_id: AtomicU64,
+}
+
+impl ReplaceState {
+    /// Remembers the (path, original, updated) contents of a multi-file
+    /// edit and returns the id `undo_replacements` restores it by.
+    pub(crate) fn record(&self, prefix: &str, files: Vec<(PathBuf, String, String)>) -> String {
+        let undo_id = format!("{}-{}", prefix, self.next_id.fetch_add(1, Ordering::Relaxed) + 1);
+        let mut undo = self.undo.lock_or_recover();
+        undo.push_back((
+            undo_id.clone(),
+            files
+                .into_iter()
+                .map(|(path, original, updated)| UndoFile {
+                    path,
+                    original,
+                    applied_hash: content_hash(&updated),
+                })
+                .collect(),
+        ));
+        while undo.len() > MAX_UNDO_ENTRIES {
+            undo.pop_front();
+        }
+        undo_id
+    }
+}
+
+pub(crate) fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Byte ranges to replace and their replacement text. In regex mode the
+/// replacement may reference capture groups as `$1` or `${name}`.
+fn plan_edits(content: &str, matcher: &Regex, replacement: &str, regex_mode: bool) -> Vec<(usize, usize, String)> {
+    let mut edits = Vec::new();
+    for caps in matcher.captures_iter(content) {
+        let Some(found) = caps.get(0) else {
+            continue;
+        };
+        if found.start() == found.end() {
+            continue;
+        }
+        let text = if regex_mode {
+            let mut expanded = String::new();
+            caps.expand(replacement, &mut expanded);
+            expanded
+        } else {
+            replacement.to_string()
+        };
+        edits.push((found.start(), found.end(), text));
+    }
+    edits
+}
+
+fn line_bounds(content: &str, start: usize, end: usize) -> (usize, usize) {
+    let line_start = content[..start].rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let line_end = content[end..].find('\n').map(|i| end + i).unwrap_or(content.len());
+    (line_start, line_end)
+}
+
+fn preview_file(path: &Path, content: &str, matcher: &Regex, replacement: &str, regex_mode: bool) -> Option<FileReplacePreview> {
+    let planned = plan_edits(content, matcher, replacement, regex_mode);
+    if planned.is_empty() {
+        return None;
+    }
+
+    let mut edits = Vec::with_capacity(planned.len());
+    let mut line = 1;
+    let mut scanned = 0;
+    for (id, (start, end, text)) in planned.into_iter().enumerate() {
+        line += content[scanned..start].matches('\n').count();
+        scanned = start;
+
+        let (line_start, line_end) = line_bounds(content, start, end);
+        edits.push(ReplaceEdit {
+            id,
+            line,
+            column: content[line_start..start].chars().count() + 1,
+            original: content[start..end].to_string(),
+            preview_before: preview(&content[line_start..line_end]),
+            preview_after: preview(&format!("{}{}{}", &content[line_start..start], text, &content[end..line_end])),
+            replacement: text,
+        });
+    }
+
+    Some(FileReplacePreview {
+        path: path.to_string_lossy().to_string(),
+        content_hash: content_hash(content),
+        edits,
+    })
+}
+
+/// Writes through a temporary file and rename so a file is never left
+/// half-written. The replacement keeps the original's encoding, its mode
+/// and, where the user may set it, its owner.
+fn write_atomically(path: &Path, content: &str) -> Result<(), String> {
+    let name = path.file_name().ok_or("Invalid file path")?.to_string_lossy().to_string();
+    let temp = path.with_file_name(format!(".{}.gencode-tmp", name));
+    let encoding = std_fs::read(path).map(|bytes| detect_encoding(&bytes)).unwrap_or_default();
+    let bytes = encode_text(content, encoding).map_err(|e| format!("{}: {}", path.display(), e))?;
+    std_fs::write(&temp, bytes).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+    if let Ok(metadata) = std_fs::metadata(path) {
+        if let Err(e) = std_fs::set_permissions(&temp, metadata.permissions()) {
+            let _ = std_fs::remove_file(&temp);
+            return Err(format!("Failed to copy permissions of {}: {}", path.display(), e));
+        }
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            // Best effort: changing the owner needs privileges the user may lack
+            let _ = std::os::unix::fs::chown(&temp, Some(metadata.uid()), Some(metadata.gid()));
+        }
+    }
+    std_fs::rename(&temp, path).map_err(|e| {
+        let _ = std_fs::remove_file(&temp);
+        format!("Failed to replace {}: {}", path.display(), e)
+    })
+}
+
+/// Writes every (path, original, updated) file, restoring the ones
+/// already written if any write fails.
+pub(crate) fn write_all_or_none(files: &[(PathBuf, String, String)]) -> Result<(), String> {
+    for (index, (path, _, updated)) in files.iter().enumerate() {
+        if let Err(e) = write_atomically(path, updated) {
+            for (path, original, _) in &files[..index] {
+                let _ = write_atomically(path, original);
+            }
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Previews replacing every match of `query` below `root`. Nothing is
+/// written; pass the chosen edits to `apply_replacements`.
+#[tauri::command]
+pub async fn replace_in_files(
+    root: String,
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<FileReplacePreview>, String> {
+    workspace.check(&root)?;
+    if query.is_empty() {
+        return Err("Search query is empty".to_string());
+    }
+    tokio::task::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let matcher = build_matcher(&query, &options)?;
+        let mut previews = Vec::new();
+        for path in search_candidates(Path::new(&root), &options)? {
+            let Some(content) = read_text_file(&path) else {
+                continue;
+            };
+            if let Some(preview) = preview_file(&path, &content, &matcher, &replacement, options.regex) {
+                previews.push(preview);
+            }
+        }
+        Ok(previews)
+    })
+    .await
+    .map_err(|e| format!("Replace task failed: {}", e))?
+}
+
+/// Applies the selected edits from a `replace_in_files` preview. Either every
+/// file is updated or, on any failure, none is. The returned `undo_id`
+/// restores the previous contents with `undo_replacements`.
+#[tauri::command]
+pub async fn apply_replacements(
+    app: AppHandle,
+    query: String,
+    replacement: String,
+    options: Option<SearchOptions>,
+    selections: Vec<ReplaceSelection>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<ReplaceApplyResult, String> {
+    for selection in &selections {
+        workspace.check(&selection.path)?;
+    }
+    tokio::task::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let matcher = build_matcher(&query, &options)?;
+
+        // Compute every new content before touching the disk
+        let mut planned: Vec<(PathBuf, String, String)> = Vec::new();
+        let mut edits_applied = 0;
+        for selection in &selections {
+            let path = PathBuf::from(&selection.path);
+            let content = read_text_file(&path).ok_or_else(|| format!("Failed to read {} as text", selection.path))?;
+            if content_hash(&content) != selection.content_hash {
+                return Err(format!("{} changed since the preview; search again", selection.path));
+            }
+
+            let mut updated = String::with_capacity(content.len());
+            let mut last = 0;
+            let mut applied = 0;
+            for (id, (start, end, text)) in plan_edits(&content, &matcher, &replacement, options.regex)
+                .into_iter()
+                .enumerate()
+            {
+                if selection.edit_ids.as_ref().is_some_and(|ids| !ids.contains(&id)) {
+                    continue;
+                }
+                updated.push_str(&content[last..start]);
+                updated.push_str(&text);
+                last = end;
+                applied += 1;
+            }
+            updated.push_str(&content[last..]);
+
+            if applied > 0 {
+                planned.push((path, content, updated));
+                edits_applied += applied;
+            }
+        }
+
+        write_all_or_none(&planned)?;
+        let files_changed = planned.len();
+        let undo_id = app.state::<ReplaceState>().record("replace", planned);
+
+        Ok(ReplaceApplyResult {
+            undo_id,
+            files_changed,
+            edits_applied,
+        })
+    })
+    .await
+    .map_err(|e| format!("Replace task failed: {}", e))?
+}
+
+/// Restores the files of a replace operation. Files edited since are
+/// reported as conflicts and kept as they are.
+#[tauri::command]
+pub fn undo_replacements(undo_id: String, state: State<'_, ReplaceState>) -> Result<ReplaceUndoResult, String> {
+    let files = {
+        let mut undo = state.undo.lock_or_recover();
+        let position = undo
+            .iter()
+            .position(|(id, _)| *id == undo_id)
+            .ok_or_else(|| format!("Nothing to undo for {}", undo_id))?;
+        undo.remove(position).map(|(_, files)| files).unwrap_or_default()
+    };
+
+    let mut result = ReplaceUndoResult {
+        restored: Vec::new(),
+        conflicts: Vec::new(),
+    };
+    for file in files {
+        let path = file.path.to_string_lossy().to_string();
+        let current = read_text_file(&file.path).unwrap_or_default();
+        if content_hash(&current) != file.applied_hash {
+            result.conflicts.push(path);
+            continue;
+        }
+        write_atomically(&file.path, &file.original)?;
+        result.restored.push(path);
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn plain_replacements_are_inserted_literally() {
+        let matcher = Regex::new("foo").unwrap();
+        let edits = plan_edits("foo bar foo", &matcher, "$1", false);
+        assert_eq!(edits, vec![(0, 3, "$1".to_string()), (8, 11, "$1".to_string())]);
+    }
+
+    #[test]
+    fn regex_replacements_expand_capture_groups() {
+        let matcher = Regex::new(r"(?P<name>\w+)\((\d)\)").unwrap();
+        let edits = plan_edits("call(1); run(2)", &matcher, "${name}_$2", true);
+        assert_eq!(edits, vec![(0, 7, "call_1".to_string()), (9, 15, "run_2".to_string())]);
+    }
+
+    #[test]
+    fn rewrites_keep_the_file_encoding() {
+        let path = std::env::temp_dir().join(format!("gencode-replace-latin1-{}.txt", std::process::id()));
+        std_fs::write(&path, b"caf\xe9 cr\xe8me br\xfbl\xe9e, d\xe9j\xe0 vu\n").unwrap();
+        let content = read_text_file(&path).unwrap();
+        assert_eq!(content, "café crème brûlée, déjà vu\n");
+        write_atomically(&path, &content.replace("café", "thé")).unwrap();
+        assert_eq!(std_fs::read(&path).unwrap(), b"th\xe9 cr\xe8me br\xfbl\xe9e, d\xe9j\xe0 vu\n");
+        let _ = std_fs::remove_file(&path);
+    }
+
+    #[test]
+    fn undo_ids_are_unique() {
+        let state = ReplaceState::default();
+        assert_ne!(state.record("replace", Vec::new()), state.record("replace", Vec::new()));
+    }
+
+    #[test]
+    fn empty_matches_are_skipped() {
+        let matcher = Regex::new("x*").unwrap();
+        assert_eq!(plan_edits("axxb", &matcher, "y", false), vec![(1, 3, "y".to_string())]);
+    }
+}
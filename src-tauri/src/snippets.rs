@@ -0,0 +1,251 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs as std_fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{State, Window};
+use tokio::task::AbortHandle;
+
+use crate::formatter::on_path;
+use crate::locks::LockExt;
+use crate::process::{run_process, CommandResult};
+use crate::sandbox::WorkspaceState;
+
+/// Compiling and running each get this long.
+const SNIPPET_TIMEOUT_MS: u64 = 30_000;
+
+// ============================================================================
+// SNIPPET STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize)]
+pub struct SnippetResult {
+    /// "python", "node" or "rust".
+    pub language: String,
+    /// The rustc step for Rust snippets.
+    pub compile: Option<CommandResult>,
+    /// `None` when compilation failed.
+    pub run: Option<CommandResult>,
+    pub success: bool,
+}
+
+/// Snippet runs in progress, so they can be cancelled.
+#[derive(Default)]
+pub struct SnippetState {
+    runs: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl SnippetState {
+    /// Aborts every snippet run, whose process dies as the task is dropped.
+    pub fn shutdown(&self) {
+        for (_, run) in self.runs.lock_or_recover().drain() {
+            run.abort();
+        }
+    }
+}
+
+enum SnippetLanguage {
+    Python,
+    Node,
+    Rust,
+}
+
+impl SnippetLanguage {
+    fn parse(language: &str) -> Result<Self, String> {
+        match language.to_lowercase().as_str() {
+            "python" | "python3" | "py" => Ok(SnippetLanguage::Python),
+            "node" | "javascript" | "js" | "mjs" | "cjs" => Ok(SnippetLanguage::Node),
+            "rust" | "rs" => Ok(SnippetLanguage::Rust),
+            other => Err(format!("Snippets can't run {} yet; use Python, JavaScript or Rust", other)),
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        match self {
+            SnippetLanguage::Python => "python",
+            SnippetLanguage::Node => "node",
+            SnippetLanguage::Rust => "rust",
+        }
+    }
+}
+
+// ============================================================================
+// SNIPPET EXECUTION
+// ============================================================================
+
+fn find_program(names: &[&str]) -> Result<String, String> {
+    names
+        .iter()
+        .find_map(|name| on_path(name))
+        .map(|path| path.to_string_lossy().to_string())
+        .ok_or_else(|| format!("{} not found on PATH", names[0]))
+}
+
+/// Chat snippets are often just statements; rustc needs a `main`.
+fn rust_source(code: &str) -> String {
+    if code.contains("fn main") {
+        code.to_string()
+    } else {
+        format!("fn main() {{\n{}\n}}\n", code)
+    }
+}
+
+/// ES module syntax needs `.mjs`; everything else runs as CommonJS.
+fn node_extension(code: &str) -> &'static str {
+    let is_module = code.lines().any(|line| {
+        let line = line.trim_start();
+        line.starts_with("import ") || line.starts_with("export ") || line.contains("await import(")
+    });
+    if is_module {
+        "mjs"
+    } else {
+        "cjs"
+    }
+}
+
+/// A fresh directory per run under the system temp dir.
+fn scratch_dir(run_id: &str) -> Result<PathBuf, String> {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    let safe_id: String = run_id.chars().filter(|c| c.is_ascii_alphanumeric() || *c == '-').collect();
+    let dir = std::env::temp_dir().join(format!("gencode-snippet-{}-{}", safe_id, nanos));
+    std_fs::create_dir_all(&dir).map_err(|e| format!("Failed to create snippet directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Writes the snippet to `dir` and runs it from `cwd` with a time limit and
+/// no stdin. Imports resolve against `cwd` like in a project REPL.
+async fn run_snippet(
+    window: Window,
+    run_id: String,
+    language: SnippetLanguage,
+    code: String,
+    cwd: PathBuf,
+    dir: PathBuf,
+) -> Result<SnippetResult, String> {
+    let write = |name: &str, source: &str| -> Result<String, String> {
+        let path = dir.join(name);
+        std_fs::write(&path, source).map_err(|e| format!("Failed to write snippet: {}", e))?;
+        Ok(path.to_string_lossy().to_string())
+    };
+    let cwd_text = cwd.to_string_lossy().to_string();
+    let mut env = HashMap::new();
+
+    let (program, args, compile) = match language {
+        SnippetLanguage::Python => {
+            let file = write("snippet.py", &code)?;
+            env.insert("PYTHONPATH".to_string(), cwd_text.clone());
+            env.insert("PYTHONDONTWRITEBYTECODE".to_string(), "1".to_string());
+            // Unbuffered, so output streams as it is printed
+            (find_program(&["python3", "python"])?, vec!["-u".to_string(), file], None)
+        }
+        SnippetLanguage::Node => {
+            let file = write(&format!("snippet.{}", node_extension(&code)), &code)?;
+            env.insert(
+                "NODE_PATH".to_string(),
+                cwd.join("node_modules").to_string_lossy().to_string(),
+            );
+            (find_program(&["node"])?, vec![file], None)
+        }
+        SnippetLanguage::Rust => {
+            let source = write("main.rs", &rust_source(&code))?;
+            let binary = dir.join(if cfg!(target_os = "windows") { "snippet.exe" } else { "snippet" });
+            let args = vec![
+                "--edition".to_string(),
+                "2021".to_string(),
+                "-o".to_string(),
+                binary.to_string_lossy().to_string(),
+                source,
+            ];
+            let rustc = find_program(&["rustc"])?;
+            let compile = run_process(
+                Some(window.clone()),
+                &run_id,
+                Some(&cwd_text),
+                &rustc,
+                &args,
+                &env,
+                Some(SNIPPET_TIMEOUT_MS),
+            )
+            .await?;
+            if compile.exit_code != Some(0) {
+                return Ok(SnippetResult {
+                    language: language.name().to_string(),
+                    compile: Some(compile),
+                    run: None,
+                    success: false,
+                });
+            }
+            (binary.to_string_lossy().to_string(), Vec::new(), Some(compile))
+        }
+    };
+
+    let run = run_process(
+        Some(window),
+        &run_id,
+        Some(&cwd_text),
+        &program,
+        &args,
+        &env,
+        Some(SNIPPET_TIMEOUT_MS),
+    )
+    .await?;
+    Ok(SnippetResult {
+        language: language.name().to_string(),
+        success: run.exit_code == Some(0),
+        compile,
+        run: Some(run),
+    })
+}
+
+// ============================================================================
+// SNIPPET TAURI COMMANDS
+// ============================================================================
+
+/// Runs a Python, JavaScript (Node) or Rust snippet from a temp file with
+/// `cwd` (a trusted workspace path, default the temp dir) as working
+/// directory. Output streams as `command-output` events tagged with
+/// `run_id`; each step is killed after 30s.
+#[tauri::command]
+pub async fn execute_snippet(
+    window: Window,
+    run_id: String,
+    language: String,
+    code: String,
+    cwd: Option<String>,
+    state: State<'_, SnippetState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<SnippetResult, String> {
+    let language = SnippetLanguage::parse(&language)?;
+    let cwd = match cwd {
+        Some(cwd) => workspace.check(&cwd)?,
+        None => std::env::temp_dir(),
+    };
+    if !cwd.is_dir() {
+        return Err(format!("Not a directory: {}", cwd.display()));
+    }
+    let dir = scratch_dir(&run_id)?;
+
+    let handle = tokio::spawn(run_snippet(window, run_id.clone(), language, code, cwd, dir.clone()));
+    state.runs.lock_or_recover().insert(run_id.clone(), handle.abort_handle());
+    let outcome = handle.await;
+    state.runs.lock_or_recover().remove(&run_id);
+    let _ = std_fs::remove_dir_all(&dir);
+    match outcome {
+        Ok(result) => result,
+        Err(e) if e.is_cancelled() => Err(format!("Snippet run cancelled: {}", run_id)),
+        Err(e) => Err(format!("Snippet run failed: {}", e)),
+    }
+}
+
+/// Aborting the run drops its process, which `kill_on_drop` kills.
+#[tauri::command]
+pub fn cancel_snippet(run_id: String, state: State<'_, SnippetState>) -> Result<(), String> {
+    let runs = state.runs.lock_or_recover();
+    let run = runs.get(&run_id).ok_or_else(|| format!("Snippet run not found: {}", run_id))?;
+    run.abort();
+    Ok(())
+}
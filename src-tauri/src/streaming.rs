@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+use tauri::ipc::{Channel, InvokeBody, InvokeResponseBody, Request};
+use tauri::{AppHandle, Manager, State};
+
+use crate::event_bus::emit_graph_updated;
+use crate::graph_builder::{build_graph, read_graph_file};
+use crate::sandbox::WorkspaceState;
+use crate::{CodeGraph, Neo4jState, ParsedFile, ParserState};
+
+/// Parsed files per chunk unless the caller picks a size; ASTs are large.
+const PARSE_BATCH_FILES: usize = 25;
+/// Source bytes after which a parse chunk is sent early.
+const PARSE_BATCH_BYTES: usize = 2 * 1024 * 1024;
+const GRAPH_BATCH_ITEMS: usize = 5000;
+
+// ============================================================================
+// STREAMING STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadEncoding {
+    /// Each chunk arrives as a JSON object.
+    #[default]
+    Json,
+    /// Each chunk arrives as an ArrayBuffer holding the same object encoded
+    /// as MessagePack with named fields.
+    Msgpack,
+}
+
+/// One message on a stream's channel. `seq` starts at 0 and increases by
+/// one per message; the last message has `done` set and no items.
+#[derive(Serialize)]
+struct StreamChunk<'a, T> {
+    seq: usize,
+    /// What `items` holds: `files`, or `nodes`/`edges`/`graphFiles` for graphs.
+    kind: &'a str,
+    items: &'a [T],
+    done: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct StreamSummary {
+    /// Messages sent, including the final one.
+    pub chunks: usize,
+    pub items: usize,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum ParseInput {
+    Content(String, String),
+    Path(String),
+}
+
+struct ChunkSender {
+    channel: Channel<InvokeResponseBody>,
+    encoding: PayloadEncoding,
+    seq: usize,
+    items: usize,
+}
+
+impl ChunkSender {
+    fn new(channel: Channel<InvokeResponseBody>, encoding: Option<PayloadEncoding>) -> Self {
+        ChunkSender {
+            channel,
+            encoding: encoding.unwrap_or_default(),
+            seq: 0,
+            items: 0,
+        }
+    }
+
+    fn send<T: Serialize>(&mut self, kind: &str, items: &[T], done: bool) -> Result<(), String> {
+        let chunk = StreamChunk {
+            seq: self.seq,
+            kind,
+            items,
+            done,
+        };
+        let body = encode(&chunk, self.encoding)?;
+        self.channel
+            .send(body)
+            .map_err(|e| format!("Failed to send chunk {}: {}", self.seq, e))?;
+        self.seq += 1;
+        self.items += items.len();
+        Ok(())
+    }
+
+    fn send_batched<T: Serialize>(&mut self, kind: &str, items: &[T], batch: usize) -> Result<(), String> {
+        for chunk in items.chunks(batch.max(1)) {
+            self.send(kind, chunk, false)?;
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<StreamSummary, String> {
+        self.send::<()>("end", &[], true)?;
+        Ok(StreamSummary {
+            chunks: self.seq,
+            items: self.items,
+        })
+    }
+}
+
+fn encode<T: Serialize>(value: &T, encoding: PayloadEncoding) -> Result<InvokeResponseBody, String> {
+    match encoding {
+        PayloadEncoding::Json => serde_json::to_string(value)
+            .map(InvokeResponseBody::Json)
+            .map_err(|e| format!("Failed to encode chunk: {}", e)),
+        PayloadEncoding::Msgpack => rmp_serde::to_vec_named(value)
+            .map(InvokeResponseBody::Raw)
+            .map_err(|e| format!("Failed to encode chunk: {}", e)),
+    }
+}
+
+fn stream_graph(graph: &CodeGraph, mut sender: ChunkSender) -> Result<StreamSummary, String> {
+    sender.send_batched("nodes", &graph.nodes, GRAPH_BATCH_ITEMS)?;
+    sender.send_batched("edges", &graph.edges, GRAPH_BATCH_ITEMS)?;
+    if let Some(files) = &graph.files {
+        sender.send_batched("graphFiles", files, GRAPH_BATCH_ITEMS)?;
+    }
+    sender.finish()
+}
+
+// ============================================================================
+// STREAMING TAURI COMMANDS
+// ============================================================================
+
+/// Parses files like `parse_files`/`read_and_parse_files` but sends the
+/// results over `on_chunk` as they are ready instead of as one response.
+/// Inputs are `[path, content]` pairs or bare paths to read from disk.
+#[tauri::command]
+pub async fn parse_files_stream(
+    app: AppHandle,
+    files: Vec<ParseInput>,
+    encoding: Option<PayloadEncoding>,
+    batch_size: Option<usize>,
+    on_chunk: Channel<InvokeResponseBody>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<StreamSummary, String> {
+    for input in &files {
+        if let ParseInput::Path(path) = input {
+            workspace.check(path)?;
+        }
+    }
+    let batch_size = batch_size.unwrap_or(PARSE_BATCH_FILES).max(1);
+    let mut sender = ChunkSender::new(on_chunk, encoding);
+
+    tokio::task::spawn_blocking(move || {
+        let parser = app.state::<ParserState>();
+        let mut batch: Vec<ParsedFile> = Vec::new();
+        let mut batch_bytes = 0;
+        for input in files {
+            let parsed = match input {
+                ParseInput::Content(path, content) => parser.parse_file(&path, &content),
+                ParseInput::Path(path) => parser.read_and_parse(&path),
+            };
+            batch_bytes += parsed.metadata.bytes;
+            batch.push(parsed);
+            if batch.len() >= batch_size || batch_bytes >= PARSE_BATCH_BYTES {
+                sender.send("files", &batch, false)?;
+                batch.clear();
+                batch_bytes = 0;
+            }
+        }
+        if !batch.is_empty() {
+            sender.send("files", &batch, false)?;
+        }
+        sender.finish()
+    })
+    .await
+    .map_err(|e| format!("Parse task failed: {}", e))?
+}
+
+/// `build_code_graph`, streamed over `on_chunk` in node, edge and file chunks.
+#[tauri::command]
+pub async fn build_code_graph_stream(
+    app: AppHandle,
+    root: String,
+    encoding: Option<PayloadEncoding>,
+    on_chunk: Channel<InvokeResponseBody>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<StreamSummary, String> {
+    let root_path = workspace.check(&root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+    let sender = ChunkSender::new(on_chunk, encoding);
+    tokio::task::spawn_blocking(move || {
+        let graph = build_graph(&app.state::<ParserState>(), &root_path)?;
+        stream_graph(&graph, sender)
+    })
+    .await
+    .map_err(|e| format!("Graph build failed: {}", e))?
+}
+
+/// `load_code_graph`, streamed over `on_chunk` in node, edge and file chunks.
+#[tauri::command]
+pub async fn load_code_graph_stream(
+    path: String,
+    encoding: Option<PayloadEncoding>,
+    on_chunk: Channel<InvokeResponseBody>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<StreamSummary, String> {
+    let path = workspace.check(&path)?;
+    let sender = ChunkSender::new(on_chunk, encoding);
+    tokio::task::spawn_blocking(move || stream_graph(&read_graph_file(&path)?, sender))
+        .await
+        .map_err(|e| format!("Failed to load graph: {}", e))?
+}
+
+/// `store_graph_in_neo4j` for large graphs: the request body is the graph
+/// as MessagePack bytes (a `Uint8Array` passed straight to `invoke`) rather
+/// than a JSON argument. A JSON body holding the graph works too.
+#[tauri::command]
+pub async fn store_graph_binary(
+    app: AppHandle,
+    request: Request<'_>,
+    state: State<'_, Neo4jState>,
+) -> Result<String, String> {
+    let graph: CodeGraph = match request.body() {
+        InvokeBody::Raw(bytes) => rmp_serde::from_slice(bytes).map_err(|e| format!("Invalid graph payload: {}", e))?,
+        InvokeBody::Json(value) => {
+            CodeGraph::deserialize(value).map_err(|e| format!("Invalid graph payload: {}", e))?
+        }
+    };
+    let neo4j = state.get_graph().await?;
+    let message = graph.store_in_neo4j(&neo4j).await?;
+    emit_graph_updated(&app, "store", None, graph.nodes.len());
+    Ok(message)
+}
@@ -0,0 +1,182 @@
+use serde_json::Value;
+use std::time::Instant;
+use tauri::State;
+
+use crate::llm::{LlmRequestRecord, LlmState, LoggedPrompt};
+use crate::{ChatMessage, OllamaChatResponse};
+
+// ============================================================================
+// SCHEMA VALIDATION
+// ============================================================================
+
+fn type_matches(value: &Value, expected: &str) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "number" => value.is_number(),
+        "integer" => value.is_i64() || value.is_u64(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// Validates `value` against the subset of JSON Schema that models are asked
+/// to produce: `type`, `enum`, `properties`, `required`,
+/// `additionalProperties: false`, `items`, `minItems` and `maxItems`.
+pub(crate) fn validate_schema(value: &Value, schema: &Value, path: &str, errors: &mut Vec<String>) {
+    let location = if path.is_empty() { "$" } else { path };
+
+    if let Some(expected) = schema.get("type") {
+        let allowed: Vec<&str> = match expected {
+            Value::String(t) => vec![t.as_str()],
+            Value::Array(types) => types.iter().filter_map(|t| t.as_str()).collect(),
+            _ => Vec::new(),
+        };
+        if !allowed.is_empty() && !allowed.iter().any(|t| type_matches(value, t)) {
+            errors.push(format!("{}: expected type {}", location, allowed.join(" or ")));
+            return;
+        }
+    }
+
+    if let Some(Value::Array(options)) = schema.get("enum") {
+        if !options.contains(value) {
+            errors.push(format!("{}: value must be one of {}", location, Value::Array(options.clone())));
+        }
+    }
+
+    if let Value::Object(map) = value {
+        if let Some(Value::Array(required)) = schema.get("required") {
+            for key in required.iter().filter_map(|k| k.as_str()) {
+                if !map.contains_key(key) {
+                    errors.push(format!("{}: missing required property '{}'", location, key));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(|p| p.as_object());
+        for (key, child) in map {
+            let child_path = format!("{}.{}", location, key);
+            match properties.and_then(|p| p.get(key)) {
+                Some(child_schema) => validate_schema(child, child_schema, &child_path, errors),
+                None => {
+                    if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+                        errors.push(format!("{}: unexpected property", child_path));
+                    }
+                }
+            }
+        }
+    }
+
+    if let Value::Array(items) = value {
+        if let Some(min) = schema.get("minItems").and_then(|m| m.as_u64()) {
+            if (items.len() as u64) < min {
+                errors.push(format!("{}: expected at least {} items", location, min));
+            }
+        }
+        if let Some(max) = schema.get("maxItems").and_then(|m| m.as_u64()) {
+            if (items.len() as u64) > max {
+                errors.push(format!("{}: expected at most {} items", location, max));
+            }
+        }
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                validate_schema(item, item_schema, &format!("{}[{}]", location, i), errors);
+            }
+        }
+    }
+}
+
+// ============================================================================
+// STRUCTURED CHAT
+// ============================================================================
+
+/// Asks the model for JSON constrained by `json_schema` through Ollama's
+/// `format` option. Invalid output is sent back to the model together with
+/// the validation errors, up to `max_retries` times.
+pub(crate) async fn structured_chat(
+    llm: &LlmState,
+    model: &str,
+    mut messages: Vec<ChatMessage>,
+    json_schema: &Value,
+    max_retries: usize,
+) -> Result<Value, String> {
+    let key = LlmState::cache_key("ollama", model, &messages, &serde_json::json!({ "format": json_schema }));
+    if let Some(value) = llm.cache_get(&key).and_then(|cached| serde_json::from_str(&cached).ok()) {
+        return Ok(value);
+    }
+
+    let mut last_error = String::new();
+
+    for _ in 0..=max_retries {
+        let request = serde_json::json!({
+            "model": model,
+            "messages": messages,
+            "format": json_schema,
+            "stream": false,
+        });
+
+        let started = Instant::now();
+        let result: Result<OllamaChatResponse, String> = async {
+            llm.post("/api/chat", &request)
+                .await?
+                .json()
+                .await
+                .map_err(|e| format!("Failed to parse response: {}", e))
+        }
+        .await;
+
+        llm.log_request(LlmRequestRecord {
+            provider: "ollama",
+            model,
+            endpoint: "/api/chat",
+            prompt: LoggedPrompt::from_messages(&messages),
+            prompt_tokens: result.as_ref().ok().and_then(|r| r.prompt_eval_count),
+            completion_tokens: result.as_ref().ok().and_then(|r| r.eval_count),
+            duration_ms: started.elapsed().as_millis() as u64,
+            error: result.as_ref().err().cloned(),
+        });
+
+        let chat_response = result?;
+        let content = chat_response.message.content;
+        let feedback = match serde_json::from_str::<Value>(&content) {
+            Ok(value) => {
+                let mut errors = Vec::new();
+                validate_schema(&value, json_schema, "", &mut errors);
+                if errors.is_empty() {
+                    llm.cache_put(&key, model, &value.to_string());
+                    return Ok(value);
+                }
+                errors.join("\n")
+            }
+            Err(e) => format!("Response is not valid JSON: {}", e),
+        };
+
+        last_error = feedback.clone();
+        messages.push(ChatMessage {
+            role: "assistant".to_string(),
+            content,
+        });
+        messages.push(ChatMessage {
+            role: "user".to_string(),
+            content: format!(
+                "Your previous response did not match the required JSON schema:\n{}\n\nRespond again with only JSON that matches the schema.",
+                feedback
+            ),
+        });
+    }
+
+    Err(format!("Model did not produce valid structured output: {}", last_error))
+}
+
+#[tauri::command]
+pub async fn chat_structured(
+    model: String,
+    messages: Vec<ChatMessage>,
+    json_schema: Value,
+    max_retries: Option<usize>,
+    llm: State<'_, LlmState>,
+) -> Result<Value, String> {
+    structured_chat(&llm, &model, messages, &json_schema, max_retries.unwrap_or(2)).await
+}
@@ -0,0 +1,239 @@
+use neo4rs::query;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::path::Path;
+use tauri::{Emitter, State, Window};
+
+use crate::agent::truncate_output;
+use crate::llm::{complete_chat, LlmState};
+use crate::sandbox::WorkspaceState;
+use crate::{run_cypher, ChatMessage, Neo4jState};
+
+const MAX_FILE_CHARS: usize = 6000;
+const MAX_REDUCE_CHARS: usize = 12000;
+/// Small enough that every batch holds at least two parts, so each round
+/// at least halves the number of summaries.
+const MAX_PART_CHARS: usize = MAX_REDUCE_CHARS / 3;
+
+#[derive(Debug, Serialize, Clone)]
+struct SummarizeProgress {
+    stage: String, // "file", "module", "project"
+    current: usize,
+    total: usize,
+    path: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RepositorySummary {
+    pub overview: String,
+    pub files_summarized: usize,
+    pub modules_summarized: usize,
+}
+
+fn prompt(system: &str, user: String) -> Vec<ChatMessage> {
+    vec![
+        ChatMessage {
+            role: "system".to_string(),
+            content: system.to_string(),
+        },
+        ChatMessage {
+            role: "user".to_string(),
+            content: user,
+        },
+    ]
+}
+
+/// Reduces a list of summaries to one, folding in batches when they do not
+/// fit a single prompt. Overlong summaries are truncated so the folding
+/// always converges.
+async fn reduce_summaries(
+    llm: &LlmState,
+    model: &str,
+    system: &str,
+    label: &str,
+    mut parts: Vec<String>,
+) -> Result<String, String> {
+    loop {
+        let mut batches: Vec<String> = Vec::new();
+        let mut current = String::new();
+        for part in parts.into_iter().map(|part| truncate_output(part, MAX_PART_CHARS)) {
+            if !current.is_empty() && current.len() + part.len() > MAX_REDUCE_CHARS {
+                batches.push(std::mem::take(&mut current));
+            }
+            current.push_str(&part);
+            current.push_str("\n\n");
+        }
+        if !current.is_empty() {
+            batches.push(current);
+        }
+
+        let mut reduced = Vec::new();
+        for batch in batches {
+            let summary = complete_chat(llm, model, prompt(system, format!("{}:\n\n{}", label, batch))).await?;
+            reduced.push(summary);
+        }
+
+        if reduced.len() <= 1 {
+            return Ok(reduced.pop().unwrap_or_default());
+        }
+        parts = reduced;
+    }
+}
+
+/// Summarizes the project bottom-up: every FILE node, then each directory from
+/// its file summaries, then the whole project from the directory summaries.
+/// File and module summaries are written back to the graph as `summary`
+/// properties so later queries can use them as context.
+#[tauri::command]
+pub async fn summarize_repository(
+    window: Window,
+    root: String,
+    model: String,
+    neo4j: State<'_, Neo4jState>,
+    llm: State<'_, LlmState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<RepositorySummary, String> {
+    workspace.check(&root)?;
+    let graph = neo4j.get_graph().await?;
+    let rows = run_cypher(&graph, "MATCH (f:FILE) RETURN f.path AS path ORDER BY path").await?;
+    let paths: Vec<String> = rows
+        .data
+        .iter()
+        .filter_map(|row| row.get("path").and_then(|p| p.as_str()).map(|p| p.to_string()))
+        .collect();
+
+    if paths.is_empty() {
+        return Err("The code graph has no files; build and store it first".to_string());
+    }
+
+    // Map: one summary per file, grouped by directory relative to the root
+    let mut modules: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let total = paths.len();
+    let mut files_summarized = 0;
+
+    for (i, path) in paths.iter().enumerate() {
+        let _ = window.emit(
+            "summarize-progress",
+            SummarizeProgress {
+                stage: "file".to_string(),
+                current: i + 1,
+                total,
+                path: path.clone(),
+            },
+        );
+
+        let full_path = if Path::new(path).is_relative() {
+            Path::new(&root).join(path)
+        } else {
+            Path::new(path).to_path_buf()
+        };
+        // Graph rows may hold absolute paths from anywhere, so each file is checked
+        let Ok(checked) = workspace.check(&full_path) else {
+            continue;
+        };
+        let content = match tokio::fs::read_to_string(&checked).await {
+            Ok(content) => content,
+            Err(_) => continue,
+        };
+
+        let summary = complete_chat(
+            &llm,
+            &model,
+            prompt(
+                "You summarize source files for other engineers. In 2-4 sentences state what the file is for, its main types or functions, and what it depends on. No preamble.",
+                format!("File `{}`:\n\n```\n{}\n```", path, truncate_output(content, MAX_FILE_CHARS)),
+            ),
+        )
+        .await?;
+
+        graph
+            .run(
+                query("MATCH (f:FILE {path: $path}) SET f.summary = $summary")
+                    .param("path", path.clone())
+                    .param("summary", summary.clone()),
+            )
+            .await
+            .map_err(|e| format!("Failed to store summary for {}: {}", path, e))?;
+
+        let relative = full_path.strip_prefix(&root).unwrap_or(&full_path);
+        let module = relative
+            .parent()
+            .map(|p| p.to_string_lossy().replace('\\', "/"))
+            .filter(|p| !p.is_empty())
+            .unwrap_or_else(|| ".".to_string());
+        modules.entry(module).or_default().push(format!("- `{}`: {}", path, summary));
+        files_summarized += 1;
+    }
+
+    // Reduce: files -> modules
+    let module_total = modules.len();
+    let mut module_summaries = Vec::new();
+
+    for (i, (module, file_summaries)) in modules.into_iter().enumerate() {
+        let _ = window.emit(
+            "summarize-progress",
+            SummarizeProgress {
+                stage: "module".to_string(),
+                current: i + 1,
+                total: module_total,
+                path: module.clone(),
+            },
+        );
+
+        let summary = reduce_summaries(
+            &llm,
+            &model,
+            "You summarize a directory of a codebase from summaries of its files. In one short paragraph describe the directory's responsibility and how its files fit together.",
+            &format!("File summaries for directory `{}`", module),
+            file_summaries,
+        )
+        .await?;
+
+        graph
+            .run(
+                query("MERGE (m:MODULE {id: $id}) SET m.name = $path, m.path = $path, m.summary = $summary")
+                    .param("id", format!("module:{}", module))
+                    .param("path", module.clone())
+                    .param("summary", summary.clone()),
+            )
+            .await
+            .map_err(|e| format!("Failed to store summary for module {}: {}", module, e))?;
+
+        module_summaries.push(format!("### {}\n{}", module, summary));
+    }
+
+    // Reduce: modules -> project
+    let _ = window.emit(
+        "summarize-progress",
+        SummarizeProgress {
+            stage: "project".to_string(),
+            current: 1,
+            total: 1,
+            path: root.clone(),
+        },
+    );
+
+    let overview = reduce_summaries(
+        &llm,
+        &model,
+        "You write the architecture overview of a software project from summaries of its directories. Produce a Markdown document with: purpose, main components and their responsibilities, how data flows between them, and key technologies. Be concrete and concise.",
+        "Directory summaries",
+        module_summaries,
+    )
+    .await?;
+
+    graph
+        .run(
+            query("MERGE (p:PROJECT {id: 'project'}) SET p.path = $path, p.summary = $summary")
+                .param("path", root.clone())
+                .param("summary", overview.clone()),
+        )
+        .await
+        .map_err(|e| format!("Failed to store project summary: {}", e))?;
+
+    Ok(RepositorySummary {
+        overview,
+        files_summarized,
+        modules_summarized: module_total,
+    })
+}
@@ -0,0 +1,1371 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+use tauri::{AppHandle, Manager, State};
+use tree_sitter::{Node, Point};
+
+pub(crate) use gencode_core::syntax::node_text;
+
+use crate::event_bus::emit_indexing_finished;
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::files::{read_text, resolve_path};
+use crate::finder::relative_path;
+use crate::locks::LockExt;
+use crate::sandbox::WorkspaceState;
+use crate::ParserState;
+
+/// Definition kinds whose members are qualified with their name.
+const CONTAINER_KINDS: &[&str] = &["class", "interface", "struct", "trait", "enum", "union", "module"];
+
+/// Nodes whose bodies hold local definitions.
+pub(crate) const FUNCTION_NODES: &[&str] = &[
+    "function_declaration",
+    "generator_function_declaration",
+    "function_definition",
+    "function_item",
+    "method_definition",
+    "method_declaration",
+    "constructor_declaration",
+    "arrow_function",
+    "function",
+    "function_expression",
+    "generator_function",
+    "lambda",
+    "closure_expression",
+    "func_literal",
+    "lambda_expression",
+];
+
+const JS_EXTENSIONS: &[&str] = &[".ts", ".tsx", ".d.ts", ".js", ".jsx", ".mjs", ".cjs"];
+
+// ============================================================================
+// SYMBOL STRUCTURES
+// ============================================================================
+
+/// 0-based line and character column.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct SourcePosition {
+    pub line: usize,
+    pub column: usize,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+pub struct SourceRange {
+    pub start: SourcePosition,
+    pub end: SourcePosition,
+}
+
+impl SourceRange {
+    pub(crate) fn contains(&self, position: SourcePosition) -> bool {
+        self.start <= position && position <= self.end
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SymbolInfo {
+    /// `relative/path#Container.name`, suffixed `~n` for repeated names.
+    pub id: String,
+    pub name: String,
+    pub kind: String,
+    pub path: String,
+    pub relative_path: String,
+    pub container: Option<String>,
+    pub range: SourceRange,
+    pub name_range: SourceRange,
+    /// Defined inside a function body, so invisible to other files.
+    #[serde(skip)]
+    pub(crate) local: bool,
+    /// Range of the enclosing function for locals.
+    #[serde(skip)]
+    pub(crate) scope: Option<SourceRange>,
+}
+
+impl SymbolInfo {
+    /// Whether the symbol is in scope at `position` of its own file.
+    pub(crate) fn visible_at(&self, position: SourcePosition) -> bool {
+        match self.scope {
+            Some(scope) => scope.contains(position),
+            None => true,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportedName {
+    /// Exported name, `default` for default imports or `*` for a whole
+    /// module.
+    pub name: String,
+    /// Local binding, when it differs from `name`.
+    pub alias: Option<String>,
+}
+
+impl ImportedName {
+    pub(crate) fn local_name(&self) -> Option<&str> {
+        match (&self.alias, self.name.as_str()) {
+            (Some(alias), _) => Some(alias),
+            (None, "*" | "default") => None,
+            (None, name) => Some(name),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ImportInfo {
+    pub source: String,
+    pub names: Vec<ImportedName>,
+    /// Project file (or package directory, for Go and Java wildcards) the
+    /// import points to, relative to the root.
+    pub resolved: Option<String>,
+    pub line: usize,
+}
+
+pub(crate) struct FileSymbols {
+    pub(crate) language: String,
+    modified: u64,
+    pub(crate) symbols: Vec<SymbolInfo>,
+    pub(crate) imports: Vec<ImportInfo>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SymbolIndexSummary {
+    pub root: String,
+    pub files: usize,
+    pub symbols: usize,
+    pub imports: usize,
+    pub resolved_imports: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DefinitionCandidate {
+    pub symbol: SymbolInfo,
+    /// "local", "import" or "project", best first.
+    pub reason: String,
+}
+
+// ============================================================================
+// POSITIONS
+// ============================================================================
+
+/// Converts between tree-sitter byte points and character positions.
+pub(crate) struct LineIndex<'a> {
+    content: &'a str,
+    starts: Vec<usize>,
+}
+
+impl<'a> LineIndex<'a> {
+    pub(crate) fn new(content: &'a str) -> Self {
+        let mut starts = vec![0];
+        starts.extend(content.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { content, starts }
+    }
+
+    pub(crate) fn position(&self, point: Point) -> SourcePosition {
+        let start = self.starts.get(point.row).copied().unwrap_or(self.content.len());
+        let column = self
+            .content
+            .get(start..start + point.column)
+            .map(|prefix| prefix.chars().count())
+            .unwrap_or(point.column);
+        SourcePosition {
+            line: point.row,
+            column,
+        }
+    }
+
+    pub(crate) fn range(&self, node: Node) -> SourceRange {
+        SourceRange {
+            start: self.position(node.start_position()),
+            end: self.position(node.end_position()),
+        }
+    }
+
+    pub(crate) fn point(&self, line: usize, column: usize) -> Point {
+        let start = self.starts.get(line).copied().unwrap_or(self.content.len());
+        let text = &self.content[start..];
+        let text = &text[..text.find('\n').unwrap_or(text.len())];
+        let bytes = text.char_indices().nth(column).map(|(i, _)| i).unwrap_or(text.len());
+        Point::new(line, bytes)
+    }
+
+    /// Byte offset of a character position.
+    pub(crate) fn offset(&self, position: SourcePosition) -> usize {
+        let start = self.starts.get(position.line).copied().unwrap_or(self.content.len());
+        start + self.point(position.line, position.column).column
+    }
+}
+
+/// Identifier-like leaves that can name or refer to a symbol.
+pub(crate) fn is_identifier(kind: &str) -> bool {
+    matches!(
+        kind,
+        "identifier"
+            | "type_identifier"
+            | "field_identifier"
+            | "property_identifier"
+            | "shorthand_property_identifier"
+            | "shorthand_property_identifier_pattern"
+            | "private_property_identifier"
+            | "package_identifier"
+            | "namespace_identifier"
+            | "constant"
+    )
+}
+
+// ============================================================================
+// SYMBOL EXTRACTION
+// ============================================================================
+
+#[derive(Clone, Copy, PartialEq)]
+enum Family {
+    Js,
+    Python,
+    Rust,
+    Java,
+    Go,
+    C,
+}
+
+fn family(language: &str) -> Option<Family> {
+    match language {
+        "javascript" | "typescript" | "tsx" => Some(Family::Js),
+        "python" => Some(Family::Python),
+        "rust" => Some(Family::Rust),
+        "java" => Some(Family::Java),
+        "go" => Some(Family::Go),
+        "c" | "cpp" => Some(Family::C),
+        _ => None,
+    }
+}
+
+/// Kind of the node two levels up, skipping Python decorators.
+fn grandparent_kind(node: Node) -> Option<&'static str> {
+    let mut parent = node.parent()?;
+    if parent.kind() == "decorated_definition" {
+        parent = parent.parent()?;
+    }
+    parent.parent().map(|gp| gp.kind())
+}
+
+/// The identifier at the bottom of a C declarator chain.
+fn declarator_name(mut node: Node) -> Option<Node> {
+    loop {
+        match node.kind() {
+            "identifier" | "field_identifier" | "type_identifier" | "destructor_name" | "operator_name" => {
+                return Some(node)
+            }
+            "qualified_identifier" => node = node.child_by_field_name("name")?,
+            _ => node = node.child_by_field_name("declarator")?,
+        }
+    }
+}
+
+/// Symbol kind and name node if `node` defines something.
+fn definition<'a>(family: Family, node: Node<'a>) -> Option<(&'static str, Node<'a>)> {
+    let name = || node.child_by_field_name("name");
+    let kind = node.kind();
+    let found = match (family, kind) {
+        (Family::Js, "function_declaration" | "generator_function_declaration" | "function_signature") => {
+            ("function", name()?)
+        }
+        (Family::Js, "class_declaration" | "abstract_class_declaration") => ("class", name()?),
+        (Family::Js, "method_definition" | "method_signature" | "abstract_method_signature") => ("method", name()?),
+        (Family::Js, "public_field_definition" | "field_definition") => {
+            ("property", name().or_else(|| node.child_by_field_name("property"))?)
+        }
+        (Family::Js, "interface_declaration") => ("interface", name()?),
+        (Family::Js, "type_alias_declaration") => ("type", name()?),
+        (Family::Js, "enum_declaration") => ("enum", name()?),
+        (Family::Js, "variable_declarator") => {
+            let name = name().filter(|n| n.kind() == "identifier")?;
+            let value = node.child_by_field_name("value").map(|v| v.kind());
+            match value {
+                Some("arrow_function" | "function" | "function_expression" | "generator_function") => ("function", name),
+                Some("class") => ("class", name),
+                _ => ("variable", name),
+            }
+        }
+
+        (Family::Python, "function_definition") => {
+            let kind = if grandparent_kind(node) == Some("class_definition") {
+                "method"
+            } else {
+                "function"
+            };
+            (kind, name()?)
+        }
+        (Family::Python, "class_definition") => ("class", name()?),
+        (Family::Python, "assignment") => {
+            let left = node.child_by_field_name("left").filter(|l| l.kind() == "identifier")?;
+            ("variable", left)
+        }
+
+        (Family::Rust, "function_item" | "function_signature_item") => {
+            let kind = match grandparent_kind(node) {
+                Some("impl_item" | "trait_item") => "method",
+                _ => "function",
+            };
+            (kind, name()?)
+        }
+        (Family::Rust, "struct_item") => ("struct", name()?),
+        (Family::Rust, "enum_item") => ("enum", name()?),
+        (Family::Rust, "union_item") => ("union", name()?),
+        (Family::Rust, "trait_item") => ("trait", name()?),
+        (Family::Rust, "type_item") => ("type", name()?),
+        (Family::Rust, "const_item") => ("constant", name()?),
+        (Family::Rust, "static_item") => ("variable", name()?),
+        (Family::Rust, "mod_item") => ("module", name()?),
+        (Family::Rust, "macro_definition") => ("macro", name()?),
+        (Family::Rust, "enum_variant") => ("enum_member", name()?),
+        (Family::Rust, "field_declaration") => ("field", name()?),
+        (Family::Rust, "let_declaration") => {
+            let pattern = node.child_by_field_name("pattern").filter(|p| p.kind() == "identifier")?;
+            ("variable", pattern)
+        }
+
+        (Family::Java, "class_declaration" | "record_declaration") => ("class", name()?),
+        (Family::Java, "interface_declaration" | "annotation_type_declaration") => ("interface", name()?),
+        (Family::Java, "enum_declaration") => ("enum", name()?),
+        (Family::Java, "method_declaration") => ("method", name()?),
+        (Family::Java, "constructor_declaration") => ("constructor", name()?),
+        (Family::Java, "enum_constant") => ("enum_member", name()?),
+        (Family::Java, "variable_declarator") => {
+            let kind = match node.parent().map(|p| p.kind()) {
+                Some("field_declaration") => "field",
+                Some("constant_declaration") => "constant",
+                _ => "variable",
+            };
+            (kind, name()?)
+        }
+
+        (Family::Go, "function_declaration") => ("function", name()?),
+        (Family::Go, "method_declaration") => ("method", name()?),
+        (Family::Go, "type_spec") => {
+            let kind = match node.child_by_field_name("type").map(|t| t.kind()) {
+                Some("struct_type") => "struct",
+                Some("interface_type") => "interface",
+                _ => "type",
+            };
+            (kind, name()?)
+        }
+        (Family::Go, "const_spec") => ("constant", name()?),
+        (Family::Go, "var_spec") => ("variable", name()?),
+        (Family::Go, "field_declaration") => ("field", name()?),
+
+        (Family::C, "function_definition") => {
+            let kind = if node.parent().is_some_and(|p| p.kind() == "field_declaration_list") {
+                "method"
+            } else {
+                "function"
+            };
+            (kind, declarator_name(node.child_by_field_name("declarator")?)?)
+        }
+        (Family::C, "struct_specifier" | "union_specifier" | "enum_specifier" | "class_specifier") => {
+            node.child_by_field_name("body")?;
+            let kind = match kind {
+                "struct_specifier" => "struct",
+                "union_specifier" => "union",
+                "enum_specifier" => "enum",
+                _ => "class",
+            };
+            (kind, name()?)
+        }
+        (Family::C, "type_definition") => ("type", declarator_name(node.child_by_field_name("declarator")?)?),
+        (Family::C, "enumerator") => ("enum_member", name()?),
+        (Family::C, "namespace_definition") => ("module", name()?),
+        (Family::C, "field_declaration") => {
+            let declarator = node.child_by_field_name("declarator")?;
+            let kind = if declarator.kind() == "function_declarator" {
+                "method"
+            } else {
+                "field"
+            };
+            (kind, declarator_name(declarator)?)
+        }
+
+        _ => return None,
+    };
+    Some(found)
+}
+
+/// Kind and name of the symbol `node` defines in `language`, if any.
+pub(crate) fn defined_symbol<'a>(language: &str, node: Node, source: &'a [u8]) -> Option<(&'static str, &'a str)> {
+    let (kind, name) = definition(family(language)?, node)?;
+    Some((kind, node_text(name, source)))
+}
+
+/// Name members of an unnamed container get qualified with: the type of
+/// a Rust impl block or the receiver of a Go method.
+fn implicit_container(family: Family, node: Node, source: &[u8]) -> Option<String> {
+    match (family, node.kind()) {
+        (Family::Rust, "impl_item") => {
+            let ty = node_text(node.child_by_field_name("type")?, source);
+            Some(ty.split('<').next().unwrap_or(ty).trim().to_string())
+        }
+        (Family::Go, "method_declaration") => {
+            let receiver = node.child_by_field_name("receiver")?;
+            let mut stack = vec![receiver];
+            while let Some(n) = stack.pop() {
+                if n.kind() == "type_identifier" {
+                    return Some(node_text(n, source).to_string());
+                }
+                let mut cursor = n.walk();
+                stack.extend(n.named_children(&mut cursor));
+            }
+            None
+        }
+        _ => None,
+    }
+}
+
+fn unquote(text: &str) -> String {
+    text.trim_matches(|c| c == '"' || c == '\'' || c == '`' || c == '<' || c == '>').to_string()
+}
+
+fn imported(name: &str, alias: Option<&str>) -> ImportedName {
+    ImportedName {
+        name: name.to_string(),
+        alias: alias.filter(|a| *a != name).map(|a| a.to_string()),
+    }
+}
+
+/// Flattens a Rust use tree into (module path, item) pairs.
+fn rust_use_items(node: Node, prefix: &str, source: &[u8], out: &mut Vec<(String, ImportedName)>) {
+    let join = |prefix: &str, path: &str| {
+        if prefix.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}::{}", prefix, path)
+        }
+    };
+    match node.kind() {
+        "identifier" | "self" | "crate" | "super" => {
+            out.push((prefix.to_string(), imported(node_text(node, source), None)));
+        }
+        "scoped_identifier" => {
+            let path = node.child_by_field_name("path").map(|p| node_text(p, source)).unwrap_or_default();
+            if let Some(name) = node.child_by_field_name("name") {
+                out.push((join(prefix, path), imported(node_text(name, source), None)));
+            }
+        }
+        "use_as_clause" => {
+            let (Some(path), Some(alias)) = (node.child_by_field_name("path"), node.child_by_field_name("alias")) else {
+                return;
+            };
+            let alias = node_text(alias, source);
+            let path = node_text(path, source);
+            let (module, name) = path.rsplit_once("::").unwrap_or(("", path));
+            out.push((join(prefix, module), imported(name, Some(alias))));
+        }
+        "scoped_use_list" => {
+            let path = node.child_by_field_name("path").map(|p| node_text(p, source)).unwrap_or_default();
+            if let Some(list) = node.child_by_field_name("list") {
+                rust_use_items(list, &join(prefix, path), source, out);
+            }
+        }
+        "use_list" => {
+            let mut cursor = node.walk();
+            for child in node.named_children(&mut cursor) {
+                rust_use_items(child, prefix, source, out);
+            }
+        }
+        "use_wildcard" => {
+            let path = node.named_child(0).map(|p| node_text(p, source)).unwrap_or_default();
+            out.push((join(prefix, path), imported("*", None)));
+        }
+        _ => {}
+    }
+}
+
+/// Imports declared by `node`, without resolution.
+fn imports(family: Family, node: Node, source: &[u8]) -> Vec<ImportInfo> {
+    let line = node.start_position().row;
+    let import = |source_name: String, names: Vec<ImportedName>| ImportInfo {
+        source: source_name,
+        names,
+        resolved: None,
+        line,
+    };
+    let mut cursor = node.walk();
+
+    match (family, node.kind()) {
+        (Family::Js, "import_statement") => {
+            let Some(from) = node.child_by_field_name("source") else {
+                return Vec::new();
+            };
+            let mut names = Vec::new();
+            for clause in node.named_children(&mut cursor).filter(|c| c.kind() == "import_clause") {
+                let mut clause_cursor = clause.walk();
+                for part in clause.named_children(&mut clause_cursor) {
+                    match part.kind() {
+                        "identifier" => names.push(imported("default", Some(node_text(part, source)))),
+                        "namespace_import" => {
+                            let alias = part.named_child(0).map(|a| node_text(a, source));
+                            names.push(imported("*", alias));
+                        }
+                        "named_imports" => {
+                            let mut spec_cursor = part.walk();
+                            for spec in part.named_children(&mut spec_cursor) {
+                                let Some(name) = spec.child_by_field_name("name") else {
+                                    continue;
+                                };
+                                let alias = spec.child_by_field_name("alias").map(|a| node_text(a, source));
+                                names.push(imported(node_text(name, source), alias));
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            vec![import(unquote(node_text(from, source)), names)]
+        }
+
+        (Family::Python, "import_statement") => node
+            .children_by_field_name("name", &mut cursor)
+            .map(|module| {
+                let (path, alias) = match module.kind() {
+                    "aliased_import" => (
+                        module.child_by_field_name("name").map(|n| node_text(n, source)).unwrap_or_default(),
+                        module.child_by_field_name("alias").map(|a| node_text(a, source)),
+                    ),
+                    _ => {
+                        let path = node_text(module, source);
+                        (path, path.split('.').next())
+                    }
+                };
+                import(path.to_string(), vec![imported("*", alias)])
+            })
+            .collect(),
+        (Family::Python, "import_from_statement") => {
+            let module = node
+                .child_by_field_name("module_name")
+                .map(|m| node_text(m, source).to_string())
+                .unwrap_or_default();
+            let mut names: Vec<ImportedName> = node
+                .children_by_field_name("name", &mut cursor)
+                .map(|name| match name.kind() {
+                    "aliased_import" => imported(
+                        name.child_by_field_name("name").map(|n| node_text(n, source)).unwrap_or_default(),
+                        name.child_by_field_name("alias").map(|a| node_text(a, source)),
+                    ),
+                    _ => imported(node_text(name, source), None),
+                })
+                .collect();
+            let mut wildcard_cursor = node.walk();
+            if node.named_children(&mut wildcard_cursor).any(|c| c.kind() == "wildcard_import") {
+                names.push(imported("*", None));
+            }
+            vec![import(module, names)]
+        }
+
+        (Family::Rust, "use_declaration") => {
+            let Some(argument) = node.child_by_field_name("argument") else {
+                return Vec::new();
+            };
+            let mut items = Vec::new();
+            rust_use_items(argument, "", source, &mut items);
+            items.into_iter().map(|(module, name)| import(module, vec![name])).collect()
+        }
+
+        (Family::Go, "import_spec") => {
+            let Some(path) = node.child_by_field_name("path") else {
+                return Vec::new();
+            };
+            let path = unquote(node_text(path, source));
+            let alias = node
+                .child_by_field_name("name")
+                .map(|n| node_text(n, source).to_string())
+                .unwrap_or_else(|| path.rsplit('/').next().unwrap_or(&path).to_string());
+            vec![import(path, vec![imported("*", Some(alias.as_str()))])]
+        }
+
+        (Family::Java, "import_declaration") => {
+            let Some(path) = node
+                .named_children(&mut cursor)
+                .find(|c| matches!(c.kind(), "scoped_identifier" | "identifier"))
+            else {
+                return Vec::new();
+            };
+            let path = node_text(path, source);
+            let mut wildcard_cursor = node.walk();
+            if node.named_children(&mut wildcard_cursor).any(|c| c.kind() == "asterisk") {
+                return vec![import(path.to_string(), vec![imported("*", None)])];
+            }
+            let name = path.rsplit('.').next().unwrap_or(path);
+            vec![import(path.to_string(), vec![imported(name, None)])]
+        }
+
+        (Family::C, "preproc_include") => match node.child_by_field_name("path") {
+            Some(path) if path.kind() == "string_literal" => {
+                vec![import(unquote(node_text(path, source)), vec![imported("*", None)])]
+            }
+            _ => Vec::new(),
+        },
+
+        _ => Vec::new(),
+    }
+}
+
+struct Extractor<'a> {
+    family: Family,
+    source: &'a [u8],
+    lines: LineIndex<'a>,
+    relative: &'a str,
+    path: String,
+    symbols: Vec<SymbolInfo>,
+    imports: Vec<ImportInfo>,
+    seen_ids: HashMap<String, usize>,
+}
+
+impl Extractor<'_> {
+    fn visit(&mut self, node: Node, container: Option<&str>, scope: Option<SourceRange>) {
+        let mut child_container = container.map(|c| c.to_string());
+        let mut child_scope = scope;
+
+        if let Some((kind, name_node)) = definition(self.family, node) {
+            let name = node_text(name_node, self.source).to_string();
+            if !name.is_empty() {
+                let qualified = match container {
+                    Some(c) => format!("{}.{}", c, name),
+                    None => name.clone(),
+                };
+                let count = self.seen_ids.entry(qualified.clone()).or_insert(0);
+                *count += 1;
+                let id = match *count {
+                    1 => format!("{}#{}", self.relative, qualified),
+                    n => format!("{}#{}~{}", self.relative, qualified, n),
+                };
+
+                self.symbols.push(SymbolInfo {
+                    id,
+                    name,
+                    kind: kind.to_string(),
+                    path: self.path.clone(),
+                    relative_path: self.relative.to_string(),
+                    container: container.map(|c| c.to_string()),
+                    range: self.lines.range(node),
+                    name_range: self.lines.range(name_node),
+                    local: scope.is_some(),
+                    scope,
+                });
+                if CONTAINER_KINDS.contains(&kind) {
+                    child_container = Some(qualified);
+                }
+            }
+        }
+        if let Some(implicit) = implicit_container(self.family, node, self.source) {
+            child_container = Some(implicit);
+        }
+        if FUNCTION_NODES.contains(&node.kind()) {
+            child_scope = Some(self.lines.range(node));
+        }
+
+        self.imports.extend(imports(self.family, node, self.source));
+
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            self.visit(child, child_container.as_deref(), child_scope);
+        }
+    }
+}
+
+fn modified_nanos(path: &Path) -> u64 {
+    std_fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+// ============================================================================
+// IMPORT RESOLUTION
+// ============================================================================
+
+/// Project-level settings import resolution depends on.
+#[derive(Default)]
+struct ImportConfig {
+    /// tsconfig `paths`, as (prefix before `*`, replacement prefixes).
+    ts_paths: Vec<(String, Vec<String>)>,
+    go_module: Option<String>,
+}
+
+/// Removes `//` and `/* */` comments and trailing commas, which tsconfig
+/// files allow.
+fn strip_json_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        out.push('\n');
+                        break;
+                    }
+                }
+            }
+            ('/', Some('*')) => {
+                chars.next();
+                let mut prev = ' ';
+                for c in chars.by_ref() {
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            (',', _) => {
+                let next = chars.clone().find(|c| !c.is_whitespace());
+                if !matches!(next, Some('}' | ']')) {
+                    out.push(c);
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+impl ImportConfig {
+    fn load(root: &Path) -> Self {
+        let mut config = ImportConfig::default();
+
+        let tsconfig = std_fs::read_to_string(root.join("tsconfig.json"))
+            .ok()
+            .and_then(|text| serde_json::from_str::<serde_json::Value>(&strip_json_comments(&text)).ok());
+        if let Some(options) = tsconfig.as_ref().and_then(|t| t.get("compilerOptions")) {
+            let base = options.get("baseUrl").and_then(|b| b.as_str()).unwrap_or(".");
+            if let Some(paths) = options.get("paths").and_then(|p| p.as_object()) {
+                for (pattern, targets) in paths {
+                    let targets = targets
+                        .as_array()
+                        .map(|t| t.iter().filter_map(|t| t.as_str()).collect::<Vec<_>>())
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|t| normalize(&format!("{}/{}", base, t.trim_end_matches('*'))))
+                        .collect();
+                    config.ts_paths.push((pattern.trim_end_matches('*').to_string(), targets));
+                }
+            }
+        }
+
+        config.go_module = std_fs::read_to_string(root.join("go.mod")).ok().and_then(|text| {
+            text.lines()
+                .find_map(|line| line.trim().strip_prefix("module "))
+                .map(|m| m.trim().to_string())
+        });
+        config
+    }
+}
+
+/// Resolves `.` and `..` in a '/'-separated relative path.
+fn normalize(path: &str) -> String {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split('/') {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop();
+            }
+            _ => parts.push(part),
+        }
+    }
+    parts.join("/")
+}
+
+fn parent_dir(relative: &str) -> &str {
+    relative.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("")
+}
+
+fn join_relative(dir: &str, path: &str) -> String {
+    if dir.is_empty() {
+        normalize(path)
+    } else {
+        normalize(&format!("{}/{}", dir, path))
+    }
+}
+
+/// Rust module directory of a file: `a/b.rs` owns `a/b/`, while `mod.rs`,
+/// `lib.rs` and `main.rs` own their own directory.
+fn rust_module_dir(relative: &str) -> String {
+    let dir = parent_dir(relative);
+    match relative.rsplit('/').next() {
+        Some("mod.rs" | "lib.rs" | "main.rs") => dir.to_string(),
+        _ => relative.trim_end_matches(".rs").to_string(),
+    }
+}
+
+fn resolve_import(
+    files: &HashMap<String, FileSymbols>,
+    config: &ImportConfig,
+    from: &str,
+    family: Family,
+    source: &str,
+) -> Option<String> {
+    let is_file = |candidate: &String| files.contains_key(candidate);
+    let is_package = |dir: &str| files.keys().any(|f| parent_dir(f) == dir);
+    let dir = parent_dir(from);
+
+    match family {
+        Family::Js => {
+            let mut bases = Vec::new();
+            if source.starts_with('.') {
+                bases.push(join_relative(dir, source));
+            } else {
+                for (prefix, targets) in &config.ts_paths {
+                    if let Some(rest) = source.strip_prefix(prefix.as_str()) {
+                        bases.extend(targets.iter().map(|t| join_relative(t, rest)));
+                    }
+                }
+            }
+            bases.into_iter().find_map(|base| {
+                let stem = base.strip_suffix(".js").unwrap_or(&base).to_string();
+                std::iter::once(base.clone())
+                    .chain(JS_EXTENSIONS.iter().map(|ext| format!("{}{}", stem, ext)))
+                    .chain(JS_EXTENSIONS.iter().map(|ext| format!("{}/index{}", base, ext)))
+                    .find(is_file)
+            })
+        }
+        Family::Python => {
+            let dots = source.chars().take_while(|c| *c == '.').count();
+            let module = source[dots..].replace('.', "/");
+            let base = if dots > 0 {
+                let mut base = dir.to_string();
+                for _ in 1..dots {
+                    base = parent_dir(&base).to_string();
+                }
+                join_relative(&base, &module)
+            } else {
+                module
+            };
+            [format!("{}.py", base), format!("{}/__init__.py", base)]
+                .into_iter()
+                .chain([format!("src/{}.py", base), format!("src/{}/__init__.py", base)])
+                .find(is_file)
+        }
+        Family::Rust => {
+            let mut segments: Vec<&str> = source.split("::").filter(|s| !s.is_empty()).collect();
+            let mut base = match segments.first().copied() {
+                Some("crate") => {
+                    // The crate root is the nearest directory with lib.rs or main.rs
+                    let mut crate_dir = dir.to_string();
+                    loop {
+                        if ["lib.rs", "main.rs"].iter().any(|f| is_file(&join_relative(&crate_dir, f))) {
+                            break;
+                        }
+                        if crate_dir.is_empty() {
+                            return None;
+                        }
+                        crate_dir = parent_dir(&crate_dir).to_string();
+                    }
+                    segments.remove(0);
+                    crate_dir
+                }
+                Some("self") => {
+                    segments.remove(0);
+                    rust_module_dir(from)
+                }
+                Some("super") => {
+                    let mut base = rust_module_dir(from);
+                    while segments.first() == Some(&"super") {
+                        segments.remove(0);
+                        base = parent_dir(&base).to_string();
+                    }
+                    base
+                }
+                _ => rust_module_dir(from),
+            };
+            if segments.is_empty() {
+                let owner = ["mod.rs", "lib.rs", "main.rs"].iter().map(|f| join_relative(&base, f)).find(is_file);
+                return owner.or_else(|| Some(format!("{}.rs", base)).filter(is_file));
+            }
+            // The deepest module file along the path wins; later segments are items
+            let mut found = None;
+            for segment in segments {
+                base = join_relative(&base, segment);
+                match [format!("{}.rs", base), format!("{}/mod.rs", base)].into_iter().find(is_file) {
+                    Some(file) => found = Some(file),
+                    None => break,
+                }
+            }
+            found
+        }
+        Family::Go => {
+            let module = config.go_module.as_deref()?;
+            let package = source.strip_prefix(module)?.trim_start_matches('/');
+            Some(package.to_string()).filter(|p| is_package(p))
+        }
+        Family::Java => {
+            let path = source.replace('.', "/");
+            files
+                .keys()
+                .find(|f| f.ends_with(&format!("{}.java", path)))
+                .cloned()
+                .or_else(|| {
+                    files
+                        .keys()
+                        .map(|f| parent_dir(f))
+                        .find(|d| d.ends_with(&path))
+                        .map(|d| d.to_string())
+                })
+        }
+        Family::C => [join_relative(dir, source), normalize(source), join_relative("include", source)]
+            .into_iter()
+            .find(is_file),
+    }
+}
+
+// ============================================================================
+// SYMBOL TABLE
+// ============================================================================
+
+/// Definitions and imports of every source file in the open project.
+#[derive(Default)]
+pub(crate) struct SymbolTable {
+    pub(crate) root: PathBuf,
+    config: ImportConfig,
+    pub(crate) files: HashMap<String, FileSymbols>,
+}
+
+/// Parses `content` and extracts its definitions and imports.
+pub(crate) fn extract_symbols(
+    parser: &ParserState,
+    root: &Path,
+    relative: &str,
+    content: &str,
+) -> Option<FileSymbols> {
+    let path = root.join(relative);
+    let (language, tree) = parser.parse_tree(&path.to_string_lossy(), content)?;
+    let mut extractor = Extractor {
+        family: family(&language)?,
+        source: content.as_bytes(),
+        lines: LineIndex::new(content),
+        relative,
+        path: path.to_string_lossy().to_string(),
+        symbols: Vec::new(),
+        imports: Vec::new(),
+        seen_ids: HashMap::new(),
+    };
+    extractor.visit(tree.root_node(), None, None);
+
+    Some(FileSymbols {
+        language,
+        modified: modified_nanos(&path),
+        symbols: extractor.symbols,
+        imports: extractor.imports,
+    })
+}
+
+impl SymbolTable {
+    fn resolve_imports(&mut self, relative: &str) {
+        let Some(file) = self.files.get(relative) else {
+            return;
+        };
+        let Some(family) = family(&file.language) else {
+            return;
+        };
+        let resolved: Vec<Option<String>> = file
+            .imports
+            .iter()
+            .map(|import| resolve_import(&self.files, &self.config, relative, family, &import.source))
+            .collect();
+        if let Some(file) = self.files.get_mut(relative) {
+            for (import, resolved) in file.imports.iter_mut().zip(resolved) {
+                import.resolved = resolved;
+            }
+        }
+    }
+
+    /// Re-extracts `relative` if it changed on disk since it was indexed.
+    /// Returns false if the file is gone or not parseable.
+    pub(crate) fn refresh(&mut self, parser: &ParserState, relative: &str) -> bool {
+        let path = self.root.join(relative);
+        if !path.is_file() {
+            self.files.remove(relative);
+            return false;
+        }
+        if self.files.get(relative).is_some_and(|f| f.modified == modified_nanos(&path)) {
+            return true;
+        }
+        let Some(symbols) = read_text(&path)
+            .ok()
+            .and_then(|(content, _)| extract_symbols(parser, &self.root, relative, &content))
+        else {
+            self.files.remove(relative);
+            return false;
+        };
+        self.files.insert(relative.to_string(), symbols);
+        self.resolve_imports(relative);
+        true
+    }
+
+    /// Re-extracts every changed source file under the root and resolves
+    /// imports against the new file set, calling `on_file(done, total)` after
+    /// each file. An error from it leaves the table as it was.
+    pub(crate) fn sync(
+        &mut self,
+        parser: &ParserState,
+        mut on_file: impl FnMut(usize, usize) -> Result<(), String>,
+    ) -> Result<(), String> {
+        self.config = ImportConfig::load(&self.root);
+        let mut on_disk = HashSet::new();
+        for (path, is_dir) in walk_entries(&self.root, &DirectoryOptions::default(), None)? {
+            if is_dir || parser.detect_language(&path.to_string_lossy()).is_none() {
+                continue;
+            }
+            if let Some(relative) = relative_path(&self.root, &path) {
+                on_disk.insert(relative);
+            }
+        }
+
+        let mut changed = Vec::new();
+        for (i, relative) in on_disk.iter().enumerate() {
+            let path = self.root.join(relative);
+            if !self.files.get(relative).is_some_and(|f| f.modified == modified_nanos(&path)) {
+                let symbols = read_text(&path)
+                    .ok()
+                    .and_then(|(content, _)| extract_symbols(parser, &self.root, relative, &content));
+                if let Some(symbols) = symbols {
+                    changed.push((relative.clone(), symbols));
+                }
+            }
+            on_file(i + 1, on_disk.len())?;
+        }
+
+        self.files.retain(|relative, _| on_disk.contains(relative));
+        self.files.extend(changed);
+
+        let relatives: Vec<String> = self.files.keys().cloned().collect();
+        for relative in relatives {
+            self.resolve_imports(&relative);
+        }
+        Ok(())
+    }
+
+    /// Definitions named `name` in a resolved import target, which is a
+    /// file or, for package imports, a directory.
+    fn definitions_in(&self, target: &str, name: &str) -> Vec<&SymbolInfo> {
+        let files: Vec<&FileSymbols> = match self.files.get(target) {
+            Some(file) => vec![file],
+            None => self
+                .files
+                .iter()
+                .filter(|(relative, _)| parent_dir(relative) == target)
+                .map(|(_, file)| file)
+                .collect(),
+        };
+        files
+            .into_iter()
+            .flat_map(|file| file.symbols.iter())
+            .filter(|s| !s.local && s.name == name)
+            .collect()
+    }
+
+    /// Stand-in symbol for an import target with no matching definition,
+    /// such as a default import.
+    fn module_symbol(&self, target: &str) -> SymbolInfo {
+        let origin = SourcePosition { line: 0, column: 0 };
+        let range = SourceRange {
+            start: origin,
+            end: origin,
+        };
+        SymbolInfo {
+            id: target.to_string(),
+            name: target.rsplit('/').next().unwrap_or(target).to_string(),
+            kind: "module".to_string(),
+            path: self.root.join(target).to_string_lossy().to_string(),
+            relative_path: target.to_string(),
+            container: None,
+            range,
+            name_range: range,
+            local: false,
+            scope: None,
+        }
+    }
+
+    pub(crate) fn symbol(&self, id: &str) -> Option<&SymbolInfo> {
+        let relative = id.split('#').next()?;
+        self.files.get(relative)?.symbols.iter().find(|s| s.id == id)
+    }
+
+    /// Candidate definitions of the identifier `name` used at `position`
+    /// in `relative`. `qualifier` is the object of a member access
+    /// (`qualifier.name`), if any.
+    pub(crate) fn definitions(
+        &self,
+        relative: &str,
+        name: &str,
+        qualifier: Option<&str>,
+        position: SourcePosition,
+    ) -> Vec<DefinitionCandidate> {
+        let mut candidates: Vec<DefinitionCandidate> = Vec::new();
+        fn push(symbol: SymbolInfo, reason: &str, candidates: &mut Vec<DefinitionCandidate>) {
+            if !candidates.iter().any(|c| c.symbol.id == symbol.id) {
+                candidates.push(DefinitionCandidate {
+                    symbol,
+                    reason: reason.to_string(),
+                });
+            }
+        }
+        let Some(file) = self.files.get(relative) else {
+            return candidates;
+        };
+
+        match qualifier {
+            Some(qualifier) => {
+                // `ns.name` where `ns` is a module or package import
+                for import in &file.imports {
+                    let Some(target) = &import.resolved else {
+                        continue;
+                    };
+                    if import.names.iter().any(|n| n.name == "*" && n.local_name() == Some(qualifier)) {
+                        for symbol in self.definitions_in(target, name) {
+                            push(symbol.clone(), "import", &mut candidates);
+                        }
+                    }
+                }
+                // Qualified by a type of the same name (`Type::item`, `Class.method`)
+                for symbol in file.symbols.iter().filter(|s| s.name == name && s.container.as_deref() == Some(qualifier)) {
+                    push(symbol.clone(), "local", &mut candidates);
+                }
+            }
+            None => {
+                // Innermost enclosing scope first, then file-level definitions
+                let mut local: Vec<&SymbolInfo> = file
+                    .symbols
+                    .iter()
+                    .filter(|s| s.name == name && s.visible_at(position))
+                    .collect();
+                local.sort_by_key(|s| {
+                    let depth = s.scope.map(|scope| scope.start).unwrap_or(SourcePosition { line: 0, column: 0 });
+                    std::cmp::Reverse(depth)
+                });
+                for symbol in local {
+                    push(symbol.clone(), "local", &mut candidates);
+                }
+
+                for import in &file.imports {
+                    let Some(target) = &import.resolved else {
+                        continue;
+                    };
+                    for imported in &import.names {
+                        let binds = imported.local_name() == Some(name) || (imported.name == "*" && imported.alias.is_none());
+                        if !binds {
+                            continue;
+                        }
+                        let exported = if imported.name == "*" { name } else { imported.name.as_str() };
+                        let found = self.definitions_in(target, exported);
+                        if found.is_empty() && imported.name != "*" {
+                            push(self.module_symbol(target), "import", &mut candidates);
+                        }
+                        for symbol in found {
+                            push(symbol.clone(), "import", &mut candidates);
+                        }
+                    }
+                }
+            }
+        }
+
+        if candidates.is_empty() {
+            let mut project: Vec<&SymbolInfo> = self
+                .files
+                .values()
+                .flat_map(|f| f.symbols.iter())
+                .filter(|s| !s.local && s.name == name)
+                .filter(|s| qualifier.is_none() || s.container.is_some())
+                .collect();
+            project.sort_by(|a, b| {
+                (a.relative_path != relative)
+                    .cmp(&(b.relative_path != relative))
+                    .then_with(|| a.id.cmp(&b.id))
+            });
+            for symbol in project {
+                push(symbol.clone(), "project", &mut candidates);
+            }
+        }
+        candidates
+    }
+}
+
+// ============================================================================
+// SYMBOL INDEX STATE
+// ============================================================================
+
+#[derive(Default)]
+pub struct SymbolIndexState {
+    pub(crate) table: Arc<Mutex<SymbolTable>>,
+}
+
+/// The identifier under `position` and, for member accesses, the object
+/// it is accessed on.
+pub(crate) fn identifier_at<'a>(root: Node<'a>, point: Point, source: &'a [u8]) -> Option<(Node<'a>, Option<&'a str>)> {
+    let node = root.named_descendant_for_point_range(point, point)?;
+    if !is_identifier(node.kind()) {
+        return None;
+    }
+    Some((node, qualifier_of(node, source)))
+}
+
+/// The object `node` is accessed on in `object.node`, `Type::node` and
+/// the like.
+pub(crate) fn qualifier_of<'a>(node: Node, source: &'a [u8]) -> Option<&'a str> {
+    node.parent().and_then(|parent| {
+        let object = match parent.kind() {
+            "member_expression" | "attribute" | "field_access" | "method_invocation" => parent.child_by_field_name("object"),
+            "field_expression" => parent
+                .child_by_field_name("value")
+                .or_else(|| parent.child_by_field_name("argument")),
+            "selector_expression" => parent.child_by_field_name("operand"),
+            "scoped_identifier" | "scoped_type_identifier" | "qualified_identifier" => parent
+                .child_by_field_name("path")
+                .or_else(|| parent.child_by_field_name("scope")),
+            _ => None,
+        }?;
+        (object.id() != node.id()).then(|| node_text(object, source))
+    })
+}
+
+/// Resolves `path` to its index key, erroring if it's outside the indexed
+/// project.
+pub(crate) fn relative_in(table: &SymbolTable, path: &str) -> Result<String, String> {
+    if table.root.as_os_str().is_empty() {
+        return Err("No project indexed; call index_symbols first".to_string());
+    }
+    let resolved = resolve_path(Path::new(path));
+    relative_path(&table.root, &resolved).ok_or_else(|| format!("{} is outside the indexed project", path))
+}
+
+/// Definition candidates for the identifier at `line`/`col` of `path`,
+/// refreshing the file and its import targets first.
+pub(crate) fn definitions_at(
+    table: &mut SymbolTable,
+    parser: &ParserState,
+    path: &str,
+    line: usize,
+    col: usize,
+) -> Result<Vec<DefinitionCandidate>, String> {
+    let relative = relative_in(table, path)?;
+    let (content, _) = read_text(&table.root.join(&relative))?;
+    let (_, tree) = parser
+        .parse_tree(path, &content)
+        .ok_or_else(|| format!("Unsupported file type: {}", path))?;
+    table.refresh(parser, &relative);
+
+    let lines = LineIndex::new(&content);
+    let Some((node, qualifier)) = identifier_at(tree.root_node(), lines.point(line, col), content.as_bytes()) else {
+        return Ok(Vec::new());
+    };
+    let name = node_text(node, content.as_bytes());
+
+    // Make sure import targets reflect their current content
+    let targets: Vec<String> = table
+        .files
+        .get(&relative)
+        .map(|f| f.imports.iter().filter_map(|i| i.resolved.clone()).collect())
+        .unwrap_or_default();
+    for target in targets {
+        if table.files.contains_key(&target) {
+            table.refresh(parser, &target);
+        }
+    }
+
+    Ok(table.definitions(&relative, name, qualifier, lines.position(node.start_position())))
+}
+
+// ============================================================================
+// SYMBOL TAURI COMMANDS
+// ============================================================================
+
+/// Builds the project-wide symbol table for `root`: definitions from every
+/// parseable file, and imports resolved to project files. Files unchanged
+/// since the last run are kept.
+#[tauri::command]
+pub async fn index_symbols(
+    app: AppHandle,
+    root: String,
+    state: State<'_, SymbolIndexState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<SymbolIndexSummary, String> {
+    index_symbols_with_progress(&app, &root, &state, &workspace, |_, _| Ok(())).await
+}
+
+/// `index_symbols`, calling `on_file(done, total)` after each file; an error
+/// from it stops indexing without applying a partial sync.
+pub(crate) async fn index_symbols_with_progress(
+    app: &AppHandle,
+    root: &str,
+    state: &SymbolIndexState,
+    workspace: &WorkspaceState,
+    on_file: impl FnMut(usize, usize) -> Result<(), String> + Send + 'static,
+) -> Result<SymbolIndexSummary, String> {
+    let root_path = workspace.check(root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+
+    let table = state.table.clone();
+    let parse_app = app.clone();
+    let summary = tokio::task::spawn_blocking(move || {
+        let parser = parse_app.state::<ParserState>();
+        let mut table = table.lock_or_recover();
+        if table.root != root_path {
+            *table = SymbolTable {
+                root: root_path,
+                ..Default::default()
+            };
+        }
+        table.sync(&parser, on_file)?;
+
+        let imports = table.files.values().flat_map(|f| f.imports.iter());
+        Ok(SymbolIndexSummary {
+            root: table.root.to_string_lossy().to_string(),
+            files: table.files.len(),
+            symbols: table.files.values().map(|f| f.symbols.len()).sum(),
+            imports: imports.clone().count(),
+            resolved_imports: imports.filter(|i| i.resolved.is_some()).count(),
+        })
+    })
+    .await
+    .map_err(|e| format!("Symbol indexing task failed: {}", e))??;
+    emit_indexing_finished(app, "symbols", &summary);
+    Ok(summary)
+}
+
+/// Candidate definitions of the identifier at `line`/`col` (0-based,
+/// character column) in `path`: enclosing scopes first, then the imported
+/// definition, then same-named definitions anywhere in the project.
+#[tauri::command]
+pub async fn find_definition(
+    app: AppHandle,
+    path: String,
+    line: usize,
+    col: usize,
+    state: State<'_, SymbolIndexState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<DefinitionCandidate>, String> {
+    workspace.check(&path)?;
+    let table = state.table.clone();
+    tokio::task::spawn_blocking(move || {
+        let parser = app.state::<ParserState>();
+        definitions_at(&mut table.lock_or_recover(), &parser, &path, line, col)
+    })
+    .await
+    .map_err(|e| format!("Definition lookup failed: {}", e))?
+}
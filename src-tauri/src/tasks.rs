@@ -0,0 +1,309 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fs as std_fs;
+use std::path::Path;
+use std::sync::Mutex;
+use tauri::{Emitter, State, Window};
+
+use crate::locks::LockExt;
+use crate::process::{run_process, CommandResult};
+use crate::sandbox::WorkspaceState;
+
+const CARGO_COMMANDS: &[&str] = &["build", "check", "test", "run", "clippy"];
+const MAKEFILES: &[&str] = &["GNUmakefile", "makefile", "Makefile"];
+const JUSTFILES: &[&str] = &["justfile", "Justfile", ".justfile"];
+
+/// (source, name, program, args)
+type FoundTask = (String, String, String, Vec<String>);
+
+// ============================================================================
+// TASK STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TaskDefinition {
+    pub id: String,
+    pub label: String,
+    pub source: String, // "npm", "cargo", "make", "just"
+    pub program: String,
+    pub args: Vec<String>,
+    pub cwd: String,
+}
+
+/// Tasks found by the last `detect_tasks` call, so `run_task` only needs an id.
+#[derive(Default)]
+pub struct TaskState {
+    tasks: Mutex<HashMap<String, TaskDefinition>>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TaskStatusEvent {
+    task_id: String,
+    status: String, // "running", "succeeded", "failed", "timed_out"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    exit_code: Option<i32>,
+}
+
+// ============================================================================
+// TASK DETECTION
+// ============================================================================
+
+/// Node package managers install `.cmd` shims on Windows, which are not found
+/// without the extension when spawning directly.
+fn script_program(name: &str) -> String {
+    if cfg!(target_os = "windows") {
+        format!("{}.cmd", name)
+    } else {
+        name.to_string()
+    }
+}
+
+fn package_manager(dir: &Path) -> &'static str {
+    if dir.join("pnpm-lock.yaml").exists() {
+        "pnpm"
+    } else if dir.join("yarn.lock").exists() {
+        "yarn"
+    } else if dir.join("bun.lockb").exists() || dir.join("bun.lock").exists() {
+        "bun"
+    } else {
+        "npm"
+    }
+}
+
+fn npm_tasks(dir: &Path) -> Vec<FoundTask> {
+    let content = match std_fs::read_to_string(dir.join("package.json")) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    let manifest: serde_json::Value = serde_json::from_str(&content).unwrap_or_default();
+    let manager = package_manager(dir);
+
+    manifest
+        .get("scripts")
+        .and_then(|s| s.as_object())
+        .map(|scripts| {
+            scripts
+                .keys()
+                .map(|name| {
+                    (
+                        "npm".to_string(),
+                        name.clone(),
+                        script_program(manager),
+                        vec!["run".to_string(), name.clone()],
+                    )
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn cargo_tasks(dir: &Path) -> Vec<FoundTask> {
+    if !dir.join("Cargo.toml").exists() {
+        return Vec::new();
+    }
+    CARGO_COMMANDS
+        .iter()
+        .map(|command| {
+            (
+                "cargo".to_string(),
+                command.to_string(),
+                "cargo".to_string(),
+                vec![command.to_string()],
+            )
+        })
+        .collect()
+}
+
+/// Rule names at the start of a line, skipping special targets, pattern rules
+/// and variable assignments.
+fn make_targets(content: &str) -> Vec<String> {
+    let mut targets = Vec::new();
+    for line in content.lines() {
+        if line.starts_with(char::is_whitespace) || line.starts_with('#') {
+            continue;
+        }
+        let Some((head, rest)) = line.split_once(':') else {
+            continue;
+        };
+        if rest.starts_with('=') || head.contains('=') {
+            continue;
+        }
+        for target in head.split_whitespace() {
+            if target.starts_with('.') || target.contains('%') || target.contains('$') {
+                continue;
+            }
+            if !targets.iter().any(|t| t == target) {
+                targets.push(target.to_string());
+            }
+        }
+    }
+    targets
+}
+
+/// Recipe names from a justfile; settings, aliases and assignments are skipped.
+fn just_recipes(content: &str) -> Vec<String> {
+    let mut recipes = Vec::new();
+    for line in content.lines() {
+        if line.starts_with(char::is_whitespace) || line.starts_with('#') || line.starts_with('[') {
+            continue;
+        }
+        let Some((head, rest)) = line.split_once(':') else {
+            continue;
+        };
+        if rest.starts_with('=') {
+            continue;
+        }
+        let mut words = head.split_whitespace();
+        let Some(name) = words.next() else {
+            continue;
+        };
+        let name = name.trim_start_matches('@');
+        if matches!(name, "set" | "alias" | "export" | "import" | "mod") || name.is_empty() {
+            continue;
+        }
+        if name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-') {
+            recipes.push(name.to_string());
+        }
+    }
+    recipes
+}
+
+fn file_tasks(
+    dir: &Path,
+    files: &[&str],
+    source: &str,
+    program: &str,
+    parse: fn(&str) -> Vec<String>,
+) -> Vec<FoundTask> {
+    let Some(content) = files.iter().find_map(|f| std_fs::read_to_string(dir.join(f)).ok()) else {
+        return Vec::new();
+    };
+    parse(&content)
+        .into_iter()
+        .map(|name| (source.to_string(), name.clone(), program.to_string(), vec![name]))
+        .collect()
+}
+
+fn detect_in_dir(dir: &Path, relative: &str) -> Vec<TaskDefinition> {
+    let mut found = npm_tasks(dir);
+    found.extend(cargo_tasks(dir));
+    found.extend(file_tasks(dir, MAKEFILES, "make", "make", make_targets));
+    found.extend(file_tasks(dir, JUSTFILES, "just", "just", just_recipes));
+
+    found
+        .into_iter()
+        .map(|(source, name, program, args)| {
+            let (id, label) = if relative.is_empty() {
+                (format!("{}:{}", source, name), name)
+            } else {
+                (
+                    format!("{}:{}:{}", source, relative, name),
+                    format!("{} ({})", name, relative),
+                )
+            };
+            TaskDefinition {
+                id,
+                label,
+                source,
+                program,
+                args,
+                cwd: dir.to_string_lossy().to_string(),
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// TASK TAURI COMMANDS
+// ============================================================================
+
+/// Finds package.json scripts, Cargo commands, Makefile targets and justfile
+/// recipes in `root` and its immediate subdirectories.
+#[tauri::command]
+pub fn detect_tasks(
+    root: String,
+    state: State<'_, TaskState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<Vec<TaskDefinition>, String> {
+    // Detected tasks run with their directory as cwd, so it must be trusted
+    let root_path = workspace.check(&root)?;
+    if !root_path.is_dir() {
+        return Err(format!("Not a directory: {}", root));
+    }
+
+    let mut tasks = detect_in_dir(&root_path, "");
+
+    let mut subdirs: Vec<_> = std_fs::read_dir(&root_path)
+        .map_err(|e| format!("Failed to read directory: {}", e))?
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.path().is_dir())
+        .filter(|entry| {
+            let name = entry.file_name().to_string_lossy().to_string();
+            !name.starts_with('.') && name != "node_modules" && name != "target"
+        })
+        .collect();
+    subdirs.sort_by_key(|entry| entry.file_name());
+
+    for entry in subdirs {
+        let name = entry.file_name().to_string_lossy().to_string();
+        tasks.extend(detect_in_dir(&entry.path(), &name));
+    }
+
+    let mut known = state.tasks.lock_or_recover();
+    known.clear();
+    for task in &tasks {
+        known.insert(task.id.clone(), task.clone());
+    }
+
+    Ok(tasks)
+}
+
+/// Runs a detected task. Output arrives as `command-output` events with the
+/// task id as `command_id`; `task-status` reports start and completion.
+#[tauri::command]
+pub async fn run_task(
+    window: Window,
+    task_id: String,
+    timeout_ms: Option<u64>,
+    state: State<'_, TaskState>,
+) -> Result<CommandResult, String> {
+    let task = state
+        .tasks
+        .lock_or_recover()
+        .get(&task_id)
+        .cloned()
+        .ok_or_else(|| format!("Unknown task: {}", task_id))?;
+
+    let emit_status = |status: &str, exit_code: Option<i32>| {
+        let _ = window.emit(
+            "task-status",
+            TaskStatusEvent {
+                task_id: task_id.clone(),
+                status: status.to_string(),
+                exit_code,
+            },
+        );
+    };
+
+    emit_status("running", None);
+
+    let result = run_process(
+        Some(window.clone()),
+        &task.id,
+        Some(&task.cwd),
+        &task.program,
+        &task.args,
+        &HashMap::new(),
+        timeout_ms,
+    )
+    .await;
+
+    match &result {
+        Ok(r) if r.timed_out => emit_status("timed_out", None),
+        Ok(r) if r.exit_code == Some(0) => emit_status("succeeded", r.exit_code),
+        Ok(r) => emit_status("failed", r.exit_code),
+        Err(_) => emit_status("failed", None),
+    }
+
+    result
+}
@@ -0,0 +1,1256 @@
+use portable_pty::{native_pty_system, Child, ChildKiller, CommandBuilder, MasterPty, PtySize};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use tauri::{Emitter, Manager, State, Window};
+
+use crate::locks::LockExt;
+
+type PtyWriter = Arc<Mutex<Box<dyn Write + Send>>>;
+type PtyReader = Arc<Mutex<Box<dyn Read + Send>>>;
+type SharedCwd = Arc<Mutex<Option<String>>>;
+
+const DEFAULT_SCROLLBACK_BYTES: usize = 256 * 1024;
+const MAX_HISTORY_ENTRIES: usize = 1000;
+const MAX_OSC_BYTES: usize = 4096;
+const COALESCE_WINDOW: Duration = Duration::from_millis(12);
+const MAX_BATCH_BYTES: usize = 64 * 1024;
+const TRUNCATED_MARKER: &str = "\r\n\x1b[0m[... output truncated ...]\r\n";
+const PROMPT_MARKERS: &[&str] = &["$ ", "# ", "% ", "> ", "❯ ", "➜ "];
+
+/// Bounded byte ring holding the most recent output of a terminal so a panel
+/// can replay it after reattaching or a webview reload.
+struct Scrollback {
+    data: VecDeque<u8>,
+    capacity: usize,
+}
+
+impl Scrollback {
+    fn new(capacity: usize) -> Self {
+        Self {
+            data: VecDeque::with_capacity(capacity.min(DEFAULT_SCROLLBACK_BYTES)),
+            capacity,
+        }
+    }
+
+    fn push(&mut self, bytes: &[u8]) {
+        let bytes = &bytes[bytes.len().saturating_sub(self.capacity)..];
+        let overflow = (self.data.len() + bytes.len()).saturating_sub(self.capacity);
+        self.data.drain(..overflow);
+        self.data.extend(bytes);
+    }
+
+    fn contents(&self) -> String {
+        let (front, back) = self.data.as_slices();
+        let mut bytes = Vec::with_capacity(self.data.len());
+        bytes.extend_from_slice(front);
+        bytes.extend_from_slice(back);
+        String::from_utf8_lossy(&bytes).to_string()
+    }
+}
+
+/// Removes CSI, OSC and other escape sequences plus carriage returns, leaving
+/// the plain text a user would see.
+pub(crate) fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\x1b' => match chars.next() {
+                Some('[') => {
+                    for c in chars.by_ref() {
+                        if ('@'..='~').contains(&c) {
+                            break;
+                        }
+                    }
+                }
+                Some(']') => {
+                    while let Some(c) = chars.next() {
+                        if c == '\x07' {
+                            break;
+                        }
+                        if c == '\x1b' && chars.peek() == Some(&'\\') {
+                            chars.next();
+                            break;
+                        }
+                    }
+                }
+                _ => {}
+            },
+            '\r' => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+/// Reconstructs the command line from what the user typed. Once history
+/// recall, completion or cursor movement is involved the typed keys no longer
+/// describe the line, so the echoed prompt line is used instead.
+#[derive(Default)]
+struct InputTracker {
+    line: String,
+    dirty: bool,
+}
+
+impl InputTracker {
+    /// Feeds keystrokes and returns the command line when Enter was pressed.
+    fn feed(&mut self, data: &str, scrollback: &Scrollback) -> Vec<String> {
+        let mut submitted = Vec::new();
+        let mut chars = data.chars();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' | '\n' => {
+                    let line = if self.dirty {
+                        command_from_prompt(&scrollback.contents())
+                    } else {
+                        Some(std::mem::take(&mut self.line))
+                    };
+                    if let Some(line) = line.map(|l| l.trim().to_string()).filter(|l| !l.is_empty()) {
+                        submitted.push(line);
+                    }
+                    self.line.clear();
+                    self.dirty = false;
+                }
+                '\x7f' | '\x08' => {
+                    self.line.pop();
+                }
+                '\x03' | '\x15' => {
+                    self.line.clear();
+                    self.dirty = false;
+                }
+                '\t' => self.dirty = true,
+                '\x1b' => {
+                    // Arrow keys and friends: skip the rest of the sequence
+                    self.dirty = true;
+                    if let Some('[') | Some('O') = chars.next() {
+                        for c in chars.by_ref() {
+                            if ('@'..='~').contains(&c) {
+                                break;
+                            }
+                        }
+                    }
+                }
+                c if c.is_control() => {}
+                c => self.line.push(c),
+            }
+        }
+        submitted
+    }
+}
+
+/// Takes the text after the prompt on the last line of output.
+fn command_from_prompt(output: &str) -> Option<String> {
+    let text = strip_ansi(output);
+    let line = text.lines().last()?;
+    PROMPT_MARKERS
+        .iter()
+        .filter_map(|marker| line.rfind(marker).map(|i| i + marker.len()))
+        .max()
+        .map(|start| line[start..].to_string())
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Extracts the directory from an OSC body: `7;file://host/path` (OSC 7),
+/// `633;P;Cwd=path` (VS Code shell integration) or `1337;CurrentDir=path`.
+fn cwd_from_osc(body: &str) -> Option<String> {
+    if let Some(url) = body.strip_prefix("7;") {
+        let rest = url.strip_prefix("file://")?;
+        let path = &rest[rest.find('/')?..];
+        let path = percent_decode(path);
+        // file:///C:/Users/... on Windows
+        let bytes = path.as_bytes();
+        if bytes.len() >= 3 && bytes[2] == b':' && bytes[1].is_ascii_alphabetic() {
+            return Some(path[1..].to_string());
+        }
+        return Some(path);
+    }
+    body.strip_prefix("633;P;Cwd=")
+        .or_else(|| body.strip_prefix("1337;CurrentDir="))
+        .map(|path| path.to_string())
+}
+
+/// Finds cwd reports in PTY output. Sequences split across reads are kept
+/// until their terminator (BEL or ST) arrives.
+#[derive(Default)]
+struct CwdTracker {
+    pending: String,
+}
+
+impl CwdTracker {
+    fn feed(&mut self, text: &str) -> Option<String> {
+        self.pending.push_str(text);
+        let mut found = None;
+        let mut offset = 0;
+        let mut incomplete = None;
+
+        while let Some(pos) = self.pending[offset..].find("\x1b]") {
+            let body_start = offset + pos + 2;
+            match self.pending[body_start..].find(|c| c == '\x07' || c == '\x1b') {
+                Some(len) => {
+                    if let Some(cwd) = cwd_from_osc(&self.pending[body_start..body_start + len]) {
+                        found = Some(cwd);
+                    }
+                    offset = body_start + len;
+                }
+                None => {
+                    incomplete = Some(offset + pos);
+                    break;
+                }
+            }
+        }
+
+        self.pending = match incomplete {
+            Some(start) if self.pending.len() - start <= MAX_OSC_BYTES => self.pending[start..].to_string(),
+            None if self.pending.ends_with('\x1b') => "\x1b".to_string(),
+            _ => String::new(),
+        };
+        found
+    }
+}
+
+// ============================================================================
+// LINK DETECTION
+// ============================================================================
+
+const LINK_DELIMITERS: &[char] = &[' ', '\t', '"', '\'', '`', '<', '>', '[', ']', '{', '}', '|'];
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TerminalLink {
+    pub text: String,
+    pub path: String,
+    pub line: usize,
+    pub column: Option<usize>,
+    /// Character offsets of `text` within the output line
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TerminalLinkEvent {
+    terminal_id: String,
+    line: String,
+    links: Vec<TerminalLink>,
+}
+
+/// Parses `path:line`, `path:line:col` (rustc, gcc, node) and `path(line,col)`
+/// (MSVC, tsc).
+fn parse_location(token: &str) -> Option<(&str, usize, Option<usize>)> {
+    if token.ends_with(')') {
+        let open = token.rfind('(')?;
+        let mut parts = token[open + 1..token.len() - 1].split(',');
+        let line = parts.next()?.trim().parse().ok()?;
+        let column = parts.next().and_then(|c| c.trim().parse().ok());
+        return Some((&token[..open], line, column));
+    }
+
+    let mut parts = token.rsplitn(3, ':');
+    let last = parts.next()?;
+    let middle = parts.next()?;
+    match (middle.parse::<usize>(), last.parse::<usize>()) {
+        (Ok(line), Ok(column)) => Some((parts.next()?, line, Some(column))),
+        (Err(_), Ok(line)) => Some((&token[..token.len() - last.len() - 1], line, None)),
+        _ => None,
+    }
+}
+
+fn resolve_link_path(path: &str, cwd: Option<&str>, root: Option<&str>) -> Option<String> {
+    if path.is_empty() || !(path.contains('.') || path.contains('/') || path.contains('\\')) {
+        return None;
+    }
+    let candidate = Path::new(path);
+    if candidate.is_absolute() {
+        return candidate.is_file().then(|| path.to_string());
+    }
+    [cwd, root]
+        .into_iter()
+        .flatten()
+        .map(|base| Path::new(base).join(path))
+        .find(|full| full.is_file())
+        .map(|full| full.to_string_lossy().to_string())
+}
+
+/// Finds file locations in one line of plain (escape-free) output.
+fn detect_links(line: &str, cwd: Option<&str>, root: Option<&str>) -> Vec<TerminalLink> {
+    let mut links = Vec::new();
+
+    // Python tracebacks: File "path", line N
+    if let Some(start) = line.find("File \"") {
+        let rest = &line[start + 6..];
+        if let Some((path, tail)) = rest.split_once("\", line ") {
+            let digits: String = tail.chars().take_while(|c| c.is_ascii_digit()).collect();
+            if let (Ok(number), Some(resolved)) = (digits.parse(), resolve_link_path(path, cwd, root)) {
+                let char_start = line[..start + 6].chars().count();
+                links.push(TerminalLink {
+                    text: path.to_string(),
+                    path: resolved,
+                    line: number,
+                    column: None,
+                    start: char_start,
+                    end: char_start + path.chars().count(),
+                });
+                return links;
+            }
+        }
+    }
+
+    let mut token_start = 0;
+    let mut token = String::new();
+    for (i, c) in line.chars().chain(std::iter::once(' ')).enumerate() {
+        if !LINK_DELIMITERS.contains(&c) {
+            if token.is_empty() {
+                token_start = i;
+            }
+            token.push(c);
+            continue;
+        }
+        if token.is_empty() {
+            continue;
+        }
+
+        let leading = token.len() - token.trim_start_matches('(').len();
+        let mut text = token.trim_start_matches('(');
+        text = text.trim_end_matches(|c| matches!(c, ':' | ',' | ';' | '.'));
+        if text.ends_with(')') && !text[..text.len() - 1].contains('(') {
+            text = &text[..text.len() - 1];
+        }
+
+        if let Some((path, number, column)) = parse_location(text) {
+            if number > 0 {
+                if let Some(resolved) = resolve_link_path(path, cwd, root) {
+                    let start = token_start + leading;
+                    links.push(TerminalLink {
+                        text: text.to_string(),
+                        path: resolved,
+                        line: number,
+                        column,
+                        start,
+                        end: start + text.chars().count(),
+                    });
+                }
+            }
+        }
+        token.clear();
+    }
+
+    links
+}
+
+/// Decodes PTY output, holding back a multibyte sequence split across reads
+/// instead of turning it into replacement characters.
+#[derive(Default)]
+struct Utf8Decoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    fn decode(&mut self, bytes: &[u8]) -> String {
+        self.pending.extend_from_slice(bytes);
+        let mut out = String::new();
+        let mut rest: &[u8] = &self.pending;
+
+        loop {
+            match std::str::from_utf8(rest) {
+                Ok(text) => {
+                    out.push_str(text);
+                    rest = &[];
+                    break;
+                }
+                Err(e) => {
+                    let (valid, after) = rest.split_at(e.valid_up_to());
+                    out.push_str(std::str::from_utf8(valid).unwrap_or_default());
+                    match e.error_len() {
+                        // Genuinely invalid bytes
+                        Some(len) => {
+                            out.push(char::REPLACEMENT_CHARACTER);
+                            rest = &after[len..];
+                        }
+                        // Incomplete sequence at the end: wait for the next read
+                        None => {
+                            rest = after;
+                            break;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.pending = rest.to_vec();
+        out
+    }
+}
+
+/// Emits PTY output as `terminal-output` events, merging reads that arrive
+/// within `COALESCE_WINDOW` into one event. When a batch exceeds
+/// `MAX_BATCH_BYTES` the oldest output is dropped and a marker is prepended,
+/// so a flood of output cannot saturate IPC and freeze the webview.
+fn forward_output(output: Receiver<String>, window: Window, terminal_id: String) {
+    while let Ok(first) = output.recv() {
+        let mut batch = first;
+        let mut truncated = false;
+        let deadline = Instant::now() + COALESCE_WINDOW;
+
+        loop {
+            if batch.len() > MAX_BATCH_BYTES {
+                let mut cut = batch.len() - MAX_BATCH_BYTES;
+                while !batch.is_char_boundary(cut) {
+                    cut += 1;
+                }
+                batch.drain(..cut);
+                truncated = true;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            match output.recv_timeout(deadline - now) {
+                Ok(more) => batch.push_str(&more),
+                Err(_) => break,
+            }
+        }
+
+        if truncated {
+            batch.insert_str(0, TRUNCATED_MARKER);
+        }
+
+        let _ = window.emit(
+            "terminal-output",
+            TerminalOutput {
+                terminal_id: terminal_id.clone(),
+                data: batch,
+            },
+        );
+    }
+}
+
+/// Splits PTY output into complete lines for link detection.
+#[derive(Default)]
+struct LineSplitter {
+    partial: String,
+}
+
+impl LineSplitter {
+    fn feed(&mut self, text: &str) -> Vec<String> {
+        self.partial.push_str(text);
+        let mut lines = Vec::new();
+        while let Some(pos) = self.partial.find('\n') {
+            let line: String = self.partial.drain(..=pos).collect();
+            lines.push(strip_ansi(&line).trim_end().to_string());
+        }
+        if self.partial.len() > MAX_OSC_BYTES {
+            self.partial.clear();
+        }
+        lines
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TerminalCwdChanged {
+    terminal_id: String,
+    cwd: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct CommandHistoryEntry {
+    pub terminal_id: String,
+    pub command: String,
+    pub cwd: Option<String>,
+    pub timestamp: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TerminalSearchMatch {
+    pub terminal_id: String,
+    pub line_number: usize,
+    pub line: String,
+}
+
+struct TerminalInstance {
+    writer: PtyWriter,
+    _reader: PtyReader,
+    /// Taken once the shell exits on Windows to close the pseudoconsole
+    master: Option<Box<dyn MasterPty + Send>>,
+    size: (u16, u16),
+    killer: Box<dyn ChildKiller + Send + Sync>,
+    scrollback: Arc<Mutex<Scrollback>>,
+    cwd: SharedCwd,
+    options: TerminalOptions,
+    input: InputTracker,
+    pid: Option<u32>,
+}
+
+impl Drop for TerminalInstance {
+    /// Dropping the instance terminates the shell so closed terminals never
+    /// leave orphaned processes behind. Fails harmlessly if it already exited.
+    fn drop(&mut self) {
+        let _ = self.killer.kill();
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TerminalInfo {
+    pub terminal_id: String,
+    pub pid: Option<u32>,
+    pub title: Option<String>,
+    pub shell: Option<String>,
+    pub cwd: Option<String>,
+    pub persist: bool,
+}
+
+// ============================================================================
+// TERMINAL STATE
+// ============================================================================
+
+/// Live PTYs plus the sessions database. Terminals created with `persist`
+/// are recorded there so their layout can be restored on the next start.
+pub struct TerminalState {
+    terminals: Mutex<HashMap<String, TerminalInstance>>,
+    history: Mutex<VecDeque<CommandHistoryEntry>>,
+    conn: Mutex<Connection>,
+}
+
+struct SavedSession {
+    id: String,
+    cwd: Option<String>,
+    options: TerminalOptions,
+    scrollback: String,
+}
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+impl TerminalState {
+    pub fn open(db_path: &Path) -> Result<Self, String> {
+        let conn = Connection::open(db_path)
+            .map_err(|e| format!("Failed to open terminal database: {}", e))?;
+
+        conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS terminal_sessions (
+                 id TEXT PRIMARY KEY,
+                 title TEXT,
+                 cwd TEXT,
+                 shell TEXT,
+                 options TEXT NOT NULL,
+                 scrollback TEXT NOT NULL DEFAULT '',
+                 created_at INTEGER NOT NULL,
+                 updated_at INTEGER NOT NULL
+             );",
+        )
+        .map_err(|e| format!("Failed to initialize terminal database: {}", e))?;
+
+        Ok(TerminalState {
+            terminals: Mutex::new(HashMap::new()),
+            history: Mutex::new(VecDeque::new()),
+            conn: Mutex::new(conn),
+        })
+    }
+
+    fn save_session(&self, id: &str, cwd: Option<&str>, options: &TerminalOptions) -> Result<(), String> {
+        let options_json = serde_json::to_string(options).map_err(|e| e.to_string())?;
+        let now = now_secs();
+        let conn = self.conn.lock_or_recover();
+        conn.execute(
+            "INSERT INTO terminal_sessions (id, title, cwd, shell, options, created_at, updated_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+             ON CONFLICT(id) DO UPDATE SET title = ?2, cwd = ?3, shell = ?4, options = ?5, updated_at = ?6",
+            params![id, options.title, cwd, options.shell, options_json, now],
+        )
+        .map_err(|e| format!("Failed to save terminal session: {}", e))?;
+        Ok(())
+    }
+
+    fn remove_session(&self, id: &str) -> Result<(), String> {
+        let conn = self.conn.lock_or_recover();
+        conn.execute("DELETE FROM terminal_sessions WHERE id = ?1", params![id])
+            .map_err(|e| format!("Failed to delete terminal session: {}", e))?;
+        Ok(())
+    }
+
+    /// Writes the current cwd and scrollback of every persisted terminal.
+    pub(crate) fn save_scrollback(&self) -> Result<usize, String> {
+        let snapshots: Vec<(String, Option<String>, String)> = {
+            let terminals = self.terminals.lock_or_recover();
+            terminals
+                .iter()
+                .filter(|(_, t)| t.options.persist)
+                .map(|(id, t)| (id.clone(), t.cwd.lock_or_recover().clone(), t.scrollback.lock_or_recover().contents()))
+                .collect()
+        };
+
+        let conn = self.conn.lock_or_recover();
+        for (id, cwd, scrollback) in &snapshots {
+            conn.execute(
+                "UPDATE terminal_sessions SET cwd = ?2, scrollback = ?3, updated_at = ?4 WHERE id = ?1",
+                params![id, cwd, scrollback, now_secs()],
+            )
+            .map_err(|e| format!("Failed to save terminal scrollback: {}", e))?;
+        }
+        Ok(snapshots.len())
+    }
+
+    /// Saves persisted scrollback and kills every shell; called on app exit.
+    pub fn shutdown(&self) {
+        if let Err(e) = self.save_scrollback() {
+            eprintln!("Failed to save terminal sessions: {}", e);
+        }
+        let terminals: Vec<TerminalInstance> = self.terminals.lock_or_recover().drain().map(|(_, t)| t).collect();
+        drop(terminals);
+    }
+
+    fn record_command(&self, terminal_id: &str, command: String, cwd: Option<String>) {
+        let mut history = self.history.lock_or_recover();
+        if history.back().is_some_and(|last| last.terminal_id == terminal_id && last.command == command) {
+            return;
+        }
+        if history.len() >= MAX_HISTORY_ENTRIES {
+            history.pop_front();
+        }
+        history.push_back(CommandHistoryEntry {
+            terminal_id: terminal_id.to_string(),
+            command,
+            cwd,
+            timestamp: now_secs(),
+        });
+    }
+
+    fn saved_sessions(&self) -> Result<Vec<SavedSession>, String> {
+        let conn = self.conn.lock_or_recover();
+        let mut stmt = conn
+            .prepare("SELECT id, cwd, options, scrollback FROM terminal_sessions ORDER BY created_at, id")
+            .map_err(|e| e.to_string())?;
+
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((
+                    row.get::<_, String>(0)?,
+                    row.get::<_, Option<String>>(1)?,
+                    row.get::<_, String>(2)?,
+                    row.get::<_, String>(3)?,
+                ))
+            })
+            .map_err(|e| e.to_string())?;
+
+        let mut sessions = Vec::new();
+        for row in rows {
+            let (id, cwd, options, scrollback) = row.map_err(|e| e.to_string())?;
+            sessions.push(SavedSession {
+                id,
+                cwd,
+                options: serde_json::from_str(&options).unwrap_or_default(),
+                scrollback,
+            });
+        }
+        Ok(sessions)
+    }
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TerminalOutput {
+    terminal_id: String,
+    data: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TerminalExited {
+    terminal_id: String,
+    exit_code: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct TerminalOptions {
+    pub title: Option<String>,
+    pub shell: Option<String>,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    pub initial_command: Option<String>,
+    /// Record the terminal so `restore_terminals` recreates it after a restart
+    #[serde(default)]
+    pub persist: bool,
+    /// Fallback for resolving relative paths in `terminal-link` detection
+    pub project_root: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RestoredTerminal {
+    pub terminal_id: String,
+    pub title: Option<String>,
+    pub cwd: Option<String>,
+    pub shell: Option<String>,
+    pub scrollback: String,
+}
+
+fn last_lines(text: &str, count: usize) -> String {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    lines[lines.len().saturating_sub(count)..].concat()
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ShellInfo {
+    pub name: String,
+    pub path: String,
+    pub args: Vec<String>,
+}
+
+// ============================================================================
+// SHELL DETECTION
+// ============================================================================
+
+pub(crate) fn find_in_path(program: &str) -> Option<PathBuf> {
+    let paths = std::env::var_os("PATH")?;
+    std::env::split_paths(&paths)
+        .map(|dir| dir.join(program))
+        .find(|candidate| candidate.is_file())
+}
+
+#[cfg(target_os = "windows")]
+fn default_shell() -> String {
+    let git_bash = Path::new("C:\\Program Files\\Git\\bin\\bash.exe");
+    let wsl = Path::new("C:\\Windows\\System32\\wsl.exe");
+    if git_bash.exists() {
+        git_bash.to_str().unwrap().to_string()
+    } else if wsl.exists() {
+        wsl.to_str().unwrap().to_string()
+    } else {
+        std::env::var("COMSPEC").unwrap_or_else(|_| "cmd.exe".to_string())
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+fn default_shell() -> String {
+    std::env::var("SHELL").unwrap_or_else(|_| "/bin/bash".to_string())
+}
+
+#[cfg(target_os = "windows")]
+fn shell_candidates() -> Vec<(&'static str, Option<PathBuf>, Vec<String>)> {
+    vec![
+        ("Git Bash", Some(PathBuf::from("C:\\Program Files\\Git\\bin\\bash.exe")), vec!["--login".to_string()]),
+        ("PowerShell 7", find_in_path("pwsh.exe"), vec!["-NoLogo".to_string()]),
+        ("Windows PowerShell", find_in_path("powershell.exe"), vec!["-NoLogo".to_string()]),
+        ("Command Prompt", std::env::var("COMSPEC").ok().map(PathBuf::from), Vec::new()),
+        ("WSL", Some(PathBuf::from("C:\\Windows\\System32\\wsl.exe")), Vec::new()),
+    ]
+}
+
+#[cfg(not(target_os = "windows"))]
+fn shell_candidates() -> Vec<(&'static str, Option<PathBuf>, Vec<String>)> {
+    vec![
+        ("bash", find_in_path("bash"), vec!["--login".to_string()]),
+        ("zsh", find_in_path("zsh"), vec!["--login".to_string()]),
+        ("fish", find_in_path("fish"), vec!["--login".to_string()]),
+        ("PowerShell", find_in_path("pwsh"), vec!["-NoLogo".to_string()]),
+        ("sh", find_in_path("sh"), Vec::new()),
+    ]
+}
+
+// ============================================================================
+// TERMINAL TAURI COMMANDS
+// ============================================================================
+
+/// Lists the shells found on this system, with suggested arguments for each.
+#[tauri::command]
+pub fn list_available_shells() -> Vec<ShellInfo> {
+    shell_candidates()
+        .into_iter()
+        .filter_map(|(name, path, args)| {
+            let path = path.filter(|p| p.is_file())?;
+            Some(ShellInfo {
+                name: name.to_string(),
+                path: path.to_string_lossy().to_string(),
+                args,
+            })
+        })
+        .collect()
+}
+
+/// Spawns a shell in a new PTY and starts forwarding its output. `replay` seeds
+/// the scrollback with output restored from a previous session.
+pub(crate) fn spawn_terminal(
+    window: &Window,
+    state: &TerminalState,
+    terminal_id: String,
+    cwd: Option<String>,
+    scrollback_bytes: Option<usize>,
+    options: TerminalOptions,
+    replay: Option<&str>,
+) -> Result<(), String> {
+    let pty_system = native_pty_system();
+
+    let pair = pty_system
+        .openpty(PtySize {
+            rows: 24,
+            cols: 80,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to open PTY: {}", e))?;
+
+    let shell = options.shell.clone().unwrap_or_else(default_shell);
+
+    let mut cmd = CommandBuilder::new(&shell);
+    cmd.args(&options.args);
+    for (key, value) in &options.env {
+        cmd.env(key, value);
+    }
+    
+    let cwd = cwd.filter(|dir| Path::new(dir).exists());
+    if let Some(dir) = &cwd {
+        cmd.cwd(dir);
+    }
+
+    let mut child = pair
+        .slave
+        .spawn_command(cmd)
+        .map_err(|e| format!("Failed to spawn shell: {}", e))?;
+
+    let reader = pair.master.try_clone_reader()
+        .map_err(|e| format!("Failed to clone reader: {}", e))?;
+    let writer = pair.master.take_writer()
+        .map_err(|e| format!("Failed to take writer: {}", e))?;
+
+    let reader = Arc::new(Mutex::new(reader));
+    let writer = Arc::new(Mutex::new(writer));
+
+    if let Some(command) = &options.initial_command {
+        // The PTY buffers input until the shell starts reading it
+        let mut writer = writer.lock_or_recover();
+        writer
+            .write_all(format!("{}\r", command).as_bytes())
+            .map_err(|e| format!("Failed to write initial command: {}", e))?;
+        let _ = writer.flush();
+        state.record_command(&terminal_id, command.clone(), cwd.clone());
+    }
+
+    let mut scrollback = Scrollback::new(scrollback_bytes.unwrap_or(DEFAULT_SCROLLBACK_BYTES));
+    if let Some(replay) = replay {
+        scrollback.push(replay.as_bytes());
+    }
+    let scrollback = Arc::new(Mutex::new(scrollback));
+
+    if options.persist {
+        state.save_session(&terminal_id, cwd.as_deref(), &options)?;
+    }
+    let cwd = Arc::new(Mutex::new(cwd));
+
+    let project_root = options.project_root.clone();
+
+    {
+        let mut terminals = state.terminals.lock_or_recover();
+        terminals.insert(
+            terminal_id.clone(),
+            TerminalInstance {
+                writer: writer.clone(),
+                _reader: reader.clone(),
+                master: Some(pair.master),
+                size: (24, 80),
+                killer: child.clone_killer(),
+                scrollback: scrollback.clone(),
+                cwd: cwd.clone(),
+                options,
+                input: InputTracker::default(),
+                pid: child.process_id(),
+            },
+        );
+    }
+
+    let terminal_id_clone = terminal_id.clone();
+    let window_clone = window.clone();
+    
+    let (output_tx, output_rx) = mpsc::channel::<String>();
+    let emitter_window = window.clone();
+    let emitter_id = terminal_id.clone();
+    let emitter_thread = std::thread::spawn(move || forward_output(output_rx, emitter_window, emitter_id));
+
+    let reader_thread = std::thread::spawn(move || {
+        let mut buf = [0u8; 4096];
+        let mut decoder = Utf8Decoder::default();
+        let mut cwd_tracker = CwdTracker::default();
+        let mut lines = LineSplitter::default();
+        loop {
+            let n = {
+                let mut reader_guard = reader.lock_or_recover();
+                match reader_guard.read(&mut buf) {
+                    Ok(0) => break,
+                    Ok(n) => n,
+                    Err(e) => {
+                        eprintln!("Error reading from PTY: {}", e);
+                        break;
+                    }
+                }
+            };
+
+            scrollback.lock_or_recover().push(&buf[..n]);
+
+            let data = decoder.decode(&buf[..n]);
+            if data.is_empty() {
+                continue;
+            }
+
+            if let Some(new_cwd) = cwd_tracker.feed(&data) {
+                let changed = cwd.lock_or_recover().replace(new_cwd.clone()).as_ref() != Some(&new_cwd);
+                if changed {
+                    let _ = window_clone.emit(
+                        "terminal-cwd",
+                        TerminalCwdChanged {
+                            terminal_id: terminal_id_clone.clone(),
+                            cwd: new_cwd,
+                        },
+                    );
+                }
+            }
+
+            let completed = lines.feed(&data);
+
+            if output_tx.send(data).is_err() {
+                break;
+            }
+
+            let current_cwd = cwd.lock_or_recover().clone();
+            for line in completed {
+                let links = detect_links(&line, current_cwd.as_deref(), project_root.as_deref());
+                if !links.is_empty() {
+                    let _ = window_clone.emit(
+                        "terminal-link",
+                        TerminalLinkEvent {
+                            terminal_id: terminal_id_clone.clone(),
+                            line,
+                            links,
+                        },
+                    );
+                }
+            }
+        }
+
+    });
+
+    let exit_window = window.clone();
+    std::thread::spawn(move || {
+        let exit_code = child.wait().ok().map(|status| status.exit_code());
+
+        // ConPTY keeps the output pipe open until the pseudoconsole is closed,
+        // so the reader would never see EOF after the shell exits. On Unix the
+        // reader ends by itself with EOF or EIO.
+        if cfg!(target_os = "windows") {
+            if let Some(state) = exit_window.try_state::<TerminalState>() {
+                if let Some(terminal) = state.terminals.lock_or_recover().get_mut(&terminal_id) {
+                    terminal.master = None;
+                }
+            }
+        }
+
+        // Report the exit only after the last output has been forwarded
+        let _ = reader_thread.join();
+        let _ = emitter_thread.join();
+        let _ = exit_window.emit(
+            "terminal-exited",
+            TerminalExited {
+                terminal_id,
+                exit_code,
+            },
+        );
+    });
+
+    Ok(())
+}
+
+/// Spawns a shell in a new PTY. `options` overrides the shell, its arguments
+/// and environment, and can type an initial command once the shell is up.
+#[tauri::command]
+pub async fn create_terminal(
+    window: Window,
+    terminal_id: String,
+    cwd: Option<String>,
+    scrollback_bytes: Option<usize>,
+    options: Option<TerminalOptions>,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    spawn_terminal(
+        &window,
+        &state,
+        terminal_id,
+        cwd,
+        scrollback_bytes,
+        options.unwrap_or_default(),
+        None,
+    )
+}
+
+/// Recreates the persisted terminals of the previous run. The initial command
+/// is not re-run; the last `replay_lines` lines of saved output are replayed.
+#[tauri::command]
+pub async fn restore_terminals(
+    window: Window,
+    replay_lines: Option<usize>,
+    state: State<'_, TerminalState>,
+) -> Result<Vec<RestoredTerminal>, String> {
+    let mut restored = Vec::new();
+
+    for session in state.saved_sessions()? {
+        if state.terminals.lock_or_recover().contains_key(&session.id) {
+            continue;
+        }
+
+        let scrollback = match replay_lines {
+            Some(count) => last_lines(&session.scrollback, count),
+            None => String::new(),
+        };
+        let options = TerminalOptions {
+            initial_command: None,
+            ..session.options
+        };
+
+        restored.push(RestoredTerminal {
+            terminal_id: session.id.clone(),
+            title: options.title.clone(),
+            cwd: session.cwd.clone(),
+            shell: options.shell.clone(),
+            scrollback: scrollback.clone(),
+        });
+        spawn_terminal(&window, &state, session.id, session.cwd, None, options, Some(&scrollback))?;
+    }
+
+    Ok(restored)
+}
+
+/// Snapshots the scrollback of persisted terminals, e.g. before the window
+/// unloads. Returns how many sessions were saved.
+#[tauri::command]
+pub fn save_terminal_sessions(state: State<'_, TerminalState>) -> Result<usize, String> {
+    state.save_scrollback()
+}
+
+#[tauri::command]
+pub fn write_terminal(
+    terminal_id: String,
+    data: String,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    let mut terminals = state.terminals.lock_or_recover();
+    
+    if let Some(terminal) = terminals.get_mut(&terminal_id) {
+        {
+            let mut writer = terminal.writer.lock_or_recover();
+            writer
+                .write_all(data.as_bytes())
+                .map_err(|e| format!("Failed to write to terminal: {}", e))?;
+            writer.flush().map_err(|e| format!("Failed to flush: {}", e))?;
+        }
+
+        let submitted = {
+            let scrollback = terminal.scrollback.lock_or_recover();
+            terminal.input.feed(&data, &scrollback)
+        };
+        for command in submitted {
+            state.record_command(&terminal_id, command, terminal.cwd.lock_or_recover().clone());
+        }
+        Ok(())
+    } else {
+        Err(format!("Terminal not found: {}", terminal_id))
+    }
+}
+
+/// Searches the scrollback of one or all terminals, ignoring escape sequences.
+#[tauri::command]
+pub fn search_terminal_output(
+    query: String,
+    terminal_id: Option<String>,
+    case_sensitive: Option<bool>,
+    state: State<'_, TerminalState>,
+) -> Result<Vec<TerminalSearchMatch>, String> {
+    let case_sensitive = case_sensitive.unwrap_or(false);
+    let needle = if case_sensitive { query.clone() } else { query.to_lowercase() };
+    if needle.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let terminals = state.terminals.lock_or_recover();
+    let mut ids: Vec<&String> = terminals
+        .keys()
+        .filter(|id| terminal_id.is_none() || terminal_id.as_ref() == Some(*id))
+        .collect();
+    ids.sort();
+
+    let mut matches = Vec::new();
+    for id in ids {
+        let text = strip_ansi(&terminals[id].scrollback.lock_or_recover().contents());
+        for (i, line) in text.lines().enumerate() {
+            let haystack = if case_sensitive { line.to_string() } else { line.to_lowercase() };
+            if haystack.contains(&needle) {
+                matches.push(TerminalSearchMatch {
+                    terminal_id: id.clone(),
+                    line_number: i + 1,
+                    line: line.to_string(),
+                });
+            }
+        }
+    }
+
+    Ok(matches)
+}
+
+/// Returns recently executed command lines, newest first, across all
+/// terminals unless `terminal_id` is given.
+#[tauri::command]
+pub fn get_command_history(
+    terminal_id: Option<String>,
+    limit: Option<usize>,
+    state: State<'_, TerminalState>,
+) -> Vec<CommandHistoryEntry> {
+    let history = state.history.lock_or_recover();
+    history
+        .iter()
+        .rev()
+        .filter(|entry| terminal_id.is_none() || terminal_id.as_ref() == Some(&entry.terminal_id))
+        .take(limit.unwrap_or(100))
+        .cloned()
+        .collect()
+}
+
+/// Resizing the master updates the kernel window size, which delivers
+/// SIGWINCH to the foreground process (on Windows ConPTY is resized instead).
+/// Unchanged sizes are skipped because ConPTY repaints the screen on every
+/// resize, which shows up as duplicated output.
+#[tauri::command]
+pub fn resize_terminal(
+    terminal_id: String,
+    rows: u16,
+    cols: u16,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    let mut terminals = state.terminals.lock_or_recover();
+
+    let terminal = terminals
+        .get_mut(&terminal_id)
+        .ok_or_else(|| format!("Terminal not found: {}", terminal_id))?;
+
+    if terminal.size == (rows, cols) {
+        return Ok(());
+    }
+
+    let master = terminal
+        .master
+        .as_ref()
+        .ok_or_else(|| format!("Terminal has exited: {}", terminal_id))?;
+
+    master
+        .resize(PtySize {
+            rows,
+            cols,
+            pixel_width: 0,
+            pixel_height: 0,
+        })
+        .map_err(|e| format!("Failed to resize terminal: {}", e))?;
+
+    terminal.size = (rows, cols);
+    Ok(())
+}
+
+/// Returns the retained output of a terminal for replay on reattach.
+#[tauri::command]
+pub fn get_terminal_buffer(
+    terminal_id: String,
+    state: State<'_, TerminalState>,
+) -> Result<String, String> {
+    let terminals = state.terminals.lock_or_recover();
+
+    let terminal = terminals
+        .get(&terminal_id)
+        .ok_or_else(|| format!("Terminal not found: {}", terminal_id))?;
+
+    let contents = terminal.scrollback.lock_or_recover().contents();
+    Ok(contents)
+}
+
+#[tauri::command]
+pub fn kill_terminal(
+    terminal_id: String,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    let mut terminals = state.terminals.lock_or_recover();
+
+    let terminal = terminals
+        .get_mut(&terminal_id)
+        .ok_or_else(|| format!("Terminal not found: {}", terminal_id))?;
+
+    terminal
+        .killer
+        .kill()
+        .map_err(|e| format!("Failed to kill terminal: {}", e))
+}
+
+#[tauri::command]
+pub fn close_terminal(
+    terminal_id: String,
+    state: State<'_, TerminalState>,
+) -> Result<(), String> {
+    let terminal = state.terminals.lock_or_recover().remove(&terminal_id);
+    drop(terminal);
+    state.remove_session(&terminal_id)
+}
+
+/// Returns the directory last reported by the shell through OSC 7 or shell
+/// integration, falling back to the directory the terminal was started in.
+#[tauri::command]
+pub fn get_terminal_cwd(
+    terminal_id: String,
+    state: State<'_, TerminalState>,
+) -> Result<Option<String>, String> {
+    let terminals = state.terminals.lock_or_recover();
+
+    let terminal = terminals
+        .get(&terminal_id)
+        .ok_or_else(|| format!("Terminal not found: {}", terminal_id))?;
+
+    let cwd = terminal.cwd.lock_or_recover().clone();
+    Ok(cwd)
+}
+
+/// Lists live terminals with the PID of their shell.
+#[tauri::command]
+pub fn list_terminals(state: State<'_, TerminalState>) -> Vec<TerminalInfo> {
+    let terminals = state.terminals.lock_or_recover();
+    let mut infos: Vec<TerminalInfo> = terminals
+        .iter()
+        .map(|(id, t)| TerminalInfo {
+            terminal_id: id.clone(),
+            pid: t.pid,
+            title: t.options.title.clone(),
+            shell: t.options.shell.clone(),
+            cwd: t.cwd.lock_or_recover().clone(),
+            persist: t.options.persist,
+        })
+        .collect();
+    infos.sort_by(|a, b| a.terminal_id.cmp(&b.terminal_id));
+    infos
+}
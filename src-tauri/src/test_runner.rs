@@ -0,0 +1,848 @@
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs as std_fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::Mutex;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager, State, Window};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::process::Command;
+use tokio::task::AbortHandle;
+use tree_sitter::Node;
+
+use crate::explorer::{walk_entries, DirectoryOptions};
+use crate::finder::relative_path;
+use crate::formatter::{node_bin, on_path};
+use crate::literals::string_value;
+use crate::locks::LockExt;
+use crate::process::run_process;
+use crate::sandbox::WorkspaceState;
+use crate::ParserState;
+
+/// Listing tests builds them first for cargo and go.
+const DISCOVERY_TIMEOUT_MS: u64 = 10 * 60 * 1000;
+const JS_TEST_MARKERS: &[&str] = &[".test.", ".spec."];
+const JS_TEST_EXTENSIONS: &[&str] = &["js", "jsx", "ts", "tsx", "mjs", "cjs", "mts", "cts"];
+const JS_SUITE_CALLS: &[&str] = &["describe", "suite", "context"];
+const JS_TEST_CALLS: &[&str] = &["it", "test"];
+const PYTEST_CONFIGS: &[&str] = &["pytest.ini", "conftest.py", "tox.ini"];
+
+// ============================================================================
+// TEST STRUCTURES
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TestItem {
+    /// "<framework>:<name>", accepted by `run_tests`.
+    pub id: String,
+    /// "cargo", "jest", "vitest", "pytest" or "go".
+    pub framework: String,
+    pub name: String,
+    pub path: Option<String>,
+    /// 0-based line of the test, for tests found in source.
+    pub line: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestDiscovery {
+    pub frameworks: Vec<String>,
+    pub tests: Vec<TestItem>,
+    /// Frameworks whose listing failed, with why.
+    pub warnings: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+#[serde(default)]
+pub struct TestSelection {
+    /// Frameworks to run in full; all detected ones when both fields are
+    /// empty.
+    pub frameworks: Vec<String>,
+    /// Ids from `discover_tests`.
+    pub tests: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TestResult {
+    pub id: String,
+    pub framework: String,
+    pub name: String,
+    pub status: String, // "passed", "failed", "skipped"
+    pub duration_ms: Option<f64>,
+    /// Failure output or assertion message.
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FrameworkRun {
+    pub framework: String,
+    pub exit_code: Option<i32>,
+    pub duration_ms: u64,
+    /// Set when the run failed without test results, e.g. a build error.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TestRunSummary {
+    pub run_id: String,
+    pub results: Vec<TestResult>,
+    pub runs: Vec<FrameworkRun>,
+    pub passed: usize,
+    pub failed: usize,
+    pub skipped: usize,
+    pub duration_ms: u64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+struct TestRunEvent {
+    run_id: String,
+    kind: &'static str, // "framework_started", "test", "framework_finished"
+    framework: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    result: Option<TestResult>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    run: Option<FrameworkRun>,
+}
+
+/// Test runs in progress, so they can be cancelled.
+#[derive(Default)]
+pub struct TestRunState {
+    runs: Mutex<HashMap<String, AbortHandle>>,
+}
+
+impl TestRunState {
+    /// Aborts every test run, whose process dies as the task is dropped.
+    pub fn shutdown(&self) {
+        for (_, run) in self.runs.lock_or_recover().drain() {
+            run.abort();
+        }
+    }
+}
+
+struct Framework {
+    name: &'static str,
+    program: Option<PathBuf>,
+}
+
+// ============================================================================
+// FRAMEWORK DETECTION
+// ============================================================================
+
+fn file_contains(path: &Path, needle: &str) -> bool {
+    std_fs::read_to_string(path).is_ok_and(|content| content.contains(needle))
+}
+
+/// A virtualenv's pytest runs with the project's dependencies installed.
+fn pytest_program(root: &Path) -> Option<PathBuf> {
+    [".venv/bin/pytest", "venv/bin/pytest", ".venv/Scripts/pytest.exe", "venv/Scripts/pytest.exe"]
+        .iter()
+        .map(|candidate| root.join(candidate))
+        .find(|candidate| candidate.is_file())
+        .or_else(|| on_path("pytest"))
+}
+
+fn detect_frameworks(root: &Path) -> Vec<Framework> {
+    let mut frameworks = Vec::new();
+    if root.join("Cargo.toml").is_file() {
+        frameworks.push(Framework {
+            name: "cargo",
+            program: on_path("cargo"),
+        });
+    }
+    let package = root.join("package.json");
+    if file_contains(&package, "\"vitest\"") {
+        frameworks.push(Framework {
+            name: "vitest",
+            program: node_bin(root, "vitest").or_else(|| on_path("vitest")),
+        });
+    } else if file_contains(&package, "\"jest\"") {
+        frameworks.push(Framework {
+            name: "jest",
+            program: node_bin(root, "jest").or_else(|| on_path("jest")),
+        });
+    }
+    if PYTEST_CONFIGS.iter().any(|c| root.join(c).is_file())
+        || file_contains(&root.join("pyproject.toml"), "[tool.pytest")
+        || file_contains(&root.join("setup.cfg"), "[tool:pytest]")
+    {
+        frameworks.push(Framework {
+            name: "pytest",
+            program: pytest_program(root),
+        });
+    }
+    if root.join("go.mod").is_file() {
+        frameworks.push(Framework {
+            name: "go",
+            program: on_path("go"),
+        });
+    }
+    frameworks
+}
+
+fn program_of(framework: &Framework) -> Result<&Path, String> {
+    framework
+        .program
+        .as_deref()
+        .ok_or_else(|| format!("{} is used by this project but not installed", framework.name))
+}
+
+fn test_id(framework: &str, name: &str) -> String {
+    format!("{}:{}", framework, name)
+}
+
+// ============================================================================
+// TEST DISCOVERY
+// ============================================================================
+
+async fn list_output(framework: &Framework, root: &Path, args: &[&str]) -> Result<String, String> {
+    let program = program_of(framework)?.to_string_lossy().to_string();
+    let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+    let cwd = root.to_string_lossy().to_string();
+    let command_id = format!("discover-{}", framework.name);
+    let env = HashMap::new();
+    let result = run_process(None, &command_id, Some(&cwd), &program, &args, &env, Some(DISCOVERY_TIMEOUT_MS)).await?;
+    if result.exit_code == Some(0) {
+        Ok(result.stdout)
+    } else {
+        let stderr = result.stderr.trim();
+        let summary = stderr.lines().rev().find(|l| !l.trim().is_empty()).unwrap_or("listing failed");
+        Err(format!("{}: {}", framework.name, summary))
+    }
+}
+
+fn cargo_tests(stdout: &str) -> Vec<TestItem> {
+    stdout
+        .lines()
+        .filter_map(|line| line.strip_suffix(": test"))
+        .map(|name| TestItem {
+            id: test_id("cargo", name),
+            framework: "cargo".to_string(),
+            name: name.to_string(),
+            path: None,
+            line: None,
+        })
+        .collect()
+}
+
+/// `go test -list` prints a package's tests before its `ok` line.
+fn go_tests(stdout: &str) -> Vec<TestItem> {
+    let mut tests = Vec::new();
+    let mut pending: Vec<&str> = Vec::new();
+    for line in stdout.lines() {
+        if let Some(rest) = line.strip_prefix("ok") {
+            let package = rest.split_whitespace().next().unwrap_or_default();
+            for name in pending.drain(..) {
+                let name = format!("{}::{}", package, name);
+                tests.push(TestItem {
+                    id: test_id("go", &name),
+                    framework: "go".to_string(),
+                    name,
+                    path: None,
+                    line: None,
+                });
+            }
+        } else if ["Test", "Benchmark", "Example", "Fuzz"].iter().any(|p| line.starts_with(p)) {
+            pending.push(line.trim());
+        }
+    }
+    tests
+}
+
+fn pytest_tests(stdout: &str, root: &Path) -> Vec<TestItem> {
+    stdout
+        .lines()
+        .map(|line| line.trim())
+        .filter(|line| line.contains("::") && !line.contains(' '))
+        .map(|node_id| TestItem {
+            id: test_id("pytest", node_id),
+            framework: "pytest".to_string(),
+            name: node_id.to_string(),
+            path: node_id.split("::").next().map(|file| root.join(file).to_string_lossy().to_string()),
+            line: None,
+        })
+        .collect()
+}
+
+fn is_js_test_file(path: &Path) -> bool {
+    let name = path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let extension = path.extension().map(|e| e.to_string_lossy().to_lowercase()).unwrap_or_default();
+    JS_TEST_EXTENSIONS.contains(&extension.as_str())
+        && (JS_TEST_MARKERS.iter().any(|m| name.contains(m)) || path.components().any(|c| c.as_os_str() == "__tests__"))
+}
+
+/// `describe`/`it`/`test` calls with a literal title; nested titles join
+/// with spaces into the full name jest and vitest match `-t` against.
+fn collect_js_tests(node: Node, source: &[u8], suites: &mut Vec<String>, found: &mut Vec<(String, usize)>) {
+    if node.kind() == "call_expression" {
+        let callee = node
+            .child_by_field_name("function")
+            .and_then(|f| f.utf8_text(source).ok())
+            .unwrap_or_default();
+        let base = callee.split('.').next().unwrap_or_default();
+        let title = node
+            .child_by_field_name("arguments")
+            .and_then(|args| args.named_child(0))
+            .filter(|arg| matches!(arg.kind(), "string" | "template_string"))
+            .map(|arg| string_value(arg, source));
+
+        if let Some(title) = title {
+            if JS_SUITE_CALLS.contains(&base) {
+                suites.push(title);
+                let mut cursor = node.walk();
+                for child in node.children(&mut cursor) {
+                    collect_js_tests(child, source, suites, found);
+                }
+                suites.pop();
+                return;
+            }
+            if JS_TEST_CALLS.contains(&base) {
+                let mut names = suites.clone();
+                names.push(title);
+                found.push((names.join(" "), node.start_position().row));
+                return;
+            }
+        }
+    }
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        collect_js_tests(child, source, suites, found);
+    }
+}
+
+fn js_tests(app: &AppHandle, framework: &str, root: &Path) -> Result<Vec<TestItem>, String> {
+    let parser = app.state::<ParserState>();
+    let mut tests = Vec::new();
+    for (path, is_dir) in walk_entries(root, &DirectoryOptions::default(), None)? {
+        if is_dir || !is_js_test_file(&path) {
+            continue;
+        }
+        let Ok(content) = std_fs::read_to_string(&path) else {
+            continue;
+        };
+        let Some((_, tree)) = parser.parse_tree(&path.to_string_lossy(), &content) else {
+            continue;
+        };
+        let mut found = Vec::new();
+        collect_js_tests(tree.root_node(), content.as_bytes(), &mut Vec::new(), &mut found);
+
+        let relative = relative_path(root, &path).unwrap_or_default();
+        for (full_name, line) in found {
+            let name = format!("{}::{}", relative, full_name);
+            tests.push(TestItem {
+                id: test_id(framework, &name),
+                framework: framework.to_string(),
+                name,
+                path: Some(path.to_string_lossy().to_string()),
+                line: Some(line),
+            });
+        }
+    }
+    Ok(tests)
+}
+
+async fn discover(app: &AppHandle, framework: &Framework, root: &Path) -> Result<Vec<TestItem>, String> {
+    match framework.name {
+        "cargo" => Ok(cargo_tests(&list_output(framework, root, &["test", "--quiet", "--", "--list"]).await?)),
+        "go" => Ok(go_tests(&list_output(framework, root, &["test", "-list", ".", "./..."]).await?)),
+        "pytest" => Ok(pytest_tests(
+            &list_output(framework, root, &["--collect-only", "-q", "--color=no"]).await?,
+            root,
+        )),
+        _ => {
+            let (app, name, root) = (app.clone(), framework.name, root.to_path_buf());
+            tokio::task::spawn_blocking(move || js_tests(&app, name, &root))
+                .await
+                .map_err(|e| format!("Test discovery failed: {}", e))?
+        }
+    }
+}
+
+// ============================================================================
+// OUTPUT PARSING
+// ============================================================================
+
+fn result(framework: &str, name: &str, status: &str, duration_ms: Option<f64>) -> TestResult {
+    TestResult {
+        id: test_id(framework, name),
+        framework: framework.to_string(),
+        name: name.to_string(),
+        status: status.to_string(),
+        duration_ms,
+        message: None,
+    }
+}
+
+/// Line-by-line parsers for runners without a stable JSON reporter (cargo,
+/// pytest) or with a streaming one (go); jest and vitest report once at
+/// the end.
+enum OutputParser {
+    Cargo {
+        line: Regex,
+        /// The test whose captured output is being read.
+        current: Option<String>,
+        failures: HashMap<String, String>,
+    },
+    Pytest {
+        line: Regex,
+        duration: Regex,
+        failure: Regex,
+        durations: HashMap<String, f64>,
+        failures: HashMap<String, String>,
+    },
+    Go {
+        output: HashMap<String, String>,
+    },
+    Report {
+        framework: &'static str,
+        root: PathBuf,
+    },
+}
+
+impl OutputParser {
+    fn new(framework: &'static str, root: &Path) -> Self {
+        match framework {
+            "cargo" => OutputParser::Cargo {
+                line: Regex::new(r"^test (.+) \.\.\. (ok|FAILED|ignored)").unwrap(),
+                current: None,
+                failures: HashMap::new(),
+            },
+            "pytest" => OutputParser::Pytest {
+                line: Regex::new(r"^(\S.*::\S.*?) (PASSED|FAILED|SKIPPED|ERROR|XFAIL|XPASS)\b").unwrap(),
+                duration: Regex::new(r"^(\d+(?:\.\d+)?)s (?:setup|call|teardown)\s+(\S.*)$").unwrap(),
+                failure: Regex::new(r"^(?:FAILED|ERROR) (\S+)(?: - (.*))?$").unwrap(),
+                durations: HashMap::new(),
+                failures: HashMap::new(),
+            },
+            "go" => OutputParser::Go { output: HashMap::new() },
+            _ => OutputParser::Report {
+                framework,
+                root: root.to_path_buf(),
+            },
+        }
+    }
+
+    /// A result as soon as a line completes one.
+    fn line(&mut self, line: &str) -> Option<TestResult> {
+        match self {
+            OutputParser::Cargo { line: pattern, current, failures } => {
+                if let Some(c) = pattern.captures(line) {
+                    let status = match &c[2] {
+                        "ok" => "passed",
+                        "FAILED" => "failed",
+                        _ => "skipped",
+                    };
+                    return Some(result("cargo", &c[1], status, None));
+                }
+                if let Some(name) = line.strip_prefix("---- ").and_then(|l| l.strip_suffix(" stdout ----")) {
+                    *current = Some(name.to_string());
+                } else if line == "failures:" {
+                    *current = None;
+                } else if let Some(name) = current {
+                    let output = failures.entry(name.clone()).or_default();
+                    output.push_str(line);
+                    output.push('\n');
+                }
+                None
+            }
+            OutputParser::Pytest { line: pattern, duration, failure, durations, failures } => {
+                if let Some(c) = pattern.captures(line) {
+                    let status = match &c[2] {
+                        "PASSED" | "XPASS" => "passed",
+                        "SKIPPED" | "XFAIL" => "skipped",
+                        _ => "failed",
+                    };
+                    return Some(result("pytest", &c[1], status, None));
+                }
+                if let Some(c) = duration.captures(line) {
+                    if let Ok(seconds) = c[1].parse::<f64>() {
+                        *durations.entry(c[2].to_string()).or_default() += seconds * 1000.0;
+                    }
+                } else if let Some(c) = failure.captures(line) {
+                    let message = c.get(2).map(|m| m.as_str()).unwrap_or_default();
+                    failures.insert(c[1].to_string(), message.to_string());
+                }
+                None
+            }
+            OutputParser::Go { output } => {
+                let event: Value = serde_json::from_str(line).ok()?;
+                let field = |key: &str| event.get(key).and_then(|v| v.as_str());
+                let name = format!("{}::{}", field("Package")?, field("Test")?);
+                let status = match field("Action")? {
+                    "output" => {
+                        output.entry(name).or_default().push_str(field("Output").unwrap_or_default());
+                        return None;
+                    }
+                    "pass" => "passed",
+                    "fail" => "failed",
+                    "skip" => "skipped",
+                    _ => return None,
+                };
+                let elapsed = event.get("Elapsed").and_then(|e| e.as_f64()).map(|s| s * 1000.0);
+                let mut test = result("go", &name, status, elapsed);
+                if status == "failed" {
+                    test.message = output.remove(&name);
+                }
+                Some(test)
+            }
+            OutputParser::Report { .. } => None,
+        }
+    }
+
+    /// Completes `results` once the runner exits: failure messages and
+    /// durations printed after the results, or the whole JSON report.
+    fn finish(self, stdout: &str, results: &mut Vec<TestResult>) {
+        match self {
+            OutputParser::Cargo { mut failures, .. } => {
+                for test in results.iter_mut().filter(|t| t.status == "failed") {
+                    test.message = failures.remove(&test.name).map(|m| m.trim_end().to_string());
+                }
+            }
+            OutputParser::Pytest { mut durations, mut failures, .. } => {
+                for test in results.iter_mut() {
+                    test.duration_ms = durations.remove(&test.name);
+                    if test.status == "failed" {
+                        test.message = failures.remove(&test.name).filter(|m| !m.is_empty());
+                    }
+                }
+            }
+            OutputParser::Go { .. } => {}
+            OutputParser::Report { framework, root } => results.extend(parse_js_report(framework, &root, stdout)),
+        }
+    }
+}
+
+/// The jest-style JSON report printed by `jest --json` and vitest's json
+/// reporter, possibly after other output.
+fn parse_js_report(framework: &str, root: &Path, stdout: &str) -> Vec<TestResult> {
+    let Some(start) = stdout.find('{') else {
+        return Vec::new();
+    };
+    let Some(Ok(report)) = serde_json::Deserializer::from_str(&stdout[start..]).into_iter::<Value>().next() else {
+        return Vec::new();
+    };
+
+    let mut results = Vec::new();
+    for file in report.get("testResults").and_then(|r| r.as_array()).into_iter().flatten() {
+        let path = file.get("name").and_then(|n| n.as_str()).unwrap_or_default();
+        let relative = relative_path(root, Path::new(path)).unwrap_or_else(|| path.to_string());
+        for assertion in file.get("assertionResults").and_then(|a| a.as_array()).into_iter().flatten() {
+            let Some(full_name) = assertion.get("fullName").and_then(|n| n.as_str()) else {
+                continue;
+            };
+            let status = match assertion.get("status").and_then(|s| s.as_str()) {
+                Some("passed") => "passed",
+                Some("failed") => "failed",
+                _ => "skipped",
+            };
+            let mut test = result(
+                framework,
+                &format!("{}::{}", relative, full_name),
+                status,
+                assertion.get("duration").and_then(|d| d.as_f64()),
+            );
+            let messages: Vec<&str> = assertion
+                .get("failureMessages")
+                .and_then(|m| m.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|m| m.as_str())
+                .collect();
+            test.message = (!messages.is_empty()).then(|| messages.join("\n"));
+            results.push(test);
+        }
+    }
+    results
+}
+
+// ============================================================================
+// TEST EXECUTION
+// ============================================================================
+
+/// Command line running `names` (all tests when empty).
+fn run_args(framework: &str, root: &Path, names: &[String]) -> Vec<String> {
+    let strings = |args: &[&str]| args.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    // jest and vitest select by file and full-name pattern
+    let js_selection = |mut args: Vec<String>| {
+        let mut files: Vec<String> = Vec::new();
+        let mut patterns: Vec<String> = Vec::new();
+        for name in names {
+            if let Some((file, full_name)) = name.split_once("::") {
+                let file = root.join(file).to_string_lossy().to_string();
+                if !files.contains(&file) {
+                    files.push(file);
+                }
+                patterns.push(regex::escape(full_name));
+            }
+        }
+        args.extend(files);
+        if !patterns.is_empty() {
+            args.push("-t".to_string());
+            args.push(format!("^({})$", patterns.join("|")));
+        }
+        args
+    };
+
+    match framework {
+        "cargo" => {
+            let mut args = strings(&["test", "--no-fail-fast"]);
+            if !names.is_empty() {
+                args.extend(strings(&["--", "--exact"]));
+                args.extend(names.iter().cloned());
+            }
+            args
+        }
+        "pytest" => {
+            let args = strings(&["-v", "-rfE", "--color=no", "--durations=0", "--durations-min=0"]);
+            [args, names.to_vec()].concat()
+        }
+        "go" => {
+            let mut args = strings(&["test", "-json"]);
+            if names.is_empty() {
+                args.push("./...".to_string());
+                return args;
+            }
+            let mut packages: Vec<&str> = Vec::new();
+            let mut tests: Vec<String> = Vec::new();
+            for (package, test) in names.iter().filter_map(|n| n.split_once("::")) {
+                if !packages.contains(&package) {
+                    packages.push(package);
+                }
+                tests.push(regex::escape(test));
+            }
+            args.push("-run".to_string());
+            args.push(format!("^({})$", tests.join("|")));
+            args.extend(packages.into_iter().map(|p| p.to_string()));
+            args
+        }
+        "vitest" => js_selection(strings(&["run", "--reporter=json"])),
+        _ => js_selection(strings(&["--json"])),
+    }
+}
+
+/// Runs one framework, emitting a `test` event per finished test.
+async fn run_framework(
+    window: &Window,
+    run_id: &str,
+    framework: &Framework,
+    root: &Path,
+    names: &[String],
+) -> (FrameworkRun, Vec<TestResult>) {
+    let started = Instant::now();
+    let emit = |kind: &'static str, result: Option<TestResult>, run: Option<FrameworkRun>| {
+        let _ = window.emit(
+            "test-run-event",
+            TestRunEvent {
+                run_id: run_id.to_string(),
+                kind,
+                framework: framework.name.to_string(),
+                result,
+                run,
+            },
+        );
+    };
+    emit("framework_started", None, None);
+
+    let mut results = Vec::new();
+    let mut parser = OutputParser::new(framework.name, root);
+    let outcome = match program_of(framework) {
+        Ok(program) => {
+            let args = run_args(framework.name, root, names);
+            stream_process(program, &args, root, |line| {
+                if let Some(test) = parser.line(line) {
+                    emit("test", Some(test.clone()), None);
+                    results.push(test);
+                }
+            })
+            .await
+        }
+        Err(e) => Err(e),
+    };
+
+    let mut run = FrameworkRun {
+        framework: framework.name.to_string(),
+        exit_code: None,
+        duration_ms: 0,
+        error: None,
+    };
+    match outcome {
+        Ok((exit_code, stdout, stderr)) => {
+            let streamed = results.len();
+            parser.finish(&stdout, &mut results);
+            for test in &results[streamed..] {
+                emit("test", Some(test.clone()), None);
+            }
+            run.exit_code = exit_code;
+            if results.is_empty() && exit_code != Some(0) {
+                let output = if stderr.trim().is_empty() { stdout } else { stderr };
+                let lines: Vec<&str> = output.trim_end().lines().collect();
+                run.error = Some(lines[lines.len().saturating_sub(20)..].join("\n"));
+            }
+        }
+        Err(e) => run.error = Some(e),
+    }
+    run.duration_ms = started.elapsed().as_millis() as u64;
+    emit("framework_finished", None, Some(run.clone()));
+    (run, results)
+}
+
+/// Spawns the runner and hands each stdout line to `on_line` as it
+/// arrives. Returns the exit code and the complete stdout and stderr.
+async fn stream_process(
+    program: &Path,
+    args: &[String],
+    cwd: &Path,
+    mut on_line: impl FnMut(&str),
+) -> Result<(Option<i32>, String, String), String> {
+    let mut child = Command::new(program)
+        .args(args)
+        .current_dir(cwd)
+        .env("CI", "1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to spawn {}: {}", program.display(), e))?;
+
+    let stdout = child.stdout.take().ok_or("Failed to capture stdout")?;
+    let mut stderr = child.stderr.take().ok_or("Failed to capture stderr")?;
+    let stderr_task = tokio::spawn(async move {
+        let mut collected = Vec::new();
+        let _ = stderr.read_to_end(&mut collected).await;
+        String::from_utf8_lossy(&collected).to_string()
+    });
+
+    let mut collected = String::new();
+    let mut lines = BufReader::new(stdout).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        on_line(&line);
+        collected.push_str(&line);
+        collected.push('\n');
+    }
+    let status = child
+        .wait()
+        .await
+        .map_err(|e| format!("Failed to wait for {}: {}", program.display(), e))?;
+    Ok((status.code(), collected, stderr_task.await.unwrap_or_default()))
+}
+
+async fn execute_run(
+    window: Window,
+    run_id: String,
+    root: PathBuf,
+    selection: TestSelection,
+) -> Result<TestRunSummary, String> {
+    let started = Instant::now();
+    let frameworks = detect_frameworks(&root);
+
+    let mut planned: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for id in &selection.tests {
+        let (framework, name) = id.split_once(':').ok_or_else(|| format!("Invalid test id: {}", id))?;
+        planned.entry(framework.to_string()).or_default().push(name.to_string());
+    }
+    for framework in &selection.frameworks {
+        planned.insert(framework.clone(), Vec::new());
+    }
+    if selection.tests.is_empty() && selection.frameworks.is_empty() {
+        planned.extend(frameworks.iter().map(|f| (f.name.to_string(), Vec::new())));
+    }
+    if planned.is_empty() {
+        return Err(format!("No test framework detected in {}", root.display()));
+    }
+
+    let mut summary = TestRunSummary {
+        run_id: run_id.clone(),
+        results: Vec::new(),
+        runs: Vec::new(),
+        passed: 0,
+        failed: 0,
+        skipped: 0,
+        duration_ms: 0,
+    };
+    // One after another: cargo and go builds would contend for the CPU
+    for (name, names) in &planned {
+        let framework = frameworks
+            .iter()
+            .find(|f| f.name == name.as_str())
+            .ok_or_else(|| format!("{} is not set up in {}", name, root.display()))?;
+        let (run, results) = run_framework(&window, &run_id, framework, &root, names).await;
+        summary.runs.push(run);
+        summary.results.extend(results);
+    }
+
+    for test in &summary.results {
+        match test.status.as_str() {
+            "passed" => summary.passed += 1,
+            "failed" => summary.failed += 1,
+            _ => summary.skipped += 1,
+        }
+    }
+    summary.duration_ms = started.elapsed().as_millis() as u64;
+    Ok(summary)
+}
+
+// ============================================================================
+// TEST TAURI COMMANDS
+// ============================================================================
+
+/// Tests of the project at `root` for every detected framework: listed by
+/// cargo, go and pytest, found in source for jest and vitest.
+#[tauri::command]
+pub async fn discover_tests(
+    app: AppHandle,
+    root: String,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<TestDiscovery, String> {
+    let root = workspace.check(&root)?;
+    let frameworks = detect_frameworks(&root);
+    let listings = futures::future::join_all(frameworks.iter().map(|f| discover(&app, f, &root))).await;
+
+    let mut discovery = TestDiscovery {
+        frameworks: frameworks.iter().map(|f| f.name.to_string()).collect(),
+        tests: Vec::new(),
+        warnings: Vec::new(),
+    };
+    for listing in listings {
+        match listing {
+            Ok(tests) => discovery.tests.extend(tests),
+            Err(e) => discovery.warnings.push(e),
+        }
+    }
+    Ok(discovery)
+}
+
+/// Runs the selected tests, or all tests of every detected framework.
+/// Results stream as `test-run-event` events tagged with `run_id`; the
+/// returned summary has every result with failure messages attached.
+#[tauri::command]
+pub async fn run_tests(
+    window: Window,
+    run_id: String,
+    root: String,
+    selection: Option<TestSelection>,
+    state: State<'_, TestRunState>,
+    workspace: State<'_, WorkspaceState>,
+) -> Result<TestRunSummary, String> {
+    let root = workspace.check(&root)?;
+    let handle = tokio::spawn(execute_run(window, run_id.clone(), root, selection.unwrap_or_default()));
+    state.runs.lock_or_recover().insert(run_id.clone(), handle.abort_handle());
+
+    let outcome = handle.await;
+    state.runs.lock_or_recover().remove(&run_id);
+    match outcome {
+        Ok(summary) => summary,
+        Err(e) if e.is_cancelled() => Err(format!("Test run cancelled: {}", run_id)),
+        Err(e) => Err(format!("Test run failed: {}", e)),
+    }
+}
+
+/// Aborting the run drops its runner process, which `kill_on_drop` kills.
+#[tauri::command]
+pub fn cancel_test_run(run_id: String, state: State<'_, TestRunState>) -> Result<(), String> {
+    let runs = state.runs.lock_or_recover();
+    let run = runs.get(&run_id).ok_or_else(|| format!("Test run not found: {}", run_id))?;
+    run.abort();
+    Ok(())
+}
@@ -23,11 +23,14 @@ export default function GitGraphPanel({ repoPath }: { repoPath: string }) {
   const loadHistory = async () => {
     setLoading(true);
     try {
-      const commits = await invoke<CommitInfo[]>("get_commit_history", {
-        repoPath,
-        limit: 50,
-      });
-      setHistory(commits);
+      const page = await invoke<{ commits: CommitInfo[]; next_cursor: string | null }>(
+        "get_commit_history",
+        {
+          repoPath,
+          limit: 50,
+        }
+      );
+      setHistory(page.commits);
     } catch (err) {
       console.error("Failed to load history:", err);
     } finally {
@@ -1,6 +1,5 @@
 import { Buttons } from "@/helpers/constants/button-constant";
 import { invoke } from "@tauri-apps/api/core";
-import { open } from "@tauri-apps/plugin-dialog";
 import { Dispatch, SetStateAction } from "react";
 import { DirEntryInfo, FileNode } from "@/helpers/interfaces/file-types";
 import { buildFileTree } from "./rootfiles/buildTree";
@@ -14,11 +13,7 @@ export default function FolderSelectorPage({
   setTree: Dispatch<SetStateAction<FileNode>>;
 }) {
   const handleSelectFolder = async () => {
-    const selectedPath = await open({
-      fileAccessMode: "copy",
-      directory: true,
-      multiple: false,
-    });
+    const selectedPath = await invoke<string | null>("pick_workspace_folder");
 
     if (!selectedPath) return;
 
@@ -9,6 +9,8 @@ export interface DirEntryInfo {
   name: string;
   path: string;
   is_dir: boolean;
+  is_symlink?: boolean;
+  ignored?: boolean;
   children?: DirEntryInfo[];
 }
 
@@ -54,9 +56,22 @@ export interface ChatMessage {
   content: string;
 }
 
+export interface ChatStats {
+  prompt_tokens?: number;
+  eval_count?: number;
+  tokens_per_second?: number;
+  total_duration_ms?: number;
+  load_duration_ms?: number;
+  prompt_eval_duration_ms?: number;
+  eval_duration_ms?: number;
+}
+
 export interface ChatStreamEvent {
+  type: "token" | "done" | "error";
   content: string;
   done: boolean;
+  stats?: ChatStats;
+  error?: string;
 }
 
 export interface FileReadProgress {